@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use log::error;
+use rocket::{get, http::Status, serde::json::Json, State};
+use serde::Serialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::audit::{AuditLog, AuditRecord, ChainVerification};
+
+/// A call's audit records with the state of the chain they belong to
+#[derive(Debug, Serialize)]
+pub struct AuditExport {
+    pub call_sid: String,
+    pub records: Vec<AuditRecord>,
+    pub chain: ChainVerification,
+}
+
+/// Export a call's audit records, checking the chain they link into
+#[get("/admin/audit/calls/<call_sid>")]
+pub async fn export_call_audit(
+    _admin: AdminAuth,
+    call_sid: &str,
+    audit: &State<Arc<AuditLog>>,
+) -> ApiResult<AuditExport> {
+    if !audit.is_enabled() {
+        return Err(api_error(Status::BadRequest, "Audit log is not enabled, set AUDIT_LOG_ENABLED"));
+    }
+
+    let (records, chain) = match (audit.call_records(call_sid).await, audit.verify().await) {
+        (Ok(records), Ok(chain)) => (records, chain),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Failed to read audit log: {}", e);
+            return Err(api_error(Status::InternalServerError, "Failed to read audit log"));
+        }
+    };
+    if records.is_empty() {
+        return Err(api_error(Status::NotFound, &format!("No audit records for call {}", call_sid)));
+    }
+
+    Ok(Json(AuditExport {
+        call_sid: call_sid.to_string(),
+        records,
+        chain,
+    }))
+}
+
+/// Check every retained audit record's hash and link
+#[get("/admin/audit/verify")]
+pub async fn verify_audit(
+    _admin: AdminAuth,
+    audit: &State<Arc<AuditLog>>,
+) -> ApiResult<ChainVerification> {
+    match audit.verify().await {
+        Ok(verification) => Ok(Json(verification)),
+        Err(e) => {
+            error!("Failed to read audit log: {}", e);
+            Err(api_error(Status::InternalServerError, "Failed to read audit log"))
+        }
+    }
+}
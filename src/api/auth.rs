@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+
+use crate::api::{api_error, ErrorResponse};
+use crate::config::SharedConfig;
+use crate::tenant::{Tenant, TenantStore};
+
+/// Request guard for administrative endpoints
+///
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`; admin endpoints are
+/// disabled entirely when no admin token is configured.
+pub struct AdminAuth;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminAuth {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected = match request.rocket().state::<SharedConfig>().and_then(|c| c.current().admin.api_token.clone()) {
+            Some(token) => token,
+            None => return Outcome::Error((Status::Forbidden, ())),
+        };
+
+        let provided = request.headers()
+            .get_one("Authorization")
+            .and_then(|h| h.strip_prefix("Bearer "));
+
+        match provided {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Outcome::Success(AdminAuth),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// Request guard for endpoints placing calls, authenticating tenant API keys with the `calls:write` scope
+///
+/// A request with `Authorization: Bearer <key>` acts for the key's tenant; an
+/// unknown key is rejected with 401 and a key without the scope with 403.
+/// Requests without the header act for the service account.
+pub struct CallsWriteAuth(pub Option<Tenant>);
+
+impl CallsWriteAuth {
+    /// Bind a request's `tenant_id` to the authenticated tenant
+    ///
+    /// Requests without a key cannot name a tenant, and a key only acts for its own tenant.
+    pub fn bind_tenant(&self, tenant_id: &mut Option<String>) -> Result<(), Custom<Json<ErrorResponse>>> {
+        match (&self.0, tenant_id.as_deref()) {
+            (Some(tenant), Some(id)) if id != tenant.id => {
+                Err(api_error(Status::Forbidden, "The API key does not belong to the tenant"))
+            },
+            (Some(tenant), _) => {
+                *tenant_id = Some(tenant.id.clone());
+                Ok(())
+            },
+            (None, Some(_)) => Err(api_error(Status::Unauthorized, "Calls for a tenant need the tenant's API key")),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CallsWriteAuth {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        tenant_key(request, "calls:write").map(CallsWriteAuth)
+    }
+}
+
+/// Resolve a request's tenant API key, requiring `scope`
+fn tenant_key(request: &Request<'_>, scope: &str) -> Outcome<Option<Tenant>, ()> {
+    let Some(header) = request.headers().get_one("Authorization") else {
+        return Outcome::Success(None);
+    };
+    let Some(tenants) = request.rocket().state::<Arc<TenantStore>>() else {
+        return Outcome::Error((Status::InternalServerError, ()));
+    };
+
+    match header.strip_prefix("Bearer ").and_then(|secret| tenants.authenticate(secret)) {
+        Some((tenant, key)) if key.scopes.iter().any(|s| s == scope) => Outcome::Success(Some(tenant)),
+        Some(_) => Outcome::Error((Status::Forbidden, ())),
+        None => Outcome::Error((Status::Unauthorized, ())),
+    }
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
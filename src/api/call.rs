@@ -1,64 +1,143 @@
 use std::sync::Arc;
-use log::{debug, error};
-use rocket::{post, serde::json::Json, State, http::Status};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use log::{debug, error, warn};
+use rocket::{post, serde::json::Json, State, http::Status, response::status::{Accepted, Custom}};
 
-use crate::config::Config;
-use crate::twilio::client::TwilioClient;
-use crate::twilio::twiml::create_voice_response;
-use crate::twilio::handlers::MakeCallRequest;
+use crate::api::{ErrorResponse, api_error};
+use crate::api::auth::CallsWriteAuth;
 
-/// Response for the make call API endpoint
-#[derive(Debug, Serialize)]
-pub struct MakeCallResponse {
-    pub message: String,
-    pub call_id: String,
-}
+use crate::bot::cdr::CdrStore;
+use crate::bot::recording::RecordingDecision;
+use crate::config::{Config, CurrentConfig};
+use crate::drain::Drain;
+use crate::tenant::{Tenant, TenantStore};
+use crate::twilio::call_jobs::{CallJob, CallJobStore};
+use crate::twilio::caller_id::CallerIds;
+use crate::twilio::cps::CallRateLimiter;
+use crate::twilio::client::{CallOptions, TwilioClient};
+use crate::twilio::twiml::create_call_start_response;
+use crate::twilio::handlers::{MakeCallRequest, PlaceCallError};
 
 /// Forward API endpoint for making outbound calls
+///
+/// Dialing can be slow, so the call is placed in the background and its
+/// progress reported by `GET /twilio/call/jobs/<id>`. Calls placed with a
+/// tenant API key use that tenant's account.
 #[post("/call", format = "json", data = "<request>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn make_call(
+    auth: CallsWriteAuth,
     request: Json<MakeCallRequest>,
-    config: &State<Config>,
-) -> Result<Json<MakeCallResponse>, Status> {
+    jobs: &State<Arc<CallJobStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+    tenants: &State<Arc<TenantStore>>,
+    caller_ids: &State<Arc<CallerIds>>,
+    rate_limiter: &State<Arc<CallRateLimiter>>,
+    drain: &State<Arc<Drain>>,
+    config: CurrentConfig,
+) -> Result<Accepted<Json<CallJob>>, Custom<Json<ErrorResponse>>> {
+    let mut request = request.into_inner();
     debug!("API call request for {}", request.to_number);
+    auth.bind_tenant(&mut request.tenant_id)?;
     
-    // Create Twilio client
-    let twilio_client = match TwilioClient::new(
-        config.inner().twilio.account_sid.clone(),
-        config.inner().twilio.auth_token.clone(),
-        config.inner().twilio.region.clone(),
-        config.inner().twilio.edge.clone()
-    ) {
-        Ok(client) => client,
+    if drain.is_draining() {
+        warn!("Rejecting call request to {}: this instance is draining", request.to_number);
+        return Err(api_error(Status::ServiceUnavailable, "Instance is draining"));
+    }
+    if let Err(e) = request.validate(&config) {
+        error!("Rejecting call request: {}", e);
+        return Err(api_error(Status::BadRequest, &e));
+    }
+    if request.retry_policy.is_some() {
+        error!("Rejecting call request: retry policies need a session, use /twilio/call");
+        return Err(api_error(Status::BadRequest, "Retry policies need a session, use /twilio/call"));
+    }
+    if request.voicemail_message.is_some() {
+        error!("Rejecting call request: voicemail messages need a session, use /twilio/call");
+        return Err(api_error(Status::BadRequest, "Voicemail messages need a session, use /twilio/call"));
+    }
+    let tenant = request.tenant_id.as_deref().and_then(|id| tenants.get(id));
+    if cdrs.budget_exceeded(tenant.as_ref(), &config.costs) {
+        warn!("Rejecting call request to {}: daily call budget exceeded", request.to_number);
+        return Err(api_error(Status::PaymentRequired, "Daily call budget exceeded"));
+    }
+    let caller_id = match caller_ids.resolve(&request, tenants, &config).await {
+        Ok(caller_id) => caller_id,
+        Err(e) => {
+            error!("Rejecting call request to {}: {}", request.to_number, e);
+            return Err(api_error(e.status(), &e.to_string()));
+        }
+    };
+    
+    let job = jobs.create(&request.to_number);
+    debug!("Queued call job {} to {}", job.job_id, request.to_number);
+    
+    let (jobs, cdrs, rate_limiter) = (jobs.inner().clone(), cdrs.inner().clone(), rate_limiter.inner().clone());
+    let config = Config::clone(&config);
+    let job_id = job.job_id.clone();
+    tokio::spawn(async move {
+        match dial(&request, &caller_id, tenant.as_ref(), &rate_limiter, &config).await {
+            Ok(call_sid) => {
+                if let Some(url) = &request.callback_url {
+                    cdrs.add_result_callback(&call_sid, url, request.tenant_id.as_deref());
+                }
+                cdrs.tag_call(&call_sid, &request.tags);
+                jobs.complete(&job_id, &call_sid);
+            },
+            Err(e) => jobs.fail(&job_id, e.status.code, &e.message),
+        }
+    });
+    
+    Ok(Accepted(Json(job)))
+}
+
+/// Dial a call that starts listening right away, returning its SID
+async fn dial(
+    request: &MakeCallRequest,
+    caller_id: &str,
+    tenant: Option<&Tenant>,
+    rate_limiter: &Arc<CallRateLimiter>,
+    config: &Config,
+) -> Result<String, PlaceCallError> {
+    let speech = request.speech_settings();
+    
+    // Create Twilio client for the tenant's account
+    let twilio_client = match TwilioClient::for_tenant(tenant, &config.twilio) {
+        Ok(client) => client.with_rate_limiter(rate_limiter.clone()),
         Err(e) => {
             error!("Failed to create Twilio client: {}", e);
-            return Err(Status::InternalServerError);
+            return Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to create Twilio client: {}", e)));
         }
     };
     
     // Create empty TwiML response
-    let twiml = create_voice_response("", &config.inner().twilio, config.inner().twilio.default_timeout, "auto");
+    let mut call_config = config.clone();
+    call_config.twilio = speech.apply(&config.twilio);
+    let recording = RecordingDecision::for_number(&request.to_number, &config.recording);
+    let consent = recording.and_then(|d| d.consent_message(&config.recording));
+    let twiml = create_call_start_response("", consent, &call_config, config.twilio.default_timeout, "auto");
+    let call_options = CallOptions {
+        record: recording.is_some_and(|d| d.records_from_start(&config.recording)),
+        ..request.call_options(config)
+    };
     
     // Make the call with retry
-    let call = match twilio_client.create_call_with_retry(
+    match twilio_client.create_call_with_retry(
         &request.to_number,
-        &config.inner().twilio.from_number,
-        &twiml,
-        &format!("{}{}", config.inner().twilio.webhook_url, "/status_callback"),
-        config.inner().backend.retry_attempts,
-        config.inner().backend.retry_base_delay_ms
+        caller_id,
+        &twiml.build(),
+        &config.twilio.callback_url("/status_callback"),
+        &call_options,
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
     ).await {
-        Ok(call) => call,
+        Ok(call) => Ok(call.sid),
+        Err(e) if e.is_invalid_number() => {
+            warn!("Rejecting call to invalid number {}: {}", request.to_number, e);
+            Err(PlaceCallError::new(Status::BadRequest, format!("Twilio rejected the number {}: {}", request.to_number, e)))
+        },
         Err(e) => {
             error!("Failed to create call: {}", e);
-            return Err(Status::InternalServerError);
+            Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to create call: {}", e)))
         }
-    };
-    
-    Ok(Json(MakeCallResponse {
-        message: "Call initiated successfully".to_string(),
-        call_id: call.sid,
-    }))
-}
\ No newline at end of file
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use rocket::{get, http::Status, patch, serde::json::Json, State};
+use serde::Deserialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::bot::cdr::{normalize_tag, CallPage, CallQuery, CallRecord, CdrStore, DailyCost, HangupSummary};
+use crate::bot::experiments::{experiment_metrics, VariantSummary};
+use crate::bot::metrics::{speech_metrics, SpeechSummary};
+use crate::bot::session::SessionStore;
+use crate::config::CurrentConfig;
+use crate::twilio::agent_pool::{agent_pool, AgentPoolSummary};
+use crate::utils::phone;
+
+/// Calls returned by a search page unless a limit is given
+const DEFAULT_CALL_PAGE_SIZE: usize = 50;
+
+/// Most calls a search page returns
+const MAX_CALL_PAGE_SIZE: usize = 500;
+
+/// Tags to add to and remove from a call
+#[derive(Debug, Deserialize)]
+pub struct TagChanges {
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Get the call detail record for a call, or the live state of an active call
+#[get("/calls/<call_sid>")]
+pub async fn get_call(
+    _admin: AdminAuth,
+    call_sid: &str,
+    sessions: &State<Arc<SessionStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+) -> ApiResult<CallRecord> {
+    if let Some(record) = cdrs.get(call_sid) {
+        return Ok(Json(record));
+    }
+
+    match sessions.lock_session_by_conversation(call_sid).await {
+        Some(session) => Ok(Json(CallRecord::from_session(call_sid, &session, "in-progress"))),
+        None => Err(api_error(Status::NotFound, &format!("Call {} not found", call_sid))),
+    }
+}
+
+/// Search the retained call records by tag, caller number and status, most recent first
+///
+/// `status` matches either the final call status or the disposition. Pages
+/// hold `limit` calls (50 by default, at most 500) starting at `offset`.
+#[get("/calls?<tag>&<from>&<status>&<offset>&<limit>")]
+pub fn search_calls(
+    _admin: AdminAuth,
+    tag: Option<&str>,
+    from: Option<&str>,
+    status: Option<&str>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    cdrs: &State<Arc<CdrStore>>,
+) -> Json<CallPage> {
+    let from = from.map(phone::normalize_caller);
+    let query = CallQuery { tag, from: from.as_deref(), status };
+    let limit = limit.unwrap_or(DEFAULT_CALL_PAGE_SIZE).min(MAX_CALL_PAGE_SIZE);
+    Json(cdrs.search(&query, offset.unwrap_or(0), limit))
+}
+
+/// Add and remove tags on a live or recorded call, returning its tags
+#[patch("/calls/<call_sid>/tags", format = "json", data = "<changes>")]
+pub async fn update_call_tags(
+    _admin: AdminAuth,
+    call_sid: &str,
+    changes: Json<TagChanges>,
+    sessions: &State<Arc<SessionStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+) -> ApiResult<BTreeSet<String>> {
+    let normalize = |tags: &[String]| tags.iter().map(|tag| normalize_tag(tag)).collect::<Result<Vec<_>, _>>();
+    let (add, remove) = match (normalize(&changes.add), normalize(&changes.remove)) {
+        (Ok(add), Ok(remove)) => (add, remove),
+        (Err(e), _) | (_, Err(e)) => return Err(api_error(Status::BadRequest, &e)),
+    };
+
+    if let Some(tags) = cdrs.update_tags(call_sid, &add, &remove) {
+        return Ok(Json(tags));
+    }
+    match sessions.lock_session_by_conversation(call_sid).await {
+        Some(mut session) => {
+            session.tags.extend(add);
+            session.tags.retain(|tag| !remove.contains(tag));
+            Ok(Json(session.tags.clone()))
+        },
+        None => Err(api_error(Status::NotFound, &format!("Call {} not found", call_sid))),
+    }
+}
+
+/// Summarize who hung up recent calls and how they ended
+#[get("/analytics/hangups")]
+pub fn hangup_analytics(
+    _admin: AdminAuth,
+    cdrs: &State<Arc<CdrStore>>,
+) -> Json<HangupSummary> {
+    Json(cdrs.hangup_summary())
+}
+
+/// Speech recognition quality and partial processing value across all calls
+#[get("/analytics/speech")]
+pub fn speech_analytics(_admin: AdminAuth) -> Json<SpeechSummary> {
+    Json(speech_metrics().summary())
+}
+
+/// Outcomes of each experiment variant's calls, by experiment
+#[get("/analytics/experiments")]
+pub fn experiment_analytics(_admin: AdminAuth) -> Json<HashMap<String, HashMap<String, VariantSummary>>> {
+    Json(experiment_metrics().summary())
+}
+
+/// Occupancy of the agents taking agent-paced campaigns' calls
+#[get("/analytics/agents")]
+pub fn agent_analytics(_admin: AdminAuth, config: CurrentConfig) -> Json<AgentPoolSummary> {
+    Json(agent_pool().summary(&config.dialer))
+}
+
+/// Call spend by tenant and day
+#[get("/analytics/costs")]
+pub fn cost_analytics(
+    _admin: AdminAuth,
+    cdrs: &State<Arc<CdrStore>>,
+) -> Json<Vec<DailyCost>> {
+    Json(cdrs.daily_costs())
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use log::{error, info, warn};
+use rocket::data::{Data, Limits, ToByteUnit};
+use rocket::http::Status;
+use rocket::response::status::{Accepted, Custom};
+use rocket::serde::json::Json;
+use rocket::{post, State};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::api::auth::AdminAuth;
+use crate::bot::cdr::CdrStore;
+use crate::config::CurrentConfig;
+use crate::drain::Drain;
+use crate::tenant::TenantStore;
+use crate::twilio::agent_pool::agent_pool;
+use crate::twilio::call_jobs::CallJobStore;
+use crate::twilio::caller_id::CallerIds;
+use crate::twilio::handlers::MakeCallRequest;
+use crate::twilio::scheduler::CallScheduler;
+
+/// Columns holding the number to dial, the first one present is used
+const NUMBER_COLUMNS: [&str; 3] = ["number", "to_number", "phone"];
+
+/// A CSV row that cannot be dialed
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    /// Line of the row in the uploaded file, counting the header as line 1
+    pub row: u64,
+    pub number: Option<String>,
+    pub error: String,
+}
+
+/// Error body of a rejected import, with the rows at fault
+#[derive(Debug, Serialize)]
+pub struct ImportError {
+    pub error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rows: Vec<RowError>,
+}
+
+/// A campaign row queued for dialing
+#[derive(Debug, Serialize)]
+pub struct CampaignEntry {
+    pub row: u64,
+    pub to_number: String,
+    /// Job reporting the call's progress at `GET /twilio/call/jobs/<job_id>`
+    pub job_id: String,
+}
+
+/// A started campaign and its queued calls
+#[derive(Debug, Serialize)]
+pub struct CampaignImport {
+    pub campaign_id: String,
+    pub entries: Vec<CampaignEntry>,
+}
+
+fn import_error(status: Status, message: &str, rows: Vec<RowError>) -> Custom<Json<ImportError>> {
+    Custom(status, Json(ImportError { error: message.to_string(), rows }))
+}
+
+/// Start an outbound campaign from a CSV of numbers to call
+///
+/// The header row names the columns: `number` is required, every other column
+/// (e.g. `name`) is passed to the backend session in `env_info`. All rows are
+/// validated first and the campaign only starts if every row can be dialed;
+/// otherwise the bad rows are returned and nothing is called. Calls are placed
+/// one after another, paced by the outbound call rate limit. Campaigns handing
+/// calls to human agents can set `agent_paced` to also wait for a free agent
+/// before each call.
+#[allow(clippy::too_many_arguments)]
+#[post("/call/batch/import?<tenant_id>&<from_number>&<callback_url>&<bot>&<agent_paced>", data = "<data>")]
+pub async fn import_campaign(
+    _admin: AdminAuth,
+    tenant_id: Option<String>,
+    from_number: Option<String>,
+    callback_url: Option<String>,
+    bot: Option<String>,
+    agent_paced: Option<bool>,
+    data: Data<'_>,
+    limits: &Limits,
+    scheduler: &State<Arc<CallScheduler>>,
+    jobs: &State<Arc<CallJobStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+    tenants: &State<Arc<TenantStore>>,
+    caller_ids: &State<Arc<CallerIds>>,
+    drain: &State<Arc<Drain>>,
+    config: CurrentConfig,
+) -> Result<Accepted<Json<CampaignImport>>, Custom<Json<ImportError>>> {
+    if drain.is_draining() {
+        warn!("Rejecting campaign: this instance is draining");
+        return Err(import_error(Status::ServiceUnavailable, "Instance is draining", Vec::new()));
+    }
+    let agent_paced = agent_paced.unwrap_or(false);
+    if agent_paced && config.dialer.agent_pool_size == 0 {
+        return Err(import_error(Status::BadRequest, "Agent pacing needs DIALER_AGENT_POOL_SIZE", Vec::new()));
+    }
+    let limit = limits.get("csv").unwrap_or(1.mebibytes());
+    let csv = match data.open(limit).into_string().await {
+        Ok(csv) if csv.is_complete() => csv.into_inner(),
+        Ok(_) => return Err(import_error(Status::PayloadTooLarge, &format!("CSV is larger than {}", limit), Vec::new())),
+        Err(e) => return Err(import_error(Status::BadRequest, &format!("Failed to read CSV: {}", e), Vec::new())),
+    };
+
+    let campaign_id = Uuid::new_v4().to_string();
+    let template = MakeCallRequest {
+        to_number: String::new(),
+        from_number,
+        tenant_id,
+        env_info: None,
+        voicemail_message: None,
+        callback_url,
+        speech_model: None,
+        enhanced: None,
+        retry_policy: None,
+        bot,
+        tags: Vec::new(),
+    };
+    let requests = parse_campaign(&csv, &campaign_id, &template, &config)?;
+    if requests.is_empty() {
+        return Err(import_error(Status::BadRequest, "CSV has no rows to call", Vec::new()));
+    }
+
+    let tenant = template.tenant_id.as_deref().and_then(|id| tenants.get(id));
+    if cdrs.budget_exceeded(tenant.as_ref(), &config.costs) {
+        warn!("Rejecting campaign of {} calls: daily call budget exceeded", requests.len());
+        return Err(import_error(Status::PaymentRequired, "Daily call budget exceeded", Vec::new()));
+    }
+    // Every row shares the campaign's caller ID, so it is checked once
+    let caller_id = match caller_ids.resolve(&template, tenants, &config).await {
+        Ok(caller_id) => caller_id,
+        Err(e) => {
+            error!("Rejecting campaign: {}", e);
+            return Err(import_error(e.status(), &e.to_string(), Vec::new()));
+        }
+    };
+
+    let mut entries = Vec::with_capacity(requests.len());
+    let mut calls = Vec::with_capacity(requests.len());
+    for (row, mut request) in requests {
+        request.from_number = Some(caller_id.clone());
+        let job = jobs.create(&request.to_number);
+        entries.push(CampaignEntry {
+            row,
+            to_number: request.to_number.clone(),
+            job_id: job.job_id.clone(),
+        });
+        calls.push((job.job_id, request));
+    }
+    info!(
+        "Starting {}campaign {} of {} calls",
+        if agent_paced { "agent-paced " } else { "" }, campaign_id, calls.len()
+    );
+
+    let (scheduler, jobs) = (scheduler.inner().clone(), jobs.inner().clone());
+    let id = campaign_id.clone();
+    let dialer = config.dialer.clone();
+    tokio::spawn(async move {
+        for (job_id, request) in calls {
+            if agent_paced {
+                agent_pool().acquire(&dialer).await;
+            }
+            let result = scheduler.place(request).await;
+            if agent_paced {
+                agent_pool().dialed(result.as_ref().ok().map(|response| response.session_id.as_str()));
+            }
+            match result {
+                Ok(response) => jobs.complete(&job_id, &response.session_id),
+                Err(e) => jobs.fail(&job_id, e.status.code, &e.message),
+            }
+        }
+        info!("Placed every call of campaign {}", id);
+    });
+
+    Ok(Accepted(Json(CampaignImport { campaign_id, entries })))
+}
+
+/// Parse and validate a campaign CSV into call requests with their row numbers
+///
+/// Fails with every invalid row if any row cannot be dialed.
+fn parse_campaign(
+    csv: &str,
+    campaign_id: &str,
+    template: &MakeCallRequest,
+    config: &crate::config::Config,
+) -> Result<Vec<(u64, MakeCallRequest)>, Custom<Json<ImportError>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+
+    let headers: Vec<String> = match reader.headers() {
+        Ok(headers) => headers.iter().map(|h| h.to_lowercase()).collect(),
+        Err(e) => return Err(import_error(Status::BadRequest, &format!("Invalid CSV header: {}", e), Vec::new())),
+    };
+    let Some(number_column) = NUMBER_COLUMNS.iter().find_map(|name| headers.iter().position(|h| h == name)) else {
+        return Err(import_error(Status::BadRequest, "CSV header has no number column", Vec::new()));
+    };
+
+    let mut requests = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // Row numbers count lines, the header being line 1
+        let row = index as u64 + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(RowError { row, number: None, error: e.to_string() });
+                continue;
+            }
+        };
+
+        let number = record.get(number_column).unwrap_or_default().to_string();
+        let mut env_info: HashMap<String, serde_json::Value> = headers.iter()
+            .zip(record.iter())
+            .enumerate()
+            .filter(|(column, (_, value))| *column != number_column && !value.is_empty())
+            .map(|(_, (header, value))| (header.clone(), serde_json::Value::from(value)))
+            .collect();
+        env_info.insert("campaign_id".to_string(), serde_json::Value::from(campaign_id));
+        env_info.insert("campaign_row".to_string(), serde_json::Value::from(row));
+
+        let mut request = MakeCallRequest {
+            to_number: number.clone(),
+            env_info: Some(serde_json::json!(env_info)),
+            ..template.clone()
+        };
+        match request.validate(config) {
+            Ok(()) => requests.push((row, request)),
+            Err(e) => errors.push(RowError { row, number: Some(number), error: e }),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(import_error(
+            Status::UnprocessableEntity,
+            &format!("{} of the CSV's rows cannot be called", errors.len()),
+            errors
+        ));
+    }
+    Ok(requests)
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use rocket::{delete, get, post, put, http::Status, serde::json::Json, State};
+use serde::Serialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::bot::ws_client::WebSocketManager;
+use crate::chaos::{ChaosControl, ChaosSettings, ChaosStatus};
+
+/// Circuit breaker trip length when none is given
+const DEFAULT_TRIP_SECONDS: u64 = 30;
+
+#[derive(Debug, Serialize)]
+pub struct DisconnectResponse {
+    pub dropped: usize,
+}
+
+/// Get the injected failures and how often they fired
+#[get("/debug/chaos")]
+pub fn get_chaos(_admin: AdminAuth, chaos: &State<Arc<ChaosControl>>) -> Json<ChaosStatus> {
+    Json(chaos.status())
+}
+
+/// Set the latency and error rate injected into backend requests
+#[put("/debug/chaos", data = "<settings>")]
+pub fn update_chaos(
+    _admin: AdminAuth,
+    settings: Json<ChaosSettings>,
+    chaos: &State<Arc<ChaosControl>>,
+) -> ApiResult<ChaosStatus> {
+    chaos.configure(settings.into_inner())
+        .map_err(|e| api_error(Status::BadRequest, &e))?;
+    Ok(Json(chaos.status()))
+}
+
+/// Stop injecting failures
+#[delete("/debug/chaos")]
+pub fn reset_chaos(_admin: AdminAuth, chaos: &State<Arc<ChaosControl>>) -> Json<ChaosStatus> {
+    chaos.reset();
+    Json(chaos.status())
+}
+
+/// Reject backend requests as if the circuit breaker opened
+#[post("/debug/chaos/circuit_breaker?<seconds>")]
+pub fn trip_circuit_breaker(
+    _admin: AdminAuth,
+    seconds: Option<u64>,
+    chaos: &State<Arc<ChaosControl>>,
+) -> Json<ChaosStatus> {
+    chaos.trip_circuit_breaker(seconds.unwrap_or(DEFAULT_TRIP_SECONDS));
+    Json(chaos.status())
+}
+
+/// Drop the backend WebSocket of one session, or of every session
+#[post("/debug/chaos/ws_disconnect?<session_id>")]
+pub async fn disconnect_websockets(
+    _admin: AdminAuth,
+    session_id: Option<&str>,
+    ws_manager: &State<Arc<WebSocketManager>>,
+) -> Json<DisconnectResponse> {
+    Json(DisconnectResponse { dropped: ws_manager.drop_connections(session_id).await })
+}
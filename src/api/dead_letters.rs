@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use log::error;
+use rocket::{get, post, http::Status, serde::json::Json, State};
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::bot::backend::BackendPool;
+use crate::config::CurrentConfig;
+use crate::dead_letter::{DeadLetter, DeadLetterStore};
+
+/// List notifications that could not be delivered, oldest first
+#[get("/admin/dead_letters")]
+pub async fn list_dead_letters(
+    _admin: AdminAuth,
+    dead_letters: &State<Arc<DeadLetterStore>>,
+) -> ApiResult<Vec<DeadLetter>> {
+    match dead_letters.list().await {
+        Ok(letters) => Ok(Json(letters)),
+        Err(e) => {
+            error!("Failed to read dead letters: {}", e);
+            Err(api_error(Status::InternalServerError, "Failed to read dead letters"))
+        }
+    }
+}
+
+/// Deliver a dead-lettered notification again, removing it once delivered
+#[post("/admin/dead_letters/<id>/replay")]
+pub async fn replay_dead_letter(
+    _admin: AdminAuth,
+    id: &str,
+    dead_letters: &State<Arc<DeadLetterStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> ApiResult<DeadLetter> {
+    match dead_letters.replay(id, backends, &config).await {
+        Ok(Some(letter)) => Ok(Json(letter)),
+        Ok(None) => Err(api_error(Status::NotFound, &format!("Dead letter {} not found", id))),
+        Err(e) => {
+            error!("Failed to replay dead letter {}: {}", id, e);
+            Err(api_error(Status::BadGateway, &format!("Replay failed: {}", e)))
+        }
+    }
+}
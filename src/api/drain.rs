@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use std::time::Duration;
+use rocket::{get, post, http::Status, serde::json::Json, Shutdown, State};
+use serde::Serialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::bot::session::SessionStore;
+use crate::config::CurrentConfig;
+use crate::drain::{Drain, DrainMode};
+
+#[derive(Debug, Serialize)]
+pub struct DrainResponse {
+    /// The drain in progress; absent while the instance is serving normally
+    pub drain: Option<DrainMode>,
+    pub active_sessions: usize,
+}
+
+/// Whether this instance is draining and how many sessions it still holds
+#[get("/admin/drain")]
+pub fn get_drain(
+    _admin: AdminAuth,
+    drain: &State<Arc<Drain>>,
+    sessions: &State<Arc<SessionStore>>,
+) -> Json<DrainResponse> {
+    Json(DrainResponse {
+        drain: drain.current(),
+        active_sessions: sessions.len(),
+    })
+}
+
+/// Put the instance in drain mode ahead of a deploy
+///
+/// New inbound calls are redirected to `peer_url` (by default `DRAIN_PEER_URL`)
+/// or answered busy, outbound calls are rejected, and the process exits once
+/// active sessions have ended or `timeout_seconds` elapses. Starting a drain
+/// twice returns the one in progress.
+#[post("/admin/drain?<peer_url>&<timeout_seconds>")]
+pub fn start_drain(
+    _admin: AdminAuth,
+    peer_url: Option<String>,
+    timeout_seconds: Option<u64>,
+    drain: &State<Arc<Drain>>,
+    sessions: &State<Arc<SessionStore>>,
+    shutdown: Shutdown,
+    config: CurrentConfig,
+) -> ApiResult<DrainResponse> {
+    let peer_url = match peer_url.filter(|url| !url.is_empty()) {
+        Some(url) if !url.starts_with("http://") && !url.starts_with("https://") => {
+            return Err(api_error(Status::BadRequest, "peer_url must be an http(s) URL"));
+        },
+        Some(url) => Some(url.trim_end_matches('/').to_string()),
+        None => config.drain.peer_url.clone(),
+    };
+    let timeout = Duration::from_secs(timeout_seconds.unwrap_or(config.drain.timeout_seconds));
+
+    let mode = drain.start(peer_url, timeout, sessions.inner().clone(), shutdown);
+    Ok(Json(DrainResponse {
+        drain: Some(mode),
+        active_sessions: sessions.len(),
+    }))
+}
@@ -1,10 +1,13 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use log::warn;
 use rocket::{get, http::Status, serde::json::Json, State};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
 
-use crate::bot::backend::BackendClient;
+use crate::bot::backend::BackendPool;
 use crate::config::Config;
+use crate::twilio::client::TwilioClient;
 
 /// Health status enum
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -16,10 +19,39 @@ pub enum HealthStatus {
 }
 
 /// Health check for a specific component
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheck {
     pub name: String,
     pub status: HealthStatus,
+    /// When the component was last probed, if it has been
+    pub last_checked: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    fn new(name: &str, result: Result<(), String>) -> Self {
+        let (status, error) = match result {
+            Ok(()) => (HealthStatus::Up, None),
+            Err(e) => (HealthStatus::Down, Some(e)),
+        };
+
+        HealthCheck {
+            name: name.to_string(),
+            status,
+            last_checked: Some(Utc::now()),
+            error,
+        }
+    }
+
+    fn unknown(name: &str) -> Self {
+        HealthCheck {
+            name: name.to_string(),
+            status: HealthStatus::Unknown,
+            last_checked: None,
+            error: None,
+        }
+    }
 }
 
 /// Overall health check response
@@ -29,40 +61,115 @@ pub struct HealthResponse {
     pub checks: Vec<HealthCheck>,
 }
 
-/// Health check endpoint
-#[get("/health")]
-pub async fn health(config: &State<Config>) -> (Status, Json<HealthResponse>) {
-    // Create a backend client
-    let backend_client = match BackendClient::new(
-        &config.inner().backend.url,
-        config.inner().backend.authorization_token.clone(),
-        config.inner().backend.enable_circuit_breaker,
-    ) {
-        Ok(client) => client,
-        Err(_) => {
-            return (
-                Status::ServiceUnavailable,
-                Json(HealthResponse {
-                    status: HealthStatus::Down,
-                    checks: vec![HealthCheck {
-                        name: "BOT_BACK".to_string(),
-                        status: HealthStatus::Down,
-                    }],
-                }),
-            );
+/// Latest results of the background dependency probes
+pub struct HealthMonitor {
+    checks: RwLock<Vec<HealthCheck>>,
+}
+
+const BACKEND_CHECK: &str = "BOT_BACK";
+const BACKEND_WS_CHECK: &str = "BOT_BACK_WS";
+const TWILIO_CHECK: &str = "TWILIO_API";
+
+impl HealthMonitor {
+    /// Create a monitor reporting every dependency as unknown until probed
+    pub fn new() -> Self {
+        HealthMonitor {
+            checks: RwLock::new(vec![
+                HealthCheck::unknown(BACKEND_CHECK),
+                HealthCheck::unknown(BACKEND_WS_CHECK),
+                HealthCheck::unknown(TWILIO_CHECK),
+            ]),
         }
-    };
+    }
 
-    // Check if the backend is healthy
-    let backend_health = get_backend_health(&backend_client).await;
-    let self_health = HealthCheck {
-        name: "TWILIO_BOT".to_string(),
-        status: HealthStatus::Up,
-    };
+    /// Latest dependency checks
+    pub fn checks(&self) -> Vec<HealthCheck> {
+        self.checks.read().unwrap().clone()
+    }
+
+    /// Probe every dependency and store the results
+    pub async fn refresh(&self, backends: &BackendPool, config: &Config) {
+        let timeout = Duration::from_millis(config.health.probe_timeout_ms);
+
+        let (backend, backend_ws, twilio) = tokio::join!(
+            probe_backend(backends, config, timeout),
+            probe_backend_ws(&config.backend.ws_url, timeout),
+            probe_twilio(config, timeout),
+        );
+
+        let checks = vec![
+            HealthCheck::new(BACKEND_CHECK, backend),
+            HealthCheck::new(BACKEND_WS_CHECK, backend_ws),
+            HealthCheck::new(TWILIO_CHECK, twilio),
+        ];
+        for check in checks.iter().filter(|c| c.status == HealthStatus::Down) {
+            warn!("Health check {} failed: {}", check.name, check.error.as_deref().unwrap_or_default());
+        }
+
+        *self.checks.write().unwrap() = checks;
+    }
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Start a task probing dependencies on the configured interval
+pub fn start_health_check_task(monitor: Arc<HealthMonitor>, backends: Arc<BackendPool>, config: Config) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.health.check_interval_seconds));
+
+        loop {
+            interval.tick().await;
+            monitor.refresh(&backends, &config).await;
+        }
+    });
+}
+
+/// Check that the backend answers its health path
+async fn probe_backend(backends: &BackendPool, config: &Config, timeout: Duration) -> Result<(), String> {
+    let client = backends.client(&config.backend, None).map_err(|e| e.to_string())?;
+    client
+        .check_health(&config.health.backend_path, timeout.as_millis() as u64)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Check that the backend WebSocket host accepts connections
+async fn probe_backend_ws(ws_url: &str, timeout: Duration) -> Result<(), String> {
+    let url = reqwest::Url::parse(ws_url).map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+    let host = url.host_str().ok_or("WebSocket URL has no host")?;
+    let port = url.port_or_known_default().ok_or("WebSocket URL has no port")?;
+
+    match tokio::time::timeout(timeout, tokio::net::TcpStream::connect((host, port))).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("Connection timed out".to_string()),
+    }
+}
+
+/// Check that Twilio accepts the account credentials
+async fn probe_twilio(config: &Config, timeout: Duration) -> Result<(), String> {
+    let client = TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ).map_err(|e| e.to_string())?;
+
+    client.fetch_account(timeout).await.map_err(|e| e.to_string())
+}
+
+/// Health of this service and the last background probe of each dependency
+fn health_report(monitor: &HealthMonitor) -> HealthResponse {
+    let self_health = HealthCheck::new("TWILIO_BOT", Ok(()));
 
     // Combine health checks
-    let mut checks = vec![self_health, backend_health];
-    
+    let mut checks = vec![self_health];
+    checks.extend(monitor.checks());
+
     // Determine overall status
     let overall_status = if checks.iter().any(|check| check.status == HealthStatus::Down) {
         HealthStatus::Down
@@ -72,14 +179,31 @@ pub async fn health(config: &State<Config>) -> (Status, Json<HealthResponse>) {
         HealthStatus::Up
     };
 
-    // Create response
-    let response = HealthResponse {
+    HealthResponse {
         status: overall_status,
         checks,
-    };
+    }
+}
 
-    // Determine HTTP status code
-    let status_code = if overall_status == HealthStatus::Up {
+/// Liveness endpoint
+///
+/// Answers 200 while the service runs, reporting the results of the last
+/// background probes without calling dependencies. A dependency being down
+/// must not get the service restarted; see [`ready`].
+#[get("/health")]
+pub async fn health(monitor: &State<Arc<HealthMonitor>>) -> Json<HealthResponse> {
+    Json(health_report(monitor))
+}
+
+/// Readiness endpoint
+///
+/// Answers 503 until every dependency has been probed and is up, so traffic is
+/// only routed to an instance that can serve calls.
+#[get("/ready")]
+pub async fn ready(monitor: &State<Arc<HealthMonitor>>) -> (Status, Json<HealthResponse>) {
+    let response = health_report(monitor);
+
+    let status_code = if response.status == HealthStatus::Up {
         Status::Ok
     } else {
         Status::ServiceUnavailable
@@ -87,11 +211,3 @@ pub async fn health(config: &State<Config>) -> (Status, Json<HealthResponse>) {
 
     (status_code, Json(response))
 }
-
-/// Check the health of the backend API
-async fn get_backend_health(client: &BackendClient) -> HealthCheck {
-    HealthCheck {
-        name: "BOT_BACK".to_string(),
-        status: HealthStatus::Up,
-    }
-}
\ No newline at end of file
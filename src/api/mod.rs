@@ -1,12 +1,85 @@
 pub mod health;
 pub mod call;
+pub mod campaigns;
+pub mod auth;
+pub mod tenants;
+pub mod calls;
+pub mod sessions;
+pub mod provision;
+pub mod dead_letters;
+pub mod audit;
+pub mod recordings;
+pub mod reload;
+pub mod drain;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 
 use rocket::{Route, routes};
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use serde::Serialize;
+
+/// Error body returned by API endpoints
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// Result type for JSON API endpoints with descriptive errors
+pub type ApiResult<T> = Result<Json<T>, Custom<Json<ErrorResponse>>>;
+
+/// Build an API error response
+pub fn api_error(status: Status, message: &str) -> Custom<Json<ErrorResponse>> {
+    Custom(status, Json(ErrorResponse { error: message.to_string() }))
+}
 
 /// Get all routes for the API module
 pub fn routes() -> Vec<Route> {
-    routes![
+    #[allow(unused_mut)]
+    let mut routes = routes![
         health::health,
+        health::ready,
         call::make_call,
-    ]
+        campaigns::import_campaign,
+        tenants::create_tenant,
+        calls::get_call,
+        calls::search_calls,
+        calls::update_call_tags,
+        calls::hangup_analytics,
+        calls::cost_analytics,
+        calls::speech_analytics,
+        calls::experiment_analytics,
+        calls::agent_analytics,
+        sessions::session_events,
+        sessions::get_session_metadata,
+        sessions::update_session_metadata,
+        sessions::keep_session_alive,
+        sessions::hold_session,
+        sessions::resume_session,
+        sessions::get_coach,
+        sessions::start_coach,
+        sessions::stop_coach,
+        sessions::take_over_session,
+        sessions::pending_messages,
+        sessions::session_stats,
+        provision::provision,
+        dead_letters::list_dead_letters,
+        dead_letters::replay_dead_letter,
+        audit::export_call_audit,
+        audit::verify_audit,
+        recordings::list_call_recordings,
+        reload::reload_config,
+        drain::get_drain,
+        drain::start_drain,
+    ];
+    #[cfg(feature = "chaos")]
+    routes.extend(routes![
+        chaos::get_chaos,
+        chaos::update_chaos,
+        chaos::reset_chaos,
+        chaos::trip_circuit_breaker,
+        chaos::disconnect_websockets,
+    ]);
+    routes
 }
@@ -0,0 +1,29 @@
+use log::error;
+use rocket::{post, http::Status, serde::json::Json};
+use serde::Serialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::config::CurrentConfig;
+use crate::twilio::provisioning::{provision_numbers, NumberProvisioning};
+
+/// Response for the number provisioning endpoint
+#[derive(Debug, Serialize)]
+pub struct ProvisionResponse {
+    pub numbers: Vec<NumberProvisioning>,
+}
+
+/// Check the inbound numbers' voice webhooks and repoint any that do not reach us
+#[post("/admin/provision")]
+pub async fn provision(
+    _admin: AdminAuth,
+    config: CurrentConfig,
+) -> ApiResult<ProvisionResponse> {
+    match provision_numbers(&config.twilio).await {
+        Ok(numbers) => Ok(Json(ProvisionResponse { numbers })),
+        Err(e) => {
+            error!("Failed to provision inbound numbers: {}", e);
+            Err(api_error(Status::InternalServerError, "Failed to create Twilio client"))
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::info;
+use rocket::{get, http::{ContentType, Status}, response::status::Custom, serde::json::Json};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::api::auth::{constant_time_eq, AdminAuth};
+use crate::api::{api_error, ApiResult, ErrorResponse};
+use crate::config::{Config, CurrentConfig};
+use crate::twilio::client::{TwilioClient, TwilioError};
+
+/// A transcription of a call recording
+#[derive(Debug, Serialize)]
+pub struct TranscriptionInfo {
+    pub sid: String,
+    pub status: Option<String>,
+    pub text: Option<String>,
+}
+
+/// A call recording with a signed link to its audio
+#[derive(Debug, Serialize)]
+pub struct RecordingInfo {
+    pub sid: String,
+    pub call_sid: Option<String>,
+    pub status: Option<String>,
+    pub duration_seconds: Option<u32>,
+    pub channels: Option<u32>,
+    pub created_at: Option<String>,
+    /// URL that serves the audio without an admin token until `url_expires_at`
+    pub audio_url: String,
+    pub url_expires_at: i64,
+    pub transcriptions: Vec<TranscriptionInfo>,
+}
+
+/// List a call's recordings with their transcriptions
+///
+/// Each recording carries a signed audio URL, so QA can play it without
+/// Twilio console access.
+#[get("/calls/<call_sid>/recordings")]
+pub async fn list_call_recordings(
+    _admin: AdminAuth,
+    call_sid: &str,
+    config: CurrentConfig,
+) -> ApiResult<Vec<RecordingInfo>> {
+    let client = twilio_client(&config)?;
+    let recordings = client.list_recordings(call_sid).await.map_err(twilio_error)?;
+
+    let expires = Utc::now().timestamp() + config.admin.recording_url_ttl_seconds as i64;
+    let mut result = Vec::with_capacity(recordings.len());
+    for recording in recordings {
+        let transcriptions = client.list_transcriptions(&recording.sid).await.map_err(twilio_error)?;
+        let signature = sign_recording(&config, &recording.sid, expires)?;
+
+        result.push(RecordingInfo {
+            audio_url: config.twilio.callback_url(
+                &format!("/recordings/{}/audio?expires={}&signature={}", recording.sid, expires, signature)
+            ),
+            url_expires_at: expires,
+            duration_seconds: recording.duration.as_deref().and_then(|d| d.parse().ok()),
+            sid: recording.sid,
+            call_sid: recording.call_sid,
+            status: recording.status,
+            channels: recording.channels,
+            created_at: recording.date_created,
+            transcriptions: transcriptions.into_iter()
+                .map(|t| TranscriptionInfo {
+                    sid: t.sid,
+                    status: t.status,
+                    text: t.transcription_text,
+                })
+                .collect(),
+        });
+    }
+
+    Ok(Json(result))
+}
+
+/// Proxy a recording's audio from Twilio as `mp3` (default) or `wav`
+///
+/// Authorized by the signature from the recordings listing rather than the admin token.
+/// Served with the Twilio routes, so its URL is built like theirs.
+#[get("/recordings/<recording_sid>/audio?<format>&<expires>&<signature>")]
+pub async fn recording_audio(
+    recording_sid: &str,
+    format: Option<&str>,
+    expires: i64,
+    signature: &str,
+    config: CurrentConfig,
+) -> Result<(ContentType, Vec<u8>), Custom<Json<ErrorResponse>>> {
+    if expires < Utc::now().timestamp() {
+        return Err(api_error(Status::Forbidden, "Recording URL has expired"));
+    }
+    let expected = sign_recording(&config, recording_sid, expires)?;
+    if !constant_time_eq(signature.as_bytes(), expected.as_bytes()) {
+        return Err(api_error(Status::Forbidden, "Invalid recording URL signature"));
+    }
+
+    let (format, content_type) = match format.unwrap_or("mp3") {
+        "mp3" => ("mp3", ContentType::new("audio", "mpeg")),
+        "wav" => ("wav", ContentType::new("audio", "wav")),
+        other => return Err(api_error(Status::BadRequest, &format!("Unsupported audio format '{}'", other))),
+    };
+
+    let audio = twilio_client(&config)?
+        .download_recording(recording_sid, format)
+        .await
+        .map_err(twilio_error)?;
+
+    info!("Served recording {} ({} bytes)", recording_sid, audio.len());
+    Ok((content_type, audio))
+}
+
+/// Sign a recording link as the hex HMAC of `<recording_sid>.<expires>` with the recording signing secret
+fn sign_recording(config: &Config, recording_sid: &str, expires: i64) -> Result<String, Custom<Json<ErrorResponse>>> {
+    let Some(secret) = config.admin.recording_signing_secret.as_deref() else {
+        return Err(api_error(Status::Forbidden, "Recording URLs require RECORDING_URL_SIGNING_SECRET to be set"));
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", recording_sid, expires).as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+fn twilio_client(config: &Config) -> Result<TwilioClient, Custom<Json<ErrorResponse>>> {
+    TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ).map_err(|e| api_error(Status::InternalServerError, &format!("Failed to create Twilio client: {}", e)))
+}
+
+/// Map a Twilio failure to a not found or bad gateway response
+fn twilio_error(error: TwilioError) -> Custom<Json<ErrorResponse>> {
+    match error {
+        TwilioError::StatusError(404, _) => api_error(Status::NotFound, "Recording not found"),
+        e => api_error(Status::BadGateway, &format!("Twilio request failed: {}", e)),
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use log::error;
+use rocket::{post, http::Status, serde::json::Json, State};
+use serde::Serialize;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::reload::ConfigReloader;
+
+#[derive(Debug, Serialize)]
+pub struct ReloadResponse {
+    /// Changed sections that only take effect after a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+/// Reload the configuration without dropping active calls
+#[post("/admin/reload")]
+pub async fn reload_config(
+    _admin: AdminAuth,
+    reloader: &State<Arc<ConfigReloader>>,
+) -> ApiResult<ReloadResponse> {
+    match reloader.reload().await {
+        Ok(restart_required) => Ok(Json(ReloadResponse { restart_required })),
+        Err(e) => {
+            error!("Configuration reload failed, keeping the current configuration: {}", e);
+            Err(api_error(Status::BadRequest, &format!("Configuration reload failed: {}", e)))
+        }
+    }
+}
@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use log::{error, info, warn};
+use rocket::{delete, get, http::Status, patch, post, response::status::Custom, serde::json::Json, Shutdown, State};
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use serde::{Deserialize, Serialize};
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult, ErrorResponse};
+use crate::bot::backend::BackendPool;
+use crate::bot::events::SessionEventKind;
+use crate::bot::message_queue::QueueStats;
+use crate::bot::metrics::SpeechSummary;
+use crate::bot::recording::RECORDING_METADATA_KEY;
+use crate::bot::session::{CallerSignals, CoachState, SessionActivity, SessionStore, TakeoverState};
+use crate::config::{Config, CurrentConfig};
+use crate::i18n::{MessageCatalog, Phrase};
+use crate::replication::{ReplicaState, SessionReplicator};
+use crate::tenant::TenantStore;
+use crate::twilio::client::{CallOptions, TwilioClient};
+use crate::twilio::cps::CallRateLimiter;
+use crate::twilio::handlers::{end_takeover_wait, escalation_conference, takeover_conference};
+use crate::twilio::twiml::{create_hold_response, create_takeover_agent_response, create_takeover_response, create_voice_response, TwiML};
+
+/// Metadata keys the service sets itself, which integrations cannot change
+const RESERVED_METADATA_KEYS: [&str; 2] = [RECORDING_METADATA_KEY, "initialization_response"];
+
+/// Stream a live session's turn events as server-sent events
+///
+/// The stream ends when the session ends.
+#[get("/sessions/<session_id>/events")]
+pub fn session_events(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![Event + 'static], Custom<Json<ErrorResponse>>> {
+    // Subscribe before checking so an ending session cannot slip past us
+    let mut events = sessions.subscribe_events();
+    if sessions.get_session(session_id).is_none() {
+        return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+    }
+
+    let session_id = session_id.to_string();
+    Ok(EventStream! {
+        loop {
+            let event = select! {
+                event = events.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Event stream for session {} skipped {} events", session_id, skipped);
+                        continue;
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut shutdown => break,
+            };
+
+            if event.session_id != session_id {
+                continue;
+            }
+
+            let ended = matches!(event.kind, SessionEventKind::Ended);
+            yield Event::json(&event).event(event.kind.name());
+            if ended {
+                break;
+            }
+        }
+    })
+}
+
+/// Longest a pending-state request may wait for a change
+const MAX_PENDING_WAIT_SECONDS: u64 = 30;
+
+/// How often a waiting pending-state request checks the session
+const PENDING_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a live session is doing and how many messages are waiting to be spoken
+#[derive(Debug, Serialize)]
+pub struct PendingResponse {
+    pub session_id: String,
+    pub activity: SessionActivity,
+    pub queue: QueueStats,
+}
+
+/// Get a live session's activity and message queue depth
+///
+/// With `wait`, the request is held for up to that many seconds (at most 30)
+/// until the activity or queue depth differs from the `activity` and `depth`
+/// the backend last saw, so it can pace streaming without flooding the queue.
+#[get("/sessions/<session_id>/pending?<wait>&<activity>&<depth>")]
+pub async fn pending_messages(
+    _admin: AdminAuth,
+    session_id: &str,
+    wait: Option<u64>,
+    activity: Option<&str>,
+    depth: Option<usize>,
+    sessions: &State<Arc<SessionStore>>,
+) -> ApiResult<PendingResponse> {
+    let last_activity = match activity {
+        Some(name) => Some(serde_json::from_value::<SessionActivity>(serde_json::Value::from(name))
+            .map_err(|_| api_error(Status::BadRequest, &format!("Unknown activity '{}'", name)))?),
+        None => None,
+    };
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(wait.unwrap_or(0).min(MAX_PENDING_WAIT_SECONDS));
+
+    loop {
+        // Read without locking through the store so polling does not extend the session
+        let Some(handle) = sessions.get_session(session_id) else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+        let pending = {
+            let session = handle.lock().await;
+            PendingResponse {
+                session_id: session_id.to_string(),
+                activity: session.activity(),
+                queue: session.message_tx.stats(),
+            }
+        };
+
+        let changed = last_activity.is_none_or(|a| a != pending.activity)
+            || depth.is_none_or(|d| d != pending.queue.depth);
+        if (last_activity.is_none() && depth.is_none()) || changed || tokio::time::Instant::now() >= deadline {
+            return Ok(Json(pending));
+        }
+        tokio::time::sleep(PENDING_POLL_INTERVAL).await;
+    }
+}
+
+/// Per-turn speech recognition stats of a live session
+#[derive(Debug, Serialize)]
+pub struct SessionStatsResponse {
+    pub session_id: String,
+    pub turn_count: u32,
+    pub speech: SpeechSummary,
+    /// Caller sentiment and escalation risk last reported by the backend
+    pub signals: Option<CallerSignals>,
+}
+
+/// Get a live session's speech recognition and turnaround stats
+#[get("/sessions/<session_id>/stats")]
+pub async fn session_stats(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+) -> ApiResult<SessionStatsResponse> {
+    let Some(handle) = sessions.get_session(session_id) else {
+        return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+    };
+    let session = handle.lock().await;
+
+    Ok(Json(SessionStatsResponse {
+        session_id: session_id.to_string(),
+        turn_count: session.turn_count,
+        speech: session.speech_stats.summary(),
+        signals: session.signals.clone(),
+    }))
+}
+
+/// Get a live session's metadata
+#[get("/sessions/<session_id>/metadata")]
+pub async fn get_session_metadata(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+) -> ApiResult<HashMap<String, serde_json::Value>> {
+    match sessions.lock_session(session_id).await {
+        Some(session) => Ok(Json(session.metadata.clone())),
+        None => Err(api_error(Status::NotFound, &format!("Session {} not found", session_id))),
+    }
+}
+
+/// Merge JSON into a live session's metadata and forward the change to the backend
+///
+/// Keys set to `null` are removed. Returns the session's updated metadata.
+#[patch("/sessions/<session_id>/metadata", format = "json", data = "<changes>")]
+pub async fn update_session_metadata(
+    _admin: AdminAuth,
+    session_id: &str,
+    changes: Json<serde_json::Map<String, serde_json::Value>>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> ApiResult<HashMap<String, serde_json::Value>> {
+    let changes = changes.into_inner();
+    if let Some(key) = changes.keys().find(|k| RESERVED_METADATA_KEYS.contains(&k.as_str())) {
+        return Err(api_error(Status::BadRequest, &format!("Metadata key {} is reserved", key)));
+    }
+
+    let (metadata, bot) = {
+        let Some(mut session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+
+        for (key, value) in &changes {
+            if value.is_null() {
+                session.metadata.remove(key);
+            } else {
+                session.metadata.insert(key.clone(), value.clone());
+            }
+        }
+        (session.metadata.clone(), session.bot.clone())
+    };
+
+    // The local copy is authoritative for reads, so a backend failure is only logged
+    match backends.client(&config.backend, bot.as_deref()) {
+        Ok(client) => {
+            if let Err(e) = client.update_session(session_id, None, Some(&changes)).await {
+                error!("Failed to forward metadata for session {} to the backend: {}", session_id, e);
+            }
+        },
+        Err(e) => error!("Failed to create backend client: {}", e),
+    }
+
+    Ok(Json(metadata))
+}
+
+/// Idle expiry of a live session
+#[derive(Debug, Serialize)]
+pub struct KeepaliveResponse {
+    pub session_id: String,
+    pub last_activity_time: chrono::DateTime<chrono::Utc>,
+    /// When the session is next checked for expiry if it stays idle
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Restart a live session's idle time so it is not expired
+///
+/// Lets an integration keep a quiet session alive explicitly, e.g. while the
+/// caller listens to a long message.
+#[post("/sessions/<session_id>/keepalive")]
+pub async fn keep_session_alive(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> ApiResult<KeepaliveResponse> {
+    // Locking the session records activity
+    let Some(session) = sessions.lock_session(session_id).await else {
+        return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+    };
+    if session.session_ends {
+        return Err(api_error(Status::Conflict, &format!("Session {} is ending", session_id)));
+    }
+
+    Ok(Json(KeepaliveResponse {
+        session_id: session_id.to_string(),
+        last_activity_time: session.last_activity_time,
+        expires_at: session.last_activity_time + chrono::Duration::minutes(config.session.max_age_minutes),
+    }))
+}
+
+/// Hold state of a live session
+#[derive(Debug, Serialize)]
+pub struct HoldResponse {
+    pub session_id: String,
+    pub on_hold: bool,
+}
+
+/// Put a live call on hold, looping hold music until it is resumed
+///
+/// The caller's speech is ignored while the call is on hold.
+#[post("/sessions/<session_id>/hold")]
+pub async fn hold_session(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    config: CurrentConfig,
+) -> ApiResult<HoldResponse> {
+    if config.twilio.hold_music_url.is_none() {
+        return Err(api_error(Status::BadRequest, "Hold music is not configured, set HOLD_MUSIC_URL"));
+    }
+    set_hold(session_id, true, sessions, catalog, replicator, &config).await
+}
+
+/// Take a held call off hold and resume the conversation
+#[post("/sessions/<session_id>/resume")]
+pub async fn resume_session(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    config: CurrentConfig,
+) -> ApiResult<HoldResponse> {
+    set_hold(session_id, false, sessions, catalog, replicator, &config).await
+}
+
+/// Mark the session held or resumed and update the live call to match
+async fn set_hold(
+    session_id: &str,
+    on_hold: bool,
+    sessions: &Arc<SessionStore>,
+    catalog: &MessageCatalog,
+    replicator: &Arc<SessionReplicator>,
+    config: &Config,
+) -> ApiResult<HoldResponse> {
+    let (call_sid, twiml) = {
+        let Some(mut session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+        if session.session_ends {
+            return Err(api_error(Status::Conflict, &format!("Session {} is ending", session_id)));
+        }
+        if session.takeover.is_some() {
+            return Err(api_error(Status::Conflict, &format!("Session {} has been taken over by an agent", session_id)));
+        }
+        if session.on_hold == on_hold {
+            let state = if on_hold { "already on hold" } else { "not on hold" };
+            return Err(api_error(Status::Conflict, &format!("Session {} is {}", session_id, state)));
+        }
+        let Some(call_sid) = session.conversation_id.clone() else {
+            return Err(api_error(Status::Conflict, &format!("Session {} has no live call", session_id)));
+        };
+
+        let twiml = match config.twilio.hold_music_url.as_deref().filter(|_| on_hold) {
+            Some(hold_music_url) => create_hold_response(hold_music_url),
+            None => {
+                let twilio = session.twilio_config(config);
+                let text = catalog.tenant_text(Phrase::HoldResumed, twilio.language.as_deref(), session.tenant_id.as_deref());
+                create_voice_response(&text, &twilio, twilio.default_timeout, "auto")
+            }
+        };
+
+        session.on_hold = on_hold;
+        replicator.replicate(&mut session, ReplicaState::Active);
+        (call_sid, twiml)
+    };
+
+    let updated = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client.update_call_with_retry(
+            &call_sid,
+            &twiml.build(),
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    // Undo the state change so the session matches the call
+    if let Err(e) = updated {
+        error!("Failed to update call {} for hold: {}", call_sid, e);
+        if let Some(mut session) = sessions.lock_session(session_id).await {
+            session.on_hold = !on_hold;
+            replicator.replicate(&mut session, ReplicaState::Active);
+        }
+        return Err(api_error(Status::BadGateway, &format!("Failed to update call: {}", e)));
+    }
+
+    info!("Call {} {}", call_sid, if on_hold { "put on hold" } else { "resumed from hold" });
+    Ok(Json(HoldResponse {
+        session_id: session_id.to_string(),
+        on_hold,
+    }))
+}
+
+/// Request to dial a supervisor into an escalated call as a coach
+#[derive(Debug, Deserialize)]
+pub struct CoachRequest {
+    /// Number or SIP address to dial the supervisor at
+    pub supervisor: String,
+}
+
+/// Coaching state of a live session
+#[derive(Debug, Serialize)]
+pub struct CoachResponse {
+    pub session_id: String,
+    pub coached: bool,
+    pub coach: Option<CoachState>,
+}
+
+/// Get whether a supervisor is coaching a live session
+#[get("/sessions/<session_id>/coach")]
+pub async fn get_coach(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+) -> ApiResult<CoachResponse> {
+    let Some(handle) = sessions.get_session(session_id) else {
+        return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+    };
+    let coach = handle.lock().await.coach.clone();
+
+    Ok(Json(CoachResponse {
+        session_id: session_id.to_string(),
+        coached: coach.is_some(),
+        coach,
+    }))
+}
+
+/// Dial a supervisor into an escalated call in coach mode
+///
+/// The supervisor hears the caller and the escalation party, and only the
+/// escalation party hears them. Only calls whose escalation party has joined
+/// the escalation conference can be coached: the bot answers over the call's
+/// own TwiML, which a conference would silence, so calls still talking to the
+/// bot, or still dialing the escalation party, are refused with 409.
+#[post("/sessions/<session_id>/coach", data = "<request>")]
+pub async fn start_coach(
+    _admin: AdminAuth,
+    session_id: &str,
+    request: Json<CoachRequest>,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> ApiResult<CoachResponse> {
+    let supervisor = request.into_inner().supervisor;
+    if supervisor.trim().is_empty() {
+        return Err(api_error(Status::BadRequest, "supervisor is required"));
+    }
+
+    let (call_sid, coached_sid) = {
+        let Some(session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+        if session.coach.is_some() {
+            return Err(api_error(Status::Conflict, &format!("Session {} is already coached", session_id)));
+        }
+        let Some(call_sid) = session.conversation_id.clone() else {
+            return Err(api_error(Status::Conflict, &format!("Session {} has no live call", session_id)));
+        };
+        let Some(coached_sid) = session.escalation_call_sid.clone() else {
+            let reason = match session.escalation_target {
+                Some(_) => "its escalation party has not joined yet",
+                None => "it is talking to the bot, and only escalated calls can be coached",
+            };
+            return Err(api_error(Status::Conflict, &format!("Session {} cannot be coached: {}", session_id, reason)));
+        };
+        (call_sid, coached_sid)
+    };
+
+    let status_callback = config.twilio.callback_url(&format!("/coach_status?call_sid={}", urlencoding::encode(&call_sid)));
+    let added = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client.add_coach(
+            &escalation_conference(&call_sid),
+            &supervisor,
+            &config.twilio.from_number,
+            &coached_sid,
+            &status_callback
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let participant = added.map_err(|e| {
+        error!("Failed to add coach {} to call {}: {}", supervisor, call_sid, e);
+        api_error(Status::BadGateway, &format!("Failed to add coach: {}", e))
+    })?;
+
+    let coach = CoachState {
+        supervisor,
+        call_sid: participant.call_sid,
+        connected: false,
+        started_at: chrono::Utc::now(),
+    };
+    if let Some(mut session) = sessions.lock_session(session_id).await {
+        session.coach = Some(coach.clone());
+    }
+
+    info!("Dialing {} into call {} as coach", coach.supervisor, call_sid);
+    Ok(Json(CoachResponse {
+        session_id: session_id.to_string(),
+        coached: true,
+        coach: Some(coach),
+    }))
+}
+
+/// Hang up the supervisor coaching a live session
+#[delete("/sessions/<session_id>/coach")]
+pub async fn stop_coach(
+    _admin: AdminAuth,
+    session_id: &str,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> ApiResult<CoachResponse> {
+    let coach = {
+        let Some(mut session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+        let Some(coach) = session.coach.take() else {
+            return Err(api_error(Status::Conflict, &format!("Session {} is not coached", session_id)));
+        };
+        coach
+    };
+
+    let ended = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client.update_call(&coach.call_sid, &TwiML::new().hangup().build())
+            .await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    // The coach leg may already be gone, which leaves nothing to undo
+    if let Err(e) = ended {
+        warn!("Failed to hang up coach {} on session {}: {}", coach.call_sid, session_id, e);
+    }
+
+    info!("Stopped coaching on session {}", session_id);
+    Ok(Json(CoachResponse {
+        session_id: session_id.to_string(),
+        coached: false,
+        coach: None,
+    }))
+}
+
+/// Request to hand a call from the bot to a live agent
+#[derive(Debug, Deserialize)]
+pub struct TakeoverRequest {
+    /// Number or SIP address to dial the agent at
+    pub agent: String,
+}
+
+/// Takeover state of a live session
+#[derive(Debug, Serialize)]
+pub struct TakeoverResponse {
+    pub session_id: String,
+    pub takeover: TakeoverState,
+}
+
+/// Hand a live call from the bot to an agent
+///
+/// The caller is moved into a conference, which silences the bot, and hears
+/// hold music while the agent is dialed in. From then on the session shadows
+/// the call: the caller's transcripts from the media stream are still sent to
+/// the backend for note-taking, but the bot no longer answers them. If the
+/// agent does not answer, the caller is handed back to the bot.
+///
+/// Shadowing needs the media stream's transcripts, so calls that are not using
+/// streaming recognition are refused with 409. The agent is dialed through the
+/// call rate limiter from the tenant's account and caller ID.
+#[post("/sessions/<session_id>/takeover", data = "<request>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn take_over_session(
+    _admin: AdminAuth,
+    session_id: &str,
+    request: Json<TakeoverRequest>,
+    sessions: &State<Arc<SessionStore>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    tenants: &State<Arc<TenantStore>>,
+    rate_limiter: &State<Arc<CallRateLimiter>>,
+    config: CurrentConfig,
+) -> ApiResult<TakeoverResponse> {
+    let agent = request.into_inner().agent;
+    if agent.trim().is_empty() {
+        return Err(api_error(Status::BadRequest, "agent is required"));
+    }
+
+    // Silence the bot first, so no reply still in flight is played over the handoff
+    let (call_sid, tenant_id) = {
+        let Some(mut session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} not found", session_id)));
+        };
+        if session.session_ends {
+            return Err(api_error(Status::Conflict, &format!("Session {} is ending", session_id)));
+        }
+        if !session.twilio_config(&config).streaming_stt {
+            return Err(api_error(Status::Conflict, &format!("Session {} is not using streaming recognition, which takeovers need", session_id)));
+        }
+        if session.takeover.is_some() {
+            return Err(api_error(Status::Conflict, &format!("Session {} has already been taken over", session_id)));
+        }
+        if session.queue.is_some() || session.transfer_target.is_some() || session.escalation_target.is_some() {
+            return Err(api_error(Status::Conflict, &format!("Session {} is already being handed off", session_id)));
+        }
+        let Some(call_sid) = session.conversation_id.clone() else {
+            return Err(api_error(Status::Conflict, &format!("Session {} has no live call", session_id)));
+        };
+
+        session.takeover = Some(TakeoverState {
+            agent: agent.clone(),
+            call_sid: String::new(),
+            connected: false,
+            started_at: chrono::Utc::now(),
+        });
+        session.on_hold = false;
+        session.pending_reply = None;
+        session.late_reply = None;
+        session.turn_overdue = false;
+        replicator.replicate(&mut session, ReplicaState::Active);
+        (call_sid, session.tenant_id.clone())
+    };
+
+    let tenant = tenant_id.as_deref().and_then(|id| tenants.get(id));
+    let caller_id = tenant.as_ref()
+        .and_then(|t| t.default_caller_id())
+        .unwrap_or(&config.twilio.from_number)
+        .to_string();
+    let client = TwilioClient::for_tenant(tenant.as_ref(), &config.twilio)
+        .map(|client| client.with_rate_limiter(rate_limiter.inner().clone()));
+    let conference = takeover_conference(&call_sid);
+
+    let moved = match &client {
+        Ok(client) => client.update_call_with_retry(
+            &call_sid,
+            &create_takeover_response(&conference, &config).build(),
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    // The caller never left the bot, so only the session needs undoing
+    if let Err(e) = moved {
+        error!("Failed to move call {} into a takeover conference: {}", call_sid, e);
+        if let Some(mut session) = sessions.lock_session(session_id).await {
+            session.takeover = None;
+            replicator.replicate(&mut session, ReplicaState::Active);
+        }
+        return Err(api_error(Status::BadGateway, &format!("Failed to update call: {}", e)));
+    }
+
+    let status_callback = config.twilio.callback_url(&format!("/takeover_status?call_sid={}", urlencoding::encode(&call_sid)));
+    let dialed = match &client {
+        Ok(client) => client.create_call_with_retry(
+            &agent,
+            &caller_id,
+            &create_takeover_agent_response(&conference, &config.twilio).build(),
+            &status_callback,
+            &CallOptions::default(),
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let agent_call = match dialed {
+        Ok(call) => call,
+        Err(e) => {
+            error!("Failed to dial agent {} into call {}: {}", agent, call_sid, e);
+            end_takeover_wait(&call_sid, "failed", &config).await;
+            return Err(api_error(Status::BadGateway, &format!("Failed to dial agent: {}", e)));
+        }
+    };
+
+    let takeover = {
+        let Some(mut session) = sessions.lock_session(session_id).await else {
+            return Err(api_error(Status::NotFound, &format!("Session {} ended during the takeover", session_id)));
+        };
+        let Some(takeover) = session.takeover.as_mut() else {
+            return Err(api_error(Status::Conflict, &format!("Takeover of session {} was abandoned", session_id)));
+        };
+        takeover.call_sid = agent_call.sid;
+        let takeover = takeover.clone();
+        replicator.replicate(&mut session, ReplicaState::Active);
+        takeover
+    };
+
+    info!("Dialing agent {} to take over call {} as {}", agent, call_sid, takeover.call_sid);
+    Ok(Json(TakeoverResponse {
+        session_id: session_id.to_string(),
+        takeover,
+    }))
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::Utc;
+use log::{error, info};
+use rocket::{post, http::Status, serde::json::Json, State};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::auth::AdminAuth;
+use crate::api::{api_error, ApiResult};
+use crate::config::{Config, CurrentConfig};
+use crate::i18n::{MessageCatalog, Phrase};
+use crate::tenant::{generate_api_key, seed_prompts, Tenant, TenantCredentials, TenantError, TenantStore, API_KEY_SCOPES};
+use crate::twilio::client::TwilioClient;
+
+/// Phone number to attach to a new tenant
+#[derive(Debug, Deserialize)]
+pub struct PhoneNumberRequest {
+    pub number: String,
+    /// Purchase the number instead of configuring one the account already owns
+    #[serde(default)]
+    pub purchase: bool,
+}
+
+/// API key to create for a new tenant
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+/// Request for provisioning a tenant
+#[derive(Debug, Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+    /// Twilio credentials; the service credentials are used when omitted
+    pub credentials: Option<TenantCredentials>,
+    pub phone_number: Option<PhoneNumberRequest>,
+    /// Default caller ID for outbound calls; must be owned or verified on the account
+    pub caller_id: Option<String>,
+    /// Daily spend above which the tenant's outbound calls are refused
+    pub daily_budget: Option<f64>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyRequest>,
+    /// Prompt overrides keyed by phrase key
+    #[serde(default)]
+    pub prompts: HashMap<String, String>,
+}
+
+/// An API key as returned once at creation time
+#[derive(Debug, Serialize)]
+pub struct IssuedApiKey {
+    pub id: String,
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<String>,
+}
+
+/// Response for the tenant provisioning endpoint
+#[derive(Debug, Serialize)]
+pub struct CreateTenantResponse {
+    pub tenant_id: String,
+    pub name: String,
+    pub phone_numbers: Vec<String>,
+    pub api_keys: Vec<IssuedApiKey>,
+}
+
+/// Provision a new tenant
+#[post("/api/tenants", format = "json", data = "<request>")]
+pub async fn create_tenant(
+    _admin: AdminAuth,
+    request: Json<CreateTenantRequest>,
+    tenants: &State<Arc<TenantStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    config: CurrentConfig,
+) -> ApiResult<CreateTenantResponse> {
+    let request = request.into_inner();
+
+    // Validate the request before touching Twilio
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(api_error(Status::BadRequest, "Tenant name cannot be empty"));
+    }
+    // Checked again when the tenant is added; this only avoids provisioning numbers for a taken name
+    if tenants.name_exists(&name) {
+        return Err(api_error(Status::Conflict, &format!("Tenant '{}' already exists", name)));
+    }
+    if request.daily_budget.is_some_and(|b| !b.is_finite() || b < 0.0) {
+        return Err(api_error(Status::BadRequest, "daily_budget must be a non-negative number"));
+    }
+    for key in &request.api_keys {
+        if let Some(scope) = key.scopes.iter().find(|s| !API_KEY_SCOPES.contains(&s.as_str())) {
+            return Err(api_error(Status::BadRequest, &format!("Unknown API key scope '{}'", scope)));
+        }
+    }
+    if let Some(key) = request.prompts.keys().find(|k| !Phrase::ALL.iter().any(|p| p.key() == k.as_str())) {
+        return Err(api_error(Status::BadRequest, &format!("Unknown prompt key '{}'", key)));
+    }
+    if request.credentials.is_some() && !tenants.can_store_credentials() {
+        return Err(api_error(Status::BadRequest, "TENANT_SECRETS_KEY must be set to store tenant credentials"));
+    }
+
+    // Configure or purchase the phone number
+    let mut phone_numbers = Vec::new();
+    let mut provisioned = None;
+    if let Some(phone) = &request.phone_number {
+        let (account_sid, auth_token) = match &request.credentials {
+            Some(c) => (c.account_sid.clone(), c.auth_token.clone()),
+            None => (config.twilio.account_sid.clone(), config.twilio.auth_token.clone()),
+        };
+        let twilio_client = match TwilioClient::new(account_sid, auth_token, config.twilio.region.clone(), config.twilio.edge.clone()) {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to create Twilio client for tenant {}: {}", name, e);
+                return Err(api_error(Status::InternalServerError, &format!("Failed to create Twilio client: {}", e)));
+            }
+        };
+
+        match provision_number(phone, &twilio_client, &config).await {
+            Ok(number) => provisioned = Some((twilio_client, number)),
+            Err(e) => {
+                error!("Failed to provision {} for tenant {}: {}", phone.number, name, e);
+                return Err(api_error(Status::BadGateway, &format!("Failed to provision phone number: {}", e)));
+            }
+        }
+        phone_numbers.push(phone.number.clone());
+    }
+
+    // Issue API keys, defaulting to a single key with every scope
+    let key_requests = if request.api_keys.is_empty() {
+        vec![ApiKeyRequest {
+            name: "default".to_string(),
+            scopes: API_KEY_SCOPES.iter().map(|s| s.to_string()).collect(),
+        }]
+    } else {
+        request.api_keys
+    };
+
+    let mut api_keys = Vec::new();
+    let mut issued = Vec::new();
+    for key_request in key_requests {
+        let (key, secret) = generate_api_key(&key_request.name, key_request.scopes);
+        issued.push(IssuedApiKey {
+            id: key.id.clone(),
+            name: key.name.clone(),
+            key: secret,
+            scopes: key.scopes.clone(),
+        });
+        api_keys.push(key);
+    }
+
+    let tenant = Tenant {
+        id: Uuid::new_v4().to_string(),
+        name: name.clone(),
+        credentials: request.credentials,
+        phone_numbers: phone_numbers.clone(),
+        caller_id: request.caller_id,
+        daily_budget: request.daily_budget,
+        api_keys,
+        prompts: seed_prompts(catalog, &request.prompts),
+        created_at: Utc::now(),
+    };
+    let tenant_id = tenant.id.clone();
+
+    if let Err(e) = tenants.add(tenant) {
+        // Nothing refers to the number without the tenant, so it is returned to how it was
+        if let Some((twilio_client, number)) = &provisioned {
+            undo_provisioning(number, twilio_client).await;
+        }
+        return Err(match e {
+            TenantError::NameTaken(_) => api_error(Status::Conflict, &e.to_string()),
+            TenantError::Persist(_) => {
+                error!("Failed to persist tenant {}: {}", name, e);
+                api_error(Status::InternalServerError, "Failed to persist tenant")
+            },
+        });
+    }
+
+    info!("Provisioned tenant {} ({})", name, tenant_id);
+
+    Ok(Json(CreateTenantResponse {
+        tenant_id,
+        name,
+        phone_numbers,
+        api_keys: issued,
+    }))
+}
+
+/// A phone number configured for a new tenant
+struct ProvisionedNumber {
+    sid: String,
+    /// Voice URL the number had before, or `None` if it was purchased
+    previous_voice_url: Option<String>,
+}
+
+/// Point a tenant phone number at our incoming call webhook, purchasing it if requested
+async fn provision_number(
+    phone: &PhoneNumberRequest,
+    twilio_client: &TwilioClient,
+    config: &Config,
+) -> Result<ProvisionedNumber, String> {
+    let voice_url = config.twilio.callback_url("/incoming_callback");
+
+    if phone.purchase {
+        let number = twilio_client.purchase_phone_number(&phone.number, &voice_url)
+            .await
+            .map_err(|e| e.to_string())?;
+        let sid = number.get("sid")
+            .and_then(|s| s.as_str())
+            .ok_or_else(|| format!("Twilio returned no SID for purchased number {}", phone.number))?;
+        return Ok(ProvisionedNumber { sid: sid.to_string(), previous_voice_url: None });
+    }
+
+    let numbers = twilio_client.list_phone_numbers(&phone.number)
+        .await
+        .map_err(|e| e.to_string())?;
+    let number = numbers.first()
+        .ok_or_else(|| format!("Number {} is not owned by the account", phone.number))?;
+    let sid = number.get("sid")
+        .and_then(|s| s.as_str())
+        .ok_or_else(|| format!("Number {} is not owned by the account", phone.number))?;
+    let previous_voice_url = number.get("voice_url")
+        .and_then(|u| u.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    twilio_client.update_phone_number(sid, &voice_url)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ProvisionedNumber { sid: sid.to_string(), previous_voice_url: Some(previous_voice_url) })
+}
+
+/// Release a purchased number, or point a configured one back at its previous voice URL
+async fn undo_provisioning(number: &ProvisionedNumber, twilio_client: &TwilioClient) {
+    let result = match &number.previous_voice_url {
+        Some(voice_url) => twilio_client.update_phone_number(&number.sid, voice_url).await.map(|_| ()),
+        None => twilio_client.release_phone_number(&number.sid).await,
+    };
+    if let Err(e) = result {
+        error!("Failed to undo provisioning of phone number {}: {}", number.sid, e);
+    }
+}
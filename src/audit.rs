@@ -0,0 +1,330 @@
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::config::AuditConfig;
+
+/// Hash the first record of a chain links to
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// How often records past the retention period are dropped
+const RETENTION_INTERVAL_SECONDS: u64 = 3600;
+
+/// What an audit record captures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEntry {
+    /// A conversation turn: what reached the backend and what the bot did with its answer
+    Turn {
+        /// Caller speech, digits or the event the turn was run for
+        input: String,
+        /// Text the bot spoke, if any
+        response: Option<String>,
+        /// Audio the bot played instead of text
+        audio_url: Option<String>,
+        /// What the bot did with the turn (e.g. `reply`, `transfer`, `hangup`)
+        decision: String,
+        /// Transfer, queue or escalation target
+        target: Option<String>,
+        /// When the input reached the service
+        input_at: DateTime<Utc>,
+        /// When the reply was returned to Twilio
+        responded_at: DateTime<Utc>,
+    },
+    /// A decision the service made outside a turn, such as ending a call at its limits
+    Decision {
+        decision: String,
+        reason: Option<String>,
+    },
+}
+
+/// Input a turn was run for, stamped when it reached the service
+#[derive(Debug, Clone)]
+pub struct TurnInput {
+    pub text: String,
+    pub at: DateTime<Utc>,
+}
+
+impl TurnInput {
+    pub fn new(text: impl Into<String>) -> Self {
+        TurnInput {
+            text: text.into(),
+            at: Utc::now(),
+        }
+    }
+}
+
+/// One link of the audit chain
+///
+/// `hash` covers every other field, including the previous record's hash, so
+/// changing, removing or reordering records breaks the chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub sequence: u64,
+    pub call_sid: String,
+    pub session_id: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// Hash of the record's contents and the hash it links to
+    fn compute_hash(&self) -> String {
+        let unsigned = AuditRecord { hash: String::new(), ..self.clone() };
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(serde_json::to_vec(&unsigned).unwrap_or_default());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Result of checking the audit chain
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub records: usize,
+    /// First record whose hash or link does not match
+    pub first_invalid_sequence: Option<u64>,
+    /// Hash the oldest retained record links to
+    pub anchor_hash: String,
+    /// Hash of the newest record
+    pub head_hash: String,
+}
+
+/// Where audit records are kept
+enum AuditTarget {
+    /// Kept only until restart when no file is configured
+    Memory,
+    /// JSON lines file
+    File(String),
+}
+
+/// End of the chain new records are linked to
+struct ChainHead {
+    sequence: u64,
+    hash: String,
+    /// Records of the in-memory target
+    memory: Vec<AuditRecord>,
+}
+
+/// Append-only, hash-chained log of conversation turns and call decisions
+pub struct AuditLog {
+    enabled: bool,
+    retention: Option<Duration>,
+    target: AuditTarget,
+    /// The lock also serializes appends and file rewrites
+    head: Mutex<ChainHead>,
+}
+
+impl AuditLog {
+    /// Create a log, continuing the chain of an existing file
+    pub fn new(config: &AuditConfig) -> Self {
+        let target = match &config.path {
+            Some(path) => AuditTarget::File(path.clone()),
+            None => AuditTarget::Memory,
+        };
+
+        let last = match &target {
+            AuditTarget::File(path) => std::fs::read_to_string(path).ok().and_then(|c| parse_records(&c).pop()),
+            AuditTarget::Memory => None,
+        };
+        if let Some(last) = &last {
+            info!("Continuing audit log at record {}", last.sequence + 1);
+        }
+
+        AuditLog {
+            enabled: config.enabled,
+            retention: (config.retention_days > 0).then(|| Duration::days(config.retention_days as i64)),
+            target,
+            head: Mutex::new(ChainHead {
+                sequence: last.as_ref().map(|r| r.sequence + 1).unwrap_or(0),
+                hash: last.map(|r| r.hash).unwrap_or_else(|| GENESIS_HASH.to_string()),
+                memory: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Append a record for a call, linked to the previous record
+    ///
+    /// A record that cannot be stored is logged and leaves the chain where it was.
+    pub async fn record(&self, call_sid: &str, session_id: Option<&str>, entry: AuditEntry) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut head = self.head.lock().await;
+        let mut record = AuditRecord {
+            sequence: head.sequence,
+            call_sid: call_sid.to_string(),
+            session_id: session_id.map(str::to_string),
+            recorded_at: Utc::now(),
+            entry,
+            prev_hash: head.hash.clone(),
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+
+        let stored = match &self.target {
+            AuditTarget::Memory => {
+                head.memory.push(record.clone());
+                Ok(())
+            },
+            AuditTarget::File(path) => append_file(path, &record).await,
+        };
+
+        match stored {
+            Ok(()) => {
+                head.sequence += 1;
+                head.hash = record.hash;
+            },
+            Err(e) => error!("Failed to store audit record for call {}: {}", call_sid, e),
+        }
+    }
+
+    /// Records of one call, oldest first
+    pub async fn call_records(&self, call_sid: &str) -> Result<Vec<AuditRecord>, String> {
+        Ok(self.records().await?.into_iter().filter(|r| r.call_sid == call_sid).collect())
+    }
+
+    /// Check every retained record's hash and its link to the one before
+    pub async fn verify(&self) -> Result<ChainVerification, String> {
+        let records = self.records().await?;
+        let anchor_hash = records.first().map(|r| r.prev_hash.clone()).unwrap_or_else(|| GENESIS_HASH.to_string());
+
+        let mut expected_prev = anchor_hash.clone();
+        let mut expected_sequence = records.first().map(|r| r.sequence);
+        let mut first_invalid_sequence = None;
+        for record in &records {
+            if record.prev_hash != expected_prev
+                || Some(record.sequence) != expected_sequence
+                || record.hash != record.compute_hash() {
+                first_invalid_sequence = Some(record.sequence);
+                break;
+            }
+            expected_prev = record.hash.clone();
+            expected_sequence = Some(record.sequence + 1);
+        }
+
+        Ok(ChainVerification {
+            valid: first_invalid_sequence.is_none(),
+            records: records.len(),
+            first_invalid_sequence,
+            head_hash: records.last().map(|r| r.hash.clone()).unwrap_or_else(|| anchor_hash.clone()),
+            anchor_hash,
+        })
+    }
+
+    /// Drop records older than the retention period
+    ///
+    /// The oldest remaining record's `prev_hash` becomes the chain's anchor.
+    pub async fn remove_expired(&self) -> Result<usize, String> {
+        let Some(retention) = self.retention else {
+            return Ok(0);
+        };
+        let cutoff = Utc::now() - retention;
+        let mut head = self.head.lock().await;
+
+        match &self.target {
+            AuditTarget::Memory => {
+                let count = head.memory.len();
+                head.memory.retain(|r| r.recorded_at > cutoff);
+                Ok(count - head.memory.len())
+            },
+            AuditTarget::File(path) => {
+                let records = read_file(path).await?;
+                let count = records.len();
+                let remaining: Vec<&AuditRecord> = records.iter().filter(|r| r.recorded_at > cutoff).collect();
+                if remaining.len() == count {
+                    return Ok(0);
+                }
+
+                let mut contents = String::new();
+                for record in remaining.iter() {
+                    contents.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+                    contents.push('\n');
+                }
+                tokio::fs::write(path, contents).await.map_err(|e| e.to_string())?;
+                Ok(count - remaining.len())
+            },
+        }
+    }
+
+    /// All retained records, oldest first
+    async fn records(&self) -> Result<Vec<AuditRecord>, String> {
+        let head = self.head.lock().await;
+
+        match &self.target {
+            AuditTarget::Memory => Ok(head.memory.clone()),
+            AuditTarget::File(path) => read_file(path).await,
+        }
+    }
+}
+
+async fn append_file(path: &str, record: &AuditRecord) -> Result<(), String> {
+    let raw = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| e.to_string())?;
+    file.write_all(format!("{}\n", raw).as_bytes())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read an audit file's records; a missing file has none
+async fn read_file(path: &str) -> Result<Vec<AuditRecord>, String> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => Ok(parse_records(&contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parse JSON lines of records
+///
+/// Unreadable lines are skipped here and show up as a broken link when the chain is verified.
+fn parse_records(contents: &str) -> Vec<AuditRecord> {
+    contents.lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping unreadable audit record: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Start a task dropping audit records past the retention period
+pub fn start_audit_retention_task(audit: Arc<AuditLog>) {
+    if !audit.is_enabled() || audit.retention.is_none() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(RETENTION_INTERVAL_SECONDS));
+
+        loop {
+            interval.tick().await;
+            match audit.remove_expired().await {
+                Ok(0) => {},
+                Ok(removed) => info!("Dropped {} audit records past retention", removed),
+                Err(e) => error!("Failed to apply audit retention: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,118 @@
+//! Replay a recorded call against a running instance
+//!
+//! ```text
+//! cargo run --bin replay -- fixtures/CA0123.jsonl [--url http://localhost:8000] [--webhook-url URL]
+//! ```
+//!
+//! Each recorded webhook is sent in order and its TwiML compared with the
+//! recorded answer element by element. Formatting, attribute order, the
+//! callback URL prefix (`--webhook-url`, by default the instance's versioned
+//! Twilio routes) and signed URL parameters are ignored.
+//! Exits with status 1 if any answer differs.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use twilio_bot::twilio::WEBHOOK_VERSION;
+use twilio_bot::twilio::fixtures::{RecordedWebhook, twiml_structure};
+
+struct Args {
+    fixture: String,
+    url: String,
+    webhook_url: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut fixture = None;
+    let mut url = "http://localhost:8000".to_string();
+    let mut webhook_url = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => url = args.next().ok_or("--url needs a value")?,
+            "--webhook-url" => webhook_url = Some(args.next().ok_or("--webhook-url needs a value")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if fixture.is_none() => fixture = Some(arg),
+            _ => return Err("only one fixture can be replayed at a time".to_string()),
+        }
+    }
+
+    Ok(Args {
+        fixture: fixture.ok_or("usage: replay <fixture.jsonl> [--url URL] [--webhook-url URL]")?,
+        url: url.trim_end_matches('/').to_string(),
+        webhook_url,
+    })
+}
+
+fn load(path: &str) -> Result<Vec<RecordedWebhook>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| serde_json::from_str(line)
+            .map_err(|e| format!("Invalid webhook on line {} of {}: {}", index + 1, path, e)))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let webhooks = match load(&args.fixture) {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let webhook_url = args.webhook_url.clone()
+        .unwrap_or_else(|| format!("{}/twilio/{}", args.url, WEBHOOK_VERSION));
+
+    let client = reqwest::Client::new();
+    let mut mismatches = 0;
+    for webhook in &webhooks {
+        let response = client.post(format!("{}{}", args.url, webhook.uri))
+            .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(webhook.form.clone())
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await;
+        let (status, twiml) = match response {
+            Ok(response) => (response.status().as_u16(), response.text().await.unwrap_or_default()),
+            Err(e) => {
+                eprintln!("Failed to send {}: {}", webhook.uri, e);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let expected = twiml_structure(&webhook.twiml, &webhook.webhook_url);
+        let actual = twiml_structure(&twiml, &webhook_url);
+        if status == webhook.status && actual == expected {
+            println!("ok       {}", webhook.uri);
+        } else {
+            mismatches += 1;
+            println!("MISMATCH {}", webhook.uri);
+            if status != webhook.status {
+                println!("  status   expected {}, got {}", webhook.status, status);
+            }
+            let differs_at = expected.iter().zip(&actual).take_while(|(e, a)| e == a).count();
+            if differs_at < expected.len().max(actual.len()) {
+                println!("  expected {}", expected.get(differs_at).map_or("(end of response)", String::as_str));
+                println!("  actual   {}", actual.get(differs_at).map_or("(end of response)", String::as_str));
+            }
+        }
+    }
+
+    println!("{} webhooks replayed, {} mismatched", webhooks.len(), mismatches);
+    if mismatches > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use futures::stream::SplitSink;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::config::AnalyticsTapConfig;
+
+/// Longest wait for the sink to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Wait after a failed connection before the sink is tried again
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Write half of the tap's WebSocket connection
+type TapSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// One turn of a call as mirrored to the tap
+#[derive(Debug, Clone, Serialize)]
+pub struct TapTurn {
+    pub call_sid: String,
+    pub session_id: String,
+    /// What the caller said or pressed, redacted
+    pub user_text: String,
+    /// Reply as spoken to the caller, redacted
+    pub bot_text: Option<String>,
+    pub audio_url: Option<String>,
+    /// How the turn was answered, as in the audit log
+    pub decision: String,
+    /// When the caller's input reached the service
+    pub input_at: DateTime<Utc>,
+    /// When the reply was returned to Twilio
+    pub responded_at: DateTime<Utc>,
+    /// Milliseconds from the input to the reply
+    pub latency_ms: i64,
+}
+
+impl TapTurn {
+    /// Turn answered at `responded_at`, with its latency from the input
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        call_sid: &str,
+        session_id: &str,
+        user_text: String,
+        bot_text: Option<String>,
+        audio_url: Option<String>,
+        decision: &str,
+        input_at: DateTime<Utc>,
+        responded_at: DateTime<Utc>,
+    ) -> Self {
+        TapTurn {
+            call_sid: call_sid.to_string(),
+            session_id: session_id.to_string(),
+            user_text,
+            bot_text,
+            audio_url,
+            decision: decision.to_string(),
+            input_at,
+            responded_at,
+            latency_ms: (responded_at - input_at).num_milliseconds(),
+        }
+    }
+}
+
+/// A turn queued for the sink the configuration named when it was taken
+#[derive(Debug)]
+struct TapMessage {
+    url: String,
+    token: Option<String>,
+    body: String,
+}
+
+/// Live mirror of call turns to an analytics WebSocket
+///
+/// Turns are queued and sent in order by a background task, so a slow or
+/// unreachable sink never delays a call. The connection is opened on the first
+/// turn and reopened after failures; turns taken while the sink is down, or
+/// beyond the queue size, are dropped. The sink is read from the configuration
+/// of each turn, so a reload pointing the tap elsewhere takes effect at once,
+/// but the queue is created with the first turn's `queue_size` and keeps it
+/// until restart.
+///
+/// A streamed reply is mirrored once its run completes, with the whole text;
+/// replies the backend pushes outside a turn are mirrored as they are spoken.
+#[derive(Default)]
+pub struct AnalyticsTap {
+    sender: OnceLock<mpsc::Sender<TapMessage>>,
+}
+
+impl AnalyticsTap {
+    /// Queue a turn for the sink; does nothing when the tap is disabled
+    pub fn send(&self, turn: &TapTurn, config: &AnalyticsTapConfig) {
+        let Some(url) = &config.url else {
+            return;
+        };
+        let body = match serde_json::to_string(turn) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to serialize turn of call {} for the analytics tap: {}", turn.call_sid, e);
+                return;
+            }
+        };
+
+        let sender = self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel(config.queue_size);
+            tokio::spawn(run(receiver));
+            sender
+        });
+        let message = TapMessage { url: url.clone(), token: config.token.clone(), body };
+        match sender.try_send(message) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => warn!("Analytics tap queue is full, dropping turn of call {}", turn.call_sid),
+            Err(TrySendError::Closed(_)) => error!("Analytics tap stopped, dropping turn of call {}", turn.call_sid),
+        }
+    }
+}
+
+/// Analytics tap shared by every call of the process
+pub fn analytics_tap() -> &'static AnalyticsTap {
+    static TAP: OnceLock<AnalyticsTap> = OnceLock::new();
+    TAP.get_or_init(AnalyticsTap::default)
+}
+
+/// Send queued turns to the sink, connecting as needed
+async fn run(mut receiver: mpsc::Receiver<TapMessage>) {
+    let mut connection: Option<(String, TapSink)> = None;
+    let mut retry_at: Option<Instant> = None;
+
+    while let Some(message) = receiver.recv().await {
+        if connection.as_ref().is_some_and(|(url, _)| *url != message.url) {
+            if let Some((url, mut sink)) = connection.take() {
+                info!("Analytics tap moved from {} to {}", url, message.url);
+                let _ = sink.close().await;
+            }
+            retry_at = None;
+        }
+
+        let sink = match &mut connection {
+            Some((_, sink)) => sink,
+            None => {
+                if retry_at.is_some_and(|at| Instant::now() < at) {
+                    debug!("Analytics tap is down, dropping turn");
+                    continue;
+                }
+                match connect(&message.url, message.token.as_deref()).await {
+                    Ok(sink) => {
+                        info!("Connected analytics tap to {}", message.url);
+                        retry_at = None;
+                        &mut connection.insert((message.url.clone(), sink)).1
+                    },
+                    Err(e) => {
+                        warn!("Failed to connect analytics tap to {}: {}", message.url, e);
+                        retry_at = Some(Instant::now() + RECONNECT_DELAY);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        if let Err(e) = sink.send(Message::Text(message.body)).await {
+            warn!("Analytics tap connection to {} failed, dropping turn: {}", message.url, e);
+            connection = None;
+        }
+    }
+}
+
+/// Open the sink's WebSocket, reading and discarding whatever it sends
+async fn connect(url: &str, token: Option<&str>) -> Result<TapSink, String> {
+    let mut request = url.into_client_request().map_err(|e| e.to_string())?;
+    if let Some(token) = token {
+        let authorization = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| "token is not a valid header value".to_string())?;
+        request.headers_mut().insert(AUTHORIZATION, authorization);
+    }
+
+    let (ws, _) = tokio::time::timeout(CONNECT_TIMEOUT, connect_async(request))
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+    let (sink, mut stream) = ws.split();
+    // Reading keeps pings answered; the sink is not expected to reply to turns
+    tokio::spawn(async move {
+        while let Some(Ok(_)) = stream.next().await {}
+    });
+    Ok(sink)
+}
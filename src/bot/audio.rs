@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant};
+
+/// Decode a single G.711 μ-law sample to 16-bit linear PCM
+pub fn mulaw_to_linear(sample: u8) -> i16 {
+    const BIAS: i16 = 0x84;
+
+    let sample = !sample;
+    let sign = sample & 0x80;
+    let exponent = (sample >> 4) & 0x07;
+    let mantissa = (sample & 0x0F) as i16;
+    let magnitude = (((mantissa << 3) + BIAS) << exponent) - BIAS;
+
+    if sign != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Root-mean-square energy of a μ-law encoded audio frame
+pub fn frame_energy(frame: &[u8]) -> f64 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = frame
+        .iter()
+        .map(|&b| {
+            let s = mulaw_to_linear(b) as f64;
+            s * s
+        })
+        .sum();
+
+    (sum / frame.len() as f64).sqrt()
+}
+
+/// Action the silence monitor asks the caller to take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceAction {
+    /// Nothing to do
+    None,
+    /// Ask the caller whether they can hear the bot
+    AudioCheck,
+    /// The check went unanswered, end the call
+    EndCall,
+}
+
+/// Tracks inbound audio energy for a call and detects sustained silence
+pub struct SilenceMonitor {
+    threshold: f64,
+    silence_timeout: Duration,
+    check_timeout: Duration,
+    last_voice: Instant,
+    check_sent: Option<Instant>,
+    ended: bool,
+    peak_energy: f64,
+}
+
+impl SilenceMonitor {
+    /// Create a new monitor
+    pub fn new(threshold: f64, silence_timeout: Duration, check_timeout: Duration) -> Self {
+        SilenceMonitor {
+            threshold,
+            silence_timeout,
+            check_timeout,
+            last_voice: Instant::now(),
+            check_sent: None,
+            ended: false,
+            peak_energy: 0.0,
+        }
+    }
+
+    /// Record an inbound audio frame, returning whether it carried speech
+    pub fn observe(&mut self, frame: &[u8]) -> bool {
+        let energy = frame_energy(frame);
+        self.peak_energy = self.peak_energy.max(energy);
+        energy >= self.threshold
+    }
+
+    /// Reset the silence window, e.g. after the caller produced a transcription
+    pub fn reset(&mut self) {
+        self.last_voice = Instant::now();
+        self.check_sent = None;
+    }
+
+    /// Reset the silence window to speech heard at `at`, if that is later than the last voiced frame
+    pub fn speech_heard(&mut self, at: Instant) {
+        if at > self.last_voice {
+            self.last_voice = at;
+            self.check_sent = None;
+        }
+    }
+
+    /// Whether the next poll would act on the silence
+    pub fn is_due(&self) -> bool {
+        if self.ended {
+            return false;
+        }
+        match self.check_sent {
+            None => self.last_voice.elapsed() >= self.silence_timeout,
+            Some(sent) => sent.elapsed() >= self.check_timeout,
+        }
+    }
+
+    /// How long the inbound leg has been silent
+    pub fn silence_duration(&self) -> Duration {
+        self.last_voice.elapsed()
+    }
+
+    /// Highest frame energy seen on the stream
+    pub fn peak_energy(&self) -> f64 {
+        self.peak_energy
+    }
+
+    /// Decide what to do given the time elapsed since the last voiced frame
+    pub fn poll(&mut self) -> SilenceAction {
+        if self.ended {
+            return SilenceAction::None;
+        }
+
+        match self.check_sent {
+            None if self.last_voice.elapsed() >= self.silence_timeout => {
+                self.check_sent = Some(Instant::now());
+                SilenceAction::AudioCheck
+            },
+            Some(sent) if sent.elapsed() >= self.check_timeout => {
+                self.ended = true;
+                SilenceAction::EndCall
+            },
+            _ => SilenceAction::None,
+        }
+    }
+}
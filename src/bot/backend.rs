@@ -1,10 +1,19 @@
+use dashmap::DashMap;
 use reqwest::{Client, ClientBuilder, StatusCode, Method};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::{Arc, atomic::{AtomicUsize, AtomicU64, Ordering}};
-use log::{debug, error, info};
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, OnceLock, atomic::{AtomicUsize, AtomicU64, Ordering}};
+use log::{debug, info, warn};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::fmt;
+use std::future::Future;
+use tokio::sync::Mutex;
+
+use crate::bot::cdr::CallSummary;
+use crate::bot::message_queue::MessageSender;
+use crate::bot::session::{MessageType, SessionStore};
+use crate::bot::payload::RunResponse;
+use crate::config::{BackendConfig, BackendTimeouts, OAuthConfig};
 
 /// Response from the backend when opening a session
 #[derive(Debug, Deserialize)]
@@ -20,6 +29,98 @@ pub struct SessionInfo {
     pub session_id: String,
 }
 
+/// Retry a backend operation with exponential backoff
+///
+/// Authentication errors and an open circuit breaker are returned without retrying.
+pub async fn with_retry<T, F, Fut>(
+    max_retries: usize,
+    base_delay_ms: u64,
+    operation: F,
+) -> Result<T, BackendError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BackendError>>,
+{
+    retry(max_retries, base_delay_ms, None, operation).await
+}
+
+/// Retry a backend operation with exponential backoff, giving up once `budget` has passed
+///
+/// Used for turns, which must answer before Twilio abandons the webhook however
+/// many attempts they take.
+pub async fn with_retry_within<T, F, Fut>(
+    max_retries: usize,
+    base_delay_ms: u64,
+    budget: Duration,
+    operation: F,
+) -> Result<T, BackendError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BackendError>>,
+{
+    retry(max_retries, base_delay_ms, Some(Instant::now() + budget), operation).await
+}
+
+async fn retry<T, F, Fut>(
+    max_retries: usize,
+    base_delay_ms: u64,
+    deadline: Option<Instant>,
+    mut operation: F,
+) -> Result<T, BackendError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BackendError>>,
+{
+    let mut attempts = 0;
+    let mut last_error = None;
+    
+    while attempts <= max_retries {
+        let attempt = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline.into(), operation()).await {
+                Ok(attempt) => attempt,
+                Err(_) => {
+                    warn!("Backend call ran out of time after {} attempts", attempts + 1);
+                    return Err(BackendError::RetryExhausted(Box::new(
+                        last_error.unwrap_or(BackendError::ApiError("Backend call timed out".to_string()))
+                    )));
+                }
+            },
+            None => operation().await,
+        };
+        match attempt {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                // Don't retry certain errors
+                match &e {
+                    BackendError::AuthError(_) => return Err(e),
+                    BackendError::CircuitBreakerOpen => return Err(e),
+                    // The backend answered; running the request again would repeat its work
+                    BackendError::JsonError(_) | BackendError::IncompleteReply(_) => return Err(e),
+                    _ => {
+                        attempts += 1;
+                        last_error = Some(e);
+                        
+                        if attempts <= max_retries {
+                            let delay = base_delay_ms * 2u64.pow(attempts as u32 - 1);
+                            if deadline.is_some_and(|deadline| Instant::now() + Duration::from_millis(delay) >= deadline) {
+                                debug!("No time left to retry backend call after {} attempts", attempts);
+                                break;
+                            }
+                            debug!("Retrying backend call, attempt {}/{} after {}ms", 
+                                   attempts, max_retries, delay);
+                            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    Err(BackendError::RetryExhausted(Box::new(
+        last_error.unwrap_or(BackendError::ApiError("Maximum retries exceeded".to_string()))
+    )))
+}
+
 /// Error type for backend client operations
 #[derive(Debug)]
 pub enum BackendError {
@@ -27,10 +128,21 @@ pub enum BackendError {
     AuthError(String),
     ApiError(String),
     JsonError(serde_json::Error),
+    /// Failure reported by a custom backend transport
+    TransportError(String),
+    /// The backend accepted the request but its reply could not be read
+    IncompleteReply(String),
     CircuitBreakerOpen,
     RetryExhausted(Box<BackendError>),
 }
 
+impl BackendError {
+    /// Whether the backend is unavailable rather than rejecting the request
+    pub fn is_outage(&self) -> bool {
+        matches!(self, BackendError::CircuitBreakerOpen | BackendError::RetryExhausted(_))
+    }
+}
+
 impl fmt::Display for BackendError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -38,6 +150,8 @@ impl fmt::Display for BackendError {
             BackendError::AuthError(msg) => write!(f, "Authentication error: {}", msg),
             BackendError::ApiError(msg) => write!(f, "API error: {}", msg),
             BackendError::JsonError(err) => write!(f, "JSON error: {}", err),
+            BackendError::TransportError(msg) => write!(f, "Transport error: {}", msg),
+            BackendError::IncompleteReply(msg) => write!(f, "Incomplete reply: {}", msg),
             BackendError::CircuitBreakerOpen => write!(f, "Circuit breaker is open"),
             BackendError::RetryExhausted(err) => write!(f, "Retry exhausted: {}", err),
         }
@@ -118,24 +232,324 @@ impl CircuitBreaker {
     }
 }
 
+/// HTTP request to the backend API, ready to send
+#[derive(Debug, Clone)]
+pub struct BackendRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    pub timeout: Duration,
+}
+
+/// Raw response from the backend API
+#[derive(Debug, Clone)]
+pub struct BackendResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Carries backend API requests, so embedders and tests can replace the HTTP client
+///
+/// Authentication, timeouts, the circuit breaker and retries are applied by
+/// `BackendClient` before a request reaches the transport. OAuth2 token requests
+/// and the backend WebSocket do not go through it.
+#[rocket::async_trait]
+pub trait BackendTransport: Send + Sync + 'static {
+    /// Send a request, returning any response the backend produced
+    async fn send(&self, request: BackendRequest) -> Result<BackendResponse, BackendError>;
+    
+    /// Send a request, handing out the response body as it arrives
+    ///
+    /// Transports that cannot stream deliver the whole body as one chunk.
+    async fn send_streaming(&self, request: BackendRequest) -> Result<BackendStream, BackendError> {
+        let response = self.send(request).await?;
+        Ok(BackendStream {
+            status: response.status,
+            content_type: response.content_type,
+            body: StreamBody::Buffered(VecDeque::from([response.body])),
+        })
+    }
+}
+
+/// Response from the backend API whose body is read as it arrives
+pub struct BackendStream {
+    pub status: u16,
+    pub content_type: Option<String>,
+    body: StreamBody,
+}
+
+enum StreamBody {
+    Buffered(VecDeque<Vec<u8>>),
+    Http(reqwest::Response),
+}
+
+impl BackendStream {
+    /// Next chunk of the body; `None` once it is complete
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, BackendError> {
+        match &mut self.body {
+            StreamBody::Buffered(chunks) => Ok(chunks.pop_front()),
+            StreamBody::Http(response) => Ok(response.chunk().await?.map(|chunk| chunk.to_vec())),
+        }
+    }
+    
+    /// The rest of the body
+    async fn read_to_end(&mut self) -> Result<Vec<u8>, BackendError> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.next_chunk().await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+/// Reply to a streamed run, read as the backend produces it
+///
+/// A `text/event-stream` reply is a series of events:
+///
+/// ```text
+/// data: Your order ships
+///
+/// data: tomorrow.
+///
+/// event: result
+/// data: {"metadata": {"expected_answer": "yes_no"}}
+/// ```
+///
+/// Unnamed `message` events carry reply text. The `result` event carries the
+/// run's JSON result and ends the reply; without a `response` the streamed
+/// text is used. An `error` event fails the run. Any other content type is
+/// read as a plain JSON result.
+pub struct RunStream {
+    stream: BackendStream,
+}
+
+impl RunStream {
+    /// Whether the reply arrives as server-sent events
+    pub fn is_streaming(&self) -> bool {
+        self.stream.content_type.as_deref()
+            .and_then(|t| t.split(';').next())
+            .is_some_and(|t| t.trim().eq_ignore_ascii_case("text/event-stream"))
+    }
+    
+    /// Read the whole reply, queueing each piece of text as it arrives
+    pub async fn forward(mut self, message_tx: &MessageSender) -> Result<RunResponse, BackendError> {
+        if !self.is_streaming() {
+            let body = self.stream.read_to_end().await?;
+            return Ok(serde_json::from_slice(&body)?);
+        }
+        
+        // Bytes are decoded once an event is complete, so characters split across chunks stay intact
+        let mut pending: Vec<u8> = Vec::new();
+        let mut text = Vec::new();
+        loop {
+            while let Some(end) = event_end(&pending) {
+                let event: Vec<u8> = pending.drain(..end).collect();
+                let (name, data) = parse_event(&String::from_utf8_lossy(&event).replace("\r\n", "\n"));
+                match name.as_str() {
+                    "message" if !data.is_empty() => {
+                        if message_tx.send(MessageType::Text(data.clone())).await.is_err() {
+                            return Err(BackendError::ApiError("Session closed while streaming".to_string()));
+                        }
+                        text.push(data);
+                    },
+                    "result" => {
+                        let mut result: RunResponse = serde_json::from_str(&data)?;
+                        if result.response.is_none() && !text.is_empty() {
+                            result.response = Some(text.join(" "));
+                        }
+                        return Ok(result);
+                    },
+                    "error" => return Err(BackendError::ApiError(data)),
+                    _ => {},
+                }
+            }
+            
+            match self.stream.next_chunk().await? {
+                Some(chunk) => pending.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        
+        debug!("Backend stream ended without a result event");
+        Ok(RunResponse {
+            response: (!text.is_empty()).then(|| text.join(" ")),
+            ..RunResponse::default()
+        })
+    }
+}
+
+/// Length of the first complete event in `pending`, including the blank line ending it
+fn event_end(pending: &[u8]) -> Option<usize> {
+    (0..pending.len()).find_map(|index| {
+        let rest = &pending[index..];
+        if rest.starts_with(b"\n\n") {
+            Some(index + 2)
+        } else if rest.starts_with(b"\r\n\r\n") {
+            Some(index + 4)
+        } else {
+            None
+        }
+    })
+}
+
+/// Name and data of a server-sent event
+fn parse_event(event: &str) -> (String, String) {
+    let mut name = "message".to_string();
+    let mut data = Vec::new();
+    for line in event.lines() {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => name = value.to_string(),
+            "data" => data.push(value),
+            _ => {},
+        }
+    }
+    (name, data.join("\n"))
+}
+
+/// Backend transport over HTTP
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Create an HTTP transport giving up on connecting after `connect_timeout_ms`
+    pub fn new(connect_timeout_ms: u64) -> Result<Self, BackendError> {
+        let client = ClientBuilder::new()
+            .connect_timeout(Duration::from_millis(connect_timeout_ms))
+            .build()?;
+        Ok(HttpTransport { client })
+    }
+}
+
+#[rocket::async_trait]
+impl BackendTransport for HttpTransport {
+    async fn send(&self, request: BackendRequest) -> Result<BackendResponse, BackendError> {
+        let mut builder = self.client.request(request.method, &request.url)
+            .timeout(request.timeout);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let content_type = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = match response.bytes().await {
+            Ok(body) => body.to_vec(),
+            // A successful run has already been carried out by the backend
+            Err(e) if (200..300).contains(&status) => return Err(BackendError::IncompleteReply(e.to_string())),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(BackendResponse { status, content_type, body })
+    }
+    
+    async fn send_streaming(&self, request: BackendRequest) -> Result<BackendStream, BackendError> {
+        let mut builder = self.client.request(request.method, &request.url)
+            .timeout(request.timeout);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        Ok(BackendStream {
+            status: response.status().as_u16(),
+            content_type: response.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            body: StreamBody::Http(response),
+        })
+    }
+}
+
+/// Access token from the OAuth2 token endpoint
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Access token cached until shortly before it expires
+#[derive(Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+/// Tokens are refreshed this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Access tokens shared by all backend clients, keyed by token URL and client ID
+///
+/// Not every client is pooled, so the cache lives for the whole process.
+fn token_cache() -> &'static Mutex<HashMap<String, CachedToken>> {
+    static TOKENS: OnceLock<Mutex<HashMap<String, CachedToken>>> = OnceLock::new();
+    TOKENS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Locks letting one token request per cache key run at a time
+///
+/// Callers waiting on a request reuse the token it fetched instead of requesting their own.
+fn token_requests() -> &'static DashMap<String, Arc<Mutex<()>>> {
+    static REQUESTS: OnceLock<DashMap<String, Arc<Mutex<()>>>> = OnceLock::new();
+    REQUESTS.get_or_init(DashMap::new)
+}
+
+/// How the client authenticates to the backend
+enum BackendAuth {
+    None,
+    /// Static bearer token
+    Bearer(String),
+    /// OAuth2 client credentials, exchanged for cached access tokens
+    ClientCredentials(OAuthConfig),
+}
+
 /// Client for interacting with the backend API
 pub struct BackendClient {
+    /// Client for OAuth2 token requests
     client: Client,
+    transport: Arc<dyn BackendTransport>,
     base_url: String,
-    authorization_token: Option<String>,
+    auth: BackendAuth,
     circuit_breaker: Option<Arc<CircuitBreaker>>,
+    timeouts: BackendTimeouts,
 }
 
 impl BackendClient {
-    /// Create a new backend client
+    /// Create a new backend client with default timeouts
     pub fn new(
         base_url: &str, 
         authorization_token: Option<String>,
         enable_circuit_breaker: bool,
+    ) -> Result<Self, BackendError> {
+        Self::with_timeouts(base_url, authorization_token, enable_circuit_breaker, BackendTimeouts::default())
+    }
+    
+    /// Create a new backend client with per-operation timeouts
+    pub fn with_timeouts(
+        base_url: &str, 
+        authorization_token: Option<String>,
+        enable_circuit_breaker: bool,
+        timeouts: BackendTimeouts,
     ) -> Result<Self, BackendError> {
         let client = ClientBuilder::new()
+            .connect_timeout(Duration::from_millis(timeouts.connect_ms))
             .build()
             .map_err(BackendError::from)?;
+        let transport = Arc::new(HttpTransport { client: client.clone() });
         
         let circuit_breaker = if enable_circuit_breaker {
             Some(Arc::new(CircuitBreaker::new(5, 30000))) // 5 failures, 30s reset
@@ -145,27 +559,123 @@ impl BackendClient {
             
         Ok(BackendClient {
             client,
+            transport,
             base_url: base_url.to_string(),
-            authorization_token,
+            auth: match authorization_token {
+                Some(token) => BackendAuth::Bearer(token),
+                None => BackendAuth::None,
+            },
             circuit_breaker,
+            timeouts,
         })
     }
     
-    /// Add authorization header to a request builder if a token is available
-    fn add_auth_header(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        if let Some(token) = &self.authorization_token {
-            builder.header("Authorization", format!("Bearer {}", token))
-        } else {
-            builder
+    /// Create a backend client from configuration, preferring OAuth2 over a static token
+    pub fn from_config(config: &BackendConfig) -> Result<Self, BackendError> {
+        let mut client = Self::with_timeouts(
+            &config.url,
+            config.authorization_token.clone(),
+            config.enable_circuit_breaker,
+            config.timeouts.clone()
+        )?;
+        
+        if let Some(oauth) = &config.oauth {
+            client.auth = BackendAuth::ClientCredentials(oauth.clone());
+        }
+        
+        Ok(client)
+    }
+    
+    /// Create a backend client from configuration that sends its API requests through `transport`
+    pub fn with_transport(config: &BackendConfig, transport: Arc<dyn BackendTransport>) -> Result<Self, BackendError> {
+        let mut client = Self::from_config(config)?;
+        client.transport = transport;
+        Ok(client)
+    }
+    
+    /// Get a cached OAuth2 access token, requesting a new one if needed
+    ///
+    /// A `rejected` token the backend turned down is never returned; it is only
+    /// replaced once, however many requests saw it rejected.
+    async fn access_token(&self, oauth: &OAuthConfig, rejected: Option<&str>) -> Result<String, BackendError> {
+        let key = format!("{}#{}", oauth.token_url, oauth.client_id);
+        let cached = |tokens: &HashMap<String, CachedToken>| {
+            tokens.get(&key)
+                .filter(|t| t.expires_at > Instant::now() && Some(t.access_token.as_str()) != rejected)
+                .map(|t| t.access_token.clone())
+        };
+        
+        if let Some(token) = cached(&*token_cache().lock().await) {
+            return Ok(token);
+        }
+        
+        // The cache is not locked during the request, only other requests for the same key wait
+        let request_lock = token_requests().entry(key.clone()).or_default().clone();
+        let _request = request_lock.lock().await;
+        if let Some(token) = cached(&*token_cache().lock().await) {
+            return Ok(token);
+        }
+        
+        debug!("Requesting backend access token from {}", oauth.token_url);
+        
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = &oauth.scope {
+            form.push(("scope", scope));
+        }
+        
+        let response = self.client.post(&oauth.token_url)
+            .basic_auth(&oauth.client_id, Some(&oauth.client_secret))
+            .form(&form)
+            .timeout(Duration::from_millis(self.timeouts.default_ms))
+            .send()
+            .await?;
+        
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            token_cache().lock().await.remove(&key);
+            return Err(BackendError::AuthError(format!("Token request failed: {} ({})", error_text, status)));
+        }
+        
+        let token: TokenResponse = response.json().await?;
+        let lifetime = Duration::from_secs(token.expires_in.unwrap_or(3600));
+        token_cache().lock().await.insert(key, CachedToken {
+            access_token: token.access_token.clone(),
+            expires_at: Instant::now() + lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN),
+        });
+        
+        Ok(token.access_token)
+    }
+    
+    /// Bearer token for the backend other than a `rejected` one, or `None` if the client does not authenticate
+    pub async fn bearer_token(&self, rejected: Option<&str>) -> Result<Option<String>, BackendError> {
+        match &self.auth {
+            BackendAuth::None => Ok(None),
+            BackendAuth::Bearer(token) => Ok(Some(token.clone())),
+            BackendAuth::ClientCredentials(oauth) => self.access_token(oauth, rejected).await.map(Some),
+        }
+    }
+    
+    /// Add the authorization header to a request if the client authenticates, returning the token sent
+    async fn add_auth_header(
+        &self,
+        request: &mut BackendRequest,
+        rejected: Option<&str>,
+    ) -> Result<Option<String>, BackendError> {
+        let token = self.bearer_token(rejected).await?;
+        if let Some(token) = &token {
+            request.headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
         }
+        Ok(token)
     }
     
-    /// Generic API request method
+    /// Generic API request method, giving up after `timeout_ms`
     async fn make_api_request<T: serde::de::DeserializeOwned>(
         &self,
         method: Method,
         path: &str,
         body: Option<serde_json::Value>,
+        timeout_ms: u64,
     ) -> Result<T, BackendError> {
         // Check circuit breaker
         if let Some(cb) = &self.circuit_breaker {
@@ -175,34 +685,50 @@ impl BackendClient {
         }
         
         let url = format!("{}{}", self.base_url, path);
+        let body = body.map(|b| serde_json::to_vec(&b)).transpose()?;
         
-        let mut request = self.client.request(method, &url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json");
+        // An expired or revoked OAuth token gets one retry with a fresh token
+        let mut rejected: Option<String> = None;
+        let response = loop {
+            let mut request = BackendRequest {
+                method: method.clone(),
+                url: url.clone(),
+                headers: vec![
+                    ("Content-Type".to_string(), "application/json".to_string()),
+                    ("Accept".to_string(), "application/json".to_string()),
+                ],
+                body: body.clone(),
+                timeout: Duration::from_millis(timeout_ms),
+            };
+            let token = self.add_auth_header(&mut request, rejected.as_deref()).await?;
             
-        request = self.add_auth_header(request);
-        
-        if let Some(body_data) = body {
-            request = request.json(&body_data);
-        }
-        
-        let response = match request.send().await {
-            Ok(resp) => resp,
-            Err(e) => {
-                // Record failure
-                if let Some(cb) = &self.circuit_breaker {
-                    cb.record_failure();
+            let response = match self.transport.send(request).await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    // Record failure
+                    if let Some(cb) = &self.circuit_breaker {
+                        cb.record_failure();
+                    }
+                    return Err(e);
                 }
-                return Err(BackendError::RequestError(e));
+            };
+            
+            let can_refresh = matches!(self.auth, BackendAuth::ClientCredentials(_)) && rejected.is_none();
+            if response.status == StatusCode::UNAUTHORIZED.as_u16() && can_refresh {
+                warn!("Backend rejected access token, refreshing");
+                rejected = Some(token.unwrap_or_default());
+                continue;
             }
+            
+            break response;
         };
         
-        let status = response.status();
+        let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::BAD_GATEWAY);
         
         if status == StatusCode::FORBIDDEN {
             return Err(BackendError::AuthError("Permission denied".to_string()));
         } else if !status.is_success() {
-            let error_text = response.text().await?;
+            let error_text = String::from_utf8_lossy(&response.body);
             
             // Record failure
             if let Some(cb) = &self.circuit_breaker {
@@ -217,10 +743,7 @@ impl BackendClient {
             cb.record_success();
         }
         
-        match response.json().await {
-            Ok(result) => Ok(result),
-            Err(e) => Err(BackendError::RequestError(e)),
-        }
+        Ok(serde_json::from_slice(&response.body)?)
     }
     
     /// Run with retry capability
@@ -231,37 +754,33 @@ impl BackendClient {
         kwargs: HashMap<String, serde_json::Value>,
         max_retries: usize,
         base_delay_ms: u64,
-    ) -> Result<serde_json::Value, BackendError> {
-        let mut attempts = 0;
-        let mut last_error = None;
+    ) -> Result<RunResponse, BackendError> {
+        let budget = Duration::from_millis(self.timeouts.run_total_ms);
+        with_retry_within(max_retries, base_delay_ms, budget, || self.run(session_id, message, kwargs.clone())).await
+    }
+    
+    /// Report a call event to an existing session, with retry capability
+    ///
+    /// Events are not user input: the run carries a `null` message and the
+    /// event under the `event` kwarg, so the backend never sees an empty utterance.
+    pub async fn run_event_with_retry(
+        &self,
+        session_id: &str,
+        event: serde_json::Value,
+        max_retries: usize,
+        base_delay_ms: u64,
+    ) -> Result<RunResponse, BackendError> {
+        let path = format!("/session/{}/run", session_id);
         
-        while attempts <= max_retries {
-            match self.run(session_id, message, kwargs.clone()).await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    // Don't retry certain errors
-                    match &e {
-                        BackendError::AuthError(_) => return Err(e),
-                        BackendError::CircuitBreakerOpen => return Err(e),
-                        _ => {
-                            attempts += 1;
-                            last_error = Some(e);
-                            
-                            if attempts <= max_retries {
-                                let delay = base_delay_ms * 2u64.pow(attempts as u32 - 1);
-                                debug!("Retrying backend call, attempt {}/{} after {}ms", 
-                                       attempts, max_retries, delay);
-                                tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let body = serde_json::json!({
+            "message": null,
+            "kwargs": { "event": event }
+        });
         
-        Err(BackendError::RetryExhausted(Box::new(
-            last_error.unwrap_or(BackendError::ApiError("Maximum retries exceeded".to_string()))
-        )))
+        let budget = Duration::from_millis(self.timeouts.run_total_ms);
+        with_retry_within(max_retries, base_delay_ms, budget, || {
+            self.make_api_request(Method::POST, &path, Some(body.clone()), self.timeouts.run_ms)
+        }).await
     }
     
     /// Run a command on an existing session
@@ -278,7 +797,7 @@ impl BackendClient {
             "args": args
         });
         
-        self.make_api_request(Method::POST, &path, Some(body)).await
+        self.make_api_request(Method::POST, &path, Some(body), self.timeouts.default_ms).await
     }
     
     /// Run a message on an existing session
@@ -287,7 +806,7 @@ impl BackendClient {
         session_id: &str,
         message: &str,
         kwargs: HashMap<String, serde_json::Value>,
-    ) -> Result<serde_json::Value, BackendError> {
+    ) -> Result<RunResponse, BackendError> {
         let path = format!("/session/{}/run", session_id);
         
         let body = serde_json::json!({
@@ -295,7 +814,82 @@ impl BackendClient {
             "kwargs": kwargs
         });
         
-        self.make_api_request(Method::POST, &path, Some(body)).await
+        self.make_api_request(Method::POST, &path, Some(body), self.timeouts.run_ms).await
+    }
+    
+    /// Run a message on an existing session, accepting the reply as server-sent events
+    ///
+    /// Returns once the backend starts answering; see [`RunStream`] for the event format.
+    /// A backend that answers with plain JSON is handled too.
+    pub async fn run_streaming(
+        &self,
+        session_id: &str,
+        message: &str,
+        kwargs: HashMap<String, serde_json::Value>,
+    ) -> Result<RunStream, BackendError> {
+        if let Some(cb) = &self.circuit_breaker {
+            if cb.is_open() {
+                return Err(BackendError::CircuitBreakerOpen);
+            }
+        }
+        
+        let url = format!("{}/session/{}/run", self.base_url, session_id);
+        let body = serde_json::to_vec(&serde_json::json!({
+            "message": message,
+            "kwargs": kwargs
+        }))?;
+        
+        // An expired or revoked OAuth token gets one retry with a fresh token
+        let mut rejected: Option<String> = None;
+        let mut stream = loop {
+            let mut request = BackendRequest {
+                method: Method::POST,
+                url: url.clone(),
+                headers: vec![
+                    ("Content-Type".to_string(), "application/json".to_string()),
+                    ("Accept".to_string(), "text/event-stream, application/json".to_string()),
+                ],
+                body: Some(body.clone()),
+                timeout: Duration::from_millis(self.timeouts.run_ms),
+            };
+            let token = self.add_auth_header(&mut request, rejected.as_deref()).await?;
+            
+            let stream = match self.transport.send_streaming(request).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    if let Some(cb) = &self.circuit_breaker {
+                        cb.record_failure();
+                    }
+                    return Err(e);
+                }
+            };
+            
+            let can_refresh = matches!(self.auth, BackendAuth::ClientCredentials(_)) && rejected.is_none();
+            if stream.status == StatusCode::UNAUTHORIZED.as_u16() && can_refresh {
+                warn!("Backend rejected access token, refreshing");
+                rejected = Some(token.unwrap_or_default());
+                continue;
+            }
+            
+            break stream;
+        };
+        
+        let status = StatusCode::from_u16(stream.status).unwrap_or(StatusCode::BAD_GATEWAY);
+        if status == StatusCode::FORBIDDEN {
+            return Err(BackendError::AuthError("Permission denied".to_string()));
+        } else if !status.is_success() {
+            let body = stream.read_to_end().await.unwrap_or_default();
+            if let Some(cb) = &self.circuit_breaker {
+                cb.record_failure();
+            }
+            return Err(BackendError::ApiError(format!("API error: {} ({})", String::from_utf8_lossy(&body), status)));
+        }
+        
+        if let Some(cb) = &self.circuit_breaker {
+            cb.record_success();
+        }
+        
+        Ok(RunStream { stream })
     }
     
     /// Start a message processing on an existing session
@@ -311,7 +905,7 @@ impl BackendClient {
             "kwargs": {}
         });
         
-        self.make_api_request(Method::POST, &path, Some(body)).await
+        self.make_api_request(Method::POST, &path, Some(body), self.timeouts.run_ms).await
     }
     
     /// Commit a message processing on an existing session
@@ -322,7 +916,7 @@ impl BackendClient {
         let path = format!("/session/{}/commit", session_id);
         let body = serde_json::json!({});
         
-        self.make_api_request(Method::POST, &path, Some(body)).await
+        self.make_api_request(Method::POST, &path, Some(body), self.timeouts.default_ms).await
     }
     
     /// Rollback a message processing on an existing session
@@ -333,7 +927,7 @@ impl BackendClient {
         let path = format!("/session/{}/rollback", session_id);
         let body = serde_json::json!({});
         
-        self.make_api_request(Method::POST, &path, Some(body)).await
+        self.make_api_request(Method::POST, &path, Some(body), self.timeouts.default_ms).await
     }
     
     /// Open a new session with the backend
@@ -360,7 +954,8 @@ impl BackendClient {
         let session_response: SessionResponse = self.make_api_request(
             Method::POST, 
             path, 
-            Some(body)
+            Some(body),
+            self.timeouts.open_session_ms
         ).await?;
         
         info!("Opened session with ID: {}", session_response.session.session_id);
@@ -368,23 +963,28 @@ impl BackendClient {
         Ok(session_response)
     }
     
-    /// Update an existing session
+    /// Update an existing session's conversation ID and metadata
+    ///
+    /// Metadata is merged into the backend's copy; keys set to `null` are removed.
     pub async fn update_session(
         &self,
         session_id: &str,
         conversation_id: Option<&str>,
+        metadata: Option<&serde_json::Map<String, serde_json::Value>>,
     ) -> Result<serde_json::Value, BackendError> {
         let path = format!("/session/{}", session_id);
         
         let mut body = serde_json::json!({});
         
         if let Some(cid) = conversation_id {
-            body = serde_json::json!({
-                "conversation_id": cid
-            });
+            body["conversation_id"] = serde_json::json!(cid);
         }
         
-        self.make_api_request(Method::PUT, &path, Some(body)).await
+        if let Some(metadata) = metadata {
+            body["metadata"] = serde_json::json!(metadata);
+        }
+        
+        self.make_api_request(Method::PUT, &path, Some(body), self.timeouts.default_ms).await
     }
     
     /// Close an existing session
@@ -401,9 +1001,373 @@ impl BackendClient {
         
         debug!("Closing session {} with status {:?}", session_id, status);
         
-        let _: serde_json::Value = self.make_api_request(Method::DELETE, &path, None).await?;
+        let _: serde_json::Value = self.make_api_request(Method::DELETE, &path, None, self.timeouts.close_session_ms).await?;
         
         info!("Successfully closed session {}", session_id);
         Ok(())
     }
-}
\ No newline at end of file
+    
+    /// Post the end-of-call summary for a session
+    pub async fn post_call_summary(
+        &self,
+        session_id: &str,
+        summary: &CallSummary,
+    ) -> Result<(), BackendError> {
+        self.post_call_summary_payload(session_id, serde_json::to_value(summary)?).await
+    }
+    
+    /// Post an already serialized end-of-call summary for a session
+    pub async fn post_call_summary_payload(
+        &self,
+        session_id: &str,
+        body: serde_json::Value,
+    ) -> Result<(), BackendError> {
+        let path = format!("/session/{}/summary", session_id);
+        
+        debug!("Posting call summary for session {}", session_id);
+        
+        let _: serde_json::Value = self.make_api_request(Method::POST, &path, Some(body), self.timeouts.default_ms).await?;
+        Ok(())
+    }
+    
+    /// Render text to speech, returning the audio and its content type
+    pub async fn render_speech(
+        &self,
+        path: &str,
+        text: &str,
+        voice: &str,
+        language: Option<&str>,
+    ) -> Result<(Vec<u8>, String), BackendError> {
+        if let Some(cb) = &self.circuit_breaker {
+            if cb.is_open() {
+                return Err(BackendError::CircuitBreakerOpen);
+            }
+        }
+        
+        let body = serde_json::json!({
+            "text": text,
+            "voice": voice,
+            "language": language
+        });
+        
+        let mut request = BackendRequest {
+            method: Method::POST,
+            url: format!("{}{}", self.base_url, path),
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: Some(serde_json::to_vec(&body)?),
+            timeout: Duration::from_millis(self.timeouts.default_ms),
+        };
+        self.add_auth_header(&mut request, None).await?;
+        
+        let response = self.transport.send(request).await?;
+        if !(200..300).contains(&response.status) {
+            let error_text = String::from_utf8_lossy(&response.body);
+            return Err(BackendError::ApiError(format!("Speech rendering failed: {} ({})", error_text, response.status)));
+        }
+        
+        let content_type = response.content_type.unwrap_or_else(|| "audio/mpeg".to_string());
+        Ok((response.body, content_type))
+    }
+    
+    /// Probe a backend health path, succeeding on any 2xx response
+    ///
+    /// Probes bypass the circuit breaker so they report recovery while it is open.
+    pub async fn check_health(&self, path: &str, timeout_ms: u64) -> Result<(), BackendError> {
+        let mut request = BackendRequest {
+            method: Method::GET,
+            url: format!("{}{}", self.base_url, path),
+            headers: Vec::new(),
+            body: None,
+            timeout: Duration::from_millis(timeout_ms),
+        };
+        self.add_auth_header(&mut request, None).await?;
+        
+        let response = self.transport.send(request).await?;
+        if !(200..300).contains(&response.status) {
+            return Err(BackendError::ApiError(format!("Health check failed ({})", response.status)));
+        }
+        
+        Ok(())
+    }
+}
+/// Backend clients kept for the life of the process, one per bot
+///
+/// Pooled clients share their transport and circuit breaker across requests.
+/// A client is rebuilt when a reload changes its backend configuration.
+pub struct BackendPool {
+    transport: Arc<dyn BackendTransport>,
+    /// Clients keyed by bot name, the default backend under `""`, with the configuration they were built from
+    clients: DashMap<String, (String, Arc<BackendClient>)>,
+}
+
+impl BackendPool {
+    /// Create a pool whose clients send their API requests through `transport`
+    pub fn new(transport: impl BackendTransport) -> Self {
+        BackendPool {
+            transport: Arc::new(transport),
+            clients: DashMap::new(),
+        }
+    }
+    
+    /// Client for a bot's backend; `None` selects the default backend
+    pub fn client(&self, config: &BackendConfig, bot: Option<&str>) -> Result<Arc<BackendClient>, BackendError> {
+        let Some(profile) = config.profile(bot) else {
+            return Err(BackendError::ApiError(format!("Unknown bot '{}'", bot.unwrap_or_default())));
+        };
+        let fingerprint = serde_json::to_string(&profile)?;
+        let key = bot.unwrap_or_default();
+        
+        if let Some(entry) = self.clients.get(key).filter(|entry| entry.0 == fingerprint) {
+            return Ok(entry.1.clone());
+        }
+        
+        debug!("Creating backend client for {}", bot.unwrap_or("the default bot"));
+        let client = Arc::new(BackendClient::with_transport(&profile, self.transport.clone())?);
+        self.clients.insert(key.to_string(), (fingerprint, client.clone()));
+        Ok(client)
+    }
+    
+    /// Client for the backend a session is bound to
+    ///
+    /// Sessions no longer in the store use the default backend.
+    pub async fn for_session(
+        &self,
+        sessions: &SessionStore,
+        session_id: &str,
+        config: &BackendConfig,
+    ) -> Result<Arc<BackendClient>, BackendError> {
+        let bot = match sessions.get_session(session_id) {
+            Some(handle) => handle.lock().await.bot.clone(),
+            None => None,
+        };
+        self.client(config, bot.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+    use crate::bot::message_queue::message_queue;
+    use crate::config::{BackendProfile, WsTlsConfig};
+    
+    /// Transport answering with scripted responses and recording what it was sent
+    #[derive(Clone, Default)]
+    struct ScriptedTransport {
+        responses: Arc<StdMutex<VecDeque<Result<BackendResponse, BackendError>>>>,
+        requests: Arc<StdMutex<Vec<BackendRequest>>>,
+    }
+    
+    impl ScriptedTransport {
+        fn reply(self, status: u16, content_type: &str, body: &str) -> Self {
+            self.responses.lock().unwrap().push_back(Ok(BackendResponse {
+                status,
+                content_type: Some(content_type.to_string()),
+                body: body.as_bytes().to_vec(),
+            }));
+            self
+        }
+        
+        fn fail(self) -> Self {
+            self.responses.lock().unwrap().push_back(Err(BackendError::TransportError("connection reset".to_string())));
+            self
+        }
+        
+        fn requests(&self) -> Vec<BackendRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+    
+    #[rocket::async_trait]
+    impl BackendTransport for ScriptedTransport {
+        async fn send(&self, request: BackendRequest) -> Result<BackendResponse, BackendError> {
+            self.requests.lock().unwrap().push(request);
+            self.responses.lock().unwrap().pop_front().expect("unexpected backend request")
+        }
+    }
+    
+    fn config() -> BackendConfig {
+        BackendConfig {
+            url: "http://backend.test".to_string(),
+            authorization_token: Some("secret".to_string()),
+            oauth: None,
+            ws_url: "ws://backend.test".to_string(),
+            ws_headers: HashMap::new(),
+            ws_tls: WsTlsConfig::default(),
+            enable_circuit_breaker: false,
+            retry_attempts: 2,
+            retry_base_delay_ms: 1,
+            timeouts: BackendTimeouts::default(),
+            turn_kwargs: Vec::new(),
+            call_summary_enabled: false,
+            stream_responses: false,
+            profiles: HashMap::new(),
+        }
+    }
+    
+    fn client(transport: &ScriptedTransport) -> BackendClient {
+        BackendClient::with_transport(&config(), Arc::new(transport.clone())).unwrap()
+    }
+    
+    fn header<'a>(request: &'a BackendRequest, name: &str) -> Option<&'a str> {
+        request.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+    
+    fn body(request: &BackendRequest) -> serde_json::Value {
+        serde_json::from_slice(request.body.as_deref().unwrap()).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn opens_session_with_bearer_token() {
+        let transport = ScriptedTransport::default()
+            .reply(200, "application/json", r#"{"session": {"session_id": "s1"}}"#);
+        
+        let session = client(&transport)
+            .open_session("+15550001111", "caller", "voice", Some("c1"), vec![], HashMap::new())
+            .await
+            .unwrap();
+        
+        assert_eq!(session.session.session_id, "s1");
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::POST);
+        assert_eq!(requests[0].url, "http://backend.test/session");
+        assert_eq!(header(&requests[0], "Authorization"), Some("Bearer secret"));
+        assert_eq!(body(&requests[0])["conversation_id"], "c1");
+    }
+    
+    #[tokio::test]
+    async fn retries_failed_turns() {
+        let transport = ScriptedTransport::default()
+            .reply(500, "text/plain", "overloaded")
+            .fail()
+            .reply(200, "application/json", r#"{"response": "Hello"}"#);
+        
+        let result = client(&transport)
+            .run_with_retry("s1", "hi", HashMap::new(), 2, 1)
+            .await
+            .unwrap();
+        
+        assert_eq!(result.response.as_deref(), Some("Hello"));
+        assert_eq!(transport.requests().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn does_not_retry_forbidden_turns() {
+        let transport = ScriptedTransport::default()
+            .reply(403, "application/json", "{}");
+        
+        let result = client(&transport).run_with_retry("s1", "hi", HashMap::new(), 2, 1).await;
+        
+        assert!(matches!(result, Err(BackendError::AuthError(_))));
+        assert_eq!(transport.requests().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn does_not_retry_unreadable_successful_turns() {
+        let transport = ScriptedTransport::default()
+            .reply(200, "application/json", r#"{"response": 42}"#);
+        
+        let result = client(&transport).run_with_retry("s1", "hi", HashMap::new(), 2, 1).await;
+        
+        assert!(matches!(result, Err(BackendError::JsonError(_))));
+        assert_eq!(transport.requests().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn reports_events_without_a_message() {
+        let transport = ScriptedTransport::default()
+            .reply(200, "application/json", "{}");
+        
+        client(&transport)
+            .run_event_with_retry("s1", serde_json::json!({"type": "dtmf", "digits": "1"}), 0, 1)
+            .await
+            .unwrap();
+        
+        let request = &transport.requests()[0];
+        assert_eq!(request.url, "http://backend.test/session/s1/run");
+        assert!(body(request)["message"].is_null());
+        assert_eq!(body(request)["kwargs"]["event"]["digits"], "1");
+    }
+    
+    #[tokio::test]
+    async fn forwards_streamed_replies() {
+        let transport = ScriptedTransport::default().reply(
+            200,
+            "text/event-stream; charset=utf-8",
+            "data: Your order ships\r\n\r\ndata: tomorrow.\n\nevent: result\ndata: {\"metadata\": {\"expected_answer\": \"yes_no\"}}\n\n",
+        );
+        let (message_tx, mut message_rx) = message_queue(10);
+        
+        let stream = client(&transport).run_streaming("s1", "hi", HashMap::new()).await.unwrap();
+        assert!(stream.is_streaming());
+        let result = stream.forward(&message_tx).await.unwrap();
+        
+        assert_eq!(result.response.as_deref(), Some("Your order ships tomorrow."));
+        let texts: Vec<String> = message_rx.drain().into_iter()
+            .filter_map(|message| match message {
+                MessageType::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, ["Your order ships", "tomorrow."]);
+        assert_eq!(header(&transport.requests()[0], "Accept"), Some("text/event-stream, application/json"));
+    }
+    
+    #[tokio::test]
+    async fn decodes_characters_split_across_chunks() {
+        let body = "data: Caf\u{e9} ouvert.\n\nevent: result\ndata: {}\n\n".as_bytes();
+        let split = body.iter().position(|b| *b == 0xc3).unwrap() + 1;
+        let stream = RunStream {
+            stream: BackendStream {
+                status: 200,
+                content_type: Some("text/event-stream".to_string()),
+                body: StreamBody::Buffered(VecDeque::from([body[..split].to_vec(), body[split..].to_vec()])),
+            },
+        };
+        let (message_tx, _message_rx) = message_queue(10);
+        
+        let result = stream.forward(&message_tx).await.unwrap();
+        
+        assert_eq!(result.response.as_deref(), Some("Caf\u{e9} ouvert."));
+    }
+    
+    #[tokio::test]
+    async fn reads_json_replies_to_streamed_runs() {
+        let transport = ScriptedTransport::default()
+            .reply(200, "application/json", r#"{"response": "Hello"}"#);
+        let (message_tx, _message_rx) = message_queue(10);
+        
+        let stream = client(&transport).run_streaming("s1", "hi", HashMap::new()).await.unwrap();
+        assert!(!stream.is_streaming());
+        
+        assert_eq!(stream.forward(&message_tx).await.unwrap().response.as_deref(), Some("Hello"));
+    }
+    
+    #[tokio::test]
+    async fn pool_clients_use_the_pool_transport() {
+        let transport = ScriptedTransport::default()
+            .reply(200, "application/json", "{}")
+            .reply(200, "application/json", "{}");
+        let pool = BackendPool::new(transport.clone());
+        let mut config = config();
+        config.profiles.insert("sales".to_string(), BackendProfile {
+            url: "http://sales.test".to_string(),
+            ws_url: "ws://sales.test".to_string(),
+            authorization_token: None,
+            numbers: Vec::new(),
+            ws_headers: HashMap::new(),
+            ws_tls: WsTlsConfig::default(),
+        });
+        
+        pool.client(&config, None).unwrap().close_session("s1", None).await.unwrap();
+        pool.client(&config, Some("sales")).unwrap().close_session("s2", None).await.unwrap();
+        
+        let requests = transport.requests();
+        assert_eq!(requests[0].url, "http://backend.test/session/s1");
+        assert_eq!(requests[1].url, "http://sales.test/session/s2");
+        assert_eq!(header(&requests[1], "Authorization"), None);
+        assert!(Arc::ptr_eq(&pool.client(&config, None).unwrap(), &pool.client(&config, None).unwrap()));
+        assert!(pool.client(&config, Some("billing")).is_err());
+    }
+}
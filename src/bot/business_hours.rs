@@ -0,0 +1,75 @@
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::config::BusinessSchedule;
+
+/// Weekday keys of a schedule's hours
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("fri", Weekday::Fri),
+    ("sat", Weekday::Sat),
+    ("sun", Weekday::Sun),
+];
+
+/// Parse comma-separated `HH:MM-HH:MM` ranges; `24:00` ends a range at midnight
+fn parse_ranges(ranges: &str) -> Result<Vec<(NaiveTime, Option<NaiveTime>)>, String> {
+    ranges.split(',')
+        .map(str::trim)
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let invalid = || format!("invalid hours '{}', expected HH:MM-HH:MM", range);
+            let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+            let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| invalid())?;
+            let end = match end.trim() {
+                "24:00" => None,
+                end => Some(NaiveTime::parse_from_str(end, "%H:%M").map_err(|_| invalid())?),
+            };
+            if end.is_some_and(|end| end <= start) {
+                return Err(format!("hours '{}' end before they start", range));
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
+
+impl BusinessSchedule {
+    /// Check the time zone, weekdays and hours
+    pub fn validate(&self) -> Result<(), String> {
+        self.timezone.parse::<Tz>()
+            .map_err(|_| format!("unknown time zone '{}'", self.timezone))?;
+        for (day, ranges) in &self.hours {
+            if !WEEKDAYS.iter().any(|(key, _)| day.eq_ignore_ascii_case(key)) {
+                return Err(format!("unknown weekday '{}', expected mon to sun", day));
+            }
+            parse_ranges(ranges)?;
+        }
+        if let Some(url) = &self.callback_url {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err("callback_url must be an http(s) URL".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the number is open at `now`
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        let Ok(timezone) = self.timezone.parse::<Tz>() else {
+            return true;
+        };
+        let local = now.with_timezone(&timezone);
+        if self.holidays.contains(&local.date_naive()) {
+            return false;
+        }
+
+        let weekday = local.weekday();
+        let time = local.time();
+        self.hours.iter()
+            .filter(|(day, _)| WEEKDAYS.iter().any(|(key, d)| *d == weekday && day.eq_ignore_ascii_case(key)))
+            .filter_map(|(_, ranges)| parse_ranges(ranges).ok())
+            .flatten()
+            .any(|(start, end)| time >= start && end.is_none_or(|end| time < end))
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::redis_layer::RedisLayer;
+
+/// How often expired entries are dropped from memory
+const CLEANUP_INTERVAL_SECONDS: u64 = 60;
+
+/// A caller's last call, offered to the backend when they call back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviousCall {
+    /// Backend session holding the earlier conversation
+    pub session_id: String,
+    pub call_sid: String,
+    pub disposition: String,
+    pub turn_count: u32,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// Short-lived mapping from caller number to their last call
+///
+/// Kept in Redis when one is shared by the replicas, so a call back can land
+/// on any instance; otherwise in memory only.
+pub struct CallerHistory {
+    window: Duration,
+    calls: DashMap<String, PreviousCall>,
+    redis: Option<RedisLayer>,
+}
+
+impl CallerHistory {
+    /// Create a history remembering calls for `window_minutes`; 0 disables it
+    pub fn new(window_minutes: u64, redis: Option<RedisLayer>) -> Self {
+        CallerHistory {
+            window: Duration::minutes(window_minutes as i64),
+            calls: DashMap::new(),
+            redis,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.window > Duration::zero()
+    }
+
+    /// Remember a finished call with a backend session
+    ///
+    /// Withheld and malformed caller numbers are not tracked.
+    pub async fn record(&self, number: &str, call: PreviousCall) {
+        if !self.is_enabled() || !number.starts_with('+') {
+            return;
+        }
+
+        if let Some(redis) = &self.redis {
+            let Ok(payload) = serde_json::to_string(&call) else {
+                return;
+            };
+            let stored = redis::cmd("SET")
+                .arg(redis.key(&["caller_history", number]))
+                .arg(payload)
+                .arg("EX")
+                .arg(self.window.num_seconds())
+                .query_async::<_, ()>(&mut redis.connection())
+                .await;
+            if let Err(e) = stored {
+                warn!("Failed to store caller history for {}: {}", number, e);
+            }
+        }
+
+        debug!("Remembering session {} for caller {}", call.session_id, number);
+        self.calls.insert(number.to_string(), call);
+    }
+
+    /// The caller's last call, if it ended within the window
+    pub async fn recent(&self, number: &str) -> Option<PreviousCall> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let call = match &self.redis {
+            Some(redis) => {
+                let payload: Option<String> = redis::cmd("GET")
+                    .arg(redis.key(&["caller_history", number]))
+                    .query_async(&mut redis.connection())
+                    .await
+                    .map_err(|e| warn!("Failed to read caller history for {}: {}", number, e))
+                    .ok()
+                    .flatten();
+                payload.and_then(|p| serde_json::from_str(&p).ok())
+                    .or_else(|| self.calls.get(number).map(|c| c.clone()))
+            },
+            None => self.calls.get(number).map(|c| c.clone()),
+        };
+
+        call.filter(|c| Utc::now() - c.ended_at <= self.window)
+    }
+
+    /// Drop calls that ended before the window
+    pub fn remove_expired(&self) {
+        let cutoff = Utc::now() - self.window;
+        self.calls.retain(|_, call| call.ended_at > cutoff);
+    }
+}
+
+/// Start a task dropping expired caller history from memory
+pub fn start_caller_history_cleanup_task(history: Arc<CallerHistory>) {
+    if !history.is_enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CLEANUP_INTERVAL_SECONDS));
+
+        loop {
+            interval.tick().await;
+            history.remove_expired();
+        }
+    });
+}
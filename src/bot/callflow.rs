@@ -0,0 +1,149 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+/// Stage of an outbound call, in the order a call goes through them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallState {
+    /// The backend session is open and the call was placed
+    #[default]
+    Created,
+    /// The callee's phone is ringing
+    Ringing,
+    /// The callee picked up
+    Answered,
+    /// The callee is hearing the greeting the call was placed with
+    Greeted,
+    /// The callee took a turn
+    Conversing,
+    /// Twilio reported a final call status
+    Ended,
+}
+
+impl CallState {
+    /// State a Twilio call status reports, for statuses that move an outbound call
+    pub fn from_call_status(call_status: &str) -> Option<Self> {
+        match call_status {
+            "ringing" => Some(CallState::Ringing),
+            "in-progress" => Some(CallState::Answered),
+            "completed" | "busy" | "no-answer" | "canceled" | "failed" => Some(CallState::Ended),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallState::Created => "created",
+            CallState::Ringing => "ringing",
+            CallState::Answered => "answered",
+            CallState::Greeted => "greeted",
+            CallState::Conversing => "conversing",
+            CallState::Ended => "ended",
+        }
+    }
+}
+
+impl fmt::Display for CallState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Progress of an outbound call through its states
+///
+/// Twilio does not guarantee webhook order, so a call only ever moves forward:
+/// a late `ringing` after the answer, a repeated `in-progress`, or an answer
+/// arriving after the callee already spoke is logged and ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallFlow {
+    pub state: CallState,
+    /// When the call entered its current state
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl CallFlow {
+    /// Start the flow of a call that was just placed
+    pub fn new() -> Self {
+        CallFlow {
+            state: CallState::Created,
+            updated_at: Some(Utc::now()),
+        }
+    }
+
+    /// Move the call to `next` if it comes after the current state
+    ///
+    /// Returns whether the call moved; repeated and out-of-order events leave it unchanged.
+    pub fn advance(&mut self, call_sid: &str, next: CallState) -> bool {
+        if next <= self.state {
+            debug!("Ignoring out-of-order {} event for outbound call {} in state {}", next, call_sid, self.state);
+            return false;
+        }
+
+        info!("Outbound call {} moved from {} to {}", call_sid, self.state, next);
+        self.state = next;
+        self.updated_at = Some(Utc::now());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_call_statuses_to_states() {
+        assert_eq!(CallState::from_call_status("ringing"), Some(CallState::Ringing));
+        assert_eq!(CallState::from_call_status("in-progress"), Some(CallState::Answered));
+        for status in ["completed", "busy", "no-answer", "canceled", "failed"] {
+            assert_eq!(CallState::from_call_status(status), Some(CallState::Ended));
+        }
+        assert_eq!(CallState::from_call_status("queued"), None);
+        assert_eq!(CallState::from_call_status("initiated"), None);
+    }
+
+    #[test]
+    fn advances_through_states_in_order() {
+        let mut flow = CallFlow::new();
+        for state in [CallState::Ringing, CallState::Answered, CallState::Greeted, CallState::Conversing, CallState::Ended] {
+            assert!(flow.advance("CA1", state));
+            assert_eq!(flow.state, state);
+        }
+    }
+
+    #[test]
+    fn skips_states_that_were_not_reported() {
+        let mut flow = CallFlow::new();
+        assert!(flow.advance("CA1", CallState::Answered));
+        assert!(flow.advance("CA1", CallState::Ended));
+        assert_eq!(flow.state, CallState::Ended);
+    }
+
+    #[test]
+    fn ignores_repeated_and_late_events() {
+        let mut flow = CallFlow::new();
+        assert!(flow.advance("CA1", CallState::Answered));
+        let updated_at = flow.updated_at;
+
+        assert!(!flow.advance("CA1", CallState::Answered));
+        assert!(!flow.advance("CA1", CallState::Ringing));
+        assert_eq!(flow.state, CallState::Answered);
+        assert_eq!(flow.updated_at, updated_at);
+
+        assert!(flow.advance("CA1", CallState::Conversing));
+        assert!(!flow.advance("CA1", CallState::Answered));
+        assert!(!flow.advance("CA1", CallState::Greeted));
+        assert_eq!(flow.state, CallState::Conversing);
+    }
+
+    #[test]
+    fn ended_calls_stay_ended() {
+        let mut flow = CallFlow::new();
+        assert!(flow.advance("CA1", CallState::Ended));
+        for state in [CallState::Ringing, CallState::Answered, CallState::Greeted, CallState::Conversing, CallState::Ended] {
+            assert!(!flow.advance("CA1", state));
+        }
+        assert_eq!(flow.state, CallState::Ended);
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::experiments::EXPERIMENT_METADATA_KEY;
+use crate::bot::recording::{RecordingDecision, RECORDING_METADATA_KEY};
+use crate::bot::session::Session;
+use crate::config::CostConfig;
+use crate::tenant::Tenant;
+
+/// Session metadata key holding the outcome of the call's last transfer
+pub const TRANSFER_METADATA_KEY: &str = "transfer";
+
+/// Party that ended a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HangupSource {
+    /// The caller hung up
+    Caller,
+    /// The bot ended the call (conversation end, voicemail drop, audio check)
+    Bot,
+    /// The party the call was transferred to hung up
+    TransferTarget,
+    /// The call ended because the service failed
+    Error,
+}
+
+impl HangupSource {
+    /// Infer the hangup source from a terminal call status when nothing was recorded
+    ///
+    /// A completed call without a session means we hung up after failing to
+    /// create or find one.
+    pub fn from_call_status(call_status: &str, had_session: bool) -> Option<Self> {
+        match call_status {
+            "completed" if had_session => Some(HangupSource::Caller),
+            "completed" | "failed" => Some(HangupSource::Error),
+            _ => None,
+        }
+    }
+
+    /// Name used in APIs and analytics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HangupSource::Caller => "caller",
+            HangupSource::Bot => "bot",
+            HangupSource::TransferTarget => "transfer_target",
+            HangupSource::Error => "error",
+        }
+    }
+}
+
+/// Call detail record for a finished (or live) call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallRecord {
+    pub call_sid: String,
+    pub session_id: Option<String>,
+    pub caller: Option<String>,
+    /// `inbound`, `outbound-api` or `outbound-dial`, as Twilio reported it
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Answering machine detection result, for calls that ran it
+    #[serde(default)]
+    pub answered_by: Option<String>,
+    /// Final Twilio call status (e.g. `completed`, `busy`)
+    pub status: String,
+    /// Call outcome (e.g. `voicemail_left`), defaults to the call status
+    pub disposition: String,
+    pub hangup_source: Option<HangupSource>,
+    /// Whether the call was recorded, when recording is enabled
+    pub recording_consent: Option<RecordingDecision>,
+    /// Dial attempt number, for calls placed with a retry policy
+    pub attempt: Option<u32>,
+    /// Tenant owning the number the call was made to or from
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// Experiment and variant the call was assigned
+    #[serde(default)]
+    pub experiment: Option<serde_json::Value>,
+    /// What Twilio charged for the call, once known
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub price_unit: Option<String>,
+    /// Personal data matches redacted from the call's speech and replies
+    #[serde(default)]
+    pub redactions: u32,
+    /// Labels attached by the call request, the backend or an admin
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Last transfer of the call: its target, dial result and duration
+    #[serde(default)]
+    pub transfer: Option<serde_json::Value>,
+    /// Message the caller recorded after hours: its recording URL and duration
+    #[serde(default)]
+    pub voicemail: Option<serde_json::Value>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<u64>,
+}
+
+impl CallRecord {
+    /// Build a record from a session, falling back to the call status for the outcome
+    pub fn from_session(call_sid: &str, session: &Session, status: &str) -> Self {
+        CallRecord {
+            call_sid: call_sid.to_string(),
+            session_id: Some(session.session_id.clone()),
+            caller: Some(session.name.clone()),
+            direction: session.call.direction.clone(),
+            answered_by: session.call.answered_by.clone(),
+            status: status.to_string(),
+            disposition: session.disposition.clone().unwrap_or_else(|| status.to_string()),
+            hangup_source: session.hangup_source,
+            recording_consent: session.metadata
+                .get(RECORDING_METADATA_KEY)
+                .and_then(|d| serde_json::from_value(d.clone()).ok()),
+            attempt: None,
+            tenant_id: session.tenant_id.clone(),
+            experiment: session.metadata.get(EXPERIMENT_METADATA_KEY).cloned(),
+            price: None,
+            price_unit: None,
+            redactions: session.redactions,
+            tags: session.tags.clone(),
+            transfer: session.metadata.get(TRANSFER_METADATA_KEY).cloned(),
+            voicemail: None,
+            started_at: Some(session.creation_time),
+            ended_at: None,
+            duration_seconds: None,
+        }
+    }
+
+    /// Build a record for a call we have no session for
+    pub fn without_session(call_sid: &str, session_id: Option<String>, status: &str) -> Self {
+        CallRecord {
+            call_sid: call_sid.to_string(),
+            session_id,
+            caller: None,
+            direction: None,
+            answered_by: None,
+            status: status.to_string(),
+            disposition: status.to_string(),
+            hangup_source: None,
+            recording_consent: None,
+            attempt: None,
+            tenant_id: None,
+            experiment: None,
+            price: None,
+            price_unit: None,
+            redactions: 0,
+            tags: BTreeSet::new(),
+            transfer: None,
+            voicemail: None,
+            started_at: None,
+            ended_at: None,
+            duration_seconds: None,
+        }
+    }
+}
+
+/// End-of-call summary posted to the backend for analytics
+#[derive(Debug, Clone, Serialize)]
+pub struct CallSummary {
+    #[serde(flatten)]
+    pub record: CallRecord,
+    pub turn_count: u32,
+    /// Queue the caller last waited in for an agent
+    pub queue: Option<String>,
+    /// Number or SIP address the caller was transferred to
+    pub transfer_target: Option<String>,
+    /// Backend session holding the conversation transcript
+    pub transcript_session_id: Option<String>,
+    /// Message the caller recorded while the backend was unavailable
+    pub outage_voicemail: Option<serde_json::Value>,
+    /// Third party dialed into the call and how the escalation ended
+    pub escalation: Option<serde_json::Value>,
+    /// Follow-up SMS sent during the call with their last delivery status
+    pub sms: Option<serde_json::Value>,
+}
+
+impl CallSummary {
+    /// Summarize a finished call from its record and the session it had, if any
+    pub fn new(record: CallRecord, session: Option<&Session>) -> Self {
+        CallSummary {
+            transcript_session_id: record.session_id.clone(),
+            turn_count: session.map(|s| s.turn_count).unwrap_or_default(),
+            queue: session.and_then(|s| s.queue.clone()),
+            transfer_target: session.and_then(|s| s.transfer_target.clone()),
+            outage_voicemail: session.and_then(|s| s.metadata.get("outage_voicemail").cloned()),
+            escalation: session.and_then(|s| s.metadata.get("escalation").cloned()),
+            sms: session.and_then(|s| s.metadata.get("sms").cloned()),
+            record,
+        }
+    }
+}
+
+/// Hangup and disposition counts across retained call records
+#[derive(Debug, Serialize)]
+pub struct HangupSummary {
+    pub total_calls: usize,
+    pub by_hangup_source: HashMap<String, usize>,
+    pub by_disposition: HashMap<String, usize>,
+}
+
+/// Call spend for one tenant on one day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyCost {
+    pub tenant_id: Option<String>,
+    pub date: NaiveDate,
+    pub calls: usize,
+    pub total: f64,
+}
+
+/// Longest tag accepted on a call
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// Trim a call tag, rejecting empty or overlong ones
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tags must not be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!("Tag '{}' is longer than {} characters", tag, MAX_TAG_LENGTH));
+    }
+    Ok(tag.to_string())
+}
+
+/// Filters of a call search; unset filters match every call
+#[derive(Debug, Default)]
+pub struct CallQuery<'a> {
+    pub tag: Option<&'a str>,
+    /// Number of the other party, as stored in the record's `caller`
+    pub from: Option<&'a str>,
+    pub status: Option<&'a str>,
+}
+
+impl CallQuery<'_> {
+    fn matches(&self, record: &CallRecord) -> bool {
+        self.tag.is_none_or(|tag| record.tags.contains(tag))
+            && self.from.is_none_or(|from| record.caller.as_deref() == Some(from))
+            && self.status.is_none_or(|status| record.status == status || record.disposition == status)
+    }
+}
+
+/// A page of call records matching a search, most recent first
+#[derive(Debug, Serialize)]
+pub struct CallPage {
+    pub calls: Vec<CallRecord>,
+    /// Matching records across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Where a call's result is reported when it ends
+#[derive(Debug, Clone)]
+pub struct ResultCallback {
+    pub url: String,
+    /// Tenant that placed the call, whose signing secrets sign the result
+    pub tenant_id: Option<String>,
+}
+
+/// How long details of a live call without a session are kept waiting for its record
+///
+/// Twilio ends calls after at most 24 hours, so older entries are for calls
+/// whose end was reported to another replica or lost.
+const PENDING_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Details of a live call without a session, added to its record when it ends
+#[derive(Debug)]
+struct PendingCall {
+    tags: BTreeSet<String>,
+    /// Message the caller recorded after hours
+    voicemail: Option<serde_json::Value>,
+    since: Instant,
+}
+
+impl Default for PendingCall {
+    fn default() -> Self {
+        PendingCall {
+            tags: BTreeSet::new(),
+            voicemail: None,
+            since: Instant::now(),
+        }
+    }
+}
+
+/// Bounded in-memory store of recent call detail records
+pub struct CdrStore {
+    capacity: usize,
+    records: RwLock<(HashMap<String, CallRecord>, VecDeque<String>)>,
+    /// Result callbacks for calls that have not ended yet, keyed by call SID
+    result_callbacks: RwLock<HashMap<String, ResultCallback>>,
+    /// Tags and messages of live calls without a session, keyed by call SID
+    pending: RwLock<HashMap<String, PendingCall>>,
+    /// Call count and spend by tenant and UTC day, kept beyond record eviction
+    costs: RwLock<HashMap<(Option<String>, NaiveDate), DailyCost>>,
+}
+
+impl CdrStore {
+    /// Create a store keeping at most `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        CdrStore {
+            capacity,
+            records: RwLock::new((HashMap::new(), VecDeque::new())),
+            result_callbacks: RwLock::new(HashMap::new()),
+            pending: RwLock::new(HashMap::new()),
+            costs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Store a record, evicting the oldest records beyond capacity
+    pub fn record(&self, mut record: CallRecord) {
+        self.attach_pending(&mut record);
+        if self.capacity == 0 {
+            return;
+        }
+
+        debug!(
+            "Call {} ended: {} (hangup source {:?})",
+            record.call_sid, record.disposition, record.hangup_source
+        );
+
+        let mut guard = self.records.write().unwrap();
+        let (records, order) = &mut *guard;
+
+        if records.insert(record.call_sid.clone(), record.clone()).is_none() {
+            order.push_back(record.call_sid);
+        }
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                records.remove(&oldest);
+            }
+        }
+    }
+
+    /// Register a URL to notify with the call's record when it ends
+    pub fn add_result_callback(&self, call_sid: &str, url: &str, tenant_id: Option<&str>) {
+        self.result_callbacks.write().unwrap().insert(call_sid.to_string(), ResultCallback {
+            url: url.to_string(),
+            tenant_id: tenant_id.map(str::to_string),
+        });
+    }
+
+    /// Remove and return the result callback for a call
+    pub fn take_result_callback(&self, call_sid: &str) -> Option<ResultCallback> {
+        self.result_callbacks.write().unwrap().remove(call_sid)
+    }
+
+    /// Keep the message recorded on a call; returns its updated record if the call already ended
+    pub fn add_voicemail(&self, call_sid: &str, voicemail: serde_json::Value) -> Option<CallRecord> {
+        let mut guard = self.records.write().unwrap();
+        match guard.0.get_mut(call_sid) {
+            Some(record) => {
+                record.voicemail = Some(voicemail);
+                Some(record.clone())
+            },
+            None => {
+                self.pending.write().unwrap().entry(call_sid.to_string()).or_default().voicemail = Some(voicemail);
+                None
+            },
+        }
+    }
+
+    /// Move the tags and message kept for a call that is ending into its record
+    ///
+    /// Done before the call's result is sent, so the result carries them.
+    pub fn attach_pending(&self, record: &mut CallRecord) {
+        if let Some(pending) = self.pending.write().unwrap().remove(&record.call_sid) {
+            record.tags.extend(pending.tags);
+            record.voicemail = pending.voicemail.or(record.voicemail.take());
+        }
+    }
+
+    /// Drop details kept for calls that never produced a record
+    pub fn purge_pending(&self) {
+        self.pending.write().unwrap().retain(|_, pending| pending.since.elapsed() < PENDING_TTL);
+    }
+
+    /// Get the record for a call
+    pub fn get(&self, call_sid: &str) -> Option<CallRecord> {
+        self.records.read().unwrap().0.get(call_sid).cloned()
+    }
+
+    /// Track a call that has no session with its request's tags, for its record when it ends
+    pub fn tag_call(&self, call_sid: &str, tags: &[String]) {
+        self.pending.write().unwrap()
+            .entry(call_sid.to_string())
+            .or_default()
+            .tags
+            .extend(tags.iter().cloned());
+    }
+
+    /// Add and remove tags on a recorded call or a live call tracked without a session, returning its tags
+    ///
+    /// `None` if the store knows nothing of the call.
+    pub fn update_tags(&self, call_sid: &str, add: &[String], remove: &[String]) -> Option<BTreeSet<String>> {
+        let update = |tags: &mut BTreeSet<String>| {
+            tags.extend(add.iter().cloned());
+            tags.retain(|tag| !remove.contains(tag));
+            tags.clone()
+        };
+
+        if let Some(record) = self.records.write().unwrap().0.get_mut(call_sid) {
+            return Some(update(&mut record.tags));
+        }
+        self.pending.write().unwrap().get_mut(call_sid).map(|pending| update(&mut pending.tags))
+    }
+
+    /// Records matching `query`, most recent first, skipping `offset` and returning at most `limit`
+    pub fn search(&self, query: &CallQuery, offset: usize, limit: usize) -> CallPage {
+        let guard = self.records.read().unwrap();
+        let (records, order) = &*guard;
+        let matching: Vec<&CallRecord> = order.iter()
+            .rev()
+            .filter_map(|call_sid| records.get(call_sid))
+            .filter(|record| query.matches(record))
+            .collect();
+
+        CallPage {
+            total: matching.len(),
+            calls: matching.into_iter().skip(offset).take(limit).cloned().collect(),
+            offset,
+            limit,
+        }
+    }
+
+    /// Record what a call cost, adding it to its tenant's spend for the day it ended
+    ///
+    /// A tenant already on the call's record takes precedence over `tenant_id`.
+    pub fn record_cost(&self, call_sid: &str, mut tenant_id: Option<String>, price: f64, price_unit: Option<String>) {
+        let date = {
+            let mut guard = self.records.write().unwrap();
+            match guard.0.get_mut(call_sid) {
+                Some(record) => {
+                    tenant_id = record.tenant_id.clone().or(tenant_id);
+                    record.tenant_id = tenant_id.clone();
+                    record.price = Some(price);
+                    record.price_unit = price_unit;
+                    record.ended_at.unwrap_or_else(Utc::now).date_naive()
+                },
+                None => Utc::now().date_naive(),
+            }
+        };
+
+        let mut costs = self.costs.write().unwrap();
+        let cost = costs.entry((tenant_id.clone(), date)).or_insert(DailyCost {
+            tenant_id,
+            date,
+            calls: 0,
+            total: 0.0,
+        });
+        cost.calls += 1;
+        cost.total += price;
+    }
+
+    /// A tenant's spend for a UTC day; `None` is the spend of calls without a tenant
+    pub fn tenant_cost(&self, tenant_id: Option<&str>, date: NaiveDate) -> f64 {
+        self.costs.read().unwrap()
+            .get(&(tenant_id.map(str::to_string), date))
+            .map_or(0.0, |cost| cost.total)
+    }
+
+    /// Whether a tenant's spend today has reached its daily budget, if it has one
+    ///
+    /// Tenants without their own budget, and calls without a tenant, use `DAILY_CALL_BUDGET`.
+    pub fn budget_exceeded(&self, tenant: Option<&Tenant>, costs: &CostConfig) -> bool {
+        tenant.and_then(|t| t.daily_budget)
+            .or(costs.daily_budget)
+            .is_some_and(|budget| self.tenant_cost(tenant.map(|t| t.id.as_str()), Utc::now().date_naive()) >= budget)
+    }
+
+    /// Spend by tenant and day, most recent day first
+    pub fn daily_costs(&self) -> Vec<DailyCost> {
+        let mut costs: Vec<DailyCost> = self.costs.read().unwrap().values().cloned().collect();
+
+        costs.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.tenant_id.cmp(&b.tenant_id)));
+        costs
+    }
+
+    /// Summarize hangup sources and dispositions
+    pub fn hangup_summary(&self) -> HangupSummary {
+        let guard = self.records.read().unwrap();
+        let mut by_hangup_source = HashMap::new();
+        let mut by_disposition = HashMap::new();
+
+        for record in guard.0.values() {
+            let source = record.hangup_source.map(|s| s.as_str()).unwrap_or("unknown");
+            *by_hangup_source.entry(source.to_string()).or_insert(0) += 1;
+            *by_disposition.entry(record.disposition.clone()).or_insert(0) += 1;
+        }
+
+        HangupSummary {
+            total_calls: guard.0.len(),
+            by_hangup_source,
+            by_disposition,
+        }
+    }
+}
+
+/// Start a periodic task that drops details kept for calls that never ended here
+pub fn start_cdr_cleanup_task(cdrs: Arc<CdrStore>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+        loop {
+            interval.tick().await;
+            cdrs.purge_pending();
+        }
+    });
+}
@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+/// Tracks how long an interim speech result has stayed the same
+///
+/// Twilio sends a partial callback for every interim result, and the text
+/// often flickers before it settles. Speculative generation only starts once
+/// the same normalized partial has been seen for long enough or often enough.
+#[derive(Debug, Default)]
+pub struct PartialDebouncer {
+    candidate: Option<String>,
+    first_seen: Option<Instant>,
+    seen_count: u32,
+}
+
+impl PartialDebouncer {
+    /// Record a partial result and report whether it is stable enough to act on
+    ///
+    /// A partial is stable once it has been seen in `min_count` consecutive
+    /// callbacks, or has stayed unchanged for `min_stable` (zero disables the
+    /// time check).
+    pub fn observe(&mut self, partial: &str, min_stable: Duration, min_count: u32) -> bool {
+        let normalized = normalize_partial(partial);
+        
+        if self.candidate.as_deref() != Some(normalized.as_str()) {
+            self.candidate = Some(normalized);
+            self.first_seen = Some(Instant::now());
+            self.seen_count = 0;
+        }
+        self.seen_count += 1;
+        
+        let stable_for = self.unchanged_for();
+        self.seen_count >= min_count || (!min_stable.is_zero() && stable_for >= min_stable)
+    }
+    
+    /// How long the current partial has stayed unchanged
+    pub fn unchanged_for(&self) -> Duration {
+        self.first_seen.map(|t| t.elapsed()).unwrap_or_default()
+    }
+    
+    /// Forget the current candidate, e.g. once the final transcription arrives
+    pub fn reset(&mut self) {
+        *self = PartialDebouncer::default();
+    }
+}
+
+/// Normalize a partial result so punctuation, case and spacing changes do not count as new text
+fn normalize_partial(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| !c.is_ascii_punctuation())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// What happened in a live call
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// The caller's final transcription for a turn
+    UserSaid { text: String },
+    /// The bot's reply to a turn
+    BotReplied {
+        text: Option<String>,
+        audio_url: Option<String>,
+    },
+    /// Session flags after a turn
+    State {
+        generation: bool,
+        session_ends: bool,
+        turn_count: u32,
+        persona: Option<String>,
+        queue: Option<String>,
+    },
+    /// The backend reported new caller sentiment or escalation risk
+    Signals {
+        sentiment: Option<String>,
+        escalation_risk: Option<f64>,
+    },
+    /// The session was removed from the store
+    Ended,
+}
+
+impl SessionEventKind {
+    /// Event name used in streams
+    pub fn name(&self) -> &'static str {
+        match self {
+            SessionEventKind::UserSaid { .. } => "user_said",
+            SessionEventKind::BotReplied { .. } => "bot_replied",
+            SessionEventKind::State { .. } => "state",
+            SessionEventKind::Signals { .. } => "signals",
+            SessionEventKind::Ended => "ended",
+        }
+    }
+}
+
+/// Turn event published on the session event bus
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    pub session_id: String,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+impl SessionEvent {
+    /// Create an event for a session, timestamped now
+    pub fn new(session_id: &str, kind: SessionEventKind) -> Self {
+        SessionEvent {
+            session_id: session_id.to_string(),
+            at: Utc::now(),
+            kind,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::config::{ExperimentConfig, ExperimentVariant};
+
+/// Session metadata key holding the call's experiment assignment
+pub const EXPERIMENT_METADATA_KEY: &str = "experiment";
+
+impl ExperimentConfig {
+    /// Pick a variant at random by weight; `None` without an experiment
+    pub fn assign(&self) -> Option<&ExperimentVariant> {
+        let total: u64 = self.variants.iter().map(|v| v.weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+
+        // Uniform draw in [0, total) from a random UUID
+        let mut draw = (Uuid::new_v4().as_u128() % total as u128) as u64;
+        for variant in &self.variants {
+            if draw < variant.weight as u64 {
+                return Some(variant);
+            }
+            draw -= variant.weight as u64;
+        }
+        None
+    }
+
+    /// Metadata recording an assignment to `variant`
+    pub fn assignment(&self, variant: &ExperimentVariant) -> Value {
+        serde_json::json!({
+            "name": self.name,
+            "variant": variant.name,
+        })
+    }
+}
+
+/// Experiment and variant a session was assigned, from its metadata
+pub fn assigned_variant(metadata: &HashMap<String, Value>) -> Option<(&str, &str)> {
+    let assignment = metadata.get(EXPERIMENT_METADATA_KEY)?;
+    Some((assignment.get("name")?.as_str()?, assignment.get("variant")?.as_str()?))
+}
+
+/// Outcomes of the calls assigned one variant
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VariantSummary {
+    /// Calls assigned the variant
+    pub assigned: u64,
+    /// Assigned calls that have ended
+    pub ended: u64,
+    /// Ended calls that connected and completed
+    pub completed: u64,
+    pub average_turns: Option<f64>,
+    pub average_duration_seconds: Option<f64>,
+    /// Ended calls by disposition (e.g. `completed`, `no-answer`, `voicemail_left`)
+    pub dispositions: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct VariantTotals {
+    assigned: u64,
+    ended: u64,
+    completed: u64,
+    turns: u64,
+    durations: u64,
+    duration_seconds: u64,
+    dispositions: HashMap<String, u64>,
+}
+
+/// Process-wide outcomes per experiment variant
+#[derive(Default)]
+pub struct ExperimentMetrics {
+    /// Totals keyed by experiment and variant name
+    variants: Mutex<HashMap<(String, String), VariantTotals>>,
+}
+
+impl ExperimentMetrics {
+    /// Count a call assigned to a variant
+    pub fn record_assignment(&self, experiment: &str, variant: &str) {
+        let key = (experiment.to_string(), variant.to_string());
+        self.variants.lock().unwrap().entry(key).or_default().assigned += 1;
+    }
+
+    /// Count how an assigned call ended
+    pub fn record_call_ended(
+        &self,
+        experiment: &str,
+        variant: &str,
+        status: &str,
+        disposition: &str,
+        turns: u32,
+        duration_seconds: Option<u64>,
+    ) {
+        let mut variants = self.variants.lock().unwrap();
+        let totals = variants.entry((experiment.to_string(), variant.to_string())).or_default();
+        totals.ended += 1;
+        if status == "completed" {
+            totals.completed += 1;
+        }
+        totals.turns += turns as u64;
+        if let Some(duration) = duration_seconds {
+            totals.durations += 1;
+            totals.duration_seconds += duration;
+        }
+        *totals.dispositions.entry(disposition.to_string()).or_default() += 1;
+    }
+
+    /// Summarize every variant seen since the process started, by experiment
+    pub fn summary(&self) -> HashMap<String, HashMap<String, VariantSummary>> {
+        let average = |total: u64, count: u64| (count > 0).then(|| total as f64 / count as f64);
+
+        let mut experiments: HashMap<String, HashMap<String, VariantSummary>> = HashMap::new();
+        for ((experiment, variant), totals) in self.variants.lock().unwrap().iter() {
+            experiments.entry(experiment.clone()).or_default().insert(variant.clone(), VariantSummary {
+                assigned: totals.assigned,
+                ended: totals.ended,
+                completed: totals.completed,
+                average_turns: average(totals.turns, totals.ended),
+                average_duration_seconds: average(totals.duration_seconds, totals.durations),
+                dispositions: totals.dispositions.clone(),
+            });
+        }
+        experiments
+    }
+}
+
+/// Experiment metrics for the whole process
+pub fn experiment_metrics() -> &'static ExperimentMetrics {
+    static METRICS: OnceLock<ExperimentMetrics> = OnceLock::new();
+    METRICS.get_or_init(ExperimentMetrics::default)
+}
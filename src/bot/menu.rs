@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+/// A structured IVR menu returned by the backend
+///
+/// ```json
+/// {
+///   "prompt": "For billing press 1 or say billing. For support press 2.",
+///   "options": [
+///     {"key": "1", "phrases": ["billing", "payments"], "value": "billing"},
+///     {"key": "2", "phrases": ["support", "help"], "value": "support"}
+///   ],
+///   "timeout": 5,
+///   "timeout_action": "repeat"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Menu {
+    /// Prompt spoken while gathering the selection
+    pub prompt: String,
+    /// Selectable options
+    pub options: Vec<MenuOption>,
+    /// Seconds to wait for a selection
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    /// What to do when the caller makes no selection
+    #[serde(default)]
+    pub timeout_action: MenuTimeoutAction,
+}
+
+/// A single selectable menu option
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuOption {
+    /// DTMF key(s) selecting this option
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Spoken phrases selecting this option
+    #[serde(default)]
+    pub phrases: Vec<String>,
+    /// Value reported to the backend when selected
+    pub value: String,
+}
+
+/// Action taken when a menu Gather times out without input
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MenuTimeoutAction {
+    /// Play the menu again
+    #[default]
+    Repeat,
+    /// End the call
+    Hangup,
+    /// Select the option with the given value
+    Select(String),
+}
+
+/// How the caller made a menu selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionInput {
+    Dtmf,
+    Speech,
+    Timeout,
+}
+
+/// Spoken digit words recognized as DTMF equivalents
+const DIGIT_WORDS: &[(&str, &str)] = &[
+    ("zero", "0"), ("one", "1"), ("two", "2"), ("three", "3"), ("four", "4"),
+    ("five", "5"), ("six", "6"), ("seven", "7"), ("eight", "8"), ("nine", "9"),
+];
+
+impl Menu {
+    /// Number of digits to gather, if every option has a key of the same length
+    pub fn num_digits(&self) -> Option<u32> {
+        let mut lengths = self.options.iter().filter_map(|o| o.key.as_ref()).map(|k| k.len());
+        let first = lengths.next()?;
+        if lengths.all(|len| len == first) {
+            Some(first as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Comma-separated speech hints built from the option phrases
+    pub fn hints(&self) -> String {
+        self.options
+            .iter()
+            .flat_map(|o| o.phrases.iter())
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Find an option by its value
+    pub fn option_by_value(&self, value: &str) -> Option<&MenuOption> {
+        self.options.iter().find(|o| o.value == value)
+    }
+
+    /// Match caller input (DTMF digits take precedence over speech) to an option
+    pub fn match_selection(&self, digits: Option<&str>, speech: Option<&str>) -> Option<(&MenuOption, SelectionInput)> {
+        if let Some(digits) = digits.map(str::trim).filter(|d| !d.is_empty()) {
+            return self.options
+                .iter()
+                .find(|o| o.key.as_deref() == Some(digits))
+                .map(|o| (o, SelectionInput::Dtmf));
+        }
+
+        let speech = normalize(speech?);
+        if speech.is_empty() {
+            return None;
+        }
+
+        // A spoken digit ("two", "2") selects by key
+        let spoken_key = DIGIT_WORDS
+            .iter()
+            .find(|(word, digit)| speech == *word || speech == *digit)
+            .map(|(_, digit)| *digit);
+        if let Some(key) = spoken_key {
+            if let Some(option) = self.options.iter().find(|o| o.key.as_deref() == Some(key)) {
+                return Some((option, SelectionInput::Speech));
+            }
+        }
+
+        self.options
+            .iter()
+            .find(|o| {
+                normalize(&o.value) == speech
+                    || o.phrases.iter().any(|p| {
+                        let phrase = normalize(p);
+                        !phrase.is_empty() && contains_phrase(&speech, &phrase)
+                    })
+            })
+            .map(|o| (o, SelectionInput::Speech))
+    }
+}
+
+/// Lowercase, strip punctuation, and collapse whitespace
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check whether a normalized phrase occurs in normalized speech on word boundaries
+fn contains_phrase(speech: &str, phrase: &str) -> bool {
+    format!(" {} ", speech).contains(&format!(" {} ", phrase))
+}
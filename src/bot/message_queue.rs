@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use log::{info, warn};
+use rocket::tokio::sync::mpsc::{channel, Receiver, Sender};
+use rocket::tokio::sync::mpsc::error::SendTimeoutError;
+use serde::Serialize;
+
+use crate::bot::session::MessageType;
+use crate::cluster::SessionCluster;
+
+/// Messages held in the channel before senders wait for room
+pub const MESSAGE_QUEUE_CAPACITY: usize = 100;
+
+/// How long a sender waits for room in a full channel before spilling
+const SPILL_AFTER: Duration = Duration::from_millis(500);
+
+/// Counters describing a session's message queue
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStats {
+    /// Messages waiting to be spoken, including spilled ones
+    pub depth: usize,
+    /// Messages the channel holds before spilling
+    pub capacity: usize,
+    /// Messages waiting in the overflow buffer
+    pub overflow: usize,
+    /// Messages that spilled into the overflow buffer since the session started
+    pub spilled_total: u64,
+    /// Largest depth the queue reached
+    pub max_depth: usize,
+}
+
+/// State shared by both ends of a queue
+struct Shared {
+    /// Messages that did not fit in the channel, in send order
+    overflow: Mutex<VecDeque<MessageType>>,
+    spilled_total: AtomicU64,
+    max_depth: AtomicUsize,
+    /// Whether the queue has spilled since it was last drained, to log once per episode
+    spilling: AtomicBool,
+    /// Cluster queue messages go to instead, with the session's ID
+    cluster: OnceLock<(Arc<SessionCluster>, String)>,
+}
+
+/// Create a session message queue
+///
+/// The queue is a bounded channel backed by an unbounded overflow buffer:
+/// senders wait briefly for room and then spill instead of dropping messages,
+/// so long streamed answers are never truncated. Messages from one sender
+/// are received in the order they were sent.
+pub fn message_queue(capacity: usize) -> (MessageSender, MessageReceiver) {
+    let (tx, rx) = channel(capacity);
+    let shared = Arc::new(Shared {
+        overflow: Mutex::new(VecDeque::new()),
+        spilled_total: AtomicU64::new(0),
+        max_depth: AtomicUsize::new(0),
+        spilling: AtomicBool::new(false),
+        cluster: OnceLock::new(),
+    });
+
+    (
+        MessageSender { tx, shared: shared.clone() },
+        MessageReceiver { rx, shared },
+    )
+}
+
+/// Sending end of a session message queue
+#[derive(Clone)]
+pub struct MessageSender {
+    tx: Sender<MessageType>,
+    shared: Arc<Shared>,
+}
+
+impl MessageSender {
+    /// Send messages to the session's queue in the cluster from now on
+    ///
+    /// The replica answering the call's next queue callback takes them with
+    /// `SessionStore::take_shared_messages`. Depth and stats then only count
+    /// messages queued locally.
+    pub fn share(&self, cluster: Arc<SessionCluster>, session_id: &str) {
+        let _ = self.shared.cluster.set((cluster, session_id.to_string()));
+    }
+
+    /// Queue a message, waiting for room before spilling into the overflow buffer
+    ///
+    /// Returns the message if the session's receiver is gone.
+    pub async fn send(&self, message: MessageType) -> Result<(), MessageType> {
+        if let Some((cluster, session_id)) = self.shared.cluster.get() {
+            match cluster.push_message(session_id, &message).await {
+                Ok(()) => return Ok(()),
+                Err(e) => warn!("Failed to share message for session {}, queueing it locally: {}", session_id, e),
+            }
+        }
+        
+        // Once messages have spilled, later ones follow them to keep their order
+        let spilled = !self.shared.overflow.lock().unwrap().is_empty();
+        let message = if spilled {
+            message
+        } else {
+            match self.tx.send_timeout(message, SPILL_AFTER).await {
+                Ok(()) => {
+                    self.record_depth();
+                    return Ok(());
+                },
+                Err(SendTimeoutError::Timeout(message)) => message,
+                Err(SendTimeoutError::Closed(message)) => return Err(message),
+            }
+        };
+
+        if self.tx.is_closed() {
+            return Err(message);
+        }
+
+        self.shared.overflow.lock().unwrap().push_back(message);
+        self.shared.spilled_total.fetch_add(1, Ordering::Relaxed);
+        if !self.shared.spilling.swap(true, Ordering::Relaxed) {
+            warn!("Message queue is full; spilling messages into the overflow buffer");
+        }
+        self.record_depth();
+        Ok(())
+    }
+
+    /// Messages waiting to be received, including spilled ones
+    pub fn depth(&self) -> usize {
+        let overflow = self.shared.overflow.lock().unwrap().len();
+        self.tx.max_capacity() - self.tx.capacity() + overflow
+    }
+
+    /// Current counters of the queue
+    pub fn stats(&self) -> QueueStats {
+        let overflow = self.shared.overflow.lock().unwrap().len();
+        QueueStats {
+            depth: self.tx.max_capacity() - self.tx.capacity() + overflow,
+            capacity: self.tx.max_capacity(),
+            overflow,
+            spilled_total: self.shared.spilled_total.load(Ordering::Relaxed),
+            max_depth: self.shared.max_depth.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_depth(&self) {
+        self.shared.max_depth.fetch_max(self.depth(), Ordering::Relaxed);
+    }
+}
+
+/// Receiving end of a session message queue
+pub struct MessageReceiver {
+    rx: Receiver<MessageType>,
+    shared: Arc<Shared>,
+}
+
+impl MessageReceiver {
+    /// Take every queued message, oldest first
+    pub fn drain(&mut self) -> Vec<MessageType> {
+        // Hold the overflow lock so no sender spills between the two reads
+        let mut overflow = self.shared.overflow.lock().unwrap();
+
+        let mut messages = Vec::new();
+        while let Ok(message) = self.rx.try_recv() {
+            messages.push(message);
+        }
+        if !overflow.is_empty() {
+            info!("Delivering {} spilled messages from the overflow buffer", overflow.len());
+            messages.extend(overflow.drain(..));
+        }
+
+        self.shared.spilling.store(false, Ordering::Relaxed);
+        messages
+    }
+}
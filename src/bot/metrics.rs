@@ -0,0 +1,178 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use serde::Serialize;
+
+/// Speech recognition and turnaround counters, for one call or the whole process
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpeechSummary {
+    /// Final transcriptions received
+    pub utterances: u64,
+    pub average_words: Option<f64>,
+    /// Average of the confidences Twilio reported; transcriptions without one are left out
+    pub average_confidence: Option<f64>,
+    /// Runs started speculatively from a partial result
+    pub speculative_started: u64,
+    /// Speculative runs whose partial matched the final transcription
+    pub speculative_committed: u64,
+    /// Speculative runs discarded because the final transcription differed
+    pub speculative_wasted: u64,
+    /// Share of resolved speculative runs that were committed
+    pub speculative_commit_rate: Option<f64>,
+    /// Time from a final transcription to the first reply handed to Twilio
+    pub average_turnaround_ms: Option<f64>,
+    pub max_turnaround_ms: Option<u64>,
+}
+
+/// Counter totals shared by the per-call and process-wide metrics
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    utterances: u64,
+    words: u64,
+    confidence_count: u64,
+    /// Sum of confidences in millionths
+    confidence_micros: u64,
+    speculative_started: u64,
+    speculative_committed: u64,
+    speculative_wasted: u64,
+    turnarounds: u64,
+    turnaround_ms: u64,
+    max_turnaround_ms: u64,
+}
+
+impl Totals {
+    fn summary(&self) -> SpeechSummary {
+        let average = |total: u64, count: u64| (count > 0).then(|| total as f64 / count as f64);
+        let resolved = self.speculative_committed + self.speculative_wasted;
+
+        SpeechSummary {
+            utterances: self.utterances,
+            average_words: average(self.words, self.utterances),
+            average_confidence: average(self.confidence_micros, self.confidence_count).map(|c| c / 1_000_000.0),
+            speculative_started: self.speculative_started,
+            speculative_committed: self.speculative_committed,
+            speculative_wasted: self.speculative_wasted,
+            speculative_commit_rate: average(self.speculative_committed, resolved),
+            average_turnaround_ms: average(self.turnaround_ms, self.turnarounds),
+            max_turnaround_ms: (self.turnarounds > 0).then_some(self.max_turnaround_ms),
+        }
+    }
+}
+
+/// Process-wide speech metrics, updated alongside every call's stats
+#[derive(Default)]
+pub struct SpeechMetrics {
+    utterances: AtomicU64,
+    words: AtomicU64,
+    confidence_count: AtomicU64,
+    confidence_micros: AtomicU64,
+    speculative_started: AtomicU64,
+    speculative_committed: AtomicU64,
+    speculative_wasted: AtomicU64,
+    turnarounds: AtomicU64,
+    turnaround_ms: AtomicU64,
+    max_turnaround_ms: AtomicU64,
+}
+
+impl SpeechMetrics {
+    /// Summarize all calls handled since the process started
+    pub fn summary(&self) -> SpeechSummary {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Totals {
+            utterances: load(&self.utterances),
+            words: load(&self.words),
+            confidence_count: load(&self.confidence_count),
+            confidence_micros: load(&self.confidence_micros),
+            speculative_started: load(&self.speculative_started),
+            speculative_committed: load(&self.speculative_committed),
+            speculative_wasted: load(&self.speculative_wasted),
+            turnarounds: load(&self.turnarounds),
+            turnaround_ms: load(&self.turnaround_ms),
+            max_turnaround_ms: load(&self.max_turnaround_ms),
+        }.summary()
+    }
+}
+
+/// Speech metrics for the whole process
+pub fn speech_metrics() -> &'static SpeechMetrics {
+    static METRICS: OnceLock<SpeechMetrics> = OnceLock::new();
+    METRICS.get_or_init(SpeechMetrics::default)
+}
+
+/// Speech recognition stats of one call
+#[derive(Debug, Clone, Default)]
+pub struct SpeechStats {
+    totals: Totals,
+    /// Whether a speculative run was started and not yet matched against a final transcription
+    speculation_pending: bool,
+    /// When the final transcription of the turn awaiting a reply arrived
+    turn_started: Option<Instant>,
+}
+
+impl SpeechStats {
+    /// Record a final transcription and the confidence Twilio reported for it
+    ///
+    /// A confidence of 0 means the speech model did not report one.
+    pub fn record_utterance(&mut self, text: &str, confidence: Option<f64>) {
+        let metrics = speech_metrics();
+        let words = text.split_whitespace().count() as u64;
+
+        self.totals.utterances += 1;
+        self.totals.words += words;
+        metrics.utterances.fetch_add(1, Ordering::Relaxed);
+        metrics.words.fetch_add(words, Ordering::Relaxed);
+
+        if let Some(confidence) = confidence.filter(|c| *c > 0.0) {
+            let micros = (confidence.clamp(0.0, 1.0) * 1_000_000.0).round() as u64;
+            self.totals.confidence_count += 1;
+            self.totals.confidence_micros += micros;
+            metrics.confidence_count.fetch_add(1, Ordering::Relaxed);
+            metrics.confidence_micros.fetch_add(micros, Ordering::Relaxed);
+        }
+
+        self.turn_started = Some(Instant::now());
+    }
+
+    /// Record a run started speculatively from a partial result
+    pub fn record_speculation_started(&mut self) {
+        self.totals.speculative_started += 1;
+        self.speculation_pending = true;
+        speech_metrics().speculative_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Resolve the pending speculative run, if any, as committed or wasted
+    pub fn resolve_speculation(&mut self, committed: bool) {
+        if !std::mem::take(&mut self.speculation_pending) {
+            return;
+        }
+        let metrics = speech_metrics();
+        if committed {
+            self.totals.speculative_committed += 1;
+            metrics.speculative_committed.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.totals.speculative_wasted += 1;
+            metrics.speculative_wasted.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record the turnaround of the current turn once its first reply is handed to Twilio
+    pub fn record_reply(&mut self) {
+        let Some(started) = self.turn_started.take() else {
+            return;
+        };
+        let metrics = speech_metrics();
+        let elapsed = started.elapsed().as_millis() as u64;
+
+        self.totals.turnarounds += 1;
+        self.totals.turnaround_ms += elapsed;
+        self.totals.max_turnaround_ms = self.totals.max_turnaround_ms.max(elapsed);
+        metrics.turnarounds.fetch_add(1, Ordering::Relaxed);
+        metrics.turnaround_ms.fetch_add(elapsed, Ordering::Relaxed);
+        metrics.max_turnaround_ms.fetch_max(elapsed, Ordering::Relaxed);
+    }
+
+    /// Summarize the call so far
+    pub fn summary(&self) -> SpeechSummary {
+        self.totals.summary()
+    }
+}
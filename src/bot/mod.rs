@@ -1,3 +1,26 @@
 pub mod session;
+pub mod callflow;
 pub mod ws_client;
 pub mod backend;
+pub mod pacing;
+pub mod debounce;
+pub mod menu;
+pub mod audio;
+pub mod cdr;
+pub mod result_callback;
+pub mod recording;
+pub mod events;
+pub mod screening;
+pub mod business_hours;
+pub mod experiments;
+pub mod postprocess;
+pub mod verbalize;
+pub mod redaction;
+pub mod analytics_tap;
+pub mod message_queue;
+pub mod metrics;
+pub mod payload;
+pub mod caller_history;
+pub mod speech;
+pub mod stt;
+pub mod tts;
@@ -0,0 +1,122 @@
+use serde_json::Value;
+
+use crate::bot::payload::RunMetadata;
+use crate::config::TwilioConfig;
+
+/// Kind of answer the bot expects from the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnswerKind {
+    /// A yes/no confirmation
+    YesNo,
+    /// A short factual answer (name, number, choice)
+    Short,
+    /// A free-form, open-ended answer
+    Open,
+}
+
+impl AnswerKind {
+    /// Parse an answer kind from a backend directive value
+    pub fn from_directive(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "yes_no" | "yesno" | "confirm" => Some(AnswerKind::YesNo),
+            "short" => Some(AnswerKind::Short),
+            "open" | "open_ended" => Some(AnswerKind::Open),
+            _ => None,
+        }
+    }
+}
+
+/// Gather timing parameters for a single turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatherTiming {
+    pub timeout: u32,
+    pub speech_timeout: String,
+}
+
+impl GatherTiming {
+    /// Timing used when adaptive pacing is disabled
+    pub fn default_for(config: &TwilioConfig) -> Self {
+        GatherTiming {
+            timeout: config.default_timeout,
+            speech_timeout: "auto".to_string(),
+        }
+    }
+}
+
+/// Words that typically start a yes/no question
+const YES_NO_OPENERS: &[&str] = &[
+    "is", "are", "am", "was", "were", "do", "does", "did", "can", "could",
+    "will", "would", "shall", "should", "have", "has", "had", "may",
+];
+
+/// Words that typically start an open-ended question
+const OPEN_OPENERS: &[&str] = &["why", "how", "describe", "tell me", "explain", "what happened"];
+
+/// Classify the question the bot is asking by its last sentence
+pub fn classify_question(text: &str) -> AnswerKind {
+    let trimmed = text.trim();
+    let last_sentence = trimmed
+        .trim_end_matches(['?', '.', '!'])
+        .rsplit(['.', '!', '?'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+
+    if OPEN_OPENERS.iter().any(|opener| last_sentence.starts_with(opener)) {
+        return AnswerKind::Open;
+    }
+
+    if trimmed.ends_with('?') {
+        let first_word = last_sentence.split_whitespace().next().unwrap_or("");
+        if YES_NO_OPENERS.contains(&first_word) || last_sentence.ends_with(" or not") {
+            return AnswerKind::YesNo;
+        }
+        return AnswerKind::Short;
+    }
+
+    AnswerKind::Open
+}
+
+/// Choose Gather timing for a turn from backend directives, falling back to heuristics
+///
+/// Recognized metadata directives are `gather_timeout` (seconds), `speech_timeout`
+/// (seconds or `"auto"`) and `expected_answer` (`yes_no`, `short`, `open`).
+pub fn gather_timing(text: &str, metadata: Option<&RunMetadata>, config: &TwilioConfig) -> GatherTiming {
+    let mut timing = GatherTiming::default_for(config);
+
+    let directive_kind = metadata
+        .and_then(|m| m.expected_answer.as_deref())
+        .and_then(AnswerKind::from_directive);
+
+    if config.adaptive_timeout || directive_kind.is_some() {
+        let kind = directive_kind.unwrap_or_else(|| classify_question(text));
+        timing = match kind {
+            AnswerKind::YesNo => GatherTiming {
+                timeout: config.short_answer_timeout,
+                speech_timeout: "1".to_string(),
+            },
+            AnswerKind::Short => GatherTiming::default_for(config),
+            AnswerKind::Open => GatherTiming {
+                timeout: config.open_answer_timeout,
+                speech_timeout: config.open_answer_speech_timeout.clone(),
+            },
+        };
+    }
+
+    if let Some(metadata) = metadata {
+        if let Some(timeout) = metadata.gather_timeout {
+            if timeout > 0 {
+                timing.timeout = timeout as u32;
+            }
+        }
+
+        match &metadata.speech_timeout {
+            Some(Value::String(s)) if !s.is_empty() => timing.speech_timeout = s.clone(),
+            Some(Value::Number(n)) => timing.speech_timeout = n.to_string(),
+            _ => {}
+        }
+    }
+
+    timing
+}
@@ -0,0 +1,316 @@
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+use crate::bot::menu::Menu;
+
+/// Result of a backend run
+///
+/// ```json
+/// {
+///   "response": "Your order ships tomorrow. Anything else?",
+///   "metadata": {"expected_answer": "yes_no", "SESSION_ENDS": false},
+///   "persona": "friendly",
+///   "speech_hints": ["yes", "no"]
+/// }
+/// ```
+///
+/// Only the reply and the directives that end or hand off the call must be
+/// well-formed. A malformed advisory field, such as a persona or speech hints,
+/// is logged and ignored so the turn is still answered.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunResponse {
+    /// Text to speak: plain text, a `<speak>` SSML document, or `Code:` followed by DTMF digits
+    #[serde(default)]
+    pub response: Option<String>,
+    /// Pre-rendered audio played instead of the text
+    #[serde(default)]
+    pub audio_url: Option<String>,
+    #[serde(default)]
+    pub metadata: RunMetadata,
+    /// Structured IVR menu to present instead of a free-form turn
+    #[serde(default)]
+    pub menu: Option<Menu>,
+    /// Voice persona to switch to; null returns to the default voice
+    #[serde(default, deserialize_with = "lenient_nullable")]
+    pub persona: Option<Option<String>>,
+    /// Catalog voice to switch to; null returns to the persona's or default voice
+    #[serde(default, deserialize_with = "lenient_nullable")]
+    pub voice: Option<Option<String>>,
+    /// Caller language detected by the backend
+    #[serde(default, deserialize_with = "lenient")]
+    pub detected_language: Option<String>,
+    /// Speech model to switch to; null returns to the default
+    #[serde(default, deserialize_with = "lenient_nullable")]
+    pub speech_model: Option<Option<String>>,
+    /// Enhanced speech recognition setting; null returns to the default
+    #[serde(default, deserialize_with = "lenient_nullable")]
+    pub enhanced: Option<Option<bool>>,
+    /// Vocabulary expected in the caller's next answer
+    #[serde(default, deserialize_with = "lenient")]
+    pub speech_hints: Option<SpeechHints>,
+}
+
+/// Directives in a run result's metadata
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunMetadata {
+    /// End the call after this response
+    #[serde(rename = "SESSION_ENDS", default)]
+    pub session_ends: bool,
+    /// Place the caller in an agent queue
+    #[serde(rename = "ENQUEUE", default)]
+    pub enqueue: Option<EnqueueRequest>,
+    /// Transfer the caller to a number or SIP address
+    #[serde(rename = "TRANSFER", default)]
+    pub transfer: Option<String>,
+    /// Dial a third party into the call
+    #[serde(rename = "ESCALATE", default)]
+    pub escalate: Option<EscalationRequest>,
+    /// Text the caller after the turn
+    #[serde(rename = "SEND_SMS", default, deserialize_with = "lenient")]
+    pub send_sms: Option<SmsRequest>,
+    /// Tags to add to the call, for searching calls later
+    #[serde(rename = "TAGS", default, deserialize_with = "lenient")]
+    pub tags: Vec<String>,
+    /// Kind of answer expected next: `yes_no`, `short` or `open`
+    #[serde(default, deserialize_with = "lenient")]
+    pub expected_answer: Option<String>,
+    /// Seconds to wait for the caller to start speaking
+    #[serde(default, deserialize_with = "lenient")]
+    pub gather_timeout: Option<u64>,
+    /// Seconds of silence ending the caller's speech, or `"auto"`
+    #[serde(default, deserialize_with = "lenient")]
+    pub speech_timeout: Option<Value>,
+    /// Caller's mood as judged by the backend, e.g. `negative`
+    #[serde(default, deserialize_with = "lenient")]
+    pub sentiment: Option<String>,
+    /// Likelihood (0 to 1) that the caller needs a human
+    #[serde(default, deserialize_with = "lenient")]
+    pub escalation_risk: Option<f64>,
+    /// Keys the service does not act on
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Agent queue requested with `ENQUEUE`: a queue name, or true for the default queue
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EnqueueRequest {
+    Queue(String),
+    Default(bool),
+}
+
+/// Escalation requested with `ESCALATE`: a number or SIP address, or a target with context
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EscalationRequest {
+    Target(String),
+    Detailed {
+        target: String,
+        /// Summary whispered to the escalation party before they join
+        #[serde(default)]
+        context: Option<String>,
+    },
+}
+
+/// Follow-up SMS requested with `SEND_SMS`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmsRequest {
+    pub to: String,
+    pub body: String,
+}
+
+/// Speech hints as a list or a comma-separated string
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SpeechHints {
+    List(Vec<String>),
+    Text(String),
+}
+
+impl SpeechHints {
+    /// Hints as a comma-separated string
+    pub fn joined(&self) -> String {
+        match self {
+            SpeechHints::List(hints) => hints.join(","),
+            SpeechHints::Text(hints) => hints.clone(),
+        }
+    }
+}
+
+/// Where the caller is handed off to instead of continuing with the bot
+#[derive(Debug, Clone, PartialEq)]
+pub enum Handoff<'a> {
+    /// Agent queue; `None` for the default queue
+    Queue(Option<&'a str>),
+    /// Number or SIP address
+    Number(&'a str),
+    /// Third party dialed into the call
+    Escalation { target: &'a str, context: Option<&'a str> },
+}
+
+/// How the bot answers a run, in order of precedence
+#[derive(Debug, Clone)]
+pub enum ResponseKind<'a> {
+    /// Hand the caller off, announcing it with any response text
+    Transfer(Handoff<'a>),
+    /// Speak any response text or audio and hang up
+    End,
+    /// Present a structured menu, prefaced by any response text
+    Menu(&'a Menu),
+    /// Play pre-rendered audio
+    AudioUrl(&'a str),
+    /// Play DTMF digits
+    Dtmf(&'a str),
+    /// Speak an SSML document
+    Ssml(&'a str),
+    /// Speak plain text
+    Text(&'a str),
+    /// The run produced nothing to say
+    Empty,
+}
+
+impl RunResponse {
+    /// Where the caller is handed off to, if anywhere
+    ///
+    /// Requests with an empty queue name or target are ignored.
+    pub fn handoff(&self) -> Option<Handoff<'_>> {
+        let metadata = &self.metadata;
+        match &metadata.enqueue {
+            Some(EnqueueRequest::Queue(name)) if !name.is_empty() => return Some(Handoff::Queue(Some(name))),
+            Some(EnqueueRequest::Default(true)) => return Some(Handoff::Queue(None)),
+            _ => {},
+        }
+        if let Some(target) = metadata.transfer.as_deref().filter(|t| !t.is_empty()) {
+            return Some(Handoff::Number(target));
+        }
+
+        let (target, context) = match metadata.escalate.as_ref()? {
+            EscalationRequest::Target(target) => (target, None),
+            EscalationRequest::Detailed { target, context } => (target, context.as_deref()),
+        };
+        Some(target.trim())
+            .filter(|t| !t.is_empty())
+            .map(|target| Handoff::Escalation { target, context })
+    }
+
+    /// Whether the call ends after this response
+    ///
+    /// A handoff takes precedence over ending the call.
+    pub fn ends_session(&self) -> bool {
+        self.metadata.session_ends && self.handoff().is_none()
+    }
+
+    /// Pre-rendered audio URL, if set
+    pub fn audio(&self) -> Option<&str> {
+        self.audio_url.as_deref().filter(|u| !u.is_empty())
+    }
+
+    /// Whether the run is answered now rather than streamed through the message queue
+    pub fn has_reply(&self) -> bool {
+        self.audio().is_some() || self.response.as_deref().is_some_and(|r| !r.trim().is_empty())
+    }
+
+    /// Classify the response
+    pub fn kind(&self) -> ResponseKind<'_> {
+        if let Some(handoff) = self.handoff() {
+            return ResponseKind::Transfer(handoff);
+        }
+        if self.ends_session() {
+            return ResponseKind::End;
+        }
+        if let Some(menu) = &self.menu {
+            return ResponseKind::Menu(menu);
+        }
+        if let Some(audio_url) = self.audio() {
+            return ResponseKind::AudioUrl(audio_url);
+        }
+
+        let Some(response) = self.response.as_deref() else {
+            return ResponseKind::Empty;
+        };
+        if let Some(code) = response.strip_prefix("Code:") {
+            return ResponseKind::Dtmf(code.trim());
+        }
+        let trimmed = response.trim();
+        if trimmed.starts_with("<speak>") && trimmed.ends_with("</speak>") {
+            return ResponseKind::Ssml(response);
+        }
+        ResponseKind::Text(response)
+    }
+}
+
+/// Deserialize an advisory field, falling back to its default when malformed
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(T::deserialize(&value).unwrap_or_else(|e| {
+        warn!("Ignoring malformed value {} in run result: {}", value, e);
+        T::default()
+    }))
+}
+
+/// Deserialize an advisory field that distinguishes null from absent
+///
+/// Absent fields default to `None`; null becomes `Some(None)`. A malformed
+/// value is treated as absent, leaving the current setting unchanged.
+fn lenient_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(Some(None));
+    }
+    match T::deserialize(&value) {
+        Ok(value) => Ok(Some(Some(value))),
+        Err(e) => {
+            warn!("Ignoring malformed value {} in run result: {}", value, e);
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ignores_malformed_advisory_fields() {
+        let result: RunResponse = serde_json::from_value(json!({
+            "response": "Anything else?",
+            "persona": 7,
+            "speech_hints": {"words": ["yes"]},
+            "metadata": {"TAGS": "vip", "gather_timeout": "soon", "SEND_SMS": {"to": "+15550001111"}},
+        })).unwrap();
+
+        assert_eq!(result.response.as_deref(), Some("Anything else?"));
+        assert!(result.persona.is_none());
+        assert!(result.speech_hints.is_none());
+        assert!(result.metadata.tags.is_empty());
+        assert!(result.metadata.gather_timeout.is_none());
+        assert!(result.metadata.send_sms.is_none());
+    }
+
+    #[test]
+    fn keeps_null_resets() {
+        let result: RunResponse = serde_json::from_value(json!({"voice": null, "enhanced": true})).unwrap();
+
+        assert_eq!(result.voice, Some(None));
+        assert_eq!(result.enhanced, Some(Some(true)));
+        assert!(result.persona.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_directives() {
+        let result = serde_json::from_value::<RunResponse>(json!({"metadata": {"SESSION_ENDS": "yes"}}));
+
+        assert!(result.is_err());
+    }
+}
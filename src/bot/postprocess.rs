@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use regex::Regex;
+
+use crate::bot::verbalize::verbalize;
+use crate::config::{PostprocessConfig, RedactionConfig, ResponseProcessor};
+use crate::twilio::twiml::SAY_CHUNK_BREAK;
+
+/// Largest number spelled out by the number processor; longer digit runs are read as digits
+pub const MAX_SPOKEN_NUMBER: u64 = 999_999_999;
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+/// Apply the configured processors, in order, to backend text before it is spoken
+///
+/// Chunks left by the split processor are joined with `SAY_CHUNK_BREAK`.
+/// SSML documents are only redacted, in their text between tags. `language` is
+/// the language the text is spoken in, as a Twilio language code.
+pub fn process(text: &str, config: &PostprocessConfig, redaction: &RedactionConfig, language: Option<&str>) -> String {
+    if text.trim_start().starts_with("<speak>") {
+        return match config.processors.contains(&ResponseProcessor::Redact) {
+            true => redact_ssml(text, redaction, &config.redaction),
+            false => text.to_string(),
+        };
+    }
+    if config.processors.is_empty() {
+        return text.to_string();
+    }
+
+    let mut chunks = vec![text.to_string()];
+    for processor in &config.processors {
+        chunks = match processor {
+            ResponseProcessor::Split => chunks.iter()
+                .flat_map(|chunk| split_chunks(chunk, config.max_chunk_chars))
+                .collect(),
+            ResponseProcessor::StripMarkdown => chunks.iter().map(|c| strip_markdown(c)).collect(),
+            ResponseProcessor::ExpandAbbreviations => chunks.iter()
+                .map(|c| expand_abbreviations(c, &config.abbreviations))
+                .collect(),
+            ResponseProcessor::ExpandNumbers => chunks.iter().map(|c| expand_numbers(c)).collect(),
+            ResponseProcessor::Verbalize => chunks.iter().map(|c| verbalize(c, language)).collect(),
+            ResponseProcessor::Redact => chunks.iter()
+                .map(|c| redaction.redact_with(c, &config.redaction).text)
+                .collect(),
+        };
+    }
+
+    chunks.retain(|chunk| !chunk.trim().is_empty());
+    chunks.join(&SAY_CHUNK_BREAK.to_string())
+}
+
+/// Break streamed reply text into sentence-sized chunks joined with `SAY_CHUNK_BREAK`
+///
+/// Chunks hold whole sentences up to `max_chars`, so the caller can barge in
+/// between them and Twilio is never sent a multi-kilobyte Say. Breaks already
+/// in the text are kept, and SSML documents are passed through unchanged.
+pub fn chunk_streamed(text: &str, max_chars: usize) -> String {
+    if text.trim_start().starts_with("<speak>") {
+        return text.to_string();
+    }
+    text.split(SAY_CHUNK_BREAK)
+        .flat_map(|part| split_chunks(part.trim(), max_chars))
+        .collect::<Vec<_>>()
+        .join(&SAY_CHUNK_BREAK.to_string())
+}
+
+/// Split text after its last complete sentence, returning the sentences and the unfinished rest
+pub fn split_unfinished_sentence(text: &str) -> (&str, &str) {
+    let mut end = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|(_, next)| next.is_whitespace()) {
+            end = index + c.len_utf8();
+        }
+    }
+    (text[..end].trim(), text[end..].trim())
+}
+
+/// Remove markdown formatting, keeping link and image text
+///
+/// Lines are joined into sentences so list items are still read with a pause.
+fn strip_markdown(text: &str) -> String {
+    static RULES: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let rules = RULES.get_or_init(|| {
+        [
+            (r"!\[([^\]]*)\]\([^)]*\)", "$1"),
+            (r"\[([^\]]+)\]\([^)]*\)", "$1"),
+            (r"(?m)^\s*```.*$", ""),
+            (r"(?m)^\s{0,3}#{1,6}\s+", ""),
+            (r"(?m)^\s*>\s?", ""),
+            (r"(?m)^\s*[-*+]\s+", ""),
+            (r"\*\*|__|~~|`|\*", ""),
+            (r"(^|\W)_([^_\n]+)_(\W|$)", "$1$2$3"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid markdown pattern"), replacement))
+        .collect()
+    });
+
+    let mut text = text.to_string();
+    for (pattern, replacement) in rules {
+        text = pattern.replace_all(&text, *replacement).into_owned();
+    }
+
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.chars().last() {
+            Some('.' | '!' | '?' | ':' | ';' | ',') => line.to_string(),
+            _ => format!("{}.", line),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replace whole-word abbreviations, keeping trailing punctuation
+fn expand_abbreviations(text: &str, abbreviations: &HashMap<String, String>) -> String {
+    text.split(' ')
+        .map(|word| {
+            let core = word.trim_end_matches([',', ';', ':', '!', '?']);
+            match abbreviations.get(core) {
+                Some(expansion) => format!("{}{}", expansion, &word[core.len()..]),
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Spell out whole numbers as English words
+///
+/// Decimals, grouped numbers, numbers with leading zeros and long digit runs
+/// (phone numbers, codes) are left for the voice to read.
+fn expand_numbers(text: &str) -> String {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    let number = NUMBER.get_or_init(|| Regex::new(r"\d[\d,.]*\d|\d").expect("valid number pattern"));
+
+    number.replace_all(text, |captures: &regex::Captures| {
+        let digits = &captures[0];
+        let spoken = digits.bytes().all(|b| b.is_ascii_digit())
+            && (digits.len() == 1 || !digits.starts_with('0'));
+        match digits.parse::<u64>().ok().filter(|n| spoken && *n <= MAX_SPOKEN_NUMBER) {
+            Some(n) => number_to_words(n),
+            None => digits.to_string(),
+        }
+    }).into_owned()
+}
+
+/// English words for a whole number
+pub fn number_to_words(n: u64) -> String {
+    match n {
+        0..=19 => ONES[n as usize].to_string(),
+        20..=99 => match n % 10 {
+            0 => TENS[(n / 10) as usize].to_string(),
+            ones => format!("{}-{}", TENS[(n / 10) as usize], ONES[ones as usize]),
+        },
+        100..=999 => scaled(n, 100, "hundred"),
+        1_000..=999_999 => scaled(n, 1_000, "thousand"),
+        _ => scaled(n, 1_000_000, "million"),
+    }
+}
+
+fn scaled(n: u64, scale: u64, name: &str) -> String {
+    match n % scale {
+        0 => format!("{} {}", number_to_words(n / scale), name),
+        rest => format!("{} {} {}", number_to_words(n / scale), name, number_to_words(rest)),
+    }
+}
+
+/// Redact the text nodes of an SSML document, leaving its tags alone
+fn redact_ssml(ssml: &str, redaction: &RedactionConfig, replacement: &str) -> String {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(r"<[^>]*>").expect("valid tag pattern"));
+    let replacement = replacement.replace('&', "&amp;").replace('<', "&lt;");
+
+    let mut redacted = String::with_capacity(ssml.len());
+    let mut last = 0;
+    for found in tag.find_iter(ssml) {
+        redacted.push_str(&redaction.redact_with(&ssml[last..found.start()], &replacement).text);
+        redacted.push_str(found.as_str());
+        last = found.end();
+    }
+    redacted.push_str(&redaction.redact_with(&ssml[last..], &replacement).text);
+    redacted
+}
+
+/// Split text into chunks of at most `max_chars`, breaking between sentences where possible
+fn split_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for piece in sentences(text).into_iter().flat_map(|s| split_long(s, max_chars)) {
+        if !current.is_empty() && current.chars().count() + 1 + piece.chars().count() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&piece);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Sentences of the text, each ending with its punctuation
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let at_break = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if at_break {
+            let end = index + c.len_utf8();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Break a sentence longer than `max_chars` between words
+fn split_long(sentence: &str, max_chars: usize) -> Vec<String> {
+    if sentence.chars().count() <= max_chars {
+        return vec![sentence.to_string()];
+    }
+
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for word in sentence.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_chars {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::RecordingConfig;
+
+/// Session metadata key holding the recording decision
+pub const RECORDING_METADATA_KEY: &str = "recording_consent";
+
+/// Whether a call is recorded, decided when the call starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingDecision {
+    /// The consent announcement was played and the call is recorded
+    Recorded,
+    /// The caller's country does not allow recording, so the call is not recorded
+    ExcludedRegion,
+    /// The caller pressed the opt-out key during the consent announcement, so the call is not recorded
+    OptedOut,
+}
+
+impl RecordingDecision {
+    /// Decide whether to record a call with the given remote party
+    ///
+    /// Returns `None` when recording is disabled.
+    pub fn for_number(phone_number: &str, config: &RecordingConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let digits = phone_number.trim().trim_start_matches('+');
+        let excluded = config.excluded_country_codes
+            .iter()
+            .any(|code| digits.starts_with(code.as_str()));
+
+        if excluded {
+            Some(RecordingDecision::ExcludedRegion)
+        } else {
+            Some(RecordingDecision::Recorded)
+        }
+    }
+
+    /// Whether the call should be recorded
+    pub fn is_recorded(&self) -> bool {
+        *self == RecordingDecision::Recorded
+    }
+
+    /// Announcement to play before the call's first prompt, if any
+    pub fn consent_message<'a>(&self, config: &'a RecordingConfig) -> Option<&'a str> {
+        self.is_recorded().then_some(config.consent_message.as_str())
+    }
+
+    /// Whether recording starts with the call
+    ///
+    /// Calls with a consent announcement start recording once it has played.
+    pub fn records_from_start(&self, config: &RecordingConfig) -> bool {
+        self.consent_message(config).is_some_and(|message| message.is_empty())
+    }
+}
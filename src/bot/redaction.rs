@@ -0,0 +1,118 @@
+use std::fmt;
+use std::sync::OnceLock;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::{PiiKind, RedactionConfig};
+
+/// Built-in kinds, longest first so a card number is not taken for a phone number
+const BUILTIN_ORDER: [PiiKind; 3] = [PiiKind::CreditCard, PiiKind::Ssn, PiiKind::Phone];
+
+impl PiiKind {
+    /// Pattern matching this kind of personal data as speech recognition writes it
+    fn pattern(&self) -> &'static Regex {
+        static CREDIT_CARD: OnceLock<Regex> = OnceLock::new();
+        static SSN: OnceLock<Regex> = OnceLock::new();
+        static PHONE: OnceLock<Regex> = OnceLock::new();
+
+        match self {
+            // 13 to 19 digits, optionally grouped by spaces or dashes
+            PiiKind::CreditCard => CREDIT_CARD.get_or_init(|| {
+                Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").expect("valid credit card pattern")
+            }),
+            PiiKind::Ssn => SSN.get_or_init(|| {
+                Regex::new(r"\b\d{3}[- ]?\d{2}[- ]?\d{4}\b").expect("valid SSN pattern")
+            }),
+            PiiKind::Phone => PHONE.get_or_init(|| {
+                Regex::new(r"(?:\+\d{1,3}[-. ]?)?(?:\(\d{3}\)|\b\d{3})[-. ]?\d{3}[-. ]?\d{4}\b").expect("valid phone pattern")
+            }),
+        }
+    }
+}
+
+/// Custom redaction patterns, compiled once when the configuration is loaded
+#[derive(Clone, Default)]
+pub struct PatternSet(Vec<Regex>);
+
+impl PatternSet {
+    /// Compile the patterns, failing on the first invalid one
+    pub fn compile(patterns: &[String]) -> Result<Self, String> {
+        patterns.iter()
+            .map(|pattern| Regex::new(pattern)
+                .map_err(|e| format!("Invalid redaction pattern '{}': {}", pattern, e)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(PatternSet)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Regex> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Debug for PatternSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|pattern| pattern.as_str())).finish()
+    }
+}
+
+impl Serialize for PatternSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|pattern| pattern.as_str()))
+    }
+}
+
+impl<'de> Deserialize<'de> for PatternSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let patterns = Vec::<String>::deserialize(deserializer)?;
+        PatternSet::compile(&patterns).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Text with its personal data replaced
+#[derive(Debug, Clone)]
+pub struct Redacted {
+    pub text: String,
+    /// Number of matches replaced
+    pub count: u32,
+}
+
+impl RedactionConfig {
+    /// Replace the personal data in `text`; unchanged when redaction is disabled
+    pub fn redact(&self, text: &str) -> Redacted {
+        if !self.enabled {
+            return Redacted { text: text.to_string(), count: 0 };
+        }
+        self.redact_with(text, &self.replacement)
+    }
+
+    /// Replace the personal data in `text` with `replacement`, whether or not
+    /// redaction of logged and stored text is enabled
+    ///
+    /// Used by the `redact` response processor, which speaks its own replacement.
+    pub fn redact_with(&self, text: &str, replacement: &str) -> Redacted {
+        let mut redacted = Redacted { text: text.to_string(), count: 0 };
+        for kind in BUILTIN_ORDER.iter().filter(|kind| self.builtins.contains(kind)) {
+            replace(&mut redacted, kind.pattern(), replacement);
+        }
+        for pattern in self.patterns.iter() {
+            replace(&mut redacted, pattern, replacement);
+        }
+        redacted
+    }
+
+    /// `text` with its personal data replaced, for logging
+    pub fn redacted(&self, text: &str) -> String {
+        self.redact(text).text
+    }
+}
+
+/// Replace every match of `pattern`, counting them
+fn replace(redacted: &mut Redacted, pattern: &Regex, replacement: &str) {
+    let mut count = 0;
+    let text = pattern.replace_all(&redacted.text, |_: &Captures| {
+        count += 1;
+        replacement
+    }).into_owned();
+    redacted.text = text;
+    redacted.count += count;
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::{debug, error, info};
+use serde_json::Value;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::bot::cdr::CallRecord;
+use crate::config::{CallbackConfig, MAX_CALLBACK_RETRY_ATTEMPTS};
+use crate::dead_letter::{DeadLetterKind, DeadLetterStore};
+
+/// Header carrying the Unix timestamp included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-Callback-Timestamp";
+/// Header carrying the payload signature
+///
+/// While a secret is being rotated it holds one comma-separated signature per
+/// secret, current first; consumers accept the payload if any of them matches.
+pub const SIGNATURE_HEADER: &str = "X-Callback-Signature";
+/// Header carrying the event ID, also in the payload's `event_id`
+///
+/// The ID stays the same across retries and dead letter replays, so consumers
+/// can drop duplicates. Together with the signed timestamp this lets them
+/// reject replayed deliveries.
+pub const EVENT_ID_HEADER: &str = "X-Callback-Event-Id";
+
+/// Sign a callback body as `sha256=<hex HMAC of "<timestamp>.<body>">`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Signature header value for a callback body, signed with each secret
+pub fn signature_header(secrets: &[&str], timestamp: i64, body: &str) -> String {
+    secrets.iter()
+        .map(|secret| sign_payload(secret, timestamp, body))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Check that a callback URL can be used for a tenant's calls
+pub fn validate_callback_url(url: &str, tenant_id: Option<&str>, config: &CallbackConfig) -> Result<(), String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err("callback_url must be an http(s) URL".to_string());
+    }
+    if config.signing_secrets(tenant_id).is_empty() {
+        return Err("callback_url requires CALLBACK_SIGNING_SECRET to be set".to_string());
+    }
+    Ok(())
+}
+
+/// POST the final call record to the consumer's callback URL in the background
+///
+/// A result that cannot be delivered after the retries is dead-lettered.
+pub fn send_call_result(url: String, record: CallRecord, config: CallbackConfig, dead_letters: Arc<DeadLetterStore>) {
+    if config.signing_secrets(record.tenant_id.as_deref()).is_empty() {
+        error!("Not sending call result for {}: no signing secret configured", record.call_sid);
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut payload = match serde_json::to_value(&record) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize call result for {}: {}", record.call_sid, e);
+                return;
+            }
+        };
+        if let Some(fields) = payload.as_object_mut() {
+            fields.insert("event_id".to_string(), Value::from(Uuid::new_v4().to_string()));
+        }
+
+        let mut attempts = 0;
+        let mut last_error = String::new();
+        while attempts <= config.retry_attempts {
+            match deliver_call_result(&url, &payload, &config).await {
+                Ok(()) => {
+                    info!("Delivered call result for {} to {}", record.call_sid, url);
+                    return;
+                },
+                Err(e) => {
+                    debug!("Call result callback to {} failed: {}", url, e);
+                    last_error = e;
+                },
+            }
+
+            attempts += 1;
+            if attempts <= config.retry_attempts {
+                let delay = 1000 * 2u64.pow((attempts as u32 - 1).min(MAX_CALLBACK_RETRY_ATTEMPTS as u32));
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+        }
+
+        error!("Giving up delivering call result for {} to {}", record.call_sid, url);
+        dead_letters.add(DeadLetterKind::CallResult, &url, None, payload, &last_error).await;
+    });
+}
+
+/// POST a signed call result to a callback URL once
+///
+/// The result is signed with the secrets of the tenant it names.
+pub async fn deliver_call_result(url: &str, payload: &Value, config: &CallbackConfig) -> Result<(), String> {
+    let secrets = config.signing_secrets(payload.get("tenant_id").and_then(Value::as_str));
+    if secrets.is_empty() {
+        return Err("No signing secret configured".to_string());
+    }
+    let body = payload.to_string();
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_seconds))
+        .build()
+        .map_err(|e| format!("Failed to create callback HTTP client: {}", e))?;
+
+    let timestamp = Utc::now().timestamp();
+    let mut request = client.post(url)
+        .header("Content-Type", "application/json")
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, signature_header(&secrets, timestamp, &body));
+    if let Some(event_id) = payload.get("event_id").and_then(Value::as_str) {
+        request = request.header(EVENT_ID_HEADER, event_id);
+    }
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Callback returned {}", response.status()))
+    }
+}
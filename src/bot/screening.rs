@@ -0,0 +1,35 @@
+use crate::config::{ScreeningConfig, ScreeningRule};
+
+/// Values Twilio reports in `From` when the caller withheld their number
+const ANONYMOUS_CALLERS: [&str; 5] = ["anonymous", "restricted", "unknown", "unavailable", "+266696687"];
+
+/// Whether the caller withheld their number
+pub fn is_anonymous(from_number: &str) -> bool {
+    let from_number = from_number.trim();
+    from_number.is_empty() || ANONYMOUS_CALLERS.iter().any(|a| from_number.eq_ignore_ascii_case(a))
+}
+
+impl ScreeningRule {
+    /// Whether every condition the rule sets matches the caller
+    pub fn matches(&self, from_number: &str, from_country: Option<&str>) -> bool {
+        let prefix = self.prefix.as_deref()
+            .is_none_or(|prefix| from_number.trim().starts_with(prefix.trim()));
+        let country = self.country.as_deref()
+            .is_none_or(|country| from_country.is_some_and(|c| c.eq_ignore_ascii_case(country)));
+        let anonymous = self.anonymous
+            .is_none_or(|anonymous| is_anonymous(from_number) == anonymous);
+
+        prefix && country && anonymous
+    }
+}
+
+/// Find the first screening rule matching an inbound caller
+///
+/// Calls matching no rule proceed.
+pub fn screen_call<'a>(
+    from_number: &str,
+    from_country: Option<&str>,
+    config: &'a ScreeningConfig,
+) -> Option<&'a ScreeningRule> {
+    config.rules.iter().find(|rule| rule.matches(from_number, from_country))
+}
@@ -1,14 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 use chrono::{DateTime, Utc, Duration};
-use regex::Regex;
-use rocket::tokio::sync::mpsc::{channel, Receiver, Sender};
+use dashmap::DashMap;
+use rocket::tokio::sync::{broadcast, Mutex, OwnedMutexGuard};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
-use log::{debug, info, error};
+
+use crate::bot::callflow::CallFlow;
+use crate::bot::debounce::PartialDebouncer;
+use crate::bot::events::{SessionEvent, SessionEventKind};
+use crate::bot::cdr::HangupSource;
+use crate::bot::menu::Menu;
+use crate::bot::experiments::assigned_variant;
+use crate::bot::metrics::SpeechStats;
+use crate::bot::message_queue::{message_queue, MessageReceiver, MessageSender, MESSAGE_QUEUE_CAPACITY};
+use crate::cluster::{SessionCluster, TurnLease};
+use crate::tenant::TenantStore;
+use crate::config::{Config, SharedConfig, SpeechSettings, TwilioConfig};
+use crate::twilio::client::TwilioClient;
+use crate::twilio::twiml::TwiML;
+use log::{debug, info, warn};
 
 /// Types of messages that can be sent through the message queue
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageType {
     /// Text message
     Text(String),
@@ -18,6 +34,159 @@ pub enum MessageType {
     EndOfStream,
 }
 
+/// What a live session is doing, as seen by the backend streaming into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionActivity {
+    /// A reply is being generated or queued messages are waiting to be spoken
+    Speaking,
+    /// The call is listening for the caller
+    Gathering,
+    /// The call is on hold, ending or not connected
+    Idle,
+}
+
+/// Caller silence being bridged with keepalive prompts
+#[derive(Debug, Clone)]
+pub struct SilencePeriod {
+    pub started: DateTime<Utc>,
+    /// Keepalive prompts played so far
+    pub prompts: u32,
+}
+
+impl SilencePeriod {
+    /// Describe the silence for the backend
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "seconds": (Utc::now() - self.started).num_seconds(),
+            "prompts": self.prompts,
+        })
+    }
+}
+
+/// Supervisor coaching a conferenced call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoachState {
+    /// Number or SIP address the supervisor was dialed at
+    pub supervisor: String,
+    /// Supervisor's call leg
+    pub call_sid: String,
+    /// Whether the supervisor has answered and is listening
+    pub connected: bool,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Agent who took a call over from the bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverState {
+    /// Number or SIP address the agent was dialed at
+    pub agent: String,
+    /// Agent's call leg
+    pub call_sid: String,
+    /// Whether the agent has answered and is talking to the caller
+    pub connected: bool,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Caller sentiment and escalation risk last reported by the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallerSignals {
+    /// Backend's label for the caller's mood, e.g. `negative`
+    pub sentiment: Option<String>,
+    /// Likelihood (0 to 1) that the caller needs a human
+    pub escalation_risk: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Call fields Twilio reports in its webhooks, as last seen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CallDetails {
+    /// `inbound`, `outbound-api` or `outbound-dial`
+    pub direction: Option<String>,
+    /// Number or SIP address that was called
+    pub to_number: Option<String>,
+    /// Answering machine detection result, e.g. `human` or `machine_end_beep`
+    pub answered_by: Option<String>,
+    /// Length of the call in seconds, once it has ended
+    pub call_duration: Option<u64>,
+}
+
+impl CallDetails {
+    /// Take the fields a webhook reported, keeping the ones it left out
+    pub fn merge(&mut self, update: CallDetails) {
+        self.direction = update.direction.or(self.direction.take());
+        self.to_number = update.to_number.or(self.to_number.take());
+        self.answered_by = update.answered_by.or(self.answered_by.take());
+        self.call_duration = update.call_duration.or(self.call_duration.take());
+    }
+
+    /// Whether Twilio reported the call as inbound
+    pub fn is_inbound(&self) -> bool {
+        self.direction.as_deref() == Some("inbound")
+    }
+
+    /// Whether Twilio reported the call as placed through the API or a Dial
+    pub fn is_outbound(&self) -> bool {
+        self.direction.as_deref().is_some_and(|d| d.starts_with("outbound"))
+    }
+}
+
+/// Serializable session state carried across a restart
+///
+/// Channels and in-flight turn state are not captured; a restored session
+/// picks up at the caller's next turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub session_id: String,
+    pub user_id: String,
+    pub name: String,
+    pub bot_type: String,
+    #[serde(default)]
+    pub bot: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    pub conversation_id: Option<String>,
+    pub creation_time: DateTime<Utc>,
+    pub last_activity_time: DateTime<Utc>,
+    pub session_ends: bool,
+    pub metadata: HashMap<String, Value>,
+    pub voicemail_message: Option<String>,
+    pub disposition: Option<String>,
+    pub hangup_source: Option<HangupSource>,
+    pub active_menu: Option<Menu>,
+    pub persona: Option<String>,
+    #[serde(default)]
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub speech: SpeechSettings,
+    pub turn_count: u32,
+    pub queue: Option<String>,
+    #[serde(default)]
+    pub transfer_target: Option<String>,
+    #[serde(default)]
+    pub escalation_target: Option<String>,
+    #[serde(default)]
+    pub escalation_call_sid: Option<String>,
+    #[serde(default)]
+    pub coach: Option<CoachState>,
+    #[serde(default)]
+    pub takeover: Option<TakeoverState>,
+    #[serde(default)]
+    pub signals: Option<CallerSignals>,
+    #[serde(default)]
+    pub call: CallDetails,
+    #[serde(default)]
+    pub detected_language: Option<String>,
+    #[serde(default)]
+    pub on_hold: bool,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub callflow: Option<CallFlow>,
+    pub resume_token: String,
+    pub replica_version: u64,
+}
+
 /// Session state for a bot conversation
 pub struct Session {
     /// Unique session identifier
@@ -28,12 +197,16 @@ pub struct Session {
     pub name: String,
     /// Bot type (e.g., "twilio")
     pub bot_type: String,
+    /// Named backend profile the session is bound to; `None` for the default backend
+    pub bot: Option<String>,
+    /// Tenant whose Twilio account carries the call; `None` for the service account
+    pub tenant_id: Option<String>,
     /// External conversation identifier (e.g., Twilio CallSid)
     pub conversation_id: Option<String>,
     /// Sender for message queue
-    pub message_tx: Sender<MessageType>,
+    pub message_tx: MessageSender,
     /// Receiver for message queue
-    pub message_rx: Receiver<MessageType>,
+    pub message_rx: MessageReceiver,
     /// Session creation time
     pub creation_time: DateTime<Utc>,
     /// Last activity time
@@ -44,18 +217,85 @@ pub struct Session {
     pub run_in_progress: bool,
     /// Current unstable speech result
     pub unstable_speech_result: Option<String>,
+    /// Stability tracking for partial results not yet acted on
+    pub partial_debouncer: PartialDebouncer,
     /// Whether generation is in progress
     pub generation: bool,
     /// Whether the session is ending
     pub session_ends: bool,
     /// Session metadata
     pub metadata: HashMap<String, Value>,
+    /// Message to leave if an outbound call reaches voicemail (text or audio URL)
+    pub voicemail_message: Option<String>,
+    /// Final call outcome reported to the backend when the session closes
+    pub disposition: Option<String>,
+    /// Party that ended the call, when the bot knows it ended the call itself
+    pub hangup_source: Option<HangupSource>,
+    /// IVR menu currently awaiting a selection
+    pub active_menu: Option<Menu>,
+    /// Voice persona selected by the backend
+    pub persona: Option<String>,
+    /// Catalog voice selected by the backend, overriding the persona's voice
+    pub voice: Option<String>,
+    /// Reply of the last turn still to be spoken over the call's Media Stream
+    pub pending_reply: Option<String>,
+    /// The caller waits on the queue callback for a turn that is still running
+    pub turn_overdue: bool,
+    /// TwiML of an overdue or streamed turn, played by the next queue callback
+    pub late_reply: Option<TwiML>,
+    /// Streamed reply text after its last complete sentence, held back until the sentence ends
+    pub streamed_tail: String,
+    /// Speech recognition settings chosen for this call
+    pub speech: SpeechSettings,
+    /// Number of caller turns sent to the backend
+    pub turn_count: u32,
+    /// Speech recognition quality and turnaround measured on this call
+    pub speech_stats: SpeechStats,
+    /// When a transcript of the caller's speech last arrived
+    pub last_speech: Option<std::time::Instant>,
+    /// With streaming recognition, when the call started waiting for the caller and how long silence may last
+    pub listening: Option<(std::time::Instant, std::time::Duration)>,
+    /// Personal data matches redacted from the call's speech and replies
+    pub redactions: u32,
+    /// Ongoing caller silence, reported to the backend with the caller's next turn
+    pub silence: Option<SilencePeriod>,
+    /// Twilio queue the caller is waiting in for an agent
+    pub queue: Option<String>,
+    /// Number or SIP address the caller is being transferred to
+    pub transfer_target: Option<String>,
+    /// Third party being dialed into the call by an escalation
+    pub escalation_target: Option<String>,
+    /// Escalation party's call leg, once they joined the conference
+    pub escalation_call_sid: Option<String>,
+    /// Supervisor coaching the call
+    pub coach: Option<CoachState>,
+    /// Agent the call was handed to; while set the session only shadows the call,
+    /// sending the caller's transcripts to the backend without answering them
+    pub takeover: Option<TakeoverState>,
+    /// Caller sentiment and escalation risk from the backend's latest run
+    pub signals: Option<CallerSignals>,
+    /// Call direction, dialed number and answering machine result reported by Twilio
+    pub call: CallDetails,
+    /// Caller language reported by the backend, used for the rest of the call
+    pub detected_language: Option<String>,
+    /// Whether the caller is on hold; their speech is ignored until the call resumes
+    pub on_hold: bool,
+    /// Labels attached by the call request, the backend or an admin, for searching calls
+    pub tags: BTreeSet<String>,
+    /// Progress of an outbound call; `None` for inbound calls
+    pub callflow: Option<CallFlow>,
+    /// Token allowing another region to resume the conversation after failover
+    pub resume_token: String,
+    /// Version of the last replica published for this session
+    pub replica_version: u64,
+    /// Version of the session state last shared with the cluster
+    pub cluster_version: u64,
 }
 
 impl Session {
     /// Create a new session
     pub fn new(user_id: String, name: String, bot_type: String, conversation_id: Option<String>) -> Self {
-        let (tx, rx) = channel(100);
+        let (tx, rx) = message_queue(MESSAGE_QUEUE_CAPACITY);
         let now = Utc::now();
         
         Session {
@@ -63,6 +303,8 @@ impl Session {
             user_id,
             name,
             bot_type,
+            bot: None,
+            tenant_id: None,
             conversation_id,
             message_tx: tx,
             message_rx: rx,
@@ -71,9 +313,42 @@ impl Session {
             speech_in_progress: false,
             run_in_progress: false,
             unstable_speech_result: None,
+            partial_debouncer: PartialDebouncer::default(),
             generation: false,
             session_ends: false,
             metadata: HashMap::new(),
+            voicemail_message: None,
+            disposition: None,
+            hangup_source: None,
+            active_menu: None,
+            persona: None,
+            voice: None,
+            pending_reply: None,
+            turn_overdue: false,
+            late_reply: None,
+            streamed_tail: String::new(),
+            speech: SpeechSettings::default(),
+            turn_count: 0,
+            speech_stats: SpeechStats::default(),
+            last_speech: None,
+            listening: None,
+            redactions: 0,
+            silence: None,
+            queue: None,
+            transfer_target: None,
+            escalation_target: None,
+            escalation_call_sid: None,
+            coach: None,
+            takeover: None,
+            signals: None,
+            call: CallDetails::default(),
+            detected_language: None,
+            on_hold: false,
+            tags: BTreeSet::new(),
+            callflow: None,
+            resume_token: Uuid::new_v4().to_string(),
+            replica_version: 0,
+            cluster_version: 0,
         }
     }
     
@@ -95,143 +370,635 @@ impl Session {
         }
     }
     
-    /// Check if the text ends with sentence punctuation
-    pub fn ends_with_sentence_punctuation(text: &str) -> bool {
-        let re = Regex::new(r".*[.!?]$").unwrap();
-        re.is_match(text.trim())
-    }
-    
     /// Update the last activity time
     pub fn update_activity_time(&mut self) {
         self.last_activity_time = Utc::now();
     }
     
+    /// Name of the call limit the session exceeded, if any
+    ///
+    /// Limits of zero are unlimited.
+    pub fn exceeded_limit(&self, max_turns: u32, max_duration_minutes: u64) -> Option<&'static str> {
+        if max_turns > 0 && self.turn_count >= max_turns {
+            return Some("max_turns");
+        }
+        if max_duration_minutes > 0 && Utc::now() - self.creation_time >= Duration::minutes(max_duration_minutes as i64) {
+            return Some("max_duration");
+        }
+        None
+    }
+    
+    /// Context forwarded to the backend with a caller turn, limited to `allowlist`
+    ///
+    /// `turn_index` and `confidence` describe the current turn and `call` holds
+    /// the call's direction, dialed number and answering machine result; any
+    /// other key is read from the session metadata (e.g. `env_info`, `caller_lookup`).
+    pub fn turn_kwargs(&self, confidence: Option<f64>, allowlist: &[String]) -> HashMap<String, Value> {
+        allowlist.iter()
+            .filter_map(|key| {
+                let value = match key.as_str() {
+                    "turn_index" => Some(Value::from(self.turn_count)),
+                    "confidence" => confidence.map(Value::from),
+                    "call" => serde_json::to_value(&self.call).ok(),
+                    _ => self.metadata.get(key).cloned(),
+                };
+                value.map(|v| (key.clone(), v))
+            })
+            .collect()
+    }
+    
+    /// Twilio settings for the call: its experiment variant, persona's or selected voice, speech settings and detected language
+    pub fn twilio_config(&self, config: &Config) -> TwilioConfig {
+        let variant_twilio = assigned_variant(&self.metadata)
+            .and_then(|(_, name)| config.experiments.get(name))
+            .map(|variant| variant.apply(&config.twilio));
+        let base = variant_twilio.as_ref().unwrap_or(&config.twilio);
+        let persona_twilio = self.persona
+            .as_deref()
+            .and_then(|name| config.personas.get(name))
+            .map(|persona| persona.apply(base));
+        let mut twilio = self.speech.apply(persona_twilio.as_ref().unwrap_or(base));
+        if let Some(voice) = &self.voice {
+            twilio.voice = voice.clone();
+        }
+        let twilio = match &self.detected_language {
+            Some(language) => config.languages.apply(&twilio, language),
+            None => twilio,
+        };
+        config.tts.apply(&twilio)
+    }
+    
+    /// Number of messages queued for the call and not yet spoken
+    pub fn queue_depth(&self) -> usize {
+        self.message_tx.depth()
+    }
+    
+    /// What the session is currently doing
+    pub fn activity(&self) -> SessionActivity {
+        if self.generation || self.queue_depth() > 0 {
+            SessionActivity::Speaking
+        } else if self.on_hold || self.session_ends || self.conversation_id.is_none() {
+            SessionActivity::Idle
+        } else {
+            SessionActivity::Gathering
+        }
+    }
+    
     /// Check if the session has expired
     pub fn is_expired(&self, max_age: Duration) -> bool {
         Utc::now() - self.last_activity_time > max_age
     }
+    
+    /// Capture the session's durable state
+    pub fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            session_id: self.session_id.clone(),
+            user_id: self.user_id.clone(),
+            name: self.name.clone(),
+            bot_type: self.bot_type.clone(),
+            bot: self.bot.clone(),
+            tenant_id: self.tenant_id.clone(),
+            conversation_id: self.conversation_id.clone(),
+            creation_time: self.creation_time,
+            last_activity_time: self.last_activity_time,
+            session_ends: self.session_ends,
+            metadata: self.metadata.clone(),
+            voicemail_message: self.voicemail_message.clone(),
+            disposition: self.disposition.clone(),
+            hangup_source: self.hangup_source,
+            active_menu: self.active_menu.clone(),
+            persona: self.persona.clone(),
+            voice: self.voice.clone(),
+            speech: self.speech.clone(),
+            turn_count: self.turn_count,
+            queue: self.queue.clone(),
+            transfer_target: self.transfer_target.clone(),
+            escalation_target: self.escalation_target.clone(),
+            escalation_call_sid: self.escalation_call_sid.clone(),
+            coach: self.coach.clone(),
+            takeover: self.takeover.clone(),
+            signals: self.signals.clone(),
+            call: self.call.clone(),
+            detected_language: self.detected_language.clone(),
+            on_hold: self.on_hold,
+            tags: self.tags.clone(),
+            callflow: self.callflow.clone(),
+            resume_token: self.resume_token.clone(),
+            replica_version: self.replica_version,
+        }
+    }
+    
+    /// Rebuild a session from a snapshot with fresh channels
+    pub fn from_snapshot(snapshot: SessionSnapshot) -> Self {
+        let mut session = Session::new(
+            snapshot.user_id.clone(),
+            snapshot.name.clone(),
+            snapshot.bot_type.clone(),
+            snapshot.conversation_id.clone()
+        );
+        session.apply_snapshot(snapshot);
+        session
+    }
+    
+    /// Replace the session's durable state, keeping its channels and in-flight turn state
+    pub fn apply_snapshot(&mut self, snapshot: SessionSnapshot) {
+        self.user_id = snapshot.user_id;
+        self.name = snapshot.name;
+        self.bot_type = snapshot.bot_type;
+        self.bot = snapshot.bot;
+        self.tenant_id = snapshot.tenant_id;
+        self.conversation_id = snapshot.conversation_id;
+        self.session_id = snapshot.session_id;
+        self.creation_time = snapshot.creation_time;
+        self.last_activity_time = snapshot.last_activity_time;
+        self.session_ends = snapshot.session_ends;
+        self.metadata = snapshot.metadata;
+        self.voicemail_message = snapshot.voicemail_message;
+        self.disposition = snapshot.disposition;
+        self.hangup_source = snapshot.hangup_source;
+        self.active_menu = snapshot.active_menu;
+        self.persona = snapshot.persona;
+        self.voice = snapshot.voice;
+        self.speech = snapshot.speech;
+        self.turn_count = snapshot.turn_count;
+        self.queue = snapshot.queue;
+        self.transfer_target = snapshot.transfer_target;
+        self.escalation_target = snapshot.escalation_target;
+        self.escalation_call_sid = snapshot.escalation_call_sid;
+        self.coach = snapshot.coach;
+        self.takeover = snapshot.takeover;
+        self.signals = snapshot.signals;
+        self.call = snapshot.call;
+        self.detected_language = snapshot.detected_language;
+        self.on_hold = snapshot.on_hold;
+        self.tags = snapshot.tags;
+        self.callflow = snapshot.callflow;
+        self.resume_token = snapshot.resume_token;
+        self.replica_version = snapshot.replica_version;
+    }
+}
+
+/// Shared handle to a session; the mutex serializes updates to a single call
+pub type SessionHandle = Arc<Mutex<Session>>;
+
+/// Exclusive right to process a turn of a call, released when dropped
+///
+/// In cluster mode it also holds the call's turn lease, so webhooks of one call
+/// wait for each other whichever replicas they reach.
+pub struct TurnGuard {
+    _local: OwnedMutexGuard<()>,
+    _lease: Option<TurnLease>,
+}
+
+/// Locked session from the store
+///
+/// In cluster mode the session's state is shared with the other replicas
+/// when the lock is released.
+pub struct SessionGuard {
+    session: OwnedMutexGuard<Session>,
+    cluster: Option<Arc<SessionCluster>>,
+}
+
+impl Deref for SessionGuard {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        &self.session
+    }
+}
+
+impl DerefMut for SessionGuard {
+    fn deref_mut(&mut self) -> &mut Session {
+        &mut self.session
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Some(cluster) = &self.cluster {
+            self.session.cluster_version += 1;
+            cluster.save_detached(self.session.snapshot(), self.session.cluster_version);
+        }
+    }
 }
 
 /// Store for managing multiple sessions
+///
+/// Sessions live in concurrent maps sharded by key, and each session sits behind
+/// its own lock, so webhooks for different calls never contend with each other.
+/// Never hold a session lock while locking another session.
 pub struct SessionStore {
     /// Sessions indexed by session ID
-    sessions: HashMap<String, Session>,
+    sessions: DashMap<String, SessionHandle>,
     /// Mapping from conversation ID to session ID
-    conversation_to_session: HashMap<String, String>,
+    conversation_to_session: DashMap<String, String>,
     /// Mapping from session ID to conversation ID
-    session_to_conversation: HashMap<String, String>,
+    session_to_conversation: DashMap<String, String>,
+    /// Notifies listeners of removed session IDs
+    removals: broadcast::Sender<String>,
+    /// Turn events for live monitoring
+    events: broadcast::Sender<SessionEvent>,
+    /// Shared state of the other replicas, in cluster mode
+    cluster: Option<Arc<SessionCluster>>,
+    /// Per-call locks serializing turn processing, indexed by conversation ID
+    turns: DashMap<String, Arc<Mutex<()>>>,
 }
 
 impl SessionStore {
     /// Create a new session store
     pub fn new() -> Self {
         SessionStore {
-            sessions: HashMap::new(),
-            conversation_to_session: HashMap::new(),
-            session_to_conversation: HashMap::new(),
+            sessions: DashMap::new(),
+            conversation_to_session: DashMap::new(),
+            session_to_conversation: DashMap::new(),
+            removals: broadcast::channel(1024).0,
+            events: broadcast::channel(1024).0,
+            cluster: None,
+            turns: DashMap::new(),
+        }
+    }
+    
+    /// Create a session store that shares sessions with the other replicas of a cluster
+    pub fn with_cluster(cluster: Arc<SessionCluster>) -> Self {
+        SessionStore {
+            cluster: Some(cluster).filter(|c| c.is_enabled()),
+            ..Self::new()
         }
     }
 
+    /// Subscribe to the IDs of sessions removed from the store
+    pub fn subscribe_removals(&self) -> broadcast::Receiver<String> {
+        self.removals.subscribe()
+    }
+    
+    /// Subscribe to turn events of all sessions
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Publish a turn event for a session
+    pub fn publish_event(&self, session_id: &str, kind: SessionEventKind) {
+        // No receivers just means nobody is monitoring
+        let _ = self.events.send(SessionEvent::new(session_id, kind));
+    }
+
     /// Get the session ID for a given conversation ID
     pub fn get_session_id_by_conversation(&self, conversation_id: &str) -> Option<String> {
-        self.conversation_to_session.get(conversation_id).cloned()
+        self.conversation_to_session.get(conversation_id).map(|id| id.clone())
     }
 
-    /// Add a session to the store
-    pub fn add_session(&mut self, session: Session) -> String {
+    /// Wait for the other webhooks of a call to finish their turn processing
+    ///
+    /// Hold the returned guard while reading and updating the turn's session
+    /// state so partial results, final transcriptions and queue callbacks of one
+    /// call cannot interleave and clobber it. Release it while the backend runs
+    /// the turn and take it again for the result, so the call's other webhooks
+    /// are not held up by a slow backend. Different calls never wait for each
+    /// other. Take it before any session lock.
+    pub async fn lock_turn(&self, conversation_id: &str) -> TurnGuard {
+        let lock = self.turns
+            .entry(conversation_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+        let local = lock.lock_owned().await;
+        let lease = match &self.cluster {
+            Some(cluster) => cluster.acquire_turn(conversation_id).await,
+            None => None,
+        };
+        TurnGuard { _local: local, _lease: lease }
+    }
+
+    /// Add a session to the store, sharing it with the cluster
+    pub fn add_session(&self, mut session: Session) -> String {
+        if let Some(cluster) = &self.cluster {
+            session.cluster_version += 1;
+            cluster.save_detached(session.snapshot(), session.cluster_version);
+        }
+        self.insert_session(session)
+    }
+    
+    fn insert_session(&self, session: Session) -> String {
         let session_id = session.session_id.clone();
         
+        // Queue callbacks may reach any replica, so queued messages are shared
+        if let Some(cluster) = &self.cluster {
+            session.message_tx.share(cluster.clone(), &session_id);
+        }
+        
         if let Some(conversation_id) = &session.conversation_id {
             self.set_conversation_mapping(conversation_id.clone(), session_id.clone());
         }
         
-        self.sessions.insert(session_id.clone(), session);
+        self.sessions.insert(session_id.clone(), Arc::new(Mutex::new(session)));
         session_id
     }
     
-    /// Get a session by session ID
-    pub fn get_session(&self, session_id: &str) -> Option<&Session> {
-        self.sessions.get(session_id)
+    /// Get a session handle by session ID
+    pub fn get_session(&self, session_id: &str) -> Option<SessionHandle> {
+        self.sessions.get(session_id).map(|session| session.clone())
     }
     
-    /// Get a mutable reference to a session by session ID
-    pub fn get_session_mut(&mut self, session_id: &str) -> Option<&mut Session> {
-        if let Some(session) = self.sessions.get_mut(session_id) {
-            session.update_activity_time();
-            Some(session)
-        } else {
-            None
-        }
+    /// Get a session handle by conversation ID
+    pub fn get_session_by_conversation(&self, conversation_id: &str) -> Option<SessionHandle> {
+        let session_id = self.get_session_id_by_conversation(conversation_id)?;
+        self.get_session(&session_id)
+    }
+    
+    /// Lock a session by session ID, recording activity
+    ///
+    /// In cluster mode the session is first brought up to date with the cluster.
+    pub async fn lock_session(&self, session_id: &str) -> Option<SessionGuard> {
+        self.sync_session(session_id).await;
+        self.lock_handle(self.get_session(session_id)?).await
+    }
+    
+    /// Lock a session by conversation ID, recording activity
+    ///
+    /// In cluster mode the session is first brought up to date with the cluster.
+    pub async fn lock_session_by_conversation(&self, conversation_id: &str) -> Option<SessionGuard> {
+        self.sync_conversation(conversation_id).await;
+        self.lock_handle(self.get_session_by_conversation(conversation_id)?).await
     }
     
-    /// Get a session by conversation ID
-    pub fn get_session_by_conversation(&self, conversation_id: &str) -> Option<&Session> {
-        self.conversation_to_session
-            .get(conversation_id)
-            .and_then(|session_id| self.sessions.get(session_id))
+    async fn lock_handle(&self, handle: SessionHandle) -> Option<SessionGuard> {
+        let mut session = handle.lock_owned().await;
+        session.update_activity_time();
+        Some(SessionGuard {
+            session,
+            cluster: self.cluster.clone(),
+        })
     }
     
-    /// Get a mutable reference to a session by conversation ID
-    pub fn get_session_by_conversation_mut(&mut self, conversation_id: &str) -> Option<&mut Session> {
-        let session_id = match self.conversation_to_session.get(conversation_id) {
-            Some(id) => id.clone(),
-            None => return None,
+    /// Bring the local copy of a call's session up to date with the cluster
+    ///
+    /// Loads sessions started on other replicas and drops sessions that ended
+    /// there. Does nothing outside cluster mode.
+    pub async fn sync_conversation(&self, conversation_id: &str) {
+        let Some(cluster) = &self.cluster else {
+            return;
         };
         
-        if let Some(session) = self.sessions.get_mut(&session_id) {
-            session.update_activity_time();
-            Some(session)
-        } else {
-            None
+        let session_id = match self.get_session_id_by_conversation(conversation_id) {
+            Some(session_id) => session_id,
+            None => match cluster.session_for_call(conversation_id).await {
+                Ok(Some(session_id)) => session_id,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!("Failed to look up shared session for call {}: {}", conversation_id, e);
+                    return;
+                }
+            },
+        };
+        self.sync_session(&session_id).await;
+    }
+    
+    /// Bring the local copy of a session up to date with the cluster
+    pub async fn sync_session(&self, session_id: &str) {
+        let Some(cluster) = &self.cluster else {
+            return;
+        };
+        
+        let shared = match cluster.load(session_id).await {
+            Ok(Some(shared)) => shared,
+            // Not shared yet; the local copy, if any, is the latest
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load shared session {}: {}", session_id, e);
+                return;
+            }
+        };
+        
+        let snapshot = match shared.snapshot {
+            Some(snapshot) if !shared.ended => snapshot,
+            _ => {
+                if self.evict_session(session_id).is_some() {
+                    debug!("Dropped local copy of session {} that ended on another replica", session_id);
+                }
+                return;
+            }
+        };
+        
+        match self.get_session(session_id) {
+            Some(handle) => {
+                let mut session = handle.lock().await;
+                if shared.version > session.cluster_version {
+                    session.apply_snapshot(snapshot);
+                    session.cluster_version = shared.version;
+                    debug!("Updated session {} from instance {} (v{})", session_id, shared.instance_id, shared.version);
+                }
+            },
+            None => {
+                let mut session = Session::from_snapshot(snapshot);
+                session.cluster_version = shared.version;
+                self.insert_session(session);
+                info!("Loaded session {} from instance {}", session_id, shared.instance_id);
+            }
+        }
+    }
+    
+    /// Take the messages queued for a session on any replica
+    ///
+    /// Always empty outside cluster mode, where the session's own queue holds them.
+    pub async fn take_shared_messages(&self, session_id: &str) -> Vec<MessageType> {
+        match &self.cluster {
+            Some(cluster) => cluster.take_messages(session_id).await,
+            None => Vec::new(),
+        }
+    }
+    
+    /// Take or renew the lease on a session's WebSocket client
+    ///
+    /// Always succeeds outside cluster mode.
+    pub async fn acquire_client_lease(&self, session_id: &str) -> bool {
+        match &self.cluster {
+            Some(cluster) => cluster.acquire_lease(session_id).await,
+            None => true,
+        }
+    }
+    
+    /// Remove a session from the store, ending it across the cluster
+    pub fn remove_session(&self, session_id: &str) -> Option<SessionHandle> {
+        if let Some(cluster) = self.cluster.clone() {
+            let session_id = session_id.to_string();
+            tokio::spawn(async move {
+                cluster.end(&session_id).await;
+                cluster.release_lease(&session_id).await;
+            });
         }
+        self.remove_local(session_id)
     }
     
-    /// Remove a session from the store
-    pub fn remove_session(&mut self, session_id: &str) -> Option<Session> {
-        if let Some(conversation_id) = self.session_to_conversation.remove(session_id) {
+    /// Remove the local copy of a session, leaving it to the rest of the cluster
+    pub fn evict_session(&self, session_id: &str) -> Option<SessionHandle> {
+        if let Some(cluster) = self.cluster.clone() {
+            let session_id = session_id.to_string();
+            tokio::spawn(async move {
+                cluster.release_lease(&session_id).await;
+            });
+        }
+        self.remove_local(session_id)
+    }
+    
+    fn remove_local(&self, session_id: &str) -> Option<SessionHandle> {
+        if let Some((_, conversation_id)) = self.session_to_conversation.remove(session_id) {
             self.conversation_to_session.remove(&conversation_id);
+            self.turns.remove(&conversation_id);
         }
         
-        self.sessions.remove(session_id)
+        let removed = self.sessions.remove(session_id).map(|(_, session)| session);
+        if removed.is_some() {
+            // No receivers is fine; nobody needs to react to the removal
+            let _ = self.removals.send(session_id.to_string());
+            self.publish_event(session_id, SessionEventKind::Ended);
+        }
+        removed
     }
     
     /// Set mapping between conversation ID and session ID
-    pub fn set_conversation_mapping(&mut self, conversation_id: String, session_id: String) {
+    pub fn set_conversation_mapping(&self, conversation_id: String, session_id: String) {
         self.conversation_to_session.insert(conversation_id.clone(), session_id.clone());
         self.session_to_conversation.insert(session_id, conversation_id);
     }
     
+    /// Handles to all sessions in the store
+    pub fn handles(&self) -> Vec<SessionHandle> {
+        self.sessions.iter().map(|entry| entry.value().clone()).collect()
+    }
+    
+    /// IDs of all sessions in the store
+    pub fn session_ids(&self) -> Vec<String> {
+        self.sessions.iter().map(|entry| entry.key().clone()).collect()
+    }
+    
+    /// Number of sessions in the store
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+    
+    /// Whether the store has no sessions
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+    
+    /// Capture the durable state of every session
+    pub async fn snapshot(&self) -> Vec<SessionSnapshot> {
+        let mut snapshots = Vec::with_capacity(self.len());
+        for handle in self.handles() {
+            snapshots.push(handle.lock().await.snapshot());
+        }
+        snapshots
+    }
+    
+    /// Add sessions from snapshots, skipping any already in the store
+    ///
+    /// Returns the IDs of the restored sessions.
+    pub fn restore(&self, snapshots: Vec<SessionSnapshot>) -> Vec<String> {
+        snapshots
+            .into_iter()
+            .filter(|snapshot| !self.sessions.contains_key(&snapshot.session_id))
+            .map(|snapshot| self.add_session(Session::from_snapshot(snapshot)))
+            .collect()
+    }
+    
     /// Clean up expired sessions
-    pub fn cleanup_expired_sessions(&mut self, max_age: Duration) {
-        let expired_sessions: Vec<String> = self.sessions
+    ///
+    /// When `twilio` gives a client for the session's tenant, a session whose call
+    /// is still live is kept and its idle time restarted, so callers listening
+    /// silently are not cut off. If the call status cannot be fetched the session
+    /// is kept for up to `grace` longer.
+    pub async fn cleanup_expired_sessions(
+        &self,
+        max_age: Duration,
+        grace: Duration,
+        twilio: impl Fn(Option<&str>) -> Option<TwilioClient>,
+    ) {
+        // Sessions that are locked are in use and therefore not expired
+        let expired_sessions: Vec<(String, Option<String>, Option<String>, bool)> = self.sessions
             .iter()
-            .filter(|(_, session)| session.is_expired(max_age))
-            .map(|(id, _)| id.clone())
+            .filter_map(|entry| {
+                let session = entry.value().try_lock().ok()?;
+                session.is_expired(max_age).then(|| (
+                    entry.key().clone(),
+                    session.conversation_id.clone().filter(|_| !session.session_ends),
+                    session.tenant_id.clone(),
+                    session.is_expired(max_age + grace),
+                ))
+            })
             .collect();
         
-        for session_id in expired_sessions {
+        for (session_id, call_sid, tenant_id, past_grace) in expired_sessions {
+            let twilio = call_sid.as_ref().and_then(|_| twilio(tenant_id.as_deref()));
+            if let (Some(twilio), Some(call_sid)) = (twilio, call_sid) {
+                match twilio.fetch_call(&call_sid).await {
+                    Ok(call) if is_live_call_status(&call.status) => {
+                        debug!("Keeping idle session {}: call {} is {}", session_id, call_sid, call.status);
+                        if let Some(handle) = self.get_session(&session_id) {
+                            handle.lock().await.update_activity_time();
+                        }
+                        continue;
+                    },
+                    Ok(_) => {},
+                    Err(e) if !past_grace => {
+                        warn!("Keeping idle session {}: failed to check call {}: {}", session_id, call_sid, e);
+                        continue;
+                    },
+                    Err(_) => {},
+                }
+            }
+            
             info!("Removing expired session: {}", session_id);
-            self.remove_session(&session_id);
+            // Another replica may still be serving the call
+            self.evict_session(&session_id);
         }
+        
+        // Drop idle turn locks of calls that never had a session here
+        self.turns.retain(|conversation_id, lock| {
+            self.conversation_to_session.contains_key(conversation_id) || Arc::strong_count(lock) > 1
+        });
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// Whether a Twilio call status means the call has not ended
+fn is_live_call_status(status: &str) -> bool {
+    matches!(status, "queued" | "ringing" | "in-progress")
+}
+
 /// Start a periodic session cleanup task
-pub fn start_session_cleanup_task(
-    session_store: Arc<tokio::sync::RwLock<SessionStore>>,
-    interval_minutes: u64,
-    max_age_minutes: i64
-) {
+///
+/// Expiry settings are read from the current configuration on every run. Calls
+/// are checked with their tenant's Twilio account.
+pub fn start_session_cleanup_task(session_store: Arc<SessionStore>, tenants: Arc<TenantStore>, config: SharedConfig) {
+    let interval_minutes = config.current().session.cleanup_interval_minutes;
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_minutes * 60));
 
         loop {
             interval.tick().await;
-            let max_age = Duration::minutes(max_age_minutes);
+            let config = config.current();
+            let max_age = Duration::minutes(config.session.max_age_minutes);
+            let grace = Duration::minutes(config.session.expiry_grace_minutes);
+
+            let twilio = |tenant_id: Option<&str>| {
+                if !config.session.check_call_status {
+                    return None;
+                }
+                let tenant = tenant_id.and_then(|id| tenants.get(id));
+                match TwilioClient::for_tenant(tenant.as_ref(), &config.twilio) {
+                    Ok(client) => Some(client),
+                    Err(e) => {
+                        warn!("Failed to create Twilio client, expiring session without checking its call: {}", e);
+                        None
+                    }
+                }
+            };
 
-            // Get write lock without pattern matching
-            let mut store = session_store.write().await;
-            store.cleanup_expired_sessions(max_age);
+            session_store.cleanup_expired_sessions(max_age, grace, twilio).await;
             debug!("Session cleanup completed");
         }
     });
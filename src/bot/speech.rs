@@ -0,0 +1,65 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use dashmap::DashMap;
+use log::warn;
+use regex::Regex;
+
+use crate::config::{UtteranceConfig, UtteranceStrategy};
+
+/// Whether text ends with sentence-ending punctuation
+pub fn ends_with_sentence_punctuation(text: &str) -> bool {
+    text.trim_end().ends_with(['.', '!', '?'])
+}
+
+/// Whether a partial transcription is a complete utterance, worth answering speculatively
+///
+/// `unchanged_for` is how long the partial has stayed the same. `language`
+/// picks the patterns; languages without any fall back to punctuation.
+pub fn is_complete_utterance(
+    text: &str,
+    unchanged_for: Duration,
+    language: Option<&str>,
+    config: &UtteranceConfig,
+) -> bool {
+    match config.strategy {
+        UtteranceStrategy::Punctuation => ends_with_sentence_punctuation(text),
+        UtteranceStrategy::MinTokens => text.split_whitespace().count() >= config.min_tokens,
+        UtteranceStrategy::Silence => unchanged_for >= Duration::from_millis(config.silence_ms),
+        UtteranceStrategy::Patterns => match language_patterns(language, config) {
+            Some(patterns) => matches_any(text.trim(), patterns),
+            None => ends_with_sentence_punctuation(text),
+        },
+    }
+}
+
+/// Patterns for a language, trying the full tag, its primary subtag and then `default`
+fn language_patterns<'a>(language: Option<&str>, config: &'a UtteranceConfig) -> Option<&'a Vec<String>> {
+    let language = language.map(str::to_lowercase);
+    let primary = language.as_deref().and_then(|l| l.split(['-', '_']).next());
+
+    let patterns = [language.as_deref(), primary, Some("default")]
+        .into_iter()
+        .flatten()
+        .find_map(|key| config.patterns.iter().find(|(k, _)| k.to_lowercase() == key).map(|(_, p)| p));
+    patterns
+}
+
+/// Whether any pattern matches, compiling each pattern once
+///
+/// Patterns are cached by their source, so a reloaded configuration only
+/// compiles the patterns it adds.
+fn matches_any(text: &str, patterns: &[String]) -> bool {
+    static COMPILED: OnceLock<DashMap<String, Option<Regex>>> = OnceLock::new();
+    let compiled = COMPILED.get_or_init(DashMap::new);
+
+    patterns.iter().any(|pattern| {
+        let regex = compiled.entry(pattern.clone()).or_insert_with(|| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                warn!("Skipping invalid utterance pattern '{}': {}", pattern, e);
+                None
+            }
+        });
+        regex.as_ref().is_some_and(|regex| regex.is_match(text))
+    })
+}
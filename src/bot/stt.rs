@@ -0,0 +1,321 @@
+use std::fmt;
+use futures::{SinkExt, StreamExt};
+use log::{debug, warn};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::config::{SttConfig, SttProvider};
+
+/// Audio sent to the provider per message: 100ms of 8kHz μ-law, within every provider's limits
+const CHUNK_BYTES: usize = 800;
+
+/// Media Stream frames buffered for the provider before new ones are dropped
+const AUDIO_BUFFER_FRAMES: usize = 500;
+
+const DEEPGRAM_URL: &str = "wss://api.deepgram.com/v1/listen";
+const ASSEMBLYAI_URL: &str = "wss://streaming.assemblyai.com/v3/ws";
+
+/// A transcription result from a streaming provider
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub text: String,
+    /// Whether the caller finished the utterance; otherwise an interim result
+    pub is_final: bool,
+    pub confidence: Option<f64>,
+}
+
+/// Error from a streaming speech recognition provider
+#[derive(Debug)]
+pub enum SttError {
+    /// The connection could not be opened
+    ConnectionFailed(String),
+    /// The provider configuration is unusable
+    InvalidConfig(String),
+}
+
+impl fmt::Display for SttError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SttError::ConnectionFailed(msg) => write!(f, "STT connection failed: {}", msg),
+            SttError::InvalidConfig(msg) => write!(f, "Invalid STT configuration: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SttError {}
+
+/// A live recognition session fed with call audio
+///
+/// Dropping it closes the provider connection.
+pub struct RecognitionStream {
+    audio: mpsc::Sender<Vec<u8>>,
+    transcripts: mpsc::Receiver<Transcript>,
+}
+
+impl RecognitionStream {
+    /// Queue a frame of 8kHz μ-law audio, dropping it if the provider has fallen behind
+    pub fn send_audio(&self, frame: Vec<u8>) {
+        if self.audio.try_send(frame).is_err() {
+            debug!("STT audio buffer full, dropping a frame");
+        }
+    }
+
+    /// Next transcript; `None` once the provider closed the connection
+    pub async fn next_transcript(&mut self) -> Option<Transcript> {
+        self.transcripts.recv().await
+    }
+}
+
+/// A streaming speech recognition provider
+#[rocket::async_trait]
+pub trait SpeechRecognizer: Send + Sync {
+    /// Provider name for logs
+    fn name(&self) -> &'static str;
+
+    /// Open a recognition session for a call in `language`
+    async fn connect(&self, language: Option<&str>) -> Result<RecognitionStream, SttError>;
+}
+
+/// The configured streaming provider, or `None` when Twilio recognizes speech
+pub fn recognizer(config: &SttConfig) -> Option<Box<dyn SpeechRecognizer>> {
+    let api_key = config.api_key.clone().unwrap_or_default();
+
+    match config.provider {
+        SttProvider::Twilio => None,
+        SttProvider::Deepgram => Some(Box::new(DeepgramRecognizer {
+            api_key,
+            model: config.model.clone(),
+            url: config.url.clone().unwrap_or_else(|| DEEPGRAM_URL.to_string()),
+        })),
+        SttProvider::AssemblyAi => Some(Box::new(AssemblyAiRecognizer {
+            api_key,
+            model: config.model.clone(),
+            url: config.url.clone().unwrap_or_else(|| ASSEMBLYAI_URL.to_string()),
+        })),
+    }
+}
+
+/// Deepgram live transcription
+pub struct DeepgramRecognizer {
+    api_key: String,
+    model: Option<String>,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramMessage {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    is_final: bool,
+    #[serde(default)]
+    speech_final: bool,
+    channel: Option<DeepgramChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramChannel {
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramAlternative {
+    transcript: String,
+    confidence: Option<f64>,
+}
+
+#[rocket::async_trait]
+impl SpeechRecognizer for DeepgramRecognizer {
+    fn name(&self) -> &'static str {
+        "deepgram"
+    }
+
+    async fn connect(&self, language: Option<&str>) -> Result<RecognitionStream, SttError> {
+        let mut url = format!(
+            "{}?encoding=mulaw&sample_rate=8000&channels=1&interim_results=true&punctuate=true",
+            self.url
+        );
+        if let Some(model) = &self.model {
+            url.push_str(&format!("&model={}", urlencoding::encode(model)));
+        }
+        if let Some(language) = language {
+            url.push_str(&format!("&language={}", urlencoding::encode(language)));
+        }
+
+        // Finalized segments are collected until Deepgram detects the end of speech
+        let mut segments: Vec<String> = Vec::new();
+        let mut confidence: Option<f64> = None;
+        let parse = move |text: &str| -> Option<Transcript> {
+            let message: DeepgramMessage = serde_json::from_str(text).ok()?;
+            if message.kind != "Results" {
+                return None;
+            }
+            let alternative = message.channel?.alternatives.into_iter().next()?;
+
+            if message.is_final && !alternative.transcript.is_empty() {
+                segments.push(alternative.transcript.clone());
+                // The utterance is only as certain as its least certain segment
+                confidence = match (confidence, alternative.confidence) {
+                    (Some(c), Some(a)) => Some(c.min(a)),
+                    (c, a) => c.or(a),
+                };
+            }
+
+            if message.speech_final && !segments.is_empty() {
+                let text = segments.join(" ");
+                segments.clear();
+                return Some(Transcript { text, is_final: true, confidence: confidence.take() });
+            }
+
+            let mut text = segments.join(" ");
+            if !message.is_final && !alternative.transcript.is_empty() {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&alternative.transcript);
+            }
+            (!text.is_empty()).then_some(Transcript { text, is_final: false, confidence: None })
+        };
+
+        open_stream(&url, &format!("Token {}", self.api_key), r#"{"type":"CloseStream"}"#, parse).await
+    }
+}
+
+/// AssemblyAI universal streaming
+pub struct AssemblyAiRecognizer {
+    api_key: String,
+    model: Option<String>,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssemblyAiMessage {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    transcript: String,
+    #[serde(default)]
+    end_of_turn: bool,
+    #[serde(default)]
+    turn_is_formatted: bool,
+}
+
+#[rocket::async_trait]
+impl SpeechRecognizer for AssemblyAiRecognizer {
+    fn name(&self) -> &'static str {
+        "assemblyai"
+    }
+
+    /// AssemblyAI detects the language itself, so `language` is not sent
+    async fn connect(&self, _language: Option<&str>) -> Result<RecognitionStream, SttError> {
+        let mut url = format!("{}?encoding=pcm_mulaw&sample_rate=8000&format_turns=true", self.url);
+        if let Some(model) = &self.model {
+            url.push_str(&format!("&speech_model={}", urlencoding::encode(model)));
+        }
+
+        // A finished turn is sent again once formatted; only that copy is final
+        let parse = |text: &str| -> Option<Transcript> {
+            let message: AssemblyAiMessage = serde_json::from_str(text).ok()?;
+            if message.kind != "Turn" || message.transcript.is_empty() {
+                return None;
+            }
+            match (message.end_of_turn, message.turn_is_formatted) {
+                (true, true) => Some(Transcript { text: message.transcript, is_final: true, confidence: None }),
+                (true, false) => None,
+                (false, _) => Some(Transcript { text: message.transcript, is_final: false, confidence: None }),
+            }
+        };
+
+        open_stream(&url, &self.api_key, r#"{"type":"Terminate"}"#, parse).await
+    }
+}
+
+/// Connect to a provider's WebSocket and pump audio and transcripts until either side closes
+///
+/// `close_message` asks the provider to flush and close once the call audio ends.
+async fn open_stream<P>(
+    url: &str,
+    authorization: &str,
+    close_message: &'static str,
+    mut parse: P,
+) -> Result<RecognitionStream, SttError>
+where
+    P: FnMut(&str) -> Option<Transcript> + Send + 'static,
+{
+    let mut request = url.into_client_request()
+        .map_err(|e| SttError::InvalidConfig(e.to_string()))?;
+    let authorization = HeaderValue::from_str(authorization)
+        .map_err(|_| SttError::InvalidConfig("API key is not a valid header value".to_string()))?;
+    request.headers_mut().insert("Authorization", authorization);
+
+    let (ws, _) = connect_async(request)
+        .await
+        .map_err(|e| SttError::ConnectionFailed(e.to_string()))?;
+    let (mut sink, mut stream) = ws.split();
+
+    let (audio_tx, mut audio_rx) = mpsc::channel::<Vec<u8>>(AUDIO_BUFFER_FRAMES);
+    let (transcript_tx, transcript_rx) = mpsc::channel(64);
+
+    tokio::spawn(async move {
+        let mut chunk = Vec::with_capacity(CHUNK_BYTES);
+
+        loop {
+            tokio::select! {
+                frame = audio_rx.recv() => match frame {
+                    Some(frame) => {
+                        chunk.extend_from_slice(&frame);
+                        if chunk.len() >= CHUNK_BYTES {
+                            let audio = std::mem::replace(&mut chunk, Vec::with_capacity(CHUNK_BYTES));
+                            if let Err(e) = sink.send(Message::Binary(audio)).await {
+                                warn!("Failed to send audio to STT provider: {}", e);
+                                break;
+                            }
+                        }
+                    },
+                    // The call audio ended; let the provider flush its last results
+                    None => {
+                        if !chunk.is_empty() {
+                            let _ = sink.send(Message::Binary(std::mem::take(&mut chunk))).await;
+                        }
+                        let _ = sink.send(Message::Text(close_message.to_string())).await;
+                        while let Some(Ok(message)) = stream.next().await {
+                            if let Message::Text(text) = message {
+                                if let Some(transcript) = parse(&text) {
+                                    let _ = transcript_tx.send(transcript).await;
+                                }
+                            }
+                        }
+                        break;
+                    },
+                },
+                message = stream.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Some(transcript) = parse(&text) {
+                            if transcript_tx.send(transcript).await.is_err() {
+                                break;
+                            }
+                        }
+                    },
+                    Some(Ok(Message::Close(_))) | None => {
+                        debug!("STT provider closed the connection");
+                        break;
+                    },
+                    Some(Ok(_)) => {},
+                    Some(Err(e)) => {
+                        warn!("STT provider connection error: {}", e);
+                        break;
+                    },
+                },
+            }
+        }
+    });
+
+    Ok(RecognitionStream {
+        audio: audio_tx,
+        transcripts: transcript_rx,
+    })
+}
@@ -0,0 +1,258 @@
+use std::fmt;
+use log::debug;
+use serde_json::json;
+
+use crate::config::{TtsConfig, TtsProvider, TtsVoice};
+
+const ELEVENLABS_URL: &str = "https://api.elevenlabs.io";
+
+/// Encoding of synthesized audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// MP3 for Twilio to play from a URL
+    Mp3,
+    /// Headerless 8kHz μ-law, the format of Media Stream audio
+    Mulaw,
+}
+
+impl AudioFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Mulaw => "audio/basic",
+        }
+    }
+}
+
+/// Error from a text-to-speech provider
+#[derive(Debug)]
+pub enum TtsError {
+    /// The provider could not be reached or rejected the request
+    RequestFailed(String),
+    /// The provider configuration is unusable
+    InvalidConfig(String),
+}
+
+impl fmt::Display for TtsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TtsError::RequestFailed(msg) => write!(f, "TTS request failed: {}", msg),
+            TtsError::InvalidConfig(msg) => write!(f, "Invalid TTS configuration: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TtsError {}
+
+impl From<reqwest::Error> for TtsError {
+    fn from(error: reqwest::Error) -> Self {
+        TtsError::RequestFailed(error.to_string())
+    }
+}
+
+/// Audio of a synthesis, read in chunks as the provider produces it
+pub struct SynthesisStream {
+    pub format: AudioFormat,
+    response: reqwest::Response,
+}
+
+impl SynthesisStream {
+    /// Next chunk of audio; `None` once the synthesis is complete
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, TtsError> {
+        Ok(self.response.chunk().await?.map(|chunk| chunk.to_vec()))
+    }
+}
+
+/// A streaming text-to-speech provider
+#[rocket::async_trait]
+pub trait SpeechSynthesizer: Send + Sync {
+    /// Provider name for logs
+    fn name(&self) -> &'static str;
+
+    /// Start synthesizing plain text or a `<speak>` SSML document
+    ///
+    /// `language` applies when the voice does not name one.
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: &TtsVoice,
+        language: Option<&str>,
+        format: AudioFormat,
+    ) -> Result<SynthesisStream, TtsError>;
+}
+
+/// Builds synthesizers for the configured provider on one shared HTTP client
+///
+/// Kept in managed state, so replies reuse connections to the provider instead
+/// of paying for a TLS handshake each.
+#[derive(Default)]
+pub struct SpeechSynthesis {
+    http: reqwest::Client,
+}
+
+impl SpeechSynthesis {
+    /// The configured provider, or `None` when Twilio speaks
+    pub fn synthesizer(&self, config: &TtsConfig) -> Option<Box<dyn SpeechSynthesizer>> {
+        let api_key = config.api_key.clone().unwrap_or_default();
+
+        match config.provider {
+            TtsProvider::Twilio => None,
+            TtsProvider::ElevenLabs => Some(Box::new(ElevenLabsSynthesizer {
+                api_key,
+                model: config.model.clone(),
+                url: config.url.clone().unwrap_or_else(|| ELEVENLABS_URL.to_string()),
+                http: self.http.clone(),
+            })),
+            TtsProvider::Azure => Some(Box::new(AzureSynthesizer {
+                api_key,
+                url: config.url.clone()
+                    .unwrap_or_else(|| format!("https://{}.tts.speech.microsoft.com", config.region)),
+                http: self.http.clone(),
+            })),
+        }
+    }
+}
+
+/// ElevenLabs streaming text-to-speech
+pub struct ElevenLabsSynthesizer {
+    api_key: String,
+    model: Option<String>,
+    url: String,
+    http: reqwest::Client,
+}
+
+#[rocket::async_trait]
+impl SpeechSynthesizer for ElevenLabsSynthesizer {
+    fn name(&self) -> &'static str {
+        "elevenlabs"
+    }
+
+    /// ElevenLabs does not read SSML, so markup is dropped
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: &TtsVoice,
+        _language: Option<&str>,
+        format: AudioFormat,
+    ) -> Result<SynthesisStream, TtsError> {
+        let output_format = match format {
+            AudioFormat::Mp3 => "mp3_22050_32",
+            AudioFormat::Mulaw => "ulaw_8000",
+        };
+        let url = format!(
+            "{}/v1/text-to-speech/{}/stream?output_format={}",
+            self.url, urlencoding::encode(&voice.id), output_format
+        );
+
+        let mut body = json!({ "text": strip_markup(text) });
+        if let Some(model) = &self.model {
+            body["model_id"] = json!(model);
+        }
+
+        debug!("Synthesizing {} characters with ElevenLabs voice {}", text.len(), voice.id);
+        let response = self.http.post(&url)
+            .header("xi-api-key", &self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+        stream_response(response, format).await
+    }
+}
+
+/// Azure Speech text-to-speech
+pub struct AzureSynthesizer {
+    api_key: String,
+    url: String,
+    http: reqwest::Client,
+}
+
+#[rocket::async_trait]
+impl SpeechSynthesizer for AzureSynthesizer {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    async fn synthesize(
+        &self,
+        text: &str,
+        voice: &TtsVoice,
+        language: Option<&str>,
+        format: AudioFormat,
+    ) -> Result<SynthesisStream, TtsError> {
+        let output_format = match format {
+            AudioFormat::Mp3 => "audio-24khz-48kbitrate-mono-mp3",
+            AudioFormat::Mulaw => "raw-8khz-8bit-mono-mulaw",
+        };
+        let language = voice.language.as_deref().or(language).unwrap_or("en-US");
+        let content = match ssml_body(text) {
+            Some(ssml) => ssml.to_string(),
+            None => escape_xml(text),
+        };
+        let ssml = format!(
+            "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"{}\"><voice name=\"{}\">{}</voice></speak>",
+            escape_xml(language), escape_xml(&voice.id), content
+        );
+
+        debug!("Synthesizing {} characters with Azure voice {}", text.len(), voice.id);
+        let response = self.http.post(format!("{}/cognitiveservices/v1", self.url))
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .header("X-Microsoft-OutputFormat", output_format)
+            .header(reqwest::header::CONTENT_TYPE, "application/ssml+xml")
+            .header(reqwest::header::USER_AGENT, "twilio-bot")
+            .body(ssml)
+            .send()
+            .await?;
+        stream_response(response, format).await
+    }
+}
+
+/// Hand out a provider response's body as it arrives, or its error
+async fn stream_response(response: reqwest::Response, format: AudioFormat) -> Result<SynthesisStream, TtsError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status.as_u16() {
+            401 | 403 => TtsError::InvalidConfig(format!("provider rejected the API key: {}", body)),
+            _ => TtsError::RequestFailed(format!("{}: {}", status, body)),
+        });
+    }
+
+    Ok(SynthesisStream { format, response })
+}
+
+/// The markup inside a `<speak>` document, if the text is one
+fn ssml_body(text: &str) -> Option<&str> {
+    text.trim()
+        .strip_prefix("<speak>")?
+        .strip_suffix("</speak>")
+}
+
+/// Text of a `<speak>` document without its tags
+fn strip_markup(text: &str) -> String {
+    let Some(body) = ssml_body(text) else {
+        return text.to_string();
+    };
+
+    let mut plain = String::with_capacity(body.len());
+    let mut in_tag = false;
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => plain.push(c),
+            _ => {},
+        }
+    }
+    plain.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
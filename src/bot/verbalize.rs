@@ -0,0 +1,665 @@
+use std::sync::OnceLock;
+use regex::{Captures, Regex};
+
+use crate::bot::postprocess::{number_to_words, MAX_SPOKEN_NUMBER};
+
+/// Language amounts, dates, times and numbers are spoken in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Locale {
+    English,
+    Spanish,
+    French,
+    German,
+}
+
+const ES_UNITS: [&str; 30] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+    "diez", "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete", "dieciocho", "diecinueve",
+    "veinte", "veintiuno", "veintidós", "veintitrés", "veinticuatro", "veinticinco", "veintiséis", "veintisiete",
+    "veintiocho", "veintinueve",
+];
+const ES_TENS: [&str; 10] = ["", "", "", "treinta", "cuarenta", "cincuenta", "sesenta", "setenta", "ochenta", "noventa"];
+const ES_HUNDREDS: [&str; 10] = [
+    "", "ciento", "doscientos", "trescientos", "cuatrocientos", "quinientos", "seiscientos", "setecientos",
+    "ochocientos", "novecientos",
+];
+
+const FR_UNITS: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+    "dix", "onze", "douze", "treize", "quatorze", "quinze", "seize",
+];
+const FR_TENS: [&str; 7] = ["", "", "vingt", "trente", "quarante", "cinquante", "soixante"];
+
+const DE_UNITS: [&str; 20] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+    "zehn", "elf", "zwölf", "dreizehn", "vierzehn", "fünfzehn", "sechzehn", "siebzehn", "achtzehn", "neunzehn",
+];
+const DE_TENS: [&str; 10] = ["", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig"];
+
+const EN_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+const ES_MONTHS: [&str; 12] = [
+    "enero", "febrero", "marzo", "abril", "mayo", "junio",
+    "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre",
+];
+const FR_MONTHS: [&str; 12] = [
+    "janvier", "février", "mars", "avril", "mai", "juin",
+    "juillet", "août", "septembre", "octobre", "novembre", "décembre",
+];
+const DE_MONTHS: [&str; 12] = [
+    "Januar", "Februar", "März", "April", "Mai", "Juni",
+    "Juli", "August", "September", "Oktober", "November", "Dezember",
+];
+
+/// Currency of an amount, by its symbol or ISO code
+#[derive(Debug, Clone, Copy)]
+enum Currency {
+    Dollar,
+    Euro,
+    Pound,
+}
+
+/// Spoken names of a currency's units in one language
+struct CurrencyNames {
+    singular: &'static str,
+    plural: &'static str,
+    minor_singular: &'static str,
+    minor_plural: &'static str,
+    /// Whether the major unit takes the feminine form of "one"
+    feminine: bool,
+}
+
+impl Currency {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "$" | "USD" => Some(Currency::Dollar),
+            "€" | "EUR" => Some(Currency::Euro),
+            "£" | "GBP" => Some(Currency::Pound),
+            _ => None,
+        }
+    }
+
+    fn names(self, locale: Locale) -> CurrencyNames {
+        let (singular, plural, minor_singular, minor_plural, feminine) = match (locale, self) {
+            (Locale::English, Currency::Dollar) => ("dollar", "dollars", "cent", "cents", false),
+            (Locale::English, Currency::Euro) => ("euro", "euros", "cent", "cents", false),
+            (Locale::English, Currency::Pound) => ("pound", "pounds", "penny", "pence", false),
+            (Locale::Spanish, Currency::Dollar) => ("dólar", "dólares", "centavo", "centavos", false),
+            (Locale::Spanish, Currency::Euro) => ("euro", "euros", "céntimo", "céntimos", false),
+            (Locale::Spanish, Currency::Pound) => ("libra", "libras", "penique", "peniques", true),
+            (Locale::French, Currency::Dollar) => ("dollar", "dollars", "cent", "cents", false),
+            (Locale::French, Currency::Euro) => ("euro", "euros", "centime", "centimes", false),
+            (Locale::French, Currency::Pound) => ("livre", "livres", "penny", "pence", true),
+            (Locale::German, Currency::Dollar) => ("Dollar", "Dollar", "Cent", "Cent", false),
+            (Locale::German, Currency::Euro) => ("Euro", "Euro", "Cent", "Cent", false),
+            (Locale::German, Currency::Pound) => ("Pfund", "Pfund", "Penny", "Pence", false),
+        };
+        CurrencyNames { singular, plural, minor_singular, minor_plural, feminine }
+    }
+}
+
+impl Locale {
+    /// Locale of a language tag such as `es-MX`; English when the language is unsupported
+    fn from_language(language: Option<&str>) -> Self {
+        let primary = language.unwrap_or_default()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match primary.as_str() {
+            "es" => Locale::Spanish,
+            "fr" => Locale::French,
+            "de" => Locale::German,
+            _ => Locale::English,
+        }
+    }
+
+    fn cardinal(self, n: u64) -> String {
+        match self {
+            Locale::English => number_to_words(n),
+            Locale::Spanish => spanish(n),
+            Locale::French => french(n),
+            Locale::German => german(n),
+        }
+    }
+
+    /// Cardinal counting a noun, agreeing with its gender where the language needs it
+    fn counting(self, n: u64, feminine: bool) -> String {
+        let words = self.cardinal(n);
+        match self {
+            Locale::English => words,
+            Locale::Spanish if feminine => replace_suffix(words, "uno", "una"),
+            Locale::Spanish => match words.strip_suffix("veintiuno") {
+                Some(stem) => format!("{}veintiún", stem),
+                None => replace_suffix(words, "uno", "un"),
+            },
+            Locale::French if feminine => replace_suffix(words, "un", "une"),
+            Locale::French => words,
+            Locale::German => replace_suffix(words, "eins", "ein"),
+        }
+    }
+
+    /// `n` followed by the singular or plural noun
+    fn count(self, n: u64, singular: &str, plural: &str, feminine: bool) -> String {
+        let is_plural = match self {
+            Locale::French => n > 1,
+            _ => n != 1,
+        };
+        let noun = if is_plural { plural } else { singular };
+        // Whole millions take "de" before the noun: "un millón de dólares"
+        let whole_millions = n >= 1_000_000 && n.is_multiple_of(1_000_000);
+        let noun = match self {
+            Locale::French if whole_millions && noun.starts_with(['a', 'e', 'i', 'o', 'u']) => format!("d'{}", noun),
+            Locale::Spanish | Locale::French if whole_millions => format!("de {}", noun),
+            _ => noun.to_string(),
+        };
+        format!("{} {}", self.counting(n, feminine), noun)
+    }
+
+    /// Word joining whole and fractional currency units
+    fn and(self) -> &'static str {
+        match self {
+            Locale::English => "and",
+            Locale::Spanish => "con",
+            Locale::French => "et",
+            Locale::German => "und",
+        }
+    }
+
+    /// Word read at the decimal separator
+    fn point(self) -> &'static str {
+        match self {
+            Locale::English => "point",
+            Locale::Spanish => "coma",
+            Locale::French => "virgule",
+            Locale::German => "Komma",
+        }
+    }
+
+    fn percent(self) -> &'static str {
+        match self {
+            Locale::English => "percent",
+            Locale::Spanish => "por ciento",
+            Locale::French => "pour cent",
+            Locale::German => "Prozent",
+        }
+    }
+
+    /// Decimal separator of written numbers; the other of `.` and `,` groups thousands
+    fn decimal_separator(self) -> char {
+        match self {
+            Locale::English => '.',
+            _ => ',',
+        }
+    }
+
+    fn month(self, month: usize) -> &'static str {
+        let months = match self {
+            Locale::English => &EN_MONTHS,
+            Locale::Spanish => &ES_MONTHS,
+            Locale::French => &FR_MONTHS,
+            Locale::German => &DE_MONTHS,
+        };
+        months[month - 1]
+    }
+
+    fn date(self, year: u64, month: usize, day: u64) -> String {
+        match self {
+            Locale::English => format!("{} {}, {}", self.month(month), english_ordinal(day), english_year(year)),
+            Locale::Spanish => {
+                let day = if day == 1 { "primero".to_string() } else { spanish(day) };
+                format!("{} de {} de {}", day, self.month(month), spanish(year))
+            },
+            Locale::French => {
+                let day = if day == 1 { "premier".to_string() } else { french(day) };
+                format!("{} {} {}", day, self.month(month), french(year))
+            },
+            Locale::German => format!("{} {} {}", german_ordinal(day), self.month(month), german_year(year)),
+        }
+    }
+
+    /// Clock time; `pm` is the AM/PM marker written after the time, if any
+    fn time(self, hour: u64, minute: u64, pm: Option<bool>) -> String {
+        if self == Locale::English {
+            let (hour, period) = match (hour, pm) {
+                (0, None) => (12, Some("AM")),
+                (13.., None) => (hour - 12, Some("PM")),
+                (_, None) => (hour, None),
+                (_, Some(pm)) => (hour, Some(if pm { "PM" } else { "AM" })),
+            };
+            let minutes = match (minute, period) {
+                (0, Some(_)) => String::new(),
+                (0, None) => "o'clock".to_string(),
+                (1..=9, _) => format!("oh {}", number_to_words(minute)),
+                _ => number_to_words(minute),
+            };
+            return [number_to_words(hour), minutes, period.unwrap_or_default().to_string()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+
+        // Other languages read the 24-hour clock
+        let hour = match pm {
+            Some(true) if hour < 12 => hour + 12,
+            Some(false) if hour == 12 => 0,
+            _ => hour,
+        };
+        let minutes = (minute > 0).then(|| self.cardinal(minute));
+        match (self, minutes) {
+            (Locale::Spanish, Some(minutes)) => format!("{} y {}", self.counting(hour, true), minutes),
+            (Locale::Spanish, None) => format!("{} en punto", self.counting(hour, true)),
+            (Locale::French, minutes) => {
+                let hours = self.count(hour, "heure", "heures", true);
+                match minutes {
+                    Some(minutes) => format!("{} {}", hours, minutes),
+                    None => hours,
+                }
+            },
+            (_, Some(minutes)) => format!("{} Uhr {}", self.counting(hour, false), minutes),
+            (_, None) => format!("{} Uhr", self.counting(hour, false)),
+        }
+    }
+
+    /// Digits read one by one, as after a decimal separator
+    fn digits(self, digits: &str) -> String {
+        digits.chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| self.cardinal(d as u64))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// A written number in words, if it is one the voice would misread
+    ///
+    /// Numbers with a leading zero (codes) and numbers too large to speak are
+    /// left as they are.
+    fn number(self, written: &str) -> Option<String> {
+        let decimal = self.decimal_separator();
+        let group = if decimal == '.' { ',' } else { '.' };
+        let (whole, fraction) = match written.split_once(decimal) {
+            Some((whole, fraction)) if fraction.chars().all(|c| c.is_ascii_digit()) => (whole, Some(fraction)),
+            Some(_) => return None,
+            None => (written, None),
+        };
+
+        let mut groups = whole.split(group);
+        let first = groups.next()?;
+        let rest: Vec<&str> = groups.collect();
+        let grouped = !rest.is_empty();
+        if grouped && (first.len() > 3 || rest.iter().any(|g| g.len() != 3)) {
+            return None;
+        }
+        if first.len() > 1 && first.starts_with('0') {
+            return None;
+        }
+        let value: u64 = rest.iter().fold(first.to_string(), |mut all, g| {
+            all.push_str(g);
+            all
+        }).parse().ok()?;
+        if value > MAX_SPOKEN_NUMBER {
+            return None;
+        }
+
+        let mut words = self.cardinal(value);
+        if let Some(fraction) = fraction.filter(|f| !f.is_empty()) {
+            words = format!("{} {} {}", words, self.point(), self.digits(fraction));
+        }
+        Some(words)
+    }
+
+    /// A currency amount in words
+    fn amount(self, currency: Currency, units: u64, cents: u64) -> String {
+        let names = currency.names(self);
+        let major = self.count(units, names.singular, names.plural, names.feminine);
+        let minor = self.count(cents, names.minor_singular, names.minor_plural, false);
+        match (units, cents) {
+            (_, 0) => major,
+            (0, _) => minor,
+            _ => format!("{} {} {}", major, self.and(), minor),
+        }
+    }
+}
+
+/// Rewrite numbers, currency amounts, dates and times as words in the session's language
+///
+/// Backend replies often carry figures like "$42.50" or "2024-03-05" that
+/// Twilio voices read poorly outside English. English, Spanish, French and
+/// German are supported; other languages are read as English.
+pub fn verbalize(text: &str, language: Option<&str>) -> String {
+    let locale = Locale::from_language(language);
+    let text = amounts(text, locale);
+    let text = dates(&text, locale);
+    let text = times(&text, locale);
+    let text = percentages(&text, locale);
+    numbers(&text, locale)
+}
+
+fn amounts(text: &str, locale: Locale) -> String {
+    static SYMBOL_FIRST: OnceLock<Regex> = OnceLock::new();
+    static SYMBOL_LAST: OnceLock<Regex> = OnceLock::new();
+    let symbol_first = SYMBOL_FIRST.get_or_init(|| Regex::new(
+        r"(?P<currency>[$€£]|\b(?:USD|EUR|GBP)\b) ?(?P<units>\d{1,3}(?:[.,]\d{3})+|\d+)(?:[.,](?P<cents>\d{1,2}))?\b"
+    ).expect("valid amount pattern"));
+    let symbol_last = SYMBOL_LAST.get_or_init(|| Regex::new(
+        r"\b(?P<units>\d{1,3}(?:[.,]\d{3})+|\d+)(?:[.,](?P<cents>\d{1,2}))? ?(?P<currency>[$€£]|(?:USD|EUR|GBP)\b)"
+    ).expect("valid amount pattern"));
+
+    let speak = |captures: &Captures| {
+        let units: Option<u64> = captures["units"].replace(['.', ','], "").parse().ok();
+        let cents = captures.name("cents").map_or(0, |cents| match cents.as_str() {
+            digit if digit.len() == 1 => digit.parse::<u64>().unwrap_or_default() * 10,
+            digits => digits.parse().unwrap_or_default(),
+        });
+        match (units.filter(|u| *u <= MAX_SPOKEN_NUMBER), Currency::from_symbol(&captures["currency"])) {
+            (Some(units), Some(currency)) => locale.amount(currency, units, cents),
+            _ => captures[0].to_string(),
+        }
+    };
+
+    let text = symbol_first.replace_all(text, speak);
+    symbol_last.replace_all(&text, speak).into_owned()
+}
+
+/// ISO dates, and numeric dates read month first in English and day first otherwise
+fn dates(text: &str, locale: Locale) -> String {
+    static ISO: OnceLock<Regex> = OnceLock::new();
+    static NUMERIC: OnceLock<Regex> = OnceLock::new();
+    let iso = ISO.get_or_init(|| Regex::new(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b").expect("valid date pattern"));
+    let numeric = NUMERIC.get_or_init(|| Regex::new(r"\b(\d{1,2})([/.])(\d{1,2})[/.](\d{4})\b").expect("valid date pattern"));
+
+    let speak = |year: &str, month: &str, day: &str| {
+        let (year, month, day) = (year.parse().ok()?, month.parse::<usize>().ok()?, day.parse().ok()?);
+        ((1..=12).contains(&month) && (1..=31).contains(&day)).then(|| locale.date(year, month, day))
+    };
+
+    let text = iso.replace_all(text, |captures: &Captures| {
+        speak(&captures[1], &captures[2], &captures[3]).unwrap_or_else(|| captures[0].to_string())
+    });
+    numeric.replace_all(&text, |captures: &Captures| {
+        let month_first = locale == Locale::English && &captures[2] == "/";
+        let (month, day) = if month_first { (&captures[1], &captures[3]) } else { (&captures[3], &captures[1]) };
+        speak(&captures[4], month, day).unwrap_or_else(|| captures[0].to_string())
+    }).into_owned()
+}
+
+fn times(text: &str, locale: Locale) -> String {
+    static TIME: OnceLock<Regex> = OnceLock::new();
+    let time = TIME.get_or_init(|| Regex::new(
+        r"\b([01]?\d|2[0-3]):([0-5]\d)\b(?: ?([AaPp])\.?[Mm]\b\.?)?"
+    ).expect("valid time pattern"));
+
+    time.replace_all(text, |captures: &Captures| {
+        let hour: u64 = captures[1].parse().unwrap_or_default();
+        let minute: u64 = captures[2].parse().unwrap_or_default();
+        let pm = captures.get(3).map(|marker| marker.as_str().eq_ignore_ascii_case("p"));
+        if pm.is_some() && !(1..=12).contains(&hour) {
+            return captures[0].to_string();
+        }
+        locale.time(hour, minute, pm)
+    }).into_owned()
+}
+
+fn percentages(text: &str, locale: Locale) -> String {
+    static PERCENT: OnceLock<Regex> = OnceLock::new();
+    let percent = PERCENT.get_or_init(|| Regex::new(r"\b(\d+(?:[.,]\d+)?) ?%").expect("valid percent pattern"));
+
+    percent.replace_all(text, |captures: &Captures| match locale.number(&captures[1]) {
+        Some(words) => format!("{} {}", words, locale.percent()),
+        None => captures[0].to_string(),
+    }).into_owned()
+}
+
+/// Remaining numbers, except parts of phone numbers and other digit sequences joined by dashes or spaces
+fn numbers(text: &str, locale: Locale) -> String {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    let number = NUMBER.get_or_init(|| Regex::new(r"\b\d+(?:[.,]\d+)*\b").expect("valid number pattern"));
+
+    number.replace_all(text, |captures: &Captures| {
+        let written = captures.get(0).expect("whole match");
+        let before = &text[..written.start()];
+        let after = &text[written.end()..];
+        let joined = before.ends_with('+')
+            || before.strip_suffix(['-', ' ']).is_some_and(|b| b.ends_with(|c: char| c.is_ascii_digit()))
+            || after.strip_prefix(['-', ' ']).is_some_and(|a| a.starts_with(|c: char| c.is_ascii_digit()));
+        if joined {
+            return written.as_str().to_string();
+        }
+        locale.number(written.as_str()).unwrap_or_else(|| written.as_str().to_string())
+    }).into_owned()
+}
+
+fn replace_suffix(words: String, suffix: &str, replacement: &str) -> String {
+    match words.strip_suffix(suffix) {
+        Some(stem) => format!("{}{}", stem, replacement),
+        None => words,
+    }
+}
+
+/// Join a scale word with the words of the remainder, if any
+fn join_rest(scale: String, rest: u64, words: fn(u64) -> String) -> String {
+    match rest {
+        0 => scale,
+        rest => format!("{} {}", scale, words(rest)),
+    }
+}
+
+fn spanish(n: u64) -> String {
+    match n {
+        0..=29 => ES_UNITS[n as usize].to_string(),
+        30..=99 => match n % 10 {
+            0 => ES_TENS[(n / 10) as usize].to_string(),
+            ones => format!("{} y {}", ES_TENS[(n / 10) as usize], ES_UNITS[ones as usize]),
+        },
+        100 => "cien".to_string(),
+        101..=999 => join_rest(ES_HUNDREDS[(n / 100) as usize].to_string(), n % 100, spanish),
+        1_000..=999_999 => {
+            let thousands = match n / 1_000 {
+                1 => "mil".to_string(),
+                count => format!("{} mil", Locale::Spanish.counting(count, false)),
+            };
+            join_rest(thousands, n % 1_000, spanish)
+        },
+        _ => {
+            let millions = match n / 1_000_000 {
+                1 => "un millón".to_string(),
+                count => format!("{} millones", Locale::Spanish.counting(count, false)),
+            };
+            join_rest(millions, n % 1_000_000, spanish)
+        },
+    }
+}
+
+fn french(n: u64) -> String {
+    match n {
+        0..=16 => FR_UNITS[n as usize].to_string(),
+        17..=19 => format!("dix-{}", FR_UNITS[(n - 10) as usize]),
+        20..=69 => match n % 10 {
+            0 => FR_TENS[(n / 10) as usize].to_string(),
+            1 => format!("{} et un", FR_TENS[(n / 10) as usize]),
+            ones => format!("{}-{}", FR_TENS[(n / 10) as usize], FR_UNITS[ones as usize]),
+        },
+        71 => "soixante et onze".to_string(),
+        70..=79 => format!("soixante-{}", french(n - 60)),
+        80 => "quatre-vingts".to_string(),
+        81..=99 => format!("quatre-vingt-{}", french(n - 80)),
+        100..=999 => {
+            let hundreds = match (n / 100, n % 100) {
+                (1, _) => "cent".to_string(),
+                (count, 0) => format!("{} cents", FR_UNITS[count as usize]),
+                (count, _) => format!("{} cent", FR_UNITS[count as usize]),
+            };
+            join_rest(hundreds, n % 100, french)
+        },
+        1_000..=999_999 => {
+            let thousands = match n / 1_000 {
+                1 => "mille".to_string(),
+                // "quatre-vingts" and "cents" lose their plural before "mille"
+                count => {
+                    let words = french(count);
+                    let words = match words.strip_suffix('s') {
+                        Some(stem) if stem.ends_with("vingt") || stem.ends_with("cent") => stem.to_string(),
+                        _ => words,
+                    };
+                    format!("{} mille", words)
+                },
+            };
+            join_rest(thousands, n % 1_000, french)
+        },
+        _ => {
+            let millions = match n / 1_000_000 {
+                1 => "un million".to_string(),
+                count => format!("{} millions", french(count)),
+            };
+            join_rest(millions, n % 1_000_000, french)
+        },
+    }
+}
+
+fn german(n: u64) -> String {
+    // "eins" becomes "ein" inside compounds: einundzwanzig, einhundert
+    let prefix = |n: u64| replace_suffix(german(n), "eins", "ein");
+    let rest = |n: u64| if n == 0 { String::new() } else { german(n) };
+    match n {
+        0..=19 => DE_UNITS[n as usize].to_string(),
+        20..=99 => match n % 10 {
+            0 => DE_TENS[(n / 10) as usize].to_string(),
+            ones => format!("{}und{}", prefix(ones), DE_TENS[(n / 10) as usize]),
+        },
+        100..=999 => format!("{}hundert{}", prefix(n / 100), rest(n % 100)),
+        1_000..=999_999 => format!("{}tausend{}", prefix(n / 1_000), rest(n % 1_000)),
+        _ => {
+            let millions = match n / 1_000_000 {
+                1 => "eine Million".to_string(),
+                count => format!("{} Millionen", german(count)),
+            };
+            join_rest(millions, n % 1_000_000, german)
+        },
+    }
+}
+
+fn english_ordinal(n: u64) -> String {
+    let words = number_to_words(n);
+    let irregular = [
+        ("one", "first"), ("two", "second"), ("three", "third"), ("five", "fifth"),
+        ("eight", "eighth"), ("nine", "ninth"), ("twelve", "twelfth"),
+    ];
+    if let Some((stem, ordinal)) = irregular.iter().find_map(|(cardinal, ordinal)| Some((words.strip_suffix(cardinal)?, ordinal))) {
+        return format!("{}{}", stem, ordinal);
+    }
+    match words.strip_suffix('y') {
+        Some(stem) => format!("{}ieth", stem),
+        None => format!("{}th", words),
+    }
+}
+
+/// Years read in pairs of digits: "nineteen ninety-nine", "twenty twenty-four"
+fn english_year(year: u64) -> String {
+    if !(1100..=9999).contains(&year) || year % 1000 < 10 {
+        return number_to_words(year);
+    }
+    match year % 100 {
+        0 => format!("{} hundred", number_to_words(year / 100)),
+        rest @ 1..=9 => format!("{} oh {}", number_to_words(year / 100), number_to_words(rest)),
+        rest => format!("{} {}", number_to_words(year / 100), number_to_words(rest)),
+    }
+}
+
+/// Day of the month as read after "am" or "vom": "fünften"
+fn german_ordinal(n: u64) -> String {
+    let stem = match n {
+        1 => "ers".to_string(),
+        3 => "drit".to_string(),
+        7 => "sieb".to_string(),
+        8 => "ach".to_string(),
+        2..=19 => german(n),
+        _ => format!("{}s", german(n)),
+    };
+    format!("{}ten", stem)
+}
+
+/// Years before 2000 read in hundreds: "neunzehnhundertneunundneunzig"
+fn german_year(year: u64) -> String {
+    match year {
+        1100..=1999 => format!("{}hundert{}", german(year / 100), match year % 100 {
+            0 => String::new(),
+            rest => german(rest),
+        }),
+        _ => german(year),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(language: &str, cases: &[(&str, &str)]) {
+        for (written, spoken) in cases {
+            assert_eq!(verbalize(written, Some(language)), *spoken, "{} in {}", written, language);
+        }
+    }
+
+    #[test]
+    fn verbalizes_english() {
+        check("en-US", &[
+            ("$42.50", "forty-two dollars and fifty cents"),
+            ("£1", "one pound"),
+            ("05.03.2024", "March fifth, twenty twenty-four"),
+            ("03/05/2024", "March fifth, twenty twenty-four"),
+            ("2024-03-05", "March fifth, twenty twenty-four"),
+            ("14:05", "two oh five PM"),
+            ("9:00 am", "nine AM"),
+            ("12.5%", "twelve point five percent"),
+            ("1,250 points", "one thousand two hundred fifty points"),
+        ]);
+    }
+
+    #[test]
+    fn verbalizes_spanish() {
+        check("es-MX", &[
+            ("21 €", "veintiún euros"),
+            ("$42.50", "cuarenta y dos dólares con cincuenta centavos"),
+            ("05.03.2024", "cinco de marzo de dos mil veinticuatro"),
+            ("01/03/2024", "primero de marzo de dos mil veinticuatro"),
+            ("14:05", "catorce y cinco"),
+            ("1.000.000 €", "un millón de euros"),
+        ]);
+    }
+
+    #[test]
+    fn verbalizes_french() {
+        check("fr-FR", &[
+            ("21 €", "vingt et un euros"),
+            ("05.03.2024", "cinq mars deux mille vingt-quatre"),
+            ("14:05", "quatorze heures cinq"),
+            ("80 €", "quatre-vingts euros"),
+            ("3,5 %", "trois virgule cinq pour cent"),
+        ]);
+    }
+
+    #[test]
+    fn verbalizes_german() {
+        check("de-DE", &[
+            ("21 €", "einundzwanzig Euro"),
+            ("05.03.2024", "fünften März zweitausendvierundzwanzig"),
+            ("14:05", "vierzehn Uhr fünf"),
+            ("1 €", "ein Euro"),
+            ("1.250", "eintausendzweihundertfünfzig"),
+        ]);
+    }
+
+    #[test]
+    fn leaves_phone_numbers_and_codes_alone() {
+        for language in ["en", "es", "fr", "de"] {
+            check(language, &[
+                ("555 123 4567", "555 123 4567"),
+                ("555-123-4567", "555-123-4567"),
+                ("+1 555 123 4567", "+1 555 123 4567"),
+                ("0042", "0042"),
+            ]);
+        }
+    }
+}
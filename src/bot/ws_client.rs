@@ -1,12 +1,28 @@
-use std::sync::Arc;
-use log::{debug, error, info};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio_tungstenite::tungstenite::Message;
-use futures::StreamExt;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::Sender;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
 
+use crate::bot::backend::BackendClient;
+use crate::bot::cdr::HangupSource;
 use crate::bot::session::{MessageType, SessionStore};
+use crate::config::{BackendConfig, Config, SharedConfig, WsTlsConfig};
+use crate::twilio::agent_pool::agent_pool;
+use crate::twilio::client::TwilioClient;
+use crate::twilio::twiml::{create_digits_response, create_hangup_response, create_transfer_response};
 
 /// Message received from the backend WebSocket
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,36 +35,150 @@ pub struct WsMessage {
     /// Optional metadata
     #[serde(default)]
     pub metadata: Value,
+    /// Position of the message in the session's stream, counting from 1, if the backend numbers them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// Numbering the sequence belongs to; a backend that forgot the session starts a new one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<String>,
 }
 
+/// Position in a session's backend message stream, shared with the reader task
+///
+/// A backend that replays messages answers `resume` with a `resumed` message.
+/// If it sends numbered messages without acknowledging, or reports a new
+/// `epoch`, it has started numbering over and the position is reset, so its
+/// messages are not dropped as replays.
+#[derive(Debug, Default)]
+struct StreamPosition {
+    /// Sequence number of the last message received
+    last_seq: u64,
+    /// Numbering the last message belonged to
+    epoch: Option<String>,
+    /// A resume was sent and not acknowledged yet
+    resume_pending: bool,
+}
+
+impl StreamPosition {
+    /// Record the backend's acknowledgement of a resume
+    fn resumed(&mut self, epoch: Option<&str>, session_id: &str) {
+        self.resume_pending = false;
+        self.enter_epoch(epoch, session_id);
+    }
+    
+    /// Whether a numbered message is new, advancing the position if it is
+    fn accept(&mut self, seq: u64, epoch: Option<&str>, session_id: &str) -> bool {
+        self.enter_epoch(epoch, session_id);
+        if self.resume_pending {
+            self.resume_pending = false;
+            if self.last_seq > 0 {
+                warn!(
+                    "Backend did not acknowledge resuming session {}, resetting its sequence from {}",
+                    session_id, self.last_seq
+                );
+                self.last_seq = 0;
+            }
+        }
+        
+        if seq <= self.last_seq {
+            return false;
+        }
+        self.last_seq = seq;
+        true
+    }
+    
+    fn enter_epoch(&mut self, epoch: Option<&str>, session_id: &str) {
+        let Some(epoch) = epoch else {
+            return;
+        };
+        if self.epoch.as_deref() == Some(epoch) {
+            return;
+        }
+        if self.epoch.is_some() || self.last_seq > 0 {
+            info!(
+                "Backend started epoch {} for session {}, resetting its sequence from {}",
+                epoch, session_id, self.last_seq
+            );
+        }
+        self.epoch = Some(epoch.to_string());
+        self.last_seq = 0;
+    }
+}
+
+/// Outbound call requested by the backend over a session's WebSocket
+#[derive(Debug, Clone)]
+pub struct CallRequest {
+    /// Session whose connection carried the request
+    pub session_id: String,
+    /// Call parameters from the message metadata
+    pub parameters: Value,
+}
+
+/// Write half of a backend WebSocket connection
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// Clients younger than this are never swept, since outbound calls create the
+/// client before their session is added to the store
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(120);
+
 /// WebSocket client for a session
 pub struct WebSocketClient {
     /// Session ID
     pub session_id: String,
     /// WebSocket URL
     pub ws_url: String,
+    /// Bot whose backend the session is bound to; `None` for the default backend
+    pub bot: Option<String>,
     /// Whether the client is connected
     pub connected: bool,
     /// Last reconnect attempt time
     pub last_reconnect_attempt: std::time::Instant,
     /// Number of consecutive connection failures
     pub consecutive_failures: usize,
+    /// When the client was created
+    pub created_at: Instant,
+    /// Connections opened so far; every one after the first resumes the session
+    connections: u32,
+    /// Position in the backend's message stream, shared with the reader task
+    position: Arc<Mutex<StreamPosition>>,
+    /// Write half of the connection, used to close it
+    sink: Option<WsSink>,
+    /// Reader and heartbeat tasks for the current connection
+    tasks: Vec<JoinHandle<()>>,
+    /// Channel for outbound calls requested by the backend
+    call_requests: Sender<CallRequest>,
+    /// Configuration for acting on the session's call
+    config: SharedConfig,
 }
 
 impl WebSocketClient {
     /// Create a new WebSocket client
-    pub fn new(session_id: String, ws_url: String) -> Self {
+    pub fn new(
+        session_id: String,
+        ws_url: String,
+        bot: Option<String>,
+        call_requests: Sender<CallRequest>,
+        config: SharedConfig,
+    ) -> Self {
         WebSocketClient {
             session_id,
             ws_url,
+            bot,
             connected: false,
             last_reconnect_attempt: std::time::Instant::now(),
             consecutive_failures: 0,
+            created_at: Instant::now(),
+            connections: 0,
+            position: Arc::new(Mutex::new(StreamPosition::default())),
+            sink: None,
+            tasks: Vec::new(),
+            call_requests,
+            config,
         }
     }
     
     /// Check if the client is connected and reconnect if needed
-    pub async fn ensure_connected(&mut self, sessions: Arc<RwLock<SessionStore>>) -> bool {
+    pub async fn ensure_connected(&mut self, sessions: Arc<SessionStore>) -> bool {
         if !self.connected {
             // Rate limit reconnect attempts
             let now = std::time::Instant::now();
@@ -74,57 +204,64 @@ impl WebSocketClient {
     }
     
     /// Start the WebSocket client
-    pub async fn start(&mut self, sessions: Arc<RwLock<SessionStore>>) {
+    pub async fn start(&mut self, sessions: Arc<SessionStore>) {
         const MAX_RECONNECT_ATTEMPTS: usize = 5;
         
         let url = format!("{}?session_id={}", self.ws_url, self.session_id);
         info!("Connecting to WebSocket server at {}", url);
         
-        match tokio_tungstenite::connect_async(&url).await {
-            Ok((ws_stream, _)) => {
+        let connection = match self.config.current().backend.profile(self.bot.as_deref()) {
+            Some(backend) => connect(&url, &backend).await,
+            None => Err(format!("Unknown bot '{}'", self.bot.as_deref().unwrap_or_default())),
+        };
+        match connection {
+            Ok(ws_stream) => {
                 info!("Connected to WebSocket server for session {}", self.session_id);
                 self.connected = true;
                 self.consecutive_failures = 0;
                 
-                // Split the WebSocket stream - we only need the read part
-                let (_, read) = ws_stream.split();
+                // Split the WebSocket stream, keeping the write half to close it later
+                let (write, read) = ws_stream.split();
+                self.stop_tasks();
+                self.sink = Some(write);
+                
+                // The backend may have restarted and forgotten the session since the last connection
+                if self.connections > 0 {
+                    self.resume().await;
+                }
+                self.connections += 1;
                 
                 // Clone sessions for async tasks
                 let sessions_clone = sessions.clone();
                 let session_id_clone = self.session_id.clone();
+                let call_requests = self.call_requests.clone();
+                let config = self.config.clone();
+                let position = self.position.clone();
                 
                 // Spawn task for receiving messages
                 let mut reader = read;
-                tokio::spawn(async move {
+                let reader_task = tokio::spawn(async move {
                     while let Some(msg_result) = reader.next().await {
                         match msg_result {
                             Ok(msg) => {
                                 if let Message::Text(text) = msg {
-                                    debug!("Received WebSocket message: {}", text);
+                                    debug!("Received WebSocket message: {}", config.current().redaction.redacted(&text));
                                     
                                     // Parse the message
                                     if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                                        let mut store = sessions_clone.write().await;
-                                        if let Some(session) = store.get_session_mut(&session_id_clone) {
-                                            match ws_msg.r#type.as_str() {
-                                                "message" => {
-                                                    if let Err(e) = session.message_tx.try_send(MessageType::Text(ws_msg.message)) {
-                                                        error!("Failed to forward WebSocket message: {}", e);
-                                                    }
-                                                },
-                                                "eos" => {
-                                                    if let Err(e) = session.message_tx.try_send(MessageType::EndOfStream) {
-                                                        error!("Failed to forward EOS: {}", e);
-                                                    }
-                                                },
-                                                "timeout" => {
-                                                    if let Err(e) = session.message_tx.try_send(MessageType::EndOfConversation) {
-                                                        error!("Failed to forward timeout: {}", e);
-                                                    }
-                                                },
-                                                _ => debug!("Unknown WebSocket message type: {}", ws_msg.r#type),
+                                        if ws_msg.r#type == "resumed" {
+                                            debug!("Backend resumed session {}", session_id_clone);
+                                            position.lock().unwrap().resumed(ws_msg.epoch.as_deref(), &session_id_clone);
+                                            continue;
+                                        }
+                                        // Messages replayed after a resume may already have been handled
+                                        if let Some(seq) = ws_msg.seq {
+                                            if !position.lock().unwrap().accept(seq, ws_msg.epoch.as_deref(), &session_id_clone) {
+                                                debug!("Skipping replayed message {} for session {}", seq, session_id_clone);
+                                                continue;
                                             }
                                         }
+                                        dispatch_message(ws_msg, &session_id_clone, &sessions_clone, &call_requests, &config.current()).await;
                                     }
                                 }
                             },
@@ -136,6 +273,7 @@ impl WebSocketClient {
                     }
                     debug!("WebSocket receiver task ended for session {}", session_id_clone);
                 });
+                self.tasks.push(reader_task);
                 
                 // Start heartbeat
                 self.start_heartbeat().await;
@@ -152,11 +290,37 @@ impl WebSocketClient {
         }
     }
     
+    /// Ask the backend to re-attach the session and replay the messages sent after the last one received
+    ///
+    /// The backend acknowledges with a `resumed` message; see [`StreamPosition`].
+    async fn resume(&mut self) {
+        let (last_seq, epoch) = {
+            let mut position = self.position.lock().unwrap();
+            position.resume_pending = true;
+            (position.last_seq, position.epoch.clone())
+        };
+        let resume = serde_json::json!({
+            "type": "resume",
+            "session_id": self.session_id,
+            "last_seq": last_seq,
+            "epoch": epoch,
+            "replay": true,
+        });
+        
+        let Some(sink) = self.sink.as_mut() else {
+            return;
+        };
+        match sink.send(Message::Text(resume.to_string())).await {
+            Ok(()) => info!("Resuming session {} after message {}", self.session_id, last_seq),
+            Err(e) => warn!("Failed to resume session {}: {}", self.session_id, e),
+        }
+    }
+    
     /// Start a heartbeat to keep the connection alive
-    pub async fn start_heartbeat(&self) {
+    pub async fn start_heartbeat(&mut self) {
         let session_id = self.session_id.clone();
         
-        tokio::spawn(async move {
+        let heartbeat_task = tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
             
             loop {
@@ -166,50 +330,315 @@ impl WebSocketClient {
                 // or a custom keep-alive message depending on the backend protocol
             }
         });
+        self.tasks.push(heartbeat_task);
+    }
+    
+    /// Abort the reader and heartbeat tasks
+    fn stop_tasks(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+    
+    /// Close the connection and stop its tasks
+    pub async fn close(&mut self) {
+        if let Some(mut sink) = self.sink.take() {
+            if let Err(e) = sink.send(Message::Close(None)).await {
+                debug!("Failed to send WebSocket close for session {}: {}", self.session_id, e);
+            }
+        }
+        
+        self.stop_tasks();
+        self.connected = false;
+        debug!("Closed WebSocket client for session {}", self.session_id);
+    }
+    
+    /// Drop the connection without a close handshake, as a network failure would
+    ///
+    /// The client stays registered, so the connection checker reconnects it.
+    pub fn drop_connection(&mut self) {
+        self.sink = None;
+        self.stop_tasks();
+        self.connected = false;
+    }
+}
+
+/// Route a backend WebSocket message to the action its type asks for
+///
+/// Conversation messages are queued on the session; call control messages
+/// act on the session's live call through the Twilio API.
+async fn dispatch_message(
+    ws_msg: WsMessage,
+    session_id: &str,
+    sessions: &SessionStore,
+    call_requests: &Sender<CallRequest>,
+    config: &Config,
+) {
+    match ws_msg.r#type.as_str() {
+        // Call requests are not tied to the session's conversation
+        "call" => {
+            let request = CallRequest {
+                session_id: session_id.to_string(),
+                parameters: ws_msg.metadata,
+            };
+            if let Err(e) = call_requests.try_send(request) {
+                error!("Failed to forward call request: {}", e);
+            }
+        },
+        "message" => forward_message(sessions, session_id, MessageType::Text(ws_msg.message), "WebSocket message").await,
+        "eos" => forward_message(sessions, session_id, MessageType::EndOfStream, "EOS").await,
+        "timeout" => forward_message(sessions, session_id, MessageType::EndOfConversation, "timeout").await,
+        // Calls taken over by an agent are no longer the bot's to control
+        "dtmf" | "transfer" | "hangup" | "update_twiml" if is_taken_over(sessions, session_id).await => {
+            info!("Ignoring {} request for session {}, which an agent took over", ws_msg.r#type, session_id);
+        },
+        "dtmf" => send_digits(&ws_msg, session_id, sessions, config).await,
+        "transfer" => transfer_call(&ws_msg, session_id, sessions, config).await,
+        "hangup" => hang_up_call(&ws_msg, session_id, sessions, config).await,
+        "update_twiml" => update_twiml(&ws_msg, session_id, sessions, config).await,
+        other => debug!("Unknown WebSocket message type: {}", other),
+    }
+}
+
+/// Whether an agent took the session's call over from the bot
+async fn is_taken_over(sessions: &SessionStore, session_id: &str) -> bool {
+    sessions.lock_session(session_id).await.is_some_and(|session| session.takeover.is_some())
+}
+
+/// Queue a message on the session
+async fn forward_message(sessions: &SessionStore, session_id: &str, message: MessageType, label: &str) {
+    // Release the session before waiting so the queue can be drained meanwhile
+    let Some(message_tx) = sessions.lock_session(session_id).await.map(|s| s.message_tx.clone()) else {
+        return;
+    };
+    if message_tx.send(message).await.is_err() {
+        error!("Failed to forward {}: session {} is closed", label, session_id);
+    }
+}
+
+/// Text field of a call control message, from its metadata or else its message
+fn message_field(ws_msg: &WsMessage, key: &str) -> Option<String> {
+    let value = ws_msg.metadata.get(key)
+        .and_then(|value| value.as_str())
+        .unwrap_or(&ws_msg.message)
+        .trim();
+    Some(value.to_string()).filter(|value| !value.is_empty())
+}
+
+/// Play DTMF digits into the call, then keep listening to the caller
+async fn send_digits(ws_msg: &WsMessage, session_id: &str, sessions: &SessionStore, config: &Config) {
+    let Some(digits) = message_field(ws_msg, "digits") else {
+        warn!("Ignoring DTMF request without digits for session {}", session_id);
+        return;
+    };
+    if !digits.chars().all(|c| c.is_ascii_digit() || matches!(c, '*' | '#' | 'w' | 'W')) {
+        warn!("Ignoring invalid DTMF digits '{}' for session {}", digits, session_id);
+        return;
+    }
+
+    let Some((call_sid, twilio)) = sessions.lock_session(session_id).await
+        .and_then(|session| Some((session.conversation_id.clone()?, session.twilio_config(config)))) else {
+        return;
+    };
+
+    info!("Sending DTMF digits to call {}", call_sid);
+    let twiml = create_digits_response(&digits, &twilio, twilio.default_timeout, "auto");
+    update_call(&call_sid, &twiml.build(), config);
+}
+
+/// Transfer the call to a number or SIP address
+async fn transfer_call(ws_msg: &WsMessage, session_id: &str, sessions: &SessionStore, config: &Config) {
+    let Some(target) = message_field(ws_msg, "target") else {
+        warn!("Ignoring transfer request without a target for session {}", session_id);
+        return;
+    };
+    let announcement = ws_msg.metadata.get("announcement").and_then(|a| a.as_str());
+
+    let Some((call_sid, twilio)) = sessions.lock_session(session_id).await.and_then(|mut session| {
+        let call_sid = session.conversation_id.clone()?;
+        session.transfer_target = Some(target.clone());
+        Some((call_sid, session.twilio_config(config)))
+    }) else {
+        return;
+    };
+
+    info!("Transferring call {} to {} at the backend's request", call_sid, target);
+    agent_pool().handed_off(&call_sid);
+    let twiml = create_transfer_response(announcement, &target, &twilio);
+    update_call(&call_sid, &twiml.build(), config);
+}
+
+/// End the call, after an optional goodbye message
+async fn hang_up_call(ws_msg: &WsMessage, session_id: &str, sessions: &SessionStore, config: &Config) {
+    let goodbye = Some(ws_msg.message.trim()).filter(|m| !m.is_empty());
+    let disposition = ws_msg.metadata.get("disposition").and_then(|d| d.as_str());
+
+    let Some((call_sid, twilio)) = sessions.lock_session(session_id).await.and_then(|mut session| {
+        let call_sid = session.conversation_id.clone()?;
+        session.session_ends = true;
+        session.hangup_source = Some(HangupSource::Bot);
+        if let Some(disposition) = disposition {
+            session.disposition = Some(disposition.to_string());
+        }
+        Some((call_sid, session.twilio_config(config)))
+    }) else {
+        return;
+    };
+
+    info!("Hanging up call {} at the backend's request", call_sid);
+    let twiml = create_hangup_response(goodbye, &twilio);
+    update_call(&call_sid, &twiml.build(), config);
+}
+
+/// Replace the call's TwiML with a document from the backend
+async fn update_twiml(ws_msg: &WsMessage, session_id: &str, sessions: &SessionStore, config: &Config) {
+    let twiml = ws_msg.message.trim();
+    if !twiml.starts_with('<') || !twiml.contains("<Response") {
+        warn!("Ignoring TwiML update without a <Response> document for session {}", session_id);
+        return;
+    }
+
+    let Some(call_sid) = sessions.lock_session(session_id).await.and_then(|session| session.conversation_id.clone()) else {
+        return;
+    };
+
+    info!("Updating TwiML of call {} at the backend's request", call_sid);
+    update_call(&call_sid, twiml, config);
+}
+
+/// Replace the TwiML of a live call in the background
+///
+/// Retries can take seconds, so the reader goes on with the next message meanwhile.
+fn update_call(call_sid: &str, twiml: &str, config: &Config) {
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return;
+        }
+    };
+
+    let (call_sid, twiml) = (call_sid.to_string(), twiml.to_string());
+    let (max_retries, base_delay_ms) = (config.backend.retry_attempts, config.backend.retry_base_delay_ms);
+    tokio::spawn(async move {
+        if let Err(e) = twilio_client.update_call_with_retry(&call_sid, &twiml, max_retries, base_delay_ms).await {
+            error!("Failed to update call {}: {}", call_sid, e);
+        }
+    });
+}
+
+/// Open a backend WebSocket with the bearer token, extra headers and TLS settings of the backend
+///
+/// Configured headers take precedence over the bearer token.
+async fn connect(url: &str, backend: &BackendConfig) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, String> {
+    let mut request = url.into_client_request().map_err(|e| e.to_string())?;
+    let headers = request.headers_mut();
+
+    let client = BackendClient::from_config(backend).map_err(|e| e.to_string())?;
+    if let Some(token) = client.bearer_token(None).await.map_err(|e| e.to_string())? {
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| "Backend token is not a valid header value".to_string())?;
+        headers.insert(AUTHORIZATION, value);
+    }
+    for (name, value) in &backend.ws_headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| e.to_string())?;
+        let value = HeaderValue::from_str(value).map_err(|e| e.to_string())?;
+        headers.insert(name, value);
     }
+
+    let connector = tls_connector(&backend.ws_tls)?;
+    let (ws_stream, _) = connect_async_tls_with_config(request, None, connector)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ws_stream)
+}
+
+/// TLS connector trusting the configured root CA and presenting the client certificate
+///
+/// `None` uses the system roots without a client certificate.
+pub fn tls_connector(tls: &WsTlsConfig) -> Result<Option<Connector>, String> {
+    if !tls.is_custom() {
+        return Ok(None);
+    }
+
+    let mut builder = native_tls::TlsConnector::builder();
+    if let Some(ca_cert) = &tls.ca_cert {
+        let certificate = native_tls::Certificate::from_pem(ca_cert.as_bytes()).map_err(|e| e.to_string())?;
+        builder.add_root_certificate(certificate);
+    }
+    if let (Some(cert), Some(key)) = (&tls.client_cert, &tls.client_key) {
+        let identity = native_tls::Identity::from_pkcs8(cert.as_bytes(), key.as_bytes()).map_err(|e| e.to_string())?;
+        builder.identity(identity);
+    }
+    let connector = builder.build().map_err(|e| e.to_string())?;
+    Ok(Some(Connector::NativeTls(connector)))
 }
 
 /// WebSocket client manager
 pub struct WebSocketManager {
     clients: Arc<RwLock<std::collections::HashMap<String, Arc<RwLock<WebSocketClient>>>>>,
+    call_requests: Sender<CallRequest>,
+    config: SharedConfig,
 }
 
 impl WebSocketManager {
-    /// Create a new WebSocket manager
-    pub fn new() -> Self {
+    /// Create a new WebSocket manager forwarding backend call requests to a channel
+    pub fn new(call_requests: Sender<CallRequest>, config: SharedConfig) -> Self {
         WebSocketManager {
             clients: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            call_requests,
+            config,
         }
     }
     
-    /// Get or create a WebSocket client for a session
+    /// Get or create a WebSocket client for a session, connected to its bot's backend
+    ///
+    /// In cluster mode a client is only created while the local replica holds
+    /// the session's lease; returns `None` when another replica owns it.
     pub async fn get_or_create_client(
         &self,
         session_id: &str,
-        ws_url: &str,
-        sessions: Arc<RwLock<SessionStore>>,
-    ) -> Arc<RwLock<WebSocketClient>> {
+        bot: Option<&str>,
+        sessions: Arc<SessionStore>,
+    ) -> Option<Arc<RwLock<WebSocketClient>>> {
         let clients_read = self.clients.read().await;
         
         if let Some(client) = clients_read.get(session_id) {
-            return client.clone();
+            return Some(client.clone());
         }
         
         // Release read lock before acquiring write lock
         drop(clients_read);
         
+        if !sessions.acquire_client_lease(session_id).await {
+            debug!("WebSocket client for session {} is owned by another instance", session_id);
+            return None;
+        }
+        
         // Acquire write lock to create a new client
         let mut clients_write = self.clients.write().await;
         
         // Check again in case another thread created the client
         if let Some(client) = clients_write.get(session_id) {
-            return client.clone();
+            return Some(client.clone());
         }
         
         // Create a new client
+        let Some(backend) = self.config.current().backend.profile(bot) else {
+            error!("No backend for bot '{}' of session {}", bot.unwrap_or_default(), session_id);
+            return None;
+        };
         let client = WebSocketClient::new(
             session_id.to_string(),
-            ws_url.to_string(),
+            backend.ws_url,
+            bot.map(|b| b.to_string()),
+            self.call_requests.clone(),
+            self.config.clone(),
         );
         
         let client_arc = Arc::new(RwLock::new(client));
@@ -224,17 +653,138 @@ impl WebSocketManager {
             client.start(sessions_clone).await;
         });
         
-        client_arc
+        Some(client_arc)
     }
     
-    /// Remove a client
+    /// Remove a client, closing its connection
     pub async fn remove_client(&self, session_id: &str) {
-        let mut clients = self.clients.write().await;
-        clients.remove(session_id);
+        let client = self.clients.write().await.remove(session_id);
+        
+        if let Some(client) = client {
+            client.write().await.close().await;
+            info!("Removed WebSocket client for session {}", session_id);
+        }
+    }
+    
+    /// Drop the connections of one session's client, or of every client
+    ///
+    /// Returns the number of connections dropped.
+    pub async fn drop_connections(&self, session_id: Option<&str>) -> usize {
+        let clients = self.clients.read().await;
+        let mut dropped = 0;
+        
+        for (id, client) in clients.iter() {
+            if session_id.is_some_and(|s| s != id) {
+                continue;
+            }
+            let mut client = client.write().await;
+            if client.connected {
+                client.drop_connection();
+                warn!("Dropped WebSocket connection for session {}", id);
+                dropped += 1;
+            }
+        }
+        
+        dropped
+    }
+    
+    /// Remove clients whose session no longer exists
+    pub async fn sweep_orphaned_clients(&self, sessions: &SessionStore) {
+        let orphaned: Vec<String> = {
+            let clients = self.clients.read().await;
+            let mut orphaned = Vec::new();
+            for (session_id, client) in clients.iter() {
+                let is_old = match client.try_read() {
+                    Ok(client) => client.created_at.elapsed() > ORPHAN_GRACE_PERIOD,
+                    Err(_) => false,
+                };
+                if is_old && sessions.get_session(session_id).is_none() {
+                    orphaned.push(session_id.clone());
+                }
+            }
+            orphaned
+        };
+        
+        for session_id in orphaned {
+            warn!("Sweeping orphaned WebSocket client for session {}", session_id);
+            self.remove_client(&session_id).await;
+        }
+    }
+    
+    /// Start a task that removes clients when their session is removed from the store
+    pub fn start_session_removal_listener(self: &Arc<Self>, sessions: Arc<SessionStore>) {
+        let self_clone = self.clone();
+        let mut removals = sessions.subscribe_removals();
+        
+        tokio::spawn(async move {
+            loop {
+                match removals.recv().await {
+                    Ok(session_id) => self_clone.remove_client(&session_id).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        // Sweep for the clients whose removal was missed
+                        warn!("Missed {} session removals", skipped);
+                        self_clone.sweep_orphaned_clients(&sessions).await;
+                    },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+    
+    /// Renew the leases of local clients and adopt sessions whose owner went away
+    ///
+    /// Clients whose lease moved to another replica are closed. Local sessions
+    /// without a client get one if their lease is free.
+    pub async fn renew_leases(&self, sessions: Arc<SessionStore>) {
+        let owned: Vec<String> = self.clients.read().await.keys().cloned().collect();
+        
+        for session_id in &owned {
+            sessions.sync_session(session_id).await;
+            if sessions.get_session(session_id).is_some() && !sessions.acquire_client_lease(session_id).await {
+                info!("Lost lease for session {} to another instance", session_id);
+                self.remove_client(session_id).await;
+            }
+        }
+        
+        for session_id in sessions.session_ids() {
+            if owned.contains(&session_id) {
+                continue;
+            }
+            
+            sessions.sync_session(&session_id).await;
+            let live_bot = match sessions.get_session(&session_id) {
+                Some(handle) => {
+                    let session = handle.lock().await;
+                    (!session.session_ends).then(|| session.bot.clone())
+                },
+                None => None,
+            };
+            let Some(bot) = live_bot else {
+                continue;
+            };
+            if self.get_or_create_client(&session_id, bot.as_deref(), sessions.clone()).await.is_some() {
+                info!("Took over WebSocket client for session {}", session_id);
+            }
+        }
+    }
+    
+    /// Start a periodic lease renewal task for cluster mode
+    pub fn start_lease_renewal(self: &Arc<Self>, sessions: Arc<SessionStore>, lease_ttl_seconds: u64) {
+        let self_clone = self.clone();
+        
+        tokio::spawn(async move {
+            // Renew well before the lease expires
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(lease_ttl_seconds / 3));
+            
+            loop {
+                interval.tick().await;
+                self_clone.renew_leases(sessions.clone()).await;
+            }
+        });
     }
     
     /// Check and reconnect all disconnected clients
-    pub async fn check_connections(&self, sessions: Arc<RwLock<SessionStore>>) {
+    pub async fn check_connections(&self, sessions: Arc<SessionStore>) {
         let clients_read = self.clients.read().await;
         
         for (session_id, client_arc) in clients_read.iter() {
@@ -247,7 +797,7 @@ impl WebSocketManager {
     }
     
     /// Start a periodic connection check task
-    pub fn start_connection_checker(self: &Arc<Self>, sessions: Arc<RwLock<SessionStore>>) {
+    pub fn start_connection_checker(self: &Arc<Self>, sessions: Arc<SessionStore>) {
         let self_clone = self.clone();
         let sessions_clone = sessions.clone();
         
@@ -260,4 +810,40 @@ impl WebSocketManager {
             }
         });
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn skips_replayed_messages() {
+        let mut position = StreamPosition::default();
+        assert!(position.accept(1, None, "s1"));
+        assert!(position.accept(2, None, "s1"));
+        
+        position.resume_pending = true;
+        position.resumed(None, "s1");
+        assert!(!position.accept(2, None, "s1"));
+        assert!(position.accept(3, None, "s1"));
+    }
+    
+    #[test]
+    fn resets_when_a_resume_is_not_acknowledged() {
+        let mut position = StreamPosition::default();
+        assert!(position.accept(5, None, "s1"));
+        
+        position.resume_pending = true;
+        assert!(position.accept(1, None, "s1"));
+        assert!(!position.accept(1, None, "s1"));
+    }
+    
+    #[test]
+    fn resets_on_a_new_epoch() {
+        let mut position = StreamPosition::default();
+        assert!(position.accept(7, Some("a"), "s1"));
+        assert!(!position.accept(7, Some("a"), "s1"));
+        
+        assert!(position.accept(1, Some("b"), "s1"));
+        assert_eq!(position.epoch.as_deref(), Some("b"));
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::bot::backend::{BackendError, BackendRequest, BackendResponse, BackendTransport};
+
+/// Failures injected into backend API requests
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChaosSettings {
+    /// Delay added before each request reaches the backend
+    pub backend_latency_ms: u64,
+    /// Share of requests failed without reaching the backend, from 0 to 1
+    pub backend_error_rate: f64,
+    /// Status of injected failures; 0 fails them as transport errors instead
+    pub backend_error_status: u16,
+}
+
+/// Current chaos settings and what they have injected so far
+#[derive(Debug, Clone, Serialize)]
+pub struct ChaosStatus {
+    #[serde(flatten)]
+    pub settings: ChaosSettings,
+    /// Seconds until the simulated circuit breaker closes again
+    pub circuit_breaker_open_seconds: Option<u64>,
+    pub delayed_requests: u64,
+    pub injected_errors: u64,
+    pub rejected_requests: u64,
+}
+
+/// Runtime failure injection for rehearsing outages in staging
+#[derive(Default)]
+pub struct ChaosControl {
+    settings: Mutex<ChaosSettings>,
+    /// When the simulated circuit breaker closes again
+    breaker_open_until: Mutex<Option<Instant>>,
+    delayed_requests: AtomicU64,
+    injected_errors: AtomicU64,
+    /// Requests rejected by the simulated circuit breaker
+    rejected_requests: AtomicU64,
+}
+
+impl ChaosControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the injected latency and error rate
+    pub fn configure(&self, settings: ChaosSettings) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&settings.backend_error_rate) {
+            return Err("backend_error_rate must be between 0 and 1".to_string());
+        }
+        if settings.backend_error_status != 0 && !(400..600).contains(&settings.backend_error_status) {
+            return Err("backend_error_status must be 0 or an HTTP error status".to_string());
+        }
+
+        warn!(
+            "Chaos settings changed: {}ms backend latency, {:.0}% backend errors",
+            settings.backend_latency_ms,
+            settings.backend_error_rate * 100.0
+        );
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    /// Reject every backend request as if the circuit breaker opened, for `seconds`
+    pub fn trip_circuit_breaker(&self, seconds: u64) {
+        warn!("Chaos circuit breaker tripped for {}s", seconds);
+        *self.breaker_open_until.lock().unwrap() = Some(Instant::now() + Duration::from_secs(seconds));
+    }
+
+    /// Stop injecting failures and close the simulated circuit breaker
+    pub fn reset(&self) {
+        warn!("Chaos settings reset");
+        *self.settings.lock().unwrap() = ChaosSettings::default();
+        *self.breaker_open_until.lock().unwrap() = None;
+    }
+
+    pub fn status(&self) -> ChaosStatus {
+        ChaosStatus {
+            settings: self.settings.lock().unwrap().clone(),
+            circuit_breaker_open_seconds: self.breaker_remaining().map(|d| d.as_secs()),
+            delayed_requests: self.delayed_requests.load(Ordering::Relaxed),
+            injected_errors: self.injected_errors.load(Ordering::Relaxed),
+            rejected_requests: self.rejected_requests.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Time left before the simulated circuit breaker closes, if it is open
+    fn breaker_remaining(&self) -> Option<Duration> {
+        let open_until = (*self.breaker_open_until.lock().unwrap())?;
+        Some(open_until.saturating_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+}
+
+/// Backend transport injecting the failures configured on a `ChaosControl`
+pub struct ChaosTransport<T> {
+    inner: T,
+    chaos: Arc<ChaosControl>,
+}
+
+impl<T: BackendTransport> ChaosTransport<T> {
+    pub fn new(inner: T, chaos: Arc<ChaosControl>) -> Self {
+        ChaosTransport { inner, chaos }
+    }
+}
+
+#[rocket::async_trait]
+impl<T: BackendTransport> BackendTransport for ChaosTransport<T> {
+    async fn send(&self, request: BackendRequest) -> Result<BackendResponse, BackendError> {
+        if self.chaos.breaker_remaining().is_some() {
+            self.chaos.rejected_requests.fetch_add(1, Ordering::Relaxed);
+            return Err(BackendError::CircuitBreakerOpen);
+        }
+
+        let settings = self.chaos.settings.lock().unwrap().clone();
+
+        if settings.backend_latency_ms > 0 {
+            self.chaos.delayed_requests.fetch_add(1, Ordering::Relaxed);
+            let latency = Duration::from_millis(settings.backend_latency_ms);
+            // Latency past the request's timeout fails it the way a slow backend would
+            if latency >= request.timeout {
+                tokio::time::sleep(request.timeout).await;
+                return Err(BackendError::TransportError("Request timed out (injected latency)".to_string()));
+            }
+            tokio::time::sleep(latency).await;
+        }
+
+        // Uniform draw in [0, 1) from a random UUID
+        let draw = (Uuid::new_v4().as_u128() as u64) as f64 / (u64::MAX as f64 + 1.0);
+        if draw < settings.backend_error_rate {
+            self.chaos.injected_errors.fetch_add(1, Ordering::Relaxed);
+            if settings.backend_error_status == 0 {
+                return Err(BackendError::TransportError("Injected backend failure".to_string()));
+            }
+            return Ok(BackendResponse {
+                status: settings.backend_error_status,
+                content_type: Some("text/plain".to_string()),
+                body: b"Injected backend failure".to_vec(),
+            });
+        }
+
+        self.inner.send(request).await
+    }
+}
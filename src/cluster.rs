@@ -0,0 +1,428 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::bot::session::{MessageType, SessionSnapshot};
+use crate::config::ClusterConfig;
+use crate::redis_layer::RedisLayer;
+
+/// Session state shared by the replicas of a cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedSession {
+    /// Monotonic write version; stale writes are rejected
+    pub version: u64,
+    /// Replica that wrote this version
+    pub instance_id: String,
+    /// Set once the session closed; no further writes are accepted
+    #[serde(default)]
+    pub ended: bool,
+    /// Session state, absent once the session ended
+    pub snapshot: Option<SessionSnapshot>,
+}
+
+/// Compare-and-set: only write if the session is open and the stored version is older
+///
+/// Returns 1 once written, 0 if the session ended and minus the stored version
+/// if it is not older.
+const SAVE_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if current then
+    local ok, decoded = pcall(cjson.decode, current)
+    if ok and decoded['ended'] == true then
+        return 0
+    end
+    if ok and tonumber(decoded['version']) >= tonumber(ARGV[2]) then
+        return -tonumber(decoded['version'])
+    end
+end
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[3])
+if #KEYS > 1 then
+    redis.call('SET', KEYS[2], ARGV[4], 'EX', ARGV[3])
+end
+return 1
+"#;
+
+/// Take or renew a lease unless another replica holds it
+const LEASE_SCRIPT: &str = r#"
+local holder = redis.call('GET', KEYS[1])
+if holder and holder ~= ARGV[1] then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+return 1
+"#;
+
+/// Drop a lease only if this replica holds it
+const RELEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Take every message of a session's shared queue
+const TAKE_MESSAGES_SCRIPT: &str = r#"
+local messages = redis.call('LRANGE', KEYS[1], 0, -1)
+redis.call('DEL', KEYS[1])
+return messages
+"#;
+
+/// Writes of a session version tried before giving up on conflicting writers
+const SAVE_ATTEMPTS: u32 = 3;
+
+/// How often a webhook waiting for a call's turn lease asks again
+const TURN_LEASE_POLL: Duration = Duration::from_millis(25);
+
+/// Result of writing a session version
+enum SaveOutcome {
+    Saved,
+    /// The session ended; it must not be written again
+    Ended,
+    /// Another replica wrote this version or a newer one
+    Conflict(u64),
+    Failed,
+}
+
+/// Shares sessions through Redis so any replica can serve any call's webhooks
+///
+/// Each session's backend WebSocket client runs on the replica holding the
+/// session's lease. Messages it queues for the caller go to a shared queue,
+/// drained by whichever replica receives the call's queue callback, and turns
+/// of one call are serialized across replicas by a turn lease.
+pub struct SessionCluster {
+    redis: Option<RedisLayer>,
+    instance_id: String,
+    lease_ttl_seconds: u64,
+    session_ttl_seconds: u64,
+    turn_lease_ms: u64,
+}
+
+/// A call's turn lease, released when dropped
+pub struct TurnLease {
+    cluster: Arc<SessionCluster>,
+    call_sid: String,
+    token: String,
+}
+
+impl Drop for TurnLease {
+    fn drop(&mut self) {
+        let (cluster, call_sid, token) = (self.cluster.clone(), std::mem::take(&mut self.call_sid), std::mem::take(&mut self.token));
+        tokio::spawn(async move {
+            cluster.release_turn(&call_sid, &token).await;
+        });
+    }
+}
+
+impl SessionCluster {
+    /// Create a cluster handle; cluster mode is disabled when no Redis layer is available
+    pub fn new(redis: Option<RedisLayer>, config: &ClusterConfig) -> Self {
+        if redis.is_some() {
+            info!("Cluster mode enabled for instance {}", config.instance_id);
+        }
+
+        SessionCluster {
+            redis,
+            instance_id: config.instance_id.clone(),
+            lease_ttl_seconds: config.lease_ttl_seconds,
+            session_ttl_seconds: config.session_ttl_seconds,
+            turn_lease_ms: config.turn_lease_ms,
+        }
+    }
+
+    /// Whether cluster mode is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    /// ID of the local replica
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// How long a lease lasts without renewal
+    pub fn lease_ttl_seconds(&self) -> u64 {
+        self.lease_ttl_seconds
+    }
+
+    /// Write a session version unless the session ended or a newer version exists
+    async fn save(&self, snapshot: SessionSnapshot, version: u64) -> SaveOutcome {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return SaveOutcome::Failed,
+        };
+
+        let session_id = snapshot.session_id.clone();
+        let call_sid = snapshot.conversation_id.clone();
+        let shared = SharedSession {
+            version,
+            instance_id: self.instance_id.clone(),
+            ended: false,
+            snapshot: Some(snapshot),
+        };
+        let payload = match serde_json::to_string(&shared) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize shared session {}: {}", session_id, e);
+                return SaveOutcome::Failed;
+            }
+        };
+
+        let script = redis::Script::new(SAVE_SCRIPT);
+        let mut script = script.prepare_invoke();
+        script.key(redis.key(&["cluster", "session", &session_id]));
+        if let Some(call_sid) = &call_sid {
+            script.key(redis.key(&["cluster", "call", call_sid]));
+        }
+        let result: Result<i64, redis::RedisError> = script
+            .arg(payload)
+            .arg(version)
+            .arg(self.session_ttl_seconds)
+            .arg(&session_id)
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(1) => {
+                debug!("Shared session {} (v{})", session_id, version);
+                SaveOutcome::Saved
+            },
+            Ok(0) => {
+                debug!("Skipped write of ended shared session {} (v{})", session_id, version);
+                SaveOutcome::Ended
+            },
+            Ok(stored) => SaveOutcome::Conflict(stored.unsigned_abs()),
+            Err(e) => {
+                error!("Failed to share session {}: {}", session_id, e);
+                SaveOutcome::Failed
+            }
+        }
+    }
+
+    /// Write a session version in the background
+    ///
+    /// When another replica wrote the session meanwhile, the write is retried
+    /// past the stored version, so the latest local change wins.
+    pub fn save_detached(self: &Arc<Self>, snapshot: SessionSnapshot, version: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let cluster = self.clone();
+        tokio::spawn(async move {
+            let mut version = version;
+            for _ in 0..SAVE_ATTEMPTS {
+                match cluster.save(snapshot.clone(), version).await {
+                    SaveOutcome::Conflict(stored) => {
+                        debug!("Shared session {} is at v{}, writing v{} again as v{}", snapshot.session_id, stored, version, stored + 1);
+                        version = stored + 1;
+                    },
+                    SaveOutcome::Saved | SaveOutcome::Ended | SaveOutcome::Failed => return,
+                }
+            }
+            warn!("Gave up sharing session {} after {} conflicting writes", snapshot.session_id, SAVE_ATTEMPTS);
+        });
+    }
+
+    /// Mark a session as ended so no replica serves or resurrects it
+    pub async fn end(&self, session_id: &str) {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return,
+        };
+
+        let tombstone = SharedSession {
+            version: 0,
+            instance_id: self.instance_id.clone(),
+            ended: true,
+            snapshot: None,
+        };
+        let payload = match serde_json::to_string(&tombstone) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize shared session {}: {}", session_id, e);
+                return;
+            }
+        };
+
+        let result: Result<(), redis::RedisError> = redis::cmd("SET")
+            .arg(redis.key(&["cluster", "session", session_id]))
+            .arg(payload)
+            .arg("EX")
+            .arg(self.session_ttl_seconds)
+            .query_async(&mut redis.connection())
+            .await;
+
+        if let Err(e) = result {
+            error!("Failed to end shared session {}: {}", session_id, e);
+        }
+    }
+
+    /// Read the shared state of a session
+    pub async fn load(&self, session_id: &str) -> Result<Option<SharedSession>, String> {
+        let Some(redis) = &self.redis else {
+            return Ok(None);
+        };
+
+        let payload: Option<String> = redis::cmd("GET")
+            .arg(redis.key(&["cluster", "session", session_id]))
+            .query_async(&mut redis.connection())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match payload {
+            Some(payload) => serde_json::from_str(&payload).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Look up the session ID of a call served by any replica
+    pub async fn session_for_call(&self, call_sid: &str) -> Result<Option<String>, String> {
+        let Some(redis) = &self.redis else {
+            return Ok(None);
+        };
+
+        redis::cmd("GET")
+            .arg(redis.key(&["cluster", "call", call_sid]))
+            .query_async(&mut redis.connection())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Take or renew the lease on a session's WebSocket client
+    ///
+    /// Returns false if another replica holds the lease. Without cluster mode
+    /// the local replica always owns its sessions.
+    pub async fn acquire_lease(&self, session_id: &str) -> bool {
+        let Some(redis) = &self.redis else {
+            return true;
+        };
+
+        let result: Result<i32, redis::RedisError> = redis::Script::new(LEASE_SCRIPT)
+            .key(redis.key(&["cluster", "lease", session_id]))
+            .arg(&self.instance_id)
+            .arg(self.lease_ttl_seconds)
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(acquired) => acquired == 1,
+            Err(e) => {
+                warn!("Failed to acquire lease for session {}: {}", session_id, e);
+                false
+            }
+        }
+    }
+
+    /// Give up the lease on a session's WebSocket client if the local replica holds it
+    pub async fn release_lease(&self, session_id: &str) {
+        let Some(redis) = &self.redis else {
+            return;
+        };
+
+        let result: Result<i32, redis::RedisError> = redis::Script::new(RELEASE_SCRIPT)
+            .key(redis.key(&["cluster", "lease", session_id]))
+            .arg(&self.instance_id)
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        if let Err(e) = result {
+            warn!("Failed to release lease for session {}: {}", session_id, e);
+        }
+    }
+
+    /// Add a message to a session's shared queue
+    pub async fn push_message(&self, session_id: &str, message: &MessageType) -> Result<(), String> {
+        let Some(redis) = &self.redis else {
+            return Err("Cluster mode is disabled".to_string());
+        };
+
+        let payload = serde_json::to_string(message).map_err(|e| e.to_string())?;
+        let key = redis.key(&["cluster", "queue", session_id]);
+        redis::pipe()
+            .atomic()
+            .cmd("RPUSH").arg(&key).arg(payload).ignore()
+            .cmd("EXPIRE").arg(&key).arg(self.session_ttl_seconds).ignore()
+            .query_async(&mut redis.connection())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Take every message of a session's shared queue, oldest first
+    pub async fn take_messages(&self, session_id: &str) -> Vec<MessageType> {
+        let Some(redis) = &self.redis else {
+            return Vec::new();
+        };
+
+        let result: Result<Vec<String>, redis::RedisError> = redis::Script::new(TAKE_MESSAGES_SCRIPT)
+            .key(redis.key(&["cluster", "queue", session_id]))
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(payloads) => payloads.iter()
+                .filter_map(|payload| match serde_json::from_str(payload) {
+                    Ok(message) => Some(message),
+                    Err(e) => {
+                        warn!("Dropping unreadable shared message for session {}: {}", session_id, e);
+                        None
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Failed to take shared messages of session {}: {}", session_id, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Wait for a call's turn lease
+    ///
+    /// A lease left by a replica that died expires after `CLUSTER_TURN_LEASE_MS`.
+    /// Returns `None` outside cluster mode, or if Redis cannot be reached, in
+    /// which case the turn goes ahead unserialized.
+    pub async fn acquire_turn(self: &Arc<Self>, call_sid: &str) -> Option<TurnLease> {
+        let redis = self.redis.as_ref()?;
+        let token = format!("{}:{}", self.instance_id, uuid::Uuid::new_v4());
+
+        loop {
+            let result: Result<Option<String>, redis::RedisError> = redis::cmd("SET")
+                .arg(redis.key(&["cluster", "turn", call_sid]))
+                .arg(&token)
+                .arg("NX")
+                .arg("PX")
+                .arg(self.turn_lease_ms)
+                .query_async(&mut redis.connection())
+                .await;
+
+            match result {
+                Ok(Some(_)) => return Some(TurnLease {
+                    cluster: self.clone(),
+                    call_sid: call_sid.to_string(),
+                    token,
+                }),
+                Ok(None) => tokio::time::sleep(TURN_LEASE_POLL).await,
+                Err(e) => {
+                    warn!("Failed to acquire turn lease for call {}: {}", call_sid, e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    async fn release_turn(&self, call_sid: &str, token: &str) {
+        let Some(redis) = &self.redis else {
+            return;
+        };
+
+        let result: Result<i32, redis::RedisError> = redis::Script::new(RELEASE_SCRIPT)
+            .key(redis.key(&["cluster", "turn", call_sid]))
+            .arg(token)
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        if let Err(e) = result {
+            warn!("Failed to release turn lease for call {}: {}", call_sid, e);
+        }
+    }
+}
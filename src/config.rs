@@ -1,8 +1,177 @@
-use std::env;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use arc_swap::ArcSwap;
+use chrono::NaiveDate;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
 use serde::{Deserialize, Serialize};
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+use crate::bot::redaction::PatternSet;
+
+/// Environment variables as the configuration reads them
+///
+/// The process environment, overlaid with the env file values applied by a
+/// configuration reload. Reloads never modify the process environment, since
+/// setting variables while other threads read it is undefined behavior.
+pub mod env {
+    use std::collections::HashMap;
+    use std::env::VarError;
+    use std::sync::{Arc, OnceLock};
+    use arc_swap::ArcSwap;
+    
+    fn overlay() -> &'static ArcSwap<HashMap<String, String>> {
+        static OVERLAY: OnceLock<ArcSwap<HashMap<String, String>>> = OnceLock::new();
+        OVERLAY.get_or_init(|| ArcSwap::from_pointee(HashMap::new()))
+    }
+    
+    /// Value of a variable, preferring one applied from the env file
+    pub fn var(key: impl AsRef<str>) -> Result<String, VarError> {
+        match overlay().load().get(key.as_ref()) {
+            Some(value) => Ok(value.clone()),
+            None => std::env::var(key.as_ref()),
+        }
+    }
+    
+    /// Replace the values applied from the env file
+    pub fn set_overlay(values: HashMap<String, String>) {
+        overlay().store(Arc::new(values));
+    }
+}
+
+/// Configuration fields holding credentials, masked in Debug output
+const SECRET_FIELDS: [&str; 14] = [
+    "auth_token",
+    "sip_auth_password",
+    "authorization_token",
+    "ws_headers",
+    "client_key",
+    "client_secret",
+    "api_key",
+    "api_token",
+    "tenant_secrets_key",
+    "signing_secret",
+    "previous_signing_secret",
+    "recording_signing_secret",
+    "tenant_signing_secrets",
+    "token",
+];
+
+/// Format a configuration section for Debug output with its credentials masked
+///
+/// Passwords in URLs, such as a Redis URL, are masked too.
+fn fmt_redacted<T: Serialize>(name: &str, section: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut value = serde_json::to_value(section).map_err(|_| fmt::Error)?;
+    redact_secrets(&mut value);
+    write!(f, "{} {}", name, value)
+}
+
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.as_str()) && !field.is_null() {
+                    *field = serde_json::Value::from("[redacted]");
+                } else {
+                    redact_secrets(field);
+                }
+            }
+        },
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        serde_json::Value::String(text) => {
+            if let Ok(mut url) = reqwest::Url::parse(text) {
+                if url.password().is_some() && url.set_password(Some("redacted")).is_ok() {
+                    *text = url.to_string();
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Implement Debug for configuration sections holding credentials
+macro_rules! redacted_debug {
+    ($($section:ident),+ $(,)?) => {
+        $(
+            impl fmt::Debug for $section {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt_redacted(stringify!($section), self, f)
+                }
+            }
+        )+
+    };
+}
+
+redacted_debug!(
+    TwilioConfig,
+    BackendConfig,
+    BackendProfile,
+    WsTlsConfig,
+    OAuthConfig,
+    SttConfig,
+    TtsConfig,
+    CallbackConfig,
+    AdminConfig,
+    RedisConfig,
+    AnalyticsTapConfig,
+);
+
+/// Source of secret configuration values, looked up by environment variable name
+pub trait SecretSource: Send + Sync {
+    /// Get a secret, or `None` if this source does not have it
+    fn get(&self, name: &str) -> Result<Option<String>, String>;
+}
+
+/// Secrets from environment variables, preferring a mounted file named by `<NAME>_FILE`
+pub struct EnvSecretSource;
+
+impl SecretSource for EnvSecretSource {
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        if let Ok(path) = env::var(format!("{}_FILE", name)) {
+            return std::fs::read_to_string(&path)
+                .map(|value| Some(value.trim_end_matches(['\r', '\n']).to_string()))
+                .map_err(|e| format!("Failed to read {}_FILE {}: {}", name, path, e));
+        }
+
+        Ok(env::var(name).ok())
+    }
+}
+
+/// Secret sources consulted in order, the first with a value winning
+pub struct Secrets {
+    sources: Vec<Box<dyn SecretSource>>,
+}
+
+impl Secrets {
+    /// Secrets from environment variables and secret files only
+    pub fn from_env() -> Self {
+        Secrets {
+            sources: vec![Box::new(EnvSecretSource)],
+        }
+    }
+
+    /// Consult another source after the existing ones
+    pub fn with_source(mut self, source: Box<dyn SecretSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Get a secret from the first source that has a non-empty value
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        for source in &self.sources {
+            if let Some(value) = source.get(name)?.filter(|v| !v.is_empty()) {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+}
 
 /// Twilio-specific configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TwilioConfig {
     pub account_sid: String,
     pub auth_token: String,
@@ -11,14 +180,105 @@ pub struct TwilioConfig {
     pub webhook_port: u16,
     pub voice: String,
     pub speech_model: String,
+    /// Use Twilio's enhanced speech recognition (premium, `phone_call` model only)
+    pub speech_enhanced: bool,
     pub default_timeout: u32,
+    pub adaptive_timeout: bool,
+    pub short_answer_timeout: u32,
+    pub open_answer_timeout: u32,
+    pub open_answer_speech_timeout: String,
     pub partial_processing: bool,
+    /// How long a partial result must stay unchanged before speculative generation (0 disables)
+    pub partial_stability_ms: u64,
+    /// Consecutive identical partial results that start speculative generation
+    pub partial_stability_count: u32,
+    /// Comma-separated phrases Twilio should expect on every speech Gather
+    pub speech_hints: Option<String>,
+    /// SSML prosody rate applied to spoken text (e.g. "90%")
+    pub speech_rate: Option<String>,
     pub language: Option<String>,
+    /// Language Gathers listen in when it differs from the spoken language (e.g. `multi`)
+    pub speech_language: Option<String>,
     pub region: Option<String>,
     pub edge: Option<String>,
+    /// How long webhook responses are kept to answer Twilio retries (0 disables)
+    pub webhook_replay_ttl_seconds: u64,
+    /// Enrich sessions with Twilio Lookup carrier and caller name data (billed per lookup)
+    pub lookup_enabled: bool,
+    /// Longest a call waits for Lookup data before the session opens without it
+    pub lookup_timeout_ms: u64,
+    /// Username for SIP endpoints that challenge outbound INVITEs
+    pub sip_auth_username: Option<String>,
+    pub sip_auth_password: Option<String>,
+    /// How long finished outbound call jobs can be polled
+    pub call_job_ttl_seconds: u64,
+    /// Twilio queue callers are placed in when the backend escalates without naming one
+    pub agent_queue: String,
+    /// Audio played to callers waiting in a queue
+    pub queue_hold_music_url: Option<String>,
+    /// Audio looped while a caller is put on hold, defaulting to the queue hold music
+    pub hold_music_url: Option<String>,
+    /// Longest a caller waits in a queue before returning to the bot (0 waits indefinitely)
+    pub queue_max_wait_seconds: u64,
+    /// How long a transfer rings before it counts as unanswered
+    pub transfer_timeout_seconds: u32,
+    /// Offer voicemail after an unanswered transfer instead of returning to the bot
+    pub transfer_voicemail_enabled: bool,
+    /// Ask callers to rate the call after a transferred call ends
+    pub transfer_survey_enabled: bool,
+    /// Speech recognized with lower confidence is clarified instead of sent to the backend (0 disables)
+    pub speech_confidence_threshold: f64,
+    /// Repeat the low-confidence text back to the caller when clarifying
+    pub clarification_includes_text: bool,
+    /// Numbers whose voice webhook should point at this service, defaulting to the from number
+    pub inbound_numbers: Vec<String>,
+    /// Check and fix the inbound numbers' voice webhooks at startup
+    pub provision_numbers_on_startup: bool,
+    /// Country calling codes outbound calls may dial (e.g. "1", "44"); empty allows all
+    pub allowed_country_codes: Vec<String>,
+    /// Path prefix a reverse proxy adds in front of this service (e.g. "/voice")
+    pub public_base_path: Option<String>,
+    /// Work out the external URL of requests from `X-Forwarded-*` headers
+    pub trust_forwarded_headers: bool,
+    /// Build callback URLs under the webhook version path (e.g. "/twilio/v2/...")
+    pub versioned_webhooks: bool,
+    /// POST a signed test call to the public incoming call URL at startup and warn if it fails
+    pub webhook_self_test: bool,
+    /// Soft prompts re-gathering speech after a silent Gather before hanging up;
+    /// 0, the default, lets Twilio end the call
+    pub keepalive_attempts: u32,
+    /// Pause before each keepalive prompt
+    pub keepalive_pause_seconds: u32,
+    /// Time a turn webhook may take before the caller hears a filler while the turn finishes
+    pub webhook_response_timeout_ms: u64,
+    /// Speech is recognized from the Media Stream, so Gathers only play prompts and take keypresses;
+    /// the call then holds in `/stream_hold` and silence is judged from the transcripts
+    pub streaming_stt: bool,
+    /// Catalog voice the TTS provider speaks in, played instead of Twilio's voice
+    pub tts_voice: Option<String>,
+    /// Bidirectional Media Stream replies are spoken over, instead of a Gather
+    pub reply_stream_url: Option<String>,
 }
 
 impl TwilioConfig {
+    /// External URL of a Twilio callback route (e.g. "/status_callback")
+    ///
+    /// With versioned webhooks the URL carries this build's webhook version, so
+    /// TwiML it generates keeps reaching handlers with the same semantics while
+    /// a later deployment rolls out under a new version.
+    pub fn callback_url(&self, path: &str) -> String {
+        if self.versioned_webhooks {
+            format!("{}/{}{}", self.webhook_url, crate::twilio::WEBHOOK_VERSION, path)
+        } else {
+            self.unversioned_callback_url(path)
+        }
+    }
+    
+    /// External URL of a Twilio callback route without the webhook version
+    pub fn unversioned_callback_url(&self, path: &str) -> String {
+        format!("{}{}", self.webhook_url, path)
+    }
+    
     /// Validate Twilio configuration
     pub fn validate(&self) -> Result<(), String> {
         if self.account_sid.is_empty() {
@@ -42,16 +302,47 @@ impl TwilioConfig {
             return Err("Default timeout must be greater than 0".to_string());
         }
         
+        if self.partial_stability_count == 0 {
+            return Err("Partial stability count must be greater than 0".to_string());
+        }
+        
+        if self.short_answer_timeout == 0 || self.open_answer_timeout == 0 {
+            return Err("Adaptive answer timeouts must be greater than 0".to_string());
+        }
+        
+        if self.webhook_response_timeout_ms == 0 || self.webhook_response_timeout_ms >= TWILIO_WEBHOOK_TIMEOUT_MS {
+            return Err(format!(
+                "WEBHOOK_RESPONSE_TIMEOUT_MS must be greater than 0 and below the Twilio webhook timeout of {}ms",
+                TWILIO_WEBHOOK_TIMEOUT_MS
+            ));
+        }
+        
+        if !self.webhook_url.starts_with("https://") && !self.webhook_url.starts_with("http://") {
+            return Err("Webhook URL must be an http(s) URL".to_string());
+        }
+        
+        if let Some(path) = &self.public_base_path {
+            if !path.starts_with('/') || path.ends_with('/') {
+                return Err("PUBLIC_BASE_PATH must start with '/' and not end with '/'".to_string());
+            }
+        }
+        
+        if let Some(code) = self.allowed_country_codes.iter()
+            .find(|code| code.is_empty() || code.len() > 3 || !code.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(format!("ALLOWED_COUNTRY_CODES has invalid country code '{}'", code));
+        }
+        
         Ok(())
     }
     
-    /// Load Twilio configuration from environment variables
-    pub fn from_env() -> Result<Self, String> {
-        let config = TwilioConfig {
+    /// Load Twilio configuration from environment variables and secret sources
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
+        let mut config = TwilioConfig {
             account_sid: env::var("TWILIO_ACCOUNT_SID")
                 .map_err(|_| "TWILIO_ACCOUNT_SID must be set".to_string())?,
-            auth_token: env::var("TWILIO_AUTH_TOKEN")
-                .map_err(|_| "TWILIO_AUTH_TOKEN must be set".to_string())?,
+            auth_token: secrets.get("TWILIO_AUTH_TOKEN")?
+                .ok_or_else(|| "TWILIO_AUTH_TOKEN must be set".to_string())?,
             from_number: env::var("FROM_NUMBER")
                 .map_err(|_| "FROM_NUMBER must be set".to_string())?,
             webhook_url: env::var("TWILIO_WEBHOOK_URL")
@@ -64,36 +355,201 @@ impl TwilioConfig {
                 .unwrap_or_else(|_| "Polly.Salli".to_string()),
             speech_model: env::var("SPEECH_MODEL")
                 .unwrap_or_else(|_| "googlev2_telephony".to_string()),
+            speech_enhanced: env::var("SPEECH_ENHANCED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
             default_timeout: env::var("DEFAULT_TIMEOUT")
                 .unwrap_or_else(|_| "10".to_string())
                 .parse()
                 .map_err(|_| "DEFAULT_TIMEOUT must be a valid number".to_string())?,
+            adaptive_timeout: env::var("ADAPTIVE_GATHER_TIMEOUT")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            short_answer_timeout: env::var("SHORT_ANSWER_TIMEOUT")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .map_err(|_| "SHORT_ANSWER_TIMEOUT must be a valid number".to_string())?,
+            open_answer_timeout: env::var("OPEN_ANSWER_TIMEOUT")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .map_err(|_| "OPEN_ANSWER_TIMEOUT must be a valid number".to_string())?,
+            open_answer_speech_timeout: env::var("OPEN_ANSWER_SPEECH_TIMEOUT")
+                .unwrap_or_else(|_| "3".to_string()),
             partial_processing: env::var("PARTIAL_PROCESSING")
                 .unwrap_or_else(|_| "true".to_string())
                 .to_lowercase() == "true",
+            partial_stability_ms: env::var("PARTIAL_STABILITY_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .map_err(|_| "PARTIAL_STABILITY_MS must be a valid number".to_string())?,
+            partial_stability_count: env::var("PARTIAL_STABILITY_COUNT")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .map_err(|_| "PARTIAL_STABILITY_COUNT must be a valid number".to_string())?,
+            speech_hints: env::var("SPEECH_HINTS")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            speech_rate: env::var("SPEECH_RATE")
+                .ok()
+                .filter(|s| !s.is_empty()),
             language: env::var("TWILIO_LANGUAGE").ok(),
+            speech_language: env::var("SPEECH_LANGUAGE")
+                .ok()
+                .filter(|s| !s.is_empty()),
             region: env::var("TWILIO_REGION")
                 .ok()
                 .filter(|s| !s.is_empty()),
             edge: env::var("TWILIO_EDGE")
                 .ok()
                 .filter(|s| !s.is_empty()),
+            webhook_replay_ttl_seconds: env::var("WEBHOOK_REPLAY_TTL_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .map_err(|_| "WEBHOOK_REPLAY_TTL_SECONDS must be a valid number".to_string())?,
+            lookup_enabled: env::var("LOOKUP_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            lookup_timeout_ms: env::var("LOOKUP_TIMEOUT_MS")
+                .unwrap_or_else(|_| "1500".to_string())
+                .parse()
+                .map_err(|_| "LOOKUP_TIMEOUT_MS must be a valid number".to_string())?,
+            sip_auth_username: env::var("SIP_AUTH_USERNAME")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            sip_auth_password: secrets.get("SIP_AUTH_PASSWORD")?,
+            call_job_ttl_seconds: env::var("CALL_JOB_TTL_SECONDS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .map_err(|_| "CALL_JOB_TTL_SECONDS must be a valid number".to_string())?,
+            agent_queue: env::var("AGENT_QUEUE")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "support".to_string()),
+            queue_hold_music_url: env::var("QUEUE_HOLD_MUSIC_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            hold_music_url: env::var("HOLD_MUSIC_URL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .or_else(|| env::var("QUEUE_HOLD_MUSIC_URL").ok().filter(|s| !s.is_empty())),
+            queue_max_wait_seconds: env::var("QUEUE_MAX_WAIT_SECONDS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .map_err(|_| "QUEUE_MAX_WAIT_SECONDS must be a valid number".to_string())?,
+            transfer_timeout_seconds: env::var("TRANSFER_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .map_err(|_| "TRANSFER_TIMEOUT_SECONDS must be a valid number".to_string())?,
+            transfer_voicemail_enabled: env::var("TRANSFER_VOICEMAIL_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            transfer_survey_enabled: env::var("TRANSFER_SURVEY_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            speech_confidence_threshold: env::var("SPEECH_CONFIDENCE_THRESHOLD")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .map_err(|_| "SPEECH_CONFIDENCE_THRESHOLD must be a valid number".to_string())?,
+            clarification_includes_text: env::var("CLARIFICATION_INCLUDES_TEXT")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase() == "true",
+            inbound_numbers: env::var("INBOUND_NUMBERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .collect(),
+            provision_numbers_on_startup: env::var("PROVISION_NUMBERS_ON_STARTUP")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            allowed_country_codes: env::var("ALLOWED_COUNTRY_CODES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|code| code.trim().trim_start_matches('+').to_string())
+                .filter(|code| !code.is_empty())
+                .collect(),
+            public_base_path: env::var("PUBLIC_BASE_PATH")
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty() && s != "/"),
+            trust_forwarded_headers: env::var("TRUST_FORWARDED_HEADERS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            versioned_webhooks: env::var("VERSIONED_WEBHOOKS")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase() == "true",
+            webhook_self_test: env::var("WEBHOOK_SELF_TEST")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            keepalive_attempts: env::var("KEEPALIVE_ATTEMPTS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .map_err(|_| "KEEPALIVE_ATTEMPTS must be a valid number".to_string())?,
+            keepalive_pause_seconds: env::var("KEEPALIVE_PAUSE_SECONDS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .map_err(|_| "KEEPALIVE_PAUSE_SECONDS must be a valid number".to_string())?,
+            webhook_response_timeout_ms: env::var("WEBHOOK_RESPONSE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .map_err(|_| "WEBHOOK_RESPONSE_TIMEOUT_MS must be a valid number".to_string())?,
+            streaming_stt: false,
+            tts_voice: None,
+            reply_stream_url: None,
         };
         
+        if config.inbound_numbers.is_empty() {
+            config.inbound_numbers.push(config.from_number.clone());
+        }
+        
+        config.webhook_url = config.webhook_url.trim_end_matches('/').to_string();
+        if let Some(path) = &config.public_base_path {
+            config.webhook_url = with_base_path(&config.webhook_url, path);
+        }
+        
         config.validate()?;
         Ok(config)
     }
 }
 
+/// Insert a reverse proxy's path prefix after the origin of a URL, unless the URL already has it
+fn with_base_path(url: &str, base_path: &str) -> String {
+    let path_start = url.find("://")
+        .and_then(|scheme_end| url[scheme_end + 3..].find('/').map(|i| scheme_end + 3 + i))
+        .unwrap_or(url.len());
+    let (origin, path) = url.split_at(path_start);
+    
+    if path == base_path || path.starts_with(&format!("{}/", base_path)) {
+        url.to_string()
+    } else {
+        format!("{}{}{}", origin, base_path, path)
+    }
+}
+
 /// Backend-specific configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BackendConfig {
     pub url: String,
     pub authorization_token: Option<String>,
+    /// OAuth2 client credentials used instead of the static token when set
+    pub oauth: Option<OAuthConfig>,
     pub ws_url: String,
+    /// Extra headers sent when opening backend WebSockets
+    pub ws_headers: HashMap<String, String>,
+    /// Root CA and client certificate for `wss://` backend WebSockets
+    pub ws_tls: WsTlsConfig,
     pub enable_circuit_breaker: bool,
     pub retry_attempts: usize,
     pub retry_base_delay_ms: u64,
+    pub timeouts: BackendTimeouts,
+    /// Session context keys forwarded as kwargs with every caller turn
+    pub turn_kwargs: Vec<String>,
+    /// Post a summary of every finished call to the backend
+    pub call_summary_enabled: bool,
+    /// Ask for turn replies as server-sent events and queue them as they arrive
+    pub stream_responses: bool,
+    /// Additional backends by bot name, e.g. `sales` and `support`
+    pub profiles: HashMap<String, BackendProfile>,
 }
 
 impl BackendConfig {
@@ -105,18 +561,84 @@ impl BackendConfig {
         if self.ws_url.is_empty() {
             return Err("Backend WebSocket URL cannot be empty".to_string());
         }
+        if let Some(oauth) = &self.oauth {
+            if oauth.client_id.is_empty() || oauth.client_secret.is_empty() {
+                return Err("BACKEND_OAUTH_CLIENT_ID and BACKEND_OAUTH_CLIENT_SECRET must be set with BACKEND_OAUTH_TOKEN_URL".to_string());
+            }
+        }
+        for (name, value) in &self.ws_headers {
+            if HeaderName::from_bytes(name.as_bytes()).is_err() || HeaderValue::from_str(value).is_err() {
+                return Err(format!("BACKEND_WS_HEADERS has invalid header '{}'", name));
+            }
+        }
+        self.ws_tls.validate()?;
+        self.timeouts.validate()?;
+        
+        let mut numbers = HashMap::new();
+        for (name, profile) in &self.profiles {
+            if profile.url.is_empty() || profile.ws_url.is_empty() {
+                return Err(format!("BACKEND_PROFILES entry '{}' must set url and ws_url", name));
+            }
+            for (header, value) in &profile.ws_headers {
+                if HeaderName::from_bytes(header.as_bytes()).is_err() || HeaderValue::from_str(value).is_err() {
+                    return Err(format!("BACKEND_PROFILES entry '{}' has invalid header '{}'", name, header));
+                }
+            }
+            profile.ws_tls.validate()
+                .map_err(|e| format!("BACKEND_PROFILES entry '{}' has invalid TLS settings: {}", name, e))?;
+            for number in &profile.numbers {
+                if let Some(other) = numbers.insert(number.as_str(), name.as_str()) {
+                    return Err(format!("BACKEND_PROFILES assigns {} to both '{}' and '{}'", number, other, name));
+                }
+            }
+        }
         
         Ok(())
     }
     
-    /// Load backend configuration from environment variables
-    pub fn from_env() -> Result<Self, String> {
+    /// Backend configuration for a bot; `None` selects the default backend
+    ///
+    /// A profile replaces the URLs, credentials and WebSocket headers and TLS
+    /// settings, and keeps every other setting. Returns `None` for an unknown bot.
+    pub fn profile(&self, bot: Option<&str>) -> Option<BackendConfig> {
+        let Some(bot) = bot else {
+            return Some(self.clone());
+        };
+        let profile = self.profiles.get(bot)?;
+        Some(BackendConfig {
+            url: profile.url.clone(),
+            ws_url: profile.ws_url.clone(),
+            authorization_token: profile.authorization_token.clone(),
+            oauth: None,
+            ws_headers: profile.ws_headers.clone(),
+            ws_tls: profile.ws_tls.clone(),
+            profiles: HashMap::new(),
+            ..self.clone()
+        })
+    }
+    
+    /// Bot answering calls to an inbound number, if a profile claims it
+    pub fn bot_for_number(&self, number: &str) -> Option<&str> {
+        self.profiles.iter()
+            .find(|(_, profile)| profile.numbers.iter().any(|n| n == number))
+            .map(|(name, _)| name.as_str())
+    }
+    
+    /// Load backend configuration from environment variables and secret sources
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
         let config = BackendConfig {
             url: env::var("BACKEND_URL")
                 .map_err(|_| "BACKEND_URL must be set".to_string())?,
-            authorization_token: env::var("AUTHORIZATION_TOKEN").ok(),
+            authorization_token: secrets.get("AUTHORIZATION_TOKEN")?,
+            oauth: OAuthConfig::from_env(secrets)?,
             ws_url: env::var("BACKEND_WS_URL")
                 .map_err(|_| "BACKEND_WS_URL must be set".to_string())?,
+            ws_headers: match secrets.get("BACKEND_WS_HEADERS")? {
+                Some(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                    .map_err(|e| format!("BACKEND_WS_HEADERS must be a JSON object of header name to value: {}", e))?,
+                _ => HashMap::new(),
+            },
+            ws_tls: WsTlsConfig::from_env(secrets)?,
             enable_circuit_breaker: env::var("ENABLE_CIRCUIT_BREAKER")
                 .unwrap_or_else(|_| "true".to_string())
                 .to_lowercase() == "true",
@@ -128,6 +650,24 @@ impl BackendConfig {
                 .unwrap_or_else(|_| "500".to_string())
                 .parse()
                 .unwrap_or(500),
+            timeouts: BackendTimeouts::from_env()?,
+            turn_kwargs: env::var("TURN_KWARGS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            call_summary_enabled: env::var("CALL_SUMMARY_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            stream_responses: env::var("BACKEND_STREAM_RESPONSES")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            profiles: match secrets.get("BACKEND_PROFILES")? {
+                Some(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                    .map_err(|e| format!("BACKEND_PROFILES must be a JSON object of bot name to backend: {}", e))?,
+                _ => HashMap::new(),
+            },
         };
         
         config.validate()?;
@@ -135,11 +675,198 @@ impl BackendConfig {
     }
 }
 
+/// Backend of a named bot, selected by inbound number or by an outbound call's `bot`
+///
+/// ```json
+/// {"sales": {"url": "https://sales.example.com", "ws_url": "wss://sales.example.com/ws",
+///            "authorization_token": "...", "numbers": ["+15551230000"],
+///            "ws_headers": {"X-Tenant": "sales"}, "ws_tls": {"ca_cert": "-----BEGIN CERTIFICATE-----..."}}}
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BackendProfile {
+    pub url: String,
+    pub ws_url: String,
+    /// Static token for this backend; profiles do not use the default backend's credentials
+    #[serde(default)]
+    pub authorization_token: Option<String>,
+    /// Inbound numbers answered by this bot
+    #[serde(default)]
+    pub numbers: Vec<String>,
+    /// Extra headers sent when opening this backend's WebSockets; the default backend's are not sent
+    #[serde(default)]
+    pub ws_headers: HashMap<String, String>,
+    /// TLS settings for this backend's `wss://` WebSockets; the default backend's are not used
+    #[serde(default)]
+    pub ws_tls: WsTlsConfig,
+}
+
+/// Twilio abandons a webhook that has not answered within this many milliseconds
+pub const TWILIO_WEBHOOK_TIMEOUT_MS: u64 = 15000;
+
+/// Timeouts for backend requests, per operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendTimeouts {
+    pub connect_ms: u64,
+    pub open_session_ms: u64,
+    /// Kept below Twilio's webhook limit so a hung backend still gets an error response out
+    pub run_ms: u64,
+    /// Total time a turn may take across its retries, also below Twilio's webhook limit
+    pub run_total_ms: u64,
+    pub close_session_ms: u64,
+    /// Timeout for all other backend operations
+    pub default_ms: u64,
+}
+
+impl Default for BackendTimeouts {
+    fn default() -> Self {
+        BackendTimeouts {
+            connect_ms: 2000,
+            open_session_ms: 5000,
+            run_ms: 12000,
+            run_total_ms: 14000,
+            close_session_ms: 5000,
+            default_ms: 10000,
+        }
+    }
+}
+
+impl BackendTimeouts {
+    /// Validate backend timeouts
+    pub fn validate(&self) -> Result<(), String> {
+        let timeouts = [
+            self.connect_ms,
+            self.open_session_ms,
+            self.run_ms,
+            self.run_total_ms,
+            self.close_session_ms,
+            self.default_ms,
+        ];
+        if timeouts.contains(&0) {
+            return Err("Backend timeouts must be greater than 0".to_string());
+        }
+        if self.run_total_ms >= TWILIO_WEBHOOK_TIMEOUT_MS {
+            return Err(format!(
+                "BACKEND_RUN_TOTAL_TIMEOUT_MS must be below the Twilio webhook timeout of {}ms",
+                TWILIO_WEBHOOK_TIMEOUT_MS
+            ));
+        }
+        if self.run_ms > self.run_total_ms {
+            return Err("BACKEND_RUN_TIMEOUT_MS cannot exceed BACKEND_RUN_TOTAL_TIMEOUT_MS".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Load backend timeouts from environment variables, rejecting values that are not milliseconds
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = BackendTimeouts::default();
+        let read = |name: &str, default: u64| match env::var(name) {
+            Ok(value) => value.trim().parse()
+                .map_err(|_| format!("{} must be a number of milliseconds, got '{}'", name, value)),
+            Err(_) => Ok(default),
+        };
+
+        Ok(BackendTimeouts {
+            connect_ms: read("BACKEND_CONNECT_TIMEOUT_MS", defaults.connect_ms)?,
+            open_session_ms: read("BACKEND_OPEN_SESSION_TIMEOUT_MS", defaults.open_session_ms)?,
+            run_ms: read("BACKEND_RUN_TIMEOUT_MS", defaults.run_ms)?,
+            run_total_ms: read("BACKEND_RUN_TOTAL_TIMEOUT_MS", defaults.run_total_ms)?,
+            close_session_ms: read("BACKEND_CLOSE_SESSION_TIMEOUT_MS", defaults.close_session_ms)?,
+            default_ms: read("BACKEND_REQUEST_TIMEOUT_MS", defaults.default_ms)?,
+        })
+    }
+}
+
+/// TLS settings for backend WebSockets, as PEM
+///
+/// Each value can also be read from a file named by `<NAME>_FILE`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct WsTlsConfig {
+    /// Root CA trusted in addition to the system roots
+    pub ca_cert: Option<String>,
+    /// Client certificate chain presented for mutual TLS
+    pub client_cert: Option<String>,
+    /// PKCS#8 private key of the client certificate
+    pub client_key: Option<String>,
+}
+
+impl WsTlsConfig {
+    /// Validate that the certificates and key parse
+    pub fn validate(&self) -> Result<(), String> {
+        if self.client_cert.is_some() != self.client_key.is_some() {
+            return Err("BACKEND_WS_CLIENT_CERT and BACKEND_WS_CLIENT_KEY must be set together".to_string());
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            native_tls::Certificate::from_pem(ca_cert.as_bytes())
+                .map_err(|e| format!("BACKEND_WS_CA_CERT is not a valid PEM certificate: {}", e))?;
+        }
+        if let (Some(cert), Some(key)) = (&self.client_cert, &self.client_key) {
+            native_tls::Identity::from_pkcs8(cert.as_bytes(), key.as_bytes())
+                .map_err(|e| format!("BACKEND_WS_CLIENT_CERT and BACKEND_WS_CLIENT_KEY are not a valid PEM certificate and PKCS#8 key: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Load WebSocket TLS settings from secret sources
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
+        Ok(WsTlsConfig {
+            ca_cert: secrets.get("BACKEND_WS_CA_CERT")?.filter(|s| !s.trim().is_empty()),
+            client_cert: secrets.get("BACKEND_WS_CLIENT_CERT")?.filter(|s| !s.trim().is_empty()),
+            client_key: secrets.get("BACKEND_WS_CLIENT_KEY")?.filter(|s| !s.trim().is_empty()),
+        })
+    }
+
+    /// Whether any setting differs from the system defaults
+    pub fn is_custom(&self) -> bool {
+        self.ca_cert.is_some() || self.client_cert.is_some()
+    }
+}
+
+/// OAuth2 client credentials for authenticating to the backend
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OAuthConfig {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: Option<String>,
+}
+
+impl OAuthConfig {
+    /// Load OAuth configuration from environment variables; `None` unless a token URL is set
+    pub fn from_env(secrets: &Secrets) -> Result<Option<Self>, String> {
+        let Some(token_url) = env::var("BACKEND_OAUTH_TOKEN_URL")
+            .ok()
+            .filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(OAuthConfig {
+            token_url,
+            client_id: env::var("BACKEND_OAUTH_CLIENT_ID").unwrap_or_default(),
+            client_secret: secrets.get("BACKEND_OAUTH_CLIENT_SECRET")?.unwrap_or_default(),
+            scope: env::var("BACKEND_OAUTH_SCOPE")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        }))
+    }
+}
+
 /// Session management configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionConfig {
     pub cleanup_interval_minutes: u64,
+    /// Idle time after which a session is expired, unless its call is still live
     pub max_age_minutes: i64,
+    /// Whether to ask Twilio if an idle session's call is still live before expiring it
+    pub check_call_status: bool,
+    /// How long past `max_age_minutes` a session is kept while its call status cannot be fetched
+    pub expiry_grace_minutes: i64,
+    /// Maximum caller turns per call (0 means unlimited)
+    pub max_turns: u32,
+    /// Maximum call duration in minutes (0 means unlimited)
+    pub max_call_duration_minutes: u64,
+    /// How long a caller's last session is offered to the backend when they call back (0 disables)
+    pub resume_window_minutes: u64,
 }
 
 impl SessionConfig {
@@ -154,41 +881,1929 @@ impl SessionConfig {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            check_call_status: env::var("SESSION_CHECK_CALL_STATUS")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase() == "true",
+            expiry_grace_minutes: env::var("SESSION_EXPIRY_GRACE_MINUTES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            max_turns: env::var("MAX_TURNS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            max_call_duration_minutes: env::var("MAX_CALL_DURATION_MINUTES")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            resume_window_minutes: env::var("CALLER_RESUME_WINDOW_MINUTES")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
         }
     }
 }
 
-/// Combined application configuration
+/// Twilio Media Streams configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub twilio: TwilioConfig,
-    pub backend: BackendConfig,
-    pub session: SessionConfig,
+pub struct MediaStreamConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub silence_threshold: f64,
+    pub silence_timeout_seconds: u64,
+    pub check_timeout_seconds: u64,
 }
 
-impl Config {
-    /// Validate the complete configuration
-    pub fn validate(&self) -> Result<(), String> {
-        self.twilio.validate()?;
-        self.backend.validate()?;
-        
-        Ok(())
-    }
-    
-    /// Create configuration from environment variables
-    pub fn from_env() -> Result<Self, String> {
-        let twilio = TwilioConfig::from_env()?;
-        let backend = BackendConfig::from_env()?;
-        let session = SessionConfig::from_env();
+impl MediaStreamConfig {
+    /// Load media stream configuration from environment variables
+    ///
+    /// The stream URL defaults to the webhook URL with a WebSocket scheme.
+    pub fn from_env(webhook_url: &str) -> Result<Self, String> {
+        let default_url = format!(
+            "{}/media_stream",
+            webhook_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1)
+        );
         
-        let config = Config {
-            twilio,
-            backend,
-            session,
+        let config = MediaStreamConfig {
+            enabled: env::var("MEDIA_STREAMS_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            url: env::var("MEDIA_STREAM_URL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or(default_url),
+            silence_threshold: env::var("SILENCE_ENERGY_THRESHOLD")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .map_err(|_| "SILENCE_ENERGY_THRESHOLD must be a valid number".to_string())?,
+            silence_timeout_seconds: env::var("SILENCE_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .map_err(|_| "SILENCE_TIMEOUT_SECONDS must be a valid number".to_string())?,
+            check_timeout_seconds: env::var("AUDIO_CHECK_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .map_err(|_| "AUDIO_CHECK_TIMEOUT_SECONDS must be a valid number".to_string())?,
         };
         
-        config.validate()?;
-        
         Ok(config)
     }
-}
\ No newline at end of file
+}
+
+/// Localized system phrase configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagesConfig {
+    pub dir: Option<String>,
+    pub default_language: String,
+    pub reload_interval_seconds: u64,
+}
+
+impl MessagesConfig {
+    /// Load message catalog configuration from environment variables
+    pub fn from_env() -> Self {
+        MessagesConfig {
+            dir: env::var("MESSAGES_DIR")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            default_language: env::var("DEFAULT_LANGUAGE")
+                .unwrap_or_else(|_| "en".to_string()),
+            reload_interval_seconds: env::var("MESSAGES_RELOAD_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+        }
+    }
+}
+
+/// Voice settings a backend response can switch to by name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoicePersona {
+    pub voice: String,
+    pub language: Option<String>,
+    /// SSML prosody rate (e.g. "90%", "slow")
+    #[serde(alias = "rate")]
+    pub speech_rate: Option<String>,
+}
+
+impl VoicePersona {
+    /// Twilio configuration with this persona's voice settings applied
+    pub fn apply(&self, twilio: &TwilioConfig) -> TwilioConfig {
+        let mut twilio = twilio.clone();
+        twilio.voice = self.voice.clone();
+        if self.language.is_some() {
+            twilio.language = self.language.clone();
+        }
+        if self.speech_rate.is_some() {
+            twilio.speech_rate = self.speech_rate.clone();
+        }
+        twilio
+    }
+}
+
+/// Speech recognition settings selected for a single call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeechSettings {
+    /// Twilio speech model (e.g. `googlev2_telephony`, `deepgram_nova-2`)
+    pub model: Option<String>,
+    pub enhanced: Option<bool>,
+}
+
+impl SpeechSettings {
+    /// Check that the selected model is one Twilio supports
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.model {
+            Some(model) if !is_supported_speech_model(model) => {
+                Err(format!("Unsupported speech model '{}'", model))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Twilio configuration with these speech settings applied
+    pub fn apply(&self, twilio: &TwilioConfig) -> TwilioConfig {
+        let mut twilio = twilio.clone();
+        if let Some(model) = &self.model {
+            twilio.speech_model = model.clone();
+        }
+        if let Some(enhanced) = self.enhanced {
+            twilio.speech_enhanced = enhanced;
+        }
+        twilio
+    }
+}
+
+/// Whether Twilio's `<Gather>` accepts a speech model name
+pub fn is_supported_speech_model(model: &str) -> bool {
+    matches!(
+        model,
+        "default"
+            | "numbers_and_commands"
+            | "phone_call"
+            | "experimental_conversations"
+            | "experimental_utterances"
+            | "googlev2_telephony"
+            | "googlev2_telephony_short"
+            | "googlev2_long"
+            | "googlev2_short"
+    ) || model.starts_with("deepgram_nova")
+}
+
+/// Caller language detection on the first turn of a call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageDetectionConfig {
+    pub enabled: bool,
+    /// Language the first Gather listens in until the backend reports the caller's
+    pub speech_language: String,
+    /// Speech model able to recognize the detection language
+    pub speech_model: String,
+    /// Voice used for each detected language, by lowercase language tag
+    pub voices: HashMap<String, String>,
+}
+
+impl LanguageDetectionConfig {
+    /// Load language detection configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let voices: HashMap<String, String> = match env::var("LANGUAGE_VOICES") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("LANGUAGE_VOICES must be a JSON object of language to voice: {}", e))?,
+            _ => HashMap::new(),
+        };
+
+        let config = LanguageDetectionConfig {
+            enabled: env::var("LANGUAGE_DETECTION_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            speech_language: env::var("LANGUAGE_DETECTION_SPEECH_LANGUAGE")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "multi".to_string()),
+            speech_model: env::var("LANGUAGE_DETECTION_SPEECH_MODEL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "deepgram_nova-3".to_string()),
+            voices: voices.into_iter()
+                .map(|(language, voice)| (language.to_lowercase(), voice))
+                .collect(),
+        };
+
+        if config.enabled && !is_supported_speech_model(&config.speech_model) {
+            return Err(format!("Unsupported LANGUAGE_DETECTION_SPEECH_MODEL '{}'", config.speech_model));
+        }
+
+        Ok(config)
+    }
+
+    /// Twilio configuration for a call's first turn, listening for any language
+    ///
+    /// Returns `None` when detection is disabled.
+    pub fn detecting(&self, twilio: &TwilioConfig) -> Option<TwilioConfig> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut twilio = twilio.clone();
+        twilio.speech_language = Some(self.speech_language.clone());
+        twilio.speech_model = self.speech_model.clone();
+        Some(twilio)
+    }
+
+    /// Twilio configuration speaking and listening in a detected language
+    ///
+    /// The voice for the language (or its primary subtag, e.g. `es` for `es-MX`)
+    /// replaces the configured voice when one is set.
+    pub fn apply(&self, twilio: &TwilioConfig, language: &str) -> TwilioConfig {
+        let mut twilio = twilio.clone();
+        let tag = language.to_lowercase();
+        let primary = tag.split('-').next().unwrap_or_default();
+
+        if let Some(voice) = self.voices.get(&tag).or_else(|| self.voices.get(primary)) {
+            twilio.voice = voice.clone();
+        }
+        twilio.language = Some(language.to_string());
+        twilio.speech_language = None;
+        twilio
+    }
+}
+
+/// Registry of voice personas
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersonaConfig {
+    pub personas: HashMap<String, VoicePersona>,
+}
+
+impl PersonaConfig {
+    /// Load personas from the VOICE_PERSONAS JSON object (name -> persona)
+    pub fn from_env() -> Result<Self, String> {
+        let personas = match env::var("VOICE_PERSONAS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("VOICE_PERSONAS must be a JSON object of personas: {}", e))?,
+            _ => HashMap::new(),
+        };
+
+        Ok(PersonaConfig { personas })
+    }
+
+    /// Look up a persona by name
+    pub fn get(&self, name: &str) -> Option<&VoicePersona> {
+        self.personas.get(name)
+    }
+}
+
+/// One arm of a prompt experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentVariant {
+    pub name: String,
+    /// Share of calls assigned the variant, relative to the other variants' weights
+    #[serde(default = "default_variant_weight")]
+    pub weight: u32,
+    /// Greeting spoken when the call is answered
+    #[serde(default)]
+    pub greeting: Option<String>,
+    /// Twilio or catalog voice the call speaks with
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// Gather timeouts in seconds, replacing DEFAULT_TIMEOUT, SHORT_ANSWER_TIMEOUT and OPEN_ANSWER_TIMEOUT
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    #[serde(default)]
+    pub short_answer_timeout: Option<u32>,
+    #[serde(default)]
+    pub open_answer_timeout: Option<u32>,
+}
+
+fn default_variant_weight() -> u32 {
+    1
+}
+
+impl ExperimentVariant {
+    /// Twilio configuration with this variant's voice and timeouts applied
+    pub fn apply(&self, twilio: &TwilioConfig) -> TwilioConfig {
+        let mut twilio = twilio.clone();
+        if let Some(voice) = &self.voice {
+            twilio.voice = voice.clone();
+        }
+        if let Some(timeout) = self.timeout {
+            twilio.default_timeout = timeout;
+        }
+        if let Some(timeout) = self.short_answer_timeout {
+            twilio.short_answer_timeout = timeout;
+        }
+        if let Some(timeout) = self.open_answer_timeout {
+            twilio.open_answer_timeout = timeout;
+        }
+        twilio
+    }
+}
+
+/// A/B experiment outbound calls are split across
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    /// Experiment name recorded with each assignment, to tell experiments apart in results
+    pub name: String,
+    /// Variants calls are assigned by weight; none disables the experiment
+    pub variants: Vec<ExperimentVariant>,
+}
+
+impl ExperimentConfig {
+    /// Load the experiment from EXPERIMENT_NAME and the EXPERIMENT_VARIANTS JSON array
+    pub fn from_env() -> Result<Self, String> {
+        let variants: Vec<ExperimentVariant> = match env::var("EXPERIMENT_VARIANTS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("EXPERIMENT_VARIANTS must be a JSON array of variants: {}", e))?,
+            _ => Vec::new(),
+        };
+
+        for (index, variant) in variants.iter().enumerate() {
+            if variant.name.trim().is_empty() {
+                return Err(format!("Experiment variant {} has no name", index));
+            }
+            if variants[..index].iter().any(|v| v.name == variant.name) {
+                return Err(format!("Experiment variant '{}' is defined twice", variant.name));
+            }
+            if [variant.timeout, variant.short_answer_timeout, variant.open_answer_timeout].contains(&Some(0)) {
+                return Err(format!("Experiment variant '{}' timeouts must be greater than 0", variant.name));
+            }
+        }
+        if !variants.is_empty() && variants.iter().all(|v| v.weight == 0) {
+            return Err("At least one experiment variant must have a weight above 0".to_string());
+        }
+
+        Ok(ExperimentConfig {
+            name: env::var("EXPERIMENT_NAME")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "default".to_string()),
+            variants,
+        })
+    }
+
+    /// Look up a variant by name
+    pub fn get(&self, name: &str) -> Option<&ExperimentVariant> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+}
+
+/// Greeting played when an inbound call starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GreetingConfig {
+    /// Default greeting text by lowercase language tag, used when the backend sends none
+    pub texts: HashMap<String, String>,
+    /// Short audio played while the backend session opens
+    pub earcon_url: Option<String>,
+    /// How long to wait for the backend after the earcon before giving up
+    pub warmup_timeout_seconds: u64,
+}
+
+impl GreetingConfig {
+    /// Load greeting configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let texts: HashMap<String, String> = match env::var("GREETING_TEXTS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("GREETING_TEXTS must be a JSON object of language to text: {}", e))?,
+            _ => HashMap::new(),
+        };
+
+        Ok(GreetingConfig {
+            texts: texts.into_iter()
+                .map(|(language, text)| (language.to_lowercase(), text))
+                .collect(),
+            earcon_url: env::var("GREETING_EARCON_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            warmup_timeout_seconds: env::var("GREETING_WARMUP_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .map_err(|_| "GREETING_WARMUP_TIMEOUT_SECONDS must be a valid number".to_string())?,
+        })
+    }
+
+    /// Greeting text for the first language in a fallback chain that has one
+    pub fn text(&self, languages: &[String]) -> Option<String> {
+        languages.iter().find_map(|language| self.texts.get(language).cloned())
+    }
+}
+
+/// Background dependency health check configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// How often dependencies are probed
+    pub check_interval_seconds: u64,
+    /// Backend path answering health probes
+    pub backend_path: String,
+    /// Time allowed for each probe
+    pub probe_timeout_ms: u64,
+}
+
+impl HealthConfig {
+    /// Load health check configuration from environment variables
+    pub fn from_env() -> Self {
+        HealthConfig {
+            check_interval_seconds: env::var("HEALTH_CHECK_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&s| s > 0)
+                .unwrap_or(30),
+            backend_path: env::var("BACKEND_HEALTH_PATH")
+                .unwrap_or_else(|_| "/health".to_string()),
+            probe_timeout_ms: env::var("HEALTH_PROBE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "3000".to_string())
+                .parse()
+                .unwrap_or(3000),
+        }
+    }
+}
+
+/// Size limits on request bodies, protecting the public webhook endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitsConfig {
+    /// Largest URL-encoded or multipart form body, which Twilio callbacks use
+    pub form_bytes: u64,
+    /// Largest JSON body accepted by the API
+    pub json_bytes: u64,
+    /// Largest CSV body accepted by the campaign import
+    pub csv_bytes: u64,
+}
+
+impl LimitsConfig {
+    /// Load request limits from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        Ok(LimitsConfig {
+            form_bytes: env::var("REQUEST_FORM_LIMIT_BYTES")
+                .unwrap_or_else(|_| "16384".to_string())
+                .parse()
+                .map_err(|_| "REQUEST_FORM_LIMIT_BYTES must be a valid number".to_string())?,
+            json_bytes: env::var("REQUEST_JSON_LIMIT_BYTES")
+                .unwrap_or_else(|_| "1048576".to_string())
+                .parse()
+                .map_err(|_| "REQUEST_JSON_LIMIT_BYTES must be a valid number".to_string())?,
+            csv_bytes: env::var("REQUEST_CSV_LIMIT_BYTES")
+                .unwrap_or_else(|_| "1048576".to_string())
+                .parse()
+                .map_err(|_| "REQUEST_CSV_LIMIT_BYTES must be a valid number".to_string())?,
+        })
+    }
+}
+
+/// Redis connection configuration shared by cross-instance features
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RedisConfig {
+    pub url: Option<String>,
+    pub key_prefix: String,
+}
+
+impl RedisConfig {
+    /// Load Redis configuration from environment variables
+    pub fn from_env() -> Self {
+        RedisConfig {
+            url: env::var("REDIS_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            key_prefix: env::var("REDIS_KEY_PREFIX")
+                .unwrap_or_else(|_| "twilio-bot".to_string()),
+        }
+    }
+}
+
+/// Multi-region session replication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    pub enabled: bool,
+    pub region: String,
+    pub ttl_seconds: u64,
+    /// How long an instance keeps its calls without renewing its lease; other
+    /// instances only take a call over once its owner's lease has lapsed
+    pub lease_seconds: u64,
+}
+
+impl ReplicationConfig {
+    /// Load replication configuration from environment variables
+    pub fn from_env() -> Self {
+        ReplicationConfig {
+            enabled: env::var("SESSION_REPLICATION_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            region: env::var("REGION_NAME")
+                .unwrap_or_else(|_| "default".to_string()),
+            ttl_seconds: env::var("SESSION_REPLICA_TTL_SECONDS")
+                .unwrap_or_else(|_| "7200".to_string())
+                .parse()
+                .unwrap_or(7200),
+            lease_seconds: env::var("SESSION_REPLICA_LEASE_SECONDS")
+                .unwrap_or_else(|_| "15".to_string())
+                .parse()
+                .unwrap_or(15)
+                .max(3),
+        }
+    }
+}
+
+/// Multi-instance cluster configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    pub enabled: bool,
+    /// Identifies this replica as the holder of WebSocket leases
+    pub instance_id: String,
+    /// How long a replica owns a session's WebSocket client without renewing
+    pub lease_ttl_seconds: u64,
+    /// How long shared session state outlives its last update
+    pub session_ttl_seconds: u64,
+    /// How long a replica holds a call's turn lease before it expires unreleased
+    pub turn_lease_ms: u64,
+}
+
+impl ClusterConfig {
+    /// Load cluster configuration from environment variables
+    pub fn from_env() -> Self {
+        ClusterConfig {
+            enabled: env::var("CLUSTER_MODE_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            instance_id: env::var("INSTANCE_ID")
+                .or_else(|_| env::var("HOSTNAME"))
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            lease_ttl_seconds: env::var("CLUSTER_LEASE_TTL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30)
+                .max(3),
+            session_ttl_seconds: env::var("CLUSTER_SESSION_TTL_SECONDS")
+                .unwrap_or_else(|_| "7200".to_string())
+                .parse()
+                .unwrap_or(7200),
+            turn_lease_ms: env::var("CLUSTER_TURN_LEASE_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000)
+                .max(100),
+        }
+    }
+}
+
+/// Call detail record configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CdrConfig {
+    /// Number of recent call records kept in memory
+    pub retention: usize,
+}
+
+impl CdrConfig {
+    /// Load CDR configuration from environment variables
+    pub fn from_env() -> Self {
+        CdrConfig {
+            retention: env::var("CDR_RETENTION")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+        }
+    }
+}
+
+/// Call cost tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostConfig {
+    /// Daily spend per tenant above which its outbound calls are refused, unless the tenant sets its own
+    ///
+    /// Spend is tracked in memory, so each instance enforces the budget on the calls it placed.
+    pub daily_budget: Option<f64>,
+    /// Delay before fetching a finished call's price, which Twilio sets after the call
+    pub price_fetch_delay_seconds: u64,
+    /// Times to fetch a call's price before giving up
+    pub price_fetch_attempts: u32,
+}
+
+impl CostConfig {
+    /// Load cost configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        Ok(CostConfig {
+            daily_budget: match env::var("DAILY_CALL_BUDGET") {
+                Ok(budget) if !budget.trim().is_empty() => Some(
+                    budget.trim()
+                        .parse()
+                        .ok()
+                        .filter(|b: &f64| *b >= 0.0)
+                        .ok_or_else(|| "DAILY_CALL_BUDGET must be a non-negative number".to_string())?
+                ),
+                _ => None,
+            },
+            price_fetch_delay_seconds: env::var("CALL_PRICE_FETCH_DELAY_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            price_fetch_attempts: env::var("CALL_PRICE_FETCH_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+        })
+    }
+}
+
+/// What to do with an inbound call matching a screening rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningAction {
+    /// Answer the call as usual
+    Proceed,
+    /// Decline the call without answering it
+    Reject,
+    /// Answer, speak the rule's message and hang up
+    Message,
+}
+
+/// Inbound call screening rule; every condition it sets must match the caller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningRule {
+    /// Caller number prefix (e.g. "+1900")
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Caller country reported by Twilio (ISO code, e.g. "US")
+    #[serde(default)]
+    pub country: Option<String>,
+    /// Whether the caller withheld their number
+    #[serde(default)]
+    pub anonymous: Option<bool>,
+    pub action: ScreeningAction,
+    /// Message spoken by the `message` action
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Reason given by the `reject` action: "rejected" (default) or "busy"
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Inbound call screening configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScreeningConfig {
+    /// Rules checked in order; the first match decides, and unmatched calls proceed
+    pub rules: Vec<ScreeningRule>,
+}
+
+impl ScreeningConfig {
+    /// Load screening rules from the SCREENING_RULES JSON array
+    pub fn from_env() -> Result<Self, String> {
+        let rules: Vec<ScreeningRule> = match env::var("SCREENING_RULES") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("SCREENING_RULES must be a JSON array of rules: {}", e))?,
+            _ => Vec::new(),
+        };
+
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.action == ScreeningAction::Message && rule.message.as_deref().is_none_or(|m| m.trim().is_empty()) {
+                return Err(format!("Screening rule {} uses the message action without a message", index));
+            }
+            if let Some(reason) = rule.reason.as_deref().filter(|r| !matches!(*r, "rejected" | "busy")) {
+                return Err(format!("Screening rule {} has unknown reject reason '{}'", index, reason));
+            }
+        }
+
+        Ok(ScreeningConfig { rules })
+    }
+}
+
+/// Opening hours of a phone number
+///
+/// ```json
+/// {
+///   "timezone": "America/New_York",
+///   "hours": {"mon": "09:00-12:00,13:00-17:00", "sat": "10:00-14:00"},
+///   "holidays": ["2026-12-25"],
+///   "voicemail": true,
+///   "callback_url": "https://example.com/voicemail"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusinessSchedule {
+    /// IANA time zone the hours are in
+    pub timezone: String,
+    /// Comma-separated `HH:MM-HH:MM` ranges per weekday (`mon` to `sun`); days without hours are closed
+    #[serde(default)]
+    pub hours: HashMap<String, String>,
+    /// Dates the number is closed all day, in its time zone
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+    /// Message spoken or played outside hours instead of the catalog's after-hours phrase
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Offer to record a message after the after-hours message
+    #[serde(default)]
+    pub voicemail: bool,
+    /// URL the call's result, with the recorded message, is posted to like a call request's `callback_url`
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    /// Text the caller is sent outside hours
+    #[serde(default)]
+    pub sms: Option<String>,
+}
+
+/// Business hours of inbound numbers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BusinessHoursConfig {
+    /// Schedules keyed by the called number; numbers without one are always open
+    pub schedules: HashMap<String, BusinessSchedule>,
+}
+
+impl BusinessHoursConfig {
+    /// Load schedules from the BUSINESS_HOURS JSON object of number to schedule
+    pub fn from_env() -> Result<Self, String> {
+        let schedules: HashMap<String, BusinessSchedule> = match env::var("BUSINESS_HOURS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("BUSINESS_HOURS must be a JSON object of phone number to schedule: {}", e))?,
+            _ => HashMap::new(),
+        };
+
+        for (number, schedule) in &schedules {
+            schedule.validate()
+                .map_err(|e| format!("Business hours of {}: {}", number, e))?;
+        }
+
+        Ok(BusinessHoursConfig { schedules })
+    }
+
+    /// Schedule of a called number, if it has one
+    pub fn schedule(&self, number: &str) -> Option<&BusinessSchedule> {
+        self.schedules.get(number.trim())
+    }
+}
+
+/// How a partial transcription is judged to be a complete utterance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UtteranceStrategy {
+    /// Ends with sentence punctuation
+    Punctuation,
+    /// Has at least a minimum number of words
+    MinTokens,
+    /// Stayed unchanged for a trailing silence
+    Silence,
+    /// Matches one of the language's regular expressions
+    Patterns,
+}
+
+/// When partial results are complete enough to start speculative generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtteranceConfig {
+    pub strategy: UtteranceStrategy,
+    /// Words a partial needs with the `min_tokens` strategy
+    pub min_tokens: usize,
+    /// Trailing silence ending an utterance with the `silence` strategy
+    pub silence_ms: u64,
+    /// Regular expressions by language for the `patterns` strategy; `default` applies to other languages
+    pub patterns: HashMap<String, Vec<String>>,
+}
+
+impl UtteranceConfig {
+    /// Load utterance completion configuration from environment variables
+    ///
+    /// The trailing silence defaults to half the open-answer `speechTimeout`, so
+    /// generation starts before Twilio ends the Gather.
+    pub fn from_env(twilio: &TwilioConfig) -> Result<Self, String> {
+        let strategy = match env::var("UTTERANCE_COMPLETION").unwrap_or_default().to_lowercase().as_str() {
+            "" | "punctuation" => UtteranceStrategy::Punctuation,
+            "min_tokens" => UtteranceStrategy::MinTokens,
+            "silence" => UtteranceStrategy::Silence,
+            "patterns" => UtteranceStrategy::Patterns,
+            other => return Err(format!(
+                "UTTERANCE_COMPLETION must be punctuation, min_tokens, silence or patterns, got '{}'", other
+            )),
+        };
+
+        let patterns: HashMap<String, Vec<String>> = match env::var("UTTERANCE_PATTERNS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("UTTERANCE_PATTERNS must be a JSON object of language to regular expressions: {}", e))?,
+            _ => HashMap::new(),
+        };
+        if let Some((pattern, e)) = patterns.values().flatten().find_map(|p| regex::Regex::new(p).err().map(|e| (p, e))) {
+            return Err(format!("Invalid UTTERANCE_PATTERNS pattern '{}': {}", pattern, e));
+        }
+        if strategy == UtteranceStrategy::Patterns && patterns.is_empty() {
+            return Err("UTTERANCE_PATTERNS must be set when UTTERANCE_COMPLETION is patterns".to_string());
+        }
+
+        let speech_timeout_ms = twilio.open_answer_speech_timeout.parse::<f64>()
+            .map(|seconds| (seconds * 500.0) as u64)
+            .unwrap_or(1000);
+
+        Ok(UtteranceConfig {
+            strategy,
+            min_tokens: env::var("UTTERANCE_MIN_TOKENS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(4),
+            silence_ms: env::var("UTTERANCE_SILENCE_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(speech_timeout_ms),
+            patterns,
+        })
+    }
+}
+
+/// How callers are handled while the backend is unavailable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutageAction {
+    /// Apologize and hang up
+    Hangup,
+    /// Apologize and transfer the caller to the fallback number
+    Transfer,
+    /// Apologize and record a message
+    Voicemail,
+}
+
+/// Who recognizes the caller's speech
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SttProvider {
+    /// Twilio's Gather verb
+    Twilio,
+    /// Deepgram streaming API, fed from the Media Stream
+    Deepgram,
+    /// AssemblyAI streaming API, fed from the Media Stream
+    AssemblyAi,
+}
+
+/// Speech recognition configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SttConfig {
+    pub provider: SttProvider,
+    /// API key of the streaming provider
+    pub api_key: Option<String>,
+    /// Provider model (e.g. "nova-2-phonecall"); the provider default when unset
+    pub model: Option<String>,
+    /// Streaming endpoint replacing the provider's, e.g. for a regional or self-hosted deployment
+    pub url: Option<String>,
+}
+
+impl SttConfig {
+    /// Load speech recognition configuration from environment variables and secret sources
+    ///
+    /// Streaming providers hear the caller through the Media Stream, so they need
+    /// Media Streams enabled.
+    pub fn from_env(secrets: &Secrets, media: &MediaStreamConfig) -> Result<Self, String> {
+        let provider = match env::var("STT_PROVIDER").unwrap_or_default().to_lowercase().as_str() {
+            "" | "twilio" => SttProvider::Twilio,
+            "deepgram" => SttProvider::Deepgram,
+            "assemblyai" => SttProvider::AssemblyAi,
+            other => return Err(format!("STT_PROVIDER must be twilio, deepgram or assemblyai, got '{}'", other)),
+        };
+
+        let config = SttConfig {
+            provider,
+            api_key: secrets.get("STT_API_KEY")?,
+            model: env::var("STT_MODEL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            url: env::var("STT_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        };
+
+        if config.is_streaming() {
+            if !media.enabled {
+                return Err("MEDIA_STREAMS_ENABLED must be true to use a streaming STT_PROVIDER".to_string());
+            }
+            if config.api_key.is_none() {
+                return Err("STT_API_KEY must be set for the streaming STT_PROVIDER".to_string());
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Whether speech is recognized from the Media Stream rather than by Gather
+    pub fn is_streaming(&self) -> bool {
+        self.provider != SttProvider::Twilio
+    }
+}
+
+/// Text-to-speech provider speaking catalog voices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsProvider {
+    /// Twilio speaks with its own voices
+    Twilio,
+    ElevenLabs,
+    Azure,
+}
+
+/// How synthesized speech reaches the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsDelivery {
+    /// Twilio plays audio streamed from a signed URL
+    Play,
+    /// Replies to streamed speech are sent over a bidirectional Media Stream
+    MediaStream,
+}
+
+/// A voice of the TTS provider, selected by its catalog name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TtsVoice {
+    /// Provider voice ID (e.g. an ElevenLabs voice ID or "en-US-JennyNeural")
+    pub id: String,
+    /// Language the voice speaks, defaulting to the call's
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// Text-to-speech configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    pub provider: TtsProvider,
+    /// API key of the provider
+    pub api_key: Option<String>,
+    /// Azure Speech region (e.g. "westeurope")
+    pub region: String,
+    /// Provider model (e.g. "eleven_flash_v2_5"); the provider default when unset
+    pub model: Option<String>,
+    /// Endpoint replacing the provider's, e.g. for a private deployment
+    pub url: Option<String>,
+    /// Voices by catalog name; a voice setting naming one is synthesized by the provider
+    pub voices: HashMap<String, TtsVoice>,
+    pub delivery: TtsDelivery,
+}
+
+impl TtsConfig {
+    /// Load text-to-speech configuration from environment variables and secret sources
+    ///
+    /// Media Stream delivery sends replies to the caller's streamed speech, so it
+    /// needs a streaming STT provider.
+    pub fn from_env(secrets: &Secrets, stt: &SttConfig) -> Result<Self, String> {
+        let provider = match env::var("TTS_PROVIDER").unwrap_or_default().to_lowercase().as_str() {
+            "" | "twilio" => TtsProvider::Twilio,
+            "elevenlabs" => TtsProvider::ElevenLabs,
+            "azure" => TtsProvider::Azure,
+            other => return Err(format!("TTS_PROVIDER must be twilio, elevenlabs or azure, got '{}'", other)),
+        };
+        let delivery = match env::var("TTS_DELIVERY").unwrap_or_default().to_lowercase().as_str() {
+            "" | "play" => TtsDelivery::Play,
+            "media_stream" => TtsDelivery::MediaStream,
+            other => return Err(format!("TTS_DELIVERY must be play or media_stream, got '{}'", other)),
+        };
+        let voices = match env::var("TTS_VOICES") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("TTS_VOICES must be a JSON object of voice name to voice: {}", e))?,
+            _ => HashMap::new(),
+        };
+
+        let config = TtsConfig {
+            provider,
+            api_key: secrets.get("TTS_API_KEY")?,
+            region: env::var("TTS_REGION").unwrap_or_else(|_| "eastus".to_string()),
+            model: env::var("TTS_MODEL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            url: env::var("TTS_URL")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            voices,
+            delivery,
+        };
+
+        if config.provider == TtsProvider::Twilio {
+            if !config.voices.is_empty() {
+                return Err("TTS_PROVIDER must be set to use the TTS_VOICES catalog".to_string());
+            }
+            if config.delivery == TtsDelivery::MediaStream {
+                return Err("TTS_PROVIDER must be set for media_stream TTS_DELIVERY".to_string());
+            }
+        } else if config.api_key.is_none() {
+            return Err("TTS_API_KEY must be set for the TTS_PROVIDER".to_string());
+        }
+        if config.delivery == TtsDelivery::MediaStream && !stt.is_streaming() {
+            return Err("A streaming STT_PROVIDER must be set for media_stream TTS_DELIVERY".to_string());
+        }
+
+        Ok(config)
+    }
+
+    /// Twilio configuration speaking with the provider when its voice is a catalog voice
+    pub fn apply(&self, twilio: &TwilioConfig) -> TwilioConfig {
+        let mut twilio = twilio.clone();
+        twilio.tts_voice = self.voices.contains_key(&twilio.voice).then(|| twilio.voice.clone());
+        twilio
+    }
+}
+
+/// Call flow used when the backend's circuit breaker is open or its retries are exhausted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutageConfig {
+    /// Outage flow; unset keeps the generic error handling
+    pub action: Option<OutageAction>,
+    /// Number or SIP address the transfer action dials
+    pub fallback_number: Option<String>,
+    /// Apology spoken first, replacing the technical difficulties phrase
+    pub message: Option<String>,
+}
+
+impl OutageConfig {
+    /// Load outage configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let action = match env::var("BACKEND_OUTAGE_ACTION").ok().filter(|s| !s.is_empty()) {
+            Some(action) => Some(match action.to_lowercase().as_str() {
+                "hangup" => OutageAction::Hangup,
+                "transfer" => OutageAction::Transfer,
+                "voicemail" => OutageAction::Voicemail,
+                other => return Err(format!(
+                    "BACKEND_OUTAGE_ACTION must be hangup, transfer or voicemail, got '{}'", other
+                )),
+            }),
+            None => None,
+        };
+        let fallback_number = env::var("OUTAGE_FALLBACK_NUMBER")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        if action == Some(OutageAction::Transfer) && fallback_number.is_none() {
+            return Err("OUTAGE_FALLBACK_NUMBER must be set when BACKEND_OUTAGE_ACTION is transfer".to_string());
+        }
+
+        Ok(OutageConfig {
+            action,
+            fallback_number,
+            message: env::var("OUTAGE_MESSAGE")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        })
+    }
+}
+
+/// Handoff of callers the backend flags as likely to escalate
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SentimentConfig {
+    /// Escalation risk (0 to 1) at or above which the caller is handed off; unset disables it
+    pub transfer_threshold: Option<f64>,
+    /// Number or SIP address to transfer to; unset places the caller in the agent queue
+    pub transfer_target: Option<String>,
+}
+
+impl SentimentConfig {
+    /// Load sentiment configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let transfer_threshold = match env::var("ESCALATION_RISK_THRESHOLD").ok().filter(|s| !s.is_empty()) {
+            Some(threshold) => Some(threshold.parse::<f64>()
+                .map_err(|_| "ESCALATION_RISK_THRESHOLD must be a valid number".to_string())?),
+            None => None,
+        };
+        if transfer_threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+            return Err("ESCALATION_RISK_THRESHOLD must be between 0 and 1".to_string());
+        }
+
+        Ok(SentimentConfig {
+            transfer_threshold,
+            transfer_target: env::var("ESCALATION_RISK_TRANSFER_TARGET")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        })
+    }
+}
+
+/// Session snapshot configuration for carrying live calls across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// File sessions are written to on shutdown and read from at startup
+    pub path: Option<String>,
+    /// Store snapshots in Redis instead of a file
+    pub use_redis: bool,
+    /// Snapshots older than this are ignored at startup
+    pub max_age_seconds: u64,
+}
+
+impl SnapshotConfig {
+    /// Load snapshot configuration from environment variables
+    pub fn from_env() -> Self {
+        SnapshotConfig {
+            path: env::var("SESSION_SNAPSHOT_PATH")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            use_redis: env::var("SESSION_SNAPSHOT_REDIS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            max_age_seconds: env::var("SESSION_SNAPSHOT_MAX_AGE_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+        }
+    }
+}
+
+/// A named step of the response text post-processing pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseProcessor {
+    /// Remove markdown formatting the backend's model may produce
+    StripMarkdown,
+    /// Replace configured abbreviations with their spoken form
+    ExpandAbbreviations,
+    /// Spell out whole numbers as words
+    ExpandNumbers,
+    /// Speak numbers, currency amounts, dates and times in the call's language
+    Verbalize,
+    /// Replace text matching the configured patterns
+    Redact,
+    /// Split long responses into chunks spoken with a pause between them
+    Split,
+}
+
+impl ResponseProcessor {
+    /// Look up a processor by its configuration name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "strip_markdown" => Some(ResponseProcessor::StripMarkdown),
+            "expand_abbreviations" => Some(ResponseProcessor::ExpandAbbreviations),
+            "expand_numbers" => Some(ResponseProcessor::ExpandNumbers),
+            "verbalize" => Some(ResponseProcessor::Verbalize),
+            "redact" => Some(ResponseProcessor::Redact),
+            "split" => Some(ResponseProcessor::Split),
+            _ => None,
+        }
+    }
+}
+
+/// Post-processing applied to backend response text before it is spoken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostprocessConfig {
+    /// Processors applied in order; empty disables post-processing
+    pub processors: Vec<ResponseProcessor>,
+    /// Abbreviations and their spoken form
+    pub abbreviations: HashMap<String, String>,
+    /// Text spoken in place of matches of the redaction patterns
+    pub redaction: String,
+    /// Longest chunk the split processor leaves, and longest Say of a streamed reply
+    pub max_chunk_chars: usize,
+}
+
+impl PostprocessConfig {
+    /// Load post-processing configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let processors = env::var("RESPONSE_PROCESSORS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| ResponseProcessor::from_name(name)
+                .ok_or_else(|| format!("Unknown response processor '{}' in RESPONSE_PROCESSORS", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let abbreviations = match env::var("RESPONSE_ABBREVIATIONS") {
+            Ok(json) if !json.trim().is_empty() => serde_json::from_str(&json)
+                .map_err(|e| format!("RESPONSE_ABBREVIATIONS must be a JSON object of abbreviation to expansion: {}", e))?,
+            _ => [("e.g.", "for example"), ("i.e.", "that is"), ("etc.", "et cetera"), ("approx.", "approximately")]
+                .into_iter()
+                .map(|(abbreviation, expansion)| (abbreviation.to_string(), expansion.to_string()))
+                .collect(),
+        };
+
+        Ok(PostprocessConfig {
+            processors,
+            abbreviations,
+            redaction: env::var("RESPONSE_REDACTION")
+                .unwrap_or_else(|_| "redacted".to_string()),
+            max_chunk_chars: env::var("RESPONSE_MAX_CHUNK_CHARS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .ok()
+                .filter(|max| *max > 0)
+                .unwrap_or(300),
+        })
+    }
+}
+
+/// Storage for notifications that could not be delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterConfig {
+    /// JSON lines file failed notifications are appended to
+    pub path: Option<String>,
+    /// Keep failed notifications in a Redis list instead of a file
+    pub use_redis: bool,
+}
+
+impl DeadLetterConfig {
+    /// Load dead-letter configuration from environment variables
+    pub fn from_env() -> Self {
+        DeadLetterConfig {
+            path: env::var("DEAD_LETTER_PATH")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            use_redis: env::var("DEAD_LETTER_REDIS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+        }
+    }
+}
+
+/// Outbound dialing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialerConfig {
+    /// Calls created per second, matching the account's Twilio CPS; 0 disables the limit
+    pub calls_per_second: f64,
+    /// Calls that may be created at once after an idle period
+    pub burst: u32,
+    /// Share the limit with the other replicas through Redis
+    pub use_redis: bool,
+    /// Agents taking the calls agent-paced campaigns hand off; 0 disables agent pacing
+    pub agent_pool_size: u32,
+    /// Average time an agent spends on a handed-off call, after which they are assumed free
+    pub average_handle_seconds: u64,
+    /// Longest a paced call holds its slot before a handoff, in case its end is reported to another replica or lost
+    pub max_connecting_seconds: u64,
+}
+
+impl DialerConfig {
+    /// Load dialer configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let config = DialerConfig {
+            calls_per_second: env::var("DIALER_CPS")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .map_err(|_| "DIALER_CPS must be a valid number".to_string())?,
+            burst: env::var("DIALER_CPS_BURST")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .map_err(|_| "DIALER_CPS_BURST must be a valid number".to_string())?,
+            use_redis: env::var("DIALER_CPS_REDIS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            agent_pool_size: env::var("DIALER_AGENT_POOL_SIZE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .map_err(|_| "DIALER_AGENT_POOL_SIZE must be a valid number".to_string())?,
+            average_handle_seconds: env::var("DIALER_AVERAGE_HANDLE_SECONDS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .map_err(|_| "DIALER_AVERAGE_HANDLE_SECONDS must be a valid number".to_string())?,
+            max_connecting_seconds: env::var("DIALER_MAX_CONNECTING_SECONDS")
+                .unwrap_or_else(|_| "1800".to_string())
+                .parse()
+                .map_err(|_| "DIALER_MAX_CONNECTING_SECONDS must be a valid number".to_string())?,
+        };
+        
+        if !config.calls_per_second.is_finite() || config.calls_per_second < 0.0 {
+            return Err("DIALER_CPS must not be negative".to_string());
+        }
+        if config.burst == 0 {
+            return Err("DIALER_CPS_BURST must be greater than 0".to_string());
+        }
+        if config.average_handle_seconds == 0 {
+            return Err("DIALER_AVERAGE_HANDLE_SECONDS must be greater than 0".to_string());
+        }
+        if config.max_connecting_seconds == 0 {
+            return Err("DIALER_MAX_CONNECTING_SECONDS must be greater than 0".to_string());
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Turn audit log configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Record every turn and call decision in the hash-chained audit log
+    pub enabled: bool,
+    /// JSON lines file the audit log is appended to; kept in memory when unset
+    pub path: Option<String>,
+    /// Days audit records are kept; 0 keeps them indefinitely
+    pub retention_days: u64,
+}
+
+impl AuditConfig {
+    /// Load audit log configuration from environment variables
+    pub fn from_env() -> Self {
+        AuditConfig {
+            enabled: env::var("AUDIT_LOG_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            path: env::var("AUDIT_LOG_PATH")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            retention_days: env::var("AUDIT_RETENTION_DAYS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Call recording configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// Record calls after playing the consent announcement
+    pub enabled: bool,
+    /// Announcement played before the first prompt of a recorded call
+    pub consent_message: String,
+    /// Country calling codes (e.g. "49", "+33") whose callers are never recorded
+    pub excluded_country_codes: Vec<String>,
+    /// Key that stops the recording when pressed during the consent announcement; unset disables opt-out
+    pub opt_out_digit: Option<String>,
+    /// Confirmation spoken once the caller has opted out
+    pub opt_out_message: String,
+}
+
+impl RecordingConfig {
+    /// Load recording configuration from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let opt_out_digit = env::var("RECORDING_OPT_OUT_DIGIT")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        if opt_out_digit.as_deref().is_some_and(|d| d.len() != 1 || !d.chars().all(|c| c.is_ascii_digit() || c == '*' || c == '#')) {
+            return Err("RECORDING_OPT_OUT_DIGIT must be a single key: 0-9, * or #".to_string());
+        }
+
+        Ok(RecordingConfig {
+            enabled: env::var("CALL_RECORDING_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            consent_message: env::var("RECORDING_CONSENT_MESSAGE")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "This call may be recorded for quality and training purposes.".to_string()),
+            excluded_country_codes: env::var("RECORDING_EXCLUDED_COUNTRY_CODES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|code| code.trim().trim_start_matches('+').to_string())
+                .filter(|code| !code.is_empty())
+                .collect(),
+            opt_out_digit,
+            opt_out_message: env::var("RECORDING_OPT_OUT_MESSAGE")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "This call will not be recorded.".to_string()),
+        })
+    }
+}
+
+/// Cache of backend-rendered prompt audio played instead of Twilio's built-in voices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptCacheConfig {
+    pub enabled: bool,
+    /// Local directory rendered audio is stored in
+    pub dir: String,
+    /// S3-compatible bucket URL rendered audio is uploaded to instead of the local directory
+    pub bucket_url: Option<String>,
+    /// Base URL Twilio fetches cached audio from
+    pub public_url: String,
+    /// Backend path rendering text to audio
+    pub tts_path: String,
+}
+
+impl PromptCacheConfig {
+    /// Load prompt cache configuration from environment variables
+    pub fn from_env(webhook_url: &str) -> Self {
+        let bucket_url = env::var("PROMPT_CACHE_BUCKET_URL")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim_end_matches('/').to_string());
+
+        // Audio in a bucket is fetched from the bucket, local audio from this service
+        let default_public_url = bucket_url
+            .clone()
+            .unwrap_or_else(|| format!("{}/prompts", webhook_url));
+
+        PromptCacheConfig {
+            enabled: env::var("PROMPT_CACHE_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            dir: env::var("PROMPT_CACHE_DIR")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "prompt_cache".to_string()),
+            bucket_url,
+            public_url: env::var("PROMPT_CACHE_PUBLIC_URL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.trim_end_matches('/').to_string())
+                .unwrap_or(default_public_url),
+            tts_path: env::var("PROMPT_TTS_PATH")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "/tts".to_string()),
+        }
+    }
+}
+
+/// One signing secret, or several while rotating with the current one first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SigningSecrets {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Most retries of a result callback; the wait between them doubles up to about 8 minutes
+pub const MAX_CALLBACK_RETRY_ATTEMPTS: usize = 10;
+
+/// Configuration for result callbacks sent to API consumers
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CallbackConfig {
+    /// Secret used to sign callback payloads; callbacks are refused when no secret applies
+    pub signing_secret: Option<String>,
+    /// Secret being rotated out, still signed with until consumers switch to the new one
+    pub previous_signing_secret: Option<String>,
+    /// Signing secrets of tenants' callbacks by tenant ID, current first, used instead of the shared ones
+    pub tenant_signing_secrets: HashMap<String, Vec<String>>,
+    pub retry_attempts: usize,
+    pub timeout_seconds: u64,
+}
+
+impl CallbackConfig {
+    /// Load callback configuration from environment variables and secret sources
+    ///
+    /// `CALLBACK_TENANT_SIGNING_SECRETS` maps tenant IDs to a secret or a list of
+    /// secrets, e.g. `{"acme": ["new-secret", "old-secret"]}` while rotating.
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
+        let tenant_signing_secrets = match secrets.get("CALLBACK_TENANT_SIGNING_SECRETS")? {
+            Some(json) if !json.trim().is_empty() => {
+                let parsed: HashMap<String, SigningSecrets> = serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid CALLBACK_TENANT_SIGNING_SECRETS: {}", e))?;
+                parsed.into_iter()
+                    .map(|(tenant_id, secrets)| {
+                        let secrets = match secrets {
+                            SigningSecrets::One(secret) => vec![secret],
+                            SigningSecrets::Many(secrets) => secrets,
+                        };
+                        if secrets.is_empty() || secrets.iter().any(|s| s.is_empty()) {
+                            return Err(format!("CALLBACK_TENANT_SIGNING_SECRETS has an empty secret for tenant '{}'", tenant_id));
+                        }
+                        Ok((tenant_id, secrets))
+                    })
+                    .collect::<Result<_, String>>()?
+            },
+            _ => HashMap::new(),
+        };
+
+        Ok(CallbackConfig {
+            signing_secret: secrets.get("CALLBACK_SIGNING_SECRET")?,
+            previous_signing_secret: secrets.get("CALLBACK_PREVIOUS_SIGNING_SECRET")?,
+            tenant_signing_secrets,
+            retry_attempts: env::var("CALLBACK_RETRY_ATTEMPTS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            timeout_seconds: env::var("CALLBACK_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+        })
+    }
+
+    /// Secrets a tenant's callbacks are signed with, current first
+    ///
+    /// Tenants without their own secrets use the shared ones.
+    pub fn signing_secrets(&self, tenant_id: Option<&str>) -> Vec<&str> {
+        if let Some(secrets) = tenant_id.and_then(|id| self.tenant_signing_secrets.get(id)) {
+            return secrets.iter().map(String::as_str).collect();
+        }
+        self.signing_secret.iter()
+            .chain(self.previous_signing_secret.iter().filter(|_| self.signing_secret.is_some()))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Administrative API configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdminConfig {
+    pub api_token: Option<String>,
+    pub tenants_file: Option<String>,
+    /// Base64-encoded 256-bit key encrypting tenant auth tokens in the tenants file
+    pub tenant_secrets_key: Option<String>,
+    /// Secret signing recording audio URLs; recordings get no URL without one
+    pub recording_signing_secret: Option<String>,
+    /// How long signed recording audio URLs stay valid
+    pub recording_url_ttl_seconds: u64,
+}
+
+impl AdminConfig {
+    /// Load admin configuration from environment variables and secret sources
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
+        Ok(AdminConfig {
+            api_token: secrets.get("ADMIN_API_TOKEN")?,
+            tenants_file: env::var("TENANTS_FILE")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            tenant_secrets_key: secrets.get("TENANT_SECRETS_KEY")?,
+            recording_signing_secret: secrets.get("RECORDING_URL_SIGNING_SECRET")?,
+            recording_url_ttl_seconds: env::var("RECORDING_URL_TTL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600),
+        })
+    }
+}
+
+/// Configuration reload settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadConfig {
+    /// Env file re-read on reload, overriding the process environment
+    pub env_file: Option<String>,
+    /// How often to check the env file for changes; 0 only reloads through the admin API
+    pub watch_interval_seconds: u64,
+}
+
+impl ReloadConfig {
+    /// Load reload configuration from environment variables
+    pub fn from_env() -> Self {
+        ReloadConfig {
+            env_file: env::var("CONFIG_FILE")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .or_else(|| Some(".env".to_string())),
+            watch_interval_seconds: env::var("CONFIG_RELOAD_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Development recording of Twilio webhooks as replayable fixtures
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FixtureConfig {
+    /// Directory the webhooks and TwiML of each call are written to; unset disables recording
+    pub record_dir: Option<String>,
+}
+
+impl FixtureConfig {
+    /// Load fixture recording settings from environment variables
+    pub fn from_env() -> Self {
+        FixtureConfig {
+            record_dir: env::var("RECORD_WEBHOOKS_DIR")
+                .ok()
+                .filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+/// Kind of personal data the redactor recognizes without a custom pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    CreditCard,
+    Ssn,
+    Phone,
+}
+
+impl PiiKind {
+    /// Look up a kind by its configuration name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "credit_card" => Some(PiiKind::CreditCard),
+            "ssn" => Some(PiiKind::Ssn),
+            "phone" => Some(PiiKind::Phone),
+            _ => None,
+        }
+    }
+}
+
+/// Redaction of personal data from caller speech and bot replies before they are logged or stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Built-in patterns applied before the custom ones
+    pub builtins: Vec<PiiKind>,
+    /// Additional regular expressions whose matches are redacted, from
+    /// `REDACT_PII_PATTERNS` and `RESPONSE_REDACT_PATTERNS`
+    pub patterns: PatternSet,
+    /// Text written in place of redacted matches
+    pub replacement: String,
+}
+
+impl RedactionConfig {
+    /// Load redaction settings from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let builtins = env::var("REDACT_PII_BUILTINS")
+            .unwrap_or_else(|_| "credit_card,ssn,phone".to_string())
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| PiiKind::from_name(name)
+                .ok_or_else(|| format!("Unknown kind of personal data '{}' in REDACT_PII_BUILTINS", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The response processor's patterns share the set, so replies and logs are redacted alike
+        let mut patterns = Vec::new();
+        for var in ["REDACT_PII_PATTERNS", "RESPONSE_REDACT_PATTERNS"] {
+            if let Some(json) = env::var(var).ok().filter(|json| !json.trim().is_empty()) {
+                let more: Vec<String> = serde_json::from_str(&json)
+                    .map_err(|e| format!("{} must be a JSON array of regular expressions: {}", var, e))?;
+                patterns.extend(more);
+            }
+        }
+        let patterns = PatternSet::compile(&patterns)?;
+
+        Ok(RedactionConfig {
+            enabled: env::var("REDACT_PII")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase() == "true",
+            builtins,
+            patterns,
+            replacement: env::var("REDACT_PII_REPLACEMENT")
+                .unwrap_or_else(|_| "[redacted]".to_string()),
+        })
+    }
+}
+
+/// Drain mode settings for taking an instance out of service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainConfig {
+    /// Twilio webhook URL of the instance inbound calls are redirected to while draining;
+    /// unset answers them busy
+    pub peer_url: Option<String>,
+    /// Longest wait for active sessions to end before the process exits anyway
+    pub timeout_seconds: u64,
+}
+
+impl DrainConfig {
+    /// Load drain settings from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let peer_url = env::var("DRAIN_PEER_URL")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|url| url.trim_end_matches('/').to_string());
+        if peer_url.as_ref().is_some_and(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+            return Err("DRAIN_PEER_URL must be an http(s) URL".to_string());
+        }
+
+        Ok(DrainConfig {
+            peer_url,
+            timeout_seconds: env::var("DRAIN_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+        })
+    }
+}
+
+/// WebSocket sink mirroring every turn to analytics and QA systems
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AnalyticsTapConfig {
+    /// ws(s) URL turns are sent to; unset disables the tap
+    pub url: Option<String>,
+    /// Bearer token sent when connecting
+    pub token: Option<String>,
+    /// Turns buffered while the sink is slow or reconnecting; newer turns are dropped beyond it
+    ///
+    /// Fixed when the first turn is mirrored, so a reload does not change it.
+    pub queue_size: usize,
+}
+
+impl AnalyticsTapConfig {
+    /// Load analytics tap settings from environment variables
+    pub fn from_env() -> Result<Self, String> {
+        let url = env::var("ANALYTICS_TAP_URL")
+            .ok()
+            .filter(|s| !s.is_empty());
+        if url.as_ref().is_some_and(|url| !url.starts_with("ws://") && !url.starts_with("wss://")) {
+            return Err("ANALYTICS_TAP_URL must be a ws(s) URL".to_string());
+        }
+
+        Ok(AnalyticsTapConfig {
+            url,
+            token: env::var("ANALYTICS_TAP_TOKEN")
+                .ok()
+                .filter(|s| !s.is_empty()),
+            queue_size: env::var("ANALYTICS_TAP_QUEUE_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|&size| size > 0)
+                .unwrap_or(1000),
+        })
+    }
+}
+
+/// Combined application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub twilio: TwilioConfig,
+    pub backend: BackendConfig,
+    pub session: SessionConfig,
+    pub messages: MessagesConfig,
+    pub media: MediaStreamConfig,
+    pub utterance: UtteranceConfig,
+    pub stt: SttConfig,
+    pub tts: TtsConfig,
+    pub admin: AdminConfig,
+    pub redis: RedisConfig,
+    pub replication: ReplicationConfig,
+    pub cluster: ClusterConfig,
+    pub cdr: CdrConfig,
+    pub callbacks: CallbackConfig,
+    pub personas: PersonaConfig,
+    pub snapshots: SnapshotConfig,
+    pub recording: RecordingConfig,
+    pub prompts: PromptCacheConfig,
+    pub greeting: GreetingConfig,
+    pub health: HealthConfig,
+    pub limits: LimitsConfig,
+    pub languages: LanguageDetectionConfig,
+    pub costs: CostConfig,
+    pub screening: ScreeningConfig,
+    pub business_hours: BusinessHoursConfig,
+    pub experiments: ExperimentConfig,
+    pub dead_letters: DeadLetterConfig,
+    pub audit: AuditConfig,
+    pub dialer: DialerConfig,
+    pub postprocess: PostprocessConfig,
+    pub outage: OutageConfig,
+    pub sentiment: SentimentConfig,
+    pub reload: ReloadConfig,
+    pub fixtures: FixtureConfig,
+    pub drain: DrainConfig,
+    pub redaction: RedactionConfig,
+    pub analytics_tap: AnalyticsTapConfig,
+}
+
+impl Config {
+    /// Validate the complete configuration
+    pub fn validate(&self) -> Result<(), String> {
+        self.twilio.validate()?;
+        self.backend.validate()?;
+        
+        if self.replication.enabled && self.redis.url.is_none() {
+            return Err("REDIS_URL must be set when session replication is enabled".to_string());
+        }
+        
+        if self.cluster.enabled && self.redis.url.is_none() {
+            return Err("REDIS_URL must be set when cluster mode is enabled".to_string());
+        }
+        
+        if self.snapshots.use_redis && self.redis.url.is_none() {
+            return Err("REDIS_URL must be set when session snapshots use Redis".to_string());
+        }
+        
+        if self.dead_letters.use_redis && self.redis.url.is_none() {
+            return Err("REDIS_URL must be set when dead letters use Redis".to_string());
+        }
+        
+        if self.dialer.use_redis && self.redis.url.is_none() {
+            return Err("REDIS_URL must be set when the dialer CPS limit uses Redis".to_string());
+        }
+        
+        if self.callbacks.retry_attempts > MAX_CALLBACK_RETRY_ATTEMPTS {
+            return Err(format!("CALLBACK_RETRY_ATTEMPTS must be at most {}", MAX_CALLBACK_RETRY_ATTEMPTS));
+        }
+        
+        Ok(())
+    }
+    
+    /// Sections differing from `other` that only take effect after a restart
+    ///
+    /// These configure connections and stores created once at startup.
+    pub fn restart_required_changes(&self, other: &Config) -> Vec<&'static str> {
+        const STARTUP_SECTIONS: [&str; 13] = [
+            "redis", "cluster", "replication", "snapshots", "dead_letters", "audit",
+            "dialer", "cdr", "prompts", "messages", "health", "limits", "reload",
+        ];
+        
+        let (Ok(current), Ok(other)) = (serde_json::to_value(self), serde_json::to_value(other)) else {
+            return Vec::new();
+        };
+        STARTUP_SECTIONS.into_iter()
+            .filter(|section| current.get(section) != other.get(section))
+            .collect()
+    }
+    
+    /// Create configuration from environment variables, reading secrets from `secrets`
+    pub fn from_env(secrets: &Secrets) -> Result<Self, String> {
+        let mut twilio = TwilioConfig::from_env(secrets)?;
+        let backend = BackendConfig::from_env(secrets)?;
+        let session = SessionConfig::from_env();
+        let messages = MessagesConfig::from_env();
+        let media = MediaStreamConfig::from_env(&twilio.callback_url(""))?;
+        let utterance = UtteranceConfig::from_env(&twilio)?;
+        let stt = SttConfig::from_env(secrets, &media)?;
+        twilio.streaming_stt = stt.is_streaming();
+        let tts = TtsConfig::from_env(secrets, &stt)?;
+        if tts.delivery == TtsDelivery::MediaStream {
+            twilio.reply_stream_url = Some(media.url.clone());
+        }
+        let twilio = tts.apply(&twilio);
+        let admin = AdminConfig::from_env(secrets)?;
+        let redis = RedisConfig::from_env();
+        let replication = ReplicationConfig::from_env();
+        let cluster = ClusterConfig::from_env();
+        let cdr = CdrConfig::from_env();
+        let callbacks = CallbackConfig::from_env(secrets)?;
+        let personas = PersonaConfig::from_env()?;
+        let snapshots = SnapshotConfig::from_env();
+        let recording = RecordingConfig::from_env()?;
+        let prompts = PromptCacheConfig::from_env(&twilio.callback_url(""));
+        let greeting = GreetingConfig::from_env()?;
+        let health = HealthConfig::from_env();
+        let limits = LimitsConfig::from_env()?;
+        let languages = LanguageDetectionConfig::from_env()?;
+        let costs = CostConfig::from_env()?;
+        let screening = ScreeningConfig::from_env()?;
+        let business_hours = BusinessHoursConfig::from_env()?;
+        let experiments = ExperimentConfig::from_env()?;
+        let dead_letters = DeadLetterConfig::from_env();
+        let audit = AuditConfig::from_env();
+        let dialer = DialerConfig::from_env()?;
+        let postprocess = PostprocessConfig::from_env()?;
+        let outage = OutageConfig::from_env()?;
+        let sentiment = SentimentConfig::from_env()?;
+        let reload = ReloadConfig::from_env();
+        let fixtures = FixtureConfig::from_env();
+        let drain = DrainConfig::from_env()?;
+        let redaction = RedactionConfig::from_env()?;
+        let analytics_tap = AnalyticsTapConfig::from_env()?;
+        
+        let config = Config {
+            twilio,
+            backend,
+            session,
+            messages,
+            media,
+            utterance,
+            stt,
+            tts,
+            admin,
+            redis,
+            replication,
+            cluster,
+            cdr,
+            callbacks,
+            personas,
+            snapshots,
+            recording,
+            prompts,
+            greeting,
+            health,
+            limits,
+            languages,
+            costs,
+            screening,
+            business_hours,
+            experiments,
+            dead_letters,
+            audit,
+            dialer,
+            postprocess,
+            outage,
+            sentiment,
+            reload,
+            fixtures,
+            drain,
+            redaction,
+            analytics_tap,
+        };
+        
+        config.validate()?;
+        
+        Ok(config)
+    }
+}
+
+/// Configuration shared by handlers and background tasks, replaced atomically on reload
+///
+/// Readers take a snapshot with `current`, so a request sees one consistent
+/// configuration even if a reload lands while it is handled.
+#[derive(Clone)]
+pub struct SharedConfig(Arc<ArcSwap<Config>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        SharedConfig(Arc::new(ArcSwap::from_pointee(config)))
+    }
+    
+    /// Snapshot of the current configuration
+    pub fn current(&self) -> Arc<Config> {
+        self.0.load_full()
+    }
+    
+    /// Swap in a new configuration for subsequent readers
+    pub fn replace(&self, config: Config) {
+        self.0.store(Arc::new(config));
+    }
+}
+
+impl fmt::Debug for SharedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.load().fmt(f)
+    }
+}
+
+/// Request guard with the configuration current when the request arrived
+pub struct CurrentConfig(pub Arc<Config>);
+
+impl Deref for CurrentConfig {
+    type Target = Config;
+    
+    fn deref(&self) -> &Config {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CurrentConfig {
+    type Error = ();
+    
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.rocket().state::<SharedConfig>() {
+            Some(config) => Outcome::Success(CurrentConfig(config.current())),
+            None => Outcome::Error((Status::InternalServerError, ())),
+        }
+    }
+}
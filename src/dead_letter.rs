@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::bot::backend::BackendPool;
+use crate::bot::result_callback::deliver_call_result;
+use crate::config::{Config, DeadLetterConfig};
+use crate::redis_layer::RedisLayer;
+
+/// Notification that could not be delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterKind {
+    /// Closing the backend session when the call ended
+    CloseSession,
+    /// Posting the end-of-call summary to the backend
+    CallSummary,
+    /// Delivering the signed call result to the consumer's callback URL
+    CallResult,
+}
+
+/// A notification that failed after its retries, kept for replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: String,
+    pub kind: DeadLetterKind,
+    /// Session ID for backend notifications, callback URL for call results
+    pub target: String,
+    /// Bot whose backend a backend notification goes to; `None` for the default backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot: Option<String>,
+    pub payload: serde_json::Value,
+    /// Last delivery error
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+    /// Times the entry was replayed without success
+    #[serde(default)]
+    pub replay_attempts: u32,
+}
+
+/// Where dead letters are kept
+enum DeadLetterTarget {
+    /// Kept only until restart when no file or Redis list is configured
+    Memory,
+    /// JSON lines file
+    File(String),
+    /// Redis list key
+    Redis(RedisLayer, String),
+}
+
+/// Store of notifications that could not be delivered
+///
+/// Entries are stored as JSON strings so they can be removed by value from a Redis list.
+pub struct DeadLetterStore {
+    target: DeadLetterTarget,
+    /// Entries of the in-memory target; the lock also serializes file rewrites
+    memory: Mutex<Vec<String>>,
+}
+
+impl DeadLetterStore {
+    /// Create a store, preferring Redis, then the file, then memory
+    pub fn new(config: &DeadLetterConfig, redis: Option<RedisLayer>) -> Self {
+        let target = match (redis.filter(|_| config.use_redis), &config.path) {
+            (Some(redis), _) => {
+                let key = redis.key(&["dead_letters"]);
+                DeadLetterTarget::Redis(redis, key)
+            },
+            (None, Some(path)) => DeadLetterTarget::File(path.clone()),
+            (None, None) => DeadLetterTarget::Memory,
+        };
+
+        DeadLetterStore {
+            target,
+            memory: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record a notification that failed after its retries
+    pub async fn add(&self, kind: DeadLetterKind, target: &str, bot: Option<&str>, payload: serde_json::Value, error: &str) {
+        let letter = DeadLetter {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            target: target.to_string(),
+            bot: bot.map(|b| b.to_string()),
+            payload,
+            error: error.to_string(),
+            failed_at: Utc::now(),
+            replay_attempts: 0,
+        };
+
+        warn!("Dead-lettered {:?} notification {} for {}: {}", kind, letter.id, target, error);
+        if let Err(e) = self.push(&letter).await {
+            error!("Failed to store dead letter {}: {}", letter.id, e);
+        }
+    }
+
+    /// All stored entries, oldest first
+    pub async fn list(&self) -> Result<Vec<DeadLetter>, String> {
+        Ok(self.entries().await?.into_iter().map(|(_, letter)| letter).collect())
+    }
+
+    /// Deliver a stored entry again
+    ///
+    /// The entry is removed when delivery succeeds and kept with the new error otherwise.
+    /// Returns `None` when there is no entry with the ID.
+    pub async fn replay(&self, id: &str, backends: &BackendPool, config: &Config) -> Result<Option<DeadLetter>, String> {
+        let Some((raw, mut letter)) = self.entries().await?.into_iter().find(|(_, l)| l.id == id) else {
+            return Ok(None);
+        };
+
+        // Claim the entry first so concurrent replays cannot deliver it twice
+        if !self.remove(&raw).await? {
+            return Ok(None);
+        }
+
+        match deliver(&letter, backends, config).await {
+            Ok(()) => {
+                info!("Replayed dead letter {} for {}", letter.id, letter.target);
+                Ok(Some(letter))
+            },
+            Err(e) => {
+                letter.error = e.clone();
+                letter.replay_attempts += 1;
+                self.push(&letter).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn push(&self, letter: &DeadLetter) -> Result<(), String> {
+        let raw = serde_json::to_string(letter).map_err(|e| e.to_string())?;
+        let mut memory = self.memory.lock().await;
+
+        match &self.target {
+            DeadLetterTarget::Memory => {
+                memory.push(raw);
+                Ok(())
+            },
+            DeadLetterTarget::File(path) => {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                file.write_all(format!("{}\n", raw).as_bytes())
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            DeadLetterTarget::Redis(redis, key) => redis::cmd("RPUSH")
+                .arg(key)
+                .arg(raw)
+                .query_async::<_, ()>(&mut redis.connection())
+                .await
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Stored entries with the raw JSON they are stored as
+    async fn entries(&self) -> Result<Vec<(String, DeadLetter)>, String> {
+        let memory = self.memory.lock().await;
+
+        let raw: Vec<String> = match &self.target {
+            DeadLetterTarget::Memory => memory.clone(),
+            DeadLetterTarget::File(path) => match tokio::fs::read_to_string(path).await {
+                Ok(contents) => contents.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e.to_string()),
+            },
+            DeadLetterTarget::Redis(redis, key) => redis::cmd("LRANGE")
+                .arg(key)
+                .arg(0)
+                .arg(-1)
+                .query_async(&mut redis.connection())
+                .await
+                .map_err(|e| e.to_string())?,
+        };
+
+        Ok(raw.into_iter()
+            .filter_map(|raw| match serde_json::from_str(&raw) {
+                Ok(letter) => Some((raw, letter)),
+                Err(e) => {
+                    warn!("Skipping unreadable dead letter: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Remove a stored entry, returning whether it was still there
+    async fn remove(&self, raw: &str) -> Result<bool, String> {
+        let mut memory = self.memory.lock().await;
+
+        match &self.target {
+            DeadLetterTarget::Memory => {
+                let count = memory.len();
+                memory.retain(|entry| entry != raw);
+                Ok(memory.len() < count)
+            },
+            DeadLetterTarget::File(path) => {
+                let contents = tokio::fs::read_to_string(path).await.map_err(|e| e.to_string())?;
+                let found = contents.lines().any(|line| line == raw);
+                let remaining: String = contents.lines()
+                    .filter(|line| *line != raw && !line.trim().is_empty())
+                    .map(|line| format!("{}\n", line))
+                    .collect();
+                tokio::fs::write(path, remaining).await.map_err(|e| e.to_string())?;
+                Ok(found)
+            },
+            DeadLetterTarget::Redis(redis, key) => redis::cmd("LREM")
+                .arg(key)
+                .arg(1)
+                .arg(raw)
+                .query_async::<_, i64>(&mut redis.connection())
+                .await
+                .map(|removed| removed > 0)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Send a dead-lettered notification once
+async fn deliver(letter: &DeadLetter, backends: &BackendPool, config: &Config) -> Result<(), String> {
+    let backend = || backends.client(&config.backend, letter.bot.as_deref()).map_err(|e| e.to_string());
+    match letter.kind {
+        DeadLetterKind::CloseSession => {
+            let status = letter.payload.get("status").and_then(|s| s.as_str());
+            backend()?
+                .close_session(&letter.target, status)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        DeadLetterKind::CallSummary => backend()?
+            .post_call_summary_payload(&letter.target, letter.payload.clone())
+            .await
+            .map_err(|e| e.to_string()),
+        DeadLetterKind::CallResult => {
+            deliver_call_result(&letter.target, &letter.payload, &config.callbacks).await
+        },
+    }
+}
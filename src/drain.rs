@@ -0,0 +1,85 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use log::info;
+use rocket::Shutdown;
+use serde::Serialize;
+
+use crate::bot::session::SessionStore;
+
+/// How often a draining instance checks whether its sessions have ended
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An instance's drain, started through `POST /admin/drain`
+#[derive(Debug, Clone, Serialize)]
+pub struct DrainMode {
+    pub started_at: DateTime<Utc>,
+    /// When the process exits even if sessions are still active
+    pub deadline: DateTime<Utc>,
+    /// Twilio webhook URL inbound calls are redirected to; unset answers them busy
+    pub peer_url: Option<String>,
+}
+
+/// Whether this instance is draining before a deploy
+///
+/// A draining instance turns inbound calls away and rejects outbound ones, and
+/// shuts down once its active sessions have ended or the drain times out.
+#[derive(Default)]
+pub struct Drain {
+    mode: RwLock<Option<DrainMode>>,
+}
+
+impl Drain {
+    /// The drain in progress, if any
+    pub fn current(&self) -> Option<DrainMode> {
+        self.mode.read().unwrap().clone()
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.mode.read().unwrap().is_some()
+    }
+
+    /// Start draining and shut down once `sessions` is empty or `timeout` elapses
+    ///
+    /// Returns the drain in progress unchanged if one was already started.
+    pub fn start(
+        self: &Arc<Self>,
+        peer_url: Option<String>,
+        timeout: Duration,
+        sessions: Arc<SessionStore>,
+        shutdown: Shutdown,
+    ) -> DrainMode {
+        let mut mode = self.mode.write().unwrap();
+        if let Some(mode) = mode.as_ref() {
+            return mode.clone();
+        }
+
+        let started_at = Utc::now();
+        let drain = DrainMode {
+            started_at,
+            deadline: started_at + chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX),
+            peer_url,
+        };
+        *mode = Some(drain.clone());
+        info!(
+            "Draining {} active sessions before shutting down, at the latest at {}",
+            sessions.len(),
+            drain.deadline
+        );
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        tokio::spawn(async move {
+            while !sessions.is_empty() && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+            if sessions.is_empty() {
+                info!("Every session has ended, shutting down");
+            } else {
+                info!("Drain timed out with {} active sessions, shutting down", sessions.len());
+            }
+            shutdown.notify();
+        });
+
+        drain
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use log::{debug, error, info, warn};
+
+use crate::tenant::TenantStore;
+
+/// System phrases spoken by the bot outside of backend-generated content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phrase {
+    /// Default greeting when the backend does not provide one
+    Greeting,
+    /// Generic error when the service cannot handle the call
+    TechnicalDifficulties,
+    /// The session for the call no longer exists
+    SessionExpired,
+    /// The backend returned no usable response
+    NotUnderstood,
+    /// The backend failed while processing a turn
+    ProcessingError,
+    /// Ask the caller to repeat themselves
+    RepeatPrompt,
+    /// Played while the caller is on hold
+    HoldMessage,
+    /// Played before transferring the caller
+    TransferAnnouncement,
+    /// Asked when no audio is heard from the caller
+    AudioCheck,
+    /// Played before ending a call with unresolved one-way audio
+    AudioCheckFailed,
+    /// Played before ending a call that hit its turn or duration limit
+    CallLimitReached,
+    /// Played to callers waiting for an agent, `{minutes}` is the estimated wait
+    QueueWaitEstimate,
+    /// Played before recording a message when a transfer goes unanswered
+    TransferVoicemail,
+    /// Asks the caller to rate a transferred call
+    TransferSurvey,
+    /// Played after the caller answers the survey
+    SurveyThanks,
+    /// Asked when speech was recognized with low confidence, `{text}` is what was heard
+    Clarification,
+    /// Spoken when a held call resumes the conversation
+    HoldResumed,
+    /// Played to the caller while a third party is dialed into the call
+    EscalationAnnouncement,
+    /// Whispered to the escalation party before they join, `{context}` is the backend's summary
+    EscalationWhisper,
+    /// Asked when the caller has been silent through a Gather
+    KeepalivePrompt,
+    /// Played before ending a call after the caller stayed silent through every keepalive prompt
+    SilenceHangup,
+    /// Played when a turn outlasts the webhook response timeout
+    TurnDelay,
+    /// Played to callers outside the number's business hours
+    AfterHours,
+    /// Played before recording a message outside business hours
+    AfterHoursVoicemail,
+}
+
+impl Phrase {
+    /// All known phrases
+    pub const ALL: [Phrase; 24] = [
+        Phrase::Greeting,
+        Phrase::TechnicalDifficulties,
+        Phrase::SessionExpired,
+        Phrase::NotUnderstood,
+        Phrase::ProcessingError,
+        Phrase::RepeatPrompt,
+        Phrase::HoldMessage,
+        Phrase::TransferAnnouncement,
+        Phrase::AudioCheck,
+        Phrase::AudioCheckFailed,
+        Phrase::CallLimitReached,
+        Phrase::QueueWaitEstimate,
+        Phrase::TransferVoicemail,
+        Phrase::TransferSurvey,
+        Phrase::SurveyThanks,
+        Phrase::Clarification,
+        Phrase::HoldResumed,
+        Phrase::EscalationAnnouncement,
+        Phrase::EscalationWhisper,
+        Phrase::KeepalivePrompt,
+        Phrase::SilenceHangup,
+        Phrase::TurnDelay,
+        Phrase::AfterHours,
+        Phrase::AfterHoursVoicemail,
+    ];
+
+    /// Key used for the phrase in catalog files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Phrase::Greeting => "greeting",
+            Phrase::TechnicalDifficulties => "technical_difficulties",
+            Phrase::SessionExpired => "session_expired",
+            Phrase::NotUnderstood => "not_understood",
+            Phrase::ProcessingError => "processing_error",
+            Phrase::RepeatPrompt => "repeat_prompt",
+            Phrase::HoldMessage => "hold_message",
+            Phrase::TransferAnnouncement => "transfer_announcement",
+            Phrase::AudioCheck => "audio_check",
+            Phrase::AudioCheckFailed => "audio_check_failed",
+            Phrase::CallLimitReached => "call_limit_reached",
+            Phrase::QueueWaitEstimate => "queue_wait_estimate",
+            Phrase::TransferVoicemail => "transfer_voicemail",
+            Phrase::TransferSurvey => "transfer_survey",
+            Phrase::SurveyThanks => "survey_thanks",
+            Phrase::Clarification => "clarification",
+            Phrase::HoldResumed => "hold_resumed",
+            Phrase::EscalationAnnouncement => "escalation_announcement",
+            Phrase::EscalationWhisper => "escalation_whisper",
+            Phrase::KeepalivePrompt => "keepalive_prompt",
+            Phrase::SilenceHangup => "silence_hangup",
+            Phrase::TurnDelay => "turn_delay",
+            Phrase::AfterHours => "after_hours",
+            Phrase::AfterHoursVoicemail => "after_hours_voicemail",
+        }
+    }
+
+    /// Built-in English text used when no catalog provides the phrase
+    pub fn default_text(&self) -> &'static str {
+        match self {
+            Phrase::Greeting => "Hello, welcome to our service.",
+            Phrase::TechnicalDifficulties => "Sorry, we're experiencing technical difficulties.",
+            Phrase::SessionExpired => "Sorry, your session has expired.",
+            Phrase::NotUnderstood => "I'm sorry, I didn't understand that.",
+            Phrase::ProcessingError => "I'm sorry, I'm having trouble processing your request right now.",
+            Phrase::RepeatPrompt => "Could you please repeat that?",
+            Phrase::HoldMessage => "Please hold while we look into this for you.",
+            Phrase::TransferAnnouncement => "Please hold while I transfer your call.",
+            Phrase::AudioCheck => "Hello? Can you hear me?",
+            Phrase::AudioCheckFailed => "It seems we have a bad connection. Please call us back. Goodbye.",
+            Phrase::CallLimitReached => "We've reached the limit for this call. Thank you for calling. Goodbye.",
+            Phrase::QueueWaitEstimate => "All of our agents are busy. Your estimated wait time is about {minutes} minutes.",
+            Phrase::TransferVoicemail => "Nobody is available to take your call. Please leave a message after the beep.",
+            Phrase::TransferSurvey => "Before you go, please rate your call from 1 to 5 on your keypad.",
+            Phrase::SurveyThanks => "Thank you for your feedback. Goodbye.",
+            Phrase::Clarification => "Sorry, did you say \"{text}\"?",
+            Phrase::HoldResumed => "Thank you for holding.",
+            Phrase::EscalationAnnouncement => "Please hold while I bring someone else into the call.",
+            Phrase::EscalationWhisper => "You are joining a call escalated by the assistant. {context}",
+            Phrase::KeepalivePrompt => "Are you still there?",
+            Phrase::SilenceHangup => "I haven't heard from you, so I'll end the call now. Goodbye.",
+            Phrase::TurnDelay => "One moment, please.",
+            Phrase::AfterHours => "Thank you for calling. We are closed right now, please call again during business hours.",
+            Phrase::AfterHoursVoicemail => "Please leave a message after the beep and we will get back to you.",
+        }
+    }
+}
+
+/// Catalog of localized system phrases keyed by language
+///
+/// Catalog files are JSON objects mapping phrase keys to text, one file per
+/// language named `<language>.json` (e.g. `es-MX.json`, `es.json`). A tenant's
+/// prompts take the place of the default language's phrases on its calls.
+pub struct MessageCatalog {
+    dir: Option<String>,
+    default_language: String,
+    languages: RwLock<HashMap<String, HashMap<String, String>>>,
+    fingerprint: RwLock<Option<(usize, SystemTime)>>,
+    tenants: Arc<TenantStore>,
+}
+
+impl MessageCatalog {
+    /// Create a catalog, loading files from the directory if one is configured
+    pub fn new(dir: Option<String>, default_language: String, tenants: Arc<TenantStore>) -> Self {
+        let catalog = MessageCatalog {
+            dir,
+            default_language,
+            languages: RwLock::new(HashMap::new()),
+            fingerprint: RwLock::new(None),
+            tenants,
+        };
+
+        if let Err(e) = catalog.reload() {
+            error!("Failed to load message catalog: {}", e);
+        }
+
+        catalog
+    }
+
+    /// Look up a phrase for a language, following the fallback chain
+    pub fn text(&self, phrase: Phrase, language: Option<&str>) -> String {
+        self.tenant_text(phrase, language, None)
+    }
+
+    /// Look up a phrase for a tenant's call, using the tenant's prompt in the default language
+    pub fn tenant_text(&self, phrase: Phrase, language: Option<&str>, tenant_id: Option<&str>) -> String {
+        let languages = self.languages.read().unwrap();
+        let default_language = self.default_language.to_lowercase();
+
+        for candidate in self.fallback_chain(language) {
+            if candidate == default_language {
+                if let Some(text) = tenant_id.and_then(|id| self.tenants.prompt(id, phrase)) {
+                    return text;
+                }
+            }
+            if let Some(text) = languages.get(&candidate).and_then(|m| m.get(phrase.key())) {
+                return text.clone();
+            }
+        }
+
+        phrase.default_text().to_string()
+    }
+
+    /// Build the fallback chain for a language, e.g. `es-MX` -> `es` -> default language
+    pub fn fallback_chain(&self, language: Option<&str>) -> Vec<String> {
+        let mut chain = Vec::new();
+
+        for lang in [language.unwrap_or(&self.default_language), &self.default_language] {
+            let lang = lang.to_lowercase();
+            if !chain.contains(&lang) {
+                chain.push(lang.clone());
+            }
+            if let Some((base, _)) = lang.split_once('-') {
+                if !chain.iter().any(|c| c == base) {
+                    chain.push(base.to_string());
+                }
+            }
+        }
+
+        chain
+    }
+
+    /// Reload all catalog files from the configured directory
+    pub fn reload(&self) -> Result<(), String> {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+
+        let mut languages = HashMap::new();
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Cannot read messages directory {}: {}", dir, e))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let language = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(stem) => stem.to_lowercase(),
+                None => continue,
+            };
+
+            match load_catalog_file(&path) {
+                Ok(phrases) => {
+                    for key in phrases.keys() {
+                        if !Phrase::ALL.iter().any(|p| p.key() == key) {
+                            warn!("Unknown phrase key '{}' in {}", key, path.display());
+                        }
+                    }
+                    languages.insert(language, phrases);
+                },
+                Err(e) => error!("Skipping message catalog {}: {}", path.display(), e),
+            }
+        }
+
+        info!("Loaded message catalogs for {} language(s)", languages.len());
+        *self.languages.write().unwrap() = languages;
+        *self.fingerprint.write().unwrap() = directory_fingerprint(dir);
+        Ok(())
+    }
+
+    /// Reload the catalog if any file in the directory changed since the last load
+    pub fn reload_if_changed(&self) {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return,
+        };
+
+        let current = directory_fingerprint(dir);
+        if current == *self.fingerprint.read().unwrap() {
+            return;
+        }
+
+        debug!("Message catalog directory changed, reloading");
+        if let Err(e) = self.reload() {
+            error!("Failed to reload message catalog: {}", e);
+        }
+    }
+}
+
+/// Load a single catalog file
+fn load_catalog_file(path: &Path) -> Result<HashMap<String, String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Compute a cheap change marker for a directory: file count and latest modification time
+fn directory_fingerprint(dir: &str) -> Option<(usize, SystemTime)> {
+    let entries = fs::read_dir(dir).ok()?;
+    let mut count = 0;
+    let mut latest = SystemTime::UNIX_EPOCH;
+
+    for entry in entries.flatten() {
+        count += 1;
+        if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+            latest = latest.max(modified);
+        }
+    }
+
+    Some((count, latest))
+}
+
+/// Start a periodic task that hot-reloads the catalog when its files change
+pub fn start_catalog_reload_task(catalog: Arc<MessageCatalog>, interval_seconds: u64) {
+    if catalog.dir.is_none() || interval_seconds == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_seconds));
+
+        loop {
+            interval.tick().await;
+            catalog.reload_if_changed();
+        }
+    });
+}
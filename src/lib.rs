@@ -0,0 +1,271 @@
+//! Twilio voice gateway connecting phone calls to a conversational backend
+//!
+//! The `twilio-bot` binary runs it standalone; [`build_rocket`] builds the same
+//! server for embedding in a larger service or for integration tests.
+
+#[macro_use] extern crate rocket;
+
+use std::sync::Arc;
+use log::info;
+use rocket::{Build, Rocket};
+use rocket::fairing::AdHoc;
+use rocket::data::{Limits, ToByteUnit};
+use rocket::http::Status;
+
+pub mod config;
+pub mod twilio;
+pub mod bot;
+pub mod api;
+pub mod utils;
+pub mod i18n;
+pub mod tenant;
+pub mod redis_layer;
+pub mod replication;
+pub mod cluster;
+pub mod snapshot;
+pub mod secrets;
+pub mod dead_letter;
+pub mod audit;
+pub mod reload;
+pub mod drain;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+
+use crate::audit::{AuditLog, start_audit_retention_task};
+use crate::api::health::{HealthMonitor, start_health_check_task};
+use crate::bot::backend::{BackendPool, BackendTransport};
+use crate::bot::caller_history::{CallerHistory, start_caller_history_cleanup_task};
+use crate::bot::cdr::{CdrStore, start_cdr_cleanup_task};
+use crate::cluster::SessionCluster;
+use crate::config::{Config, SharedConfig};
+use crate::dead_letter::DeadLetterStore;
+use crate::drain::Drain;
+use crate::reload::{ConfigReloader, start_config_reload_task};
+use crate::twilio::caller_id::CallerIds;
+use crate::twilio::cps::{self, CallRateLimiter};
+use crate::twilio::call_jobs::{CallJobStore, start_call_job_cleanup_task};
+use crate::twilio::greeting::PendingGreetings;
+use crate::twilio::prompt_cache::PromptCache;
+use crate::twilio::twiml::TwiML;
+use crate::twilio::idempotency::{ReplayCache, start_replay_cache_cleanup_task};
+use crate::bot::session::{SessionStore, start_session_cleanup_task};
+use crate::bot::tts::SpeechSynthesis;
+use crate::bot::ws_client::WebSocketManager;
+use crate::i18n::{MessageCatalog, start_catalog_reload_task};
+use crate::tenant::TenantStore;
+use crate::redis_layer::RedisLayer;
+use crate::replication::{SessionReplicator, start_replica_lease_task};
+use crate::snapshot::SnapshotStore;
+use crate::twilio::handlers::start_outbound_call_dispatcher;
+use crate::twilio::provisioning::start_number_provisioning;
+use crate::twilio::scheduler::CallScheduler;
+use crate::twilio::watchdog::start_call_duration_watchdog;
+
+/// Build the voice gateway with its background tasks started and routes mounted
+///
+/// Backend API requests go through `backend`; pass an `HttpTransport` to talk to
+/// the backend at `config.backend.url` directly. Twilio routes are mounted under
+/// `/twilio`, and again under `/twilio/<WEBHOOK_VERSION>`, and the API under `/`.
+///
+/// With the `chaos` feature, backend requests also pass through a
+/// `ChaosTransport` controlled from the `/debug/chaos` endpoints.
+pub async fn build_rocket(config: Config, backend: impl BackendTransport) -> Result<Rocket<Build>, String> {
+    #[cfg(feature = "chaos")]
+    let chaos = Arc::new(chaos::ChaosControl::new());
+    #[cfg(feature = "chaos")]
+    let backend = {
+        log::warn!("Chaos endpoints enabled; failures can be injected at /debug/chaos");
+        chaos::ChaosTransport::new(backend, chaos.clone())
+    };
+    let backends = Arc::new(BackendPool::new(backend));
+
+    // Share the configuration with handlers and background tasks, swapped on reload
+    let shared_config = SharedConfig::new(config.clone());
+    let reloader = Arc::new(ConfigReloader::new(shared_config.clone()));
+    start_config_reload_task(reloader.clone(), config.reload.watch_interval_seconds);
+
+    // Connect the shared Redis layer if configured
+    let redis = match &config.redis.url {
+        Some(url) => match RedisLayer::connect(url, &config.redis.key_prefix).await {
+            Ok(redis) => {
+                info!("Connected to Redis");
+                Some(redis)
+            },
+            Err(e) => return Err(format!("Failed to connect to Redis: {}", e)),
+        },
+        None => None,
+    };
+
+    // Share sessions with the other replicas in cluster mode
+    let cluster = Arc::new(SessionCluster::new(
+        redis.clone().filter(|_| config.cluster.enabled),
+        &config.cluster
+    ));
+
+    // Create session store
+    let session_store = Arc::new(SessionStore::with_cluster(cluster.clone()));
+    info!("Session store initialized");
+
+    // Create WebSocket manager
+    let (call_requests_tx, call_requests_rx) = tokio::sync::mpsc::channel(100);
+    let ws_manager = Arc::new(WebSocketManager::new(call_requests_tx, shared_config.clone()));
+    ws_manager.start_session_removal_listener(session_store.clone());
+    if cluster.is_enabled() {
+        ws_manager.start_lease_renewal(session_store.clone(), cluster.lease_ttl_seconds());
+    }
+    info!("WebSocket manager initialized");
+
+    // Load provisioned tenants
+    let tenants = Arc::new(TenantStore::new(
+        config.admin.tenants_file.clone(),
+        config.admin.tenant_secrets_key.as_deref()
+    )?);
+    info!("Tenant store initialized");
+
+    // Load the localized system phrase catalog
+    let catalog = Arc::new(MessageCatalog::new(
+        config.messages.dir.clone(),
+        config.messages.default_language.clone(),
+        tenants.clone()
+    ));
+    start_catalog_reload_task(catalog.clone(), config.messages.reload_interval_seconds);
+    info!("Message catalog initialized");
+
+    // Start the session cleanup task
+    start_session_cleanup_task(session_store.clone(), tenants.clone(), shared_config.clone());
+    info!("Session cleanup task started");
+
+    // Remember callers' last sessions so a call back can resume the conversation
+    let caller_history = Arc::new(CallerHistory::new(
+        config.session.resume_window_minutes,
+        redis.clone().filter(|_| config.cluster.enabled)
+    ));
+    start_caller_history_cleanup_task(caller_history.clone());
+
+    // Create call detail record store
+    let cdrs = Arc::new(CdrStore::new(config.cdr.retention));
+    start_cdr_cleanup_task(cdrs.clone());
+
+    // Create caches answering Twilio webhook retries
+    let twiml_replays = Arc::new(ReplayCache::<TwiML>::new(config.twilio.webhook_replay_ttl_seconds));
+    let status_replays = Arc::new(ReplayCache::<Status>::new(config.twilio.webhook_replay_ttl_seconds));
+    start_replay_cache_cleanup_task(twiml_replays.clone());
+    start_replay_cache_cleanup_task(status_replays.clone());
+
+    // Create the store tracking outbound calls placed in the background
+    let call_jobs = Arc::new(CallJobStore::new(config.twilio.call_job_ttl_seconds));
+    start_call_job_cleanup_task(call_jobs.clone());
+
+    // Create the cache of rendered prompt audio
+    let prompts = Arc::new(PromptCache::new(&config.prompts, &config.backend, backends.clone()));
+
+    // Create the cross-region session replicator
+    let replicator = Arc::new(SessionReplicator::new(
+        redis.clone().filter(|_| config.replication.enabled),
+        &config.replication
+    ));
+    start_replica_lease_task(replicator.clone());
+
+    // Restore sessions saved by the previous instance
+    let snapshots = Arc::new(SnapshotStore::new(&config.snapshots, &config.replication, redis.clone()));
+    for session_id in snapshots.restore(&session_store).await {
+        let bot = match session_store.get_session(&session_id) {
+            Some(handle) => handle.lock().await.bot.clone(),
+            None => None,
+        };
+        ws_manager.get_or_create_client(&session_id, bot.as_deref(), session_store.clone()).await;
+    }
+
+    // Keep notifications that fail after their retries for replay
+    let dead_letters = Arc::new(DeadLetterStore::new(&config.dead_letters, redis.clone()));
+
+    // Record turns and call decisions in the tamper-evident audit log
+    let audit = Arc::new(AuditLog::new(&config.audit));
+    start_audit_retention_task(audit.clone());
+
+    // Place outbound calls the backend requests over its WebSockets, within the account's CPS
+    let caller_ids = Arc::new(CallerIds::new());
+    let rate_limiter = Arc::new(CallRateLimiter::new(&config.dialer, &config.twilio.account_sid, redis.clone()));
+    cps::install(rate_limiter.clone());
+    let drain = Arc::new(Drain::default());
+    let scheduler = Arc::new(CallScheduler::new(
+        session_store.clone(),
+        ws_manager.clone(),
+        replicator.clone(),
+        cdrs.clone(),
+        tenants.clone(),
+        caller_ids.clone(),
+        dead_letters.clone(),
+        rate_limiter.clone(),
+        backends.clone(),
+        drain.clone(),
+        shared_config.clone()
+    ));
+    start_outbound_call_dispatcher(call_requests_rx, scheduler.clone());
+
+    // End calls that run past the maximum call duration
+    start_call_duration_watchdog(
+        session_store.clone(),
+        catalog.clone(),
+        replicator.clone(),
+        shared_config.clone()
+    );
+
+    // Point the inbound numbers' voice webhooks at this service
+    start_number_provisioning(config.twilio.clone());
+
+    // Probe dependencies in the background for the health and readiness endpoints
+    let health = Arc::new(HealthMonitor::new());
+    start_health_check_task(health.clone(), backends.clone(), config.clone());
+
+    // Save live sessions when shutting down for a deploy
+    let shutdown_sessions = session_store.clone();
+    let snapshot_hook = AdHoc::on_shutdown("Session snapshot", move |_| Box::pin(async move {
+        snapshots.save(&shutdown_sessions).await;
+    }));
+
+    // Bound request bodies before they are read into memory
+    let limits = Limits::default()
+        .limit("form", config.limits.form_bytes.bytes())
+        .limit("data-form", config.limits.form_bytes.bytes())
+        .limit("json", config.limits.json_bytes.bytes())
+        .limit("csv", config.limits.csv_bytes.bytes());
+    let figment = rocket::Config::figment().merge(("limits", limits));
+
+    // Build Rocket instance with routes and state
+    let rocket = rocket::custom(figment)
+        .attach(snapshot_hook)
+        .attach(twilio::fixtures::fairing())
+        .attach(twilio::proxy::fairing())
+        .attach(twilio::self_test::fairing())
+        .manage(shared_config)
+        .manage(reloader)
+        .manage(session_store)
+        .manage(ws_manager)
+        .manage(catalog)
+        .manage(tenants)
+        .manage(caller_ids)
+        .manage(replicator)
+        .manage(cdrs)
+        .manage(twiml_replays)
+        .manage(status_replays)
+        .manage(call_jobs)
+        .manage(scheduler)
+        .manage(rate_limiter)
+        .manage(prompts)
+        .manage(health)
+        .manage(dead_letters)
+        .manage(caller_history)
+        .manage(audit)
+        .manage(backends)
+        .manage(drain)
+        .manage(Arc::new(PendingGreetings::new()))
+        .manage(Arc::new(SpeechSynthesis::default()))
+        .mount("/", api::routes())
+        .mount("/twilio", twilio::routes())
+        .register("/twilio", twilio::catchers());
+    #[cfg(feature = "chaos")]
+    let rocket = rocket.manage(chaos);
+
+    Ok(rocket)
+}
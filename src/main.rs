@@ -1,23 +1,13 @@
-#[macro_use] extern crate rocket;
-
-use std::sync::Arc;
 use dotenv::dotenv;
 use log::{info, error, LevelFilter};
 use rocket::{Build, Rocket};
-use tokio::sync::RwLock;
-
-mod config;
-mod twilio;
-mod bot;
-mod api;
-mod utils;
 
-use crate::bot::session::{SessionStore, start_session_cleanup_task};
-use crate::bot::ws_client::WebSocketManager;
+use twilio_bot::bot::backend::HttpTransport;
+use twilio_bot::{build_rocket, config, secrets};
 
 /// Application entry point
-#[launch]
-fn rocket() -> Rocket<Build> {
+#[rocket::launch]
+async fn rocket() -> Rocket<Build> {
     // Initialize logging
     env_logger::builder()
         .filter_level(LevelFilter::Info)
@@ -29,8 +19,17 @@ fn rocket() -> Rocket<Build> {
 
     info!("Starting Twilio Bot service");
 
+    // Load secrets from files and the external secret store, if one is configured
+    let secrets = match secrets::load().await {
+        Ok(secrets) => secrets,
+        Err(e) => {
+            error!("Secret loading error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Load configuration from environment variables
-    let config = match config::Config::from_env() {
+    let config = match config::Config::from_env(&secrets) {
         Ok(config) => config,
         Err(e) => {
             error!("Configuration error: {}", e);
@@ -39,27 +38,20 @@ fn rocket() -> Rocket<Build> {
     };
     info!("Configuration loaded and validated");
 
-    // Create session store
-    let session_store = Arc::new(RwLock::new(SessionStore::new()));
-    info!("Session store initialized");
-
-    // Start the session cleanup task
-    start_session_cleanup_task(
-        session_store.clone(), 
-        config.session.cleanup_interval_minutes,
-        config.session.max_age_minutes
-    );
-    info!("Session cleanup task started");
-
-    // Create WebSocket manager
-    let ws_manager = Arc::new(WebSocketManager::new());
-    info!("WebSocket manager initialized");
+    // Talk to the backend over HTTP
+    let transport = match HttpTransport::new(config.backend.timeouts.connect_ms) {
+        Ok(transport) => transport,
+        Err(e) => {
+            error!("Failed to create backend transport: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    // Build Rocket instance with routes and state
-    rocket::build()
-        .manage(config)
-        .manage(session_store)
-        .manage(ws_manager)
-        .mount("/", api::routes())
-        .mount("/twilio", twilio::routes())
-}
\ No newline at end of file
+    match build_rocket(config, transport).await {
+        Ok(rocket) => rocket,
+        Err(e) => {
+            error!("Startup error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
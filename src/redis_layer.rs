@@ -0,0 +1,36 @@
+use redis::aio::ConnectionManager;
+
+/// Redis connection shared by subsystems that need cross-instance state
+#[derive(Clone)]
+pub struct RedisLayer {
+    connection: ConnectionManager,
+    key_prefix: String,
+}
+
+impl RedisLayer {
+    /// Connect to Redis
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+
+        Ok(RedisLayer {
+            connection,
+            key_prefix: key_prefix.to_string(),
+        })
+    }
+
+    /// Build a namespaced key
+    pub fn key(&self, parts: &[&str]) -> String {
+        let mut key = self.key_prefix.clone();
+        for part in parts {
+            key.push(':');
+            key.push_str(part);
+        }
+        key
+    }
+
+    /// Get a connection handle (cheap to clone)
+    pub fn connection(&self) -> ConnectionManager {
+        self.connection.clone()
+    }
+}
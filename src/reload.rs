@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use log::{error, info, warn};
+use tokio::sync::Mutex;
+
+use crate::config::{Config, SharedConfig, env};
+use crate::secrets;
+
+/// Reloads the configuration from the env file, environment and secret sources
+///
+/// Variables set in the process environment take precedence over the env file,
+/// as they do at startup; file values only replace values the file provided.
+/// Variables removed from the file keep their last value until a restart.
+///
+/// File values are applied as an overlay the configuration reads through
+/// [`env::var`]; the process environment itself is left untouched.
+pub struct ConfigReloader {
+    config: SharedConfig,
+    env_file: Option<PathBuf>,
+    /// The env file's values at startup, which the process environment got from it
+    startup: HashMap<String, String>,
+    /// Values applied from the env file since startup, serializing reloads
+    applied: Mutex<HashMap<String, String>>,
+}
+
+impl ConfigReloader {
+    /// Create a reloader, taking the env file's current values as the ones applied at startup
+    pub fn new(config: SharedConfig) -> Self {
+        let env_file = config.current().reload.env_file.clone().map(PathBuf::from);
+        let startup = env_file.as_deref()
+            .and_then(|path| read_env_file(path).ok())
+            .unwrap_or_default();
+
+        ConfigReloader {
+            config,
+            env_file,
+            startup,
+            applied: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Load and validate a new configuration and swap it in
+    ///
+    /// Calls in progress pick up the new configuration on their next webhook.
+    /// Returns the changed sections that only take effect after a restart; on
+    /// error the current configuration stays in place.
+    pub async fn reload(&self) -> Result<Vec<&'static str>, String> {
+        let mut applied = self.applied.lock().await;
+
+        let mut values = applied.clone();
+        if let Some(path) = &self.env_file {
+            for (key, value) in read_env_file(path)? {
+                let external = std::env::var(&key).ok().filter(|current| self.startup.get(&key) != Some(current));
+                if external.is_none() {
+                    values.insert(key, value);
+                }
+            }
+        }
+
+        env::set_overlay(values.clone());
+        let loaded = match secrets::load().await {
+            Ok(secrets) => Config::from_env(&secrets),
+            Err(e) => Err(e),
+        };
+        let mut config = match loaded {
+            Ok(config) => config,
+            Err(e) => {
+                env::set_overlay(applied.clone());
+                return Err(e);
+            }
+        };
+        *applied = values;
+
+        // Keep what identifies this process, including a generated instance ID
+        let current = self.config.current();
+        config.cluster.instance_id = current.cluster.instance_id.clone();
+        let restart_required = current.restart_required_changes(&config);
+
+        self.config.replace(config);
+        info!("Configuration reloaded");
+        if !restart_required.is_empty() {
+            warn!("Changes to {} take effect after a restart", restart_required.join(", "));
+        }
+
+        Ok(restart_required)
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        let path = self.env_file.as_deref()?;
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+/// Read an env file's variables; a missing file has none
+fn read_env_file(path: &Path) -> Result<HashMap<String, String>, String> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    // The only dotenv API returning the values instead of setting missing ones
+    #[allow(deprecated)]
+    dotenv::from_path_iter(path)
+        .and_then(|items| items.collect())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Start a task reloading the configuration whenever the env file changes
+pub fn start_config_reload_task(reloader: Arc<ConfigReloader>, interval_seconds: u64) {
+    if reloader.env_file.is_none() || interval_seconds == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_seconds));
+        let mut last_modified = reloader.modified();
+
+        loop {
+            interval.tick().await;
+
+            let modified = reloader.modified();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            if let Err(e) = reloader.reload().await {
+                error!("Configuration reload failed, keeping the current configuration: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,320 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::bot::session::{Session, SessionSnapshot};
+use crate::config::ReplicationConfig;
+use crate::redis_layer::RedisLayer;
+
+/// Lifecycle state of a replicated session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicaState {
+    /// The call is in progress
+    Active,
+    /// The bot decided to end the call
+    Ending,
+    /// The call ended and the backend session was closed
+    Closed,
+}
+
+/// Minimal session state shared across regions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReplica {
+    pub call_sid: String,
+    pub session_id: String,
+    pub region: String,
+    pub state: ReplicaState,
+    pub resume_token: String,
+    /// Monotonic write version; stale writes are rejected
+    pub version: u64,
+    pub updated_at: DateTime<Utc>,
+    /// Instance that owns the call while its lease is renewed
+    #[serde(default)]
+    pub owner: String,
+    /// Session state for the instance taking the call over
+    #[serde(default)]
+    pub snapshot: Option<SessionSnapshot>,
+}
+
+/// Compare-and-set: only write if the stored version is older than the new one
+const CAS_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if current then
+    local ok, decoded = pcall(cjson.decode, current)
+    if ok and tonumber(decoded['version']) >= tonumber(ARGV[2]) then
+        return 0
+    end
+end
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[3])
+redis.call('SET', KEYS[2], ARGV[4], 'EX', ARGV[3])
+return 1
+"#;
+
+/// Replicates minimal session state through Redis so another region can take over
+pub struct SessionReplicator {
+    redis: Option<RedisLayer>,
+    region: String,
+    ttl_seconds: u64,
+    /// Identifies this process as the owner of the calls it replicates
+    instance_id: String,
+    lease_seconds: u64,
+}
+
+impl SessionReplicator {
+    /// Create a replicator; replication is disabled when no Redis layer is available
+    pub fn new(redis: Option<RedisLayer>, config: &ReplicationConfig) -> Self {
+        if redis.is_some() {
+            info!("Session replication enabled for region {}", config.region);
+        }
+
+        SessionReplicator {
+            redis,
+            region: config.region.clone(),
+            ttl_seconds: config.ttl_seconds,
+            instance_id: Uuid::new_v4().to_string(),
+            lease_seconds: config.lease_seconds,
+        }
+    }
+
+    /// Whether replication is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    /// Name of the local region
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// Build a replica record owned by this instance
+    pub fn replica(
+        &self,
+        call_sid: &str,
+        session_id: &str,
+        resume_token: &str,
+        state: ReplicaState,
+        version: u64,
+        snapshot: Option<SessionSnapshot>,
+    ) -> SessionReplica {
+        SessionReplica {
+            call_sid: call_sid.to_string(),
+            session_id: session_id.to_string(),
+            region: self.region.clone(),
+            state,
+            resume_token: resume_token.to_string(),
+            version,
+            updated_at: Utc::now(),
+            owner: self.instance_id.clone(),
+            snapshot,
+        }
+    }
+
+    /// Renew this instance's lease on the calls it owns
+    pub async fn renew_lease(&self) {
+        let Some(redis) = &self.redis else {
+            return;
+        };
+
+        let result: Result<(), redis::RedisError> = redis::cmd("SET")
+            .arg(redis.key(&["owner", &self.instance_id]))
+            .arg(&self.region)
+            .arg("EX")
+            .arg(self.lease_seconds)
+            .query_async(&mut redis.connection())
+            .await;
+        if let Err(e) = result {
+            error!("Failed to renew replica lease for instance {}: {}", self.instance_id, e);
+        }
+    }
+
+    /// Whether the instance owning a replica still holds its lease
+    ///
+    /// Replicas written before owners were recorded have no lease. A failed
+    /// lookup counts as held, so a Redis hiccup never triggers a takeover.
+    async fn owner_alive(&self, owner: &str) -> bool {
+        let Some(redis) = &self.redis else {
+            return false;
+        };
+        if owner.is_empty() {
+            return false;
+        }
+
+        let result: Result<bool, redis::RedisError> = redis::cmd("EXISTS")
+            .arg(redis.key(&["owner", owner]))
+            .query_async(&mut redis.connection())
+            .await;
+        match result {
+            Ok(exists) => exists,
+            Err(e) => {
+                error!("Failed to check replica lease of instance {}: {}", owner, e);
+                true
+            }
+        }
+    }
+
+    /// Write a replica; returns false if a newer version already exists
+    pub async fn publish(&self, replica: &SessionReplica) -> bool {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return false,
+        };
+
+        let payload = match serde_json::to_string(replica) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize session replica: {}", e);
+                return false;
+            }
+        };
+
+        let result: Result<i32, redis::RedisError> = redis::Script::new(CAS_SCRIPT)
+            .key(redis.key(&["call", &replica.call_sid]))
+            .key(redis.key(&["session", &replica.session_id]))
+            .arg(payload)
+            .arg(replica.version)
+            .arg(self.ttl_seconds)
+            .arg(&replica.call_sid)
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(1) => {
+                debug!("Replicated session {} ({:?}, v{})", replica.session_id, replica.state, replica.version);
+                true
+            },
+            Ok(_) => {
+                warn!("Rejected stale replica write for call {} (v{})", replica.call_sid, replica.version);
+                false
+            },
+            Err(e) => {
+                error!("Failed to replicate session {}: {}", replica.session_id, e);
+                false
+            }
+        }
+    }
+
+    /// Publish a replica in the background
+    pub fn publish_detached(self: &Arc<Self>, replica: SessionReplica) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let replicator = self.clone();
+        tokio::spawn(async move {
+            replicator.publish(&replica).await;
+        });
+    }
+
+    /// Bump a session's replica version and publish its state in the background
+    pub fn replicate(self: &Arc<Self>, session: &mut Session, state: ReplicaState) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let call_sid = match &session.conversation_id {
+            Some(call_sid) => call_sid.clone(),
+            None => return,
+        };
+
+        session.replica_version += 1;
+        let replica = self.replica(
+            &call_sid, &session.session_id, &session.resume_token, state, session.replica_version, Some(session.snapshot())
+        );
+        self.publish_detached(replica);
+    }
+
+    /// Rebuild a local session for a call owned by an instance that went away
+    ///
+    /// Returns `None` if there is no live replica, its owner still holds its
+    /// lease, or another instance claimed the call first.
+    pub async fn take_over(&self, call_sid: &str) -> Option<Session> {
+        let replica = self.get(call_sid).await?;
+        if replica.state == ReplicaState::Closed || replica.owner == self.instance_id {
+            return None;
+        }
+        if self.owner_alive(&replica.owner).await {
+            debug!("Call {} is still owned by instance {} in region {}", call_sid, replica.owner, replica.region);
+            return None;
+        }
+
+        let mut session = match replica.snapshot.clone() {
+            Some(snapshot) => Session::from_snapshot(snapshot),
+            None => Session::new(
+                call_sid.to_string(),
+                call_sid.to_string(),
+                "twilio".to_string(),
+                Some(call_sid.to_string())
+            ),
+        };
+        session.conversation_id = Some(call_sid.to_string());
+        session.session_id = replica.session_id.clone();
+        session.resume_token = replica.resume_token.clone();
+        session.session_ends = replica.state == ReplicaState::Ending;
+        session.replica_version = replica.version + 1;
+
+        let claim = self.replica(
+            call_sid, &session.session_id, &session.resume_token, replica.state, session.replica_version, Some(session.snapshot())
+        );
+        if !self.publish(&claim).await {
+            return None;
+        }
+
+        info!("Region {} took over call {} from region {}", self.region, call_sid, replica.region);
+        Some(session)
+    }
+
+    /// Mark a call owned by another region as closed; returns its session ID if it was still open
+    pub async fn close_orphan(&self, call_sid: &str) -> Option<String> {
+        let replica = self.get(call_sid).await?;
+        if replica.state == ReplicaState::Closed {
+            return None;
+        }
+
+        let closed = self.replica(call_sid, &replica.session_id, &replica.resume_token, ReplicaState::Closed, replica.version + 1, None);
+        if !self.publish(&closed).await {
+            return None;
+        }
+
+        info!("Region {} closed session {} orphaned by region {}", self.region, replica.session_id, replica.region);
+        Some(replica.session_id)
+    }
+
+    /// Look up the replica for a call
+    pub async fn get(&self, call_sid: &str) -> Option<SessionReplica> {
+        let redis = self.redis.as_ref()?;
+
+        let result: Result<Option<String>, redis::RedisError> = redis::cmd("GET")
+            .arg(redis.key(&["call", call_sid]))
+            .query_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(Some(payload)) => serde_json::from_str(&payload).ok(),
+            Ok(None) => None,
+            Err(e) => {
+                error!("Failed to read session replica for call {}: {}", call_sid, e);
+                None
+            }
+        }
+    }
+}
+
+/// Renew the instance's replica lease in the background while replication is enabled
+pub fn start_replica_lease_task(replicator: Arc<SessionReplicator>) {
+    if !replicator.is_enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs((replicator.lease_seconds / 3).max(1)));
+
+        loop {
+            interval.tick().await;
+            replicator.renew_lease().await;
+        }
+    });
+}
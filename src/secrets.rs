@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::info;
+use sha2::{Digest, Sha256};
+
+use crate::config::{EnvSecretSource, SecretSource, Secrets, env};
+
+/// Time allowed for fetching secrets from an external store at startup
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Secrets fetched once from an external store, keyed by environment variable name
+pub struct StoredSecrets {
+    values: HashMap<String, String>,
+}
+
+impl SecretSource for StoredSecrets {
+    fn get(&self, name: &str) -> Result<Option<String>, String> {
+        Ok(self.values.get(name).cloned())
+    }
+}
+
+/// Build the secret sources, fetching from the store named by `SECRETS_PROVIDER` if set
+///
+/// Environment variables and `_FILE` secrets take precedence over the store.
+pub async fn load() -> Result<Secrets, String> {
+    let secrets = Secrets::from_env();
+
+    let stored = match env::var("SECRETS_PROVIDER").unwrap_or_default().to_lowercase().as_str() {
+        "" | "env" => return Ok(secrets),
+        "vault" => fetch_vault().await?,
+        "aws" => fetch_aws().await?,
+        other => return Err(format!("Unknown SECRETS_PROVIDER '{}'", other)),
+    };
+
+    info!("Loaded {} secrets from the secret store", stored.values.len());
+    Ok(secrets.with_source(Box::new(stored)))
+}
+
+/// Read a secret from a HashiCorp Vault KV engine
+///
+/// `VAULT_SECRET_PATH` is the full API path (e.g. `secret/data/twilio-bot` for KV v2).
+async fn fetch_vault() -> Result<StoredSecrets, String> {
+    let address = required("VAULT_ADDR")?;
+    let path = required("VAULT_SECRET_PATH")?;
+    let token = EnvSecretSource.get("VAULT_TOKEN")?
+        .ok_or_else(|| "VAULT_TOKEN must be set for the Vault secret store".to_string())?;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/v1/{}", address.trim_end_matches('/'), path.trim_start_matches('/')))
+        .header("X-Vault-Token", token)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to read Vault secret {}: {}", path, e))?;
+
+    let body: serde_json::Value = response.json()
+        .await
+        .map_err(|e| format!("Invalid Vault response: {}", e))?;
+
+    // KV v2 nests the secret under data.data, KV v1 under data
+    let data = match body["data"].get("data") {
+        Some(data) if data.is_object() => data,
+        _ => &body["data"],
+    };
+
+    string_map(data).map(|values| StoredSecrets { values })
+}
+
+/// Read a JSON secret from AWS Secrets Manager using credentials from the environment
+async fn fetch_aws() -> Result<StoredSecrets, String> {
+    let secret_id = required("AWS_SECRET_ID")?;
+    let region = required("AWS_REGION")?;
+    let access_key = required("AWS_ACCESS_KEY_ID")?;
+    let secret_key = EnvSecretSource.get("AWS_SECRET_ACCESS_KEY")?
+        .ok_or_else(|| "AWS_SECRET_ACCESS_KEY must be set for the AWS secret store".to_string())?;
+    let session_token = env::var("AWS_SESSION_TOKEN").ok().filter(|s| !s.is_empty());
+
+    let host = format!("secretsmanager.{}.amazonaws.com", region);
+    let body = serde_json::json!({ "SecretId": secret_id }).to_string();
+    let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut headers = vec![
+        ("content-type", "application/x-amz-json-1.1".to_string()),
+        ("host", host.clone()),
+        ("x-amz-date", amz_date.clone()),
+        ("x-amz-target", "secretsmanager.GetSecretValue".to_string()),
+    ];
+    if let Some(token) = &session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    headers.sort();
+
+    let authorization = sign_aws_request(&headers, &body, &amz_date, &region, &access_key, &secret_key);
+
+    let mut request = reqwest::Client::new()
+        .post(format!("https://{}/", host))
+        .header("Authorization", authorization)
+        .body(body)
+        .timeout(FETCH_TIMEOUT);
+    for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+        request = request.header(*name, value);
+    }
+
+    let response = request.send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to read AWS secret {}: {}", secret_id, e))?;
+
+    let body: serde_json::Value = response.json()
+        .await
+        .map_err(|e| format!("Invalid AWS Secrets Manager response: {}", e))?;
+    let secret_string = body["SecretString"].as_str()
+        .ok_or_else(|| format!("AWS secret {} has no SecretString", secret_id))?;
+    let data: serde_json::Value = serde_json::from_str(secret_string)
+        .map_err(|e| format!("AWS secret {} must be a JSON object: {}", secret_id, e))?;
+
+    string_map(&data).map(|values| StoredSecrets { values })
+}
+
+/// Authorization header for a Secrets Manager request signed with AWS Signature Version 4
+///
+/// `headers` must be sorted by lowercase name.
+fn sign_aws_request(
+    headers: &[(&str, String)],
+    body: &str,
+    amz_date: &str,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+) -> String {
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/secretsmanager/aws4_request", date, region);
+
+    let canonical_headers: String = headers.iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body.as_bytes()))
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date);
+    for part in [region, "secretsmanager", "aws4_request"] {
+        key = hmac_sha256(&key, part);
+    }
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Collect the string values of a JSON object
+fn string_map(data: &serde_json::Value) -> Result<HashMap<String, String>, String> {
+    let object = data.as_object()
+        .ok_or_else(|| "Secret store value must be a JSON object".to_string())?;
+
+    Ok(object.iter()
+        .filter_map(|(name, value)| value.as_str().map(|v| (name.clone(), v.to_string())))
+        .collect())
+}
+
+fn required(name: &str) -> Result<String, String> {
+    env::var(name)
+        .ok()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("{} must be set for the secret store", name))
+}
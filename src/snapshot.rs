@@ -0,0 +1,150 @@
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::bot::session::{SessionSnapshot, SessionStore};
+use crate::config::{ReplicationConfig, SnapshotConfig};
+use crate::redis_layer::RedisLayer;
+
+/// Sessions captured at shutdown
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotDocument {
+    taken_at: DateTime<Utc>,
+    sessions: Vec<SessionSnapshot>,
+}
+
+/// Where session snapshots are kept between deploys
+enum SnapshotTarget {
+    File(String),
+    /// Redis key for the local region's snapshot
+    Redis(RedisLayer, String),
+}
+
+/// Saves live sessions on shutdown and restores them at startup
+///
+/// With Redis, the snapshot is claimed atomically at startup so only one new
+/// instance restores it.
+pub struct SnapshotStore {
+    target: Option<SnapshotTarget>,
+    max_age: Duration,
+}
+
+impl SnapshotStore {
+    /// Create a store; snapshots are disabled when no file or Redis target is configured
+    pub fn new(config: &SnapshotConfig, replication: &ReplicationConfig, redis: Option<RedisLayer>) -> Self {
+        let target = match (redis.filter(|_| config.use_redis), &config.path) {
+            (Some(redis), _) => {
+                let key = redis.key(&["snapshot", &replication.region]);
+                Some(SnapshotTarget::Redis(redis, key))
+            },
+            (None, Some(path)) => Some(SnapshotTarget::File(path.clone())),
+            (None, None) => None,
+        };
+
+        SnapshotStore {
+            target,
+            max_age: Duration::seconds(config.max_age_seconds as i64),
+        }
+    }
+
+    /// Write all sessions to the snapshot target
+    pub async fn save(&self, sessions: &SessionStore) {
+        let target = match &self.target {
+            Some(target) => target,
+            None => return,
+        };
+
+        let document = SnapshotDocument {
+            taken_at: Utc::now(),
+            sessions: sessions.snapshot().await,
+        };
+        let count = document.sessions.len();
+
+        let payload = match serde_json::to_string(&document) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize session snapshot: {}", e);
+                return;
+            }
+        };
+
+        let result = match target {
+            SnapshotTarget::File(path) => tokio::fs::write(path, payload)
+                .await
+                .map_err(|e| e.to_string()),
+            SnapshotTarget::Redis(redis, key) => {
+                let ttl = self.max_age.num_seconds().max(1);
+                redis::cmd("SET")
+                    .arg(key)
+                    .arg(payload)
+                    .arg("EX")
+                    .arg(ttl)
+                    .query_async::<_, ()>(&mut redis.connection())
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+        };
+
+        match result {
+            Ok(()) => info!("Saved snapshot of {} sessions", count),
+            Err(e) => error!("Failed to save session snapshot: {}", e),
+        }
+    }
+
+    /// Take the stored snapshot and add its sessions to the store
+    ///
+    /// The snapshot is removed once read. Returns the IDs of the restored sessions.
+    pub async fn restore(&self, sessions: &SessionStore) -> Vec<String> {
+        let target = match &self.target {
+            Some(target) => target,
+            None => return Vec::new(),
+        };
+
+        let payload = match target {
+            SnapshotTarget::File(path) => match tokio::fs::read_to_string(path).await {
+                Ok(payload) => {
+                    if let Err(e) = tokio::fs::remove_file(path).await {
+                        warn!("Failed to remove session snapshot {}: {}", path, e);
+                    }
+                    Some(payload)
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    error!("Failed to read session snapshot {}: {}", path, e);
+                    None
+                }
+            },
+            SnapshotTarget::Redis(redis, key) => {
+                let result: Result<Option<String>, redis::RedisError> = redis::cmd("GETDEL")
+                    .arg(key)
+                    .query_async(&mut redis.connection())
+                    .await;
+                match result {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        error!("Failed to read session snapshot: {}", e);
+                        None
+                    }
+                }
+            },
+        };
+
+        let document: SnapshotDocument = match payload.map(|p| serde_json::from_str(&p)) {
+            Some(Ok(document)) => document,
+            Some(Err(e)) => {
+                error!("Failed to parse session snapshot: {}", e);
+                return Vec::new();
+            },
+            None => return Vec::new(),
+        };
+
+        if Utc::now() - document.taken_at > self.max_age {
+            warn!("Ignoring session snapshot taken at {}", document.taken_at);
+            return Vec::new();
+        }
+
+        let restored = sessions.restore(document.sessions);
+        info!("Restored {} sessions from snapshot", restored.len());
+        restored
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::sync::RwLock;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::i18n::{MessageCatalog, Phrase};
+
+/// Scopes that can be granted to tenant API keys
+pub const API_KEY_SCOPES: &[&str] = &[
+    "calls:read",
+    "calls:write",
+    "sessions:read",
+    "sessions:write",
+];
+
+/// Twilio credentials used for a tenant's calls
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TenantCredentials {
+    pub account_sid: String,
+    pub auth_token: String,
+}
+
+impl fmt::Debug for TenantCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantCredentials")
+            .field("account_sid", &self.account_sid)
+            .field("auth_token", &"[redacted]")
+            .finish()
+    }
+}
+
+/// An API key issued to a tenant; only the hash of the secret is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A provisioned tenant (brand)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,
+    pub credentials: Option<TenantCredentials>,
+    #[serde(default)]
+    pub phone_numbers: Vec<String>,
+    /// Caller ID for the tenant's outbound calls; the first phone number when unset
+    #[serde(default)]
+    pub caller_id: Option<String>,
+    /// Daily spend above which the tenant's outbound calls are refused; `DAILY_CALL_BUDGET` when unset
+    #[serde(default)]
+    pub daily_budget: Option<f64>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    #[serde(default)]
+    pub prompts: HashMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tenant {
+    /// Caller ID for the tenant's outbound calls, if it has one
+    pub fn default_caller_id(&self) -> Option<&str> {
+        self.caller_id.as_deref().or(self.phone_numbers.first().map(|n| n.as_str()))
+    }
+}
+
+/// Prefix of tenant secrets encrypted in the tenants file
+const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Why a tenant could not be added
+#[derive(Debug)]
+pub enum TenantError {
+    /// Another tenant already has the name
+    NameTaken(String),
+    /// The tenants file could not be written
+    Persist(String),
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TenantError::NameTaken(name) => write!(f, "Tenant '{}' already exists", name),
+            TenantError::Persist(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Encrypts tenant auth tokens written to the tenants file with AES-256-GCM
+struct SecretCipher(Aes256Gcm);
+
+impl SecretCipher {
+    /// Create a cipher from a base64-encoded 256-bit key
+    fn from_base64(key: &str) -> Result<Self, String> {
+        let key = general_purpose::STANDARD.decode(key.trim())
+            .map_err(|e| format!("TENANT_SECRETS_KEY is not valid base64: {}", e))?;
+        if key.len() != 32 {
+            return Err("TENANT_SECRETS_KEY must be 32 bytes".to_string());
+        }
+        Ok(SecretCipher(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))))
+    }
+
+    /// Encrypt a secret under a fresh nonce
+    fn seal(&self, secret: &str) -> Result<String, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let mut sealed = nonce.to_vec();
+        sealed.extend(self.0.encrypt(&nonce, secret.as_bytes()).map_err(|e| format!("Failed to encrypt secret: {}", e))?);
+        Ok(format!("{}{}", SEALED_PREFIX, general_purpose::STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a secret written by `seal`
+    fn open(&self, sealed: &str) -> Result<String, String> {
+        let sealed = general_purpose::STANDARD.decode(sealed.trim_start_matches(SEALED_PREFIX))
+            .map_err(|e| format!("Invalid encrypted secret: {}", e))?;
+        if sealed.len() < 12 {
+            return Err("Invalid encrypted secret".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(12);
+        let secret = self.0.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt secret; is TENANT_SECRETS_KEY the key it was written with?".to_string())?;
+        String::from_utf8(secret).map_err(|e| format!("Invalid encrypted secret: {}", e))
+    }
+}
+
+/// Hash an API key secret for storage and comparison
+pub fn hash_api_key(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Store of provisioned tenants, optionally persisted to a JSON file
+///
+/// Tenant auth tokens are encrypted in the file with `TENANT_SECRETS_KEY`;
+/// tenants with Twilio credentials cannot be persisted without it.
+pub struct TenantStore {
+    file: Option<String>,
+    cipher: Option<SecretCipher>,
+    tenants: RwLock<HashMap<String, Tenant>>,
+}
+
+impl TenantStore {
+    /// Create a tenant store, loading existing tenants from the file if configured
+    ///
+    /// `secrets_key` is the base64-encoded 256-bit key for the tenants' auth tokens.
+    /// A file that exists but cannot be read is an error, since the next tenant
+    /// added would overwrite it.
+    pub fn new(file: Option<String>, secrets_key: Option<&str>) -> Result<Self, String> {
+        let cipher = secrets_key.map(SecretCipher::from_base64).transpose()?;
+        let mut tenants = HashMap::new();
+
+        if let Some(path) = &file {
+            match fs::read_to_string(path) {
+                Ok(content) => match serde_json::from_str::<Vec<Tenant>>(&content) {
+                    Ok(list) => {
+                        info!("Loaded {} tenant(s) from {}", list.len(), path);
+                        for mut tenant in list {
+                            if let Some(credentials) = tenant.credentials.as_mut().filter(|c| c.auth_token.starts_with(SEALED_PREFIX)) {
+                                let cipher = cipher.as_ref()
+                                    .ok_or_else(|| format!("TENANT_SECRETS_KEY is required to read the credentials in {}", path))?;
+                                credentials.auth_token = cipher.open(&credentials.auth_token)?;
+                            }
+                            tenants.insert(tenant.id.clone(), tenant);
+                        }
+                    },
+                    Err(e) => return Err(format!("Failed to parse tenants file {}: {}", path, e)),
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                Err(e) => return Err(format!("Failed to read tenants file {}: {}", path, e)),
+            }
+        }
+
+        Ok(TenantStore {
+            file,
+            cipher,
+            tenants: RwLock::new(tenants),
+        })
+    }
+
+    /// Check whether a tenant name is already taken
+    pub fn name_exists(&self, name: &str) -> bool {
+        self.tenants
+            .read()
+            .unwrap()
+            .values()
+            .any(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether tenants with Twilio credentials can be persisted, which needs `TENANT_SECRETS_KEY`
+    pub fn can_store_credentials(&self) -> bool {
+        self.file.is_none() || self.cipher.is_some()
+    }
+
+    /// Add a tenant and persist the store
+    ///
+    /// The name is checked and the tenant inserted under one lock, so two
+    /// concurrent requests cannot both register a name. A tenant that cannot be
+    /// persisted is not kept.
+    pub fn add(&self, tenant: Tenant) -> Result<(), TenantError> {
+        let mut tenants = self.tenants.write().unwrap();
+        if tenants.values().any(|t| t.name.eq_ignore_ascii_case(&tenant.name)) {
+            return Err(TenantError::NameTaken(tenant.name));
+        }
+
+        let tenant_id = tenant.id.clone();
+        tenants.insert(tenant_id.clone(), tenant);
+        if let Err(e) = self.persist(&tenants) {
+            tenants.remove(&tenant_id);
+            return Err(TenantError::Persist(e));
+        }
+        Ok(())
+    }
+
+    /// Get a tenant by ID
+    pub fn get(&self, tenant_id: &str) -> Option<Tenant> {
+        self.tenants.read().unwrap().get(tenant_id).cloned()
+    }
+
+    /// Find the tenant that owns a phone number
+    pub fn find_by_number(&self, phone_number: &str) -> Option<Tenant> {
+        self.tenants
+            .read()
+            .unwrap()
+            .values()
+            .find(|t| t.phone_numbers.iter().any(|n| n == phone_number))
+            .cloned()
+    }
+
+    /// A tenant's own text for a phrase, if it has one
+    pub fn prompt(&self, tenant_id: &str, phrase: Phrase) -> Option<String> {
+        self.tenants.read().unwrap()
+            .get(tenant_id)
+            .and_then(|t| t.prompts.get(phrase.key()))
+            .cloned()
+    }
+
+    /// Resolve an API key secret to its tenant and key
+    pub fn authenticate(&self, secret: &str) -> Option<(Tenant, ApiKey)> {
+        let hash = hash_api_key(secret);
+        let tenants = self.tenants.read().unwrap();
+
+        tenants.values().find_map(|tenant| {
+            tenant.api_keys
+                .iter()
+                .find(|k| k.key_hash == hash)
+                .map(|key| (tenant.clone(), key.clone()))
+        })
+    }
+
+    /// Write all tenants to the configured file, with their auth tokens encrypted
+    ///
+    /// Called with the store's write lock held, so writes never interleave.
+    fn persist(&self, tenants: &HashMap<String, Tenant>) -> Result<(), String> {
+        let path = match &self.file {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut sealed = Vec::with_capacity(tenants.len());
+        for tenant in tenants.values() {
+            let mut tenant = tenant.clone();
+            if let Some(credentials) = tenant.credentials.as_mut() {
+                let cipher = self.cipher.as_ref()
+                    .ok_or_else(|| "TENANT_SECRETS_KEY must be set to store tenant credentials".to_string())?;
+                credentials.auth_token = cipher.seal(&credentials.auth_token)?;
+            }
+            sealed.push(tenant);
+        }
+        let content = serde_json::to_string_pretty(&sealed).map_err(|e| e.to_string())?;
+
+        // Write to a temporary file first so a crash never leaves a truncated store
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, content).map_err(|e| format!("Failed to write {}: {}", tmp_path, e))?;
+        fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace {}: {}", path, e))
+    }
+}
+
+/// Generate a new API key, returning the key record and the plaintext secret
+pub fn generate_api_key(name: &str, scopes: Vec<String>) -> (ApiKey, String) {
+    let secret = format!("tb_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+    let key = ApiKey {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        key_hash: hash_api_key(&secret),
+        scopes,
+        created_at: Utc::now(),
+    };
+
+    (key, secret)
+}
+
+/// Build the prompt set for a new tenant from the catalog's default language, applying overrides
+pub fn seed_prompts(catalog: &MessageCatalog, overrides: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut prompts: HashMap<String, String> = Phrase::ALL
+        .iter()
+        .map(|p| (p.key().to_string(), catalog.text(*p, None)))
+        .collect();
+
+    for (key, text) in overrides {
+        prompts.insert(key.clone(), text.clone());
+    }
+
+    prompts
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use log::debug;
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::config::DialerConfig;
+
+/// Longest wait before an agent-paced dial re-checks the pool, so expired handoffs free their slot
+const POOL_RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A call placed by an agent-paced campaign
+#[derive(Debug, Clone, Copy)]
+struct PacedCall {
+    placed_at: Instant,
+    /// When the bot handed the call to an agent
+    handed_off_at: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    /// Dials that were let through but have no call SID yet
+    placing: u32,
+    /// Dials waiting for a free slot
+    waiting: u32,
+    calls: HashMap<String, PacedCall>,
+}
+
+impl PoolState {
+    /// Drop handoffs that are over by the average handle time, and calls connecting for longer than they may
+    ///
+    /// The end of a call is only seen by the replica receiving its final status
+    /// callback, so calls are also let go of without it.
+    fn expire(&mut self, config: &DialerConfig) {
+        let handle_time = Duration::from_secs(config.average_handle_seconds);
+        let max_connecting = Duration::from_secs(config.max_connecting_seconds);
+        self.calls.retain(|_, call| match call.handed_off_at {
+            Some(at) => at.elapsed() < handle_time,
+            None => call.placed_at.elapsed() < max_connecting,
+        });
+    }
+
+    fn busy(&self) -> usize {
+        self.calls.values().filter(|call| call.handed_off_at.is_some()).count()
+    }
+
+    /// Calls with the bot that may yet need an agent, and dials being placed
+    fn connecting(&self) -> usize {
+        self.calls.len() - self.busy() + self.placing as usize
+    }
+}
+
+/// Agent occupancy of agent-paced campaigns
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentPoolSummary {
+    pub pool_size: u32,
+    /// Agents handling a call handed off by a paced campaign
+    pub busy_agents: u32,
+    /// Paced calls not handed off yet, each of which may need an agent
+    pub connecting_calls: u32,
+    /// Share of the pool the busy agents and connecting calls take up
+    pub occupancy: Option<f64>,
+    /// Dials waiting for a free agent
+    pub waiting_dials: u32,
+}
+
+/// Pacing of campaigns whose calls are handed to human agents
+///
+/// A power dialer places a call only while the calls it has in progress and
+/// the calls its agents are handling fit in the agent pool, so no caller is
+/// connected without an agent to take them. An agent is assumed free once the
+/// call ends, or once the average handle time has passed since the handoff. A
+/// call that is never handed off frees its slot after `max_connecting_seconds`
+/// even if its end is not seen.
+#[derive(Default)]
+pub struct AgentPool {
+    state: Mutex<PoolState>,
+    freed: Notify,
+}
+
+impl AgentPool {
+    /// Wait until an agent is free for another call, then hold its slot for the dial
+    ///
+    /// Follow with `dialed` once the call is placed or has failed.
+    pub async fn acquire(&self, config: &DialerConfig) {
+        let mut waiting = false;
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                state.expire(config);
+                if state.busy() + state.connecting() < config.agent_pool_size as usize {
+                    state.placing += 1;
+                    if waiting {
+                        state.waiting -= 1;
+                    }
+                    return;
+                }
+                if !waiting {
+                    state.waiting += 1;
+                    waiting = true;
+                    debug!("Agent pool is full, waiting to dial");
+                }
+            }
+            let _ = tokio::time::timeout(POOL_RECHECK_INTERVAL, self.freed.notified()).await;
+        }
+    }
+
+    /// Track the call placed with an acquired slot; `None` gives the slot back
+    pub fn dialed(&self, call_sid: Option<&str>) {
+        let mut state = self.state.lock().unwrap();
+        state.placing = state.placing.saturating_sub(1);
+        match call_sid {
+            Some(call_sid) => {
+                state.calls.insert(call_sid.to_string(), PacedCall {
+                    placed_at: Instant::now(),
+                    handed_off_at: None,
+                });
+            },
+            None => self.freed.notify_waiters(),
+        }
+    }
+
+    /// Count a paced call's agent busy from now; other calls are ignored
+    pub fn handed_off(&self, call_sid: &str) {
+        if let Some(call) = self.state.lock().unwrap().calls.get_mut(call_sid) {
+            call.handed_off_at.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Free the slot of a paced call that ended
+    pub fn release(&self, call_sid: &str) {
+        if self.state.lock().unwrap().calls.remove(call_sid).is_some() {
+            self.freed.notify_waiters();
+        }
+    }
+
+    pub fn summary(&self, config: &DialerConfig) -> AgentPoolSummary {
+        let mut state = self.state.lock().unwrap();
+        state.expire(config);
+        let (busy, connecting) = (state.busy() as u32, state.connecting() as u32);
+
+        AgentPoolSummary {
+            pool_size: config.agent_pool_size,
+            busy_agents: busy,
+            connecting_calls: connecting,
+            occupancy: (config.agent_pool_size > 0)
+                .then(|| (busy + connecting) as f64 / config.agent_pool_size as f64),
+            waiting_dials: state.waiting,
+        }
+    }
+}
+
+/// Agent pool shared by every agent-paced campaign of the process
+pub fn agent_pool() -> &'static AgentPool {
+    static POOL: OnceLock<AgentPool> = OnceLock::new();
+    POOL.get_or_init(AgentPool::default)
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, warn};
+
+use crate::bot::cdr::CdrStore;
+use crate::config::Config;
+use crate::tenant::TenantStore;
+use crate::twilio::client::{TwilioCallDetails, TwilioClient};
+
+/// Fetch a finished call's price in the background and add it to the CDR spend
+///
+/// Twilio sets the price some time after the call ends, so the fetch waits and
+/// retries until the price appears.
+pub fn start_call_price_fetch(call_sid: String, cdrs: Arc<CdrStore>, tenants: Arc<TenantStore>, config: Config) {
+    let costs = config.costs.clone();
+    if costs.price_fetch_attempts == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let twilio_client = match TwilioClient::new(
+            config.twilio.account_sid.clone(),
+            config.twilio.auth_token.clone(),
+            config.twilio.region.clone(),
+            config.twilio.edge.clone()
+        ) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to create Twilio client for call {} price: {}", call_sid, e);
+                return;
+            }
+        };
+
+        for _ in 0..costs.price_fetch_attempts {
+            tokio::time::sleep(Duration::from_secs(costs.price_fetch_delay_seconds)).await;
+
+            let call = match twilio_client.fetch_call(&call_sid).await {
+                Ok(call) => call,
+                Err(_) => continue,
+            };
+            let Some(price) = call.price.as_deref().and_then(|p| p.parse::<f64>().ok()) else {
+                continue;
+            };
+
+            // Twilio reports charges as negative amounts
+            let price = price.abs();
+            let tenant_id = service_number(&call)
+                .and_then(|number| tenants.find_by_number(number))
+                .map(|tenant| tenant.id);
+            debug!("Call {} cost {} {}", call_sid, price, call.price_unit.as_deref().unwrap_or_default());
+
+            cdrs.record_cost(&call_sid, tenant_id, price, call.price_unit);
+            return;
+        }
+
+        warn!("Price for call {} was not available after {} attempts", call_sid, costs.price_fetch_attempts);
+    });
+}
+
+/// Our number on the call: the dialed number for inbound calls, the caller ID otherwise
+fn service_number(call: &TwilioCallDetails) -> Option<&str> {
+    match call.direction.as_deref() {
+        Some("inbound") => call.to.as_deref(),
+        _ => call.from.as_deref(),
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Progress of an outbound call job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallJobStatus {
+    /// Opening the backend session and dialing
+    Pending,
+    /// Twilio accepted the call
+    Completed,
+    /// The call could not be placed
+    Failed,
+}
+
+/// Outbound call placed in the background
+#[derive(Debug, Clone, Serialize)]
+pub struct CallJob {
+    pub job_id: String,
+    pub status: CallJobStatus,
+    pub to_number: String,
+    pub call_sid: Option<String>,
+    /// HTTP status the synchronous endpoint would have returned on failure
+    pub error_status: Option<u16>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// In-memory store of outbound call jobs, kept for a limited time
+pub struct CallJobStore {
+    ttl: Duration,
+    jobs: DashMap<String, CallJob>,
+}
+
+impl CallJobStore {
+    /// Create a store keeping finished jobs for `ttl_seconds`
+    pub fn new(ttl_seconds: u64) -> Self {
+        CallJobStore {
+            ttl: Duration::from_secs(ttl_seconds),
+            jobs: DashMap::new(),
+        }
+    }
+
+    /// Register a new pending job and return it
+    pub fn create(&self, to_number: &str) -> CallJob {
+        let now = Utc::now();
+        let job = CallJob {
+            job_id: Uuid::new_v4().to_string(),
+            status: CallJobStatus::Pending,
+            to_number: to_number.to_string(),
+            call_sid: None,
+            error_status: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.jobs.insert(job.job_id.clone(), job.clone());
+        job
+    }
+
+    /// Mark a job as completed with the call SID Twilio returned
+    pub fn complete(&self, job_id: &str, call_sid: &str) {
+        if let Some(mut job) = self.jobs.get_mut(job_id) {
+            job.status = CallJobStatus::Completed;
+            job.call_sid = Some(call_sid.to_string());
+            job.updated_at = Utc::now();
+        }
+    }
+
+    /// Mark a job as failed
+    pub fn fail(&self, job_id: &str, status: u16, error: &str) {
+        if let Some(mut job) = self.jobs.get_mut(job_id) {
+            job.status = CallJobStatus::Failed;
+            job.error_status = Some(status);
+            job.error = Some(error.to_string());
+            job.updated_at = Utc::now();
+        }
+    }
+
+    /// Get a job by ID
+    pub fn get(&self, job_id: &str) -> Option<CallJob> {
+        self.jobs.get(job_id).map(|job| job.clone())
+    }
+
+    /// Drop finished jobs older than the TTL
+    pub fn purge_expired(&self) {
+        let cutoff = Utc::now() - chrono::Duration::from_std(self.ttl).unwrap_or_else(|_| chrono::Duration::zero());
+        self.jobs.retain(|_, job| job.status == CallJobStatus::Pending || job.updated_at > cutoff);
+    }
+}
+
+/// Start a periodic task that drops expired call jobs
+pub fn start_call_job_cleanup_task(jobs: Arc<CallJobStore>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+        loop {
+            interval.tick().await;
+            jobs.purge_expired();
+        }
+    });
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use log::debug;
+use rocket::http::Status;
+
+use crate::config::Config;
+use crate::tenant::TenantStore;
+use crate::twilio::client::TwilioClient;
+use crate::twilio::handlers::MakeCallRequest;
+
+/// How long a successful caller ID check is trusted before asking Twilio again
+const VERIFIED_TTL: Duration = Duration::from_secs(600);
+
+/// Why an outbound call's caller ID could not be used
+#[derive(Debug)]
+pub enum CallerIdError {
+    /// The request asked for a caller ID it may not use
+    Rejected(String),
+    /// Twilio could not be asked whether the caller ID is verified
+    Unavailable(String),
+}
+
+impl CallerIdError {
+    /// HTTP status for rejecting the call
+    pub fn status(&self) -> Status {
+        match self {
+            CallerIdError::Rejected(_) => Status::BadRequest,
+            CallerIdError::Unavailable(_) => Status::BadGateway,
+        }
+    }
+}
+
+impl fmt::Display for CallerIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallerIdError::Rejected(msg) | CallerIdError::Unavailable(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Chooses and verifies the caller ID for outbound calls
+pub struct CallerIds {
+    /// When each caller ID was last confirmed, keyed by account SID and caller ID
+    verified: DashMap<(String, String), Instant>,
+}
+
+impl CallerIds {
+    pub fn new() -> Self {
+        CallerIds {
+            verified: DashMap::new(),
+        }
+    }
+
+    /// Caller ID for an outbound call
+    ///
+    /// Uses the request's `from_number`, then the tenant's default caller ID,
+    /// then `FROM_NUMBER`. Numbers other than `FROM_NUMBER` must be owned by or
+    /// verified on the Twilio account placing the call, which is the tenant's own
+    /// account when it has credentials.
+    pub async fn resolve(&self, request: &MakeCallRequest, tenants: &TenantStore, config: &Config) -> Result<String, CallerIdError> {
+        let tenant = match &request.tenant_id {
+            Some(tenant_id) => Some(tenants.get(tenant_id)
+                .ok_or_else(|| CallerIdError::Rejected(format!("Unknown tenant '{}'", tenant_id)))?),
+            None => None,
+        };
+
+        let caller_id = request.from_number.as_deref()
+            .or(tenant.as_ref().and_then(|t| t.default_caller_id()))
+            .unwrap_or(&config.twilio.from_number)
+            .trim()
+            .to_string();
+
+        if caller_id.is_empty() {
+            return Err(CallerIdError::Rejected("Caller ID cannot be empty".to_string()));
+        }
+        let own_account = tenant.as_ref().and_then(|t| t.credentials.as_ref()).is_some();
+        if caller_id == config.twilio.from_number && !own_account {
+            return Ok(caller_id);
+        }
+
+        let twilio_client = TwilioClient::for_tenant(tenant.as_ref(), &config.twilio)
+            .map_err(|e| CallerIdError::Unavailable(e.to_string()))?;
+        let key = (twilio_client.account_sid().to_string(), caller_id.clone());
+        if self.verified.get(&key).is_some_and(|checked| checked.elapsed() < VERIFIED_TTL) {
+            return Ok(caller_id);
+        }
+
+        if !Self::is_verified(&caller_id, &twilio_client).await.map_err(CallerIdError::Unavailable)? {
+            return Err(CallerIdError::Rejected(format!(
+                "Caller ID {} is not a phone number or verified caller ID on the Twilio account",
+                caller_id
+            )));
+        }
+
+        self.verified.insert(key, Instant::now());
+        Ok(caller_id)
+    }
+
+    /// Whether the client's account owns the number or has it as a verified outgoing caller ID
+    async fn is_verified(caller_id: &str, twilio_client: &TwilioClient) -> Result<bool, String> {
+        debug!("Verifying caller ID {}", caller_id);
+
+        let owned = twilio_client.list_phone_numbers(caller_id)
+            .await
+            .map_err(|e| format!("Failed to verify caller ID {}: {}", caller_id, e))?;
+        if !owned.is_empty() {
+            return Ok(true);
+        }
+
+        let verified = twilio_client.list_outgoing_caller_ids(caller_id)
+            .await
+            .map_err(|e| format!("Failed to verify caller ID {}: {}", caller_id, e))?;
+        Ok(!verified.is_empty())
+    }
+}
+
+impl Default for CallerIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
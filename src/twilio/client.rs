@@ -1,15 +1,196 @@
-use reqwest::{Client, Error as ReqwestError};
+use reqwest::{Client, Error as ReqwestError, Response};
 use base64::{Engine as _, engine::general_purpose};
 use serde::{Deserialize, Serialize};
 use log::{debug, error, info};
+
+use crate::config::TwilioConfig;
+use crate::tenant::Tenant;
+use crate::twilio::cps::{self, CallRateLimiter};
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 /// Represents a Twilio call resource
 #[derive(Debug, Deserialize)]
 pub struct TwilioCall {
     pub sid: String,
     pub status: String,
+    /// `inbound`, `outbound-api` or `outbound-dial`
+    #[serde(default)]
+    pub direction: Option<String>,
+}
+
+/// A Twilio call resource with its billing details
+#[derive(Debug, Deserialize)]
+pub struct TwilioCallDetails {
+    pub sid: String,
+    pub status: String,
+    /// Charge for the call as a negative decimal string, set some time after it ends
+    pub price: Option<String>,
+    pub price_unit: Option<String>,
+    /// `inbound`, `outbound-api` or `outbound-dial`
+    pub direction: Option<String>,
+    pub to: Option<String>,
+    pub from: Option<String>,
+}
+
+/// Represents a Twilio queue resource
+#[derive(Debug, Deserialize)]
+pub struct TwilioQueue {
+    pub sid: String,
+    pub friendly_name: String,
+    /// Number of calls currently waiting in the queue
+    pub current_size: u32,
+    /// Average time in seconds the calls currently in the queue have waited
+    pub average_wait_time: u32,
+}
+
+/// Represents a Twilio message (SMS) resource
+#[derive(Debug, Deserialize)]
+pub struct TwilioMessage {
+    pub sid: String,
+    pub status: String,
+}
+
+/// Represents a Twilio conference participant resource
+#[derive(Debug, Deserialize)]
+pub struct TwilioParticipant {
+    pub call_sid: String,
+}
+
+/// Represents a Twilio recording resource
+#[derive(Debug, Clone, Deserialize)]
+pub struct TwilioRecording {
+    pub sid: String,
+    pub call_sid: Option<String>,
+    pub status: Option<String>,
+    /// Length in seconds as a decimal string, absent while recording
+    pub duration: Option<String>,
+    pub channels: Option<u32>,
+    /// Creation time in RFC 2822 format
+    pub date_created: Option<String>,
+}
+
+/// Represents a Twilio transcription of a recording
+#[derive(Debug, Clone, Deserialize)]
+pub struct TwilioTranscription {
+    pub sid: String,
+    pub status: Option<String>,
+    pub transcription_text: Option<String>,
+}
+
+/// Carrier and caller details for a phone number from the Lookup v2 API
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NumberLookup {
+    pub phone_number: String,
+    pub country_code: Option<String>,
+    pub carrier_name: Option<String>,
+    /// Line type (e.g. `mobile`, `landline`, `nonFixedVoip`)
+    pub line_type: Option<String>,
+    pub caller_name: Option<String>,
+    /// Caller type (`CONSUMER` or `BUSINESS`)
+    pub caller_type: Option<String>,
+}
+
+impl NumberLookup {
+    /// Extract the fields we use from a Lookup v2 response
+    fn from_response(value: &serde_json::Value) -> Self {
+        let text = |v: &serde_json::Value| v.as_str().map(|s| s.to_string());
+        let line_type = &value["line_type_intelligence"];
+        let caller_name = &value["caller_name"];
+
+        NumberLookup {
+            phone_number: text(&value["phone_number"]).unwrap_or_default(),
+            country_code: text(&value["country_code"]),
+            carrier_name: text(&line_type["carrier_name"]),
+            line_type: text(&line_type["type"]),
+            caller_name: text(&caller_name["caller_name"]),
+            caller_type: text(&caller_name["caller_type"]),
+        }
+    }
+}
+
+/// Optional parameters for creating an outbound call
+#[derive(Debug, Clone, Default)]
+pub struct CallOptions {
+    /// Answering machine detection mode (e.g. `DetectMessageEnd`)
+    pub machine_detection: Option<String>,
+    /// Callback URL for asynchronous answering machine detection results
+    pub async_amd_status_callback: Option<String>,
+    /// Record the call from the moment it is answered
+    pub record: bool,
+    /// Custom `X-` headers sent in the SIP INVITE when dialing a SIP URI
+    pub sip_headers: Vec<(String, String)>,
+    /// Credentials for SIP endpoints that challenge the INVITE
+    pub sip_auth_username: Option<String>,
+    pub sip_auth_password: Option<String>,
+    /// DTMF digits played once the call is answered, e.g. to pick an IVR menu option
+    pub send_digits: Option<String>,
+}
+
+/// Most characters Twilio accepts in `SendDigits`
+pub const MAX_SEND_DIGITS_LENGTH: usize = 32;
+
+/// Whether a `SendDigits` sequence only holds digits, `*`, `#` and `w` pauses
+pub fn is_valid_send_digits(digits: &str) -> bool {
+    !digits.is_empty()
+        && digits.len() <= MAX_SEND_DIGITS_LENGTH
+        && digits.chars().all(|c| c.is_ascii_digit() || matches!(c, '*' | '#' | 'w' | 'W'))
+}
+
+/// Whether a destination is a SIP URI (e.g. `sip:agent@pbx.example.com`) rather than a phone number
+pub fn is_sip_address(to: &str) -> bool {
+    to.trim().get(..4).is_some_and(|scheme| scheme.eq_ignore_ascii_case("sip:"))
+}
+
+/// Append custom headers to a SIP URI the way Twilio expects them
+///
+/// Headers without the required `X-` prefix are dropped.
+fn sip_uri_with_headers(uri: &str, headers: &[(String, String)]) -> String {
+    let query: Vec<String> = headers.iter()
+        .filter(|(name, _)| name.len() > 2 && name[..2].eq_ignore_ascii_case("x-"))
+        .map(|(name, value)| format!("{}={}", urlencoding::encode(name), urlencoding::encode(value)))
+        .collect();
+
+    if query.is_empty() {
+        return uri.to_string();
+    }
+
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", uri, separator, query.join("&"))
+}
+
+/// Twilio error code for failed authentication
+pub const ERROR_AUTHENTICATION: u32 = 20003;
+/// Twilio error code for exceeding the API concurrency or rate limit
+pub const ERROR_RATE_LIMITED: u32 = 20429;
+/// Twilio error code for an invalid `To` phone number
+pub const ERROR_INVALID_TO_NUMBER: u32 = 21211;
+/// Twilio error code for a `To` phone number that is not a valid phone number
+pub const ERROR_NOT_A_PHONE_NUMBER: u32 = 21217;
+
+/// Error body returned by the Twilio REST API
+#[derive(Debug, Clone, Deserialize)]
+pub struct TwilioApiError {
+    /// Twilio error code (e.g. 21211)
+    pub code: Option<u32>,
+    pub message: String,
+    /// Link to the Twilio documentation for the error code
+    pub more_info: Option<String>,
+    /// Delay requested by a `Retry-After` header
+    #[serde(skip)]
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for TwilioApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{} (code {})", self.message, code),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
 /// Error type for Twilio client operations
@@ -17,7 +198,7 @@ pub struct TwilioCall {
 pub enum TwilioError {
     RequestError(ReqwestError),
     ApiError(String),
-    StatusError(u16, String),
+    StatusError(u16, TwilioApiError),
     RetryExhausted(Box<TwilioError>),
 }
 
@@ -26,12 +207,81 @@ impl fmt::Display for TwilioError {
         match self {
             TwilioError::RequestError(err) => write!(f, "Request error: {}", err),
             TwilioError::ApiError(err) => write!(f, "API error: {}", err),
-            TwilioError::StatusError(status, msg) => write!(f, "Status {} error: {}", status, msg),
+            TwilioError::StatusError(status, err) => write!(f, "Status {} error: {}", status, err),
             TwilioError::RetryExhausted(err) => write!(f, "Retry exhausted: {}", err),
         }
     }
 }
 
+impl TwilioError {
+    /// Build an error from a failed API response, parsing Twilio's error body when present
+    async fn from_response(response: Response) -> Self {
+        let status = response.status().as_u16();
+        let retry_after = response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+        
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return TwilioError::RequestError(e),
+        };
+        
+        let mut error = serde_json::from_str::<TwilioApiError>(&body).unwrap_or(TwilioApiError {
+            code: None,
+            message: body,
+            more_info: None,
+            retry_after: None,
+        });
+        error.retry_after = retry_after;
+        
+        TwilioError::StatusError(status, error)
+    }
+    
+    /// Twilio error code, if the API returned one
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            TwilioError::StatusError(_, err) => err.code,
+            TwilioError::RetryExhausted(err) => err.code(),
+            _ => None,
+        }
+    }
+    
+    /// Whether the request may succeed if sent again
+    ///
+    /// Network failures, rate limits and server errors are retryable;
+    /// authentication and validation errors are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TwilioError::RequestError(err) => err.is_timeout() || err.is_connect() || err.is_request(),
+            TwilioError::StatusError(status, err) => {
+                *status == 429 || *status >= 500 || err.code == Some(ERROR_RATE_LIMITED)
+            },
+            TwilioError::ApiError(_) | TwilioError::RetryExhausted(_) => false,
+        }
+    }
+    
+    /// Whether Twilio rejected the credentials
+    pub fn is_authentication_error(&self) -> bool {
+        self.code() == Some(ERROR_AUTHENTICATION)
+            || matches!(self, TwilioError::StatusError(401, _))
+    }
+    
+    /// Whether Twilio rejected the destination phone number
+    pub fn is_invalid_number(&self) -> bool {
+        matches!(self.code(), Some(ERROR_INVALID_TO_NUMBER) | Some(ERROR_NOT_A_PHONE_NUMBER))
+    }
+    
+    /// Delay Twilio asked for before the next request
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            TwilioError::StatusError(_, err) => err.retry_after,
+            _ => None,
+        }
+    }
+}
+
 impl std::error::Error for TwilioError {}
 
 impl From<ReqwestError> for TwilioError {
@@ -47,10 +297,14 @@ pub struct TwilioClient {
     auth_token: String,
     region: Option<String>,
     edge: Option<String>,
+    /// Limiter outbound calls wait on before they are created
+    rate_limiter: Option<Arc<CallRateLimiter>>,
+    /// Set when the next call's slot was already taken from the limiter
+    slot_reserved: AtomicBool,
 }
 
 impl TwilioClient {
-    /// Create a new Twilio client
+    /// Create a new Twilio client, waiting on the process's call rate limiter if one is installed
     pub fn new(
         account_sid: String,
         auth_token: String,
@@ -67,9 +321,38 @@ impl TwilioClient {
             auth_token,
             region,
             edge,
+            rate_limiter: cps::installed(),
+            slot_reserved: AtomicBool::new(false),
         })
     }
     
+    /// Create a client for the tenant's own Twilio account, or the service account
+    /// when there is no tenant or it has no credentials
+    pub fn for_tenant(tenant: Option<&Tenant>, config: &TwilioConfig) -> Result<Self, TwilioError> {
+        let (account_sid, auth_token) = match tenant.and_then(|t| t.credentials.as_ref()) {
+            Some(credentials) => (credentials.account_sid.clone(), credentials.auth_token.clone()),
+            None => (config.account_sid.clone(), config.auth_token.clone()),
+        };
+        Self::new(account_sid, auth_token, config.region.clone(), config.edge.clone())
+    }
+    
+    /// Account the client acts on
+    pub fn account_sid(&self) -> &str {
+        &self.account_sid
+    }
+    
+    /// Create calls only as fast as the limiter allows
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<CallRateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+    
+    /// Create the next call without waiting, its slot having been acquired from the limiter beforehand
+    pub fn with_reserved_slot(self) -> Self {
+        self.slot_reserved.store(true, Ordering::Relaxed);
+        self
+    }
+    
     /// Get the base URL for Twilio API requests
     fn base_url(&self) -> String {
         let region_prefix = match &self.region {
@@ -103,20 +386,56 @@ impl TwilioClient {
         from: &str,
         twiml: &str,
         status_callback: &str,
+        options: &CallOptions,
     ) -> Result<TwilioCall, TwilioError> {
         let url = format!("{}/Calls.json", self.base_url());
+        if let Some(rate_limiter) = &self.rate_limiter {
+            if !self.slot_reserved.swap(false, Ordering::Relaxed) {
+                rate_limiter.acquire().await;
+            }
+        }
         debug!("Creating call to {} from {}", to, from);
         
+        let to = if is_sip_address(to) {
+            sip_uri_with_headers(to, &options.sip_headers)
+        } else {
+            to.to_string()
+        };
+        
         let mut form = HashMap::new();
-        form.insert("To", to);
+        form.insert("To", to.as_str());
         form.insert("From", from);
         form.insert("Twiml", twiml);
         form.insert("StatusCallback", status_callback);
         form.insert("StatusCallbackEvent", 
-                   "initiated answered completed busy no-answer canceled failed");
+                   "initiated ringing answered completed busy no-answer canceled failed");
         form.insert("StatusCallbackMethod", "POST");
         form.insert("Timeout", "600");
         
+        if options.record {
+            form.insert("Record", "true");
+        }
+        
+        if let Some(username) = &options.sip_auth_username {
+            form.insert("SipAuthUsername", username);
+        }
+        if let Some(password) = &options.sip_auth_password {
+            form.insert("SipAuthPassword", password);
+        }
+        if let Some(digits) = &options.send_digits {
+            form.insert("SendDigits", digits);
+        }
+        
+        if let Some(machine_detection) = &options.machine_detection {
+            form.insert("MachineDetection", machine_detection);
+            
+            if let Some(amd_callback) = &options.async_amd_status_callback {
+                form.insert("AsyncAmd", "true");
+                form.insert("AsyncAmdStatusCallback", amd_callback);
+                form.insert("AsyncAmdStatusCallbackMethod", "POST");
+            }
+        }
+        
         let response = self.client.post(&url)
             .header("Authorization", self.auth_header())
             .form(&form)
@@ -125,9 +444,9 @@ impl TwilioClient {
             
         let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("Failed to create call: {}", error_text);
-            return Err(TwilioError::StatusError(status.as_u16(), error_text));
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to create call: {}", error);
+            return Err(error);
         }
         
         let call: TwilioCall = response.json().await?;
@@ -136,12 +455,14 @@ impl TwilioClient {
     }
     
     /// Create a new outbound call with retry capability
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_call_with_retry(
         &self,
         to: &str,
         from: &str,
         twiml: &str,
         status_callback: &str,
+        options: &CallOptions,
         max_retries: usize,
         base_delay_ms: u64,
     ) -> Result<TwilioCall, TwilioError> {
@@ -149,18 +470,21 @@ impl TwilioClient {
         let mut last_error = None;
         
         while attempts <= max_retries {
-            match self.create_call(to, from, twiml, status_callback).await {
+            match self.create_call(to, from, twiml, status_callback, options).await {
                 Ok(result) => return Ok(result),
+                Err(e) if !e.is_retryable() => return Err(e),
                 Err(e) => {
                     attempts += 1;
-                    last_error = Some(e);
                     
                     if attempts <= max_retries {
-                        let delay = base_delay_ms * 2u64.pow(attempts as u32 - 1);
+                        // Back off exponentially, or longer if Twilio asked us to
+                        let backoff = Duration::from_millis(base_delay_ms * 2u64.pow(attempts as u32 - 1));
+                        let delay = e.retry_after().map_or(backoff, |after| after.max(backoff));
                         debug!("Retrying Twilio call creation, attempt {}/{} after {}ms", 
-                              attempts, max_retries, delay);
-                        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+                              attempts, max_retries, delay.as_millis());
+                        tokio::time::sleep(delay).await;
                     }
+                    last_error = Some(e);
                 }
             }
         }
@@ -186,9 +510,9 @@ impl TwilioClient {
             
         let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("Failed to update call {}: {}", call_sid, error_text);
-            return Err(TwilioError::StatusError(status.as_u16(), error_text));
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to update call {}: {}", call_sid, error);
+            return Err(error);
         }
         
         debug!("Successfully updated call {}", call_sid);
@@ -209,16 +533,19 @@ impl TwilioClient {
         while attempts <= max_retries {
             match self.update_call(call_sid, twiml).await {
                 Ok(result) => return Ok(result),
+                Err(e) if !e.is_retryable() => return Err(e),
                 Err(e) => {
                     attempts += 1;
-                    last_error = Some(e);
                     
                     if attempts <= max_retries {
-                        let delay = base_delay_ms * 2u64.pow(attempts as u32 - 1);
+                        // Back off exponentially, or longer if Twilio asked us to
+                        let backoff = Duration::from_millis(base_delay_ms * 2u64.pow(attempts as u32 - 1));
+                        let delay = e.retry_after().map_or(backoff, |after| after.max(backoff));
                         debug!("Retrying call update, attempt {}/{} after {}ms", 
-                              attempts, max_retries, delay);
-                        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+                              attempts, max_retries, delay.as_millis());
+                        tokio::time::sleep(delay).await;
                     }
+                    last_error = Some(e);
                 }
             }
         }
@@ -228,6 +555,258 @@ impl TwilioClient {
         )))
     }
     
+    /// Start recording an in-progress call
+    pub async fn start_recording(&self, call_sid: &str) -> Result<(), TwilioError> {
+        let url = format!("{}/Calls/{}/Recordings.json", self.base_url(), call_sid);
+        debug!("Starting recording for call {}", call_sid);
+        
+        let mut form = HashMap::new();
+        form.insert("RecordingChannels", "dual");
+        
+        let response = self.client.post(&url)
+            .header("Authorization", self.auth_header())
+            .form(&form)
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to start recording for call {}: {}", call_sid, error);
+            return Err(error);
+        }
+        
+        info!("Started recording call {}", call_sid);
+        Ok(())
+    }
+    
+    /// Send an SMS, reporting delivery updates to `status_callback`
+    pub async fn send_message(
+        &self,
+        to: &str,
+        from: &str,
+        body: &str,
+        status_callback: &str,
+    ) -> Result<TwilioMessage, TwilioError> {
+        let url = format!("{}/Messages.json", self.base_url());
+        debug!("Sending message to {} from {}", to, from);
+        
+        let mut form = HashMap::new();
+        form.insert("To", to);
+        form.insert("From", from);
+        form.insert("Body", body);
+        form.insert("StatusCallback", status_callback);
+        
+        let response = self.client.post(&url)
+            .header("Authorization", self.auth_header())
+            .form(&form)
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to send message to {}: {}", to, error);
+            return Err(error);
+        }
+        
+        let message: TwilioMessage = response.json().await?;
+        info!("Sent message with SID: {}", message.sid);
+        Ok(message)
+    }
+    
+    /// Dial a supervisor into a conference to coach one of its participants
+    ///
+    /// The supervisor hears every participant but only `call_sid_to_coach` hears them.
+    /// `conference` may be the conference's SID or its friendly name.
+    pub async fn add_coach(
+        &self,
+        conference: &str,
+        to: &str,
+        from: &str,
+        call_sid_to_coach: &str,
+        status_callback: &str,
+    ) -> Result<TwilioParticipant, TwilioError> {
+        let url = format!("{}/Conferences/{}/Participants.json", self.base_url(), urlencoding::encode(conference));
+        debug!("Adding coach {} to conference {}", to, conference);
+        
+        let mut form = HashMap::new();
+        form.insert("To", to);
+        form.insert("From", from);
+        form.insert("Coaching", "true");
+        form.insert("CallSidToCoach", call_sid_to_coach);
+        form.insert("Beep", "false");
+        form.insert("EndConferenceOnExit", "false");
+        form.insert("StatusCallback", status_callback);
+        form.insert("StatusCallbackEvent", "answered completed");
+        
+        let response = self.client.post(&url)
+            .header("Authorization", self.auth_header())
+            .form(&form)
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to add coach {} to conference {}: {}", to, conference, error);
+            return Err(error);
+        }
+        
+        let participant: TwilioParticipant = response.json().await?;
+        info!("Added coach to conference {} as call {}", conference, participant.call_sid);
+        Ok(participant)
+    }
+    
+    /// List the recordings of a call
+    pub async fn list_recordings(&self, call_sid: &str) -> Result<Vec<TwilioRecording>, TwilioError> {
+        let url = format!("{}/Calls/{}/Recordings.json", self.base_url(), call_sid);
+        debug!("Listing recordings for call {}", call_sid);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to list recordings for call {}: {}", call_sid, error);
+            return Err(error);
+        }
+        
+        let mut result: serde_json::Value = response.json().await?;
+        let recordings = serde_json::from_value(result["recordings"].take())
+            .map_err(|e| TwilioError::ApiError(format!("Invalid recordings response: {}", e)))?;
+        Ok(recordings)
+    }
+    
+    /// List the transcriptions of a recording
+    pub async fn list_transcriptions(&self, recording_sid: &str) -> Result<Vec<TwilioTranscription>, TwilioError> {
+        let url = format!("{}/Recordings/{}/Transcriptions.json", self.base_url(), recording_sid);
+        debug!("Listing transcriptions for recording {}", recording_sid);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to list transcriptions for recording {}: {}", recording_sid, error);
+            return Err(error);
+        }
+        
+        let mut result: serde_json::Value = response.json().await?;
+        let transcriptions = serde_json::from_value(result["transcriptions"].take())
+            .map_err(|e| TwilioError::ApiError(format!("Invalid transcriptions response: {}", e)))?;
+        Ok(transcriptions)
+    }
+    
+    /// Download a recording's audio as `mp3` or `wav`
+    pub async fn download_recording(&self, recording_sid: &str, format: &str) -> Result<Vec<u8>, TwilioError> {
+        let url = format!("{}/Recordings/{}.{}", self.base_url(), recording_sid, format);
+        debug!("Downloading recording {} as {}", recording_sid, format);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to download recording {}: {}", recording_sid, error);
+            return Err(error);
+        }
+        
+        Ok(response.bytes().await?.to_vec())
+    }
+    
+    /// Fetch the account, confirming the credentials are accepted
+    pub async fn fetch_account(&self, timeout: Duration) -> Result<(), TwilioError> {
+        let url = format!("{}.json", self.base_url());
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .timeout(timeout)
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(TwilioError::from_response(response).await);
+        }
+        
+        Ok(())
+    }
+    
+    /// Fetch a call and its price
+    pub async fn fetch_call(&self, call_sid: &str) -> Result<TwilioCallDetails, TwilioError> {
+        let url = format!("{}/Calls/{}.json", self.base_url(), call_sid);
+        debug!("Fetching call {}", call_sid);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to fetch call {}: {}", call_sid, error);
+            return Err(error);
+        }
+        
+        let call: TwilioCallDetails = response.json().await?;
+        Ok(call)
+    }
+    
+    /// Fetch a queue and its current statistics
+    pub async fn get_queue(&self, queue_sid: &str) -> Result<TwilioQueue, TwilioError> {
+        let url = format!("{}/Queues/{}.json", self.base_url(), queue_sid);
+        debug!("Fetching queue {}", queue_sid);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to fetch queue {}: {}", queue_sid, error);
+            return Err(error);
+        }
+        
+        let queue: TwilioQueue = response.json().await?;
+        Ok(queue)
+    }
+    
+    /// Look up carrier, line type and caller name for a phone number
+    pub async fn lookup_number(&self, phone_number: &str) -> Result<NumberLookup, TwilioError> {
+        let url = format!(
+            "https://lookups.twilio.com/v2/PhoneNumbers/{}?Fields=line_type_intelligence,caller_name",
+            urlencoding::encode(phone_number)
+        );
+        debug!("Looking up phone number {}", phone_number);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to look up phone number {}: {}", phone_number, error);
+            return Err(error);
+        }
+        
+        let result: serde_json::Value = response.json().await?;
+        Ok(NumberLookup::from_response(&result))
+    }
+    
     /// List phone numbers for a specific phone number
     pub async fn list_phone_numbers(&self, phone_number: &str) -> Result<Vec<serde_json::Value>, TwilioError> {
         let url = format!("{}/IncomingPhoneNumbers.json?PhoneNumber={}", 
@@ -241,9 +820,9 @@ impl TwilioClient {
             
         let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("Failed to list phone numbers: {}", error_text);
-            return Err(TwilioError::StatusError(status.as_u16(), error_text));
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to list phone numbers: {}", error);
+            return Err(error);
         }
         
         let result: serde_json::Value = response.json().await?;
@@ -254,6 +833,64 @@ impl TwilioClient {
         Ok(numbers)
     }
     
+    /// List the account's verified outgoing caller IDs matching a phone number
+    pub async fn list_outgoing_caller_ids(&self, phone_number: &str) -> Result<Vec<serde_json::Value>, TwilioError> {
+        let url = format!("{}/OutgoingCallerIds.json?PhoneNumber={}", 
+                         self.base_url(), urlencoding::encode(phone_number));
+        debug!("Listing outgoing caller IDs for {}", phone_number);
+        
+        let response = self.client.get(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to list outgoing caller IDs: {}", error);
+            return Err(error);
+        }
+        
+        let result: serde_json::Value = response.json().await?;
+        let caller_ids = result["outgoing_caller_ids"].as_array()
+            .ok_or_else(|| TwilioError::ApiError("No outgoing caller IDs found".to_string()))?
+            .clone();
+            
+        Ok(caller_ids)
+    }
+    
+    /// Purchase a phone number and point its voice webhook at the given URL
+    pub async fn purchase_phone_number(
+        &self,
+        phone_number: &str,
+        voice_url: &str
+    ) -> Result<serde_json::Value, TwilioError> {
+        let url = format!("{}/IncomingPhoneNumbers.json", self.base_url());
+        debug!("Purchasing phone number {}", phone_number);
+        
+        let mut form = HashMap::new();
+        form.insert("PhoneNumber", phone_number);
+        form.insert("VoiceUrl", voice_url);
+        form.insert("VoiceMethod", "POST");
+        
+        let response = self.client.post(&url)
+            .header("Authorization", self.auth_header())
+            .form(&form)
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to purchase phone number: {}", error);
+            return Err(error);
+        }
+        
+        let result: serde_json::Value = response.json().await?;
+        info!("Purchased phone number {}", phone_number);
+        Ok(result)
+    }
+    
     /// Update phone number configuration
     pub async fn update_phone_number(
         &self, 
@@ -275,13 +912,34 @@ impl TwilioClient {
             
         let status = response.status();
         if !status.is_success() {
-            let error_text = response.text().await?;
-            error!("Failed to update phone number: {}", error_text);
-            return Err(TwilioError::StatusError(status.as_u16(), error_text));
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to update phone number: {}", error);
+            return Err(error);
         }
         
         let result: serde_json::Value = response.json().await?;
         info!("Updated phone number {} with voice URL {}", phone_number_sid, voice_url);
         Ok(result)
     }
+    
+    /// Release a phone number from the account
+    pub async fn release_phone_number(&self, phone_number_sid: &str) -> Result<(), TwilioError> {
+        let url = format!("{}/IncomingPhoneNumbers/{}.json", self.base_url(), phone_number_sid);
+        debug!("Releasing phone number {}", phone_number_sid);
+        
+        let response = self.client.delete(&url)
+            .header("Authorization", self.auth_header())
+            .send()
+            .await?;
+            
+        let status = response.status();
+        if !status.is_success() {
+            let error = TwilioError::from_response(response).await;
+            error!("Failed to release phone number: {}", error);
+            return Err(error);
+        }
+        
+        info!("Released phone number {}", phone_number_sid);
+        Ok(())
+    }
 }
\ No newline at end of file
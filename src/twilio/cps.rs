@@ -0,0 +1,140 @@
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
+use tokio::sync::Mutex;
+
+use crate::config::DialerConfig;
+use crate::redis_layer::RedisLayer;
+
+/// Reserve a token from a bucket shared by every replica, returning how long to wait for it
+///
+/// The bucket may go negative: each reservation queues behind the ones before it.
+const RESERVE_SCRIPT: &str = r#"
+local rate = tonumber(ARGV[1])
+local burst = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or burst
+local updated = tonumber(state[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated) * rate / 1000)
+tokens = tokens - 1
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(now))
+redis.call('PEXPIRE', KEYS[1], math.ceil((burst - tokens) * 1000 / rate) + 1000)
+if tokens >= 0 then
+    return 0
+end
+return math.ceil(-tokens * 1000 / rate)
+"#;
+
+/// Limiter every Twilio client of the process waits on before creating a call
+static INSTALLED: OnceLock<Arc<CallRateLimiter>> = OnceLock::new();
+
+/// Make `limiter` the one every Twilio client created afterwards waits on
+pub fn install(limiter: Arc<CallRateLimiter>) {
+    if INSTALLED.set(limiter).is_err() {
+        debug!("A call rate limiter is already installed");
+    }
+}
+
+/// Limiter installed for the process, if any
+pub fn installed() -> Option<Arc<CallRateLimiter>> {
+    INSTALLED.get().cloned()
+}
+
+/// Token bucket of the local replica
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Calls-per-second limiter for outbound calls
+///
+/// Twilio rejects calls created faster than the account's CPS with 429s. Dial
+/// attempts wait here for a slot instead, in the order they asked for one. With
+/// `DIALER_CPS_REDIS` the bucket is shared by every replica dialing for the account.
+pub struct CallRateLimiter {
+    calls_per_second: f64,
+    burst: u32,
+    /// Redis layer and bucket key, when shared
+    redis: Option<(RedisLayer, String)>,
+    /// Used without Redis, and when Redis cannot be reached
+    local: Mutex<Bucket>,
+}
+
+impl CallRateLimiter {
+    /// Create a limiter for an account, sharing its bucket through Redis if configured
+    pub fn new(config: &DialerConfig, account_sid: &str, redis: Option<RedisLayer>) -> Self {
+        let redis = redis.filter(|_| config.use_redis).map(|redis| {
+            let key = redis.key(&["cps", account_sid]);
+            (redis, key)
+        });
+        if config.calls_per_second > 0.0 {
+            info!(
+                "Limiting outbound calls to {} per second ({})",
+                config.calls_per_second,
+                if redis.is_some() { "shared" } else { "local" }
+            );
+        }
+
+        CallRateLimiter {
+            calls_per_second: config.calls_per_second,
+            burst: config.burst,
+            redis,
+            local: Mutex::new(Bucket {
+                tokens: config.burst as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Wait until the next call may be created
+    pub async fn acquire(&self) {
+        if self.calls_per_second <= 0.0 {
+            return;
+        }
+
+        let wait = match self.reserve_shared().await {
+            Some(wait) => wait,
+            None => self.reserve_local().await,
+        };
+        if !wait.is_zero() {
+            debug!("Waiting {}ms for an outbound call slot", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Reserve a token from the shared bucket; `None` without Redis or when it fails
+    async fn reserve_shared(&self) -> Option<Duration> {
+        let (redis, key) = self.redis.as_ref()?;
+
+        let result: Result<u64, redis::RedisError> = redis::Script::new(RESERVE_SCRIPT)
+            .key(key)
+            .arg(self.calls_per_second)
+            .arg(self.burst)
+            .arg(chrono::Utc::now().timestamp_millis())
+            .invoke_async(&mut redis.connection())
+            .await;
+
+        match result {
+            Ok(wait_ms) => Some(Duration::from_millis(wait_ms)),
+            Err(e) => {
+                warn!("Failed to reserve a shared call slot, limiting locally: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn reserve_local(&self) -> Duration {
+        let mut bucket = self.local.lock().await;
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.calls_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst as f64) - 1.0;
+        bucket.updated = now;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.calls_per_second)
+        }
+    }
+}
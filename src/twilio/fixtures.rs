@@ -0,0 +1,301 @@
+use std::io::Cursor;
+use std::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use rocket::data::{self, Data, FromData, Limits};
+use rocket::fairing::AdHoc;
+use rocket::form::{self, Form, FromForm, ValueField};
+use rocket::http::{RawStr, Status};
+use rocket::outcome::Outcome;
+use rocket::Request;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::{RedactionConfig, SharedConfig};
+
+/// Form fields holding the caller's speech, redacted before recording
+const SPEECH_FIELDS: [&str; 2] = ["SpeechResult", "UnstableSpeechResult"];
+
+/// URL query parameters that differ between runs of the same call, masked when comparing TwiML
+const VOLATILE_PARAMS: [&str; 3] = ["sig", "signature", "expires"];
+
+/// A webhook Twilio sent and the TwiML it was answered with, one line of a fixture
+///
+/// Fixtures are JSON Lines files named after the call SID, replayed in order by
+/// the `replay` binary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedWebhook {
+    pub received_at: DateTime<Utc>,
+    /// Path and query the webhook was sent to, e.g. `/twilio/v2/incoming_callback`
+    pub uri: String,
+    /// URL-encoded form body as Twilio sent it
+    pub form: String,
+    pub status: u16,
+    pub twiml: String,
+    /// Callback URL prefix in the recorded TwiML, swapped for the local one on replay
+    pub webhook_url: String,
+}
+
+/// Raw body of a request's Twilio callback form
+struct RawForm(Option<String>);
+
+/// Percent-decoded fields of a request's Twilio callback form
+struct DecodedForm(Vec<(String, String)>);
+
+/// Twilio callback form that keeps the body as sent
+///
+/// Parses like Rocket's `Form`, so validation failures still answer 422. The
+/// raw body is what the fixture recorder writes to disk.
+pub struct TwilioForm<T>(T);
+
+impl<T> TwilioForm<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for TwilioForm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for TwilioForm<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: FromForm<'r>> FromData<'r> for TwilioForm<T> {
+    type Error = form::Errors<'r>;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        if !request.content_type().is_some_and(|ct| ct.is_form()) {
+            return Outcome::Forward((data, Status::UnsupportedMediaType));
+        }
+
+        let limit = request.limits().get("form").unwrap_or(Limits::FORM);
+        let body = match data.open(limit).into_string().await {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => {
+                let error = form::Error::from((None, Some(limit.as_u64())));
+                return Outcome::Error((error.status(), error.into()));
+            },
+            Err(e) => {
+                let error = form::Error::from(e);
+                return Outcome::Error((error.status(), error.into()));
+            },
+        };
+
+        let body = request.local_cache(|| RawForm(Some(body)));
+        // Decoded fields live in the request cache so the parsed form can borrow them
+        let fields = request.local_cache(|| DecodedForm(
+            body.0.as_deref().unwrap_or_default()
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+                .map(|(name, value)| (
+                    RawStr::new(name).url_decode_lossy().into_owned(),
+                    RawStr::new(value).url_decode_lossy().into_owned(),
+                ))
+                .collect()
+        ));
+        let fields = fields.0.iter().map(|(name, value)| ValueField::from((name.as_str(), value.as_str())));
+        match Form::<T>::parse_iter(fields) {
+            Ok(value) => Outcome::Success(TwilioForm(value)),
+            Err(e) => Outcome::Error((e.status(), e)),
+        }
+    }
+}
+
+/// Response fairing writing Twilio webhooks and their TwiML to fixture files
+///
+/// Only runs with `RECORD_WEBHOOKS_DIR` set. Webhooks are grouped by the call
+/// they concern, so a call's fixture holds every callback about it.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_response("Webhook recorder", |request, response| Box::pin(async move {
+        let Some(config) = request.rocket().state::<SharedConfig>().map(SharedConfig::current) else {
+            return;
+        };
+        let Some(dir) = &config.fixtures.record_dir else {
+            return;
+        };
+        let RawForm(Some(form)) = request.local_cache(|| RawForm(None)) else {
+            return;
+        };
+
+        // Callbacks about another leg name the call in the query
+        let call_sid = request.query_value::<&str>("call_sid")
+            .and_then(Result::ok)
+            .or_else(|| form_value(form, "CallSid"))
+            .unwrap_or_default()
+            .to_string();
+        if call_sid.is_empty() || !call_sid.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            debug!("Not recording webhook to {} without a call SID", request.uri());
+            return;
+        }
+
+        let twiml = match response.body_mut().to_string().await {
+            Ok(twiml) => twiml,
+            Err(e) => {
+                warn!("Failed to read TwiML response for recording: {}", e);
+                return;
+            }
+        };
+        response.set_sized_body(twiml.len(), Cursor::new(twiml.clone()));
+
+        let webhook = RecordedWebhook {
+            received_at: Utc::now(),
+            uri: request.uri().to_string(),
+            form: redact_speech(form, &config.redaction),
+            status: response.status().code,
+            twiml,
+            webhook_url: config.twilio.callback_url(""),
+        };
+        if let Err(e) = append(dir, &call_sid, &webhook).await {
+            warn!("Failed to record webhook for call {}: {}", call_sid, e);
+        }
+    }))
+}
+
+/// Value of a field in a URL-encoded form, if it needs no decoding
+fn form_value<'a>(form: &'a str, name: &str) -> Option<&'a str> {
+    form.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Form with the caller's speech redacted, other fields as sent
+fn redact_speech(form: &str, redaction: &RedactionConfig) -> String {
+    if !redaction.enabled {
+        return form.to_string();
+    }
+    form.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if SPEECH_FIELDS.contains(&key) => {
+                let value = urlencoding::decode(&value.replace('+', " "))
+                    .map(|v| v.into_owned())
+                    .unwrap_or_default();
+                format!("{}={}", key, urlencoding::encode(&redaction.redacted(&value)))
+            },
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Append a webhook to its call's fixture file
+async fn append(dir: &str, call_sid: &str, webhook: &RecordedWebhook) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let mut line = serde_json::to_string(webhook)?;
+    line.push('\n');
+    let path = std::path::Path::new(dir).join(format!("{}.jsonl", call_sid));
+    let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    file.write_all(line.as_bytes()).await
+}
+
+/// TwiML reduced to its structure, for comparing a replayed answer with the recording
+///
+/// Yields one line per element with its attributes sorted by name, one per
+/// closing tag and one per text node; the XML declaration, attribute order,
+/// self-closing syntax and whitespace between elements do not matter. The
+/// callback URL prefix becomes `{webhook_url}` and signed URL parameters such
+/// as `sig` and `expires` are masked, so answers that differ only in the host
+/// they were served from or in their signatures compare equal.
+pub fn twiml_structure(twiml: &str, webhook_url: &str) -> Vec<String> {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let token = TOKEN.get_or_init(|| Regex::new(r"<[^>]*>|[^<]+").unwrap());
+    let attribute = ATTRIBUTE.get_or_init(|| Regex::new(r#"([\w:.-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+    let mut lines = Vec::new();
+    for token in token.find_iter(twiml).map(|m| m.as_str()) {
+        let Some(tag) = token.strip_prefix('<').and_then(|t| t.strip_suffix('>')) else {
+            let text = token.trim();
+            if !text.is_empty() {
+                lines.push(mask_volatile(text, webhook_url));
+            }
+            continue;
+        };
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            lines.push(format!("</{}>", name.trim()));
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        let mut attributes: Vec<String> = attribute.captures_iter(&tag[name.len()..])
+            .map(|c| format!("{}=\"{}\"", &c[1], mask_volatile(&c[2], webhook_url)))
+            .collect();
+        attributes.sort();
+        lines.push(format!("<{}>", std::iter::once(name.to_string()).chain(attributes).collect::<Vec<_>>().join(" ")));
+        if self_closing {
+            lines.push(format!("</{}>", name));
+        }
+    }
+    lines
+}
+
+/// Attribute value or text with the callback URL prefix and volatile parameters masked
+fn mask_volatile(value: &str, webhook_url: &str) -> String {
+    static PARAMS: OnceLock<Regex> = OnceLock::new();
+    let params = PARAMS.get_or_init(|| Regex::new(&format!(
+        r"((?:[?&]|&amp;)(?:{})=)[^&\s<]*",
+        VOLATILE_PARAMS.join("|"),
+    )).unwrap());
+
+    let value = if webhook_url.is_empty() {
+        value.to_string()
+    } else {
+        value.replace(webhook_url, "{webhook_url}")
+    };
+    params.replace_all(&value, "${1}*").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_formatting_and_attribute_order() {
+        let recorded = r#"<?xml version="1.0" encoding="UTF-8"?><Response><Gather input="speech" action="https://a.example/twilio/v2/cb"><Say>Hi</Say></Gather><Pause length="1"/></Response>"#;
+        let replayed = "<Response>\n  <Gather action=\"http://localhost:8000/twilio/v2/cb\" input=\"speech\">\n    <Say>Hi</Say>\n  </Gather>\n  <Pause length=\"1\"></Pause>\n</Response>";
+
+        assert_eq!(
+            twiml_structure(recorded, "https://a.example/twilio/v2"),
+            twiml_structure(replayed, "http://localhost:8000/twilio/v2"),
+        );
+    }
+
+    #[test]
+    fn masks_signed_url_parameters() {
+        let recorded = "<Response><Play>https://a.example/audio/nova?text=Hi&amp;sig=abc</Play><Play>https://a.example/r/1?expires=1&amp;signature=x</Play></Response>";
+        let replayed = "<Response><Play>https://a.example/audio/nova?text=Hi&amp;sig=def</Play><Play>https://a.example/r/1?expires=2&amp;signature=y</Play></Response>";
+
+        assert_eq!(twiml_structure(recorded, ""), twiml_structure(replayed, ""));
+    }
+
+    #[test]
+    fn detects_changed_answers() {
+        let recorded = r#"<Response><Say voice="alice">Hi</Say></Response>"#;
+
+        assert_ne!(twiml_structure(recorded, ""), twiml_structure(r#"<Response><Say voice="bob">Hi</Say></Response>"#, ""));
+        assert_ne!(twiml_structure(recorded, ""), twiml_structure(r#"<Response><Say voice="alice">Bye</Say></Response>"#, ""));
+        assert_ne!(twiml_structure(recorded, ""), twiml_structure(r#"<Response><Say voice="alice">Hi</Say><Hangup/></Response>"#, ""));
+        assert_ne!(
+            twiml_structure(r#"<Response><Play>https://a.example/audio?text=Hi&amp;sig=a</Play></Response>"#, ""),
+            twiml_structure(r#"<Response><Play>https://a.example/audio?text=Bye&amp;sig=a</Play></Response>"#, ""),
+        );
+    }
+}
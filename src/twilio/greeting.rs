@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use log::warn;
+use tokio::sync::watch;
+
+use crate::twilio::twiml::TwiML;
+
+/// Greetings still being prepared while callers hear the warm-up earcon
+pub struct PendingGreetings {
+    pending: DashMap<String, (Instant, watch::Receiver<Option<TwiML>>)>,
+}
+
+/// Greetings nobody collected are dropped after this long
+const PENDING_GREETING_TTL: Duration = Duration::from_secs(60);
+
+impl PendingGreetings {
+    /// Create an empty store
+    pub fn new() -> Self {
+        PendingGreetings {
+            pending: DashMap::new(),
+        }
+    }
+
+    /// Register a call whose greeting is being prepared, returning the sender for its TwiML
+    pub fn start(&self, call_sid: &str) -> watch::Sender<Option<TwiML>> {
+        // Callers who hung up during the earcon never collect their greeting
+        self.pending.retain(|_, (created, _)| created.elapsed() < PENDING_GREETING_TTL);
+
+        let (tx, rx) = watch::channel(None);
+        self.pending.insert(call_sid.to_string(), (Instant::now(), rx));
+        tx
+    }
+
+    /// Wait up to `timeout` for a call's greeting TwiML
+    ///
+    /// The greeting stays available so retried requests get the same response.
+    pub async fn wait(&self, call_sid: &str, timeout: Duration) -> Option<TwiML> {
+        let mut rx = self.pending.get(call_sid)?.1.clone();
+
+        let result = tokio::time::timeout(timeout, rx.wait_for(|twiml| twiml.is_some())).await;
+        match result {
+            Ok(Ok(twiml)) => twiml.clone(),
+            Ok(Err(_)) => None,
+            Err(_) => {
+                warn!("Timed out preparing the greeting for call {}", call_sid);
+                None
+            }
+        }
+    }
+}
+
+impl Default for PendingGreetings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
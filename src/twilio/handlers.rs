@@ -1,216 +1,823 @@
 use std::sync::Arc;
-use log::{debug, error, info};
-use rocket::{State, post, serde::json::Json, form::Form, http::Status};
-use crate::utils::Xml;
+use std::time::Duration;
+use log::{debug, error, info, warn};
+use rocket::{State, get, post, serde::json::Json, http::Status, response::status::{Accepted, Custom}};
+use crate::utils::phone;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
 use std::collections::HashMap;
 
-use crate::bot::backend::BackendClient;
-use crate::bot::session::{MessageType, Session, SessionStore};
-use crate::config::Config;
-use crate::twilio::client::TwilioClient;
-use crate::twilio::twiml::{create_hangup_response, create_voice_response, ends_with_sentence_punctuation};
-use crate::bot::ws_client::WebSocketManager;
+use crate::api::{ErrorResponse, api_error};
+use crate::api::auth::CallsWriteAuth;
+use crate::bot::analytics_tap::{analytics_tap, TapTurn};
+use crate::bot::backend::{BackendError, BackendPool, RunStream, with_retry, with_retry_within};
+use crate::bot::message_queue::MessageSender;
+use crate::bot::events::SessionEventKind;
+use crate::bot::experiments::{EXPERIMENT_METADATA_KEY, assigned_variant, experiment_metrics};
+use crate::bot::callflow::{CallFlow, CallState};
+use crate::bot::cdr::{normalize_tag, CallRecord, CallSummary, CdrStore, HangupSource, TRANSFER_METADATA_KEY};
+use crate::bot::caller_history::{CallerHistory, PreviousCall};
+use crate::bot::recording::{RecordingDecision, RECORDING_METADATA_KEY};
+use crate::bot::{postprocess, speech};
+use crate::bot::screening::{is_anonymous, screen_call};
+use crate::bot::result_callback::{send_call_result, validate_callback_url};
+use crate::bot::menu::{MenuTimeoutAction, SelectionInput};
+use crate::bot::pacing::gather_timing;
+use crate::bot::payload::{Handoff, ResponseKind, RunResponse, SmsRequest};
+use crate::bot::session::{CallDetails, CallerSignals, MessageType, Session, SessionActivity, SessionStore, SilencePeriod, TurnGuard};
+use crate::config::{Config, CurrentConfig, OutageAction, ScreeningAction, SpeechSettings, TtsDelivery, TwilioConfig, is_supported_speech_model};
+use crate::audit::{AuditEntry, AuditLog, TurnInput};
+use crate::dead_letter::{DeadLetterKind, DeadLetterStore};
+use crate::drain::Drain;
+use crate::twilio::agent_pool::agent_pool;
+use crate::twilio::caller_id::CallerIds;
+use crate::twilio::cps::CallRateLimiter;
+use crate::twilio::call_jobs::{CallJob, CallJobStore};
+use crate::twilio::client::{CallOptions, MAX_SEND_DIGITS_LENGTH, TwilioClient, is_sip_address, is_valid_send_digits};
+use crate::twilio::fixtures::TwilioForm;
+use crate::twilio::greeting::PendingGreetings;
+use crate::twilio::proxy::ExternalUrl;
+use crate::twilio::self_test::SelfTestCall;
+use crate::twilio::idempotency::{IdempotencyToken, ReplayCache};
+use crate::twilio::scheduler::{CallScheduler, RetryPolicy};
+use crate::twilio::synthesis::SynthesizedVoice;
+use crate::twilio::validation::{MAX_ADDRESS_LENGTH, MAX_FIELD_LENGTH, MAX_TEXT_LENGTH, MAX_URL_LENGTH, max_length, sid};
+use crate::twilio::twiml::{SAY_CHUNK_BREAK, TwiML, create_after_hours_response, create_call_start_response, create_enqueue_response, create_escalation_response, create_escalation_whisper_response, create_hangup_response, create_hold_response, create_keepalive_response, create_menu_response, create_outage_transfer_response, create_outage_voicemail_response, create_reject_response, create_queue_poll_response, create_queue_wait_response, create_stream_hold_response, create_survey_response, create_takeover_response, create_transfer_response, create_transfer_voicemail_response, create_voice_response, create_turn_response, create_turn_wait_response, create_voicemail_response, create_warmup_response, merge_hints};
+use crate::bot::ws_client::{CallRequest, WebSocketManager};
+use crate::i18n::{MessageCatalog, Phrase};
+use crate::replication::{ReplicaState, SessionReplicator};
+use crate::tenant::{Tenant, TenantStore};
+use crate::twilio::billing::start_call_price_fetch;
 
 /// Form data for Twilio webhook callbacks
+///
+/// Fields are checked against Twilio's formats and bounded in length; a
+/// malformed callback is rejected with 400.
 #[derive(FromForm, Debug)]
 pub struct TwilioCallbackForm {
-    #[field(name = "CallSid")]
+    #[field(name = "CallSid", validate = sid(&["CA"]))]
     call_sid: Option<String>,
     
-    #[field(name = "CallStatus")]
+    #[field(name = "CallStatus", validate = max_length(MAX_FIELD_LENGTH))]
     call_status: Option<String>,
     
-    #[field(name = "From")]
+    #[field(name = "From", validate = max_length(MAX_ADDRESS_LENGTH))]
     from_number: Option<String>,
     
-    #[field(name = "SpeechResult")]
+    #[field(name = "FromCountry", validate = max_length(MAX_FIELD_LENGTH))]
+    from_country: Option<String>,
+    
+    #[field(name = "To", validate = max_length(MAX_ADDRESS_LENGTH))]
+    to_number: Option<String>,
+    
+    #[field(name = "SpeechResult", validate = max_length(MAX_TEXT_LENGTH))]
     speech_result: Option<String>,
     
-    #[field(name = "UnstableSpeechResult")]
+    #[field(name = "UnstableSpeechResult", validate = max_length(MAX_TEXT_LENGTH))]
     unstable_speech_result: Option<String>,
+    
+    #[field(name = "Confidence")]
+    confidence: Option<f64>,
+    
+    #[field(name = "AnsweredBy", validate = max_length(MAX_FIELD_LENGTH))]
+    answered_by: Option<String>,
+    
+    #[field(name = "Digits", validate = max_length(MAX_FIELD_LENGTH))]
+    digits: Option<String>,
+    
+    #[field(name = "CallDuration")]
+    call_duration: Option<u64>,
+    
+    #[field(name = "SequenceNumber", validate = max_length(MAX_FIELD_LENGTH))]
+    sequence_number: Option<String>,
+    
+    #[field(name = "Timestamp", validate = max_length(MAX_FIELD_LENGTH))]
+    timestamp: Option<String>,
+    
+    #[field(name = "Direction", validate = max_length(MAX_FIELD_LENGTH))]
+    direction: Option<String>,
+}
+
+impl TwilioCallbackForm {
+    /// Call fields reported by the webhook
+    fn call_details(&self) -> CallDetails {
+        CallDetails {
+            direction: self.direction.clone(),
+            to_number: self.to_number.clone().filter(|to| !to.is_empty()),
+            answered_by: self.answered_by.clone(),
+            call_duration: self.call_duration,
+        }
+    }
+}
+
+/// Form data for Twilio queue callbacks
+#[derive(FromForm, Debug)]
+pub struct QueueCallbackForm {
+    #[field(name = "CallSid", validate = sid(&["CA"]))]
+    call_sid: Option<String>,
+    
+    #[field(name = "QueueSid", validate = sid(&["QU"]))]
+    queue_sid: Option<String>,
+    
+    #[field(name = "QueueResult", validate = max_length(MAX_FIELD_LENGTH))]
+    queue_result: Option<String>,
+    
+    #[field(name = "QueueTime")]
+    queue_time: Option<u64>,
+    
+    #[field(name = "DequeingCallSid", validate = sid(&["CA"]))]
+    dequeuing_call_sid: Option<String>,
+}
+
+/// Form data for Twilio message status callbacks
+#[derive(FromForm, Debug)]
+pub struct MessageStatusForm {
+    #[field(name = "MessageSid", validate = sid(&["SM", "MM"]))]
+    message_sid: Option<String>,
+    
+    #[field(name = "MessageStatus", validate = max_length(MAX_FIELD_LENGTH))]
+    message_status: Option<String>,
+    
+    #[field(name = "ErrorCode", validate = max_length(MAX_FIELD_LENGTH))]
+    error_code: Option<String>,
+}
+
+/// Form data for Twilio callbacks after a transfer
+#[derive(FromForm, Debug)]
+pub struct TransferCallbackForm {
+    #[field(name = "CallSid", validate = sid(&["CA"]))]
+    call_sid: Option<String>,
+    
+    #[field(name = "DialCallStatus", validate = max_length(MAX_FIELD_LENGTH))]
+    dial_call_status: Option<String>,
+    
+    #[field(name = "DialCallDuration")]
+    dial_call_duration: Option<u64>,
+    
+    #[field(name = "RecordingUrl", validate = max_length(MAX_URL_LENGTH))]
+    recording_url: Option<String>,
+    
+    #[field(name = "RecordingDuration")]
+    recording_duration: Option<u64>,
+    
+    #[field(name = "To", validate = max_length(MAX_ADDRESS_LENGTH))]
+    to_number: Option<String>,
+    
+    #[field(name = "Digits", validate = max_length(MAX_FIELD_LENGTH))]
+    digits: Option<String>,
+    
+    #[field(name = "SpeechResult", validate = max_length(MAX_TEXT_LENGTH))]
+    speech_result: Option<String>,
 }
 
 /// Request for making a new outbound call
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct MakeCallRequest {
     pub to_number: String,
+    /// Caller ID for the call; must be owned or verified on the Twilio account
+    pub from_number: Option<String>,
+    /// Tenant placing the call, whose default caller ID is used without `from_number`
+    pub tenant_id: Option<String>,
+    #[serde(alias = "kwargs")]
     pub env_info: Option<serde_json::Value>,
+    /// Message left after the beep if the call reaches voicemail (text or audio URL)
+    pub voicemail_message: Option<String>,
+    /// URL that receives a signed call result when the call ends
+    pub callback_url: Option<String>,
+    /// Speech model for the call, overriding `SPEECH_MODEL`
+    pub speech_model: Option<String>,
+    /// Whether to use enhanced speech recognition, overriding `SPEECH_ENHANCED`
+    pub enhanced: Option<bool>,
+    /// Re-dial the number if the call is not answered
+    pub retry_policy: Option<RetryPolicy>,
+    /// Bot whose backend handles the call, from `BACKEND_PROFILES`; the default backend when unset
+    #[serde(default)]
+    pub bot: Option<String>,
+    /// Tags attached to the call, for searching calls later
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl MakeCallRequest {
+    /// Check the request's numbers, callback URL, retry policy, tags and speech settings
+    ///
+    /// Phone numbers are normalized to E.164; SIP destinations are left as given.
+    pub fn validate(&mut self, config: &Config) -> Result<(), String> {
+        if !is_sip_address(&self.to_number) {
+            self.to_number = phone::validate(&self.to_number, &config.twilio.allowed_country_codes)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(from_number) = &self.from_number {
+            self.from_number = Some(phone::normalize(from_number).map_err(|e| format!("Caller ID: {}", e))?);
+        }
+        if let Some(url) = &self.callback_url {
+            validate_callback_url(url, self.tenant_id.as_deref(), &config.callbacks)?;
+        }
+        if let Some(policy) = &self.retry_policy {
+            policy.validate()?;
+        }
+        self.tags = self.tags.iter().map(|tag| normalize_tag(tag)).collect::<Result<_, _>>()?;
+        if let Some(bot) = self.bot.as_deref().filter(|bot| !config.backend.profiles.contains_key(*bot)) {
+            return Err(format!("Unknown bot '{}'", bot));
+        }
+        match self.env_info.as_ref().and_then(|env_info| env_info.get("send_digits")) {
+            None | Some(serde_json::Value::Null) => {},
+            Some(serde_json::Value::String(digits)) if is_valid_send_digits(digits) => {},
+            Some(_) => return Err(format!(
+                "send_digits must be up to {} digits, '*', '#' or 'w' pauses",
+                MAX_SEND_DIGITS_LENGTH
+            )),
+        }
+        self.speech_settings().validate()
+    }
+    
+    /// DTMF digits from `env_info` to play once the call is answered
+    pub fn send_digits(&self) -> Option<&str> {
+        self.env_info.as_ref()?.get("send_digits")?.as_str()
+    }
+    
+    /// Call options for dialing the destination
+    ///
+    /// The `send_digits` string from `env_info` is played once the call is
+    /// answered, e.g. `ww2` to pick option 2 of an IVR menu before the bot
+    /// speaks. SIP destinations also carry the `sip_headers` object from
+    /// `env_info` as custom INVITE headers and authenticate with the configured
+    /// SIP credentials.
+    pub fn call_options(&self, config: &Config) -> CallOptions {
+        let send_digits = self.send_digits().map(str::to_string);
+        if !is_sip_address(&self.to_number) {
+            return CallOptions {
+                send_digits,
+                ..CallOptions::default()
+            };
+        }
+        
+        let sip_headers = self.env_info.as_ref()
+            .and_then(|env_info| env_info.get("sip_headers"))
+            .and_then(|headers| headers.as_object())
+            .map(|headers| {
+                headers.iter()
+                    .filter_map(|(name, value)| match value {
+                        serde_json::Value::String(value) => Some((name.clone(), value.clone())),
+                        serde_json::Value::Null => None,
+                        value => Some((name.clone(), value.to_string())),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        
+        CallOptions {
+            sip_headers,
+            sip_auth_username: config.twilio.sip_auth_username.clone(),
+            sip_auth_password: config.twilio.sip_auth_password.clone(),
+            send_digits,
+            ..CallOptions::default()
+        }
+    }
+    
+    /// Speech recognition settings requested for the call
+    pub fn speech_settings(&self) -> SpeechSettings {
+        SpeechSettings {
+            model: self.speech_model.clone(),
+            enhanced: self.enhanced,
+        }
+    }
 }
 
 /// Response for the make call endpoint
 #[derive(Debug, Serialize)]
 pub struct MakeCallResponse {
-    message: String,
-    session_id: String,
+    pub message: String,
+    /// SID of the placed call
+    pub session_id: String,
+}
+
+/// Why an outbound call could not be placed
+#[derive(Debug)]
+pub struct PlaceCallError {
+    /// HTTP status a synchronous endpoint would answer with
+    pub status: Status,
+    pub message: String,
+}
+
+impl PlaceCallError {
+    pub fn new(status: Status, message: impl Into<String>) -> Self {
+        PlaceCallError { status, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for PlaceCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.status.code)
+    }
 }
 
 /// Handle incoming calls from Twilio
+///
+/// With a warm-up earcon configured, the earcon plays while the backend
+/// session opens and the greeting is served from `/greeting` afterwards.
+/// Calls to a number outside its business hours get the after-hours message
+/// without a backend session.
 #[post("/incoming_callback", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_incoming_call(
-    form: Form<TwilioCallbackForm>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
     ws_manager: &State<Arc<WebSocketManager>>,
-    config: &State<Config>,
-) -> Xml<String> {
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    greetings: &State<Arc<PendingGreetings>>,
+    caller_history: &State<Arc<CallerHistory>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    tenants: &State<Arc<TenantStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+    drain: &State<Arc<Drain>>,
+    external_url: ExternalUrl,
+    self_test: SelfTestCall,
+    config: CurrentConfig,
+) -> TwiML {
+    external_url.check("/incoming_callback", &config.twilio);
+    
     let form = form.into_inner();
+    let call = form.call_details();
     let call_sid = form.call_sid.unwrap_or_default();
-    let from_number = form.from_number.unwrap_or_default();
+    let from_number = phone::normalize_caller(&form.from_number.unwrap_or_default());
     
     debug!("Incoming call from {} with SID {}", from_number, call_sid);
     
-    // Create a new backend client with circuit breaker enabled
-    let backend_client = match BackendClient::new(
-        &config.backend.url, 
-        config.backend.authorization_token.clone(),
-        config.backend.enable_circuit_breaker
-    ) {
+    // The startup self-test only checks that the webhook reaches the service
+    if self_test.0 {
+        return create_hangup_response(None, &config.twilio);
+    }
+    
+    // A draining instance hands new calls to its peer, or turns them away
+    if let Some(mode) = drain.current() {
+        return match mode.peer_url {
+            Some(peer_url) => {
+                info!("Redirecting call {} to {} while draining", call_sid, peer_url);
+                TwiML::new().redirect(&format!("{}/incoming_callback", peer_url))
+            },
+            None => {
+                info!("Rejecting call {} while draining", call_sid);
+                create_reject_response(Some("busy"))
+            },
+        };
+    }
+    
+    // Turn away screened callers before opening a backend session
+    if let Some(rule) = screen_call(&from_number, form.from_country.as_deref(), &config.screening) {
+        match rule.action {
+            ScreeningAction::Proceed => {},
+            ScreeningAction::Reject => {
+                info!("Rejecting screened call {} from {}", call_sid, from_number);
+                return create_reject_response(rule.reason.as_deref());
+            },
+            ScreeningAction::Message => {
+                info!("Ending screened call {} from {}", call_sid, from_number);
+                return create_hangup_response(rule.message.as_deref(), &config.twilio);
+            },
+        }
+    }
+    
+    let to_number = form.to_number.unwrap_or_default();
+    // The dialed number picks the tenant the call belongs to
+    let tenant_id = tenants.find_by_number(&to_number).map(|t| t.id);
+    if let Some(schedule) = config.business_hours.schedule(&to_number).filter(|s| !s.is_open(chrono::Utc::now())) {
+        info!("Call {} to {} is outside business hours", call_sid, to_number);
+        audit.record(&call_sid, None, AuditEntry::Decision {
+            decision: "after_hours".to_string(),
+            reason: Some(to_number.clone()),
+        }).await;
+        
+        if let Some(body) = schedule.sms.clone().filter(|_| !is_anonymous(&from_number)) {
+            send_follow_up_sms(call_sid.clone(), SmsRequest { to: from_number.clone(), body }, sessions.inner().clone(), Config::clone(&config));
+        }
+        
+        let language = config.twilio.language.as_deref();
+        let message = schedule.message.clone()
+            .unwrap_or_else(|| catalog.tenant_text(Phrase::AfterHours, language, tenant_id.as_deref()));
+        let voicemail_prompt = schedule.voicemail
+            .then(|| catalog.tenant_text(Phrase::AfterHoursVoicemail, language, tenant_id.as_deref()));
+        // The message reaches the consumer with the call's result when it ends
+        if let Some(url) = schedule.callback_url.as_deref().filter(|_| schedule.voicemail) {
+            cdrs.add_result_callback(&call_sid, url, tenant_id.as_deref());
+        }
+        return create_after_hours_response(&message, voicemail_prompt.as_deref(), &config.twilio);
+    }
+    
+    // The dialed number also picks the bot answering the call
+    let bot = config.backend.bot_for_number(&to_number).map(|b| b.to_string());
+    
+    let Some(earcon_url) = &config.greeting.earcon_url else {
+        return start_inbound_call(
+            &call_sid, &from_number, call, bot.as_deref(), tenant_id, sessions, ws_manager, catalog, replicator, caller_history, backends, &config
+        ).await;
+    };
+    
+    let greeting_tx = greetings.start(&call_sid);
+    let (sessions, ws_manager, catalog, replicator, caller_history, backends) = (
+        sessions.inner().clone(),
+        ws_manager.inner().clone(),
+        catalog.inner().clone(),
+        replicator.inner().clone(),
+        caller_history.inner().clone(),
+        backends.inner().clone()
+    );
+    let task_config = Config::clone(&config);
+    tokio::spawn(async move {
+        let twiml = start_inbound_call(
+            &call_sid, &from_number, call, bot.as_deref(), tenant_id, &sessions, &ws_manager, &catalog, &replicator, &caller_history, &backends, &task_config
+        ).await;
+        let _ = greeting_tx.send(Some(twiml));
+    });
+    
+    create_warmup_response(earcon_url, &config.twilio)
+}
+
+/// Serve the greeting of a call that started with the warm-up earcon
+#[post("/greeting", data = "<form>")]
+pub async fn handle_greeting(
+    form: TwilioForm<TwilioCallbackForm>,
+    greetings: &State<Arc<PendingGreetings>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let call_sid = form.into_inner().call_sid.unwrap_or_default();
+    let timeout = Duration::from_secs(config.greeting.warmup_timeout_seconds);
+    
+    match greetings.wait(&call_sid, timeout).await {
+        Some(twiml) => twiml,
+        None => create_hangup_response(
+            Some(&catalog.text(Phrase::TechnicalDifficulties, config.twilio.language.as_deref())),
+            &config.twilio
+        ),
+    }
+}
+
+/// Open the backend session for an inbound call and build its first response
+#[allow(clippy::too_many_arguments)]
+async fn start_inbound_call(
+    call_sid: &str,
+    from_number: &str,
+    call: CallDetails,
+    bot: Option<&str>,
+    tenant_id: Option<String>,
+    sessions: &Arc<SessionStore>,
+    ws_manager: &Arc<WebSocketManager>,
+    catalog: &Arc<MessageCatalog>,
+    replicator: &Arc<SessionReplicator>,
+    caller_history: &CallerHistory,
+    backends: &BackendPool,
+    config: &Config,
+) -> TwiML {
+    let language = config.twilio.language.as_deref();
+    
+    let backend_client = match backends.client(&config.backend, bot) {
         Ok(client) => client,
         Err(e) => {
             error!("Failed to create backend client: {}", e);
-            return Xml(create_hangup_response(
-                Some("Sorry, we're experiencing technical difficulties."), 
-                &config.twilio
-            ));
+            return outage_response(catalog, tenant_id.as_deref(), config);
         }
     };
     
     // Create a new session
-    let mut session = Session::new(call_sid.clone(), from_number.clone(), "twilio".to_string(), Some(call_sid.clone()));
+    let mut session = Session::new(call_sid.to_string(), from_number.to_string(), "twilio".to_string(), Some(call_sid.to_string()));
+    session.bot = bot.map(|b| b.to_string());
+    session.tenant_id = tenant_id;
+    
+    let recording = RecordingDecision::for_number(from_number, &config.recording);
+    if let Some(decision) = recording {
+        session.metadata.insert(RECORDING_METADATA_KEY.to_string(), serde_json::json!(decision));
+    }
     
     // Initialize the session with the backend
     let args = vec![];
-    let kwargs = HashMap::new();
+    let mut kwargs = HashMap::new();
+    kwargs.insert("call".to_string(), serde_json::json!(call));
+    session.call = call;
+    if let Some(lookup) = lookup_caller(from_number, config).await {
+        session.metadata.insert("caller_lookup".to_string(), lookup.clone());
+        kwargs.insert("caller_lookup".to_string(), lookup);
+    }
+    
+    // Let the backend resume the conversation of a recent call from the same number
+    if let Some(previous) = caller_history.recent(from_number).await {
+        debug!("Caller {} called back after session {}", from_number, previous.session_id);
+        session.metadata.insert("previous_session_id".to_string(), serde_json::json!(previous.session_id));
+        kwargs.insert("previous_session_id".to_string(), serde_json::json!(previous.session_id));
+        kwargs.insert("previous_call".to_string(), serde_json::json!(previous));
+    }
     
     match backend_client.open_session(
-        &call_sid,
-        &from_number,
+        call_sid,
+        from_number,
         "twilio",
-        Some(&call_sid),
+        Some(call_sid),
         args,
         kwargs
     ).await {
         Ok(response) => {
             // Extract greeting from response
-            let greeting = if let Some(init_response) = response.metadata.get("initialization_response") {
-                if let Some(greeting) = init_response.get("greeting") {
-                    greeting.as_str().unwrap_or("Hello, welcome to our service.").to_string()
-                } else {
-                    "Hello, welcome to our service.".to_string()
-                }
-            } else {
-                "Hello, welcome to our service.".to_string()
-            };
+            let greeting = response.metadata.get("initialization_response")
+                .and_then(|init_response| init_response.get("greeting"))
+                .and_then(|greeting| greeting.as_str())
+                .map(|s| s.to_string())
+                .or_else(|| config.greeting.text(&catalog.fallback_chain(language)))
+                .unwrap_or_else(|| catalog.tenant_text(Phrase::Greeting, language, session.tenant_id.as_deref()));
             
             // Store session data
+            session.session_id = response.session.session_id.clone();
             session.metadata.insert("initialization_response".to_string(), 
                                     serde_json::json!({"greeting": greeting.clone()}));
             
-            // Add session to store
-            let session_id = {
-                let mut store = sessions.write().await;
-                store.add_session(session)
-            };
+            // The greeting is read in the language the session speaks, e.g. its persona's
+            let session_language = session.twilio_config(config).language;
+            
+            // Share the session with other regions, then add it to the store
+            replicator.replicate(&mut session, ReplicaState::Active);
+            sessions.add_session(session);
             
-            // Create WebSocket client for this session if needed
-            if !config.backend.ws_url.is_empty() {
-                ws_manager.get_or_create_client(
-                    &response.session.session_id,
-                    &config.backend.ws_url,
-                    sessions.inner().clone()
-                ).await;
+            // Create WebSocket client for this session
+            ws_manager.get_or_create_client(&response.session.session_id, bot, sessions.clone()).await;
+            
+            if recording.is_some_and(|d| d.records_from_start(&config.recording)) {
+                start_call_recording(call_sid, config);
             }
             
             debug!("Created new session for call {}", call_sid);
-            Xml(create_voice_response(&greeting, &config.twilio, config.twilio.default_timeout, "auto"))
+            let greeting = postprocess::process(&greeting, &config.postprocess, &config.redaction, session_language.as_deref());
+            let timing = gather_timing(&greeting, None, &config.twilio);
+            let consent = recording.and_then(|d| d.consent_message(&config.recording));
+            create_call_start_response(&greeting, consent, config, timing.timeout, &timing.speech_timeout)
         },
         Err(e) => {
             error!("Failed to initialize session with backend: {}", e);
-            Xml(create_hangup_response(
-                Some("Sorry, we're experiencing technical difficulties."), 
-                &config.twilio
-            ))
+            outage_response(catalog, session.tenant_id.as_deref(), config)
+        }
+    }
+}
+
+/// Response for a caller the backend cannot serve, following the configured outage flow
+///
+/// Without an outage flow the caller hears the technical difficulties phrase and is hung up on.
+fn outage_response(catalog: &MessageCatalog, tenant_id: Option<&str>, config: &Config) -> TwiML {
+    let language = config.twilio.language.as_deref();
+    let apology = config.outage.message.clone()
+        .unwrap_or_else(|| catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id));
+    
+    match (config.outage.action, &config.outage.fallback_number) {
+        (Some(OutageAction::Transfer), Some(fallback_number)) => {
+            create_outage_transfer_response(&apology, fallback_number, &config.twilio)
+        },
+        (Some(OutageAction::Voicemail), _) => create_outage_voicemail_response(
+            Some(&apology),
+            &catalog.tenant_text(Phrase::TransferVoicemail, language, tenant_id),
+            &config.twilio
+        ),
+        _ => create_hangup_response(Some(&apology), &config.twilio),
+    }
+}
+
+/// Hand a call whose turn failed over to the outage flow, if one is configured and the backend is down
+///
+/// Returns `None` when the caller should just be asked to try again.
+async fn outage_turn_response(
+    error: &BackendError,
+    session_id: &str,
+    sessions: &SessionStore,
+    catalog: &MessageCatalog,
+    replicator: &Arc<SessionReplicator>,
+    config: &Config,
+) -> Option<TwiML> {
+    if config.outage.action.is_none() || !error.is_outage() {
+        return None;
+    }
+    
+    let mut tenant_id = None;
+    if let Some(mut session) = sessions.lock_session(session_id).await {
+        info!("Backend unavailable, moving session {} to the outage flow", session_id);
+        session.session_ends = true;
+        session.disposition = Some("backend_outage".to_string());
+        session.hangup_source = Some(HangupSource::Bot);
+        replicator.replicate(&mut session, ReplicaState::Ending);
+        tenant_id = session.tenant_id.clone();
+    }
+    
+    Some(outage_response(catalog, tenant_id.as_deref(), config))
+}
+
+/// Start recording an inbound call in the background
+fn start_call_recording(call_sid: &str, config: &Config) {
+    let call_sid = call_sid.to_string();
+    let config = config.twilio.clone();
+    
+    tokio::spawn(async move {
+        let twilio_client = match TwilioClient::new(
+            config.account_sid.clone(),
+            config.auth_token.clone(),
+            config.region.clone(),
+            config.edge.clone()
+        ) {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to create Twilio client: {}", e);
+                return;
+            }
+        };
+        
+        if let Err(e) = twilio_client.start_recording(&call_sid).await {
+            error!("Failed to record call {}: {}", call_sid, e);
+        }
+    });
+}
+
+/// Look up carrier and caller name details for a number, if enabled
+///
+/// Gives up after the configured timeout so a slow lookup never delays the call
+/// for long.
+async fn lookup_caller(phone_number: &str, config: &Config) -> Option<serde_json::Value> {
+    if !config.twilio.lookup_enabled || phone_number.is_empty() || is_sip_address(phone_number) {
+        return None;
+    }
+    
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return None;
+        }
+    };
+    
+    let timeout = Duration::from_millis(config.twilio.lookup_timeout_ms);
+    match tokio::time::timeout(timeout, twilio_client.lookup_number(phone_number)).await {
+        Ok(Ok(lookup)) => serde_json::to_value(lookup).ok(),
+        Ok(Err(e)) => {
+            warn!("Lookup failed for {}: {}", phone_number, e);
+            None
+        },
+        Err(_) => {
+            warn!("Lookup timed out for {}", phone_number);
+            None
         }
     }
 }
 
 /// Handle Twilio call status callbacks
 #[post("/status_callback", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_call_status(
-    form: Form<TwilioCallbackForm>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
-    config: &State<Config>,
+    form: TwilioForm<TwilioCallbackForm>,
+    token: IdempotencyToken,
+    replays: &State<Arc<ReplayCache<Status>>>,
+    sessions: &State<Arc<SessionStore>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    cdrs: &State<Arc<CdrStore>>,
+    scheduler: &State<Arc<CallScheduler>>,
+    tenants: &State<Arc<TenantStore>>,
+    dead_letters: &State<Arc<DeadLetterStore>>,
+    caller_history: &State<Arc<CallerHistory>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
 ) -> Status {
     let form = form.into_inner();
+    let key = token.replay_key(
+        "status_callback",
+        form.call_sid.as_deref().unwrap_or_default(),
+        form.sequence_number.as_deref(),
+        form.timestamp.as_deref(),
+        &[form.call_status.as_deref()]
+    );
+    
+    // Failed attempts are not stored so Twilio's retry is processed again
+    replays.get_or_run(key, |status| status.code < 500, || {
+        process_call_status(form, sessions, replicator, cdrs, scheduler, tenants, dead_letters, caller_history, audit, backends, &config)
+    }).await
+}
+
+/// Apply a call status change
+#[allow(clippy::too_many_arguments)]
+async fn process_call_status(
+    form: TwilioCallbackForm,
+    sessions: &Arc<SessionStore>,
+    replicator: &Arc<SessionReplicator>,
+    cdrs: &Arc<CdrStore>,
+    scheduler: &Arc<CallScheduler>,
+    tenants: &Arc<TenantStore>,
+    dead_letters: &Arc<DeadLetterStore>,
+    caller_history: &CallerHistory,
+    audit: &AuditLog,
+    backends: &BackendPool,
+    config: &Config,
+) -> Status {
+    let details = form.call_details();
     let call_status = form.call_status.unwrap_or_default();
     let call_sid = form.call_sid.unwrap_or_default();
     
-    debug!("Call status update for {}: {}", call_sid, call_status);
-    
-    if call_status == "in-progress" {
-        // Call is in progress, send greeting via TTS
-        let greeting = {
-            let store = sessions.read().await;
-            if let Some(session) = store.get_session_by_conversation(&call_sid) {
-                session.metadata.get("initialization_response")
-                    .and_then(|resp| resp.get("greeting"))
-                    .and_then(|greeting| greeting.as_str())
-                    .map(|s| s.to_string())
-            } else {
-                None
-            }
-        };
-        
-        if let Some(greeting_text) = greeting {
-            // Create TwiML for greeting
-            let twiml = create_voice_response(&greeting_text, &config.twilio, config.twilio.default_timeout, "auto");
-            
-            // Update the call with the TwiML
-            let twilio_client = match TwilioClient::new(
-                config.twilio.account_sid.clone(),
-                config.twilio.auth_token.clone(),
-                config.twilio.region.clone(),
-                config.twilio.edge.clone()
-            ) {
-                Ok(client) => client,
-                Err(e) => {
-                    error!("Failed to create Twilio client: {}", e);
-                    return Status::InternalServerError;
-                }
-            };
-            
-            // Use the retry-capable method with parameters from config
-            if let Err(e) = twilio_client.update_call_with_retry(
-                &call_sid, 
-                &twiml,
-                config.backend.retry_attempts,
-                config.backend.retry_base_delay_ms
-            ).await {
-                error!("Failed to update call with greeting: {}", e);
-                return Status::InternalServerError;
+    debug!("Call status update for {}: {} ({})", call_sid, call_status, details.direction.as_deref().unwrap_or("unknown direction"));
+    // Outbound calls only move forward, so late or repeated statuses are not acted on again
+    if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+        session.call.merge(details.clone());
+        if let (Some(flow), Some(state)) = (session.callflow.as_mut(), CallState::from_call_status(&call_status)) {
+            // Outbound calls are placed with TwiML that greets the callee as soon as they answer
+            if flow.advance(&call_sid, state) && state == CallState::Answered {
+                flow.advance(&call_sid, CallState::Greeted);
             }
         }
-    } else if ["completed", "busy", "no-answer", "canceled", "failed"].contains(&call_status.as_str()) {
+    }
+    
+    if ["completed", "busy", "no-answer", "canceled", "failed"].contains(&call_status.as_str()) {
         // Call has ended, close the session
-        let session_id_option = {
-            let store = sessions.read().await;
-            store.get_session_id_by_conversation(&call_sid)
+        sessions.sync_conversation(&call_sid).await;
+        let session_id_option = match sessions.get_session_id_by_conversation(&call_sid) {
+            Some(session_id) => Some(session_id),
+            // The call may have been owned by a region that failed over
+            None => replicator.close_orphan(&call_sid).await,
+        };
+        let session = session_id_option.as_deref().and_then(|session_id| sessions.remove_session(session_id));
+        agent_pool().release(&call_sid);
+        
+        // Record who ended the call, inferring it from the status if the bot didn't
+        let session = match &session {
+            Some(session) => {
+                let mut session = session.lock().await;
+                replicator.replicate(&mut session, ReplicaState::Closed);
+                Some(session)
+            },
+            None => None,
         };
+        let mut record = match &session {
+            Some(session) => CallRecord::from_session(&call_sid, session, &call_status),
+            None => CallRecord::without_session(&call_sid, session_id_option.clone(), &call_status),
+        };
+        if record.hangup_source.is_none() {
+            record.hangup_source = HangupSource::from_call_status(&call_status, session_id_option.is_some());
+        }
+        record.ended_at = Some(chrono::Utc::now());
+        record.duration_seconds = details.call_duration;
+        record.direction = record.direction.take().or(details.direction);
+        record.answered_by = record.answered_by.take().or(details.answered_by);
+        let disposition = record.disposition.clone();
+        
+        cdrs.attach_pending(&mut record);
+        
+        // Calls that will be re-dialed report their result after the last attempt
+        let result_callback = cdrs.take_result_callback(&call_sid).map(|callback| {
+            record.tenant_id = record.tenant_id.take().or(callback.tenant_id);
+            callback.url
+        });
+        if let Some(url) = scheduler.call_ended(&mut record, result_callback) {
+            send_call_result(url, record.clone(), config.callbacks.clone(), dead_letters.clone());
+        }
+        let summary = CallSummary::new(record.clone(), session.as_deref());
+        let bot = session.as_deref().and_then(|s| s.bot.clone());
+        if let Some((experiment, variant)) = session.as_deref().and_then(|s| assigned_variant(&s.metadata)) {
+            experiment_metrics().record_call_ended(
+                experiment, variant, &call_status, &record.disposition, summary.turn_count, record.duration_seconds
+            );
+        }
+        drop(session);
+        
+        // Remember the conversation in case the caller calls back
+        if let (Some(session_id), Some(caller), "completed") = (&record.session_id, &record.caller, call_status.as_str()) {
+            caller_history.record(caller, PreviousCall {
+                session_id: session_id.clone(),
+                call_sid: call_sid.clone(),
+                disposition: record.disposition.clone(),
+                turn_count: summary.turn_count,
+                ended_at: record.ended_at.unwrap_or_else(chrono::Utc::now),
+            }).await;
+        }
+        if record.redactions > 0 {
+            audit.record(&call_sid, record.session_id.as_deref(), AuditEntry::Decision {
+                decision: "redacted".to_string(),
+                reason: Some(format!("{} personal data matches", record.redactions)),
+            }).await;
+        }
+        audit.record(&call_sid, record.session_id.as_deref(), AuditEntry::Decision {
+            decision: "call_ended".to_string(),
+            reason: Some(match record.hangup_source {
+                Some(source) => format!("{} ({})", record.disposition, source.as_str()),
+                None => record.disposition.clone(),
+            }),
+        }).await;
+        cdrs.record(record);
+        
+        // Only connected calls are billed
+        if call_status == "completed" {
+            start_call_price_fetch(call_sid.clone(), cdrs.clone(), tenants.clone(), config.clone());
+        }
         
         if let Some(session_id) = session_id_option {
-            {
-                let mut store = sessions.write().await;
-                store.remove_session(&session_id);
-            }
             debug!("Removed session {} for ended call {}", session_id, call_sid);
             
             // Close session with backend
-            let backend_client = match BackendClient::new(
-                &config.backend.url, 
-                config.backend.authorization_token.clone(),
-                config.backend.enable_circuit_breaker
-            ) {
+            let backend_client = match backends.client(&config.backend, bot.as_deref()) {
                 Ok(client) => client,
                 Err(e) => {
                     error!("Failed to create backend client: {}", e);
@@ -218,363 +825,2542 @@ pub async fn handle_call_status(
                 }
             };
             
-            if let Err(e) = backend_client.close_session(&session_id, Some(&call_status)).await {
+            // Notifications that fail after their retries are dead-lettered for replay
+            let (retries, delay_ms) = (config.backend.retry_attempts, config.backend.retry_base_delay_ms);
+            if config.backend.call_summary_enabled {
+                let posted = with_retry(retries, delay_ms, || backend_client.post_call_summary(&session_id, &summary)).await;
+                if let Err(e) = posted {
+                    error!("Failed to post call summary for session {}: {}", session_id, e);
+                    let payload = serde_json::to_value(&summary).unwrap_or_default();
+                    dead_letters.add(DeadLetterKind::CallSummary, &session_id, bot.as_deref(), payload, &e.to_string()).await;
+                }
+            }
+            
+            let closed = with_retry(retries, delay_ms, || backend_client.close_session(&session_id, Some(&disposition))).await;
+            if let Err(e) = closed {
                 error!("Failed to close session with backend: {}", e);
+                let payload = serde_json::json!({ "status": disposition });
+                dead_letters.add(DeadLetterKind::CloseSession, &session_id, bot.as_deref(), payload, &e.to_string()).await;
+            }
+        }
+    }
+    
+    Status::Ok
+}
+
+/// Handle asynchronous answering machine detection results from Twilio
+#[post("/amd_callback", data = "<form>")]
+pub async fn handle_amd_callback(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> Status {
+    let form = form.into_inner();
+    let details = form.call_details();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let answered_by = form.answered_by.unwrap_or_default();
+    
+    debug!("Answering machine detection for call {}: {}", call_sid, answered_by);
+    
+    let twiml = {
+        let mut session = match sessions.lock_session_by_conversation(&call_sid).await {
+            Some(session) => session,
+            None => return Status::Ok,
+        };
+        session.call.merge(details);
+        
+        if answered_by.starts_with("machine_end") {
+            match session.voicemail_message.clone() {
+                Some(message) => {
+                    session.disposition = Some("voicemail_left".to_string());
+                    session.hangup_source = Some(HangupSource::Bot);
+                    session.session_ends = true;
+                    create_voicemail_response(&message, &config.twilio)
+                },
+                None => return Status::Ok,
             }
+        } else if answered_by == "fax" {
+            session.disposition = Some("fax".to_string());
+            session.hangup_source = Some(HangupSource::Bot);
+            session.session_ends = true;
+            create_hangup_response(None, &config.twilio)
+        } else {
+            return Status::Ok;
+        }
+    };
+    
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return Status::InternalServerError;
         }
+    };
+    
+    if let Err(e) = twilio_client.update_call_with_retry(
+        &call_sid,
+        &twiml.build(),
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        error!("Failed to drop voicemail on call {}: {}", call_sid, e);
+        return Status::InternalServerError;
     }
     
+    debug!("Dropped voicemail on call {}", call_sid);
     Status::Ok
 }
 
 /// Handle transcription callbacks from Twilio
 #[post("/transcription_callback", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_call_transcription(
-    form: Form<TwilioCallbackForm>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
-    config: &State<Config>,
-) -> Xml<String> {
+    form: TwilioForm<TwilioCallbackForm>,
+    token: IdempotencyToken,
+    replays: &State<Arc<ReplayCache<TwiML>>>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
     let form = form.into_inner();
-    let call_sid = form.call_sid.unwrap_or_default();
-    let transcription = form.speech_result.unwrap_or_default();
+    let turn = sessions.lock_turn(form.call_sid.as_deref().unwrap_or_default()).await;
+    let confidence = form.confidence.map(|c| c.to_string());
+    let key = token.replay_key(
+        "transcription_callback",
+        form.call_sid.as_deref().unwrap_or_default(),
+        form.sequence_number.as_deref(),
+        form.timestamp.as_deref(),
+        &[form.speech_result.as_deref(), confidence.as_deref(), form.digits.as_deref()]
+    );
     
-    debug!("Transcription for call {}: {}", call_sid, transcription);
+    // Twilio retries on errors and timeouts; answer replays with the original TwiML,
+    // unless the turn failed and the retry should run it again
+    replays.get_or_run(key, |twiml| !twiml.is_failed(), || async {
+        let call_sid = form.call_sid.unwrap_or_default();
+        let turn = {
+            let call_sid = call_sid.clone();
+            let transcription = form.speech_result.unwrap_or_default();
+            let (sessions, catalog, replicator, audit, backends) = (
+                sessions.inner().clone(),
+                catalog.inner().clone(),
+                replicator.inner().clone(),
+                audit.inner().clone(),
+                backends.inner().clone()
+            );
+            let config = Config::clone(&config);
+            async move {
+                process_transcription(
+                    turn, call_sid, transcription, form.confidence, &sessions, &catalog, &replicator, &audit, &backends, &config
+                ).await
+            }
+        };
+        within_webhook_timeout(&call_sid, turn, sessions, catalog, &config).await
+    }).await
+}
+
+/// Handle keypresses on a call whose speech is recognized from the Media Stream
+///
+/// The digits are the caller's turn, as a transcription would be. A Gather that
+/// ended without digits goes back to the stream hold.
+#[post("/dtmf_callback", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_dtmf_callback(
+    form: TwilioForm<TwilioCallbackForm>,
+    token: IdempotencyToken,
+    replays: &State<Arc<ReplayCache<TwiML>>>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let digits = form.digits.clone().unwrap_or_default();
+    if digits.is_empty() {
+        return create_stream_hold_response(&config.twilio);
+    }
+    let turn = sessions.lock_turn(form.call_sid.as_deref().unwrap_or_default()).await;
+    let key = token.replay_key(
+        "dtmf_callback",
+        form.call_sid.as_deref().unwrap_or_default(),
+        form.sequence_number.as_deref(),
+        form.timestamp.as_deref(),
+        &[Some(&digits)]
+    );
     
-    // Check if session exists and get necessary state
-    let (session_id, session_ends, is_same_result, has_generation) = {
-        let mut store = sessions.write().await;
-        
-        if let Some(session) = store.get_session_by_conversation_mut(&call_sid) {
+    replays.get_or_run(key, |twiml| !twiml.is_failed(), || async {
+        let call_sid = form.call_sid.unwrap_or_default();
+        debug!("Keypresses on call {}: {}", call_sid, digits);
+        let turn = {
+            let call_sid = call_sid.clone();
+            let (sessions, catalog, replicator, audit, backends) = (
+                sessions.inner().clone(),
+                catalog.inner().clone(),
+                replicator.inner().clone(),
+                audit.inner().clone(),
+                backends.inner().clone()
+            );
+            let config = Config::clone(&config);
+            async move {
+                process_transcription(
+                    turn, call_sid, digits, None, &sessions, &catalog, &replicator, &audit, &backends, &config
+                ).await
+            }
+        };
+        within_webhook_timeout(&call_sid, turn, sessions, catalog, &config).await
+    }).await
+}
+
+/// Hold a call whose speech is recognized from the Media Stream until the caller speaks
+///
+/// Reached once a prompt has played, and again whenever a hold ends. The
+/// caller's silence is judged by the media stream from its transcripts, which
+/// starts the keepalive prompts after `timeout` seconds without speech.
+#[post("/stream_hold?<timeout>", data = "<form>")]
+pub async fn handle_stream_hold(
+    timeout: Option<u32>,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let _turn = sessions.lock_turn(&call_sid).await;
+    
+    match sessions.lock_session_by_conversation(&call_sid).await {
+        Some(mut session) => {
             if session.session_ends {
-                debug!("Session for call {} has already ended", call_sid);
-                return Xml(create_hangup_response(None, &config.twilio));
+                return create_hangup_response(None, &config.twilio);
+            }
+            if session.activity() == SessionActivity::Speaking {
+                return TwiML::new().redirect(&config.twilio.callback_url("/queue_callback"));
             }
             
-            // Check if we need to generate new response
-            let is_same = session.unstable_speech_result_is_the_same(&transcription);
-            let has_gen = session.generation;
+            let timeout = Duration::from_secs(timeout.unwrap_or(config.twilio.default_timeout).into());
+            session.listening.get_or_insert((std::time::Instant::now(), timeout));
+            create_stream_hold_response(&session.twilio_config(&config))
+        },
+        None => {
+            debug!("No session found for held call {}", call_sid);
+            create_hangup_response(None, &config.twilio)
+        }
+    }
+}
+
+/// Speech settings and tenant for a call's prompts: its session's, or the defaults without one
+async fn call_prompt_settings(sessions: &SessionStore, call_sid: &str, config: &Config) -> (TwilioConfig, Option<String>) {
+    match sessions.lock_session_by_conversation(call_sid).await {
+        Some(session) => (session.twilio_config(config), session.tenant_id.clone()),
+        None => (config.twilio.clone(), None),
+    }
+}
+
+/// Answer a turn webhook before Twilio gives up on it
+///
+/// The turn runs in its own task. If it has not produced TwiML within
+/// `WEBHOOK_RESPONSE_TIMEOUT_MS`, the caller hears a filler and is sent to the
+/// queue callback, which plays the turn's TwiML once it is ready. Letting Twilio
+/// time out instead makes it replay the request and run the turn twice.
+async fn within_webhook_timeout<F>(
+    call_sid: &str,
+    turn: F,
+    sessions: &Arc<SessionStore>,
+    catalog: &MessageCatalog,
+    config: &Config,
+) -> TwiML
+where
+    F: std::future::Future<Output = TwiML> + Send + 'static,
+{
+    let (twilio, tenant_id) = call_prompt_settings(sessions, call_sid, config).await;
+    let language = twilio.language.as_deref();
+    let mut task = tokio::spawn(turn);
+    let limit = Duration::from_millis(config.twilio.webhook_response_timeout_ms);
+    
+    match tokio::time::timeout(limit, &mut task).await {
+        Ok(Ok(twiml)) => return twiml,
+        Ok(Err(e)) => {
+            error!("Turn for call {} failed: {}", call_sid, e);
+            return create_hangup_response(Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), &twilio).failed();
+        },
+        Err(_) => {},
+    }
+    
+    warn!("Turn for call {} outlasted {}ms, answering with a filler", call_sid, limit.as_millis());
+    if let Some(mut session) = sessions.lock_session_by_conversation(call_sid).await {
+        session.turn_overdue = true;
+    }
+    
+    let late_sessions = sessions.clone();
+    let late_call_sid = call_sid.to_string();
+    let hangup = create_hangup_response(Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), &twilio);
+    tokio::spawn(async move {
+        let twiml = match task.await {
+            Ok(twiml) => twiml,
+            Err(e) => {
+                error!("Turn for call {} failed: {}", late_call_sid, e);
+                hangup
+            },
+        };
+        debug!("Overdue turn for call {} is ready", late_call_sid);
+        if let Some(mut session) = late_sessions.lock_session_by_conversation(&late_call_sid).await {
+            session.late_reply = Some(twiml);
+        }
+    });
+    
+    create_turn_wait_response(Some(&catalog.tenant_text(Phrase::TurnDelay, language, tenant_id.as_deref())), &twilio)
+}
+
+/// Run a conversation turn for the caller's final transcription
+///
+/// Used for Gather transcription callbacks and for streaming speech recognition.
+/// `turn` is the call's turn lock, released while the backend runs the turn.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_transcription(
+    turn: TurnGuard,
+    call_sid: String,
+    transcription: String,
+    confidence: Option<f64>,
+    sessions: &Arc<SessionStore>,
+    catalog: &Arc<MessageCatalog>,
+    replicator: &Arc<SessionReplicator>,
+    audit: &Arc<AuditLog>,
+    backends: &Arc<BackendPool>,
+    config: &Config,
+) -> TwiML {
+    let redacted = config.redaction.redact(&transcription);
+    let input = TurnInput::new(redacted.text.clone());
+    
+    debug!("Transcription for call {}: {}", call_sid, redacted.text);
+    
+    // Resume the conversation locally if another replica or region owned the call
+    sessions.sync_conversation(&call_sid).await;
+    let is_local = sessions.get_session_id_by_conversation(&call_sid).is_some();
+    if !is_local {
+        if let Some(session) = replicator.take_over(&call_sid).await {
+            sessions.add_session(session);
+        }
+    }
+    // Prompts follow the session's language and voice
+    let (twilio, tenant_id) = call_prompt_settings(sessions, &call_sid, config).await;
+    let language = twilio.language.as_deref();
+    
+    // Check if session exists and get necessary state
+    let (session_id, is_same_result, has_generation) = {
+        if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+            if session.session_ends {
+                debug!("Session for call {} has already ended", call_sid);
+                return create_hangup_response(None, &twilio);
+            }
+            session.redactions += redacted.count;
+            session.last_speech = Some(std::time::Instant::now());
+            session.listening = None;
+            
+            // Calls taken over by an agent only pass the caller's speech on for the backend's notes
+            if session.takeover.is_some() {
+                debug!("Shadowing speech on call {}", call_sid);
+                session.speech_stats.record_utterance(&transcription, confidence);
+                let session_id = session.session_id.clone();
+                drop(session);
+                
+                sessions.publish_event(&session_id, SessionEventKind::UserSaid { text: redacted.text });
+                report_event(session_id, serde_json::json!({
+                    "type": "shadow_transcript",
+                    "text": transcription,
+                    "confidence": confidence,
+                }), sessions, backends, config);
+                return create_takeover_response(&takeover_conference(&call_sid), config);
+            }
+            
+            // Speech that was in flight when the caller was put on hold is dropped
+            if let Some(hold_music_url) = config.twilio.hold_music_url.as_deref().filter(|_| session.on_hold) {
+                debug!("Ignoring speech on held call {}", call_sid);
+                return create_hold_response(hold_music_url);
+            }
+            
+            // End the call gracefully instead of running another turn past the limits
+            if let Some(limit) = session.exceeded_limit(config.session.max_turns, config.session.max_call_duration_minutes) {
+                info!("Ending call {}: {} reached", call_sid, limit);
+                session.session_ends = true;
+                session.disposition = Some(limit.to_string());
+                session.hangup_source = Some(HangupSource::Bot);
+                replicator.replicate(&mut session, ReplicaState::Ending);
+                audit.record(&call_sid, Some(&session.session_id), AuditEntry::Decision {
+                    decision: "hangup".to_string(),
+                    reason: Some(limit.to_string()),
+                }).await;
+                return create_hangup_response(Some(&catalog.tenant_text(Phrase::CallLimitReached, language, tenant_id.as_deref())), &twilio);
+            }
+            
+            session.partial_debouncer.reset();
+            
+            // Check if we need to generate new response
+            let is_same = session.unstable_speech_result_is_the_same(&transcription);
+            let has_gen = session.generation;
+            
+            session.speech_stats.record_utterance(&transcription, confidence);
+            session.speech_stats.resolve_speculation(has_gen && is_same);
+            
+            (
+                session.session_id.clone(),
+                is_same,
+                has_gen
+            )
+        } else {
+            // Session not found
+            error!("No session found for call {}", call_sid);
+            return create_hangup_response(Some(&catalog.tenant_text(Phrase::SessionExpired, language, tenant_id.as_deref())), &twilio);
+        }
+    };
+    
+    // Ask the caller to clarify instead of sending likely misrecognized speech to the backend
+    if let Some(prompt) = clarification_prompt(&transcription, confidence, catalog, &twilio, tenant_id.as_deref()) {
+        info!("Clarifying low-confidence speech on call {} ({:?})", call_sid, confidence);
+        return create_voice_response(&prompt, &twilio, twilio.default_timeout, "auto");
+    }
+    
+    sessions.publish_event(&session_id, SessionEventKind::UserSaid { text: redacted.text });
+    
+    // Check if we need to generate new response
+    let should_generate = if has_generation {
+        !is_same_result
+    } else {
+        true
+    };
+    
+    if should_generate {
+        let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to create backend client: {}", e);
+                return create_hangup_response(
+                    Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), 
+                    &twilio
+                ).failed();
+            }
+        };
+        
+        // Update session state and collect the context sent with the turn
+        let (kwargs, message_tx) = {
+            if let Some(mut session) = sessions.lock_session(&session_id).await {
+                session.run_in_progress = true;
+                session.speech_in_progress = false;
+                session.unstable_speech_result = Some(transcription.clone());
+                session.generation = true;
+                session.turn_count += 1;
+                if let Some(flow) = session.callflow.as_mut() {
+                    flow.advance(&call_sid, CallState::Conversing);
+                }
+                
+                let mut kwargs = session.turn_kwargs(confidence, &config.backend.turn_kwargs);
+                if let Some(silence) = session.silence.take() {
+                    kwargs.insert("silence".to_string(), silence.to_json());
+                }
+                (kwargs, Some(session.message_tx.clone()))
+            } else {
+                (HashMap::new(), None)
+            }
+        };
+        
+        // Replies spoken over a Media Stream need the whole text, so they are never streamed
+        let stream_tx = message_tx.filter(|_| {
+            config.backend.stream_responses && config.tts.delivery != TtsDelivery::MediaStream
+        });
+        drop(turn);
+        
+        // Send transcription to backend with retry
+        let result = match stream_tx {
+            Some(message_tx) => match with_retry_within(config.backend.retry_attempts, config.backend.retry_base_delay_ms, Duration::from_millis(config.backend.timeouts.run_total_ms), || {
+                backend_client.run_streaming(&session_id, &transcription, kwargs.clone())
+            }).await {
+                Ok(stream) if stream.is_streaming() => {
+                    return stream_reply(stream, message_tx, input, &session_id, &call_sid, sessions, catalog, replicator, audit, config).await;
+                },
+                Ok(stream) => stream.forward(&message_tx).await,
+                Err(e) => Err(e),
+            },
+            None => backend_client.run_with_retry(
+                &session_id, 
+                &transcription, 
+                kwargs,
+                config.backend.retry_attempts,
+                config.backend.retry_base_delay_ms
+            ).await,
+        };
+        let _turn = sessions.lock_turn(&call_sid).await;
+        match result {
+            Ok(result) => {
+                respond_to_run_result(&result, input, &session_id, &call_sid, sessions, catalog, replicator, audit, config).await
+            },
+            Err(e) => {
+                // Update session state
+                {
+                    if let Some(mut session) = sessions.lock_session(&session_id).await {
+                        session.generation = false;
+                    }
+                }
+                
+                error!("Failed to run backend command: {}", e);
+                if let Some(twiml) = outage_turn_response(&e, &session_id, sessions, catalog, replicator, config).await {
+                    return twiml.failed();
+                }
+                create_voice_response(
+                    &catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref()), 
+                    &twilio, 
+                    twilio.default_timeout, 
+                    "auto"
+                ).failed()
+            }
+        }
+    } else {
+        // Re-use previous response
+        create_voice_response(
+            &catalog.tenant_text(Phrase::RepeatPrompt, language, tenant_id.as_deref()), 
+            &twilio, 
+            twilio.default_timeout, 
+            "auto"
+        )
+    }
+}
+
+/// Speak a backend reply from the message queue while it streams in
+///
+/// The caller is sent to the queue callback at once and hears the reply text as
+/// it arrives. Once the run completes, a plain reply ends the stream; transfers,
+/// hangups, menus and other replies are answered by the queue callback instead.
+#[allow(clippy::too_many_arguments)]
+async fn stream_reply(
+    stream: RunStream,
+    message_tx: MessageSender,
+    input: TurnInput,
+    session_id: &str,
+    call_sid: &str,
+    sessions: &Arc<SessionStore>,
+    catalog: &Arc<MessageCatalog>,
+    replicator: &Arc<SessionReplicator>,
+    audit: &Arc<AuditLog>,
+    config: &Config,
+) -> TwiML {
+    if let Some(mut session) = sessions.lock_session(session_id).await {
+        session.turn_overdue = true;
+    }
+    
+    let (session_id, call_sid) = (session_id.to_string(), call_sid.to_string());
+    let (sessions, catalog, replicator, audit) = (sessions.clone(), catalog.clone(), replicator.clone(), audit.clone());
+    let config_owned = config.clone();
+    tokio::spawn(async move {
+        let config = &config_owned;
+        let forwarded = stream.forward(&message_tx).await;
+        let _turn = sessions.lock_turn(&call_sid).await;
+        let late_reply = match forwarded {
+            Ok(mut result) => {
+                let kind = result.kind();
+                let handed_off = risk_handoff(&result, config).is_some();
+                let plain = matches!(kind, ResponseKind::Text(_) | ResponseKind::Ssml(_)) && !handed_off;
+                // The caller already heard the text as it streamed
+                if handed_off || matches!(kind, ResponseKind::Transfer(_) | ResponseKind::End | ResponseKind::Menu(_)) {
+                    result.response = None;
+                }
+                let twiml = respond_to_run_result(&result, input, &session_id, &call_sid, &sessions, &catalog, &replicator, &audit, config).await;
+                (!plain).then_some(twiml)
+            },
+            Err(e) => {
+                error!("Backend reply stream failed for call {}: {}", call_sid, e);
+                let (twilio, tenant_id) = match sessions.lock_session(&session_id).await {
+                    Some(mut session) => {
+                        session.generation = false;
+                        (session.twilio_config(config), session.tenant_id.clone())
+                    },
+                    None => (config.twilio.clone(), None),
+                };
+                let language = twilio.language.as_deref();
+                Some(create_voice_response(
+                    &catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref()),
+                    &twilio,
+                    twilio.default_timeout,
+                    "auto"
+                ))
+            },
+        };
+        
+        match late_reply {
+            Some(twiml) => {
+                if let Some(mut session) = sessions.lock_session(&session_id).await {
+                    session.late_reply = Some(twiml);
+                }
+            },
+            None => {
+                let _ = message_tx.send(MessageType::EndOfStream).await;
+                if let Some(mut session) = sessions.lock_session(&session_id).await {
+                    session.turn_overdue = false;
+                }
+            },
+        }
+    });
+    
+    create_turn_wait_response(None, &config.twilio)
+}
+
+/// Prompt asking the caller to clarify speech recognized below the confidence threshold
+///
+/// A confidence of 0 means the speech model did not report one.
+fn clarification_prompt(
+    transcription: &str,
+    confidence: Option<f64>,
+    catalog: &MessageCatalog,
+    twilio: &TwilioConfig,
+    tenant_id: Option<&str>,
+) -> Option<String> {
+    let threshold = twilio.speech_confidence_threshold;
+    let confidence = confidence.filter(|c| *c > 0.0)?;
+    if threshold <= 0.0 || confidence >= threshold || transcription.trim().is_empty() {
+        return None;
+    }
+    
+    let language = twilio.language.as_deref();
+    Some(if twilio.clarification_includes_text {
+        catalog.tenant_text(Phrase::Clarification, language, tenant_id).replace("{text}", transcription.trim())
+    } else {
+        catalog.tenant_text(Phrase::RepeatPrompt, language, tenant_id)
+    })
+}
+
+/// Build the TwiML reply for a completed backend run, recording the turn in the audit log
+#[allow(clippy::too_many_arguments)]
+async fn respond_to_run_result(
+    result: &RunResponse,
+    input: TurnInput,
+    session_id: &str,
+    call_sid: &str,
+    sessions: &Arc<SessionStore>,
+    catalog: &MessageCatalog,
+    replicator: &Arc<SessionReplicator>,
+    audit: &AuditLog,
+    config: &Config,
+) -> TwiML {
+    let mut kind = result.kind();
+    let ends = result.ends_session();
+    if let Some(handoff) = risk_handoff(result, config) {
+        info!(
+            "Escalation risk {} reached the threshold for call {}, handing off",
+            result.metadata.escalation_risk.unwrap_or_default(), call_sid
+        );
+        kind = ResponseKind::Transfer(handoff);
+    }
+    let (decision, target) = audit_decision(&kind, config);
+    
+    // Response text as it will be spoken, in the language the backend detected or else the session's
+    let language = match sessions.lock_session(session_id).await {
+        Some(session) => session.twilio_config(config).language,
+        None => config.twilio.language.clone(),
+    };
+    let language = result.detected_language.clone().filter(|l| !l.is_empty()).or(language);
+    let spoken = result.response.as_deref()
+        .map(|r| postprocess::process(r, &config.postprocess, &config.redaction, language.as_deref()));
+    // ...and as it is audited
+    let redacted = spoken.as_deref().map(|s| config.redaction.redact(s));
+    
+    // Update session state
+    let (twilio, tenant_id) = {
+        if let Some(mut session) = sessions.lock_session(session_id).await {
+            session.generation = false;
+            if result.has_reply() {
+                session.speech_stats.record_reply();
+            }
+            session.redactions += redacted.as_ref().map_or(0, |r| r.count);
+            session.active_menu = result.menu.clone();
+            
+            // Switch voice persona if requested; null returns to the default voice
+            match &result.persona {
+                Some(Some(name)) if config.personas.get(name).is_some() => {
+                    session.persona = Some(name.clone());
+                },
+                Some(Some(name)) => warn!("Unknown voice persona '{}' for call {}", name, call_sid),
+                Some(None) => session.persona = None,
+                None => {},
+            }
+            
+            // Switch to a catalog voice if requested; null returns to the persona's or default voice
+            match &result.voice {
+                Some(Some(name)) if config.tts.voices.contains_key(name) => {
+                    session.voice = Some(name.clone());
+                },
+                Some(Some(name)) => warn!("Unknown TTS voice '{}' for call {}", name, call_sid),
+                Some(None) => session.voice = None,
+                None => {},
+            }
+            
+            // Speak and listen in the caller's language once the backend detects it
+            if let Some(detected) = result.detected_language.as_deref().filter(|l| !l.is_empty()) {
+                if session.detected_language.as_deref() != Some(detected) {
+                    info!("Detected language {} for call {}", detected, call_sid);
+                    session.detected_language = Some(detected.to_string());
+                }
+            }
+            
+            // Switch speech recognition settings if requested; null returns to the default
+            match &result.speech_model {
+                Some(Some(model)) if is_supported_speech_model(model) => {
+                    session.speech.model = Some(model.clone());
+                },
+                Some(Some(model)) => warn!("Unsupported speech model '{}' for call {}", model, call_sid),
+                Some(None) => session.speech.model = None,
+                None => {},
+            }
+            if let Some(enhanced) = result.enhanced {
+                session.speech.enhanced = enhanced;
+            }
+            
+            // Relay new caller sentiment and escalation risk to supervisors
+            let metadata = &result.metadata;
+            if metadata.sentiment.is_some() || metadata.escalation_risk.is_some() {
+                let previous = session.signals.as_ref();
+                let sentiment = metadata.sentiment.clone().or_else(|| previous.and_then(|s| s.sentiment.clone()));
+                let escalation_risk = metadata.escalation_risk.or_else(|| previous.and_then(|s| s.escalation_risk));
+                if !previous.is_some_and(|s| s.sentiment == sentiment && s.escalation_risk == escalation_risk) {
+                    sessions.publish_event(session_id, SessionEventKind::Signals {
+                        sentiment: sentiment.clone(),
+                        escalation_risk,
+                    });
+                }
+                session.signals = Some(CallerSignals {
+                    sentiment,
+                    escalation_risk,
+                    updated_at: chrono::Utc::now(),
+                });
+            }
+            
+            for tag in &metadata.tags {
+                match normalize_tag(tag) {
+                    Ok(tag) => {
+                        session.tags.insert(tag);
+                    },
+                    Err(e) => warn!("Ignoring tag for call {}: {}", call_sid, e),
+                }
+            }
+            
+            match &kind {
+                ResponseKind::Transfer(Handoff::Queue(queue)) => {
+                    let queue = queue.unwrap_or(&config.twilio.agent_queue);
+                    info!("Placing call {} in queue {}", call_sid, queue);
+                    session.queue = Some(queue.to_string());
+                },
+                ResponseKind::Transfer(Handoff::Number(target)) => {
+                    info!("Transferring call {} to {}", call_sid, target);
+                    session.transfer_target = Some(target.to_string());
+                },
+                ResponseKind::Transfer(Handoff::Escalation { target, .. }) => {
+                    info!("Escalating call {} to {}", call_sid, target);
+                    session.escalation_target = Some(target.to_string());
+                },
+                _ => {},
+            }
+            if matches!(kind, ResponseKind::Transfer(_)) {
+                agent_pool().handed_off(call_sid);
+            }
+            
+            if ends {
+                session.session_ends = true;
+                session.hangup_source = Some(HangupSource::Bot);
+                replicator.replicate(&mut session, ReplicaState::Ending);
+                debug!("Session for call {} will end after this response", call_sid);
+            }
+            
+            // Plain replies can be spoken over the call's Media Stream without new TwiML
+            if config.tts.delivery == TtsDelivery::MediaStream {
+                session.pending_reply = match &kind {
+                    ResponseKind::Ssml(_) | ResponseKind::Text(_) => spoken.clone(),
+                    _ => None,
+                };
+            }
+            
+            sessions.publish_event(session_id, SessionEventKind::State {
+                generation: session.generation,
+                session_ends: session.session_ends,
+                turn_count: session.turn_count,
+                persona: session.persona.clone(),
+                queue: session.queue.clone(),
+            });
+            
+            (session.twilio_config(config), session.tenant_id.clone())
+        } else {
+            (config.twilio.clone(), None)
+        }
+    };
+    
+    // Text the caller a follow-up once the turn's state is updated
+    if let Some(sms) = &result.metadata.send_sms {
+        send_follow_up_sms(call_sid.to_string(), sms.clone(), sessions.clone(), config.clone());
+    }
+    
+    sessions.publish_event(session_id, SessionEventKind::BotReplied {
+        text: result.response.as_deref().map(|r| config.redaction.redacted(r)),
+        audio_url: result.audio().map(|u| u.to_string()),
+    });
+    
+    // Speak with the session's persona and listen with its speech settings
+    let twilio = &twilio;
+    let language = twilio.language.as_deref();
+    
+    // Vocabulary the backend expects in the caller's next answer
+    let hints = result.speech_hints.as_ref().map(|h| h.joined());
+    
+    let twiml = match kind {
+        ResponseKind::Transfer(Handoff::Queue(queue)) => {
+            let announcement = spoken.unwrap_or_else(|| catalog.tenant_text(Phrase::TransferAnnouncement, language, tenant_id.as_deref()));
+            create_enqueue_response(Some(&announcement), queue.unwrap_or(&config.twilio.agent_queue), twilio)
+        },
+        ResponseKind::Transfer(Handoff::Number(target)) => {
+            let announcement = spoken.unwrap_or_else(|| catalog.tenant_text(Phrase::TransferAnnouncement, language, tenant_id.as_deref()));
+            create_transfer_response(Some(&announcement), target, twilio)
+        },
+        ResponseKind::Transfer(Handoff::Escalation { target, context }) => {
+            let announcement = spoken.unwrap_or_else(|| catalog.tenant_text(Phrase::EscalationAnnouncement, language, tenant_id.as_deref()));
+            let whisper = catalog.tenant_text(Phrase::EscalationWhisper, language, tenant_id.as_deref())
+                .replace("{context}", context.unwrap_or_default());
+            let conference_name = escalation_conference(call_sid);
+            start_escalation(call_sid.to_string(), target.to_string(), whisper, twilio.clone(), config.clone());
+            create_escalation_response(&announcement, &conference_name, twilio)
+        },
+        ResponseKind::End => match result.audio() {
+            Some(audio_url) => TwiML::new().play(audio_url, None).hangup(),
+            None => create_hangup_response(spoken.as_deref(), twilio),
+        },
+        // Render a structured menu, using any response text as its preface
+        ResponseKind::Menu(menu) => create_menu_response(menu, spoken.as_deref(), twilio),
+        ResponseKind::AudioUrl(audio_url) => {
+            let timing = gather_timing(result.response.as_deref().unwrap_or(""), Some(&result.metadata), twilio);
+            create_turn_response("", Some(audio_url), hints.as_deref(), twilio, timing.timeout, &timing.speech_timeout)
+        },
+        ResponseKind::Dtmf(code) => {
+            debug!("Returning DTMF code: {}", code);
+            create_dtmf_response(code, hints.as_deref(), twilio)
+        },
+        // Normal text response, paced for the kind of answer the bot expects
+        ResponseKind::Ssml(response) | ResponseKind::Text(response) => {
+            let response = spoken.as_deref().unwrap_or(response);
+            let timing = gather_timing(response, Some(&result.metadata), twilio);
+            create_turn_response(response, None, hints.as_deref(), twilio, timing.timeout, &timing.speech_timeout)
+        },
+        // Default response if no response text found
+        ResponseKind::Empty => create_voice_response(
+            &catalog.tenant_text(Phrase::NotUnderstood, language, tenant_id.as_deref()), 
+            twilio, 
+            twilio.default_timeout, 
+            "auto"
+        ),
+    };
+    
+    let responded_at = chrono::Utc::now();
+    let response = redacted.map(|r| r.text);
+    analytics_tap().send(&TapTurn::new(
+        call_sid,
+        session_id,
+        input.text.clone(),
+        response.clone(),
+        result.audio().map(|u| u.to_string()),
+        decision,
+        input.at,
+        responded_at,
+    ), &config.analytics_tap);
+    
+    audit.record(call_sid, Some(session_id), AuditEntry::Turn {
+        input: input.text,
+        response,
+        audio_url: result.audio().map(|u| u.to_string()),
+        decision: decision.to_string(),
+        target,
+        input_at: input.at,
+        responded_at,
+    }).await;
+    
+    twiml
+}
+
+/// Handoff forced by the backend's escalation risk crossing the configured threshold
+///
+/// Only applies to turns the backend neither hands off nor ends itself.
+fn risk_handoff<'a>(result: &RunResponse, config: &'a Config) -> Option<Handoff<'a>> {
+    let threshold = config.sentiment.transfer_threshold?;
+    let risk = result.metadata.escalation_risk?;
+    if risk < threshold || result.handoff().is_some() || result.metadata.session_ends {
+        return None;
+    }
+    Some(match config.sentiment.transfer_target.as_deref() {
+        Some(target) => Handoff::Number(target),
+        None => Handoff::Queue(None),
+    })
+}
+
+/// What the bot did with a turn, and who it handed the caller to, for the audit log
+fn audit_decision(kind: &ResponseKind, config: &Config) -> (&'static str, Option<String>) {
+    match kind {
+        ResponseKind::Transfer(Handoff::Queue(queue)) => ("queue", Some(queue.unwrap_or(&config.twilio.agent_queue).to_string())),
+        ResponseKind::Transfer(Handoff::Number(target)) => ("transfer", Some(target.to_string())),
+        ResponseKind::Transfer(Handoff::Escalation { target, .. }) => ("escalation", Some(target.to_string())),
+        ResponseKind::End => ("hangup", None),
+        ResponseKind::Menu(_) => ("menu", None),
+        ResponseKind::Dtmf(_) => ("dtmf", None),
+        ResponseKind::AudioUrl(_) | ResponseKind::Ssml(_) | ResponseKind::Text(_) => ("reply", None),
+        ResponseKind::Empty => ("not_understood", None),
+    }
+}
+
+/// Play DTMF digits the backend asked for, then keep listening for speech
+fn create_dtmf_response(code: &str, hints: Option<&str>, twilio: &TwilioConfig) -> TwiML {
+    let action_url = twilio.callback_url("/transcription_callback");
+    let partial_callback_url = twilio.callback_url("/partial_callback");
+    let merged_hints = merge_hints(hints, twilio.speech_hints.as_deref());
+    let synthesized = SynthesizedVoice::from_config(twilio);
+
+    let gather_options = crate::twilio::twiml::GatherOptions {
+        input: Some("speech"),
+        action: Some(&action_url),
+        method: Some("POST"),
+        timeout: Some(10),
+        speech_timeout: Some("auto"),
+        barge_in: Some(true),
+        partial_result_callback: Some(&partial_callback_url),
+        speech_model: Some(&twilio.speech_model),
+        enhanced: twilio.speech_enhanced.then_some(true),
+        language: twilio.language.as_deref(),
+        say_text: Some(code),
+        voice: Some(&twilio.voice),
+        num_digits: None,
+        hints: merged_hints.as_deref(),
+        play_url: None,
+        speech_rate: twilio.speech_rate.as_deref(),
+        synthesized: synthesized.as_ref(),
+    };
+    
+    TwiML::new()
+        .gather(gather_options)
+        .play_digits(code)
+}
+
+/// Handle IVR menu selections and timeouts from Twilio
+#[post("/menu_callback", data = "<form>")]
+pub async fn handle_menu_callback(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let turn = sessions.lock_turn(&call_sid).await;
+    
+    debug!(
+        "Menu selection for call {}: digits={:?} speech={:?}",
+        call_sid, form.digits, form.speech_result.as_deref().map(|s| config.redaction.redacted(s))
+    );
+    
+    let (session_id, menu, twilio, tenant_id) = {
+        match sessions.lock_session_by_conversation(&call_sid).await {
+            Some(session) => (
+                session.session_id.clone(),
+                session.active_menu.clone(),
+                session.twilio_config(&config),
+                session.tenant_id.clone(),
+            ),
+            None => {
+                error!("No session found for call {}", call_sid);
+                let language = config.twilio.language.as_deref();
+                return create_hangup_response(Some(&catalog.text(Phrase::SessionExpired, language)), &config.twilio);
+            }
+        }
+    };
+    let language = twilio.language.as_deref();
+    
+    let menu = match menu {
+        Some(menu) => menu,
+        None => {
+            // No menu pending, continue the regular conversation
+            return create_voice_response("", &twilio, twilio.default_timeout, "auto");
+        }
+    };
+    
+    let no_input = form.digits.as_deref().unwrap_or("").is_empty()
+        && form.speech_result.as_deref().unwrap_or("").is_empty();
+    
+    let selection = if no_input {
+        match &menu.timeout_action {
+            MenuTimeoutAction::Repeat => {
+                return create_menu_response(&menu, None, &twilio);
+            },
+            MenuTimeoutAction::Hangup => {
+                if let Some(mut session) = sessions.lock_session(&session_id).await {
+                    session.session_ends = true;
+                    session.hangup_source = Some(HangupSource::Bot);
+                }
+                return create_hangup_response(None, &twilio);
+            },
+            MenuTimeoutAction::Select(value) => {
+                menu.option_by_value(value).map(|option| (option, SelectionInput::Timeout))
+            },
+        }
+    } else {
+        menu.match_selection(form.digits.as_deref(), form.speech_result.as_deref())
+    };
+    
+    let (option, input) = match selection {
+        Some(selection) => selection,
+        None => {
+            debug!("No menu option matched for call {}", call_sid);
+            return create_menu_response(
+                &menu,
+                Some(&catalog.tenant_text(Phrase::NotUnderstood, language, tenant_id.as_deref())),
+                &twilio
+            );
+        }
+    };
+    
+    let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create backend client: {}", e);
+            return create_hangup_response(
+                Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), 
+                &twilio
+            );
+        }
+    };
+    
+    {
+        if let Some(mut session) = sessions.lock_session(&session_id).await {
+            session.active_menu = None;
+            session.generation = true;
+        }
+    }
+    drop(turn);
+    
+    // Report the selection as a structured event rather than free text
+    let turn_input = TurnInput::new(option.value.clone());
+    let mut kwargs = HashMap::new();
+    kwargs.insert("event".to_string(), serde_json::json!({
+        "type": "menu_selection",
+        "value": option.value,
+        "key": option.key,
+        "input": input,
+        "digits": form.digits,
+        "speech": form.speech_result,
+    }));
+    
+    let turn = {
+        let call_sid = call_sid.clone();
+        let value = option.value.clone();
+        let (sessions, catalog, replicator, audit) =
+            (sessions.inner().clone(), catalog.inner().clone(), replicator.inner().clone(), audit.inner().clone());
+        let config = Config::clone(&config);
+        let (twilio, tenant_id) = (twilio.clone(), tenant_id.clone());
+        async move {
+            let language = twilio.language.as_deref();
+            let result = backend_client.run_with_retry(
+                &session_id,
+                &value,
+                kwargs,
+                config.backend.retry_attempts,
+                config.backend.retry_base_delay_ms
+            ).await;
+            let _turn = sessions.lock_turn(&call_sid).await;
+            match result {
+                Ok(result) => {
+                    respond_to_run_result(&result, turn_input, &session_id, &call_sid, &sessions, &catalog, &replicator, &audit, &config).await
+                },
+                Err(e) => {
+                    {
+                        if let Some(mut session) = sessions.lock_session(&session_id).await {
+                            session.generation = false;
+                            session.active_menu = Some(menu.clone());
+                        }
+                    }
+                    
+                    error!("Failed to report menu selection to backend: {}", e);
+                    if let Some(twiml) = outage_turn_response(&e, &session_id, &sessions, &catalog, &replicator, &config).await {
+                        return twiml;
+                    }
+                    create_menu_response(
+                        &menu,
+                        Some(&catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref())),
+                        &twilio
+                    )
+                }
+            }
+        }
+    };
+    within_webhook_timeout(&call_sid, turn, sessions, catalog, &config).await
+}
+
+/// Handle partial speech results from Twilio
+#[post("/partial_callback", data = "<form>")]
+pub async fn handle_partial_callback(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> Status {
+    let form = form.into_inner();
+    
+    if !config.twilio.partial_processing {
+        return Status::Ok;
+    }
+    
+    let call_sid = form.call_sid.unwrap_or_default();
+    let unstable_speech_result = form.unstable_speech_result.unwrap_or_default();
+    
+    debug!("Partial speech result for call {}: {}", call_sid, config.redaction.redacted(&unstable_speech_result));
+    
+    if unstable_speech_result.trim().is_empty() {
+        return Status::Ok;
+    }
+    
+    let turn = sessions.lock_turn(&call_sid).await;
+    process_partial(turn, &call_sid, unstable_speech_result, sessions, backends, &config).await
+}
+
+/// Start speculative generation for a partial transcription that looks complete
+///
+/// Used for Gather partial results and for interim results of streaming speech
+/// recognition. `turn` is the call's turn lock, released once the speculative
+/// run is marked in the session.
+pub async fn process_partial(
+    turn: TurnGuard,
+    call_sid: &str,
+    unstable_speech_result: String,
+    sessions: &SessionStore,
+    backends: &BackendPool,
+    config: &Config,
+) -> Status {
+    // Get session info with write lock
+    let (session_id, should_process) = {
+        if let Some(mut session) = sessions.lock_session_by_conversation(call_sid).await {
+            session.last_speech = Some(std::time::Instant::now());
+            if session.session_ends || session.on_hold || session.takeover.is_some() {
+                return Status::Ok;
+            }
+            
+            // Wait for the partial to settle before spending a backend run on it
+            let stable = session.partial_debouncer.observe(
+                &unstable_speech_result,
+                Duration::from_millis(config.twilio.partial_stability_ms),
+                config.twilio.partial_stability_count
+            );
+            
+            // Only speculate on partials that look like the caller finished speaking
+            let language = session.detected_language.as_deref().or(config.twilio.language.as_deref());
+            let complete = speech::is_complete_utterance(
+                &unstable_speech_result,
+                session.partial_debouncer.unchanged_for(),
+                language,
+                &config.utterance
+            );
+            
+            let should_process = stable && complete && (!session.generation || 
+                                !session.unstable_speech_result_is_the_same(&unstable_speech_result));
+            
+            if should_process {
+                // Update session state
+                session.run_in_progress = true;
+                session.speech_in_progress = false;
+                session.unstable_speech_result = Some(unstable_speech_result.clone());
+                session.generation = true;
+            }
+            
+            (session.session_id.clone(), should_process)
+        } else {
+            return Status::Ok;
+        }
+    };
+    drop(turn);
+    
+    if should_process {
+        // Start speculative generation
+        debug!("Starting speculative generation for partial result: {}", config.redaction.redacted(&unstable_speech_result));
+        
+        let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to create backend client: {}", e);
+                return Status::InternalServerError;
+            }
+        };
+        
+        // Send unstable speech result to backend as a "start" command
+        if let Err(e) = backend_client.start(&session_id, &unstable_speech_result).await {
+            error!("Failed to start backend generation: {}", e);
+            
+            // Reset generation flag on error
+            if let Some(mut session) = sessions.lock_session(&session_id).await {
+                session.generation = false;
+            }
+            
+            return Status::InternalServerError;
+        }
+        
+        if let Some(mut session) = sessions.lock_session(&session_id).await {
+            session.speech_stats.record_speculation_started();
+        }
+    }
+    
+    Status::Ok
+}
+
+/// Handle queue callback from Twilio
+#[post("/queue_callback", data = "<form>")]
+pub async fn handle_call_queue(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    
+    debug!("Queue callback for call {}", call_sid);
+    let _turn = sessions.lock_turn(&call_sid).await;
+    
+    // In cluster mode the replica streaming the reply may be another one
+    sessions.sync_conversation(&call_sid).await;
+    let shared = match sessions.get_session_id_by_conversation(&call_sid) {
+        Some(session_id) => sessions.take_shared_messages(&session_id).await,
+        None => Vec::new(),
+    };
+    
+    let mut buffer = Vec::new();
+    let mut eoc = false;
+    let mut eos = false;
+    let mut text = String::new();
+    let mut twilio = config.twilio.clone();
+    // Session of replies the backend pushed outside a streamed turn, which are mirrored to the analytics tap here
+    let mut pushed_by = None;
+    
+    // Process message queue
+    {
+        if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+            twilio = session.twilio_config(&config);
+            for message in session.message_rx.drain().into_iter().chain(shared) {
+                match message {
+                    MessageType::Text(text) => buffer.push(text),
+                    MessageType::EndOfConversation => eoc = true,
+                    MessageType::EndOfStream => eos = true,
+                }
+            }
+            let tail = std::mem::take(&mut session.streamed_tail);
+            
+            // A turn that outlasted its webhook or streamed its reply answers here once it is done
+            if buffer.is_empty() && tail.is_empty() && !eos && !eoc {
+                if let Some(twiml) = session.late_reply.take() {
+                    session.turn_overdue = false;
+                    return twiml;
+                }
+                if session.turn_overdue {
+                    return create_turn_wait_response(None, &twilio);
+                }
+            }
+            if !buffer.is_empty() {
+                session.speech_stats.record_reply();
+            }
+            // A streamed turn's reply is mirrored with its run's result instead
+            if !buffer.is_empty() && !session.turn_overdue && !eos {
+                pushed_by = Some(session.session_id.clone());
+            }
+            
+            let streaming = !buffer.is_empty() && !eos && !eoc;
+            if !tail.is_empty() {
+                buffer.insert(0, tail);
+            }
+            text = buffer.join(" ");
+            
+            // While more text is streaming in, an unfinished sentence waits for its end
+            if streaming {
+                let (sentences, unfinished) = postprocess::split_unfinished_sentence(&text);
+                if !sentences.is_empty() {
+                    session.streamed_tail = unfinished.to_string();
+                    text = sentences.to_string();
+                }
+            }
+        }
+    }
+    
+    // Streamed text is spoken like a run's response, in the session's voice and language
+    let text = postprocess::process(&text, &config.postprocess, &config.redaction, twilio.language.as_deref());
+    let text = postprocess::chunk_streamed(&text, config.postprocess.max_chunk_chars);
+    
+    if let Some(session_id) = pushed_by.filter(|_| !text.is_empty()) {
+        let now = chrono::Utc::now();
+        analytics_tap().send(&TapTurn::new(
+            &call_sid,
+            &session_id,
+            String::new(),
+            Some(config.redaction.redacted(&text.replace(SAY_CHUNK_BREAK, " "))),
+            None,
+            "queued",
+            now,
+            now,
+        ), &config.analytics_tap);
+    }
+    
+    if eoc {
+        create_hangup_response(if text.is_empty() { None } else { Some(&text) }, &twilio)
+    } else if !eos && !text.is_empty() {
+        // Come back for the rest of the stream
+        create_queue_poll_response(&text, &twilio)
+    } else {
+        let (timeout, speech_timeout) = if eos {
+            let timing = gather_timing(&text, None, &twilio);
+            (timing.timeout, timing.speech_timeout)
+        } else {
+            (1, "1".to_string())
+        };
+        
+        create_voice_response(&text, &twilio, timeout, &speech_timeout)
+    }
+}
+
+/// Keep a silent caller on the line with soft prompts
+///
+/// Twilio reaches this when a speech Gather ends without input. While the bot
+/// is still producing a reply the caller goes back to the message queue;
+/// otherwise they are prompted up to `KEEPALIVE_ATTEMPTS` times, none by
+/// default, before the call ends and the silence is reported to the backend.
+#[post("/keepalive?<attempt>", data = "<form>")]
+pub async fn handle_keepalive(
+    attempt: u32,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let _turn = sessions.lock_turn(&call_sid).await;
+    let give_up = attempt > config.twilio.keepalive_attempts;
+    
+    let (session_id, silence, twilio, tenant_id) = match sessions.lock_session_by_conversation(&call_sid).await {
+        Some(mut session) => {
+            if session.session_ends {
+                return create_hangup_response(None, &config.twilio);
+            }
+            if session.activity() == SessionActivity::Speaking {
+                return TwiML::new().redirect(&config.twilio.callback_url("/queue_callback"));
+            }
+            
+            let silence = session.silence.get_or_insert_with(|| SilencePeriod {
+                started: chrono::Utc::now(),
+                prompts: 0,
+            });
+            if !give_up {
+                silence.prompts = attempt;
+            }
+            let silence = silence.clone();
+            
+            if give_up {
+                session.session_ends = true;
+                session.disposition = Some("silence".to_string());
+                session.hangup_source = Some(HangupSource::Bot);
+                replicator.replicate(&mut session, ReplicaState::Ending);
+            }
+            
+            (session.session_id.clone(), silence, session.twilio_config(&config), session.tenant_id.clone())
+        },
+        None => {
+            debug!("No session found for silent call {}", call_sid);
+            return create_hangup_response(None, &config.twilio);
+        }
+    };
+    let language = twilio.language.as_deref();
+    
+    if give_up {
+        info!("Ending call {} after {} keepalive prompts", call_sid, silence.prompts);
+        let mut event = silence.to_json();
+        event["type"] = serde_json::json!("silence");
+        event["outcome"] = serde_json::json!("hangup");
+        report_event(session_id, event, sessions, backends, &config);
+        return create_hangup_response(Some(&catalog.tenant_text(Phrase::SilenceHangup, language, tenant_id.as_deref())), &twilio);
+    }
+    
+    debug!("Keepalive prompt {} on call {}", attempt, call_sid);
+    create_keepalive_response(&catalog.tenant_text(Phrase::KeepalivePrompt, language, tenant_id.as_deref()), attempt, &twilio)
+}
+
+/// Play hold audio and the estimated wait to a caller waiting in a queue
+#[post("/queue_wait", data = "<form>")]
+pub async fn handle_queue_wait(
+    form: TwilioForm<QueueCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let queue_time = form.queue_time.unwrap_or(0);
+    
+    debug!("Call {} has waited {}s in queue", call_sid, queue_time);
+    
+    // Return the caller to the bot once they have waited too long
+    let max_wait = config.twilio.queue_max_wait_seconds;
+    if max_wait > 0 && queue_time >= max_wait {
+        info!("Call {} exceeded the maximum queue wait", call_sid);
+        return TwiML::new().leave();
+    }
+    
+    let (twilio, tenant_id) = call_prompt_settings(sessions, &call_sid, &config).await;
+    let announcement = match form.queue_sid {
+        Some(queue_sid) => queue_wait_announcement(&queue_sid, catalog, twilio.language.as_deref(), tenant_id.as_deref(), &config).await,
+        None => None,
+    };
+    
+    create_queue_wait_response(announcement.as_deref(), &twilio)
+}
+
+/// Build the estimated wait announcement from the queue's current statistics
+async fn queue_wait_announcement(
+    queue_sid: &str,
+    catalog: &MessageCatalog,
+    language: Option<&str>,
+    tenant_id: Option<&str>,
+    config: &Config,
+) -> Option<String> {
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return None;
+        }
+    };
+    
+    let queue = match twilio_client.get_queue(queue_sid).await {
+        Ok(queue) => queue,
+        Err(e) => {
+            warn!("Failed to fetch statistics for queue {}: {}", queue_sid, e);
+            return None;
+        }
+    };
+    
+    let minutes = queue.average_wait_time.div_ceil(60).max(1);
+    Some(
+        catalog.tenant_text(Phrase::QueueWaitEstimate, language, tenant_id)
+            .replace("{minutes}", &minutes.to_string())
+    )
+}
+
+/// Notify the backend when an agent dequeues a caller
+///
+/// Point the agent leg's `<Queue url>` here; Twilio requests it before bridging
+/// the calls, with `CallSid` set to the dequeued caller.
+#[post("/queue_bridge", data = "<form>")]
+pub async fn handle_queue_bridge(
+    form: TwilioForm<QueueCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    
+    let (session_id, queue) = match sessions.lock_session_by_conversation(&call_sid).await {
+        Some(session) => (session.session_id.clone(), session.queue.clone()),
+        None => {
+            warn!("Agent picked up call {} without a session", call_sid);
+            return TwiML::new();
+        }
+    };
+    
+    info!("Agent connected to call {} from queue {:?}", call_sid, queue);
+    
+    // Report in the background so the bridge is not delayed
+    report_event(session_id, serde_json::json!({
+        "type": "agent_connected",
+        "queue": queue,
+        "queue_sid": form.queue_sid,
+        "queue_time": form.queue_time,
+        "agent_call_sid": form.dequeuing_call_sid,
+    }), sessions, backends, &config);
+    
+    TwiML::new()
+}
+
+/// Report a call event to the session's backend in the background, ignoring its response
+fn report_event(
+    session_id: String,
+    event: serde_json::Value,
+    sessions: &Arc<SessionStore>,
+    backends: &Arc<BackendPool>,
+    config: &Config,
+) {
+    let (sessions, backends, config) = (sessions.clone(), backends.clone(), config.clone());
+    tokio::spawn(async move {
+        let backend_client = match backends.for_session(&sessions, &session_id, &config.backend).await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to create backend client: {}", e);
+                return;
+            }
+        };
+        
+        if let Err(e) = backend_client.run_event_with_retry(
+            &session_id,
+            event,
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await {
+            error!("Failed to report event for session {}: {}", session_id, e);
+        }
+    });
+}
+
+/// Handle a caller leaving an agent queue
+///
+/// Callers who reached an agent are hung up once the bridge ends; callers who
+/// left for any other reason (e.g. the wait limit) return to the bot.
+#[post("/queue_result", data = "<form>")]
+pub async fn handle_queue_result(
+    form: TwilioForm<QueueCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let queue_result = form.queue_result.unwrap_or_default();
+    
+    info!("Call {} left queue: {}", call_sid, queue_result);
+    
+    let (session_id, queue, twilio, tenant_id) = {
+        match sessions.lock_session_by_conversation(&call_sid).await {
+            Some(mut session) => {
+                let queue = session.queue.take();
+                
+                if queue_result == "bridged" {
+                    session.disposition = Some("agent_handled".to_string());
+                    session.session_ends = true;
+                    replicator.replicate(&mut session, ReplicaState::Ending);
+                } else if queue_result != "hangup" {
+                    session.generation = true;
+                }
+                
+                (session.session_id.clone(), queue, session.twilio_config(&config), session.tenant_id.clone())
+            },
+            None => {
+                error!("No session found for call {}", call_sid);
+                return create_hangup_response(None, &config.twilio);
+            }
+        }
+    };
+    let language = twilio.language.as_deref();
+    
+    if queue_result == "bridged" || queue_result == "hangup" {
+        return create_hangup_response(None, &twilio);
+    }
+    
+    let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create backend client: {}", e);
+            return create_hangup_response(
+                Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), 
+                &twilio
+            );
+        }
+    };
+    
+    // Let the backend decide how to continue without an agent
+    let event = serde_json::json!({
+        "type": "queue_result",
+        "result": queue_result,
+        "queue": queue,
+        "queue_time": form.queue_time,
+    });
+    let input = TurnInput::new(event.to_string());
+    match backend_client.run_event_with_retry(
+        &session_id,
+        event,
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        Ok(result) => {
+            respond_to_run_result(&result, input, &session_id, &call_sid, sessions.inner(), catalog.inner(), replicator.inner(), audit, &config).await
+        },
+        Err(e) => {
+            if let Some(mut session) = sessions.lock_session(&session_id).await {
+                session.generation = false;
+            }
+            
+            error!("Failed to report queue result to backend: {}", e);
+            create_voice_response(
+                &catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref()),
+                &twilio,
+                twilio.default_timeout,
+                "auto"
+            )
+        }
+    }
+}
+
+/// Whether a `<Dial>` reached its target, from its `DialCallStatus`
+///
+/// Twilio reports `answered` instead of `completed` while the caller is still bridged.
+fn dial_answered(dial_status: &str) -> bool {
+    matches!(dial_status, "completed" | "answered")
+}
+
+/// Handle the end of a transfer started with `<Dial>`
+///
+/// Answered transfers end the call, after a rating survey when enabled. Callers
+/// nobody answered for leave a message when voicemail is enabled, otherwise
+/// they return to the bot.
+#[post("/transfer_result", data = "<form>")]
+pub async fn handle_transfer_result(
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let dial_status = form.dial_call_status.unwrap_or_default();
+    let answered = dial_answered(&dial_status);
+    
+    info!("Transfer of call {} ended: {}", call_sid, dial_status);
+    
+    let (session_id, target, twilio, tenant_id) = {
+        match sessions.lock_session_by_conversation(&call_sid).await {
+            Some(mut session) => {
+                // A failed transfer returns the caller to the bot, so only an answered one keeps its target
+                let target = if answered {
+                    session.transfer_target.clone()
+                } else {
+                    session.transfer_target.take()
+                };
+                session.metadata.insert(TRANSFER_METADATA_KEY.to_string(), serde_json::json!({
+                    "target": target,
+                    "result": dial_status,
+                    "duration": form.dial_call_duration,
+                }));
+                
+                if answered {
+                    session.disposition = Some("transferred".to_string());
+                    session.hangup_source = Some(HangupSource::TransferTarget);
+                    session.session_ends = true;
+                    replicator.replicate(&mut session, ReplicaState::Ending);
+                } else if config.twilio.transfer_voicemail_enabled {
+                    session.disposition = Some("transfer_voicemail".to_string());
+                    session.session_ends = true;
+                    replicator.replicate(&mut session, ReplicaState::Ending);
+                } else {
+                    session.generation = true;
+                }
+                
+                (session.session_id.clone(), target, session.twilio_config(&config), session.tenant_id.clone())
+            },
+            None => {
+                error!("No session found for call {}", call_sid);
+                return create_hangup_response(None, &config.twilio);
+            }
+        }
+    };
+    let language = twilio.language.as_deref();
+    
+    let event = serde_json::json!({
+        "type": "transfer_result",
+        "result": dial_status,
+        "target": target,
+        "duration": form.dial_call_duration,
+    });
+    
+    if answered || config.twilio.transfer_voicemail_enabled {
+        report_event(session_id, event, sessions, backends, &config);
+        
+        return if answered && config.twilio.transfer_survey_enabled {
+            create_survey_response(&catalog.tenant_text(Phrase::TransferSurvey, language, tenant_id.as_deref()), &twilio)
+        } else if answered {
+            create_hangup_response(None, &twilio)
+        } else {
+            create_transfer_voicemail_response(&catalog.tenant_text(Phrase::TransferVoicemail, language, tenant_id.as_deref()), &twilio)
+        };
+    }
+    
+    let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create backend client: {}", e);
+            return create_hangup_response(
+                Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), 
+                &twilio
+            );
+        }
+    };
+    
+    // Let the backend decide how to continue without the transfer
+    let input = TurnInput::new(event.to_string());
+    match backend_client.run_event_with_retry(
+        &session_id,
+        event,
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        Ok(result) => {
+            respond_to_run_result(&result, input, &session_id, &call_sid, sessions.inner(), catalog.inner(), replicator.inner(), audit, &config).await
+        },
+        Err(e) => {
+            if let Some(mut session) = sessions.lock_session(&session_id).await {
+                session.generation = false;
+            }
+            
+            error!("Failed to report transfer result to backend: {}", e);
+            create_voice_response(
+                &catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref()),
+                &twilio,
+                twilio.default_timeout,
+                "auto"
+            )
+        }
+    }
+}
+
+/// Report the message a caller left after an unanswered transfer
+#[post("/transfer_voicemail", data = "<form>")]
+pub async fn handle_transfer_voicemail(
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    
+    sessions.sync_conversation(&call_sid).await;
+    if let Some(session_id) = sessions.get_session_id_by_conversation(&call_sid) {
+        info!("Caller left a message on call {}", call_sid);
+        report_event(session_id, serde_json::json!({
+            "type": "voicemail",
+            "recording_url": form.recording_url,
+            "duration": form.recording_duration,
+        }), sessions, backends, &config);
+    }
+    
+    create_hangup_response(None, &config.twilio)
+}
+
+/// Conference room holding an escalated call
+pub fn escalation_conference(call_sid: &str) -> String {
+    format!("escalation-{}", call_sid)
+}
+
+/// Dial the escalation party in the background, whispering context before joining them to the conference
+///
+/// If the dial cannot be placed the caller is taken out of the conference.
+fn start_escalation(call_sid: String, target: String, whisper: String, twilio: TwilioConfig, config: Config) {
+    tokio::spawn(async move {
+        let twiml = create_escalation_whisper_response(&whisper, &escalation_conference(&call_sid), &twilio);
+        let status_callback = twilio.callback_url(&format!("/escalation_status?call_sid={}", urlencoding::encode(&call_sid)));
+        
+        let created = match TwilioClient::new(
+            config.twilio.account_sid.clone(),
+            config.twilio.auth_token.clone(),
+            config.twilio.region.clone(),
+            config.twilio.edge.clone()
+        ) {
+            Ok(client) => client.create_call_with_retry(
+                &target,
+                &config.twilio.from_number,
+                &twiml.build(),
+                &status_callback,
+                &CallOptions::default(),
+                config.backend.retry_attempts,
+                config.backend.retry_base_delay_ms
+            ).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        
+        match created {
+            Ok(call) => info!("Dialing {} into call {} as {}", target, call_sid, call.sid),
+            Err(e) => {
+                error!("Failed to dial {} into call {}: {}", target, call_sid, e);
+                end_escalation_wait(&call_sid, "failed", &config).await;
+            }
+        }
+    });
+}
+
+/// Take the caller out of the escalation conference and back to the bot
+async fn end_escalation_wait(call_sid: &str, outcome: &str, config: &Config) {
+    let action_url = config.twilio.callback_url(&format!("/escalation_result?outcome={}", urlencoding::encode(outcome)));
+    let twiml = TwiML::new().redirect(&action_url).build();
+    
+    let updated = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client.update_call_with_retry(
+            call_sid,
+            &twiml,
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    
+    if let Err(e) = updated {
+        error!("Failed to return call {} from escalation: {}", call_sid, e);
+    }
+}
+
+/// Handle status updates of the escalation party's call leg
+///
+/// Answered legs are reported to the backend; legs that never connect return
+/// the waiting caller to the bot.
+#[post("/escalation_status?<call_sid>", data = "<form>")]
+pub async fn handle_escalation_status(
+    call_sid: &str,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> Status {
+    let form = form.into_inner();
+    let leg_status = form.call_status.unwrap_or_default();
+    
+    debug!("Escalation leg {} of call {}: {}", form.call_sid.as_deref().unwrap_or_default(), call_sid, leg_status);
+    
+    match leg_status.as_str() {
+        "in-progress" | "answered" => {
+            sessions.sync_conversation(call_sid).await;
+            let Some((session_id, target)) = sessions.lock_session_by_conversation(call_sid).await.map(|mut session| {
+                let target = session.escalation_target.clone();
+                session.escalation_call_sid = form.call_sid.clone();
+                session.metadata.insert("escalation".to_string(), serde_json::json!({
+                    "target": target,
+                    "result": "answered",
+                }));
+                (session.session_id.clone(), target)
+            }) else {
+                return Status::Ok;
+            };
+            
+            info!("Escalation of call {} answered by {}", call_sid, target.as_deref().unwrap_or_default());
+            report_event(session_id, serde_json::json!({
+                "type": "escalation_result",
+                "result": "answered",
+                "target": target,
+            }), sessions, backends, &config);
+        },
+        "completed" => {
+            if let Some(mut session) = sessions.lock_session_by_conversation(call_sid).await {
+                session.escalation_call_sid = None;
+            }
+        },
+        "busy" | "no-answer" | "failed" | "canceled" => {
+            info!("Escalation of call {} not answered: {}", call_sid, leg_status);
+            end_escalation_wait(call_sid, &leg_status, &config).await;
+        },
+        _ => {},
+    }
+    
+    Status::Ok
+}
+
+/// Handle status updates of a supervisor's coaching leg
+#[post("/coach_status?<call_sid>", data = "<form>")]
+pub async fn handle_coach_status(
+    call_sid: &str,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+) -> Status {
+    let form = form.into_inner();
+    let leg_sid = form.call_sid.unwrap_or_default();
+    let leg_status = form.call_status.unwrap_or_default();
+    
+    debug!("Coach leg {} of call {}: {}", leg_sid, call_sid, leg_status);
+    
+    let Some(mut session) = sessions.lock_session_by_conversation(call_sid).await else {
+        return Status::Ok;
+    };
+    // Ignore legs replaced by a later coach
+    if session.coach.as_ref().map(|c| c.call_sid.as_str()) != Some(leg_sid.as_str()) {
+        return Status::Ok;
+    }
+    
+    match leg_status.as_str() {
+        "in-progress" | "answered" => {
+            info!("Supervisor joined call {} as coach", call_sid);
+            if let Some(coach) = session.coach.as_mut() {
+                coach.connected = true;
+            }
+        },
+        "completed" | "busy" | "no-answer" | "failed" | "canceled" => {
+            info!("Supervisor left call {}: {}", call_sid, leg_status);
+            session.coach = None;
+        },
+        _ => {},
+    }
+    
+    Status::Ok
+}
+
+/// Conference room holding a call taken over by an agent
+pub fn takeover_conference(call_sid: &str) -> String {
+    format!("takeover-{}", call_sid)
+}
+
+/// Take the caller out of the takeover conference and back to the bot
+pub async fn end_takeover_wait(call_sid: &str, outcome: &str, config: &Config) {
+    let action_url = config.twilio.callback_url(&format!("/takeover_result?outcome={}", urlencoding::encode(outcome)));
+    let twiml = TwiML::new().redirect(&action_url).build();
+    
+    let updated = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client.update_call_with_retry(
+            call_sid,
+            &twiml,
+            config.backend.retry_attempts,
+            config.backend.retry_base_delay_ms
+        ).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    
+    if let Err(e) = updated {
+        error!("Failed to return call {} from takeover: {}", call_sid, e);
+    }
+}
+
+/// Handle status updates of the call leg of an agent taking a call over
+///
+/// Legs that never connect return the waiting caller to the bot.
+#[post("/takeover_status?<call_sid>", data = "<form>")]
+pub async fn handle_takeover_status(
+    call_sid: &str,
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> Status {
+    let form = form.into_inner();
+    let leg_sid = form.call_sid.unwrap_or_default();
+    let leg_status = form.call_status.unwrap_or_default();
+    
+    debug!("Takeover leg {} of call {}: {}", leg_sid, call_sid, leg_status);
+    
+    sessions.sync_conversation(call_sid).await;
+    let (session_id, agent) = {
+        let Some(mut session) = sessions.lock_session_by_conversation(call_sid).await else {
+            return Status::Ok;
+        };
+        let session_id = session.session_id.clone();
+        // Ignore legs of an earlier takeover
+        let Some(takeover) = session.takeover.as_mut().filter(|t| t.call_sid == leg_sid) else {
+            return Status::Ok;
+        };
+        if matches!(leg_status.as_str(), "in-progress" | "answered") {
+            takeover.connected = true;
+        }
+        (session_id, takeover.agent.clone())
+    };
+    
+    match leg_status.as_str() {
+        "in-progress" | "answered" => {
+            info!("Agent {} took over call {}", agent, call_sid);
+            report_event(session_id, serde_json::json!({
+                "type": "takeover_result",
+                "result": "answered",
+                "agent": agent,
+            }), sessions, backends, &config);
+        },
+        "busy" | "no-answer" | "failed" | "canceled" => {
+            info!("Takeover of call {} not answered: {}", call_sid, leg_status);
+            end_takeover_wait(call_sid, &leg_status, &config).await;
+        },
+        _ => {},
+    }
+    
+    Status::Ok
+}
+
+/// Handle the end of a takeover
+///
+/// Called with an `outcome` when the agent never joined, which hands the call
+/// back to the bot. Otherwise the conference ended after the agent spoke with
+/// the caller and the call is over.
+#[post("/takeover_result?<outcome>", data = "<form>")]
+pub async fn handle_takeover_result(
+    outcome: Option<&str>,
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let result = outcome.unwrap_or("completed");
+    
+    info!("Takeover of call {} ended: {}", call_sid, result);
+    
+    let (session_id, agent, twilio, tenant_id) = match sessions.lock_session_by_conversation(&call_sid).await {
+        Some(mut session) => {
+            let agent = session.takeover.take().map(|t| t.agent);
+            session.metadata.insert("takeover".to_string(), serde_json::json!({
+                "agent": agent,
+                "result": result,
+                "duration": form.dial_call_duration,
+            }));
+            
+            if outcome.is_none() {
+                session.disposition = Some("taken_over".to_string());
+                session.hangup_source = Some(HangupSource::TransferTarget);
+                session.session_ends = true;
+                replicator.replicate(&mut session, ReplicaState::Ending);
+            } else {
+                replicator.replicate(&mut session, ReplicaState::Active);
+            }
+            
+            (session.session_id.clone(), agent, session.twilio_config(&config), session.tenant_id.clone())
+        },
+        None => {
+            error!("No session found for call {}", call_sid);
+            return create_hangup_response(None, &config.twilio);
+        }
+    };
+    
+    report_event(session_id, serde_json::json!({
+        "type": "takeover_result",
+        "result": result,
+        "agent": agent,
+        "duration": form.dial_call_duration,
+    }), sessions, backends, &config);
+    
+    if outcome.is_none() {
+        return create_hangup_response(None, &config.twilio);
+    }
+    
+    // The bot picks the conversation back up where it left off
+    let text = catalog.tenant_text(Phrase::HoldResumed, twilio.language.as_deref(), tenant_id.as_deref());
+    create_voice_response(&text, &twilio, twilio.default_timeout, "auto")
+}
+
+/// Handle the end of an escalation
+///
+/// Called with an `outcome` when the escalation party never joined, which
+/// lets the backend continue the conversation. Otherwise the conference
+/// ended after the parties spoke and the call is over.
+#[post("/escalation_result?<outcome>", data = "<form>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_escalation_result(
+    outcome: Option<&str>,
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let result = outcome.unwrap_or("completed");
+    
+    info!("Escalation of call {} ended: {}", call_sid, result);
+    
+    let (session_id, target, twilio, tenant_id) = match sessions.lock_session_by_conversation(&call_sid).await {
+        Some(mut session) => {
+            let target = session.escalation_target.take();
+            session.escalation_call_sid = None;
+            session.coach = None;
+            session.metadata.insert("escalation".to_string(), serde_json::json!({
+                "target": target,
+                "result": result,
+                "duration": form.dial_call_duration,
+            }));
+            
+            if outcome.is_none() {
+                session.disposition = Some("escalated".to_string());
+                session.hangup_source = Some(HangupSource::TransferTarget);
+                session.session_ends = true;
+                replicator.replicate(&mut session, ReplicaState::Ending);
+            } else {
+                session.generation = true;
+            }
             
-            (
-                session.session_id.clone(),
-                session.session_ends,
-                is_same,
-                has_gen
-            )
-        } else {
-            // Session not found
+            (session.session_id.clone(), target, session.twilio_config(&config), session.tenant_id.clone())
+        },
+        None => {
             error!("No session found for call {}", call_sid);
-            return Xml(create_hangup_response(Some("Sorry, your session has expired."), &config.twilio));
+            return create_hangup_response(None, &config.twilio);
         }
     };
+    let language = twilio.language.as_deref();
     
-    // Check if we need to generate new response
-    let should_generate = if has_generation {
-        !is_same_result
-    } else {
-        true
+    let event = serde_json::json!({
+        "type": "escalation_result",
+        "result": result,
+        "target": target,
+        "duration": form.dial_call_duration,
+    });
+    
+    if outcome.is_none() {
+        report_event(session_id, event, sessions, backends, &config);
+        return create_hangup_response(None, &twilio);
+    }
+    
+    let backend_client = match backends.for_session(sessions, &session_id, &config.backend).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create backend client: {}", e);
+            return create_hangup_response(
+                Some(&catalog.tenant_text(Phrase::TechnicalDifficulties, language, tenant_id.as_deref())), 
+                &twilio
+            );
+        }
     };
     
-    if should_generate {
-        // Create backend client
-        let backend_client = match BackendClient::new(
-            &config.backend.url, 
-            config.backend.authorization_token.clone(),
-            config.backend.enable_circuit_breaker
-        ) {
-            Ok(client) => client,
+    // Let the backend decide how to continue without the escalation
+    let input = TurnInput::new(event.to_string());
+    match backend_client.run_event_with_retry(
+        &session_id,
+        event,
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        Ok(result) => {
+            respond_to_run_result(&result, input, &session_id, &call_sid, sessions.inner(), catalog.inner(), replicator.inner(), audit, &config).await
+        },
+        Err(e) => {
+            if let Some(mut session) = sessions.lock_session(&session_id).await {
+                session.generation = false;
+            }
+            
+            error!("Failed to report escalation result to backend: {}", e);
+            create_voice_response(
+                &catalog.tenant_text(Phrase::ProcessingError, language, tenant_id.as_deref()),
+                &twilio,
+                twilio.default_timeout,
+                "auto"
+            )
+        }
+    }
+}
+
+/// Send a follow-up SMS in the background and record it on the session
+///
+/// Delivery updates arrive at `/sms_status` and are recorded as they come in.
+fn send_follow_up_sms(call_sid: String, sms: SmsRequest, sessions: Arc<SessionStore>, config: Config) {
+    if sms.body.trim().is_empty() {
+        warn!("Ignoring SEND_SMS without a body for call {}", call_sid);
+        return;
+    }
+    
+    tokio::spawn(async move {
+        let to = match phone::validate(&sms.to, &config.twilio.allowed_country_codes) {
+            Ok(to) => to,
             Err(e) => {
-                error!("Failed to create backend client: {}", e);
-                return Xml(create_hangup_response(
-                    Some("Sorry, we're experiencing technical difficulties."), 
-                    &config.twilio
-                ));
+                warn!("Not sending SMS for call {}: {}", call_sid, e);
+                return;
             }
         };
+        let status_callback = config.twilio.callback_url(&format!("/sms_status?call_sid={}", urlencoding::encode(&call_sid)));
         
-        // Update session state
-        {
-            let mut store = sessions.write().await;
-            if let Some(session) = store.get_session_mut(&session_id) {
-                session.run_in_progress = true;
-                session.speech_in_progress = false;
-                session.unstable_speech_result = Some(transcription.clone());
-                session.generation = true;
-            }
-        }
+        let sent = match TwilioClient::new(
+            config.twilio.account_sid.clone(),
+            config.twilio.auth_token.clone(),
+            config.twilio.region.clone(),
+            config.twilio.edge.clone()
+        ) {
+            Ok(client) => client.send_message(&to, &config.twilio.from_number, &sms.body, &status_callback)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
         
-        // Send transcription to backend with retry
-        let kwargs = HashMap::new();
-        match backend_client.run_with_retry(
-            &session_id, 
-            &transcription, 
-            kwargs,
-            config.backend.retry_attempts,
-            config.backend.retry_base_delay_ms
-        ).await {
-            Ok(result) => {
-                // Update session state
-                let session_should_end = {
-                    let mut store = sessions.write().await;
-                    if let Some(session) = store.get_session_mut(&session_id) {
-                        session.generation = false;
-                        
-                        // Check if session should end
-                        let ends = result.get("metadata")
-                            .and_then(|m| m.get("SESSION_ENDS"))
-                            .and_then(|e| e.as_bool())
-                            .unwrap_or(false);
-                            
-                        if ends {
-                            session.session_ends = true;
-                            debug!("Session for call {} will end after this response", call_sid);
-                        }
-                        
-                        ends
-                    } else {
-                        false
-                    }
-                };
-                
-                if session_should_end {
-                    if let Some(response) = result.get("response").and_then(|r| r.as_str()) {
-                        return Xml(create_hangup_response(Some(response), &config.twilio));
-                    } else {
-                        return Xml(create_hangup_response(None, &config.twilio));
-                    }
-                }
-                
-                // Check for special code response format
-                if let Some(response) = result.get("response").and_then(|r| r.as_str()) {
-                    if response.starts_with("Code:") {
-                        // Handle DTMF code
-                        let code = &response[5..].trim();
-                        debug!("Returning DTMF code: {}", code);
-                        
-                        // Build TwiML with play digits
-                        let mut twiml = crate::twilio::twiml::TwiML::new();
-                        let action_url = format!("{}{}", config.inner().twilio.webhook_url, "/transcription_callback");
-                        let partial_callback_url = format!("{}{}", config.inner().twilio.webhook_url, "/partial_callback");
-
-                        let gather_options = crate::twilio::twiml::GatherOptions {
-                            input: Some("speech"),
-                            action: Some(&action_url),  // Reference to longer-lived string
-                            method: Some("POST"),
-                            timeout: Some(10),
-                            speech_timeout: Some("auto"),
-                            barge_in: Some(true),
-                            partial_result_callback: Some(&partial_callback_url),  // Reference to longer-lived string
-                            speech_model: Some(&config.inner().twilio.speech_model),
-                            language: config.inner().twilio.language.as_deref(),
-                            say_text: Some(code),
-                            voice: Some(&config.inner().twilio.voice),
-                        };
-                        
-                        twiml = twiml.gather(gather_options);
-                        twiml = twiml.play_digits(code);
-                        
-                        return Xml(twiml.build());
-                    } else {
-                        // Normal text response
-                        return Xml(create_voice_response(response, &config.twilio, config.twilio.default_timeout, "auto"));
-                    }
-                }
-                
-                // Default response if no response text found
-                Xml(create_voice_response(
-                    "I'm sorry, I didn't understand that.", 
-                    &config.twilio, 
-                    config.twilio.default_timeout, 
-                    "auto"
-                ))
+        let entry = match sent {
+            Ok(message) => {
+                info!("Sent SMS {} to {} for call {}", message.sid, to, call_sid);
+                serde_json::json!({"sid": message.sid, "to": to, "status": message.status})
             },
             Err(e) => {
-                // Update session state
-                {
-                    let mut store = sessions.write().await;
-                    if let Some(session) = store.get_session_mut(&session_id) {
-                        session.generation = false;
-                    }
-                }
-                
-                error!("Failed to run backend command: {}", e);
-                Xml(create_voice_response(
-                    "I'm sorry, I'm having trouble processing your request right now.", 
-                    &config.twilio, 
-                    config.twilio.default_timeout, 
-                    "auto"
-                ))
+                error!("Failed to send SMS to {} for call {}: {}", to, call_sid, e);
+                serde_json::json!({"sid": null, "to": to, "status": "failed", "error": e})
+            },
+        };
+        
+        if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+            let messages = session.metadata.entry("sms".to_string()).or_insert_with(|| serde_json::json!([]));
+            if let Some(messages) = messages.as_array_mut() {
+                messages.push(entry);
             }
         }
-    } else {
-        // Re-use previous response
-        Xml(create_voice_response(
-            "Could you please repeat that?", 
-            &config.twilio, 
-            config.twilio.default_timeout, 
-            "auto"
-        ))
-    }
+    });
 }
 
-/// Handle partial speech results from Twilio
-#[post("/partial_callback", data = "<form>")]
-pub async fn handle_partial_callback(
-    form: Form<TwilioCallbackForm>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
-    config: &State<Config>,
+/// Handle delivery status updates of follow-up SMS
+///
+/// The status is recorded on the call's session while it is live, and final
+/// statuses are reported to the backend.
+#[post("/sms_status?<call_sid>", data = "<form>")]
+pub async fn handle_sms_status(
+    call_sid: &str,
+    form: TwilioForm<MessageStatusForm>,
+    sessions: &State<Arc<SessionStore>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
 ) -> Status {
     let form = form.into_inner();
+    let message_sid = form.message_sid.unwrap_or_default();
+    let message_status = form.message_status.unwrap_or_default();
     
-    if !config.twilio.partial_processing {
+    info!("SMS {} for call {}: {}", message_sid, call_sid, message_status);
+    
+    let Some(session_id) = sessions.lock_session_by_conversation(call_sid).await.map(|mut session| {
+        let message = session.metadata.get_mut("sms")
+            .and_then(|m| m.as_array_mut())
+            .and_then(|messages| messages.iter_mut().find(|m| m.get("sid").and_then(|s| s.as_str()) == Some(message_sid.as_str())));
+        if let Some(message) = message {
+            message["status"] = serde_json::json!(message_status);
+            if let Some(code) = &form.error_code {
+                message["error_code"] = serde_json::json!(code);
+            }
+        }
+        session.session_id.clone()
+    }) else {
+        debug!("SMS {} status arrived after call {} ended", message_sid, call_sid);
         return Status::Ok;
+    };
+    
+    if matches!(message_status.as_str(), "delivered" | "undelivered" | "failed") {
+        report_event(session_id, serde_json::json!({
+            "type": "sms_status",
+            "message_sid": message_sid,
+            "status": message_status,
+            "error_code": form.error_code,
+        }), sessions, backends, &config);
     }
     
+    Status::Ok
+}
+
+/// Handle the end of the dial to the fallback number during a backend outage
+///
+/// Unanswered dials fall back to taking a message.
+#[post("/outage_result", data = "<form>")]
+pub async fn handle_outage_result(
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
     let call_sid = form.call_sid.unwrap_or_default();
-    let unstable_speech_result = form.unstable_speech_result.unwrap_or_default();
+    let dial_status = form.dial_call_status.unwrap_or_default();
     
-    debug!("Partial speech result for call {}: {}", call_sid, unstable_speech_result);
+    info!("Outage transfer of call {} ended: {}", call_sid, dial_status);
     
-    // Check if speech ends with sentence punctuation
-    if !ends_with_sentence_punctuation(&unstable_speech_result) {
-        return Status::Ok;
+    if dial_answered(&dial_status) {
+        return create_hangup_response(None, &config.twilio);
     }
     
-    // Get session info with write lock
-    let (session_id, should_process) = {
-        let mut store = sessions.write().await;
-        
-        if let Some(session) = store.get_session_by_conversation_mut(&call_sid) {
-            if session.session_ends {
-                return Status::Ok;
-            }
-            
-            let should_process = !session.generation || 
-                                !session.unstable_speech_result_is_the_same(&unstable_speech_result);
-            
-            if should_process {
-                // Update session state
-                session.run_in_progress = true;
-                session.speech_in_progress = false;
-                session.unstable_speech_result = Some(unstable_speech_result.clone());
-                session.generation = true;
-            }
-            
-            (session.session_id.clone(), should_process)
-        } else {
-            return Status::Ok;
-        }
-    };
+    let (twilio, tenant_id) = call_prompt_settings(sessions, &call_sid, &config).await;
+    create_outage_voicemail_response(
+        None,
+        &catalog.tenant_text(Phrase::TransferVoicemail, twilio.language.as_deref(), tenant_id.as_deref()),
+        &twilio
+    )
+}
+
+/// Keep the message a caller left during a backend outage
+#[post("/outage_voicemail", data = "<form>")]
+pub async fn handle_outage_voicemail(
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
     
-    if should_process {
-        // Start speculative generation
-        debug!("Starting speculative generation for partial result: {}", unstable_speech_result);
-        
-        // Create backend client
-        let backend_client = match BackendClient::new(
-            &config.backend.url, 
-            config.backend.authorization_token.clone(),
-            config.backend.enable_circuit_breaker
-        ) {
-            Ok(client) => client,
-            Err(e) => {
-                error!("Failed to create backend client: {}", e);
-                return Status::InternalServerError;
-            }
-        };
-        
-        // Send unstable speech result to backend as a "start" command
-        if let Err(e) = backend_client.start(&session_id, &unstable_speech_result).await {
-            error!("Failed to start backend generation: {}", e);
-            
-            // Reset generation flag on error
-            let mut store = sessions.write().await;
-            if let Some(session) = store.get_session_mut(&session_id) {
-                session.generation = false;
-            }
-            
-            return Status::InternalServerError;
-        }
+    warn!(
+        "Caller left a message during a backend outage on call {}: {} ({}s)",
+        call_sid,
+        form.recording_url.as_deref().unwrap_or("no recording"),
+        form.recording_duration.unwrap_or_default()
+    );
+    
+    // Sent to the backend with the call summary when the call ends
+    if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+        session.metadata.insert("outage_voicemail".to_string(), serde_json::json!({
+            "recording_url": form.recording_url,
+            "duration": form.recording_duration,
+        }));
     }
     
-    Status::Ok
+    create_hangup_response(None, &config.twilio)
 }
 
-/// Handle queue callback from Twilio
-#[post("/queue_callback", data = "<form>")]
-pub async fn handle_call_queue(
-    form: Form<TwilioCallbackForm>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
-    config: &State<Config>,
-) -> Xml<String> {
+/// Keep the message a caller left outside business hours
+///
+/// There is no backend session, so the recording is kept on the call's record
+/// and in the audit log. The record is posted to the schedule's `callback_url`
+/// when the call ends, or right away if the call ended before the recording
+/// was ready; results that cannot be delivered are dead-lettered.
+#[post("/after_hours_voicemail", data = "<form>")]
+pub async fn handle_after_hours_voicemail(
+    form: TwilioForm<TransferCallbackForm>,
+    audit: &State<Arc<AuditLog>>,
+    cdrs: &State<Arc<CdrStore>>,
+    tenants: &State<Arc<TenantStore>>,
+    dead_letters: &State<Arc<DeadLetterStore>>,
+    config: CurrentConfig,
+) -> TwiML {
     let form = form.into_inner();
     let call_sid = form.call_sid.unwrap_or_default();
+    let to_number = form.to_number.unwrap_or_default();
     
-    debug!("Queue callback for call {}", call_sid);
+    info!(
+        "Caller left a message after hours on call {}: {} ({}s)",
+        call_sid,
+        form.recording_url.as_deref().unwrap_or("no recording"),
+        form.recording_duration.unwrap_or_default()
+    );
+    let voicemail = serde_json::json!({
+        "recording_url": form.recording_url,
+        "duration": form.recording_duration,
+    });
+    if let Some(mut record) = cdrs.add_voicemail(&call_sid, voicemail) {
+        // The call's result went out without the message, so it is sent again
+        if let Some(url) = config.business_hours.schedule(&to_number).and_then(|s| s.callback_url.clone()) {
+            record.tenant_id = record.tenant_id.take().or_else(|| tenants.find_by_number(&to_number).map(|t| t.id));
+            send_call_result(url, record, config.callbacks.clone(), dead_letters.inner().clone());
+        }
+    }
+    audit.record(&call_sid, None, AuditEntry::Decision {
+        decision: "voicemail".to_string(),
+        reason: form.recording_url,
+    }).await;
     
-    let mut buffer = Vec::new();
-    let mut eoc = false;
-    let mut eos = false;
+    create_hangup_response(None, &config.twilio)
+}
+
+/// Report the caller's rating from the post-transfer survey
+#[post("/transfer_survey", data = "<form>")]
+pub async fn handle_transfer_survey(
+    form: TwilioForm<TransferCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    backends: &State<Arc<BackendPool>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
     
-    // Process message queue
-    {
-        let mut store = sessions.write().await;
-        
-        if let Some(session) = store.get_session_by_conversation_mut(&call_sid) {
-            // In a real implementation, would process the queue here
-            // For now, just check if there are any pending messages
-            
-            // Example of how to process the queue:
-            let mut messages = Vec::new();
-            while let Ok(message) = session.message_rx.try_recv() {
-                messages.push(message);
-            }
-            
-            for message in messages {
-                match message {
-                    MessageType::Text(text) => buffer.push(text),
-                    MessageType::EndOfConversation => eoc = true,
-                    MessageType::EndOfStream => eos = true,
-                }
-            }
-        }
+    sessions.sync_conversation(&call_sid).await;
+    if let Some(session_id) = sessions.get_session_id_by_conversation(&call_sid) {
+        debug!(
+            "Survey answer for call {}: digits={:?} speech={:?}",
+            call_sid, form.digits, form.speech_result.as_deref().map(|s| config.redaction.redacted(s))
+        );
+        report_event(session_id, serde_json::json!({
+            "type": "survey",
+            "rating": form.digits,
+            "speech": form.speech_result,
+        }), sessions, backends, &config);
     }
     
-    let text = buffer.join(" ");
+    let (twilio, tenant_id) = call_prompt_settings(sessions, &call_sid, &config).await;
+    create_hangup_response(
+        Some(&catalog.tenant_text(Phrase::SurveyThanks, twilio.language.as_deref(), tenant_id.as_deref())),
+        &twilio
+    )
+}
+
+/// Start recording a call once its consent announcement has played, then greet the caller
+#[post("/recording_consent", data = "<form>")]
+pub async fn handle_recording_consent(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let call_sid = form.into_inner().call_sid.unwrap_or_default();
+    greet_after_consent(&call_sid, false, sessions, replicator, audit, &config).await
+}
+
+/// Handle a key pressed during the consent announcement
+///
+/// The opt-out key leaves the call unrecorded; any other key is ignored and the
+/// call is recorded. Either way the call goes on with the greeting.
+#[post("/recording_opt_out", data = "<form>")]
+pub async fn handle_recording_opt_out(
+    form: TwilioForm<TwilioCallbackForm>,
+    sessions: &State<Arc<SessionStore>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    config: CurrentConfig,
+) -> TwiML {
+    let form = form.into_inner();
+    let call_sid = form.call_sid.unwrap_or_default();
+    let opted_out = form.digits.is_some() && form.digits == config.recording.opt_out_digit;
+    greet_after_consent(&call_sid, opted_out, sessions, replicator, audit, &config).await
+}
+
+/// Start recording unless the caller opted out, and answer with the greeting
+async fn greet_after_consent(
+    call_sid: &str,
+    opted_out: bool,
+    sessions: &SessionStore,
+    replicator: &Arc<SessionReplicator>,
+    audit: &AuditLog,
+    config: &Config,
+) -> TwiML {
+    let (session_id, greeting, twilio) = match sessions.lock_session_by_conversation(call_sid).await {
+        Some(mut session) => {
+            if opted_out {
+                session.metadata.insert(RECORDING_METADATA_KEY.to_string(), serde_json::json!(RecordingDecision::OptedOut));
+                replicator.replicate(&mut session, ReplicaState::Active);
+            }
+            let twilio = session.twilio_config(config);
+            let greeting = session.metadata.get("initialization_response")
+                .and_then(|init_response| init_response.get("greeting"))
+                .and_then(|greeting| greeting.as_str())
+                .map(|greeting| postprocess::process(greeting, &config.postprocess, &config.redaction, twilio.language.as_deref()))
+                .unwrap_or_default();
+            (Some(session.session_id.clone()), greeting, twilio)
+        },
+        None => (None, String::new(), config.twilio.clone()),
+    };
     
-    if eoc {
-        Xml(create_hangup_response(if text.is_empty() { None } else { Some(&text) }, &config.twilio))
+    let text = if opted_out {
+        info!("Caller opted out of recording call {}", call_sid);
+        audit.record(call_sid, session_id.as_deref(), AuditEntry::Decision {
+            decision: "recording_opt_out".to_string(),
+            reason: None,
+        }).await;
+        format!("{} {}", config.recording.opt_out_message, greeting).trim().to_string()
     } else {
-        let timeout = if eos { config.twilio.default_timeout } else { 1 };
-        let speech_timeout = if eos { "auto" } else { "1" };
-        
-        let twiml = if text.is_empty() {
-            create_voice_response("", &config.twilio, timeout, speech_timeout)
-        } else {
-            let mut response = create_voice_response(&text, &config.twilio, timeout, speech_timeout);
-            
-            // Add redirect
-            response = response.replace("</Response>", 
-                &format!("<Redirect>{}/queue_callback</Redirect></Response>", config.twilio.webhook_url));
-            
-            response
-        };
-        
-        Xml(twiml)
-    }
+        start_call_recording(call_sid, config);
+        greeting
+    };
+    
+    let timing = gather_timing(&text, None, &twilio);
+    create_voice_response(&text, &twilio, timing.timeout, &timing.speech_timeout)
 }
 
 /// Make a new outbound call
+///
+/// Opening the backend session and dialing can be slow, so the call is placed
+/// in the background and its progress reported by `GET /twilio/call/jobs/<id>`.
+/// Calls placed with a tenant API key use that tenant's account.
 #[post("/call", format = "json", data = "<request>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn make_call(
+    auth: CallsWriteAuth,
     request: Json<MakeCallRequest>,
-    sessions: &State<Arc<RwLock<SessionStore>>>,
-    ws_manager: &State<Arc<WebSocketManager>>,
-    config: &State<Config>,
-) -> Result<Json<MakeCallResponse>, Status> {
-    let request = request.into_inner();
+    scheduler: &State<Arc<CallScheduler>>,
+    jobs: &State<Arc<CallJobStore>>,
+    cdrs: &State<Arc<CdrStore>>,
+    tenants: &State<Arc<TenantStore>>,
+    caller_ids: &State<Arc<CallerIds>>,
+    drain: &State<Arc<Drain>>,
+    config: CurrentConfig,
+) -> Result<Accepted<Json<CallJob>>, Custom<Json<ErrorResponse>>> {
+    let mut request = request.into_inner();
+    auth.bind_tenant(&mut request.tenant_id)?;
+    
+    if drain.is_draining() {
+        warn!("Rejecting outbound call to {}: this instance is draining", request.to_number);
+        return Err(api_error(Status::ServiceUnavailable, "Instance is draining"));
+    }
+    if let Err(e) = request.validate(&config) {
+        error!("Rejecting outbound call: {}", e);
+        return Err(api_error(Status::BadRequest, &e));
+    }
+    let tenant = request.tenant_id.as_deref().and_then(|id| tenants.get(id));
+    if cdrs.budget_exceeded(tenant.as_ref(), &config.costs) {
+        warn!("Rejecting outbound call to {}: daily call budget exceeded", request.to_number);
+        return Err(api_error(Status::PaymentRequired, "Daily call budget exceeded"));
+    }
+    // Reject caller IDs the account cannot use before queueing the job
+    match caller_ids.resolve(&request, tenants, &config).await {
+        Ok(caller_id) => request.from_number = Some(caller_id),
+        Err(e) => {
+            error!("Rejecting outbound call to {}: {}", request.to_number, e);
+            return Err(api_error(e.status(), &e.to_string()));
+        }
+    }
     
+    let job = jobs.create(&request.to_number);
+    debug!("Queued outbound call job {} to {}", job.job_id, request.to_number);
+    
+    let (scheduler, jobs) = (scheduler.inner().clone(), jobs.inner().clone());
+    let job_id = job.job_id.clone();
+    tokio::spawn(async move {
+        match scheduler.place(request).await {
+            Ok(response) => jobs.complete(&job_id, &response.session_id),
+            Err(e) => jobs.fail(&job_id, e.status.code, &e.message),
+        }
+    });
+    
+    Ok(Accepted(Json(job)))
+}
+
+/// Get the progress of an outbound call job
+#[get("/call/jobs/<job_id>")]
+pub fn get_call_job(job_id: &str, jobs: &State<Arc<CallJobStore>>) -> Option<Json<CallJob>> {
+    jobs.get(job_id).map(Json)
+}
+
+/// Open a backend session and place an outbound call for it
+#[allow(clippy::too_many_arguments)]
+pub async fn place_outbound_call(
+    mut request: MakeCallRequest,
+    sessions: &Arc<SessionStore>,
+    ws_manager: &Arc<WebSocketManager>,
+    replicator: &Arc<SessionReplicator>,
+    cdrs: &Arc<CdrStore>,
+    rate_limiter: &Arc<CallRateLimiter>,
+    backends: &BackendPool,
+    tenant: Option<&Tenant>,
+    config: &Config,
+) -> Result<MakeCallResponse, PlaceCallError> {
     debug!("Making outbound call to {}", request.to_number);
     
+    if let Err(e) = request.validate(config) {
+        error!("Rejecting outbound call: {}", e);
+        return Err(PlaceCallError::new(Status::BadRequest, e));
+    }
+    if cdrs.budget_exceeded(tenant, &config.costs) {
+        warn!("Rejecting outbound call to {}: daily call budget exceeded", request.to_number);
+        return Err(PlaceCallError::new(Status::PaymentRequired, "Daily call budget exceeded"));
+    }
+    let speech = request.speech_settings();
+    
     // Create a new session
     let mut session = Session::new(
         "".to_string(),
@@ -582,23 +3368,20 @@ pub async fn make_call(
         "twilio".to_string(), 
         None
     );
+    session.bot = request.bot.clone();
+    session.tenant_id = tenant.map(|t| t.id.clone());
     
-    // Create backend client
-    let backend_client = match BackendClient::new(
-        &config.backend.url, 
-        config.backend.authorization_token.clone(),
-        config.backend.enable_circuit_breaker
-    ) {
+    let backend_client = match backends.client(&config.backend, request.bot.as_deref()) {
         Ok(client) => client,
         Err(e) => {
             error!("Failed to create backend client: {}", e);
-            return Err(Status::InternalServerError);
+            return Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to create backend client: {}", e)));
         }
     };
     
     // Initialize session with backend
     let args = vec![];
-    let kwargs = if let Some(env_info) = request.env_info {
+    let mut kwargs = if let Some(env_info) = &request.env_info {
         if let Some(obj) = env_info.as_object() {
             // Convert serde_json::Map to HashMap
             let mut map = HashMap::new();
@@ -612,7 +3395,26 @@ pub async fn make_call(
     } else {
         HashMap::new()
     };
+    if let Some(env_info) = &request.env_info {
+        session.metadata.insert("env_info".to_string(), env_info.clone());
+    }
+    if let Some(lookup) = lookup_caller(&request.to_number, config).await {
+        session.metadata.insert("caller_lookup".to_string(), lookup.clone());
+        kwargs.insert("caller_lookup".to_string(), lookup);
+    }
+    
+    // Split calls across the running experiment's variants, telling the backend which one it got
+    let variant = config.experiments.assign();
+    if let Some(variant) = variant {
+        let assignment = config.experiments.assignment(variant);
+        session.metadata.insert(EXPERIMENT_METADATA_KEY.to_string(), assignment.clone());
+        kwargs.insert(EXPERIMENT_METADATA_KEY.to_string(), assignment);
+        experiment_metrics().record_assignment(&config.experiments.name, &variant.name);
+    }
 
+    // Wait for a dial slot first, so the backend session does not sit idle while the call is queued
+    rate_limiter.acquire().await;
+    
     let session_response = match backend_client.open_session(
         "", 
         &request.to_number, 
@@ -624,71 +3426,122 @@ pub async fn make_call(
         Ok(response) => response,
         Err(e) => {
             error!("Failed to initialize session with backend: {}", e);
-            return Err(Status::InternalServerError);
+            return Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to open backend session: {}", e)));
         }
     };
     
     // Initialize WebSocket connection for session
-    if !config.backend.ws_url.is_empty() {
-        ws_manager.get_or_create_client(
-            &session_response.session.session_id,
-            &config.backend.ws_url,
-            sessions.inner().clone()
-        ).await;
-    }
+    ws_manager.get_or_create_client(&session_response.session.session_id, request.bot.as_deref(), sessions.clone()).await;
     
     // Create Twilio client
-    let twilio_client = match TwilioClient::new(
-        config.twilio.account_sid.clone(),
-        config.twilio.auth_token.clone(),
-        config.twilio.region.clone(),
-        config.twilio.edge.clone()
-    ) {
-        Ok(client) => client,
+    let twilio_client = match TwilioClient::for_tenant(tenant, &config.twilio) {
+        Ok(client) => client.with_rate_limiter(rate_limiter.clone()).with_reserved_slot(),
         Err(e) => {
             error!("Failed to create Twilio client: {}", e);
-            return Err(Status::InternalServerError);
+            return Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to create Twilio client: {}", e)));
         }
     };
     
-    // Create empty TwiML response
-    let twiml = create_voice_response("", &config.twilio, config.twilio.default_timeout, "auto");
+    // Create the TwiML answering the call with the variant's greeting, if any, listening with the requested speech settings
+    session.speech = speech;
+    let mut call_config = config.clone();
+    call_config.twilio = session.twilio_config(config);
+    let recording = RecordingDecision::for_number(&request.to_number, &config.recording);
+    let consent = recording.and_then(|d| d.consent_message(&config.recording));
+    let greeting = variant.and_then(|v| v.greeting.as_deref()).unwrap_or_default();
+    let twiml = create_call_start_response(greeting, consent, &call_config, call_config.twilio.default_timeout, "auto");
+    if let Some(decision) = recording {
+        session.metadata.insert(RECORDING_METADATA_KEY.to_string(), serde_json::json!(decision));
+    }
+    
+    // Detect answering machines only when there is a voicemail to leave
+    let mut call_options = request.call_options(config);
+    if request.voicemail_message.is_some() {
+        call_options.machine_detection = Some("DetectMessageEnd".to_string());
+        call_options.async_amd_status_callback = Some(config.twilio.callback_url("/amd_callback"));
+    }
+    call_options.record = recording.is_some_and(|d| d.records_from_start(&config.recording));
+    session.voicemail_message = request.voicemail_message.clone();
+    session.tags.extend(request.tags.iter().cloned());
+    session.callflow = Some(CallFlow::new());
     
     // Make the call with retry
     let call = match twilio_client.create_call_with_retry(
         &request.to_number,
-        &config.twilio.from_number,
-        &twiml,
-        &format!("{}{}", config.twilio.webhook_url, "/status_callback"),
+        request.from_number.as_deref().unwrap_or(&config.twilio.from_number),
+        &twiml.build(),
+        &config.twilio.callback_url("/status_callback"),
+        &call_options,
         config.backend.retry_attempts,
         config.backend.retry_base_delay_ms
     ).await {
         Ok(call) => call,
+        Err(e) if e.is_invalid_number() => {
+            warn!("Rejecting call to invalid number {}: {}", request.to_number, e);
+            return Err(PlaceCallError::new(Status::BadRequest, format!("Twilio rejected the number {}: {}", request.to_number, e)));
+        },
         Err(e) => {
             error!("Failed to create call: {}", e);
-            return Err(Status::InternalServerError);
+            return Err(PlaceCallError::new(Status::InternalServerError, format!("Failed to create call: {}", e)));
         }
     };
     
-    // Update session with call SID
+    if let Some(url) = &request.callback_url {
+        cdrs.add_result_callback(&call.sid, url, request.tenant_id.as_deref());
+    }
+    
+    // Update session with the backend session ID and call SID
+    session.session_id = session_response.session.session_id.clone();
     session.conversation_id = Some(call.sid.clone());
+    session.call.merge(CallDetails {
+        direction: call.direction.clone(),
+        to_number: Some(request.to_number.clone()),
+        ..CallDetails::default()
+    });
+    replicator.replicate(&mut session, ReplicaState::Active);
     
     // Add session to store
-    {
-        let mut store = sessions.write().await;
-        store.add_session(session);
-    }
+    sessions.add_session(session);
     
     // Update backend session with call SID
     if let Err(e) = backend_client.update_session(
         &session_response.session.session_id, 
-        Some(&call.sid)
+        Some(&call.sid),
+        None
     ).await {
         error!("Failed to update session with call SID: {}", e);
     }
     
-    Ok(Json(MakeCallResponse {
+    Ok(MakeCallResponse {
         message: "ok".to_string(),
         session_id: call.sid,
-    }))
-}
\ No newline at end of file
+    })
+}
+
+/// Start a task placing outbound calls the backend requests over its WebSocket
+pub fn start_outbound_call_dispatcher(
+    mut requests: tokio::sync::mpsc::Receiver<CallRequest>,
+    scheduler: Arc<CallScheduler>,
+) {
+    tokio::spawn(async move {
+        while let Some(call_request) = requests.recv().await {
+            let request = match serde_json::from_value::<MakeCallRequest>(call_request.parameters) {
+                Ok(request) => request,
+                Err(e) => {
+                    error!("Invalid call request from session {}: {}", call_request.session_id, e);
+                    continue;
+                }
+            };
+            
+            debug!("Session {} requested an outbound call to {}", call_request.session_id, request.to_number);
+            
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                let to_number = request.to_number.clone();
+                if let Err(e) = scheduler.place(request).await {
+                    error!("Backend-requested call to {} failed: {}", to_number, e);
+                }
+            });
+        }
+    });
+}
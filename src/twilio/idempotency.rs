@@ -0,0 +1,143 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use log::{debug, info};
+use rocket::request::{FromRequest, Outcome, Request};
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+
+/// Header Twilio sets to the same value on every retry of a webhook request
+const IDEMPOTENCY_TOKEN_HEADER: &str = "I-Twilio-Idempotency-Token";
+
+/// Request guard exposing Twilio's idempotency token, if present
+pub struct IdempotencyToken(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyToken {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = request.headers()
+            .get_one(IDEMPOTENCY_TOKEN_HEADER)
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_string());
+
+        Outcome::Success(IdempotencyToken(token))
+    }
+}
+
+impl IdempotencyToken {
+    /// Build a replay key for a webhook
+    ///
+    /// Identifies the request by Twilio's token when sent, otherwise by the
+    /// callback's sequence number or timestamp, and hashes that together with
+    /// the call SID and the `payload` fields telling the call's callbacks apart
+    /// (e.g. the speech result). Returns `None` when the request cannot be
+    /// identified, in which case it is never deduplicated.
+    pub fn replay_key(
+        &self,
+        endpoint: &str,
+        call_sid: &str,
+        sequence_number: Option<&str>,
+        timestamp: Option<&str>,
+        payload: &[Option<&str>],
+    ) -> Option<String> {
+        let marker = match &self.0 {
+            Some(token) => token.as_str(),
+            None if call_sid.is_empty() => return None,
+            None => sequence_number.or(timestamp)?,
+        };
+
+        let mut hasher = Sha256::new();
+        for field in [Some(call_sid), Some(marker), sequence_number, timestamp].iter().chain(payload) {
+            // Length-prefix each field so adjacent fields cannot run together
+            let field = field.unwrap_or_default();
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        Some(format!("{}:{}", endpoint, hex::encode(hasher.finalize())))
+    }
+}
+
+/// Cache of webhook responses used to answer replayed requests
+///
+/// Concurrent duplicates wait for the first request to finish and share its
+/// response, so a retry that arrives while the backend is still running does
+/// not start a second run.
+pub struct ReplayCache<T> {
+    ttl: Duration,
+    entries: DashMap<String, (Instant, Arc<OnceCell<T>>)>,
+}
+
+impl<T: Clone> ReplayCache<T> {
+    /// Create a cache keeping responses for `ttl_seconds` (0 disables deduplication)
+    pub fn new(ttl_seconds: u64) -> Self {
+        ReplayCache {
+            ttl: Duration::from_secs(ttl_seconds),
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Return the stored response for a key, or run the handler and store its response
+    ///
+    /// Responses rejected by `keep` (e.g. server errors Twilio should retry) are
+    /// not stored.
+    pub async fn get_or_run<F, Fut>(&self, key: Option<String>, keep: fn(&T) -> bool, run: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let key = match key {
+            Some(key) if !self.ttl.is_zero() => key,
+            _ => return run().await,
+        };
+
+        let cell = {
+            let mut entry = self.entries
+                .entry(key.clone())
+                .or_insert_with(|| (Instant::now(), Arc::new(OnceCell::new())));
+            if entry.0.elapsed() > self.ttl {
+                *entry = (Instant::now(), Arc::new(OnceCell::new()));
+            }
+            entry.1.clone()
+        };
+
+        if cell.initialized() {
+            info!("Replaying stored response for webhook {}", key);
+        }
+
+        let response = cell.get_or_init(run).await.clone();
+
+        if !keep(&response) {
+            debug!("Not storing response for webhook {}", key);
+            self.entries.remove_if(&key, |_, (_, stored)| Arc::ptr_eq(stored, &cell));
+        }
+
+        response
+    }
+
+    /// Drop expired responses
+    pub fn purge_expired(&self) {
+        self.entries.retain(|_, (created, _)| created.elapsed() <= self.ttl);
+    }
+}
+
+/// Start a periodic task that drops expired webhook responses
+pub fn start_replay_cache_cleanup_task<T>(cache: Arc<ReplayCache<T>>)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if cache.ttl.is_zero() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(cache.ttl);
+
+        loop {
+            interval.tick().await;
+            cache.purge_expired();
+        }
+    });
+}
@@ -0,0 +1,533 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use base64::{Engine as _, engine::general_purpose};
+use futures::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use rocket::data::{IoHandler, IoStream};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use rocket::{get, State};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::audit::AuditLog;
+use crate::bot::audio::{SilenceAction, SilenceMonitor};
+use crate::bot::backend::BackendPool;
+use crate::bot::cdr::HangupSource;
+use crate::bot::session::{SessionActivity, SessionStore};
+use crate::bot::stt::{self, RecognitionStream, Transcript};
+use crate::bot::tts::{AudioFormat, SpeechSynthesis};
+use crate::config::{Config, CurrentConfig, TtsDelivery, TwilioConfig};
+use crate::i18n::{MessageCatalog, Phrase};
+use crate::replication::SessionReplicator;
+use crate::twilio::client::TwilioClient;
+use crate::twilio::handlers::{process_partial, process_transcription};
+use crate::twilio::twiml::{SAY_CHUNK_BREAK, TwiML, create_hangup_response, create_voice_response};
+
+/// Messages queued for Twilio on a bidirectional stream before senders wait
+const OUTBOUND_BUFFER_MESSAGES: usize = 256;
+
+/// Request guard for a WebSocket upgrade request
+pub struct WebSocketUpgrade {
+    key: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebSocketUpgrade {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        let is_upgrade = headers
+            .get_one("Upgrade")
+            .map(|v| v.eq_ignore_ascii_case("websocket"))
+            .unwrap_or(false);
+
+        match (is_upgrade, headers.get_one("Sec-WebSocket-Key")) {
+            (true, Some(key)) => Outcome::Success(WebSocketUpgrade { key: key.to_string() }),
+            _ => Outcome::Error((Status::BadRequest, ())),
+        }
+    }
+}
+
+/// Responder that completes the WebSocket handshake and hands the socket to a handler
+pub struct WebSocketResponse<H> {
+    key: String,
+    handler: H,
+}
+
+impl<'r, H: IoHandler + 'static> Responder<'r, 'static> for WebSocketResponse<H> {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .raw_header("Sec-WebSocket-Accept", derive_accept_key(self.key.as_bytes()))
+            .upgrade("websocket", self.handler)
+            .ok()
+    }
+}
+
+/// Events sent by Twilio over a Media Stream
+#[derive(Debug, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum StreamEvent {
+    Connected,
+    Start { start: StreamStart },
+    Media { media: StreamMedia },
+    Stop,
+    #[serde(other)]
+    Other,
+}
+
+/// Metadata sent when a stream starts
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamStart {
+    call_sid: String,
+    stream_sid: String,
+}
+
+/// A chunk of base64-encoded μ-law audio
+#[derive(Debug, Deserialize)]
+struct StreamMedia {
+    #[serde(default)]
+    track: String,
+    payload: String,
+}
+
+/// Sends synthesized replies to the caller over a bidirectional Media Stream
+#[derive(Clone)]
+struct MediaOutlet {
+    stream_sid: String,
+    messages: mpsc::Sender<String>,
+    synthesis: Arc<SpeechSynthesis>,
+}
+
+impl MediaOutlet {
+    /// Drop audio Twilio has buffered but not yet played, when the caller talks over the bot
+    async fn clear(&self) {
+        self.send(json!({ "event": "clear", "streamSid": self.stream_sid })).await;
+    }
+
+    /// Speak text in the call's catalog voice as the provider synthesizes it
+    ///
+    /// Returns false if nothing could be spoken, e.g. for a Twilio voice.
+    async fn speak(&self, text: &str, twilio: &TwilioConfig, config: &Config) -> bool {
+        let Some(voice) = twilio.tts_voice.as_deref().and_then(|name| config.tts.voices.get(name)) else {
+            return false;
+        };
+        let Some(synthesizer) = self.synthesis.synthesizer(&config.tts) else {
+            return false;
+        };
+
+        let text = text.replace(SAY_CHUNK_BREAK, " ");
+        let mut stream = match synthesizer.synthesize(&text, voice, twilio.language.as_deref(), AudioFormat::Mulaw).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("Failed to synthesize reply with {}: {}", synthesizer.name(), e);
+                return false;
+            }
+        };
+
+        loop {
+            match stream.next_chunk().await {
+                Ok(Some(chunk)) => self.send(json!({
+                    "event": "media",
+                    "streamSid": self.stream_sid,
+                    "media": { "payload": general_purpose::STANDARD.encode(chunk) },
+                })).await,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Reply synthesis stream failed: {}", e);
+                    break;
+                }
+            }
+        }
+        self.send(json!({ "event": "mark", "streamSid": self.stream_sid, "mark": { "name": "reply" } })).await;
+        true
+    }
+
+    async fn send(&self, message: serde_json::Value) {
+        if self.messages.send(message.to_string()).await.is_err() {
+            debug!("Media stream {} closed before a message was sent", self.stream_sid);
+        }
+    }
+}
+
+/// Handles a single Twilio Media Stream connection
+pub struct MediaStreamHandler {
+    sessions: Arc<SessionStore>,
+    catalog: Arc<MessageCatalog>,
+    replicator: Arc<SessionReplicator>,
+    audit: Arc<AuditLog>,
+    backends: Arc<BackendPool>,
+    synthesis: Arc<SpeechSynthesis>,
+    config: Config,
+}
+
+#[rocket::async_trait]
+impl IoHandler for MediaStreamHandler {
+    async fn io(self: Pin<Box<Self>>, io: IoStream) -> io::Result<()> {
+        let ws = WebSocketStream::from_raw_socket(io, Role::Server, None).await;
+        self.run(ws).await;
+        Ok(())
+    }
+}
+
+impl MediaStreamHandler {
+    /// Read stream events and monitor inbound audio until the stream stops
+    ///
+    /// With a streaming STT provider, inbound audio is also recognized and its
+    /// transcripts drive the conversation. When replies are delivered over the
+    /// stream, they are sent back on it as synthesized audio.
+    async fn run(&self, mut ws: WebSocketStream<IoStream>) {
+        let media = &self.config.media;
+        let mut monitor = SilenceMonitor::new(
+            media.silence_threshold,
+            std::time::Duration::from_secs(media.silence_timeout_seconds),
+            std::time::Duration::from_secs(media.check_timeout_seconds),
+        );
+        let mut call_sid: Option<String> = None;
+        let mut recognition: Option<RecognitionStream> = None;
+        let (outbound_tx, mut outbound_rx) = mpsc::channel::<String>(OUTBOUND_BUFFER_MESSAGES);
+        let mut outlet: Option<MediaOutlet> = None;
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(1));
+
+        loop {
+            tokio::select! {
+                message = ws.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => {
+                            error!("Media stream error: {}", e);
+                            break;
+                        }
+                    };
+
+                    match serde_json::from_str::<StreamEvent>(&text) {
+                        Ok(StreamEvent::Start { start }) => {
+                            info!("Media stream {} started for call {}", start.stream_sid, start.call_sid);
+                            recognition = self.start_recognition(&start.call_sid).await;
+                            if self.config.tts.delivery == TtsDelivery::MediaStream {
+                                outlet = Some(MediaOutlet {
+                                    stream_sid: start.stream_sid.clone(),
+                                    messages: outbound_tx.clone(),
+                                    synthesis: self.synthesis.clone(),
+                                });
+                            }
+                            call_sid = Some(start.call_sid);
+                            monitor.reset();
+                        },
+                        Ok(StreamEvent::Media { media }) => {
+                            if media.track.is_empty() || media.track == "inbound" {
+                                match general_purpose::STANDARD.decode(&media.payload) {
+                                    Ok(frame) => {
+                                        if monitor.observe(&frame) {
+                                            monitor.reset();
+                                        }
+                                        if let Some(recognition) = &recognition {
+                                            recognition.send_audio(frame);
+                                        }
+                                    },
+                                    Err(e) => debug!("Invalid media payload: {}", e),
+                                }
+                            }
+                        },
+                        Ok(StreamEvent::Stop) => break,
+                        Ok(StreamEvent::Connected) | Ok(StreamEvent::Other) => {},
+                        Err(e) => debug!("Unrecognized media stream message: {}", e),
+                    }
+                },
+                transcript = next_transcript(&mut recognition) => {
+                    match (transcript, &call_sid) {
+                        (Some(transcript), Some(call_sid)) => {
+                            monitor.reset();
+                            self.handle_transcript(call_sid, transcript, outlet.clone());
+                        },
+                        (Some(_), None) => {},
+                        (None, _) => {
+                            warn!("Speech recognition for call {:?} closed before the stream", call_sid);
+                            recognition = None;
+                        },
+                    }
+                },
+                Some(message) = outbound_rx.recv() => {
+                    if let Err(e) = ws.send(Message::Text(message)).await {
+                        error!("Failed to send on media stream: {}", e);
+                        break;
+                    }
+                },
+                _ = ticker.tick() => {
+                    let call_sid = match &call_sid {
+                        Some(call_sid) => call_sid,
+                        None => continue,
+                    };
+
+                    if recognition.is_some() {
+                        self.check_no_input(call_sid).await;
+                    }
+
+                    // Speech recognized by a Gather also counts, even if its frames were quiet
+                    if monitor.is_due() {
+                        if let Some(heard) = self.sessions.lock_session_by_conversation(call_sid).await.and_then(|s| s.last_speech) {
+                            monitor.speech_heard(heard);
+                        }
+                    }
+
+                    match monitor.poll() {
+                        SilenceAction::AudioCheck => {
+                            debug!("No inbound audio on call {} for {:?}, checking", call_sid, monitor.silence_duration());
+                            self.send_audio_check(call_sid).await;
+                        },
+                        SilenceAction::EndCall => {
+                            warn!(
+                                "Quality alert: one-way audio on call {} (silent for {:?}, peak energy {:.0})",
+                                call_sid, monitor.silence_duration(), monitor.peak_energy()
+                            );
+                            self.end_call(call_sid).await;
+                        },
+                        SilenceAction::None => {},
+                    }
+                },
+            }
+        }
+
+        debug!("Media stream closed for call {:?}", call_sid);
+    }
+
+    /// Open a recognition session for a call when a streaming STT provider is configured
+    async fn start_recognition(&self, call_sid: &str) -> Option<RecognitionStream> {
+        let recognizer = stt::recognizer(&self.config.stt)?;
+
+        let language = {
+            let detected = match self.sessions.lock_session_by_conversation(call_sid).await {
+                Some(session) => session.detected_language.clone(),
+                None => None,
+            };
+            detected.or_else(|| self.config.twilio.speech_language.clone()).or_else(|| self.config.twilio.language.clone())
+        };
+
+        match recognizer.connect(language.as_deref()).await {
+            Ok(recognition) => {
+                info!("Recognizing speech on call {} with {}", call_sid, recognizer.name());
+                Some(recognition)
+            },
+            Err(e) => {
+                error!("Failed to start {} speech recognition for call {}: {}", recognizer.name(), call_sid, e);
+                None
+            }
+        }
+    }
+
+    /// Run a turn for a final transcript, or speculate on an interim one, in the background
+    ///
+    /// The stream keeps reading audio meanwhile; the call's turn lock keeps the
+    /// transcripts' turns in order. With an `outlet`, the caller's speech cuts off
+    /// the bot's reply and plain replies are spoken over the stream.
+    fn handle_transcript(&self, call_sid: &str, transcript: Transcript, outlet: Option<MediaOutlet>) {
+        let call_sid = call_sid.to_string();
+        let sessions = self.sessions.clone();
+        let backends = self.backends.clone();
+        let config = self.config.clone();
+
+        if let Some(outlet) = outlet.clone() {
+            tokio::spawn(async move { outlet.clear().await });
+        }
+
+        if !transcript.is_final {
+            tokio::spawn(async move {
+                debug!("Interim transcript for call {}: {}", call_sid, config.redaction.redacted(&transcript.text));
+                if !config.twilio.partial_processing {
+                    // The caller is still talking, so the call is not silent
+                    if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+                        session.last_speech = Some(std::time::Instant::now());
+                    }
+                    return;
+                }
+                let turn = sessions.lock_turn(&call_sid).await;
+                process_partial(turn, &call_sid, transcript.text, &sessions, &backends, &config).await;
+            });
+            return;
+        }
+
+        let catalog = self.catalog.clone();
+        let replicator = self.replicator.clone();
+        let audit = self.audit.clone();
+        tokio::spawn(async move {
+            debug!("Final transcript for call {}: {}", call_sid, config.redaction.redacted(&transcript.text));
+            let twiml = {
+                let turn = sessions.lock_turn(&call_sid).await;
+                if let Some(mut session) = sessions.lock_session_by_conversation(&call_sid).await {
+                    session.pending_reply = None;
+                }
+                process_transcription(
+                    turn,
+                    call_sid.clone(),
+                    transcript.text,
+                    transcript.confidence,
+                    &sessions,
+                    &catalog,
+                    &replicator,
+                    &audit,
+                    &backends,
+                    &config
+                ).await
+            };
+
+            // A call taken over by an agent stays in their conference
+            if sessions.lock_session_by_conversation(&call_sid).await.is_some_and(|s| s.takeover.is_some()) {
+                return;
+            }
+
+            if let Some(outlet) = &outlet {
+                let reply = match sessions.lock_session_by_conversation(&call_sid).await {
+                    Some(mut session) => session.pending_reply.take().map(|text| (text, session.twilio_config(&config))),
+                    None => None,
+                };
+                if let Some((text, twilio)) = reply {
+                    if outlet.speak(&text, &twilio, &config).await {
+                        debug!("Spoke reply over the media stream for call {}", call_sid);
+                        return;
+                    }
+                }
+            }
+            update_call(&call_sid, &twiml.build(), &config).await;
+        });
+    }
+
+    /// Start the keepalive prompts once recognition has heard nothing for the call's no-input timeout
+    ///
+    /// The wait starts when the call enters the stream hold after a prompt.
+    async fn check_no_input(&self, call_sid: &str) {
+        let (attempt, timeout, twilio) = {
+            let Some(mut session) = self.sessions.lock_session_by_conversation(call_sid).await else {
+                return;
+            };
+            let Some((since, timeout)) = session.listening else {
+                return;
+            };
+            let heard = session.last_speech.filter(|heard| *heard > since).unwrap_or(since);
+            if session.activity() != SessionActivity::Gathering || heard.elapsed() < timeout {
+                return;
+            }
+            session.listening = None;
+            (session.silence.as_ref().map_or(1, |silence| silence.prompts + 1), timeout, session.twilio_config(&self.config))
+        };
+
+        debug!("No speech recognized on call {} for {:?}", call_sid, timeout);
+        let twiml = TwiML::new().redirect(&twilio.callback_url(&format!("/keepalive?attempt={}", attempt)));
+        self.update_call(call_sid, &twiml.build()).await;
+    }
+
+    /// Ask the caller whether they can hear the bot, unless an agent took the call over
+    async fn send_audio_check(&self, call_sid: &str) {
+        let tenant_id = match self.sessions.lock_session_by_conversation(call_sid).await {
+            Some(session) if session.takeover.is_some() => return,
+            Some(session) => session.tenant_id.clone(),
+            None => None,
+        };
+
+        let language = self.config.twilio.language.as_deref();
+        let twiml = create_voice_response(
+            &self.catalog.tenant_text(Phrase::AudioCheck, language, tenant_id.as_deref()),
+            &self.config.twilio,
+            self.config.twilio.default_timeout,
+            "auto"
+        );
+
+        self.update_call(call_sid, &twiml.build()).await;
+    }
+
+    /// End a call whose audio check went unanswered
+    async fn end_call(&self, call_sid: &str) {
+        let tenant_id = match self.sessions.lock_session_by_conversation(call_sid).await {
+            Some(mut session) if !session.session_ends && session.takeover.is_none() => {
+                session.session_ends = true;
+                session.disposition = Some("one_way_audio".to_string());
+                session.hangup_source = Some(HangupSource::Bot);
+                session.tenant_id.clone()
+            },
+            _ => return,
+        };
+
+        let language = self.config.twilio.language.as_deref();
+        let twiml = create_hangup_response(
+            Some(&self.catalog.tenant_text(Phrase::AudioCheckFailed, language, tenant_id.as_deref())),
+            &self.config.twilio
+        );
+
+        self.update_call(call_sid, &twiml.build()).await;
+    }
+
+    /// Replace the live call's TwiML
+    async fn update_call(&self, call_sid: &str, twiml: &str) {
+        update_call(call_sid, twiml, &self.config).await;
+    }
+}
+
+/// Next transcript of the call's recognition session, waiting forever without one
+async fn next_transcript(recognition: &mut Option<RecognitionStream>) -> Option<Transcript> {
+    match recognition {
+        Some(recognition) => recognition.next_transcript().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Replace a live call's TwiML
+async fn update_call(call_sid: &str, twiml: &str, config: &Config) {
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = twilio_client.update_call_with_retry(
+        call_sid,
+        twiml,
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        error!("Failed to update call {} from media stream: {}", call_sid, e);
+    }
+}
+
+/// Accept a Twilio Media Stream WebSocket connection
+#[get("/media_stream")]
+#[allow(clippy::too_many_arguments)]
+pub fn handle_media_stream(
+    upgrade: WebSocketUpgrade,
+    sessions: &State<Arc<SessionStore>>,
+    catalog: &State<Arc<MessageCatalog>>,
+    replicator: &State<Arc<SessionReplicator>>,
+    audit: &State<Arc<AuditLog>>,
+    backends: &State<Arc<BackendPool>>,
+    synthesis: &State<Arc<SpeechSynthesis>>,
+    config: CurrentConfig,
+) -> WebSocketResponse<MediaStreamHandler> {
+    WebSocketResponse {
+        key: upgrade.key,
+        handler: MediaStreamHandler {
+            sessions: sessions.inner().clone(),
+            catalog: catalog.inner().clone(),
+            replicator: replicator.inner().clone(),
+            audit: audit.inner().clone(),
+            backends: backends.inner().clone(),
+            synthesis: synthesis.inner().clone(),
+            config: Config::clone(&config),
+        },
+    }
+}
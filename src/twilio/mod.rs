@@ -1,17 +1,85 @@
 pub mod client;
 pub mod twiml;
 pub mod handlers;
+pub mod media_stream;
+pub mod idempotency;
+pub mod watchdog;
+pub mod call_jobs;
+pub mod prompt_cache;
+pub mod greeting;
+pub mod scheduler;
+pub mod provisioning;
+pub mod billing;
+pub mod caller_id;
+pub mod proxy;
+pub mod cps;
+pub mod agent_pool;
+pub mod synthesis;
+pub mod validation;
+pub mod fixtures;
+pub mod self_test;
 
-use rocket::{Route, routes};
+use rocket::{Catcher, Route, catchers, routes};
+
+/// Version of the webhook semantics this build implements
+///
+/// Callback URLs are built under `/twilio/<version>/...`. Bump it when a handler
+/// changes what it expects from TwiML generated earlier, so a blue/green
+/// rollout can keep sending in-flight calls to the old deployment.
+pub const WEBHOOK_VERSION: &str = "v2";
 
 /// Get all routes for the Twilio module
+///
+/// Each route is served both unversioned, for TwiML and number configuration
+/// predating versioned URLs, and under `WEBHOOK_VERSION`.
 pub fn routes() -> Vec<Route> {
-    routes![
+    let unversioned = routes![
         handlers::handle_incoming_call,
+        handlers::handle_greeting,
         handlers::handle_call_status,
+        handlers::handle_amd_callback,
         handlers::handle_call_transcription,
         handlers::handle_partial_callback,
+        handlers::handle_menu_callback,
         handlers::handle_call_queue,
+        handlers::handle_keepalive,
+        handlers::handle_dtmf_callback,
+        handlers::handle_stream_hold,
+        handlers::handle_queue_wait,
+        handlers::handle_queue_bridge,
+        handlers::handle_queue_result,
+        handlers::handle_transfer_result,
+        handlers::handle_transfer_voicemail,
+        handlers::handle_transfer_survey,
+        handlers::handle_recording_consent,
+        handlers::handle_recording_opt_out,
+        handlers::handle_outage_result,
+        handlers::handle_outage_voicemail,
+        handlers::handle_after_hours_voicemail,
+        handlers::handle_escalation_status,
+        handlers::handle_escalation_result,
+        handlers::handle_coach_status,
+        handlers::handle_takeover_status,
+        handlers::handle_takeover_result,
+        handlers::handle_sms_status,
         handlers::make_call,
-    ]
+        handlers::get_call_job,
+        prompt_cache::get_prompt,
+        synthesis::get_speech,
+        crate::api::recordings::recording_audio,
+        media_stream::handle_media_stream,
+        proxy::proxy_check,
+    ];
+
+    let versioned: Vec<Route> = unversioned.iter()
+        .map(|route| route.clone()
+            .map_base(|base| format!("/{}{}", WEBHOOK_VERSION, base))
+            .expect("webhook version is a valid path segment"))
+        .collect();
+    unversioned.into_iter().chain(versioned).collect()
+}
+
+/// Get the error catchers for the Twilio module
+pub fn catchers() -> Vec<Catcher> {
+    catchers![validation::malformed_callback]
 }
@@ -0,0 +1,150 @@
+use std::path::Path;
+use std::sync::Arc;
+use dashmap::DashMap;
+use log::{debug, info, warn};
+use rocket::fs::NamedFile;
+use rocket::State;
+use sha2::{Digest, Sha256};
+
+use crate::bot::backend::BackendPool;
+use crate::config::{BackendConfig, PromptCacheConfig};
+use crate::twilio::twiml::{Say, TwiML};
+
+/// Cache of backend-rendered prompt audio, keyed by text, voice and language
+///
+/// Webhook responses play prompts whose audio is already cached instead of speaking them.
+/// Uncached prompts are still spoken by Twilio while their audio renders in the
+/// background, so only repeated prompts (greetings, error messages) are played
+/// from the cache.
+pub struct PromptCache {
+    config: PromptCacheConfig,
+    backend: BackendConfig,
+    backends: Arc<BackendPool>,
+    /// Cached audio file names, keyed by prompt key
+    rendered: DashMap<String, String>,
+    /// Keys of prompts being rendered
+    pending: DashMap<String, ()>,
+    http: reqwest::Client,
+}
+
+impl PromptCache {
+    /// Create a cache, indexing audio already stored in the local directory
+    ///
+    /// Audio uploaded to a bucket is not indexed and renders again after a restart.
+    pub fn new(config: &PromptCacheConfig, backend: &BackendConfig, backends: Arc<BackendPool>) -> Self {
+        let rendered = DashMap::new();
+
+        if config.enabled && config.bucket_url.is_none() {
+            if let Ok(entries) = std::fs::read_dir(&config.dir) {
+                for entry in entries.flatten() {
+                    let file = entry.file_name().to_string_lossy().to_string();
+                    if let Some((key, _)) = file.split_once('.') {
+                        rendered.insert(key.to_string(), file.clone());
+                    }
+                }
+            }
+            info!("Prompt cache loaded {} cached prompts from {}", rendered.len(), config.dir);
+        }
+
+        PromptCache {
+            config: config.clone(),
+            backend: backend.clone(),
+            backends,
+            rendered,
+            pending: DashMap::new(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Replace plain-text Say verbs whose audio is cached with Play verbs
+    pub fn apply(self: &Arc<Self>, twiml: TwiML) -> TwiML {
+        if !self.config.enabled {
+            return twiml;
+        }
+        twiml.replace_says(|say| self.cached_url(say))
+    }
+
+    /// URL of the cached audio for a Say, rendering it in the background if missing
+    fn cached_url(self: &Arc<Self>, say: &Say) -> Option<String> {
+        if !say.is_plain() || say.text.trim().is_empty() {
+            return None;
+        }
+        let (text, voice, language) = (say.text.clone(), say.voice.clone(), say.language.clone());
+        let key = prompt_key(&text, &voice, language.as_deref());
+
+        if let Some(file) = self.rendered.get(&key) {
+            debug!("Playing cached prompt {}", file.value());
+            return Some(format!("{}/{}", self.config.public_url, file.value()));
+        }
+
+        if self.pending.insert(key.clone(), ()).is_none() {
+            let cache = self.clone();
+            tokio::spawn(async move {
+                match cache.render(&key, &text, &voice, language.as_deref()).await {
+                    Ok(file) => {
+                        debug!("Cached prompt {}", file);
+                        cache.rendered.insert(key.clone(), file);
+                    },
+                    Err(e) => warn!("Failed to cache prompt {}: {}", key, e),
+                }
+                cache.pending.remove(&key);
+            });
+        }
+
+        None
+    }
+
+    /// Render a prompt with the backend and store its audio, returning the file name
+    async fn render(&self, key: &str, text: &str, voice: &str, language: Option<&str>) -> Result<String, String> {
+        let client = self.backends.client(&self.backend, None).map_err(|e| e.to_string())?;
+        let (audio, content_type) = client
+            .render_speech(&self.config.tts_path, text, voice, language)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let extension = match content_type.as_str() {
+            "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+            _ => "mp3",
+        };
+        let file = format!("{}.{}", key, extension);
+
+        match &self.config.bucket_url {
+            Some(bucket_url) => {
+                self.http.put(format!("{}/{}", bucket_url, file))
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(audio)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| e.to_string())?;
+            },
+            None => {
+                tokio::fs::create_dir_all(&self.config.dir).await.map_err(|e| e.to_string())?;
+                tokio::fs::write(Path::new(&self.config.dir).join(&file), audio)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            },
+        }
+
+        Ok(file)
+    }
+}
+
+/// Cache key for a prompt
+fn prompt_key(text: &str, voice: &str, language: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}\n{}\n{}", voice, language.unwrap_or_default(), text));
+    hex::encode(hasher.finalize())
+}
+
+/// Serve cached prompt audio stored in the local directory
+#[get("/prompts/<file>")]
+pub async fn get_prompt(file: &str, prompts: &State<Arc<PromptCache>>) -> Option<NamedFile> {
+    // Only serve files named by the cache itself
+    let (key, _) = file.split_once('.')?;
+    if !prompts.config.enabled || prompts.rendered.get(key)?.value() != file {
+        return None;
+    }
+
+    NamedFile::open(Path::new(&prompts.config.dir).join(file)).await.ok()
+}
@@ -0,0 +1,124 @@
+use log::{error, info, warn};
+use serde::Serialize;
+
+use crate::config::TwilioConfig;
+use crate::twilio::client::TwilioClient;
+
+/// What checking a number's voice webhook did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisionAction {
+    /// The webhook already pointed at this service
+    Unchanged,
+    /// The webhook was repointed at this service
+    Updated,
+    /// The account does not own the number
+    NotOwned,
+    Failed,
+}
+
+/// Result of checking one inbound number
+#[derive(Debug, Clone, Serialize)]
+pub struct NumberProvisioning {
+    pub number: String,
+    pub sid: Option<String>,
+    pub action: ProvisionAction,
+    /// Voice URL the number had before it was updated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_voice_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl NumberProvisioning {
+    fn new(number: &str, sid: Option<String>, action: ProvisionAction) -> Self {
+        NumberProvisioning {
+            number: number.to_string(),
+            sid,
+            action,
+            previous_voice_url: None,
+            error: None,
+        }
+    }
+
+    fn failed(number: &str, sid: Option<String>, error: String) -> Self {
+        NumberProvisioning {
+            error: Some(error),
+            ..Self::new(number, sid, ProvisionAction::Failed)
+        }
+    }
+}
+
+/// Make sure every inbound number's voice webhook points at our incoming call handler
+pub async fn provision_numbers(config: &TwilioConfig) -> Result<Vec<NumberProvisioning>, String> {
+    let twilio_client = TwilioClient::new(
+        config.account_sid.clone(),
+        config.auth_token.clone(),
+        config.region.clone(),
+        config.edge.clone()
+    ).map_err(|e| e.to_string())?;
+
+    let voice_url = config.callback_url("/incoming_callback");
+
+    let mut results = Vec::new();
+    for number in &config.inbound_numbers {
+        results.push(provision_number(&twilio_client, number, &voice_url).await);
+    }
+
+    Ok(results)
+}
+
+/// Check one number's voice webhook and repoint it if needed
+async fn provision_number(twilio_client: &TwilioClient, number: &str, voice_url: &str) -> NumberProvisioning {
+    let numbers = match twilio_client.list_phone_numbers(number).await {
+        Ok(numbers) => numbers,
+        Err(e) => return NumberProvisioning::failed(number, None, e.to_string()),
+    };
+
+    let Some(owned) = numbers.first() else {
+        warn!("Inbound number {} is not owned by the account", number);
+        return NumberProvisioning::new(number, None, ProvisionAction::NotOwned);
+    };
+    let sid = owned.get("sid").and_then(|s| s.as_str()).map(|s| s.to_string());
+    let Some(number_sid) = sid.clone() else {
+        return NumberProvisioning::failed(number, None, "Number has no SID".to_string());
+    };
+
+    let current_url = owned.get("voice_url").and_then(|u| u.as_str()).unwrap_or_default();
+    let current_method = owned.get("voice_method").and_then(|m| m.as_str()).unwrap_or_default();
+    if current_url == voice_url && current_method.eq_ignore_ascii_case("POST") {
+        return NumberProvisioning::new(number, sid, ProvisionAction::Unchanged);
+    }
+
+    match twilio_client.update_phone_number(&number_sid, voice_url).await {
+        Ok(_) => {
+            info!("Repointed {} voice webhook from '{}' to {}", number, current_url, voice_url);
+            NumberProvisioning {
+                previous_voice_url: Some(current_url.to_string()),
+                ..NumberProvisioning::new(number, sid, ProvisionAction::Updated)
+            }
+        },
+        Err(e) => NumberProvisioning::failed(number, sid, e.to_string()),
+    }
+}
+
+/// Start a task checking the inbound numbers' webhooks once at startup, if enabled
+pub fn start_number_provisioning(config: TwilioConfig) {
+    if !config.provision_numbers_on_startup {
+        return;
+    }
+
+    tokio::spawn(async move {
+        match provision_numbers(&config).await {
+            Ok(results) => {
+                for result in results.iter().filter(|r| r.action == ProvisionAction::Failed) {
+                    error!(
+                        "Failed to provision {}: {}",
+                        result.number, result.error.as_deref().unwrap_or_default()
+                    );
+                }
+            },
+            Err(e) => error!("Failed to provision inbound numbers: {}", e),
+        }
+    });
+}
@@ -0,0 +1,122 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use log::{error, info, warn};
+use rocket::fairing::AdHoc;
+use rocket::get;
+use rocket::request::{FromRequest, Outcome, Request};
+use uuid::Uuid;
+
+use crate::config::{SharedConfig, TwilioConfig};
+
+/// Route answering the startup check that the webhook URL reaches this instance
+const PROXY_CHECK_PATH: &str = "/proxy_check";
+
+/// Token identifying this process to its own startup check
+fn instance_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(|| Uuid::new_v4().to_string())
+}
+
+/// External URL of the current request, as seen by Twilio through the reverse proxy
+///
+/// Only available when `TRUST_FORWARDED_HEADERS` is enabled. Built from
+/// `X-Forwarded-Proto`, `X-Forwarded-Host` and `X-Forwarded-Prefix`, falling
+/// back to the `Host` header and `PUBLIC_BASE_PATH`. The query string is left out.
+pub struct ExternalUrl(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ExternalUrl {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(config) = request.rocket().state::<SharedConfig>().map(SharedConfig::current) else {
+            return Outcome::Success(ExternalUrl(None));
+        };
+        let twilio = &config.twilio;
+        if !twilio.trust_forwarded_headers {
+            return Outcome::Success(ExternalUrl(None));
+        }
+
+        let headers = request.headers();
+        // Proxies chaining several hops list the client-facing value first
+        let forwarded = |name: &str| headers.get_one(name)
+            .and_then(|value| value.split(',').next())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+
+        let Some(host) = forwarded("X-Forwarded-Host").or_else(|| forwarded("Host")) else {
+            return Outcome::Success(ExternalUrl(None));
+        };
+        let proto = forwarded("X-Forwarded-Proto").unwrap_or_else(|| "http".to_string());
+        let prefix = forwarded("X-Forwarded-Prefix")
+            .or_else(|| twilio.public_base_path.clone())
+            .unwrap_or_default();
+
+        let url = format!("{}://{}{}{}", proto, host, prefix.trim_end_matches('/'), request.uri().path());
+        Outcome::Success(ExternalUrl(Some(url)))
+    }
+}
+
+impl ExternalUrl {
+    /// Warn once if Twilio reached a callback route at a different URL than the one we build
+    pub fn check(&self, path: &str, twilio: &TwilioConfig) {
+        static WARNED: AtomicBool = AtomicBool::new(false);
+
+        let Some(external) = &self.0 else {
+            return;
+        };
+        let expected = twilio.callback_url(path);
+        // Legacy unversioned routes are still served during a rollout
+        if *external != expected
+            && *external != twilio.unversioned_callback_url(path)
+            && !WARNED.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Twilio reached {} but callback URLs are built as {}; check TWILIO_WEBHOOK_URL and PUBLIC_BASE_PATH",
+                external, expected
+            );
+        }
+    }
+}
+
+/// Answer the startup check with this instance's token
+#[get("/proxy_check")]
+pub fn proxy_check() -> &'static str {
+    instance_token()
+}
+
+/// Liftoff fairing checking that the configured webhook URL reaches this instance
+///
+/// A mismatch means Twilio would be sent callback URLs that miss the service,
+/// usually because of a missing or wrong `PUBLIC_BASE_PATH`. Failures are only
+/// logged, since the proxy may not be reachable from inside the deployment.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Webhook URL check", |rocket| Box::pin(async move {
+        let Some(config) = rocket.state::<SharedConfig>().map(SharedConfig::current) else {
+            return;
+        };
+        let url = config.twilio.callback_url(PROXY_CHECK_PATH);
+
+        // Check in the background; the server only serves requests once liftoff completes
+        tokio::spawn(check_webhook_url(url));
+    }))
+}
+
+async fn check_webhook_url(url: String) {
+    let response = match reqwest::Client::new().get(&url).timeout(Duration::from_secs(5)).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Could not verify webhook URL {}: {}", url, e);
+            return;
+        }
+    };
+
+    match response.text().await {
+        Ok(body) if body == instance_token() => info!("Webhook URL {} reaches this instance", url),
+        Ok(_) => error!(
+            "Webhook URL {} does not reach this instance; check TWILIO_WEBHOOK_URL and PUBLIC_BASE_PATH",
+            url
+        ),
+        Err(e) => warn!("Could not verify webhook URL {}: {}", url, e),
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use std::time::Duration;
+use dashmap::DashMap;
+use log::{error, info};
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::backend::BackendPool;
+use crate::bot::cdr::{CallRecord, CdrStore};
+use crate::bot::result_callback::send_call_result;
+use crate::bot::session::SessionStore;
+use crate::bot::ws_client::WebSocketManager;
+use crate::config::SharedConfig;
+use crate::dead_letter::DeadLetterStore;
+use crate::drain::Drain;
+use crate::replication::SessionReplicator;
+use crate::tenant::TenantStore;
+use crate::twilio::caller_id::CallerIds;
+use crate::twilio::cps::CallRateLimiter;
+use crate::twilio::handlers::{MakeCallRequest, MakeCallResponse, PlaceCallError, place_outbound_call};
+
+/// Final call statuses a retry policy can re-dial on
+const RETRYABLE_STATUSES: [&str; 4] = ["busy", "no-answer", "failed", "canceled"];
+
+/// Most dial attempts a retry policy may ask for
+const MAX_DIAL_ATTEMPTS: u32 = 10;
+
+/// Longest wait a retry policy may ask for between attempts, one day
+const MAX_DELAY_MINUTES: u64 = 24 * 60;
+
+/// When to re-dial an outbound call that did not connect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total dial attempts, including the first
+    pub max_attempts: u32,
+    #[serde(default = "default_delay_minutes")]
+    pub delay_minutes: u64,
+    /// Final call statuses that trigger a re-dial
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<String>,
+}
+
+fn default_delay_minutes() -> u64 {
+    5
+}
+
+fn default_retry_on() -> Vec<String> {
+    vec!["busy".to_string(), "no-answer".to_string()]
+}
+
+impl RetryPolicy {
+    /// Check the attempt limit, delay and statuses
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 || self.max_attempts > MAX_DIAL_ATTEMPTS {
+            return Err(format!("max_attempts must be between 1 and {}", MAX_DIAL_ATTEMPTS));
+        }
+        if self.delay_minutes > MAX_DELAY_MINUTES {
+            return Err(format!("delay_minutes must be at most {}", MAX_DELAY_MINUTES));
+        }
+        if let Some(status) = self.retry_on.iter().find(|s| !RETRYABLE_STATUSES.contains(&s.as_str())) {
+            return Err(format!("Cannot retry calls ending with status '{}'", status));
+        }
+
+        Ok(())
+    }
+
+    /// Whether a call that ended with `status` on attempt `attempt` should be re-dialed
+    pub fn should_retry(&self, status: &str, attempt: u32) -> bool {
+        attempt < self.max_attempts && self.retry_on.iter().any(|s| s == status)
+    }
+}
+
+/// Places outbound calls and schedules re-dials for calls with a retry policy
+///
+/// Pending re-dials are kept in memory and do not survive a restart.
+pub struct CallScheduler {
+    sessions: Arc<SessionStore>,
+    ws_manager: Arc<WebSocketManager>,
+    replicator: Arc<SessionReplicator>,
+    cdrs: Arc<CdrStore>,
+    tenants: Arc<TenantStore>,
+    caller_ids: Arc<CallerIds>,
+    dead_letters: Arc<DeadLetterStore>,
+    rate_limiter: Arc<CallRateLimiter>,
+    backends: Arc<BackendPool>,
+    drain: Arc<Drain>,
+    config: SharedConfig,
+    /// Calls placed with a retry policy and their attempt number, keyed by call SID
+    attempts: DashMap<String, (MakeCallRequest, u32)>,
+}
+
+impl CallScheduler {
+    /// Create a scheduler placing calls with the given state
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        sessions: Arc<SessionStore>,
+        ws_manager: Arc<WebSocketManager>,
+        replicator: Arc<SessionReplicator>,
+        cdrs: Arc<CdrStore>,
+        tenants: Arc<TenantStore>,
+        caller_ids: Arc<CallerIds>,
+        dead_letters: Arc<DeadLetterStore>,
+        rate_limiter: Arc<CallRateLimiter>,
+        backends: Arc<BackendPool>,
+        drain: Arc<Drain>,
+        config: SharedConfig,
+    ) -> Self {
+        CallScheduler {
+            sessions,
+            ws_manager,
+            replicator,
+            cdrs,
+            tenants,
+            caller_ids,
+            dead_letters,
+            rate_limiter,
+            backends,
+            drain,
+            config,
+            attempts: DashMap::new(),
+        }
+    }
+
+    /// Place an outbound call, tracking it for re-dials if it has a retry policy
+    pub async fn place(&self, request: MakeCallRequest) -> Result<MakeCallResponse, PlaceCallError> {
+        self.place_attempt(request, 1).await
+    }
+
+    async fn place_attempt(&self, mut request: MakeCallRequest, attempt: u32) -> Result<MakeCallResponse, PlaceCallError> {
+        if self.drain.is_draining() {
+            info!("Not calling {}: this instance is draining", request.to_number);
+            return Err(PlaceCallError::new(Status::ServiceUnavailable, "Instance is draining"));
+        }
+        let config = self.config.current();
+        match self.caller_ids.resolve(&request, &self.tenants, &config).await {
+            Ok(caller_id) => request.from_number = Some(caller_id),
+            Err(e) => {
+                error!("Rejecting outbound call to {}: {}", request.to_number, e);
+                return Err(PlaceCallError::new(e.status(), e.to_string()));
+            }
+        }
+        let tracked = request.retry_policy.is_some().then(|| request.clone());
+        let tenant = request.tenant_id.as_deref().and_then(|id| self.tenants.get(id));
+
+        let response = place_outbound_call(
+            request,
+            &self.sessions,
+            &self.ws_manager,
+            &self.replicator,
+            &self.cdrs,
+            &self.rate_limiter,
+            &self.backends,
+            tenant.as_ref(),
+            &config
+        ).await?;
+
+        if let Some(request) = tracked {
+            self.attempts.insert(response.session_id.clone(), (request, attempt));
+        }
+
+        Ok(response)
+    }
+
+    /// Handle the end of a call, scheduling a re-dial if its retry policy allows one
+    ///
+    /// Records the attempt number on calls placed with a retry policy. Returns the
+    /// result callback URL when the call's result is final and should be reported
+    /// now; while a re-dial is pending, the next attempt reports instead.
+    pub fn call_ended(self: &Arc<Self>, record: &mut CallRecord, result_callback: Option<String>) -> Option<String> {
+        let Some((_, (request, attempt))) = self.attempts.remove(&record.call_sid) else {
+            return result_callback;
+        };
+        record.attempt = Some(attempt);
+
+        let Some(policy) = request.retry_policy.as_ref().filter(|p| p.should_retry(&record.status, attempt)) else {
+            return result_callback;
+        };
+
+        info!(
+            "Re-dialing {} in {} minutes after {} (attempt {}/{})",
+            request.to_number, policy.delay_minutes, record.status, attempt + 1, policy.max_attempts
+        );
+
+        let delay = Duration::from_secs(policy.delay_minutes * 60);
+        let last_record = record.clone();
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+
+            let to_number = request.to_number.clone();
+            if let Err(e) = scheduler.place_attempt(request, attempt + 1).await {
+                error!("Re-dial to {} failed: {}", to_number, e);
+
+                // Report the last attempt that did reach Twilio
+                if let Some(url) = result_callback {
+                    send_call_result(url, last_record, scheduler.config.current().callbacks.clone(), scheduler.dead_letters.clone());
+                }
+            }
+        });
+
+        None
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use base64::{Engine as _, engine::general_purpose};
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use rocket::fairing::AdHoc;
+use rocket::request::{FromRequest, Outcome, Request};
+use sha1::Sha1;
+use uuid::Uuid;
+
+use crate::config::{SharedConfig, TwilioConfig};
+
+/// Attempts before the webhook URL is declared unreachable
+const SELF_TEST_ATTEMPTS: u32 = 3;
+
+/// Pause between attempts, giving load balancers time to register the instance
+const SELF_TEST_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Header marking a self-test call, so whichever replica it reaches answers it
+const SELF_TEST_HEADER: &str = "X-Webhook-Self-Test";
+
+/// Call SID of this process's self-test call, which no real call can have
+fn self_test_call_sid() -> &'static str {
+    static CALL_SID: OnceLock<String> = OnceLock::new();
+    CALL_SID.get_or_init(|| format!("CA{}", Uuid::new_v4().simple()))
+}
+
+/// Value of the self-test header, derived from the auth token every replica shares
+fn self_test_token(auth_token: &str) -> String {
+    twilio_signature(auth_token, "webhook-self-test", &[])
+}
+
+/// Whether an incoming call is a startup self-test, sent by this or any other replica
+///
+/// A test call behind a load balancer may reach another instance, which must
+/// not open a backend session for it either.
+pub struct SelfTestCall(pub bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SelfTestCall {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(token) = request.headers().get_one(SELF_TEST_HEADER) else {
+            return Outcome::Success(SelfTestCall(false));
+        };
+        let Some(config) = request.rocket().state::<SharedConfig>().map(SharedConfig::current) else {
+            return Outcome::Success(SelfTestCall(false));
+        };
+        Outcome::Success(SelfTestCall(token == self_test_token(&config.twilio.auth_token)))
+    }
+}
+
+/// `X-Twilio-Signature` Twilio sends with a form POST to `url`
+///
+/// The URL followed by every parameter name and value, sorted by name, is
+/// signed with HMAC-SHA1 keyed by the account's auth token.
+pub fn twilio_signature(auth_token: &str, url: &str, params: &[(&str, &str)]) -> String {
+    let mut params = params.to_vec();
+    params.sort();
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(auth_token.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(url.as_bytes());
+    for (name, value) in params {
+        mac.update(name.as_bytes());
+        mac.update(value.as_bytes());
+    }
+    general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// Liftoff fairing POSTing a signed test call to the public incoming call URL
+///
+/// Only runs with `WEBHOOK_SELF_TEST` enabled. The call is answered with a
+/// hangup without opening a backend session by whichever replica receives it.
+/// If the public URL does not reach the service, a warning is logged; the
+/// instance keeps running, since the URL may only be unreachable for a moment.
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Webhook self-test", |rocket| Box::pin(async move {
+        let Some(config) = rocket.state::<SharedConfig>().map(SharedConfig::current) else {
+            return;
+        };
+        if !config.twilio.webhook_self_test {
+            return;
+        }
+
+        // Test in the background; the server only serves requests once liftoff completes
+        let twilio = config.twilio.clone();
+        tokio::spawn(async move {
+            let url = twilio.callback_url("/incoming_callback");
+            for attempt in 1..=SELF_TEST_ATTEMPTS {
+                match post_test_call(&url, &twilio).await {
+                    Ok(()) => {
+                        info!("Webhook self-test passed: {} reaches this instance", url);
+                        return;
+                    },
+                    Err(e) if attempt < SELF_TEST_ATTEMPTS => {
+                        warn!("Webhook self-test attempt {} failed: {}", attempt, e);
+                        tokio::time::sleep(SELF_TEST_RETRY_DELAY).await;
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Webhook self-test failed, Twilio may not reach this service at {}: {}; \
+                             check TWILIO_WEBHOOK_URL, PUBLIC_BASE_PATH and that the URL is reachable from the internet",
+                            url, e
+                        );
+                    },
+                }
+            }
+        });
+    }))
+}
+
+/// POST the self-test call and check that this instance answered it
+async fn post_test_call(url: &str, twilio: &TwilioConfig) -> Result<(), String> {
+    let params = [
+        ("AccountSid", twilio.account_sid.as_str()),
+        ("CallSid", self_test_call_sid()),
+        ("CallStatus", "ringing"),
+        ("Direction", "inbound"),
+        ("From", twilio.from_number.as_str()),
+        ("To", twilio.from_number.as_str()),
+    ];
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("X-Twilio-Signature", twilio_signature(&twilio.auth_token, url, &params))
+        .header(SELF_TEST_HEADER, self_test_token(&twilio.auth_token))
+        .form(&params)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("answered with status {}", status));
+    }
+    let twiml = response.text().await.map_err(|e| e.to_string())?;
+    if !twiml.contains("<Hangup/>") {
+        return Err("answered with TwiML from another service or a replica without the self-test".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use hmac::{Hmac, Mac};
+use log::{error, info, warn};
+use rocket::http::{ContentType, Status};
+use rocket::response::stream::ByteStream;
+use rocket::State;
+use sha2::Sha256;
+
+use crate::api::auth::constant_time_eq;
+use crate::bot::tts::{AudioFormat, SpeechSynthesis};
+use crate::config::{CurrentConfig, TwilioConfig};
+
+/// A catalog voice Twilio plays from signed synthesis URLs
+///
+/// URLs are signed with the auth token, so only text the service put in its own
+/// TwiML is synthesized.
+pub struct SynthesizedVoice {
+    voice: String,
+    language: Option<String>,
+    base_url: String,
+    key: String,
+}
+
+impl SynthesizedVoice {
+    /// The synthesized voice of a Twilio configuration, if its voice is a catalog voice
+    pub fn from_config(twilio: &TwilioConfig) -> Option<Self> {
+        Some(SynthesizedVoice {
+            voice: twilio.tts_voice.clone()?,
+            language: twilio.language.clone(),
+            base_url: twilio.callback_url("/tts"),
+            key: twilio.auth_token.clone(),
+        })
+    }
+
+    /// URL of the audio speaking `text`
+    pub fn url(&self, text: &str) -> String {
+        let mut url = format!(
+            "{}/{}?text={}&sig={}",
+            self.base_url,
+            urlencoding::encode(&self.voice),
+            urlencoding::encode(text),
+            sign(&self.key, &self.voice, self.language.as_deref(), text)
+        );
+        if let Some(language) = &self.language {
+            url.push_str(&format!("&language={}", urlencoding::encode(language)));
+        }
+        url
+    }
+}
+
+/// Sign a synthesis request as the hex HMAC of `<voice>\n<language>\n<text>`
+fn sign(key: &str, voice: &str, language: Option<&str>, text: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}", voice, language.unwrap_or_default(), text).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Stream a catalog voice speaking signed text, as the provider synthesizes it
+#[get("/tts/<voice>?<text>&<sig>&<language>")]
+pub async fn get_speech(
+    voice: &str,
+    text: &str,
+    sig: &str,
+    language: Option<&str>,
+    synthesis: &State<Arc<SpeechSynthesis>>,
+    config: CurrentConfig,
+) -> Result<(ContentType, ByteStream![Vec<u8>]), Status> {
+    let expected = sign(&config.twilio.auth_token, voice, language, text);
+    if !constant_time_eq(sig.as_bytes(), expected.as_bytes()) {
+        warn!("Rejected synthesis request with an invalid signature for voice {}", voice);
+        return Err(Status::Forbidden);
+    }
+
+    let Some(catalog_voice) = config.tts.voices.get(voice) else {
+        warn!("Synthesis requested for unknown voice {}", voice);
+        return Err(Status::NotFound);
+    };
+    let Some(synthesizer) = synthesis.synthesizer(&config.tts) else {
+        return Err(Status::NotFound);
+    };
+
+    let mut stream = match synthesizer.synthesize(text, catalog_voice, language, AudioFormat::Mp3).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Failed to synthesize speech with {}: {}", synthesizer.name(), e);
+            return Err(Status::BadGateway);
+        }
+    };
+
+    info!("Streaming {} speech for voice {}", synthesizer.name(), voice);
+    Ok((ContentType::new("audio", "mpeg"), ByteStream! {
+        loop {
+            match stream.next_chunk().await {
+                Ok(Some(chunk)) => yield chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Speech synthesis stream failed: {}", e);
+                    break;
+                }
+            }
+        }
+    }))
+}
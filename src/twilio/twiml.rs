@@ -1,134 +1,559 @@
 use std::fmt;
+use std::sync::{Arc, OnceLock};
+use log::warn;
+use regex::Regex;
+use rocket::response::{self, Responder};
+use rocket::Request;
+
+use crate::twilio::prompt_cache::PromptCache;
+use crate::twilio::synthesis::SynthesizedVoice;
+use crate::utils::Xml;
+
+/// Separates chunks of text spoken as separate Says with a pause between them
+pub const SAY_CHUNK_BREAK: char = '\u{2029}';
+
+/// Pause between the chunks of a split response, in seconds
+const CHUNK_PAUSE_SECONDS: u32 = 1;
+
+/// Length of each wait in the stream hold loop, in seconds
+const STREAM_HOLD_SECONDS: u32 = 60;
+
+/// SSML elements Twilio's `<Say>` accepts inside `<speak>`
+const SSML_ELEMENTS: [&str; 13] = [
+    "break", "emphasis", "lang", "p", "phoneme", "prosody", "s", "say-as", "sub", "w",
+    "amazon:domain", "amazon:effect", "amazon:emphasis",
+];
 
 /// TwiML response builder for Twilio voice responses
+///
+/// Verbs are collected as a typed tree and rendered when the response is built.
+/// Verbs that nest others only accept the children Twilio allows, so invalid
+/// nesting (e.g. a Dial inside a Gather) does not compile.
+#[derive(Clone)]
 pub struct TwiML {
-    content: String,
+    verbs: Vec<Verb>,
+    /// Whether the response apologizes for a failed turn; such responses are not replayed
+    failed: bool,
+}
+
+/// A top-level TwiML verb
+#[derive(Clone)]
+pub enum Verb {
+    Say(Say),
+    Play(Play),
+    Pause(u32),
+    Gather(Gather),
+    Dial(Dial),
+    Enqueue(Enqueue),
+    Record(Record),
+    /// Start a Media Stream forking the call audio
+    Start(Stream),
+    /// Hand the call to a bidirectional Media Stream until it closes
+    Connect(Stream),
+    Redirect(String),
+    Leave,
+    Hangup,
+    /// Decline the call without answering, optionally with a reason ("rejected" or "busy")
+    Reject(Option<String>),
+}
+
+/// A verb allowed inside a Gather
+#[derive(Clone)]
+pub enum GatherVerb {
+    Say(Say),
+    Play(Play),
+    Pause(u32),
+}
+
+/// Spoken text, plain or a `<speak>` SSML document
+#[derive(Clone)]
+pub struct Say {
+    pub text: String,
+    pub voice: String,
+    pub language: Option<String>,
+    /// SSML prosody rate (e.g. "90%")
+    pub rate: Option<String>,
+}
+
+/// Audio played to the caller
+#[derive(Clone)]
+pub enum Play {
+    Url { url: String, loop_count: Option<u32> },
+    /// DTMF tones sent on the call
+    Digits(String),
+}
+
+/// Speech and DTMF input collection with its prompts
+#[derive(Clone)]
+pub struct Gather {
+    pub input: Option<String>,
+    pub action: Option<String>,
+    pub method: Option<String>,
+    pub timeout: Option<u32>,
+    pub speech_timeout: Option<String>,
+    pub barge_in: Option<bool>,
+    pub partial_result_callback: Option<String>,
+    pub speech_model: Option<String>,
+    pub enhanced: Option<bool>,
+    pub language: Option<String>,
+    pub num_digits: Option<u32>,
+    pub hints: Option<String>,
+    pub children: Vec<GatherVerb>,
+}
+
+/// Connection of the caller to another party
+#[derive(Clone)]
+pub struct Dial {
+    pub timeout: u32,
+    /// URL called when the dial ends; without one the call continues with the next verb
+    pub action: Option<String>,
+    pub target: DialNoun,
+}
+
+/// Party a Dial connects to
+#[derive(Clone)]
+pub enum DialNoun {
+    Number(String),
+    Sip(String),
+    Conference(Conference),
+}
+
+/// Named conference room joined by every leg of a multi-party call
+#[derive(Clone)]
+pub struct Conference {
+    pub name: String,
+    /// Whether the conference starts when this participant joins; others hear hold music until then
+    pub start_on_enter: bool,
+    /// Whether the conference ends for everyone when this participant leaves
+    pub end_on_exit: bool,
+    /// Hold audio played before the conference starts, Twilio's default when absent
+    pub wait_url: Option<String>,
+}
+
+/// Placement of the caller in a named queue
+#[derive(Clone)]
+pub struct Enqueue {
+    pub queue_name: String,
+    pub wait_url: String,
+    pub action: String,
+}
+
+/// Recording of a message after a beep
+#[derive(Clone)]
+pub struct Record {
+    pub max_length: u32,
+    pub action: String,
+}
+
+/// Media Stream receiving the call audio
+#[derive(Clone)]
+pub struct Stream {
+    pub url: String,
+    pub track: String,
+}
+
+impl Default for TwiML {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TwiML {
     /// Create a new TwiML response
     pub fn new() -> Self {
         TwiML {
-            content: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response>"),
+            verbs: Vec::new(),
+            failed: false,
         }
     }
-    
+
+    /// Mark the response as answering a turn that failed, so a retry of the webhook runs it again
+    pub fn failed(mut self) -> Self {
+        self.failed = true;
+        self
+    }
+
+    /// Whether the response answers a turn that failed
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Add a verb to the response
+    pub fn verb(mut self, verb: Verb) -> Self {
+        self.verbs.push(verb);
+        self
+    }
+
     /// Add a Say verb to the response
-    pub fn say(mut self, text: &str, voice: &str, language: Option<&str>) -> Self {
-        self.content.push_str("<Say");
-        
-        if !voice.is_empty() {
-            self.content.push_str(&format!(" voice=\"{}\"", escape_xml_attr(voice)));
-        }
-        
-        if let Some(lang) = language {
-            if !lang.is_empty() {
-                self.content.push_str(&format!(" language=\"{}\"", escape_xml_attr(lang)));
-            }
-        }
-        
-        self.content.push_str(&format!(">{}</Say>", escape_xml(text)));
+    pub fn say(self, text: &str, voice: &str, language: Option<&str>) -> Self {
+        self.say_with_rate(text, voice, language, None)
+    }
+
+    /// Add a Say verb, wrapping the text in an SSML prosody rate (e.g. "90%") if given
+    ///
+    /// Text split with `SAY_CHUNK_BREAK` is spoken as several Says with pauses between.
+    pub fn say_with_rate(mut self, text: &str, voice: &str, language: Option<&str>, rate: Option<&str>) -> Self {
+        self.verbs.extend(chunked_says(text, voice, language, rate, None, Verb::Say, Verb::Play, Verb::Pause));
         self
     }
-    
-    /// Add a Gather verb to the response
-    pub fn gather(mut self, options: GatherOptions) -> Self {
-        self.content.push_str("<Gather");
-        
-        if let Some(input) = options.input {
-            self.content.push_str(&format!(" input=\"{}\"", escape_xml_attr(input)));
-        }
-        
-        if let Some(action) = options.action {
-            self.content.push_str(&format!(" action=\"{}\"", escape_xml_attr(action)));
-        }
-        
-        if let Some(method) = options.method {
-            self.content.push_str(&format!(" method=\"{}\"", escape_xml_attr(method)));
-        }
-        
-        if let Some(timeout) = options.timeout {
-            self.content.push_str(&format!(" timeout=\"{}\"", timeout));
-        }
-        
-        if let Some(speech_timeout) = options.speech_timeout {
-            self.content.push_str(&format!(" speechTimeout=\"{}\"", escape_xml_attr(speech_timeout)));
-        }
-        
-        if let Some(barge_in) = options.barge_in {
-            self.content.push_str(&format!(" bargeIn=\"{}\"", barge_in));
-        }
-        
-        if let Some(partial_result_callback) = options.partial_result_callback {
-            self.content.push_str(&format!(" partialResultCallback=\"{}\"", escape_xml_attr(partial_result_callback)));
-        }
-        
-        if let Some(speech_model) = options.speech_model {
-            self.content.push_str(&format!(" speechModel=\"{}\"", escape_xml_attr(speech_model)));
-        }
-        
-        if let Some(language) = options.language {
-            self.content.push_str(&format!(" language=\"{}\"", escape_xml_attr(language)));
-        }
-        
-        self.content.push_str(">");
-        
-        if let Some(say_text) = options.say_text {
-            self.content.push_str(&format!(
-                "<Say{}{}>{}</Say>",
-                if let Some(voice) = options.voice {
-                    format!(" voice=\"{}\"", escape_xml_attr(voice))
-                } else {
-                    String::new()
-                },
-                if let Some(language) = options.language {
-                    format!(" language=\"{}\"", escape_xml_attr(language))
-                } else {
-                    String::new()
-                },
-                escape_xml(&say_text)
-            ));
-        }
-        
-        self.content.push_str("</Gather>");
+
+    /// Speak text in the configured voice, playing it from the TTS provider for a catalog voice
+    pub fn speak(mut self, text: &str, config: &crate::config::TwilioConfig) -> Self {
+        let synthesized = SynthesizedVoice::from_config(config);
+        self.verbs.extend(chunked_says(
+            text,
+            &config.voice,
+            config.language.as_deref(),
+            config.speech_rate.as_deref(),
+            synthesized.as_ref(),
+            Verb::Say,
+            Verb::Play,
+            Verb::Pause,
+        ));
         self
     }
-    
+
+    /// Add a Gather verb to the response
+    pub fn gather(self, options: GatherOptions) -> Self {
+        self.verb(Verb::Gather(options.into()))
+    }
+
+    /// Add a Start verb that forks call audio to a Media Stream
+    pub fn start_stream(self, url: &str, track: &str) -> Self {
+        self.verb(Verb::Start(Stream {
+            url: url.to_string(),
+            track: track.to_string(),
+        }))
+    }
+
+    /// Add a Connect verb handing the call to a bidirectional Media Stream
+    pub fn connect_stream(self, url: &str) -> Self {
+        self.verb(Verb::Connect(Stream {
+            url: url.to_string(),
+            track: "inbound_track".to_string(),
+        }))
+    }
+
     /// Add a Hangup verb to the response
-    pub fn hangup(mut self) -> Self {
-        self.content.push_str("<Hangup/>");
-        self
+    pub fn hangup(self) -> Self {
+        self.verb(Verb::Hangup)
     }
-    
+
+    /// Add a Reject verb declining the call without answering it
+    pub fn reject(self, reason: Option<&str>) -> Self {
+        self.verb(Verb::Reject(reason.map(str::to_string)))
+    }
+
     /// Add a Redirect verb to the response
-    pub fn redirect(mut self, url: &str) -> Self {
-        self.content.push_str(&format!("<Redirect>{}</Redirect>", escape_xml(url)));
-        self
+    pub fn redirect(self, url: &str) -> Self {
+        self.verb(Verb::Redirect(url.to_string()))
     }
-    
+
+    /// Add a Gather followed by a Redirect to `url`, which Twilio follows when the Gather ends without input
+    pub fn gather_or_redirect(self, gather: Gather, url: &str) -> Self {
+        self.verb(Verb::Gather(gather)).redirect(url)
+    }
+
+    /// Add a Play verb to the response for an audio URL
+    pub fn play(self, url: &str, loop_count: Option<u32>) -> Self {
+        self.verb(Verb::Play(Play::Url {
+            url: url.to_string(),
+            loop_count,
+        }))
+    }
+
     /// Add a Play verb to the response with digits
-    pub fn play_digits(mut self, digits: &str) -> Self {
-        self.content.push_str(&format!("<Play digits=\"{}\"/>", escape_xml_attr(digits)));
-        self
+    pub fn play_digits(self, digits: &str) -> Self {
+        self.verb(Verb::Play(Play::Digits(digits.to_string())))
     }
-    
+
+    /// Add an Enqueue verb placing the caller in a named queue
+    pub fn enqueue(self, queue_name: &str, wait_url: &str, action: &str) -> Self {
+        self.verb(Verb::Enqueue(Enqueue {
+            queue_name: queue_name.to_string(),
+            wait_url: wait_url.to_string(),
+            action: action.to_string(),
+        }))
+    }
+
+    /// Add a Dial verb connecting the caller to a number or SIP address
+    pub fn dial(self, target: &str, timeout: u32, action: &str) -> Self {
+        self.verb(Verb::Dial(Dial {
+            timeout,
+            action: Some(action.to_string()),
+            target: DialNoun::from_target(target),
+        }))
+    }
+
+    /// Add a Dial verb joining the call to a conference
+    pub fn conference(self, conference: Conference, timeout: u32, action: Option<&str>) -> Self {
+        self.verb(Verb::Dial(Dial {
+            timeout,
+            action: action.map(str::to_string),
+            target: DialNoun::Conference(conference),
+        }))
+    }
+
+    /// Add a Record verb capturing a message after a beep
+    pub fn record(self, max_length: u32, action: &str) -> Self {
+        self.verb(Verb::Record(Record {
+            max_length,
+            action: action.to_string(),
+        }))
+    }
+
+    /// Add a Leave verb taking the caller out of the queue they are waiting in
+    pub fn leave(self) -> Self {
+        self.verb(Verb::Leave)
+    }
+
     /// Add a Pause verb to the response
-    pub fn pause(mut self, length: u32) -> Self {
-        self.content.push_str(&format!("<Pause length=\"{}\"/>", length));
+    pub fn pause(self, length: u32) -> Self {
+        self.verb(Verb::Pause(length))
+    }
+
+    /// Replace Says, including a Gather's prompts, with the audio `audio_url` finds for them
+    pub fn replace_says(mut self, mut audio_url: impl FnMut(&Say) -> Option<String>) -> Self {
+        let mut play = |say: &Say| audio_url(say).map(|url| Play::Url { url, loop_count: None });
+        for verb in &mut self.verbs {
+            match verb {
+                Verb::Say(say) => if let Some(audio) = play(say) {
+                    *verb = Verb::Play(audio);
+                },
+                Verb::Gather(gather) => for child in &mut gather.children {
+                    if let GatherVerb::Say(say) = child {
+                        if let Some(audio) = play(say) {
+                            *child = GatherVerb::Play(audio);
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
         self
     }
-    
+
     /// Finalize the TwiML response
-    pub fn build(mut self) -> String {
-        self.content.push_str("</Response>");
-        self.content
+    pub fn build(self) -> String {
+        self.to_string()
+    }
+}
+
+/// Webhook responses render the document, playing prompts whose audio is cached instead of speaking them
+impl<'r> Responder<'r, 'static> for TwiML {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let twiml = match request.rocket().state::<Arc<PromptCache>>() {
+            Some(cache) => cache.apply(self),
+            None => self,
+        };
+        Xml(twiml.build()).respond_to(request)
     }
 }
 
 impl fmt::Display for TwiML {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let response = format!("{}</Response>", self.content);
-        write!(f, "{}", response)
+        write!(f, "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response>")?;
+        for verb in &self.verbs {
+            write!(f, "{}", verb)?;
+        }
+        write!(f, "</Response>")
+    }
+}
+
+impl fmt::Display for Verb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verb::Say(say) => write!(f, "{}", say),
+            Verb::Play(play) => write!(f, "{}", play),
+            Verb::Pause(length) => write!(f, "<Pause length=\"{}\"/>", length),
+            Verb::Gather(gather) => write!(f, "{}", gather),
+            Verb::Dial(dial) => write!(f, "{}", dial),
+            Verb::Enqueue(enqueue) => write!(
+                f,
+                "<Enqueue waitUrl=\"{}\" waitUrlMethod=\"POST\" action=\"{}\" method=\"POST\">{}</Enqueue>",
+                escape_xml_attr(&enqueue.wait_url),
+                escape_xml_attr(&enqueue.action),
+                escape_xml(&enqueue.queue_name)
+            ),
+            Verb::Record(record) => write!(
+                f,
+                "<Record maxLength=\"{}\" playBeep=\"true\" action=\"{}\" method=\"POST\"/>",
+                record.max_length,
+                escape_xml_attr(&record.action)
+            ),
+            Verb::Start(stream) => write!(
+                f,
+                "<Start><Stream url=\"{}\" track=\"{}\"/></Start>",
+                escape_xml_attr(&stream.url),
+                escape_xml_attr(&stream.track)
+            ),
+            // Bidirectional streams always carry the inbound track
+            Verb::Connect(stream) => write!(
+                f,
+                "<Connect><Stream url=\"{}\"/></Connect>",
+                escape_xml_attr(&stream.url)
+            ),
+            Verb::Redirect(url) => write!(f, "<Redirect>{}</Redirect>", escape_xml(url)),
+            Verb::Leave => write!(f, "<Leave/>"),
+            Verb::Hangup => write!(f, "<Hangup/>"),
+            Verb::Reject(Some(reason)) => write!(f, "<Reject reason=\"{}\"/>", escape_xml_attr(reason)),
+            Verb::Reject(None) => write!(f, "<Reject/>"),
+        }
+    }
+}
+
+impl fmt::Display for GatherVerb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatherVerb::Say(say) => write!(f, "{}", say),
+            GatherVerb::Play(play) => write!(f, "{}", play),
+            GatherVerb::Pause(length) => write!(f, "<Pause length=\"{}\"/>", length),
+        }
+    }
+}
+
+impl Say {
+    /// Create a Say with an optional language and speaking rate
+    pub fn new(text: &str, voice: &str, language: Option<&str>, rate: Option<&str>) -> Self {
+        Say {
+            text: text.to_string(),
+            voice: voice.to_string(),
+            language: language.map(str::to_string),
+            rate: rate.map(str::to_string),
+        }
+    }
+
+    /// Whether the text is spoken as is, without SSML or a speaking rate
+    pub fn is_plain(&self) -> bool {
+        ssml_body(&self.text).is_none() && self.rate.as_deref().is_none_or(|rate| rate.is_empty() || self.text.is_empty())
+    }
+}
+
+impl fmt::Display for Say {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Say")?;
+
+        if !self.voice.is_empty() {
+            write!(f, " voice=\"{}\"", escape_xml_attr(&self.voice))?;
+        }
+
+        if let Some(lang) = self.language.as_deref().filter(|l| !l.is_empty()) {
+            write!(f, " language=\"{}\"", escape_xml_attr(lang))?;
+        }
+
+        write!(f, ">{}</Say>", say_content(&self.text, self.rate.as_deref()))
+    }
+}
+
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Play::Url { url, loop_count: Some(count) } => {
+                write!(f, "<Play loop=\"{}\">{}</Play>", count, escape_xml(url))
+            },
+            Play::Url { url, loop_count: None } => write!(f, "<Play>{}</Play>", escape_xml(url)),
+            Play::Digits(digits) => write!(f, "<Play digits=\"{}\"/>", escape_xml_attr(digits)),
+        }
+    }
+}
+
+impl fmt::Display for Gather {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Gather")?;
+
+        if let Some(input) = &self.input {
+            write!(f, " input=\"{}\"", escape_xml_attr(input))?;
+        }
+
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\"", escape_xml_attr(action))?;
+        }
+
+        if let Some(method) = &self.method {
+            write!(f, " method=\"{}\"", escape_xml_attr(method))?;
+        }
+
+        if let Some(timeout) = self.timeout {
+            write!(f, " timeout=\"{}\"", timeout)?;
+        }
+
+        if let Some(speech_timeout) = &self.speech_timeout {
+            write!(f, " speechTimeout=\"{}\"", escape_xml_attr(speech_timeout))?;
+        }
+
+        if let Some(barge_in) = self.barge_in {
+            write!(f, " bargeIn=\"{}\"", barge_in)?;
+        }
+
+        if let Some(partial_result_callback) = &self.partial_result_callback {
+            write!(f, " partialResultCallback=\"{}\"", escape_xml_attr(partial_result_callback))?;
+        }
+
+        if let Some(speech_model) = &self.speech_model {
+            write!(f, " speechModel=\"{}\"", escape_xml_attr(speech_model))?;
+        }
+
+        if let Some(enhanced) = self.enhanced {
+            write!(f, " enhanced=\"{}\"", enhanced)?;
+        }
+
+        if let Some(language) = &self.language {
+            write!(f, " language=\"{}\"", escape_xml_attr(language))?;
+        }
+
+        if let Some(num_digits) = self.num_digits {
+            write!(f, " numDigits=\"{}\"", num_digits)?;
+        }
+
+        if let Some(hints) = self.hints.as_deref().filter(|h| !h.is_empty()) {
+            write!(f, " hints=\"{}\"", escape_xml_attr(hints))?;
+        }
+
+        write!(f, ">")?;
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+        write!(f, "</Gather>")
+    }
+}
+
+impl DialNoun {
+    /// Pick the noun for a transfer target, dialing `sip:` addresses over SIP
+    pub fn from_target(target: &str) -> Self {
+        if target.starts_with("sip:") {
+            DialNoun::Sip(target.to_string())
+        } else {
+            DialNoun::Number(target.to_string())
+        }
+    }
+}
+
+impl fmt::Display for Dial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Dial timeout=\"{}\"", self.timeout)?;
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\" method=\"POST\"", escape_xml_attr(action))?;
+        }
+        write!(f, ">")?;
+
+        match &self.target {
+            DialNoun::Number(number) => write!(f, "{}", escape_xml(number))?,
+            DialNoun::Sip(address) => write!(f, "<Sip>{}</Sip>", escape_xml(address))?,
+            DialNoun::Conference(conference) => {
+                write!(
+                    f,
+                    "<Conference startConferenceOnEnter=\"{}\" endConferenceOnExit=\"{}\"",
+                    conference.start_on_enter,
+                    conference.end_on_exit
+                )?;
+                if let Some(wait_url) = &conference.wait_url {
+                    write!(f, " waitUrl=\"{}\"", escape_xml_attr(wait_url))?;
+                }
+                write!(f, ">{}</Conference>", escape_xml(&conference.name))?;
+            },
+        }
+
+        write!(f, "</Dial>")
     }
 }
 
@@ -142,9 +567,16 @@ pub struct GatherOptions<'a> {
     pub barge_in: Option<bool>,
     pub partial_result_callback: Option<&'a str>,
     pub speech_model: Option<&'a str>,
+    pub enhanced: Option<bool>,
     pub language: Option<&'a str>,
     pub say_text: Option<&'a str>,
     pub voice: Option<&'a str>,
+    pub num_digits: Option<u32>,
+    pub hints: Option<&'a str>,
+    pub play_url: Option<&'a str>,
+    pub speech_rate: Option<&'a str>,
+    /// Catalog voice `say_text` is played in instead of `voice`
+    pub synthesized: Option<&'a SynthesizedVoice>,
 }
 
 impl<'a> Default for GatherOptions<'a> {
@@ -158,9 +590,58 @@ impl<'a> Default for GatherOptions<'a> {
             barge_in: Some(true),
             partial_result_callback: None,
             speech_model: None,
+            enhanced: None,
             language: None,
             say_text: None,
             voice: None,
+            num_digits: None,
+            hints: None,
+            play_url: None,
+            speech_rate: None,
+            synthesized: None,
+        }
+    }
+}
+
+impl From<GatherOptions<'_>> for Gather {
+    /// Build a Gather prompting with the spoken text, then the audio URL
+    fn from(options: GatherOptions<'_>) -> Self {
+        let mut children = Vec::new();
+
+        if let Some(say_text) = options.say_text {
+            children.extend(chunked_says(
+                say_text,
+                options.voice.unwrap_or_default(),
+                options.language,
+                options.speech_rate,
+                options.synthesized,
+                GatherVerb::Say,
+                GatherVerb::Play,
+                GatherVerb::Pause,
+            ));
+        }
+
+        if let Some(play_url) = options.play_url {
+            children.push(GatherVerb::Play(Play::Url {
+                url: play_url.to_string(),
+                loop_count: None,
+            }));
+        }
+
+        Gather {
+            input: options.input.map(str::to_string),
+            action: options.action.map(str::to_string),
+            method: options.method.map(str::to_string),
+            timeout: options.timeout,
+            speech_timeout: options.speech_timeout.map(str::to_string),
+            barge_in: options.barge_in,
+            partial_result_callback: options.partial_result_callback.map(str::to_string),
+            speech_model: options.speech_model.map(str::to_string),
+            enhanced: options.enhanced,
+            language: options.language.map(str::to_string),
+            num_digits: options.num_digits,
+            hints: options.hints.map(str::to_string),
+            children,
         }
     }
 }
@@ -171,10 +652,187 @@ pub fn create_voice_response(
     config: &crate::config::TwilioConfig,
     timeout: u32,
     speech_timeout: &str
-) -> String {
+) -> TwiML {
+    append_voice_gather(TwiML::new(), text, None, None, config, timeout, speech_timeout)
+}
+
+/// Helper function to create a voice response that plays pre-rendered audio inside the Gather
+pub fn create_audio_response(
+    audio_url: &str,
+    config: &crate::config::TwilioConfig,
+    timeout: u32,
+    speech_timeout: &str
+) -> TwiML {
+    append_voice_gather(TwiML::new(), "", Some(audio_url), None, config, timeout, speech_timeout)
+}
+
+/// Helper function to play DTMF digits into the call, then keep listening to the caller
+pub fn create_digits_response(
+    digits: &str,
+    config: &crate::config::TwilioConfig,
+    timeout: u32,
+    speech_timeout: &str
+) -> TwiML {
+    append_voice_gather(TwiML::new().play_digits(digits), "", None, None, config, timeout, speech_timeout)
+}
+
+/// Helper function to create a conversational turn response with speech hints for the next answer
+///
+/// Plays `audio_url` when given, otherwise speaks `text`. The hints are added to the
+/// configured default hints.
+pub fn create_turn_response(
+    text: &str,
+    audio_url: Option<&str>,
+    hints: Option<&str>,
+    config: &crate::config::TwilioConfig,
+    timeout: u32,
+    speech_timeout: &str
+) -> TwiML {
+    append_voice_gather(TwiML::new(), text, audio_url, hints, config, timeout, speech_timeout)
+}
+
+/// Combine per-turn speech hints with the configured default hints
+pub fn merge_hints(turn_hints: Option<&str>, default_hints: Option<&str>) -> Option<String> {
+    let hints: Vec<&str> = [turn_hints, default_hints]
+        .into_iter()
+        .flatten()
+        .flat_map(|h| h.split(','))
+        .map(|h| h.trim())
+        .filter(|h| !h.is_empty())
+        .collect();
+
+    if hints.is_empty() {
+        None
+    } else {
+        Some(hints.join(", "))
+    }
+}
+
+/// Helper function to play the warm-up earcon while the call's session opens
+///
+/// Twilio requests the greeting URL once the earcon finishes.
+pub fn create_warmup_response(
+    earcon_url: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    TwiML::new()
+        .play(earcon_url, None)
+        .redirect(&config.callback_url("/greeting"))
+}
+
+/// Helper function to create the first voice response of a call
+///
+/// When Media Streams are enabled, the inbound audio is forked to the media stream
+/// endpoint before the Gather starts. The recording consent notice is spoken
+/// on its own so the caller cannot barge in over it; with a recording opt-out
+/// key configured, it is spoken in a DTMF Gather listening for the key instead.
+/// The call then moves to the consent endpoint, which starts the recording and
+/// plays the greeting. With language detection enabled, the Gather listens for any language.
+pub fn create_call_start_response(
+    text: &str,
+    consent: Option<&str>,
+    config: &crate::config::Config,
+    timeout: u32,
+    speech_timeout: &str
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    // Replies spoken over a bidirectional stream hear the caller through that stream
+    if config.media.enabled && config.twilio.reply_stream_url.is_none() {
+        twiml = twiml.start_stream(&config.media.url, "inbound_track");
+    }
+    
+    let detecting = config.languages.detecting(&config.twilio);
+    let twilio = detecting.as_ref().unwrap_or(&config.twilio);
+    
+    if let Some(consent) = consent.filter(|c| !c.is_empty()) {
+        twiml = match &config.recording.opt_out_digit {
+            Some(_) => {
+                let action_url = twilio.callback_url("/recording_opt_out");
+                let synthesized = SynthesizedVoice::from_config(twilio);
+                twiml.gather(GatherOptions {
+                    input: Some("dtmf"),
+                    action: Some(&action_url),
+                    timeout: Some(1),
+                    speech_timeout: None,
+                    language: twilio.language.as_deref(),
+                    say_text: Some(consent),
+                    voice: Some(&twilio.voice),
+                    num_digits: Some(1),
+                    speech_rate: twilio.speech_rate.as_deref(),
+                    synthesized: synthesized.as_ref(),
+                    ..GatherOptions::default()
+                })
+            },
+            None => twiml.speak(consent, twilio),
+        };
+        return twiml.redirect(&twilio.callback_url("/recording_consent"));
+    }
+    
+    append_voice_gather(twiml, text, None, None, twilio, timeout, speech_timeout)
+}
+
+/// Append the conversational speech Gather to a TwiML response
+///
+/// When keepalive is enabled, a silent caller is redirected to the first keepalive prompt.
+/// When replies are spoken over a Media Stream, the prompt is played before the
+/// call connects to the stream instead.
+fn append_voice_gather(
+    twiml: TwiML,
+    text: &str,
+    audio_url: Option<&str>,
+    hints: Option<&str>,
+    config: &crate::config::TwilioConfig,
+    timeout: u32,
+    speech_timeout: &str
+) -> TwiML {
+    if let Some(stream_url) = &config.reply_stream_url {
+        let mut twiml = twiml;
+        if !text.is_empty() {
+            twiml = twiml.speak(text, config);
+        }
+        if let Some(audio_url) = audio_url {
+            twiml = twiml.play(audio_url, None);
+        }
+        return twiml.connect_stream(stream_url);
+    }
+
+    let gather = voice_gather(text, audio_url, hints, config, timeout, speech_timeout);
+    append_keepalive_gather(twiml, gather, config, 1)
+}
+
+/// Append a speech Gather, redirecting to a keepalive prompt once it ends without speech if keepalive is enabled
+///
+/// With streaming recognition the Gather only plays the prompt and takes
+/// keypresses. The call then holds in the stream hold loop, and the caller's
+/// silence is judged from the Media Stream instead of by the Gather's timeout.
+fn append_keepalive_gather(twiml: TwiML, mut gather: Gather, config: &crate::config::TwilioConfig, attempt: u32) -> TwiML {
+    if config.streaming_stt {
+        let no_input = gather.timeout.unwrap_or(config.default_timeout);
+        gather.timeout = Some(1);
+        return twiml.gather_or_redirect(gather, &config.callback_url(&format!("/stream_hold?timeout={}", no_input)));
+    }
+    if config.keepalive_attempts == 0 {
+        return twiml.verb(Verb::Gather(gather));
+    }
+    twiml.gather_or_redirect(gather, &config.callback_url(&format!("/keepalive?attempt={}", attempt)))
+}
+
+/// Build the conversational speech Gather
+fn voice_gather(
+    text: &str,
+    audio_url: Option<&str>,
+    hints: Option<&str>,
+    config: &crate::config::TwilioConfig,
+    timeout: u32,
+    speech_timeout: &str
+) -> Gather {
     // Create longer-lived strings first
-    let action_url = format!("{}{}", config.webhook_url, "/transcription_callback");
-    let partial_callback_url = format!("{}{}", config.webhook_url, "/partial_callback");
+    let action_url = config.callback_url("/transcription_callback");
+    let partial_callback_url = config.callback_url("/partial_callback");
+    let dtmf_url = config.callback_url("/dtmf_callback");
+    let hints = merge_hints(hints, config.speech_hints.as_deref());
+    let synthesized = SynthesizedVoice::from_config(config);
 
     let gather_options = GatherOptions {
         input: Some("speech"),
@@ -185,25 +843,404 @@ pub fn create_voice_response(
         barge_in: Some(true),
         partial_result_callback: Some(&partial_callback_url),
         speech_model: Some(&config.speech_model),
+        enhanced: config.speech_enhanced.then_some(true),
         language: config.language.as_deref(),
-        say_text: Some(text),
+        say_text: if audio_url.is_some() && text.is_empty() { None } else { Some(text) },
         voice: Some(&config.voice),
+        num_digits: None,
+        hints: hints.as_deref(),
+        play_url: audio_url,
+        speech_rate: config.speech_rate.as_deref(),
+        synthesized: synthesized.as_ref(),
     };
 
-    TwiML::new()
-        .gather(gather_options)
-        .build()
+    // Streaming recognition hears the caller through the Media Stream; the Gather only plays the prompt
+    if config.streaming_stt {
+        return Gather::from(GatherOptions {
+            input: Some("dtmf"),
+            action: Some(&dtmf_url),
+            speech_timeout: None,
+            partial_result_callback: None,
+            speech_model: None,
+            enhanced: None,
+            hints: None,
+            ..gather_options
+        });
+    }
+
+    // The prompt is spoken in the configured language even when listening differs
+    let mut gather = Gather::from(gather_options);
+    if let Some(speech_language) = &config.speech_language {
+        gather.language = Some(speech_language.clone());
+    }
+
+    gather
+}
+
+/// Helper function to softly prompt a silent caller and listen again
+///
+/// The pause gives a caller who is still thinking a moment before the prompt.
+/// Another silent Gather leads to the next keepalive `attempt`.
+pub fn create_keepalive_response(
+    prompt: &str,
+    attempt: u32,
+    config: &crate::config::TwilioConfig,
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    if config.keepalive_pause_seconds > 0 {
+        twiml = twiml.pause(config.keepalive_pause_seconds);
+    }
+    let gather = voice_gather(prompt, None, None, config, config.default_timeout, "auto");
+    append_keepalive_gather(twiml, gather, config, attempt + 1)
+}
+
+/// Helper function to hold a call whose speech is recognized from the Media Stream
+///
+/// A long DTMF Gather stands in for a pause so keypresses still reach the DTMF
+/// callback; the Redirect loops back to the hold when it ends. Nothing is said,
+/// and no keepalive prompt follows.
+pub fn create_stream_hold_response(config: &crate::config::TwilioConfig) -> TwiML {
+    let gather = Gather {
+        input: Some("dtmf".to_string()),
+        action: Some(config.callback_url("/dtmf_callback")),
+        method: Some("POST".to_string()),
+        timeout: Some(STREAM_HOLD_SECONDS),
+        speech_timeout: None,
+        barge_in: None,
+        partial_result_callback: None,
+        speech_model: None,
+        enhanced: None,
+        language: None,
+        num_digits: None,
+        hints: None,
+        children: Vec::new(),
+    };
+    TwiML::new().gather_or_redirect(gather, &config.callback_url("/stream_hold"))
+}
+
+/// Helper function to keep the caller waiting on a turn that outlasted its webhook
+///
+/// No Gather is started, so the caller cannot start another turn; the queue
+/// callback is polled until the turn's TwiML is ready.
+pub fn create_turn_wait_response(
+    filler: Option<&str>,
+    config: &crate::config::TwilioConfig,
+) -> TwiML {
+    let twiml = match filler {
+        Some(filler) => TwiML::new().speak(filler, config),
+        None => TwiML::new().pause(1),
+    };
+    twiml.redirect(&config.callback_url("/queue_callback"))
+}
+
+/// Helper function to speak a chunk of a streamed reply and come back for the next one
+///
+/// The caller can still barge in; otherwise the queue callback is polled again
+/// after a one second Gather.
+pub fn create_queue_poll_response(
+    text: &str,
+    config: &crate::config::TwilioConfig,
+) -> TwiML {
+    let gather = voice_gather(text, None, None, config, 1, "1");
+    TwiML::new().gather_or_redirect(gather, &config.callback_url("/queue_callback"))
+}
+
+/// Helper function to render a backend IVR menu as a DTMF+speech Gather
+///
+/// A Redirect follows the Gather so that a timeout without input also reaches the
+/// menu callback, which then applies the menu's timeout action.
+pub fn create_menu_response(
+    menu: &crate::bot::menu::Menu,
+    preface: Option<&str>,
+    config: &crate::config::TwilioConfig,
+) -> TwiML {
+    let action_url = config.callback_url("/menu_callback");
+    let hints = menu.hints();
+    let prompt = match preface {
+        Some(preface) if !preface.is_empty() => format!("{} {}", preface, menu.prompt),
+        _ => menu.prompt.clone(),
+    };
+    let synthesized = SynthesizedVoice::from_config(config);
+
+    let gather_options = GatherOptions {
+        input: Some("dtmf speech"),
+        action: Some(&action_url),
+        method: Some("POST"),
+        timeout: Some(menu.timeout.unwrap_or(config.default_timeout)),
+        speech_timeout: Some("auto"),
+        barge_in: Some(true),
+        partial_result_callback: None,
+        speech_model: Some(&config.speech_model),
+        enhanced: config.speech_enhanced.then_some(true),
+        language: config.language.as_deref(),
+        say_text: Some(&prompt),
+        voice: Some(&config.voice),
+        num_digits: menu.num_digits(),
+        hints: Some(&hints),
+        play_url: None,
+        speech_rate: config.speech_rate.as_deref(),
+        synthesized: synthesized.as_ref(),
+    };
+
+    TwiML::new().gather_or_redirect(gather_options.into(), &action_url)
 }
 
 /// Helper function to create a hangup response
-pub fn create_hangup_response(text: Option<&str>, config: &crate::config::TwilioConfig) -> String {
+pub fn create_hangup_response(text: Option<&str>, config: &crate::config::TwilioConfig) -> TwiML {
     let mut twiml = TwiML::new();
     
     if let Some(message) = text {
-        twiml = twiml.say(message, &config.voice, config.language.as_deref());
+        twiml = twiml.speak(message, config);
+    }
+    
+    twiml.hangup()
+}
+
+/// Helper function to decline an inbound call without answering it
+pub fn create_reject_response(reason: Option<&str>) -> TwiML {
+    TwiML::new().reject(reason)
+}
+
+/// Helper function to place the caller in an agent queue after an optional announcement
+pub fn create_enqueue_response(
+    announcement: Option<&str>,
+    queue_name: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    if let Some(message) = announcement.filter(|m| !m.is_empty()) {
+        twiml = twiml.speak(message, config);
+    }
+    
+    let wait_url = config.callback_url("/queue_wait");
+    let action_url = config.callback_url("/queue_result");
+    
+    twiml.enqueue(queue_name, &wait_url, &action_url)
+}
+
+/// Helper function to create a response transferring the caller to a number or SIP address
+pub fn create_transfer_response(
+    announcement: Option<&str>,
+    target: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    if let Some(message) = announcement.filter(|m| !m.is_empty()) {
+        twiml = twiml.speak(message, config);
+    }
+    
+    let action_url = config.callback_url("/transfer_result");
+    
+    twiml.dial(target, config.transfer_timeout_seconds, &action_url)
+}
+
+/// Helper function to hold the caller in a conference while a third party is dialed in
+///
+/// The conference starts when the third party joins and ends when the caller leaves.
+pub fn create_escalation_response(
+    announcement: &str,
+    conference_name: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let action_url = config.callback_url("/escalation_result");
+    
+    TwiML::new()
+        .speak(announcement, config)
+        .conference(Conference {
+            name: conference_name.to_string(),
+            start_on_enter: false,
+            end_on_exit: true,
+            wait_url: config.hold_music_url.clone(),
+        }, config.transfer_timeout_seconds, Some(&action_url))
+}
+
+/// Helper function to whisper context to the escalation party, then join them to the caller's conference
+pub fn create_escalation_whisper_response(
+    whisper: &str,
+    conference_name: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    TwiML::new()
+        .speak(whisper, config)
+        .conference(Conference {
+            name: conference_name.to_string(),
+            start_on_enter: true,
+            end_on_exit: true,
+            wait_url: None,
+        }, config.transfer_timeout_seconds, None)
+        .hangup()
+}
+
+/// Helper function to move the caller into a conference with the agent taking the call over
+///
+/// The caller hears hold music until the agent joins. When replies were spoken over a
+/// bidirectional stream, the inbound audio is forked to the media stream again so the
+/// caller's speech is still transcribed.
+pub fn create_takeover_response(conference_name: &str, config: &crate::config::Config) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    if config.media.enabled && config.twilio.reply_stream_url.is_some() {
+        twiml = twiml.start_stream(&config.media.url, "inbound_track");
     }
     
-    twiml.hangup().build()
+    let action_url = config.twilio.callback_url("/takeover_result");
+    
+    twiml
+        .conference(Conference {
+            name: conference_name.to_string(),
+            start_on_enter: false,
+            end_on_exit: true,
+            wait_url: config.twilio.hold_music_url.clone(),
+        }, config.twilio.transfer_timeout_seconds, Some(&action_url))
+}
+
+/// Helper function to join the agent taking a call over to the caller's conference
+pub fn create_takeover_agent_response(conference_name: &str, config: &crate::config::TwilioConfig) -> TwiML {
+    TwiML::new()
+        .conference(Conference {
+            name: conference_name.to_string(),
+            start_on_enter: true,
+            end_on_exit: true,
+            wait_url: None,
+        }, config.transfer_timeout_seconds, None)
+        .hangup()
+}
+
+/// Helper function to create a response recording a message after an unanswered transfer
+pub fn create_transfer_voicemail_response(
+    prompt: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let action_url = config.callback_url("/transfer_voicemail");
+    
+    TwiML::new()
+        .speak(prompt, config)
+        .record(120, &action_url)
+        .hangup()
+}
+
+/// Helper function to apologize for a backend outage and transfer the caller to the fallback number
+pub fn create_outage_transfer_response(
+    apology: &str,
+    fallback_number: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let action_url = config.callback_url("/outage_result");
+    
+    TwiML::new()
+        .speak(apology, config)
+        .dial(fallback_number, config.transfer_timeout_seconds, &action_url)
+}
+
+/// Helper function to record a message during a backend outage, after an optional apology
+pub fn create_outage_voicemail_response(
+    apology: Option<&str>,
+    prompt: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    if let Some(apology) = apology {
+        twiml = twiml.speak(apology, config);
+    }
+    
+    let action_url = config.callback_url("/outage_voicemail");
+    
+    twiml.speak(prompt, config)
+        .record(120, &action_url)
+        .hangup()
+}
+
+/// Helper function to create a response asking for a single-digit rating after a transfer
+pub fn create_survey_response(
+    prompt: &str,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let action_url = config.callback_url("/transfer_survey");
+    let synthesized = SynthesizedVoice::from_config(config);
+    
+    TwiML::new()
+        .gather(GatherOptions {
+            input: Some("dtmf speech"),
+            action: Some(&action_url),
+            timeout: Some(5),
+            language: config.language.as_deref(),
+            say_text: Some(prompt),
+            voice: Some(&config.voice),
+            num_digits: Some(1),
+            speech_rate: config.speech_rate.as_deref(),
+            synthesized: synthesized.as_ref(),
+            ..GatherOptions::default()
+        })
+        .hangup()
+}
+
+/// Helper function to create the TwiML played to a caller waiting in a queue
+///
+/// Twilio requests the wait URL again once the TwiML finishes, so the hold
+/// audio (or a pause) paces how often the announcement repeats.
+pub fn create_queue_wait_response(
+    announcement: Option<&str>,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let mut twiml = TwiML::new();
+    
+    if let Some(message) = announcement.filter(|m| !m.is_empty()) {
+        twiml = twiml.speak(message, config);
+    }
+    
+    match &config.queue_hold_music_url {
+        Some(url) => twiml.play(url, None),
+        None => twiml.pause(30),
+    }
+}
+
+/// Helper function to put a caller on hold, looping the hold music until the call is updated
+pub fn create_hold_response(hold_music_url: &str) -> TwiML {
+    TwiML::new().play(hold_music_url, Some(0))
+}
+
+/// Helper function to create a voicemail drop response: play or say the message, then hang up
+///
+/// Messages that look like an audio URL are played with `<Play>`, anything else is spoken.
+pub fn create_voicemail_response(message: &str, config: &crate::config::TwilioConfig) -> TwiML {
+    let twiml = if is_audio_url(message) {
+        TwiML::new().play(message, None)
+    } else {
+        TwiML::new().speak(message, config)
+    };
+    
+    twiml.hangup()
+}
+
+/// Helper function to answer a call outside business hours, then record a message or hang up
+///
+/// Messages that look like an audio URL are played with `<Play>`, anything else is spoken.
+pub fn create_after_hours_response(
+    message: &str,
+    voicemail_prompt: Option<&str>,
+    config: &crate::config::TwilioConfig
+) -> TwiML {
+    let mut twiml = if is_audio_url(message) {
+        TwiML::new().play(message, None)
+    } else {
+        TwiML::new().speak(message, config)
+    };
+    
+    if let Some(prompt) = voicemail_prompt {
+        let action_url = config.callback_url("/after_hours_voicemail");
+        twiml = twiml.speak(prompt, config).record(120, &action_url);
+    }
+    
+    twiml.hangup()
+}
+
+/// Check whether a string is an audio URL rather than text to speak
+pub fn is_audio_url(s: &str) -> bool {
+    let s = s.trim();
+    (s.starts_with("https://") || s.starts_with("http://")) && !s.contains(char::is_whitespace)
 }
 
 /// Escape XML text content
@@ -213,15 +1250,438 @@ fn escape_xml(s: &str) -> String {
         .replace(">", "&gt;")
 }
 
+/// Says for each chunk of the text, separated by pauses
+///
+/// With a synthesized voice, each chunk is played from its synthesis URL instead.
+#[allow(clippy::too_many_arguments)]
+fn chunked_says<V>(
+    text: &str,
+    voice: &str,
+    language: Option<&str>,
+    rate: Option<&str>,
+    synthesized: Option<&SynthesizedVoice>,
+    say: impl Fn(Say) -> V,
+    play: impl Fn(Play) -> V,
+    pause: impl Fn(u32) -> V,
+) -> Vec<V> {
+    let mut verbs = Vec::new();
+    for (index, chunk) in text.split(SAY_CHUNK_BREAK).enumerate() {
+        if index > 0 {
+            verbs.push(pause(CHUNK_PAUSE_SECONDS));
+        }
+        verbs.push(match synthesized {
+            Some(synthesized) => play(Play::Url { url: synthesized.url(chunk), loop_count: None }),
+            None => say(Say::new(chunk, voice, language, rate)),
+        });
+    }
+    verbs
+}
+
+/// Render Say text, wrapped in SSML prosody when a speaking rate is set
+///
+/// Text wrapped in `<speak>` is SSML and is inserted without escaping once it
+/// is known to hold only SSML elements; see [`ssml_body`].
+fn say_content(text: &str, rate: Option<&str>) -> String {
+    let content = match ssml_body(text) {
+        Some(ssml) => ssml.to_string(),
+        None => escape_xml(text),
+    };
+    
+    match rate {
+        Some(rate) if !rate.is_empty() && !text.is_empty() => {
+            format!("<prosody rate=\"{}\">{}</prosody>", escape_xml_attr(rate), content)
+        },
+        _ => content,
+    }
+}
+
+/// The markup inside a `<speak>` document, if the text is one
+///
+/// A document using anything but properly nested [`SSML_ELEMENTS`] and XML
+/// entities is not SSML, so it is escaped and cannot add TwiML to the response.
+fn ssml_body(text: &str) -> Option<&str> {
+    let body = text.trim()
+        .strip_prefix("<speak>")?
+        .strip_suffix("</speak>")?;
+    
+    if is_allowed_ssml(body) {
+        Some(body)
+    } else {
+        warn!("Speaking SSML with elements other than {:?} as plain text", SSML_ELEMENTS);
+        None
+    }
+}
+
+/// Whether SSML markup uses only allowed elements, properly nested, and valid entities
+fn is_allowed_ssml(markup: &str) -> bool {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static ENTITY: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(
+        r#"^<(?P<close>/)?(?P<name>[a-z][a-z:-]*)(?P<attrs>(?:\s+[a-zA-Z:-]+\s*=\s*(?:"[^"<>]*"|'[^'<>]*'))*)\s*(?P<empty>/)?>$"#
+    ).expect("valid SSML tag pattern"));
+    let entity = ENTITY.get_or_init(|| Regex::new(
+        r"^&(?:amp|lt|gt|quot|apos|#[0-9]+|#x[0-9a-fA-F]+);"
+    ).expect("valid entity pattern"));
+    
+    if !markup.match_indices('&').all(|(index, _)| entity.is_match(&markup[index..])) {
+        return false;
+    }
+    
+    let mut open = Vec::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            return false;
+        };
+        let Some(captures) = tag.captures(&rest[start..start + end + 1]) else {
+            return false;
+        };
+        let name = captures.name("name").map_or("", |m| m.as_str());
+        if !SSML_ELEMENTS.contains(&name) {
+            return false;
+        }
+        match (captures.name("close").is_some(), captures.name("empty").is_some()) {
+            (true, false) if captures["attrs"].is_empty() => {
+                if open.pop() != Some(name) {
+                    return false;
+                }
+            },
+            (false, false) => open.push(name),
+            (false, true) => {},
+            _ => return false,
+        }
+        rest = &rest[start + end + 1..];
+    }
+    
+    open.is_empty()
+}
+
 /// Escape XML attribute values
 fn escape_xml_attr(s: &str) -> String {
     escape_xml(s)
         .replace("\"", "&quot;")
         .replace("'", "&apos;")
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TwilioConfig;
+
+    const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Response>";
+
+    fn twilio_config() -> TwilioConfig {
+        TwilioConfig {
+            account_sid: "AC123".to_string(),
+            auth_token: "token".to_string(),
+            from_number: "+15550000000".to_string(),
+            webhook_url: "https://bot.example.com/twilio".to_string(),
+            webhook_port: 8000,
+            voice: "Polly.Joanna".to_string(),
+            speech_model: "phone_call".to_string(),
+            speech_enhanced: false,
+            default_timeout: 5,
+            adaptive_timeout: false,
+            short_answer_timeout: 3,
+            open_answer_timeout: 8,
+            open_answer_speech_timeout: "auto".to_string(),
+            partial_processing: false,
+            partial_stability_ms: 0,
+            partial_stability_count: 1,
+            speech_hints: None,
+            speech_rate: None,
+            language: Some("en-US".to_string()),
+            speech_language: None,
+            region: None,
+            edge: None,
+            webhook_replay_ttl_seconds: 0,
+            lookup_enabled: false,
+            lookup_timeout_ms: 0,
+            sip_auth_username: None,
+            sip_auth_password: None,
+            call_job_ttl_seconds: 0,
+            agent_queue: "support".to_string(),
+            queue_hold_music_url: None,
+            hold_music_url: None,
+            queue_max_wait_seconds: 0,
+            transfer_timeout_seconds: 20,
+            transfer_voicemail_enabled: false,
+            transfer_survey_enabled: false,
+            speech_confidence_threshold: 0.0,
+            clarification_includes_text: false,
+            inbound_numbers: Vec::new(),
+            provision_numbers_on_startup: false,
+            allowed_country_codes: Vec::new(),
+            public_base_path: None,
+            trust_forwarded_headers: false,
+            versioned_webhooks: false,
+            webhook_self_test: false,
+            keepalive_attempts: 0,
+            keepalive_pause_seconds: 0,
+            webhook_response_timeout_ms: 8000,
+            streaming_stt: false,
+            tts_voice: None,
+            reply_stream_url: None,
+        }
+    }
 
-/// Helper function to determine if text ends with sentence punctuation
-pub fn ends_with_sentence_punctuation(text: &str) -> bool {
-    let trimmed = text.trim();
-    trimmed.ends_with(".") || trimmed.ends_with("!") || trimmed.ends_with("?")
-}
\ No newline at end of file
+    fn document(verbs: &str) -> String {
+        format!("{}{}</Response>", HEADER, verbs)
+    }
+
+    #[test]
+    fn renders_an_empty_response() {
+        assert_eq!(TwiML::new().build(), document(""));
+    }
+
+    #[test]
+    fn renders_every_top_level_verb() {
+        let cases: Vec<(TwiML, &str)> = vec![
+            (TwiML::new().say("Hello", "alice", None), "<Say voice=\"alice\">Hello</Say>"),
+            (TwiML::new().say("Hola", "", Some("es-ES")), "<Say language=\"es-ES\">Hola</Say>"),
+            (TwiML::new().play("https://a.example/x.mp3", None), "<Play>https://a.example/x.mp3</Play>"),
+            (TwiML::new().play("https://a.example/x.mp3", Some(0)), "<Play loop=\"0\">https://a.example/x.mp3</Play>"),
+            (TwiML::new().play_digits("1w2"), "<Play digits=\"1w2\"/>"),
+            (TwiML::new().pause(2), "<Pause length=\"2\"/>"),
+            (
+                TwiML::new().dial("+15551234567", 20, "https://b.example/t"),
+                "<Dial timeout=\"20\" action=\"https://b.example/t\" method=\"POST\">+15551234567</Dial>",
+            ),
+            (
+                TwiML::new().dial("sip:agent@pbx.example", 20, "https://b.example/t"),
+                "<Dial timeout=\"20\" action=\"https://b.example/t\" method=\"POST\"><Sip>sip:agent@pbx.example</Sip></Dial>",
+            ),
+            (
+                TwiML::new().conference(Conference {
+                    name: "room".to_string(),
+                    start_on_enter: true,
+                    end_on_exit: false,
+                    wait_url: Some("https://b.example/hold".to_string()),
+                }, 30, None),
+                "<Dial timeout=\"30\"><Conference startConferenceOnEnter=\"true\" endConferenceOnExit=\"false\" waitUrl=\"https://b.example/hold\">room</Conference></Dial>",
+            ),
+            (
+                TwiML::new().enqueue("support", "https://b.example/w", "https://b.example/r"),
+                "<Enqueue waitUrl=\"https://b.example/w\" waitUrlMethod=\"POST\" action=\"https://b.example/r\" method=\"POST\">support</Enqueue>",
+            ),
+            (
+                TwiML::new().record(60, "https://b.example/vm"),
+                "<Record maxLength=\"60\" playBeep=\"true\" action=\"https://b.example/vm\" method=\"POST\"/>",
+            ),
+            (
+                TwiML::new().start_stream("wss://b.example/media", "inbound_track"),
+                "<Start><Stream url=\"wss://b.example/media\" track=\"inbound_track\"/></Start>",
+            ),
+            (
+                TwiML::new().connect_stream("wss://b.example/reply"),
+                "<Connect><Stream url=\"wss://b.example/reply\"/></Connect>",
+            ),
+            (TwiML::new().redirect("https://b.example/next"), "<Redirect>https://b.example/next</Redirect>"),
+            (TwiML::new().leave(), "<Leave/>"),
+            (TwiML::new().hangup(), "<Hangup/>"),
+            (TwiML::new().reject(None), "<Reject/>"),
+            (TwiML::new().reject(Some("busy")), "<Reject reason=\"busy\"/>"),
+        ];
+
+        for (twiml, verbs) in cases {
+            assert_eq!(twiml.build(), document(verbs));
+        }
+    }
+
+    #[test]
+    fn renders_a_gather_with_its_attributes_and_prompts() {
+        let twiml = TwiML::new().gather(GatherOptions {
+            input: Some("dtmf speech"),
+            action: Some("https://b.example/menu?a=1&b=2"),
+            timeout: Some(4),
+            speech_timeout: Some("auto"),
+            partial_result_callback: Some("https://b.example/partial"),
+            speech_model: Some("phone_call"),
+            enhanced: Some(true),
+            language: Some("en-US"),
+            say_text: Some("Press one"),
+            voice: Some("alice"),
+            num_digits: Some(1),
+            hints: Some("sales, support"),
+            play_url: Some("https://a.example/beep.mp3"),
+            ..GatherOptions::default()
+        });
+
+        assert_eq!(twiml.build(), document(concat!(
+            "<Gather input=\"dtmf speech\" action=\"https://b.example/menu?a=1&amp;b=2\" method=\"POST\" ",
+            "timeout=\"4\" speechTimeout=\"auto\" bargeIn=\"true\" partialResultCallback=\"https://b.example/partial\" ",
+            "speechModel=\"phone_call\" enhanced=\"true\" language=\"en-US\" numDigits=\"1\" hints=\"sales, support\">",
+            "<Say voice=\"alice\" language=\"en-US\">Press one</Say><Play>https://a.example/beep.mp3</Play>",
+            "</Gather>",
+        )));
+    }
+
+    #[test]
+    fn omits_empty_hints() {
+        let twiml = TwiML::new().gather(GatherOptions {
+            hints: Some(""),
+            ..GatherOptions::default()
+        });
+        assert!(!twiml.build().contains("hints="));
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        let twiml = TwiML::new()
+            .say("Tom & Jerry <3", "a\"b", Some("en'US"))
+            .redirect("https://b.example/x?a=1&b=<2>");
+
+        assert_eq!(twiml.build(), document(concat!(
+            "<Say voice=\"a&quot;b\" language=\"en&apos;US\">Tom &amp; Jerry &lt;3</Say>",
+            "<Redirect>https://b.example/x?a=1&amp;b=&lt;2&gt;</Redirect>",
+        )));
+    }
+
+    #[test]
+    fn splits_chunked_text_into_says_with_pauses() {
+        let text = format!("First{}Second", SAY_CHUNK_BREAK);
+        let twiml = TwiML::new().say(&text, "alice", None);
+
+        assert_eq!(twiml.build(), document("<Say voice=\"alice\">First</Say><Pause length=\"1\"/><Say voice=\"alice\">Second</Say>"));
+    }
+
+    #[test]
+    fn wraps_text_in_prosody_for_a_speaking_rate() {
+        let twiml = TwiML::new().say_with_rate("Slowly", "alice", None, Some("90%"));
+        assert_eq!(twiml.build(), document("<Say voice=\"alice\"><prosody rate=\"90%\">Slowly</prosody></Say>"));
+    }
+
+    #[test]
+    fn keeps_allowed_ssml() {
+        let text = "<speak>Hi <break time=\"1s\"/><prosody rate='slow'>there</prosody> &amp; bye</speak>";
+        let twiml = TwiML::new().say(text, "alice", None);
+
+        assert_eq!(twiml.build(), document(
+            "<Say voice=\"alice\">Hi <break time=\"1s\"/><prosody rate='slow'>there</prosody> &amp; bye</Say>"
+        ));
+    }
+
+    #[test]
+    fn escapes_speak_documents_that_are_not_ssml() {
+        let cases = [
+            "<speak>Bye</Say><Redirect>https://evil.example</Redirect><Say>x</speak>",
+            "<speak><prosody rate=\"slow\">unclosed</speak>",
+            "<speak><break/></prosody></speak>",
+            "<speak>Tom & Jerry</speak>",
+            "<speak><!-- comment --></speak>",
+            "<speak><break time=\"1s\" onload=\"<x>\"/></speak>",
+        ];
+
+        for text in cases {
+            let rendered = TwiML::new().say(text, "", None).build();
+            assert_eq!(rendered, document(&format!("<Say>{}</Say>", escape_xml(text))), "{}", text);
+        }
+    }
+
+    #[test]
+    fn replaces_says_with_cached_audio() {
+        let twiml = TwiML::new()
+            .say("cached", "alice", None)
+            .say("fresh", "alice", None)
+            .gather(GatherOptions {
+                say_text: Some("cached"),
+                voice: Some("alice"),
+                ..GatherOptions::default()
+            })
+            .replace_says(|say| (say.text == "cached").then(|| "https://cdn.example/cached.mp3".to_string()));
+
+        let rendered = twiml.build();
+        assert_eq!(rendered.matches("<Play>https://cdn.example/cached.mp3</Play>").count(), 2);
+        assert!(rendered.contains("<Say voice=\"alice\">fresh</Say>"));
+    }
+
+    #[test]
+    fn picks_the_dial_noun_from_the_target() {
+        assert!(matches!(DialNoun::from_target("sip:a@b.example"), DialNoun::Sip(_)));
+        assert!(matches!(DialNoun::from_target("+15551234567"), DialNoun::Number(_)));
+    }
+
+    #[test]
+    fn marks_failed_responses_without_rendering_it() {
+        let twiml = TwiML::new().hangup().failed();
+        assert!(twiml.is_failed());
+        assert!(!TwiML::new().is_failed());
+        assert_eq!(twiml.build(), document("<Hangup/>"));
+    }
+
+    #[test]
+    fn merges_turn_hints_with_default_hints() {
+        assert_eq!(merge_hints(Some("yes, no"), Some("agent")), Some("yes, no, agent".to_string()));
+        assert_eq!(merge_hints(Some(" , "), None), None);
+        assert_eq!(merge_hints(None, None), None);
+    }
+
+    #[test]
+    fn creates_a_voice_response_listening_for_speech() {
+        let config = twilio_config();
+        let twiml = create_voice_response("How can I help?", &config, 5, "auto");
+
+        let rendered = twiml.build();
+        assert!(rendered.starts_with(&format!("{}<Gather input=\"speech\"", HEADER)));
+        assert!(rendered.contains("action=\"https://bot.example.com/twilio/transcription_callback\""));
+        assert!(rendered.contains("<Say voice=\"Polly.Joanna\" language=\"en-US\">How can I help?</Say></Gather>"));
+        assert!(!rendered.contains("<Redirect>"));
+    }
+
+    #[test]
+    fn redirects_a_silent_caller_to_keepalive_when_enabled() {
+        let config = TwilioConfig { keepalive_attempts: 2, ..twilio_config() };
+        let rendered = create_voice_response("Hello", &config, 5, "auto").build();
+
+        assert!(rendered.ends_with("</Gather><Redirect>https://bot.example.com/twilio/keepalive?attempt=1</Redirect></Response>"));
+    }
+
+    #[test]
+    fn holds_instead_of_keepalive_with_streaming_recognition() {
+        let config = TwilioConfig { streaming_stt: true, keepalive_attempts: 2, ..twilio_config() };
+        let rendered = create_voice_response("Hello", &config, 7, "auto").build();
+
+        assert!(rendered.contains("<Gather input=\"dtmf\" action=\"https://bot.example.com/twilio/dtmf_callback\" method=\"POST\" timeout=\"1\""));
+        assert!(rendered.ends_with("</Gather><Redirect>https://bot.example.com/twilio/stream_hold?timeout=7</Redirect></Response>"));
+        assert!(!rendered.contains("keepalive"));
+
+        let hold = create_stream_hold_response(&config).build();
+        assert_eq!(hold, document(concat!(
+            "<Gather input=\"dtmf\" action=\"https://bot.example.com/twilio/dtmf_callback\" method=\"POST\" timeout=\"60\"></Gather>",
+            "<Redirect>https://bot.example.com/twilio/stream_hold</Redirect>",
+        )));
+    }
+
+    #[test]
+    fn speaks_over_the_reply_stream_instead_of_gathering() {
+        let config = TwilioConfig { reply_stream_url: Some("wss://bot.example.com/reply".to_string()), ..twilio_config() };
+        let rendered = create_voice_response("Hello", &config, 5, "auto").build();
+
+        assert_eq!(rendered, document(concat!(
+            "<Say voice=\"Polly.Joanna\" language=\"en-US\">Hello</Say>",
+            "<Connect><Stream url=\"wss://bot.example.com/reply\"/></Connect>",
+        )));
+    }
+
+    #[test]
+    fn creates_hangup_and_transfer_responses() {
+        let config = twilio_config();
+
+        assert_eq!(create_hangup_response(None, &config).build(), document("<Hangup/>"));
+        assert_eq!(
+            create_hangup_response(Some("Bye"), &config).build(),
+            document("<Say voice=\"Polly.Joanna\" language=\"en-US\">Bye</Say><Hangup/>")
+        );
+        assert_eq!(
+            create_transfer_response(Some(""), "+15551234567", &config).build(),
+            document("<Dial timeout=\"20\" action=\"https://bot.example.com/twilio/transfer_result\" method=\"POST\">+15551234567</Dial>")
+        );
+        assert_eq!(
+            create_enqueue_response(None, "support", &config).build(),
+            document(concat!(
+                "<Enqueue waitUrl=\"https://bot.example.com/twilio/queue_wait\" waitUrlMethod=\"POST\" ",
+                "action=\"https://bot.example.com/twilio/queue_result\" method=\"POST\">support</Enqueue>",
+            ))
+        );
+        assert_eq!(create_reject_response(Some("rejected")).build(), document("<Reject reason=\"rejected\"/>"));
+    }
+}
@@ -0,0 +1,53 @@
+use log::warn;
+use rocket::form;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::Request;
+
+/// Longest speech result or other free text Twilio sends
+pub const MAX_TEXT_LENGTH: usize = 4096;
+
+/// Longest status, code or other short field
+pub const MAX_FIELD_LENGTH: usize = 64;
+
+/// Longest phone number or SIP address
+pub const MAX_ADDRESS_LENGTH: usize = 256;
+
+/// Longest URL, such as a recording URL
+pub const MAX_URL_LENGTH: usize = 1024;
+
+/// Check that a field is at most `max` characters long
+///
+/// Unlike Rocket's `len`, which answers 413, an overlong field is a malformed callback.
+pub fn max_length<'v>(value: &Option<String>, max: usize) -> form::Result<'v, ()> {
+    match value {
+        Some(value) if value.chars().count() > max => {
+            Err(form::Error::validation(format!("must be at most {} characters", max)))?
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Check a Twilio SID: its two-letter type prefix followed by 32 lowercase hex digits
+///
+/// A missing SID passes; handlers decide whether they need one.
+pub fn sid<'v>(value: &Option<String>, prefixes: &[&str]) -> form::Result<'v, ()> {
+    let Some(sid) = value else {
+        return Ok(());
+    };
+
+    let valid = sid.len() == 34
+        && prefixes.iter().any(|prefix| sid.starts_with(prefix))
+        && sid[2..].bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    if !valid {
+        Err(form::Error::validation(format!("must be a {} SID", prefixes.join(" or "))))?;
+    }
+    Ok(())
+}
+
+/// Answer a malformed Twilio callback with 400 instead of Rocket's 422
+#[catch(422)]
+pub fn malformed_callback(request: &Request) -> Custom<&'static str> {
+    warn!("Rejected malformed Twilio callback to {}", request.uri());
+    Custom(Status::BadRequest, "Malformed callback")
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use log::{error, info};
+
+use crate::bot::cdr::HangupSource;
+use crate::bot::session::SessionStore;
+use crate::config::{Config, SharedConfig};
+use crate::i18n::{MessageCatalog, Phrase};
+use crate::replication::{ReplicaState, SessionReplicator};
+use crate::twilio::client::TwilioClient;
+use crate::twilio::twiml::create_hangup_response;
+
+/// How often the watchdog checks call durations
+const WATCHDOG_INTERVAL_SECONDS: u64 = 30;
+
+/// Start a task that ends calls running past the maximum call duration
+///
+/// The transcription handler enforces the limits between turns; the watchdog
+/// catches calls stuck without turns (e.g. long holds or silent loops).
+pub fn start_call_duration_watchdog(
+    sessions: Arc<SessionStore>,
+    catalog: Arc<MessageCatalog>,
+    replicator: Arc<SessionReplicator>,
+    config: SharedConfig,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(WATCHDOG_INTERVAL_SECONDS));
+
+        loop {
+            interval.tick().await;
+
+            // Checked every tick, since a reload may change the limit
+            let config = config.current();
+            if config.session.max_call_duration_minutes == 0 {
+                continue;
+            }
+
+            for (call_sid, tenant_id) in expire_long_calls(&sessions, &replicator, &config).await {
+                end_call(&call_sid, tenant_id.as_deref(), &catalog, &config).await;
+            }
+        }
+    });
+}
+
+/// Mark sessions past the duration limit as ending and return their call SIDs and tenants
+async fn expire_long_calls(
+    sessions: &SessionStore,
+    replicator: &Arc<SessionReplicator>,
+    config: &Config,
+) -> Vec<(String, Option<String>)> {
+    let mut expired = Vec::new();
+
+    for handle in sessions.handles() {
+        let mut session = handle.lock().await;
+        // The duration limit bounds the bot's calls, not an agent's
+        if session.session_ends || session.takeover.is_some() || session.exceeded_limit(0, config.session.max_call_duration_minutes).is_none() {
+            continue;
+        }
+
+        let call_sid = match &session.conversation_id {
+            Some(call_sid) => call_sid.clone(),
+            None => continue,
+        };
+
+        info!("Ending call {}: max_duration reached", call_sid);
+        session.session_ends = true;
+        session.disposition = Some("max_duration".to_string());
+        session.hangup_source = Some(HangupSource::Bot);
+        replicator.replicate(&mut session, ReplicaState::Ending);
+        expired.push((call_sid, session.tenant_id.clone()));
+    }
+
+    expired
+}
+
+/// Replace the live call's TwiML with a goodbye and hangup
+async fn end_call(call_sid: &str, tenant_id: Option<&str>, catalog: &MessageCatalog, config: &Config) {
+    let twilio_client = match TwilioClient::new(
+        config.twilio.account_sid.clone(),
+        config.twilio.auth_token.clone(),
+        config.twilio.region.clone(),
+        config.twilio.edge.clone()
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Twilio client: {}", e);
+            return;
+        }
+    };
+
+    let language = config.twilio.language.as_deref();
+    let twiml = create_hangup_response(
+        Some(&catalog.tenant_text(Phrase::CallLimitReached, language, tenant_id)),
+        &config.twilio
+    );
+
+    if let Err(e) = twilio_client.update_call_with_retry(
+        call_sid,
+        &twiml.build(),
+        config.backend.retry_attempts,
+        config.backend.retry_base_delay_ms
+    ).await {
+        error!("Failed to end call {} at max duration: {}", call_sid, e);
+    }
+}
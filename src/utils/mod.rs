@@ -1,3 +1,5 @@
+pub mod phone;
+
 use rocket::http::ContentType;
 use rocket::request::Request;
 use rocket::response::{self, Responder, Response};
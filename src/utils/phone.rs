@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// Fewest digits in a dialable E.164 number, country code included
+const MIN_DIGITS: usize = 8;
+/// Most digits E.164 allows, country code included
+const MAX_DIGITS: usize = 15;
+
+/// Why a phone number was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PhoneNumberError {
+    Empty,
+    /// The number has characters other than digits, separators and a leading `+`
+    InvalidCharacters(String),
+    /// The number has no `+` or `00` international prefix
+    MissingCountryCode(String),
+    /// The number has too few or too many digits, or starts with 0
+    InvalidLength(String),
+    /// The number's country code is not in the allowed list
+    CountryNotAllowed(String),
+}
+
+impl fmt::Display for PhoneNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhoneNumberError::Empty => write!(f, "Phone number is empty"),
+            PhoneNumberError::InvalidCharacters(number) => {
+                write!(f, "Phone number '{}' contains characters other than digits", number)
+            },
+            PhoneNumberError::MissingCountryCode(number) => write!(
+                f,
+                "Phone number '{}' must be in E.164 format with a country code (e.g. +14155550123)",
+                number
+            ),
+            PhoneNumberError::InvalidLength(number) => write!(
+                f,
+                "Phone number '{}' must have {} to {} digits including the country code",
+                number, MIN_DIGITS, MAX_DIGITS
+            ),
+            PhoneNumberError::CountryNotAllowed(number) => {
+                write!(f, "Calls to {} are not allowed: country code is not enabled", number)
+            },
+        }
+    }
+}
+
+/// Normalize a phone number to E.164
+///
+/// Spaces, dashes, dots and parentheses are removed and a `00` international
+/// prefix becomes `+`.
+pub fn normalize(number: &str) -> Result<String, PhoneNumberError> {
+    let trimmed = number.trim();
+    if trimmed.is_empty() {
+        return Err(PhoneNumberError::Empty);
+    }
+
+    let (has_plus, rest) = match trimmed.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+
+    let mut digits = String::with_capacity(rest.len());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {},
+            _ => return Err(PhoneNumberError::InvalidCharacters(trimmed.to_string())),
+        }
+    }
+
+    let digits = match (has_plus, digits.strip_prefix("00")) {
+        (true, _) => digits,
+        (false, Some(international)) => international.to_string(),
+        (false, None) => return Err(PhoneNumberError::MissingCountryCode(trimmed.to_string())),
+    };
+
+    if digits.starts_with('0') || !(MIN_DIGITS..=MAX_DIGITS).contains(&digits.len()) {
+        return Err(PhoneNumberError::InvalidLength(trimmed.to_string()));
+    }
+
+    Ok(format!("+{}", digits))
+}
+
+/// Normalize a number and check its country calling code is allowed
+///
+/// An empty list allows every country.
+pub fn validate(number: &str, allowed_country_codes: &[String]) -> Result<String, PhoneNumberError> {
+    let normalized = normalize(number)?;
+
+    let allowed = allowed_country_codes.is_empty()
+        || allowed_country_codes.iter().any(|code| normalized[1..].starts_with(code.as_str()));
+    if !allowed {
+        return Err(PhoneNumberError::CountryNotAllowed(normalized));
+    }
+
+    Ok(normalized)
+}
+
+/// Caller number from an inbound webhook, normalized when it is a phone number
+///
+/// Withheld numbers, client identities and SIP addresses are kept as Twilio sent them.
+pub fn normalize_caller(from: &str) -> String {
+    normalize(from).unwrap_or_else(|_| from.trim().to_string())
+}